@@ -0,0 +1,51 @@
+//! Arbitrage scan over a large market universe
+//!
+//! Run with `cargo bench --bench arb_scan`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use polyshark::arb::ArbitrageDetector;
+use polyshark::fees::FeeModel;
+use polyshark::strategy::Strategy;
+use polyshark::types::Market;
+
+/// Binary markets whose NO price steps through 0.40..0.59, so about half
+/// of them signal
+fn universe(size: usize) -> Vec<Market> {
+    (0..size)
+        .map(|i| {
+            let no_price = 0.40 + (i % 20) as f64 * 0.01;
+            Market {
+                id: format!("m{}", i).into(),
+                question: "Bench market?".to_string(),
+                slug: format!("bench-{}", i),
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+                outcome_prices: vec![0.48, no_price],
+                clob_token_ids: vec![format!("{}-yes", i).into(), format!("{}-no", i).into()],
+                best_bid: Some(0.47),
+                best_ask: Some(0.49),
+                maker_base_fee: 0,
+                taker_base_fee: 200,
+                liquidity: Some(1000.0),
+                volume_24hr: Some(5000.0),
+                active: true,
+                accepting_orders: true,
+                condition_id: String::new(),
+                category: String::new(),
+                tick_size: 0.0,
+                min_order_size: 0.0,
+                outcome_quotes: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+fn bench_scan(c: &mut Criterion) {
+    let detector = ArbitrageDetector::new(0.02, 0.10).with_sizing(100.0, FeeModel::new(0, 200));
+    let markets = universe(1_000);
+    c.bench_function("arb_scan_1000_markets", |b| {
+        b.iter(|| Strategy::scan(&detector, black_box(&markets)))
+    });
+}
+
+criterion_group!(benches, bench_scan);
+criterion_main!(benches);
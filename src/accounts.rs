@@ -0,0 +1,274 @@
+//! Multiple smart accounts
+//!
+//! The agent can trade through several smart accounts, each with its own
+//! permission grant and daily allowance. The router sends every trade to the
+//! active account with the most allowance left that can afford it and stays
+//! within its exposure cap, and remembers which account each execution went
+//! through so positions and spend are reported per account.
+
+use crate::metamask::MetaMaskClient;
+use crate::money::{self, Decimal};
+use crate::positions::Position;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Name of the account configured under `[permission]`
+pub const PRIMARY_ACCOUNT: &str = "primary";
+
+/// One smart account the agent trades through
+#[derive(Debug, Clone)]
+pub struct Account {
+    pub name: String,
+    pub metamask: Arc<MetaMaskClient>,
+    /// Cap on the cost of open positions held through it in USDC (0 = no cap)
+    pub max_exposure: f64,
+}
+
+/// One account's standing, for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountReport {
+    pub name: String,
+    pub permission_id: Option<String>,
+    pub active: bool,
+    pub daily_limit: Decimal,
+    pub spent_today: Decimal,
+    pub remaining: Decimal,
+    /// Cost of open positions held through the account
+    pub exposure: f64,
+    pub max_exposure: f64,
+    pub open_positions: usize,
+}
+
+/// Assigns trades to accounts and tracks which account holds what
+#[derive(Debug)]
+pub struct AccountRouter {
+    accounts: Vec<Account>,
+    /// Account each execution was placed through
+    executions: Mutex<HashMap<String, String>>,
+}
+
+impl AccountRouter {
+    /// Route everything through the `[permission]` account
+    pub fn new(primary: Arc<MetaMaskClient>) -> Self {
+        Self {
+            accounts: vec![Account {
+                name: PRIMARY_ACCOUNT.to_string(),
+                metamask: primary,
+                max_exposure: 0.0,
+            }],
+            executions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Also route through `account`
+    pub fn with_account(mut self, account: Account) -> Self {
+        self.accounts.push(account);
+        self
+    }
+
+    pub fn accounts(&self) -> &[Account] {
+        &self.accounts
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Account> {
+        self.accounts.iter().find(|a| a.name == name)
+    }
+
+    pub fn primary(&self) -> &Account {
+        &self.accounts[0]
+    }
+
+    /// Whether any account holds a usable grant
+    pub async fn any_active(&self) -> bool {
+        for account in &self.accounts {
+            if account.metamask.has_valid_permission().await {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// (remaining allowance, daily limit) summed over active accounts
+    pub async fn allowance(&self) -> (Decimal, Decimal) {
+        let (mut remaining, mut daily_limit) = (Decimal::ZERO, Decimal::ZERO);
+        for account in &self.accounts {
+            if !account.metamask.has_valid_permission().await {
+                continue;
+            }
+            if let Some(grant) = account.metamask.get_permission().await {
+                remaining += account.metamask.get_remaining_allowance().await;
+                daily_limit += grant.daily_limit;
+            }
+        }
+        (remaining, daily_limit)
+    }
+
+    /// Account a trade costing `amount` should go through
+    ///
+    /// The active account with the most allowance left, net of what orders in
+    /// flight have `reserved` through it, among those that can cover `amount`
+    /// without going over their exposure cap; None when no account can take
+    /// the trade.
+    pub async fn route(
+        &self,
+        amount: Decimal,
+        positions: &[&Position],
+        reserved: &HashMap<String, Decimal>,
+    ) -> Option<&Account> {
+        let exposure = self.exposure(positions);
+        let mut best: Option<(&Account, Decimal)> = None;
+        for account in &self.accounts {
+            if !account.metamask.has_valid_permission().await {
+                continue;
+            }
+            let remaining = account.metamask.get_remaining_allowance().await
+                - reserved
+                    .get(&account.name)
+                    .copied()
+                    .unwrap_or(Decimal::ZERO);
+            let held = exposure.get(&account.name).copied().unwrap_or(0.0);
+            let capped =
+                account.max_exposure > 0.0 && held + money::to_f64(amount) > account.max_exposure;
+            if remaining < amount || capped {
+                continue;
+            }
+            if best.is_none_or(|(_, most)| remaining > most) {
+                best = Some((account, remaining));
+            }
+        }
+        best.map(|(account, _)| account)
+    }
+
+    /// Remember the account an execution was placed through
+    pub fn assign(&self, execution_id: &str, account: &str) {
+        self.executions
+            .lock()
+            .unwrap()
+            .insert(execution_id.to_string(), account.to_string());
+    }
+
+    /// Account a position is held through (the one that opened it)
+    pub fn holder(&self, position: &Position) -> String {
+        let executions = self.executions.lock().unwrap();
+        position
+            .entry_executions
+            .iter()
+            .find_map(|id| executions.get(id).cloned())
+            .unwrap_or_else(|| PRIMARY_ACCOUNT.to_string())
+    }
+
+    /// Cost of open positions per account
+    pub fn exposure(&self, positions: &[&Position]) -> HashMap<String, f64> {
+        let mut exposure = HashMap::new();
+        for position in positions {
+            *exposure.entry(self.holder(position)).or_insert(0.0) +=
+                (position.size * position.entry_price).abs();
+        }
+        exposure
+    }
+
+    /// Allowance and holdings of every account
+    pub async fn reports(&self, positions: &[&Position]) -> Vec<AccountReport> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for position in positions {
+            *counts.entry(self.holder(position)).or_insert(0) += 1;
+        }
+        let exposure = self.exposure(positions);
+        let mut reports = Vec::new();
+        for account in &self.accounts {
+            let grant = account.metamask.get_permission().await;
+            reports.push(AccountReport {
+                name: account.name.clone(),
+                permission_id: grant.as_ref().map(|g| g.permission_id.clone()),
+                active: account.metamask.has_valid_permission().await,
+                daily_limit: grant.as_ref().map_or(Decimal::ZERO, |g| g.daily_limit),
+                spent_today: grant.as_ref().map_or(Decimal::ZERO, |g| g.spent_today),
+                remaining: account.metamask.get_remaining_allowance().await,
+                exposure: exposure.get(&account.name).copied().unwrap_or(0.0),
+                max_exposure: account.max_exposure,
+                open_positions: counts.get(&account.name).copied().unwrap_or(0),
+            });
+        }
+        reports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metamask::{GrantScope, PermissionGrant};
+    use crate::money::usdc;
+    use crate::types::Side;
+
+    async fn client(daily_limit: f64, spent_today: f64) -> Arc<MetaMaskClient> {
+        let client = MetaMaskClient::new();
+        client
+            .set_permission(PermissionGrant {
+                permission_id: format!("perm_{}", daily_limit),
+                token: "USDC".into(),
+                daily_limit: usdc(daily_limit),
+                spent_today: usdc(spent_today),
+                expires_at: u64::MAX,
+                granted_at: 0,
+                revoked: false,
+                scope: GrantScope::default(),
+            })
+            .await;
+        Arc::new(client)
+    }
+
+    fn position(execution_id: &str, size: f64) -> Position {
+        Position {
+            market_id: "m1".into(),
+            token_id: execution_id.into(),
+            side: Side::Buy,
+            size,
+            entry_price: 0.5,
+            entry_time: 0,
+            entry_spread: 0.0,
+            entry_executions: vec![execution_id.to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes_to_account_with_room() {
+        let router = AccountRouter::new(client(10.0, 6.0).await)
+            .with_account(Account {
+                name: "second".into(),
+                metamask: client(20.0, 5.0).await,
+                max_exposure: 10.0,
+            })
+            .with_account(Account {
+                name: "idle".into(),
+                metamask: Arc::new(MetaMaskClient::new()),
+                max_exposure: 0.0,
+            });
+
+        let (remaining, daily_limit) = router.allowance().await;
+        assert_eq!((remaining, daily_limit), (usdc(19.0), usdc(30.0)));
+
+        // Most allowance left
+        let none = HashMap::new();
+        let account = router.route(usdc(3.0), &[], &none).await.unwrap();
+        assert_eq!(account.name, "second");
+        // Orders in flight through "second" leave the primary with more room
+        let reserved = HashMap::from([("second".to_string(), usdc(12.0))]);
+        let account = router.route(usdc(3.0), &[], &reserved).await.unwrap();
+        assert_eq!(account.name, PRIMARY_ACCOUNT);
+
+        // $8 already held through "second": a $3 trade breaks its cap
+        router.assign("exec-1", "second");
+        let held = position("exec-1", 16.0);
+        let account = router.route(usdc(2.0), &[&held], &none).await.unwrap();
+        assert_eq!(account.name, "second");
+        let account = router.route(usdc(2.5), &[&held], &none).await.unwrap();
+        assert_eq!(account.name, PRIMARY_ACCOUNT);
+        assert!(router.route(usdc(5.0), &[&held], &none).await.is_none());
+
+        let reports = router.reports(&[&held, &position("exec-2", 2.0)]).await;
+        assert_eq!(reports[0].open_positions, 1);
+        assert_eq!(reports[1].exposure, 8.0);
+        assert!(!reports[2].active);
+    }
+}
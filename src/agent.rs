@@ -0,0 +1,968 @@
+//! Agent assembly
+//!
+//! Wires a [`Config`] into the shared state every loop reads: the worker
+//! context the engine and workers trade through, and the API state the
+//! dashboard serves. Building touches nothing but storage (and the CLOB when
+//! an EVM key needs API credentials derived); [`Agent::start`] spawns the
+//! background tasks and [`Agent::run`] drives the trading loop.
+
+use crate::accounts::{Account, AccountRouter};
+use crate::allocator::CapitalAllocator;
+use crate::anomaly::AnomalyBreaker;
+use crate::api;
+use crate::audit::AuditLog;
+use crate::auth::{ApiCredentials, ClobAuth};
+use crate::bankroll::Bankroll;
+use crate::breakdown::{PnlBreakdown, BREAKDOWN_DOCUMENT};
+use crate::cadence::RateLimiter;
+use crate::candles::{CandleStore, CANDLES_DOCUMENT};
+use crate::config::{Config, StrategyParams};
+use crate::correlation::CorrelationTracker;
+use crate::ctf::CtfClient;
+use crate::data_source::{DataSource, DataSourceState, DataSourceSupervisor};
+use crate::engine::{EngineStatus, TradingEngine};
+use crate::evm::{BalanceMonitor, OnChainState, PolygonRpc};
+use crate::exchange::{price_gaps, ExchangeAdapter, SolanaAdapter};
+use crate::execution::ExecutionEngine;
+use crate::external::ExternalSignalQueue;
+use crate::fees::{FeeModel, VolumeHistory, VOLUME_DOCUMENT};
+use crate::fills::{EdgeDecay, FillModel};
+use crate::gas::GasModel;
+use crate::latency::LatencyModel;
+use crate::ledger::{ExecutedSpend, SpendLedger, EXECUTIONS_LOG, LEDGER_DOCUMENT};
+use crate::market::MarketDataProvider;
+use crate::metamask::{self, MetaMaskClient};
+use crate::metrics::LatencyTracker;
+use crate::money;
+use crate::order_spec::SizingBasis;
+use crate::orders::OrderManager;
+use crate::panics::spawn_supervised;
+use crate::pipeline::LiveOrders;
+use crate::policy::PolicyEngine;
+use crate::positions::{PositionManager, ProfitTarget, TrailingStop, STATS_DOCUMENT};
+use crate::pretrade::PreTradeChecks;
+use crate::quarantine::TokenQuarantine;
+use crate::reconcile::Reconciler;
+use crate::script::TradeFilter;
+use crate::secrets::{self, EvmKeystore, SecretStore};
+use crate::shadow::ShadowLedger;
+use crate::signer::{parse_address, EvmSigner, SignatureType};
+use crate::skips::SkipCounter;
+use crate::solana::{parse_keypair, SolanaManager, SolanaState};
+use crate::spread_history::SpreadHistory;
+use crate::storage::Storage;
+use crate::strategy::StrategyRegistry;
+use crate::streak::LosingStreakGuard;
+use crate::tape::TradeTape;
+use crate::throttle::TradeThrottle;
+use crate::tui::Tui;
+use crate::tuning::EdgeTuner;
+use crate::user_stream::UserStream;
+use crate::utilization::UtilizationTracker;
+use crate::wallet::Wallet;
+use crate::watchdog::{Heartbeat, Watchdog};
+use crate::webhook::WebhookPublisher;
+use crate::websocket::WebSocketClient;
+use crate::workers::{ContextState, WorkerContext};
+use colored::*;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use std::error::Error;
+use std::sync::atomic::AtomicUsize;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+
+/// Builds an [`Agent`] from configuration
+pub struct AgentBuilder {
+    config: Config,
+    storage: Option<Storage>,
+}
+
+impl AgentBuilder {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            storage: None,
+        }
+    }
+
+    /// Persist state here instead of `storage.data_dir`
+    pub fn with_storage(mut self, storage: Storage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Load persisted state, unlock secrets and wire every shared component
+    pub async fn build(self) -> Result<Agent, Box<dyn Error>> {
+        let config = self.config;
+        let headless = config.api.headless;
+
+        // Persisted state (lifetime stats survive restarts)
+        let storage = self
+            .storage
+            .unwrap_or_else(|| Storage::new(&config.storage.data_dir));
+        let webhook = WebhookPublisher::spawn(&config.webhook);
+
+        // Every grant, spend, reset, revoke, and denial lands in the hash-chained audit log
+        let audit = match AuditLog::open(storage.clone()) {
+            Ok(audit) => {
+                match audit.verify() {
+                    Ok(entries) => println!(
+                        "{} Audit Log: {} entries, chain intact",
+                        "📜 [Init]".bold().yellow(),
+                        entries
+                    ),
+                    Err(e) => println!("{} {}", "📜 [Init]".bold().yellow(), e.to_string().red()),
+                }
+                Some(Arc::new(audit))
+            }
+            Err(e) => {
+                println!("⚠️ {}", e);
+                None
+            }
+        };
+        let new_client = || {
+            let client = MetaMaskClient::new()
+                .with_policy(PolicyEngine::new(&config.polygon.exchange_address));
+            match &audit {
+                Some(audit) => client.with_audit_log(audit.clone()),
+                None => client,
+            }
+        };
+        let metamask = Arc::new(new_client());
+        match metamask::load_grant(&config.permission.grant_file, &config.permission.grant_env) {
+            Ok(Some(grant)) => metamask.set_permission(grant).await,
+            Ok(None) => {}
+            Err(e) => println!("⚠️ {}", e),
+        }
+        // Further smart accounts, each with its own grant
+        let mut accounts = AccountRouter::new(metamask.clone());
+        for account in &config.accounts {
+            let client = Arc::new(new_client());
+            match metamask::load_grant(&account.grant_file, &account.grant_env) {
+                Ok(Some(grant)) => client.set_permission(grant).await,
+                Ok(None) => println!("⚠️ Account {} has no permission grant", account.name),
+                Err(e) => println!("⚠️ Account {}: {}", account.name, e),
+            }
+            accounts = accounts.with_account(Account {
+                name: account.name.clone(),
+                metamask: client,
+                max_exposure: account.max_exposure,
+            });
+        }
+        let accounts = Arc::new(accounts);
+        let lifetime_stats = storage.load(STATS_DOCUMENT).unwrap_or_else(|e| {
+            println!("⚠️ Failed to load lifetime stats ({}), starting fresh", e);
+            None
+        });
+
+        // Position manager for exit logic (Shared)
+        let mut manager = PositionManager::new(
+            config.positions.profit_target_spread,
+            config.positions.stop_loss_spread,
+            config.positions.max_hold_time_secs,
+        )
+        .with_lifetime_stats(lifetime_stats.unwrap_or_default())
+        .with_duplicate_entry(config.positions.duplicate_entry);
+        if config.positions.trailing_stop_activation > 0.0 {
+            manager = manager.with_trailing_stop(TrailingStop {
+                activation: config.positions.trailing_stop_activation,
+                distance: config.positions.trailing_stop_distance,
+            });
+        }
+        if config.positions.profit_target == ProfitTarget::BreakEven {
+            manager = manager.with_break_even(config.positions.break_even_buffer);
+        }
+        if config.positions.scale_out_fraction > 0.0 {
+            manager = manager.with_scale_out(config.positions.scale_out_fraction);
+        }
+        let position_manager = Arc::new(RwLock::new(manager));
+
+        // Shared market cache for API
+        let market_cache = Arc::new(RwLock::new(api::MarketCache::default()));
+
+        // Shared latency tracker (fetchers record, API exposes, loop suspends on spikes)
+        let latency = Arc::new(LatencyTracker::new(config.safety.max_latency_p99_ms));
+
+        // Recent trades per token (fed by the book stream)
+        let tape = Arc::new(TradeTape::new(config.api.tape_size));
+
+        // Rolling spread per market (strategies, volatility filter, charts)
+        let spread_history = Arc::new(SpreadHistory::new(config.spread_history.capacity));
+
+        // OHLC candles per token (workers record, API charts, optionally persisted)
+        let mut candle_store = CandleStore::new(config.candles.capacity);
+        if config.candles.persist {
+            match storage.load(CANDLES_DOCUMENT) {
+                Ok(Some(series)) => candle_store = candle_store.with_series(series),
+                Ok(None) => {}
+                Err(e) => println!("⚠️ Failed to load candles ({}), starting fresh", e),
+            }
+        }
+        let candles = Arc::new(candle_store);
+
+        // Latest on-chain balance/allowance (filled in once an account is known)
+        let on_chain: OnChainState = Arc::new(RwLock::new(None));
+        let solana: SolanaState = Arc::new(RwLock::new(None));
+
+        // Where workers read books from (REST until the stream proves healthy)
+        let data_source: DataSourceState = Arc::new(RwLock::new(DataSource::Rest));
+
+        // Account orders and fills (fed by the user channel once authenticated)
+        let orders = Arc::new(OrderManager::default());
+
+        // Third-party signals posted to the API, consumed by the external strategy
+        let external_signals = Arc::new(ExternalSignalQueue::new(
+            config.strategies.external.ttl_secs,
+        ));
+
+        // Beaten by the trading loop, checked by the watchdog and /api/health
+        let heartbeat = Heartbeat::new();
+        let skips = Arc::new(SkipCounter::new());
+        let pnl_breakdown = match storage.load(BREAKDOWN_DOCUMENT) {
+            Ok(Some(state)) => PnlBreakdown::new().with_state(state),
+            Ok(None) => PnlBreakdown::new(),
+            Err(e) => {
+                println!("⚠️ Failed to load PnL breakdown ({}), starting fresh", e);
+                PnlBreakdown::new()
+            }
+        };
+        let pnl_breakdown = Arc::new(pnl_breakdown);
+        let utilization = Arc::new(UtilizationTracker::new());
+        // Capital put in and taken out, for return on capital
+        let bankroll = match Bankroll::open(storage.clone()) {
+            Ok(bankroll) => Some(Arc::new(bankroll)),
+            Err(e) => {
+                println!("⚠️ Failed to load bankroll ({}), funding is not tracked", e);
+                None
+            }
+        };
+        let quarantine = Arc::new(TokenQuarantine::new(&config.quarantine));
+        let anomalies = Arc::new(AnomalyBreaker::new(&config.anomaly));
+        let losing_streak = Arc::new(LosingStreakGuard::new(&config.losing_streak));
+        let correlations = Arc::new(CorrelationTracker::new(&config.correlation));
+        // Filled in once the worker context is built (dry-run quotes)
+        let trading: ContextState = Arc::new(RwLock::new(None));
+
+        let api_state = api::ApiState {
+            metamask: metamask.clone(),
+            accounts: accounts.clone(),
+            position_manager: position_manager.clone(),
+            market_cache: market_cache.clone(),
+            latency: latency.clone(),
+            tape: tape.clone(),
+            spread_history: spread_history.clone(),
+            candles: candles.clone(),
+            on_chain: on_chain.clone(),
+            solana,
+            orders: orders.clone(),
+            data_source: data_source.clone(),
+            external_signals: external_signals.clone(),
+            external_signals_enabled: config.strategies.external.enabled,
+            heartbeat: heartbeat.clone(),
+            skips: skips.clone(),
+            pnl_breakdown: pnl_breakdown.clone(),
+            utilization: utilization.clone(),
+            bankroll,
+            quarantine: quarantine.clone(),
+            anomalies: anomalies.clone(),
+            losing_streak: losing_streak.clone(),
+            correlations: correlations.clone(),
+            read_only: config.api.read_only,
+            expiry_warning_secs: config.permission.expiry_warning_window(),
+            trading: trading.clone(),
+        };
+
+        // Secrets (keys and credentials never come from config.toml)
+        let mut secrets = SecretStore::new(
+            config.secrets.source,
+            &config.secrets.keystore_path,
+            &config.secrets.passphrase_env,
+        );
+        if !config.secrets.evm_keystore_path.is_empty() {
+            let path = &config.secrets.evm_keystore_path;
+            match EvmKeystore::load(path).and_then(|keystore| {
+                let passphrase = secrets::read_passphrase(
+                    &config.secrets.evm_keystore_passphrase_env,
+                    &format!("Passphrase for {}: ", path),
+                )?;
+                keystore.decrypt(&passphrase)
+            }) {
+                Ok(key) => secrets = secrets.with_evm_key(key),
+                Err(e) => println!("⚠️ [Secrets] EVM keystore not unlocked: {}", e),
+            }
+        }
+        println!(
+            "{} Secrets: {:?} (EVM key: {})",
+            "🔑 [Init]".bold().yellow(),
+            secrets.source(),
+            if secrets.has(secrets::EVM_PRIVATE_KEY) {
+                "loaded".green()
+            } else {
+                "not set".red()
+            }
+        );
+
+        // CLOB authentication (L1 signer + L2 API credentials)
+        let clob_auth = match secrets
+            .get(secrets::EVM_PRIVATE_KEY)
+            .map_err(|e| e.to_string())
+            .and_then(|key| EvmSigner::from_private_key(&key).map_err(|e| e.to_string()))
+        {
+            Ok(signer) => {
+                let mut auth = ClobAuth::new(&config.api.clob_url, signer);
+                let signature_type = config.polygon.signature_type;
+                if signature_type != SignatureType::Eoa {
+                    match parse_address(&config.polygon.account) {
+                        Ok(_) => auth = auth.with_funder(&config.polygon.account, signature_type),
+                        Err(_) => println!(
+                            "⚠️ [CLOB] {:?} signing needs the proxy wallet as polygon.account, trading from the EOA",
+                            signature_type
+                        ),
+                    }
+                }
+                if config.builder.enabled {
+                    match ApiCredentials::builder_from_secrets(&secrets) {
+                        Ok(builder) => {
+                            println!(
+                                "{} Builder: {}",
+                                "🏗️ [Init]".bold().yellow(),
+                                builder.api_key
+                            );
+                            auth = auth.with_builder(builder);
+                        }
+                        Err(e) => println!("⚠️ [Builder] {}, orders are not attributed", e),
+                    }
+                }
+                match ApiCredentials::from_secrets(&secrets) {
+                    Ok(credentials) => auth = auth.with_credentials(credentials),
+                    // Derive the key this signer already has, or create its first one
+                    Err(_) => {
+                        if let Err(e) = auth.derive_api_key(0).await.map(|_| ()) {
+                            println!("⚠️ CLOB API key derivation failed ({}), creating one", e);
+                            if let Err(e) = auth.create_api_key(0).await {
+                                if config.orders.submit {
+                                    return Err(format!(
+                                        "orders.submit is on but no CLOB API key could be derived or created: {}",
+                                        e
+                                    )
+                                    .into());
+                                }
+                                println!("⚠️ CLOB API key creation failed: {}", e);
+                            }
+                        }
+                    }
+                }
+                Some(auth)
+            }
+            Err(_) => None,
+        };
+        println!(
+            "{} CLOB Auth: {}",
+            "🔐 [Init]".bold().yellow(),
+            match &clob_auth {
+                Some(auth) if auth.credentials().is_some() => {
+                    format!("L2 ready ({})", auth.address()).green()
+                }
+                Some(auth) => format!("L1 only ({})", auth.address()).yellow(),
+                None => "Unauthenticated (no EVM key)".red(),
+            }
+        );
+
+        if let Some(auth) = clob_auth
+            .as_ref()
+            .filter(|auth| auth.funder() != auth.address())
+        {
+            println!(
+                "{} Trading for proxy wallet {}",
+                "🔐 [Init]".bold().yellow(),
+                auth.funder()
+            );
+        }
+
+        // The trading account's USDC balance and exchange allowance on Polygon
+        let account = if config.polygon.account.is_empty() {
+            clob_auth.as_ref().map(|auth| auth.address())
+        } else {
+            Some(config.polygon.account.clone())
+        };
+        let balance_monitor =
+            account
+                .filter(|_| !config.polygon.rpc_url.is_empty())
+                .map(|account| {
+                    BalanceMonitor::new(
+                        PolygonRpc::new(&config.polygon.rpc_url),
+                        &account,
+                        &config.polygon.usdc_address,
+                        &config.polygon.exchange_address,
+                    )
+                });
+
+        // The Solana wallet whose balance is reported
+        let solana_wallet = if secrets.has(secrets::SOLANA_KEYPAIR) {
+            match secrets
+                .get(secrets::SOLANA_KEYPAIR)
+                .map_err(|e| e.to_string())
+                .and_then(|key| parse_keypair(key.expose()).map_err(|e| e.to_string()))
+            {
+                Ok(keypair) => Some(keypair.pubkey()),
+                Err(e) => {
+                    println!("⚠️ [Solana] Invalid keypair: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let sol_manager = Arc::new(SolanaManager::new(&config.solana));
+        let solana_markets = if config.solana.market_program.is_empty() {
+            None
+        } else {
+            // Markets are only read, to compare prices; no order goes to Solana
+            match SolanaAdapter::new(sol_manager.clone(), &config.solana.market_program) {
+                Ok(adapter) => Some(Arc::new(adapter)),
+                Err(e) => {
+                    println!("⚠️ [Solana] {}", e);
+                    None
+                }
+            }
+        };
+
+        // Merge filled bundles and redeem resolved markets on-chain (signs with the
+        // EOA from the secret store)
+        let ctf_enabled = config.ctf.merge_bundles || config.ctf.redeem_resolved;
+        let ctf = if ctf_enabled && !config.polygon.rpc_url.is_empty() {
+            secrets
+                .get(secrets::EVM_PRIVATE_KEY)
+                .map_err(|e| e.to_string())
+                .and_then(|key| EvmSigner::from_private_key(&key).map_err(|e| e.to_string()))
+                .and_then(|signer| {
+                    CtfClient::new(
+                        PolygonRpc::new(&config.polygon.rpc_url),
+                        signer,
+                        &config.ctf.ctf_address,
+                        &config.polygon.usdc_address,
+                        config.ctf.gas_limit,
+                    )
+                    .map_err(|e| e.to_string())
+                })
+                .map_err(|e| println!("⚠️ CTF transactions disabled: {}", e))
+                .ok()
+        } else {
+            None
+        };
+        println!(
+            "{} CTF Transactions: {}",
+            "🔀 [Init]".bold().yellow(),
+            if ctf.is_some() {
+                "Enabled".green()
+            } else {
+                "Disabled".red()
+            }
+        );
+
+        // Order acknowledgements and fills, once we have L2 credentials
+        let user_stream = if config.api.stream_user {
+            match clob_auth.as_ref().and_then(|auth| auth.credentials()) {
+                Some(credentials) => Some(Arc::new(
+                    UserStream::new(
+                        &config.api.websocket_url,
+                        credentials.clone(),
+                        orders.clone(),
+                    )
+                    .with_idle_timeout(Duration::from_secs(config.api.ws_idle_timeout_secs)),
+                )),
+                None => {
+                    println!(
+                        "{} User Channel: {}",
+                        "📨 [Init]".bold().yellow(),
+                        "Skipped (no L2 credentials)".red()
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // L2-authenticated CLOB access, shared by reconciliation and order cancels
+        let clob_auth = clob_auth
+            .filter(|a| a.credentials().is_some())
+            .map(Arc::new);
+
+        // Fee schedule for the active venue, tiered by persisted 30-day volume
+        let volume: VolumeHistory = storage
+            .load(VOLUME_DOCUMENT)
+            .unwrap_or_else(|e| {
+                println!("⚠️ Failed to load traded volume ({}), starting fresh", e);
+                None
+            })
+            .unwrap_or_default();
+        let volume = Arc::new(StdMutex::new(volume));
+        let fee_model = FeeModel::from_config(&config.fees).with_volume(volume.clone());
+        println!(
+            "{} Fees: {} | taker {} bps | maker {:+.0} bps",
+            "🧾 [Init]".bold().yellow(),
+            config.fees.venue,
+            fee_model.taker_bps(),
+            fee_model.maker_net_bps()
+        );
+        // The local spend cap covers every account's grant
+        let daily_limit = config.permission.daily_limit_usdc
+            + config
+                .accounts
+                .iter()
+                .map(|a| a.daily_limit_usdc)
+                .sum::<f64>();
+        let wallet = config.permission.token_limits.iter().fold(
+            Wallet::new(money::usdc(daily_limit)),
+            |wallet, (token, limit)| wallet.with_token_limit(token, money::usdc(*limit)),
+        );
+        let book_rate_limiter =
+            Arc::new(RateLimiter::new(config.cadence.max_book_requests_per_sec));
+        let market_provider = MarketDataProvider::new(&config.api.gamma_url)
+            .with_hydration(config.api.hydration)
+            .with_latency_tracker(latency.clone())
+            .with_rate_limiter(book_rate_limiter.clone());
+        let gas_model = GasModel::from_config(&config.gas);
+        let strategies = StrategyRegistry::from_config(
+            &config,
+            &gas_model,
+            &fee_model,
+            &spread_history,
+            &external_signals,
+        );
+        let latency_model = LatencyModel::new(
+            config.timing.latency_base_ms,
+            config.timing.adverse_selection_std,
+        );
+        println!(
+            "{} Gas Model: {} @ ${:.4}/tx",
+            "⛽ [Init]".bold().yellow(),
+            gas_model.chain,
+            gas_model.cost_per_tx
+        );
+        let mut execution_engine = ExecutionEngine::new(fee_model.clone(), latency_model)
+            .with_gas_model(gas_model)
+            .with_fill_model(FillModel::new(config.trading.competitor_intensity))
+            .with_edge_decay(EdgeDecay::new(config.trading.competitor_arrival_rate))
+            .with_checks(PreTradeChecks::from_config(&config));
+
+        // Local L2 books from the WebSocket market channel
+        let book_stream = config.api.stream_books.then(|| {
+            Arc::new(
+                WebSocketClient::new(&config.api.websocket_url)
+                    .with_trade_tape(tape.clone())
+                    .with_snapshot_source(Arc::new(
+                        MarketDataProvider::new(&config.api.gamma_url)
+                            .with_latency_tracker(latency.clone())
+                            .with_rate_limiter(book_rate_limiter.clone()),
+                    ))
+                    .with_idle_timeout(Duration::from_secs(config.api.ws_idle_timeout_secs)),
+            )
+        });
+        if let Some(stream) = &book_stream {
+            execution_engine = execution_engine.with_order_books(stream.order_books());
+        }
+        println!(
+            "{} Strategies: {}",
+            "🧠 [Init]".bold().yellow(),
+            strategies.names().join(", ")
+        );
+
+        // Per-strategy budgets, enforced in the persisted spend ledger
+        let allocator =
+            CapitalAllocator::new(config.allocation.mode, config.allocation.weights.clone());
+        let mut ledger: SpendLedger = storage
+            .load(LEDGER_DOCUMENT)
+            .unwrap_or_else(|e| {
+                println!("⚠️ Failed to load spend ledger ({}), starting fresh", e);
+                None
+            })
+            .unwrap_or_default();
+        // Settle spends a previous run reserved but never confirmed
+        if !ledger.dangling_intents().is_empty() {
+            let executed: Vec<ExecutedSpend> =
+                storage.read_log(EXECUTIONS_LOG).unwrap_or_else(|e| {
+                    println!("⚠️ Failed to read execution journal ({})", e);
+                    Vec::new()
+                });
+            let (confirmed, released) =
+                ledger.reconcile_intents(&executed, Wallet::current_timestamp());
+            println!(
+                "{} Spend Intents: {} confirmed, {} released from the last run",
+                "💸 [Init]".bold().yellow(),
+                confirmed,
+                released
+            );
+            if let Err(e) = storage.save(LEDGER_DOCUMENT, &ledger) {
+                println!("⚠️ Failed to persist spend ledger: {}", e);
+            }
+        }
+
+        println!(
+            "{} Daily Allowance: ${:.2} USDC (Enforced by ERC-7715)",
+            "💸 [Init]".bold().yellow(),
+            wallet.daily_limit()
+        );
+        match config.trading.sizing {
+            SizingBasis::Shares => println!(
+                "{} Trade Size: {:.2} shares per leg",
+                "📊 [Init]".bold().yellow(),
+                config.trading.trade_size
+            ),
+            SizingBasis::Notional => println!(
+                "{} Trade Size: ${:.2} per leg",
+                "📊 [Init]".bold().yellow(),
+                config.trading.trade_size
+            ),
+        }
+        let shadow_mode = config.trading.shadow_mode;
+        let shadow = ShadowLedger::new(storage.clone());
+        if shadow_mode {
+            println!(
+                "{} Shadow Mode: {}",
+                "👻 [Init]".bold().yellow(),
+                "ON (no orders placed, no allowance spent)".cyan()
+            );
+        }
+        let trade_filter = if config.script.path.is_empty() {
+            None
+        } else {
+            let filter = TradeFilter::from_file(&config.script.path, config.script.max_operations)?;
+            println!(
+                "{} Filter Script: {}",
+                "🧪 [Init]".bold().yellow(),
+                config.script.path.cyan()
+            );
+            Some(filter)
+        };
+        if webhook.is_some() {
+            println!(
+                "{} Webhook: {}",
+                "🪝 [Init]".bold().yellow(),
+                config.webhook.url.cyan()
+            );
+        }
+        println!();
+        if !shadow_mode && !metamask.has_valid_permission().await {
+            if headless {
+                println!(
+                    "⏳ Waiting for a permission grant (set permission.grant_file or ${})...",
+                    config.permission.grant_env
+                );
+            } else {
+                println!("⏳ Waiting for MetaMask permission via Dashboard...");
+            }
+        }
+
+        // Live orders are only posted when enabled and we can authenticate,
+        // and their fills are only booked from the user channel
+        let clob = clob_auth
+            .clone()
+            .filter(|_| config.orders.submit && user_stream.is_some());
+        println!(
+            "{} Order Submission: {}",
+            "📤 [Init]".bold().yellow(),
+            match &clob {
+                Some(auth) => format!("CLOB (maker {})", auth.funder()).green(),
+                None if config.orders.submit && clob_auth.is_none() => {
+                    "Disabled (no L2 credentials)".red()
+                }
+                None if config.orders.submit => "Disabled (user channel off)".red(),
+                None => "Paper fills".yellow(),
+            }
+        );
+
+        let ctx = Arc::new(WorkerContext {
+            config: config.clone(),
+            params: RwLock::new(StrategyParams::from_config(&config)),
+            metamask,
+            accounts,
+            position_manager,
+            market_cache,
+            market_provider,
+            execution_engine,
+            strategies: Mutex::new(strategies),
+            allocator: Mutex::new(allocator),
+            tuner: Mutex::new(EdgeTuner::new(&config.tuning, &config.strategy)),
+            ledger: Mutex::new(ledger),
+            wallet: Mutex::new(wallet),
+            shadow: Mutex::new(shadow),
+            latency,
+            order_books: book_stream.as_ref().map(|s| s.order_books()),
+            tape,
+            spread_history,
+            candles,
+            on_chain,
+            data_source,
+            ctf,
+            storage,
+            webhook,
+            trade_filter,
+            skips,
+            throttle: TradeThrottle::new(&config.safety),
+            losing_streak,
+            pnl_breakdown,
+            utilization,
+            orders,
+            clob,
+            live_orders: LiveOrders::default(),
+            quarantine,
+            anomalies,
+            correlations,
+            intent_count: AtomicUsize::new(0),
+            status: RwLock::new(EngineStatus::Running),
+            volume,
+        });
+        *trading.write().await = Some(ctx.clone());
+
+        Ok(Agent {
+            ctx,
+            api_state,
+            heartbeat,
+            book_stream,
+            clob_auth,
+            user_stream,
+            balance_monitor,
+            solana_wallet,
+            sol_manager,
+            solana_markets,
+        })
+    }
+}
+
+/// A fully wired agent, ready to start
+pub struct Agent {
+    /// State shared by the engine, workers and pipeline
+    pub ctx: Arc<WorkerContext>,
+    /// State served by the dashboard API
+    pub api_state: api::ApiState,
+    heartbeat: Heartbeat,
+    book_stream: Option<Arc<WebSocketClient>>,
+    /// L2-authenticated CLOB access
+    clob_auth: Option<Arc<ClobAuth>>,
+    user_stream: Option<Arc<UserStream>>,
+    balance_monitor: Option<BalanceMonitor>,
+    solana_wallet: Option<Pubkey>,
+    sol_manager: Arc<SolanaManager>,
+    /// Solana binary-market program, compared against Polymarket
+    solana_markets: Option<Arc<SolanaAdapter>>,
+}
+
+impl Agent {
+    /// Spawn the API server, streams and periodic background tasks
+    pub async fn start(&self) {
+        let config = &self.ctx.config;
+        if !config.api.headless {
+            let api_state = self.api_state.clone();
+            spawn_supervised("API", move || api::start_server(api_state.clone()));
+        }
+
+        println!(
+            "{} Market Data:   Envio Indexer...           {}",
+            "📡 [Init]".bold().yellow(),
+            "Connected.".green()
+        );
+
+        // Solana Check
+        print!(
+            "{} Solana ({:?}):  Connecting... ",
+            "☀️ [Init]".bold().yellow(),
+            config.solana.commitment
+        );
+        let sol_manager = self.sol_manager.clone();
+        match sol_manager.check_connection().await {
+            Ok(v) => println!("{}", format!("Connected! (v{})", v).green()),
+            Err(_) => println!("{}", "Skipped (Offline)".red()),
+        }
+
+        // Poll the trading account's USDC balance and exchange allowance on Polygon
+        match &self.balance_monitor {
+            Some(monitor) => {
+                println!(
+                    "{} Polygon RPC: watching {}",
+                    "🔗 [Init]".bold().yellow(),
+                    monitor.account()
+                );
+                let (monitor, on_chain) = (monitor.clone(), self.ctx.on_chain.clone());
+                let interval = Duration::from_secs(config.polygon.refresh_secs);
+                spawn_supervised("Polygon", move || {
+                    monitor.clone().run_periodic(on_chain.clone(), interval)
+                });
+            }
+            None => println!(
+                "{} Polygon RPC: {}",
+                "🔗 [Init]".bold().yellow(),
+                "Skipped (no account)".red()
+            ),
+        }
+
+        // Poll the Solana wallet's balance
+        if let Some(wallet) = self.solana_wallet {
+            println!(
+                "{} Solana wallet: watching {}",
+                "☀️ [Init]".bold().yellow(),
+                wallet
+            );
+            let solana = self.api_state.solana.clone();
+            let interval = Duration::from_secs(config.solana.refresh_secs);
+            spawn_supervised("Solana", move || {
+                sol_manager
+                    .clone()
+                    .run_periodic(wallet, solana.clone(), interval)
+            });
+        }
+
+        // Compare Solana markets with the Polymarket ones workers trade
+        if let Some(adapter) = self.solana_markets.clone() {
+            let market_cache = self.ctx.market_cache.clone();
+            let interval = Duration::from_secs(config.timing.discovery_interval_secs.max(1));
+            spawn_supervised("CrossChain", move || {
+                compare_venues(adapter.clone(), market_cache.clone(), interval)
+            });
+        }
+
+        // Stream order acknowledgements and fills
+        if let Some(user_stream) = self.user_stream.clone() {
+            spawn_supervised("UserStream", move || user_stream.clone().maintain());
+        }
+
+        // Reconcile local positions with exchange fills
+        if let Some(auth) = self.clob_auth.clone() {
+            let reconciler = Reconciler::new(
+                Wallet::current_timestamp(),
+                config.reconciliation.tolerance,
+                config.reconciliation.auto_correct,
+            );
+            let position_manager = self.ctx.position_manager.clone();
+            let interval = Duration::from_secs(config.reconciliation.interval_secs);
+            spawn_supervised("Reconcile", move || {
+                reconciler
+                    .clone()
+                    .run_periodic(auth.clone(), position_manager.clone(), interval)
+            });
+        }
+
+        if let Some(stream) = &self.book_stream {
+            let maintained = stream.clone();
+            spawn_supervised("WebSocket", move || maintained.clone().maintain());
+            let (monitored, source, thresholds) = (
+                stream.clone(),
+                self.ctx.data_source.clone(),
+                config.data_source.clone(),
+            );
+            spawn_supervised("DataSource", move || {
+                DataSourceSupervisor::new(monitored.clone(), source.clone(), &thresholds).run()
+            });
+        }
+
+        // Resting order housekeeping: expired GTD orders and stale quotes
+        let (sweeping, clob_auth, interval) = (
+            self.ctx.clone(),
+            self.clob_auth.clone(),
+            Duration::from_secs(config.orders.expiry_sweep_secs.max(1)),
+        );
+        spawn_supervised("Orders", move || {
+            let (ctx, auth) = (sweeping.clone(), clob_auth.clone());
+            async move {
+                loop {
+                    tokio::time::sleep(interval).await;
+                    ctx.sweep_expired_orders(auth.as_deref()).await;
+                    if let Some(auth) = &auth {
+                        ctx.requote_stale_orders(auth).await;
+                    }
+                }
+            }
+        });
+    }
+
+    /// A trading engine over this agent's context
+    pub fn engine(&self) -> TradingEngine {
+        let engine = TradingEngine::new(self.ctx.clone());
+        match &self.book_stream {
+            Some(stream) => engine.with_stream(stream.clone()),
+            None => engine,
+        }
+    }
+
+    /// Drive the trading loop (under the watchdog when enabled) until it ends
+    pub async fn run(self) {
+        let config = &self.ctx.config;
+        if config.tui.enabled {
+            let ui = Tui::new(self.ctx.clone());
+            tokio::spawn(async move {
+                if let Err(e) = ui.run().await {
+                    eprintln!("⚠️ Terminal UI failed: {}", e);
+                }
+                // Quitting the UI stops the agent
+                std::process::exit(0);
+            });
+        }
+        if !config.watchdog.enabled {
+            let trading = spawn_supervised("Trading", move || {
+                run_trading_loop(self.engine(), self.heartbeat.clone())
+            });
+            let _ = trading.await;
+            return;
+        }
+        println!(
+            "{} Watchdog: stalled after {}s without a heartbeat{}",
+            "🐕 [Init]".bold().yellow(),
+            config.watchdog.timeout_secs,
+            if config.watchdog.restart {
+                " (auto-restart)"
+            } else {
+                ""
+            }
+        );
+        let watchdog = Watchdog::new(self.heartbeat.clone(), &config.watchdog)
+            .with_webhook(self.ctx.webhook.clone());
+        watchdog
+            .supervise(move || run_trading_loop(self.engine(), self.heartbeat.clone()))
+            .await;
+    }
+}
+
+/// Log questions listed on both Polymarket and the Solana program, widest
+/// YES price gap first, forever on a fixed interval
+async fn compare_venues(
+    solana: Arc<SolanaAdapter>,
+    market_cache: Arc<RwLock<api::MarketCache>>,
+    interval: Duration,
+) {
+    loop {
+        match solana.fetch_markets().await {
+            Ok(markets) => {
+                let polymarket = market_cache.read().await.markets.snapshot();
+                let gaps = price_gaps(&polymarket, &markets);
+                println!(
+                    "☀️ [CrossChain] {} Solana markets, {} also on Polymarket",
+                    markets.len(),
+                    gaps.len()
+                );
+                for gap in gaps.iter().take(3) {
+                    println!(
+                        "   {} | Polymarket {} vs Solana {} | YES gap {:+.3}",
+                        gap.question, gap.left, gap.right, gap.gap
+                    );
+                }
+            }
+            Err(e) => println!("⚠️ [CrossChain] {}", e),
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Drive a trading engine forever, beating `heartbeat` on every tick
+///
+/// Everything the engine needs is shared state, so the watchdog can abort
+/// this task and start a fresh one.
+async fn run_trading_loop(mut engine: TradingEngine, heartbeat: Heartbeat) {
+    loop {
+        heartbeat.beat();
+        let wait = engine.tick().await;
+        heartbeat.sleep(wait).await;
+    }
+}
@@ -0,0 +1,248 @@
+//! Alert rules
+//!
+//! User-defined conditions from `[alerts]`, evaluated on every engine tick.
+//! A rule that fires is logged and sent as a webhook alert, then held back
+//! for a cooldown so a lasting condition does not alert on every tick.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// A condition worth telling the user about
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AlertRule {
+    /// Session PnL below `usdc`
+    PnlBelow { usdc: f64 },
+    /// Win rate under `rate` over the last `trades` closed trades
+    WinRateBelow { rate: f64, trades: usize },
+    /// More than `fraction` of the daily allowance used
+    AllowanceUsedAbove { fraction: f64 },
+    /// No successful market fetch for `secs`
+    NoData { secs: u64 },
+}
+
+impl AlertRule {
+    /// Label used in logs and webhook payloads
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PnlBelow { .. } => "pnl_below",
+            Self::WinRateBelow { .. } => "win_rate_below",
+            Self::AllowanceUsedAbove { .. } => "allowance_used_above",
+            Self::NoData { .. } => "no_data",
+        }
+    }
+
+    /// Closed trades the rule needs to look back over
+    fn lookback(&self) -> usize {
+        match self {
+            Self::WinRateBelow { trades, .. } => *trades,
+            _ => 0,
+        }
+    }
+
+    /// Describe the condition if it currently holds
+    pub fn check(&self, inputs: &AlertInputs) -> Option<String> {
+        match *self {
+            Self::PnlBelow { usdc } => (inputs.total_pnl < usdc)
+                .then(|| format!("PnL ${:.2} is below ${:.2}", inputs.total_pnl, usdc)),
+            Self::WinRateBelow { rate, trades } => {
+                if trades == 0 || inputs.recent_pnls.len() < trades {
+                    return None;
+                }
+                let recent = &inputs.recent_pnls[inputs.recent_pnls.len() - trades..];
+                let wins = recent.iter().filter(|pnl| **pnl > 0.0).count();
+                let win_rate = wins as f64 / trades as f64;
+                (win_rate < rate).then(|| {
+                    format!(
+                        "Win rate {:.0}% over the last {} trades is under {:.0}%",
+                        win_rate * 100.0,
+                        trades,
+                        rate * 100.0
+                    )
+                })
+            }
+            Self::AllowanceUsedAbove { fraction } => inputs
+                .allowance_used
+                .filter(|used| *used > fraction)
+                .map(|used| {
+                    format!(
+                        "{:.0}% of the daily allowance used (alert above {:.0}%)",
+                        used * 100.0,
+                        fraction * 100.0
+                    )
+                }),
+            Self::NoData { secs } => inputs
+                .data_age_secs
+                .filter(|age| *age >= secs)
+                .map(|age| format!("No market data for {}s", age)),
+        }
+    }
+}
+
+/// What the rules are evaluated against
+#[derive(Debug, Clone, Default)]
+pub struct AlertInputs {
+    pub total_pnl: f64,
+    /// PnL of the most recent closed trades, oldest first
+    pub recent_pnls: Vec<f64>,
+    /// Fraction of the daily allowance spent (None without a limit)
+    pub allowance_used: Option<f64>,
+    /// Seconds since the last successful market fetch
+    pub data_age_secs: Option<u64>,
+}
+
+/// A rule that fired
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub rule: &'static str,
+    pub message: String,
+}
+
+/// Evaluates the configured rules, enforcing each one's cooldown
+#[derive(Debug)]
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    cooldown_secs: u64,
+    /// When each rule (by index) last fired
+    last_fired: HashMap<usize, u64>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>, cooldown_secs: u64) -> Self {
+        Self {
+            rules,
+            cooldown_secs,
+            last_fired: HashMap::new(),
+        }
+    }
+
+    /// Closed trades the inputs must include for every rule
+    pub fn lookback(&self) -> usize {
+        self.rules
+            .iter()
+            .map(AlertRule::lookback)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Rules that hold now and are out of their cooldown
+    pub fn evaluate(&mut self, inputs: &AlertInputs, now: u64) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            let Some(message) = rule.check(inputs) else {
+                continue;
+            };
+            let cooling = self
+                .last_fired
+                .get(&index)
+                .is_some_and(|last| now < last + self.cooldown_secs);
+            if cooling {
+                continue;
+            }
+            self.last_fired.insert(index, now);
+            alerts.push(Alert {
+                rule: rule.as_str(),
+                message,
+            });
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules() {
+        let inputs = AlertInputs {
+            total_pnl: -6.0,
+            recent_pnls: vec![1.0, -1.0, -1.0, -1.0],
+            allowance_used: Some(0.85),
+            data_age_secs: Some(30),
+        };
+
+        assert!(AlertRule::PnlBelow { usdc: -5.0 }.check(&inputs).is_some());
+        assert!(AlertRule::PnlBelow { usdc: -10.0 }.check(&inputs).is_none());
+        // 0 of the last 3 won; the 4-trade window is at 25%
+        let rule = AlertRule::WinRateBelow {
+            rate: 0.2,
+            trades: 3,
+        };
+        assert!(rule.check(&inputs).is_some());
+        let rule = AlertRule::WinRateBelow {
+            rate: 0.2,
+            trades: 4,
+        };
+        assert!(rule.check(&inputs).is_none());
+        let rule = AlertRule::WinRateBelow {
+            rate: 0.9,
+            trades: 5,
+        };
+        assert!(rule.check(&inputs).is_none(), "not enough trades yet");
+        assert!(AlertRule::AllowanceUsedAbove { fraction: 0.8 }
+            .check(&inputs)
+            .is_some());
+        assert!(AlertRule::NoData { secs: 60 }.check(&inputs).is_none());
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let mut engine = AlertEngine::new(
+            vec![
+                AlertRule::PnlBelow { usdc: 0.0 },
+                AlertRule::NoData { secs: 60 },
+            ],
+            300,
+        );
+        let inputs = AlertInputs {
+            total_pnl: -1.0,
+            data_age_secs: Some(90),
+            ..Default::default()
+        };
+
+        let fired: Vec<&str> = engine
+            .evaluate(&inputs, 1_000)
+            .iter()
+            .map(|a| a.rule)
+            .collect();
+        assert_eq!(fired, vec!["pnl_below", "no_data"]);
+        assert!(engine.evaluate(&inputs, 1_299).is_empty());
+        assert_eq!(engine.evaluate(&inputs, 1_300).len(), 2);
+    }
+
+    #[test]
+    fn test_parses_from_toml() {
+        #[derive(Deserialize)]
+        struct Rules {
+            rules: Vec<AlertRule>,
+        }
+        let parsed: Rules = toml::from_str(
+            r#"
+            [[rules]]
+            kind = "win_rate_below"
+            rate = 0.4
+            trades = 20
+
+            [[rules]]
+            kind = "no_data"
+            secs = 120
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.rules,
+            vec![
+                AlertRule::WinRateBelow {
+                    rate: 0.4,
+                    trades: 20
+                },
+                AlertRule::NoData { secs: 120 },
+            ]
+        );
+    }
+}
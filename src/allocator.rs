@@ -0,0 +1,118 @@
+//! Capital allocation module
+//!
+//! Splits the daily allowance across enabled strategies, either by fixed
+//! weights or by weights scaled with each strategy's realized performance.
+
+use crate::money::Decimal;
+use crate::positions::TradeStats;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Closed trades a strategy needs before performance affects its weight
+const MIN_TRADES_FOR_PERFORMANCE: usize = 10;
+
+/// How the daily allowance is split across strategies
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AllocationMode {
+    /// Configured weights only
+    #[default]
+    Fixed,
+    /// Configured weights scaled by realized win rate
+    Performance,
+}
+
+/// Splits the daily allowance into per-strategy budgets
+#[derive(Debug, Clone)]
+pub struct CapitalAllocator {
+    mode: AllocationMode,
+    /// Base weight per strategy (missing strategies weigh 1.0)
+    weights: HashMap<String, f64>,
+    /// Realized results per strategy
+    performance: HashMap<String, TradeStats>,
+}
+
+impl CapitalAllocator {
+    pub fn new(mode: AllocationMode, weights: HashMap<String, f64>) -> Self {
+        Self {
+            mode,
+            weights,
+            performance: HashMap::new(),
+        }
+    }
+
+    /// Record a closed trade for a strategy
+    pub fn record_result(&mut self, strategy: &str, pnl: Decimal) {
+        self.performance
+            .entry(strategy.to_string())
+            .or_default()
+            .record(pnl);
+    }
+
+    /// Effective weight of a strategy
+    pub fn weight(&self, strategy: &str) -> f64 {
+        let base = self.weights.get(strategy).copied().unwrap_or(1.0).max(0.0);
+        match (self.mode, self.performance.get(strategy)) {
+            // Win rate 0% halves the weight, 100% raises it by half
+            (AllocationMode::Performance, Some(stats))
+                if stats.trades >= MIN_TRADES_FOR_PERFORMANCE =>
+            {
+                base * (0.5 + stats.win_rate())
+            }
+            _ => base,
+        }
+    }
+
+    /// Split `daily_limit` across `strategies` in proportion to their weights
+    pub fn allocate(&self, daily_limit: f64, strategies: &[&str]) -> HashMap<String, f64> {
+        let total: f64 = strategies.iter().map(|s| self.weight(s)).sum();
+        strategies
+            .iter()
+            .map(|s| {
+                let share = if total > 0.0 {
+                    self.weight(s) / total
+                } else {
+                    0.0
+                };
+                (s.to_string(), daily_limit * share)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_weights_split_allowance() {
+        let allocator = CapitalAllocator::new(
+            AllocationMode::Fixed,
+            HashMap::from([("arb".to_string(), 3.0)]),
+        );
+
+        let budgets = allocator.allocate(20.0, &["arb", "mean_reversion"]);
+        assert_eq!(budgets["arb"], 15.0);
+        assert_eq!(budgets["mean_reversion"], 5.0);
+    }
+
+    #[test]
+    fn test_performance_shifts_allocation() {
+        let mut allocator = CapitalAllocator::new(AllocationMode::Performance, HashMap::new());
+        for _ in 0..MIN_TRADES_FOR_PERFORMANCE {
+            allocator.record_result("winner", Decimal::ONE);
+            allocator.record_result("loser", -Decimal::ONE);
+        }
+
+        let budgets = allocator.allocate(20.0, &["winner", "loser"]);
+        assert_eq!(budgets["winner"], 15.0);
+        assert_eq!(budgets["loser"], 5.0);
+
+        // Fixed mode ignores performance
+        let fixed = CapitalAllocator {
+            mode: AllocationMode::Fixed,
+            ..allocator
+        };
+        assert_eq!(fixed.allocate(20.0, &["winner", "loser"])["winner"], 10.0);
+    }
+}
@@ -0,0 +1,139 @@
+//! Allowance usage timeline
+//!
+//! Timestamped record of every spend and daily reset charged to the
+//! permission grant, so the dashboard can chart intraday consumption and
+//! project when the agent will hit its daily cap.
+
+use crate::money::{self, Decimal};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Points kept before the oldest are dropped
+pub const HISTORY_CAPACITY: usize = 2_000;
+
+/// What changed the allowance
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AllowanceEvent {
+    Spend,
+    Reset,
+}
+
+/// Allowance state right after one event
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AllowancePoint {
+    /// Unix time in seconds
+    pub timestamp: u64,
+    pub event: AllowanceEvent,
+    /// Amount spent (zero for resets)
+    pub amount: Decimal,
+    pub spent_today: Decimal,
+    pub daily_limit: Decimal,
+}
+
+/// Bounded allowance timeline
+#[derive(Debug)]
+pub struct AllowanceHistory {
+    points: Mutex<VecDeque<AllowancePoint>>,
+    capacity: usize,
+}
+
+impl Default for AllowanceHistory {
+    fn default() -> Self {
+        Self::new(HISTORY_CAPACITY)
+    }
+}
+
+impl AllowanceHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            points: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn record(&self, point: AllowancePoint) {
+        let mut points = self.points.lock().unwrap();
+        if points.len() == self.capacity {
+            points.pop_front();
+        }
+        points.push_back(point);
+    }
+
+    /// All retained points, oldest first
+    pub fn points(&self) -> Vec<AllowancePoint> {
+        self.points.lock().unwrap().iter().copied().collect()
+    }
+
+    /// When spending at today's average rate would reach the daily cap
+    ///
+    /// The rate is measured from the last reset (or the first retained
+    /// point) to `now`; None when nothing was spent or the cap is reached.
+    pub fn projected_cap_at(&self, now: u64) -> Option<u64> {
+        let points = self.points.lock().unwrap();
+        let last = points.back()?;
+        let start = points
+            .iter()
+            .rev()
+            .find(|p| p.event == AllowanceEvent::Reset)
+            .or(points.front())?
+            .timestamp;
+        let spent = money::to_f64(last.spent_today);
+        let left = money::to_f64(last.daily_limit - last.spent_today);
+        if spent <= 0.0 || left <= 0.0 || now <= start {
+            return None;
+        }
+        let rate = spent / (now - start) as f64;
+        Some(now + (left / rate).ceil() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::usdc;
+
+    fn spend(timestamp: u64, amount: f64, spent_today: f64) -> AllowancePoint {
+        AllowancePoint {
+            timestamp,
+            event: AllowanceEvent::Spend,
+            amount: usdc(amount),
+            spent_today: usdc(spent_today),
+            daily_limit: usdc(10.0),
+        }
+    }
+
+    #[test]
+    fn test_projects_cap_from_todays_rate() {
+        let history = AllowanceHistory::new(10);
+        assert_eq!(history.projected_cap_at(1_000), None);
+
+        history.record(AllowancePoint {
+            timestamp: 0,
+            event: AllowanceEvent::Reset,
+            amount: Decimal::ZERO,
+            spent_today: Decimal::ZERO,
+            daily_limit: usdc(10.0),
+        });
+        history.record(spend(1_000, 1.0, 1.0));
+        history.record(spend(3_000, 3.0, 4.0));
+
+        // $4 in 4000s: the remaining $6 takes another 6000s
+        assert_eq!(history.projected_cap_at(4_000), Some(10_000));
+
+        history.record(spend(5_000, 6.0, 10.0));
+        assert_eq!(history.projected_cap_at(5_000), None);
+    }
+
+    #[test]
+    fn test_bounded() {
+        let history = AllowanceHistory::new(2);
+        for t in 0..5 {
+            history.record(spend(t, 1.0, t as f64));
+        }
+        let points = history.points();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].timestamp, 3);
+    }
+}
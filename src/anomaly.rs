@@ -0,0 +1,287 @@
+//! Price anomaly circuit breaker
+//!
+//! Feed data that cannot be right (a price outside (0, 1)) or is unlikely to
+//! be (a price moving further than `max_jump` in one tick, a book whose depth
+//! collapses to nothing) trips the breaker for that market. A tripped market
+//! is held out of trading, exits included, until the cooldown passes with no
+//! further anomalies, rather than being traded on corrupted data.
+
+use crate::config::AnomalyConfig;
+use crate::types::{MarketId, TokenId};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Feed data the breaker refused to trust
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Anomaly {
+    /// A price that is not a probability
+    OutOfRange { token_id: TokenId, price: f64 },
+    /// A price that moved too far since the last tick
+    Jump {
+        token_id: TokenId,
+        from: f64,
+        to: f64,
+    },
+    /// A book that had depth last tick and has none now
+    DepthCollapse { token_id: TokenId },
+}
+
+impl std::fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfRange { token_id, price } => {
+                write!(f, "{} priced at {} (outside (0, 1))", token_id, price)
+            }
+            Self::Jump { token_id, from, to } => {
+                write!(
+                    f,
+                    "{} jumped {:.4} -> {:.4} in one tick",
+                    token_id, from, to
+                )
+            }
+            Self::DepthCollapse { token_id } => write!(f, "{} book depth fell to zero", token_id),
+        }
+    }
+}
+
+/// One token's data from a worker tick
+#[derive(Debug, Clone)]
+pub struct Observation {
+    pub token_id: TokenId,
+    pub price: f64,
+    /// Total bid and ask size, when a full book was seen this tick
+    pub depth: Option<f64>,
+}
+
+#[derive(Debug, Default)]
+struct MarketRecord {
+    /// Last plausible price per token
+    prices: HashMap<TokenId, f64>,
+    /// Last book depth per token
+    depths: HashMap<TokenId, f64>,
+    /// Held out of trading until then, for this anomaly
+    tripped: Option<(Instant, Anomaly)>,
+}
+
+/// A market currently held out of trading
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrippedMarket {
+    pub market_id: MarketId,
+    pub anomaly: Anomaly,
+    pub resumes_in_secs: u64,
+}
+
+/// Per-market breaker state, shared by the workers and the API
+#[derive(Debug)]
+pub struct AnomalyBreaker {
+    markets: Mutex<HashMap<MarketId, MarketRecord>>,
+    max_jump: f64,
+    cooldown: Duration,
+}
+
+impl AnomalyBreaker {
+    pub fn new(config: &AnomalyConfig) -> Self {
+        Self {
+            markets: Mutex::new(HashMap::new()),
+            max_jump: config.max_jump,
+            cooldown: Duration::from_secs(config.cooldown_secs),
+        }
+    }
+
+    /// Check a tick's data against the last one, tripping on an anomaly
+    ///
+    /// Out-of-range prices are never kept as the baseline; a jump is, so a
+    /// genuine repricing trips the breaker once rather than on every tick.
+    pub fn observe(
+        &self,
+        market_id: &MarketId,
+        observations: &[Observation],
+        now: Instant,
+    ) -> Option<Anomaly> {
+        let mut markets = self.markets.lock().unwrap();
+        let record = markets.entry(market_id.clone()).or_default();
+        let mut found = None;
+        for observation in observations {
+            let token_id = &observation.token_id;
+            let price = observation.price;
+            let anomaly = if !(price > 0.0 && price < 1.0) {
+                Some(Anomaly::OutOfRange {
+                    token_id: token_id.clone(),
+                    price,
+                })
+            } else {
+                let previous = record.prices.insert(token_id.clone(), price);
+                previous
+                    .filter(|from| (price - from).abs() > self.max_jump)
+                    .map(|from| Anomaly::Jump {
+                        token_id: token_id.clone(),
+                        from,
+                        to: price,
+                    })
+            };
+            let collapsed = observation.depth.and_then(|depth| {
+                let previous = record.depths.insert(token_id.clone(), depth);
+                (depth <= 0.0 && previous.is_some_and(|p| p > 0.0)).then(|| {
+                    Anomaly::DepthCollapse {
+                        token_id: token_id.clone(),
+                    }
+                })
+            });
+            if found.is_none() {
+                found = anomaly.or(collapsed);
+            }
+        }
+
+        let anomaly = found?;
+        if record.tripped.is_none() {
+            println!(
+                "🛑 [Breaker] {} held out of trading for {}s: {}",
+                market_id,
+                self.cooldown.as_secs(),
+                anomaly
+            );
+        }
+        // Further anomalies restart the cooldown
+        record.tripped = Some((now + self.cooldown, anomaly.clone()));
+        Some(anomaly)
+    }
+
+    /// Whether a market may trade now
+    pub fn allows(&self, market_id: &MarketId, now: Instant) -> bool {
+        let mut markets = self.markets.lock().unwrap();
+        let Some(record) = markets.get_mut(market_id) else {
+            return true;
+        };
+        match &record.tripped {
+            Some((until, _)) if now < *until => false,
+            Some(_) => {
+                record.tripped = None;
+                println!("✅ [Breaker] {} resumed", market_id);
+                true
+            }
+            None => true,
+        }
+    }
+
+    /// Time left before a market may trade again
+    pub fn held_for(&self, market_id: &MarketId, now: Instant) -> Option<Duration> {
+        let markets = self.markets.lock().unwrap();
+        let (until, _) = markets.get(market_id)?.tripped.as_ref()?;
+        (now < *until).then(|| *until - now)
+    }
+
+    /// Markets held out of trading, longest wait first
+    pub fn tripped(&self, now: Instant) -> Vec<TrippedMarket> {
+        let markets = self.markets.lock().unwrap();
+        let mut tripped: Vec<TrippedMarket> = markets
+            .iter()
+            .filter_map(|(market_id, record)| {
+                let (until, anomaly) = record.tripped.as_ref().filter(|(u, _)| now < *u)?;
+                Some(TrippedMarket {
+                    market_id: market_id.clone(),
+                    anomaly: anomaly.clone(),
+                    resumes_in_secs: until.saturating_duration_since(now).as_secs(),
+                })
+            })
+            .collect();
+        tripped.sort_by_key(|m| std::cmp::Reverse(m.resumes_in_secs));
+        tripped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> AnomalyBreaker {
+        AnomalyBreaker::new(&AnomalyConfig {
+            max_jump: 0.3,
+            cooldown_secs: 60,
+        })
+    }
+
+    fn observation(token: &str, price: f64, depth: Option<f64>) -> Observation {
+        Observation {
+            token_id: token.into(),
+            price,
+            depth,
+        }
+    }
+
+    #[test]
+    fn test_detects_each_anomaly() {
+        let b = breaker();
+        let market: MarketId = "m1".into();
+        let now = Instant::now();
+
+        assert_eq!(
+            b.observe(&market, &[observation("yes", 0.5, Some(100.0))], now),
+            None
+        );
+        assert_eq!(
+            b.observe(&market, &[observation("yes", 0.7, Some(80.0))], now),
+            None
+        );
+        assert_eq!(
+            b.observe(&market, &[observation("yes", 1.2, Some(80.0))], now),
+            Some(Anomaly::OutOfRange {
+                token_id: "yes".into(),
+                price: 1.2
+            })
+        );
+        // Compared with 0.7, not the rejected 1.2
+        assert_eq!(
+            b.observe(&market, &[observation("yes", 0.3, Some(80.0))], now),
+            Some(Anomaly::Jump {
+                token_id: "yes".into(),
+                from: 0.7,
+                to: 0.3
+            })
+        );
+        assert_eq!(
+            b.observe(&market, &[observation("yes", 0.35, Some(0.0))], now),
+            Some(Anomaly::DepthCollapse {
+                token_id: "yes".into()
+            })
+        );
+        // No book this tick: depth is not judged
+        assert_eq!(
+            b.observe(&market, &[observation("yes", 0.35, None)], now),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cooldown_holds_market_out() {
+        let b = breaker();
+        let market: MarketId = "m1".into();
+        let now = Instant::now();
+
+        b.observe(&market, &[observation("yes", 0.5, None)], now);
+        assert!(b.allows(&market, now));
+        b.observe(&market, &[observation("yes", 0.0, None)], now);
+        assert!(!b.allows(&market, now + Duration::from_secs(59)));
+        assert!(b.allows(&"m2".into(), now));
+
+        let tripped = b.tripped(now + Duration::from_secs(20));
+        assert_eq!(tripped.len(), 1);
+        assert_eq!(tripped[0].resumes_in_secs, 40);
+        assert_eq!(
+            b.held_for(&market, now + Duration::from_secs(20)),
+            Some(Duration::from_secs(40))
+        );
+
+        // Another anomaly restarts the cooldown
+        b.observe(
+            &market,
+            &[observation("yes", -1.0, None)],
+            now + Duration::from_secs(30),
+        );
+        assert!(!b.allows(&market, now + Duration::from_secs(60)));
+        assert!(b.allows(&market, now + Duration::from_secs(90)));
+        assert!(b.tripped(now + Duration::from_secs(90)).is_empty());
+    }
+}
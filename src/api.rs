@@ -2,30 +2,83 @@
 //!
 //! Exposes endpoints for the dashboard to control the agent and view stats.
 
+use crate::accounts::{AccountReport, AccountRouter};
+use crate::allowance_history::AllowancePoint;
+use crate::anomaly::{AnomalyBreaker, TrippedMarket};
+use crate::bankroll::{Bankroll, FundingError, FundingRequest};
+use crate::breakdown::PnlBreakdown;
+use crate::candles::{Candle, CandleInterval, CandleStore};
+use crate::config::StrategyPatch;
+use crate::correlation::CorrelationTracker;
+use crate::data_source::{DataSource, DataSourceState};
+use crate::evm::{OnChainAccount, OnChainState};
+use crate::external::{self, ExternalSignal, ExternalSignalQueue, SignalError};
+use crate::market_store::MarketStore;
 use crate::metamask::{MetaMaskClient, PermissionGrant};
-use crate::positions::PositionManager;
-use crate::types::Market;
-use serde::Serialize;
+use crate::metrics::{LatencyPercentiles, LatencyTracker};
+use crate::money::Decimal;
+use crate::orders::{Fill, Order, OrderManager};
+use crate::positions::{PositionManager, TradeStats};
+use crate::quarantine::{QuarantinedToken, TokenQuarantine};
+use crate::quote::{self, QuoteError};
+use crate::simulate::{self, WhatIf};
+use crate::skips::SkipCounter;
+use crate::solana::SolanaState;
+use crate::spread_history::{SpreadHistory, SpreadSample};
+use crate::strategy::Intent;
+use crate::streak::{LosingStreakGuard, StreakStatus};
+use crate::tape::{TapeMetrics, Trade, TradeTape};
+use crate::types::{Market, MarketId, TokenId};
+use crate::utilization::UtilizationTracker;
+use crate::watchdog::Heartbeat;
+use crate::workers::ContextState;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::RwLock;
+use warp::filters::BoxedFilter;
 use warp::Filter;
 
+/// Signals kept for the terminal UI
+const MAX_RECENT_SIGNALS: usize = 50;
+
+/// Summary of one detected intent
+#[derive(Debug, Clone)]
+pub struct RecentSignal {
+    pub strategy: &'static str,
+    pub market_id: MarketId,
+    pub spread: f64,
+    pub expected_profit: f64,
+    pub timestamp: u64,
+}
+
 /// Cached market data with timestamp
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct MarketCache {
-    pub markets: Vec<Market>,
+    pub markets: MarketStore,
     pub last_update: Option<Instant>,
     pub signal_count: usize,
+    /// Most recent signals, oldest first
+    pub recent_signals: VecDeque<RecentSignal>,
 }
 
-impl Default for MarketCache {
-    fn default() -> Self {
-        Self {
-            markets: Vec::new(),
-            last_update: None,
-            signal_count: 0,
+impl MarketCache {
+    /// Count new intents and keep a short history of them
+    pub fn record_signals(&mut self, intents: &[Intent], now: u64) {
+        self.signal_count += intents.len();
+        for intent in intents {
+            if self.recent_signals.len() == MAX_RECENT_SIGNALS {
+                self.recent_signals.pop_front();
+            }
+            self.recent_signals.push_back(RecentSignal {
+                strategy: intent.strategy,
+                market_id: intent.market_id.clone(),
+                spread: intent.spread,
+                expected_profit: intent.expected_profit,
+                timestamp: now,
+            });
         }
     }
 }
@@ -34,17 +87,78 @@ impl Default for MarketCache {
 #[derive(Clone)]
 pub struct ApiState {
     pub metamask: Arc<MetaMaskClient>,
+    /// Smart accounts trades are routed across
+    pub accounts: Arc<AccountRouter>,
     pub position_manager: Arc<RwLock<PositionManager>>,
     pub market_cache: Arc<RwLock<MarketCache>>,
+    pub latency: Arc<LatencyTracker>,
+    pub tape: Arc<TradeTape>,
+    pub spread_history: Arc<SpreadHistory>,
+    pub candles: Arc<CandleStore>,
+    pub on_chain: OnChainState,
+    /// Solana wallet balance and today's fees against `daily_limit_sol`,
+    /// when a keypair is loaded
+    pub solana: SolanaState,
+    pub orders: Arc<OrderManager>,
+    pub data_source: DataSourceState,
+    /// Third-party signals waiting for their market's next scan
+    pub external_signals: Arc<ExternalSignalQueue>,
+    /// Whether POST /api/signals/external is accepted
+    pub external_signals_enabled: bool,
+    /// Trading loop liveness, flagged by the watchdog
+    pub heartbeat: Heartbeat,
+    /// Signals dropped before trading, by reason
+    pub skips: Arc<SkipCounter>,
+    /// Realized PnL by trade origin and entry mode
+    pub pnl_breakdown: Arc<PnlBreakdown>,
+    /// Capital in positions vs idle allowance over the session
+    pub utilization: Arc<UtilizationTracker>,
+    /// Initial bankroll, deposits, and withdrawals (None if it failed to load)
+    pub bankroll: Option<Arc<Bankroll>>,
+    /// Tokens backing off after failed book requests
+    pub quarantine: Arc<TokenQuarantine>,
+    /// Markets held out of trading after implausible feed data
+    pub anomalies: Arc<AnomalyBreaker>,
+    /// Entry pause after consecutive losing exits
+    pub losing_streak: Arc<LosingStreakGuard>,
+    /// Return correlations between markets
+    pub correlations: Arc<CorrelationTracker>,
+    /// Refuse every control route
+    pub read_only: bool,
+    /// Grants expiring within this many seconds are flagged in stats
+    pub expiry_warning_secs: u64,
+    /// Trading context, set once the agent has finished starting
+    pub trading: ContextState,
 }
 
 /// Start the API server
 pub async fn start_server(state: ApiState) {
+    if state.read_only {
+        println!("🔒 [API] Read-only: control routes are disabled");
+    }
+    println!("🌍 [API] Server starting on http://localhost:3030");
+    warp::serve(routes(state)).run(([127, 0, 0, 1], 3030)).await;
+}
+
+/// Every API route, the dashboard, and CORS over `state`
+fn routes(state: ApiState) -> BoxedFilter<(warp::reply::Response,)> {
     // CORS configuration
+    let methods = if state.read_only {
+        vec!["GET", "OPTIONS"]
+    } else {
+        vec!["GET", "POST", "PATCH", "OPTIONS"]
+    };
     let cors = warp::cors()
         .allow_any_origin()
         .allow_headers(vec!["content-type"])
-        .allow_methods(vec!["GET", "POST", "OPTIONS"]);
+        .allow_methods(methods);
+
+    // POST/PATCH /api/* in read-only mode
+    // Refused before any control route sees the request
+    let read_only_route = warp::path("api")
+        .and(warp::post().or(warp::patch()).unify())
+        .and(with_state(state.clone()))
+        .and_then(handle_read_only);
 
     // POST /api/permission
     // Receives permission grant from frontend (MetaMask)
@@ -54,6 +168,14 @@ pub async fn start_server(state: ApiState) {
         .and(with_state(state.clone()))
         .and_then(handle_permission);
 
+    // POST /api/signals/external
+    // Queues a third-party signal for its market's next scan
+    let external_signal_route = warp::path!("api" / "signals" / "external")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_external_signal);
+
     // GET /api/stats
     // Returns live stats for dashboard
     let stats_route = warp::path!("api" / "stats")
@@ -61,6 +183,41 @@ pub async fn start_server(state: ApiState) {
         .and(with_state(state.clone()))
         .and_then(handle_stats);
 
+    // GET /api/stats/skips
+    // Returns skipped signal counts by reason and category
+    let skips_route = warp::path!("api" / "stats" / "skips")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .map(|state: ApiState| warp::reply::json(&state.skips.snapshot()));
+
+    // GET /api/stats/pnl
+    // Returns realized PnL by trade origin, strategy mode at entry, and both
+    let pnl_breakdown_route = warp::path!("api" / "stats" / "pnl")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .map(|state: ApiState| warp::reply::json(&state.pnl_breakdown.snapshot()));
+
+    // GET /api/stats/utilization
+    // Returns time-weighted capital in positions vs idle allowance, and hold times
+    let utilization_route = warp::path!("api" / "stats" / "utilization")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .map(|state: ApiState| warp::reply::json(&state.utilization.report()));
+
+    // GET /api/allowance/history
+    // Returns spends and resets against the grant, with a cap projection
+    let allowance_history_route = warp::path!("api" / "allowance" / "history")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_allowance_history);
+
+    // GET /api/accounts
+    // Returns allowance, exposure, and open positions per smart account
+    let accounts_route = warp::path!("api" / "accounts")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_accounts);
+
     // GET /api/markets
     // Returns cached market data for dashboard
     let markets_route = warp::path!("api" / "markets")
@@ -68,6 +225,150 @@ pub async fn start_server(state: ApiState) {
         .and(with_state(state.clone()))
         .and_then(handle_markets);
 
+    // GET /api/markets/{id}/spread-history
+    // Returns the market's timestamped spread samples for charting
+    let spread_history_route = warp::path!("api" / "markets" / MarketId / "spread-history")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .map(|market_id: MarketId, state: ApiState| {
+            warp::reply::json(&SpreadHistoryResponse {
+                samples: state.spread_history.samples(&market_id),
+                market_id,
+            })
+        });
+
+    // GET /api/markets/{id}/candles?interval=1m|5m|1h
+    // Returns OHLC candles for each of the market's tokens
+    let candles_route = warp::path!("api" / "markets" / MarketId / "candles")
+        .and(warp::get())
+        .and(warp::query::<CandlesQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_candles);
+
+    // GET /api/tape/{token_id}
+    // Returns recent trades and order-flow metrics for a token
+    let tape_route = warp::path!("api" / "tape" / TokenId)
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .map(|token_id: TokenId, state: ApiState| {
+            warp::reply::json(&TapeResponse {
+                metrics: state.tape.metrics(&token_id),
+                trades: state.tape.recent(&token_id),
+                token_id,
+            })
+        });
+
+    // GET /api/orders
+    // Returns open orders and recent fills from the user channel
+    let orders_route = warp::path!("api" / "orders")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .map(|state: ApiState| {
+            warp::reply::json(&OrdersResponse {
+                open_orders: state.orders.open_orders(),
+                fills: state.orders.fills(),
+                rejections: state.orders.rejection_counts(),
+            })
+        });
+
+    // GET /api/orders/{id}
+    // Returns one order by id, including filled and rejected ones
+    let order_route = warp::path!("api" / "orders" / String)
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .map(
+            |order_id: String, state: ApiState| match state.orders.order(&order_id) {
+                Some(order) => {
+                    warp::reply::with_status(warp::reply::json(&order), warp::http::StatusCode::OK)
+                }
+                None => warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({ "error": "unknown order" })),
+                    warp::http::StatusCode::NOT_FOUND,
+                ),
+            },
+        );
+
+    // GET /api/exposure
+    // Returns net directional exposure per market (hedged YES+NO pairs netted out)
+    let exposure_route = warp::path!("api" / "exposure")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_exposure);
+
+    // GET /api/analytics/correlations
+    // Returns rolling return correlations between tracked markets
+    let correlations_route = warp::path!("api" / "analytics" / "correlations")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .map(|state: ApiState| warp::reply::json(&state.correlations.matrix()));
+
+    // GET /api/bankroll
+    // Returns capital contributed, equity, and total return on it
+    let bankroll_route = warp::path!("api" / "bankroll")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_bankroll);
+
+    // POST /api/bankroll
+    // Records the initial bankroll, a deposit, or a withdrawal
+    let funding_route = warp::path!("api" / "bankroll")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_funding);
+
+    // PATCH /api/config/strategy
+    // Adjusts min edges, trade size, and thresholds while running
+    let strategy_config_route = warp::path!("api" / "config" / "strategy")
+        .and(warp::patch())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_strategy_patch);
+
+    // POST /api/simulate
+    // Scans the current snapshot and recent candles with overridden parameters
+    let simulate_route = warp::path!("api" / "simulate")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_simulate);
+
+    // GET /api/quote?market_id=..&size=..
+    // Prices buying a market's full bundle now, without trading
+    let quote_route = warp::path!("api" / "quote")
+        .and(warp::get())
+        .and(warp::query::<QuoteQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_quote);
+
+    // GET /api/quarantine
+    // Returns tokens whose book requests are backing off after repeated
+    // failures, and markets held out of trading after a price anomaly
+    let quarantine_route = warp::path!("api" / "quarantine")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .map(|state: ApiState| {
+            let now = Instant::now();
+            warp::reply::json(&QuarantineResponse {
+                tokens: state.quarantine.quarantined(now),
+                markets: state.anomalies.tripped(now),
+            })
+        });
+
+    // GET /api/health
+    // Returns agent health and per-endpoint latency percentiles
+    let health_route = warp::path!("api" / "health")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_health);
+
+    // GET /metrics
+    // Prometheus scrape endpoint
+    let metrics_route = warp::path!("metrics")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .map(|state: ApiState| state.latency.render_prometheus());
+
     // Serve dashboard static files
     // Get the dashboard directory path (relative to executable or use manifest dir for dev)
     let dashboard_dir = get_dashboard_path();
@@ -81,15 +382,41 @@ pub async fn start_server(state: ApiState) {
     // Serve other static files from dashboard directory
     let static_route = warp::fs::dir(dashboard_dir);
 
-    let routes = permission_route
-        .or(stats_route)
+    // Boxed so the full route tree stays within the type recursion limit
+    let stats_routes = stats_route
+        .or(skips_route)
+        .or(pnl_breakdown_route)
+        .or(utilization_route)
+        .boxed();
+    let bankroll_routes = bankroll_route.or(funding_route).boxed();
+    let orders_routes = orders_route.or(order_route).boxed();
+
+    // Simulations change nothing, so they stay open in read-only mode
+    simulate_route
+        .or(read_only_route)
+        .or(permission_route)
+        .or(external_signal_route)
+        .or(stats_routes)
+        .or(bankroll_routes)
         .or(markets_route)
+        .or(spread_history_route)
+        .or(candles_route)
+        .or(tape_route)
+        .or(orders_routes)
+        .or(exposure_route)
+        .or(correlations_route)
+        .or(quarantine_route)
+        .or(quote_route)
+        .or(allowance_history_route)
+        .or(accounts_route)
+        .or(strategy_config_route)
+        .or(health_route)
+        .or(metrics_route)
         .or(index_route)
         .or(static_route)
-        .with(cors);
-
-    println!("🌍 [API] Server starting on http://localhost:3030");
-    warp::serve(routes).run(([127, 0, 0, 1], 3030)).await;
+        .with(cors)
+        .map(warp::Reply::into_response)
+        .boxed()
 }
 
 fn with_state(
@@ -98,6 +425,19 @@ fn with_state(
     warp::any().map(move || state.clone())
 }
 
+/// Refuse a control request while the API is read-only
+///
+/// Otherwise the request falls through to the control routes.
+async fn handle_read_only(state: ApiState) -> Result<impl warp::Reply, warp::Rejection> {
+    if !state.read_only {
+        return Err(warp::reject::not_found());
+    }
+    Ok(warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({ "error": "API is read-only" })),
+        warp::http::StatusCode::FORBIDDEN,
+    ))
+}
+
 /// Handle permission update from frontend
 async fn handle_permission(
     grant: PermissionGrant, // Frontend sends the grant object directly
@@ -114,51 +454,292 @@ async fn handle_permission(
     Ok(warp::reply::json(&serde_json::json!({ "status": "ok" })))
 }
 
+/// Validate and queue an external signal
+async fn handle_external_signal(
+    signal: ExternalSignal,
+    state: ApiState,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let result = if state.external_signals_enabled {
+        external::validate(&signal, state.market_cache.read().await.markets.as_slice())
+    } else {
+        Err(SignalError::Disabled)
+    };
+
+    let (status, body) = match result {
+        Ok(()) => {
+            println!(
+                "📥 [API] External signal: {:?} on {} ({:.0}% confidence)",
+                signal.direction,
+                signal.market_id,
+                signal.confidence * 100.0
+            );
+            state
+                .external_signals
+                .push(signal, crate::wallet::Wallet::current_timestamp());
+            (
+                warp::http::StatusCode::ACCEPTED,
+                serde_json::json!({ "status": "queued" }),
+            )
+        }
+        Err(e) => {
+            let status = match e {
+                SignalError::Disabled => warp::http::StatusCode::FORBIDDEN,
+                _ => warp::http::StatusCode::BAD_REQUEST,
+            };
+            (
+                status,
+                serde_json::json!({ "status": "rejected", "error": e.to_string() }),
+            )
+        }
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&body), status))
+}
+
 #[derive(Serialize)]
 struct StatsResponse {
     connected: bool, // Agent is running
     permission_active: bool,
-    daily_limit: f64,
-    spent_today: f64,
+    /// Seconds until the grant expires (None without a grant)
+    permission_expires_in_secs: Option<u64>,
+    /// Set once the grant is within the widest warning horizon
+    permission_expiring: bool,
+    daily_limit: Decimal,
+    spent_today: Decimal,
+    // Session stats (since this process started)
     total_trades: usize,
     win_rate: f64,
-    total_pnl: f64,
+    total_pnl: Decimal,
+    realized_pnl: Decimal,
+    unrealized_pnl: Decimal,
     open_positions: usize,
+    // Value of all holdings vs. the unhedged (directional) part
+    gross_exposure: f64,
+    net_exposure: f64,
+    // Simulated demo-mode trades this session (excluded from the stats above)
+    demo: StatsBucket,
+    // On-chain USDC balance and exchange allowance (None until first read)
+    on_chain: Option<OnChainAccount>,
+    lifetime: LifetimeResponse,
+    // Balance and daily spend per chain with a wallet
+    treasury: Vec<ChainTreasury>,
+    // Losing streak and the entry pause it may have started
+    losing_streak: StreakStatus,
+}
+
+/// One chain's wallet against its daily limit
+#[derive(Serialize)]
+struct ChainTreasury {
+    chain: &'static str,
+    /// Asset the balance, limit, and spend are counted in
+    asset: &'static str,
+    /// None until the first on-chain read
+    balance: Option<f64>,
+    /// None when the chain has no limit
+    daily_limit: Option<f64>,
+    spent_today: f64,
+    remaining_today: Option<f64>,
+}
+
+impl ChainTreasury {
+    fn new(
+        chain: &'static str,
+        asset: &'static str,
+        balance: Option<f64>,
+        daily_limit: Option<f64>,
+        spent_today: f64,
+    ) -> Self {
+        Self {
+            chain,
+            asset,
+            balance,
+            daily_limit,
+            spent_today,
+            remaining_today: daily_limit.map(|limit| (limit - spent_today).max(0.0)),
+        }
+    }
+}
+
+/// Aggregate stats for one bucket of trades
+#[derive(Serialize)]
+struct StatsBucket {
+    trades: usize,
+    win_rate: f64,
+    total_pnl: Decimal,
+}
+
+impl From<&TradeStats> for StatsBucket {
+    fn from(stats: &TradeStats) -> Self {
+        Self {
+            trades: stats.trades,
+            win_rate: stats.win_rate() * 100.0,
+            total_pnl: stats.total_pnl,
+        }
+    }
+}
+
+/// Cumulative stats across restarts
+#[derive(Serialize)]
+struct LifetimeResponse {
+    live: StatsBucket,
+    demo: StatsBucket,
 }
 
 /// Handle stats request
 async fn handle_stats(state: ApiState) -> Result<impl warp::Reply, warp::Rejection> {
     let perm = state.metamask.get_permission().await;
     let pm = state.position_manager.read().await;
+    let cache = state.market_cache.read().await;
+    let unrealized_pnl = pm.unrealized_pnl(cache.markets.as_slice());
+    let exposure = pm.exposure(cache.markets.as_slice());
 
-    let (active, limit, spent) = match perm {
+    let (active, limit, spent) = match &perm {
         Some(p) => (!p.revoked, p.daily_limit, p.spent_today),
-        None => (false, 0.0, 0.0),
+        None => (false, Decimal::ZERO, Decimal::ZERO),
     };
+    let expires_in = perm
+        .as_ref()
+        .map(|p| p.secs_until_expiry(crate::wallet::Wallet::current_timestamp()));
+    let on_chain = *state.on_chain.read().await;
+
+    // Polygon spend is the permission's; Solana spend is transaction fees
+    let mut treasury = vec![ChainTreasury::new(
+        "polygon",
+        "USDC",
+        on_chain.map(|account| account.usdc_balance),
+        perm.as_ref().map(|p| crate::money::to_f64(p.daily_limit)),
+        crate::money::to_f64(spent),
+    )];
+    if let Some(account) = state.solana.read().await.as_ref() {
+        treasury.push(ChainTreasury::new(
+            "solana",
+            "SOL",
+            Some(account.sol_balance),
+            (account.daily_limit > 0.0).then_some(account.daily_limit),
+            account.spent_today,
+        ));
+    }
 
     let stats = StatsResponse {
         connected: true,
         permission_active: active,
+        permission_expires_in_secs: expires_in,
+        permission_expiring: active
+            && expires_in.is_some_and(|secs| secs <= state.expiry_warning_secs),
         daily_limit: limit,
         spent_today: spent,
         total_trades: pm.trade_count(),
         win_rate: pm.win_rate() * 100.0,
         total_pnl: pm.total_pnl(),
+        realized_pnl: pm.total_pnl(),
+        unrealized_pnl,
         open_positions: pm.get_positions().len(),
+        gross_exposure: exposure.gross_value,
+        net_exposure: exposure.net_value,
+        demo: StatsBucket::from(pm.demo_stats()),
+        on_chain,
+        lifetime: LifetimeResponse {
+            live: StatsBucket::from(&pm.lifetime_stats().live),
+            demo: StatsBucket::from(&pm.lifetime_stats().demo),
+        },
+        treasury,
+        losing_streak: state
+            .losing_streak
+            .status(crate::wallet::Wallet::current_timestamp()),
     };
 
     Ok(warp::reply::json(&stats))
 }
 
+#[derive(Serialize)]
+struct AllowanceHistoryResponse {
+    daily_limit: Decimal,
+    spent_today: Decimal,
+    points: Vec<AllowancePoint>,
+    /// When today's spend rate would exhaust the allowance (None if idle)
+    projected_cap_at: Option<u64>,
+}
+
+/// Handle allowance history request
+async fn handle_allowance_history(state: ApiState) -> Result<impl warp::Reply, warp::Rejection> {
+    let (daily_limit, spent_today) = match state.metamask.get_permission().await {
+        Some(p) => (p.daily_limit, p.spent_today),
+        None => (Decimal::ZERO, Decimal::ZERO),
+    };
+    let history = state.metamask.allowance_history();
+    Ok(warp::reply::json(&AllowanceHistoryResponse {
+        daily_limit,
+        spent_today,
+        points: history.points(),
+        projected_cap_at: history.projected_cap_at(crate::wallet::Wallet::current_timestamp()),
+    }))
+}
+
+/// Handle exposure request
+async fn handle_exposure(state: ApiState) -> Result<impl warp::Reply, warp::Rejection> {
+    let pm = state.position_manager.read().await;
+    let exposure = pm.exposure(state.market_cache.read().await.markets.as_slice());
+    Ok(warp::reply::json(&exposure))
+}
+
+/// Handle per-account report request
+async fn handle_accounts(state: ApiState) -> Result<impl warp::Reply, warp::Rejection> {
+    let pm = state.position_manager.read().await;
+    let reports: Vec<AccountReport> = state.accounts.reports(&pm.get_positions()).await;
+    Ok(warp::reply::json(&reports))
+}
+
+/// Health API response
+#[derive(Serialize)]
+struct HealthResponse {
+    /// "ok", "degraded" while trading is suspended for latency, or
+    /// "unhealthy" while the trading loop is stalled
+    status: &'static str,
+    degraded_endpoint: Option<&'static str>,
+    /// Seconds since the trading loop's last heartbeat
+    heartbeat_age_secs: u64,
+    /// Where order books currently come from
+    data_source: DataSource,
+    latency: HashMap<&'static str, LatencyPercentiles>,
+}
+
+/// Handle health request
+async fn handle_health(state: ApiState) -> Result<impl warp::Reply, warp::Rejection> {
+    let degraded = state.latency.degraded();
+    let stalled = state.heartbeat.is_stalled();
+
+    let response = HealthResponse {
+        status: match (stalled, degraded.is_some()) {
+            (true, _) => "unhealthy",
+            (false, true) => "degraded",
+            (false, false) => "ok",
+        },
+        degraded_endpoint: degraded.map(|(endpoint, _)| endpoint.as_str()),
+        heartbeat_age_secs: state.heartbeat.age().as_secs(),
+        data_source: *state.data_source.read().await,
+        latency: state.latency.snapshot(),
+    };
+
+    // Liveness probes only look at the status code
+    let code = if stalled {
+        warp::http::StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        warp::http::StatusCode::OK
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&response), code))
+}
+
 /// Market info for API response
 #[derive(Serialize)]
 struct MarketInfo {
-    id: String,
+    id: MarketId,
     question: String,
     slug: String,
     outcomes: Vec<String>,
     prices: Vec<f64>,
     active: bool,
+    /// USDC on the books, per Gamma (null if unknown)
+    liquidity: Option<f64>,
+    volume_24hr: Option<f64>,
 }
 
 /// Markets API response
@@ -170,6 +751,235 @@ struct MarketsResponse {
     signal_count: usize,
 }
 
+#[derive(Deserialize)]
+struct CandlesQuery {
+    /// "1m" (default), "5m", or "1h"
+    interval: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CandlesResponse {
+    market_id: MarketId,
+    interval: CandleInterval,
+    tokens: HashMap<TokenId, Vec<Candle>>,
+}
+
+/// Handle candles request
+async fn handle_candles(
+    market_id: MarketId,
+    query: CandlesQuery,
+    state: ApiState,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let label = query.interval.as_deref().unwrap_or("1m");
+    let Some(interval) = CandleInterval::parse(label) else {
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({
+                "error": format!("unknown interval {}, expected 1m, 5m, or 1h", label)
+            })),
+            warp::http::StatusCode::BAD_REQUEST,
+        ));
+    };
+    let token_ids = state
+        .market_cache
+        .read()
+        .await
+        .markets
+        .get(&market_id)
+        .map(|m| m.clob_token_ids.clone());
+    let Some(token_ids) = token_ids else {
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({ "error": "unknown market" })),
+            warp::http::StatusCode::NOT_FOUND,
+        ));
+    };
+    let tokens = token_ids
+        .into_iter()
+        .map(|token_id| {
+            let candles = state.candles.candles(&token_id, interval);
+            (token_id, candles)
+        })
+        .collect();
+    Ok(warp::reply::with_status(
+        warp::reply::json(&CandlesResponse {
+            market_id,
+            interval,
+            tokens,
+        }),
+        warp::http::StatusCode::OK,
+    ))
+}
+
+#[derive(Serialize)]
+struct SpreadHistoryResponse {
+    market_id: MarketId,
+    samples: Vec<SpreadSample>,
+}
+
+#[derive(Serialize)]
+struct TapeResponse {
+    token_id: TokenId,
+    metrics: Option<TapeMetrics>,
+    trades: Vec<Trade>,
+}
+
+#[derive(Serialize)]
+struct QuarantineResponse {
+    tokens: Vec<QuarantinedToken>,
+    markets: Vec<TrippedMarket>,
+}
+
+/// Realized (lifetime) and unrealized PnL of live trading
+async fn live_pnl(state: &ApiState) -> (Decimal, Decimal) {
+    let pm = state.position_manager.read().await;
+    let cache = state.market_cache.read().await;
+    (
+        pm.lifetime_stats().live.total_pnl,
+        pm.unrealized_pnl(cache.markets.as_slice()),
+    )
+}
+
+fn bankroll_unavailable() -> warp::reply::WithStatus<warp::reply::Json> {
+    warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({ "error": "bankroll is unavailable" })),
+        warp::http::StatusCode::SERVICE_UNAVAILABLE,
+    )
+}
+
+/// Handle bankroll request
+async fn handle_bankroll(state: ApiState) -> Result<impl warp::Reply, warp::Rejection> {
+    let Some(bankroll) = &state.bankroll else {
+        return Ok(bankroll_unavailable());
+    };
+    let (realized, unrealized) = live_pnl(&state).await;
+    Ok(warp::reply::with_status(
+        warp::reply::json(&bankroll.report(realized, unrealized)),
+        warp::http::StatusCode::OK,
+    ))
+}
+
+/// Handle a recorded deposit, withdrawal, or initial bankroll
+async fn handle_funding(
+    request: FundingRequest,
+    state: ApiState,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let Some(bankroll) = &state.bankroll else {
+        return Ok(bankroll_unavailable());
+    };
+    let (realized, unrealized) = live_pnl(&state).await;
+    let equity = bankroll.net_contributed() + realized + unrealized;
+    let now = crate::wallet::Wallet::current_timestamp();
+    let (status, body) = match bankroll.record(&request, equity, now) {
+        Ok(event) => {
+            println!(
+                "🏦 [Bankroll] Recorded {:?} of ${:.2}",
+                event.kind, event.amount
+            );
+            (
+                warp::http::StatusCode::OK,
+                serde_json::to_value(&event).unwrap_or_default(),
+            )
+        }
+        Err(e) => (
+            match e {
+                FundingError::Storage(_) => warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+                _ => warp::http::StatusCode::BAD_REQUEST,
+            },
+            serde_json::json!({ "error": e.to_string() }),
+        ),
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&body), status))
+}
+
+/// Handle runtime strategy parameter change
+async fn handle_strategy_patch(
+    patch: StrategyPatch,
+    state: ApiState,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let Some(ctx) = state.trading.read().await.clone() else {
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({ "error": "agent is still starting" })),
+            warp::http::StatusCode::SERVICE_UNAVAILABLE,
+        ));
+    };
+    let (status, body) = match ctx.update_params(&patch).await {
+        Ok(params) => (
+            warp::http::StatusCode::OK,
+            serde_json::to_value(&params).unwrap_or_default(),
+        ),
+        Err(e) => (
+            warp::http::StatusCode::BAD_REQUEST,
+            serde_json::json!({ "error": e.to_string() }),
+        ),
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&body), status))
+}
+
+/// Handle what-if simulation request
+async fn handle_simulate(
+    what_if: WhatIf,
+    state: ApiState,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let Some(ctx) = state.trading.read().await.clone() else {
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({ "error": "agent is still starting" })),
+            warp::http::StatusCode::SERVICE_UNAVAILABLE,
+        ));
+    };
+    let (status, body) = match simulate::simulate(&ctx, &what_if).await {
+        Ok(simulation) => (
+            warp::http::StatusCode::OK,
+            serde_json::to_value(&simulation).unwrap_or_default(),
+        ),
+        Err(e) => (
+            warp::http::StatusCode::BAD_REQUEST,
+            serde_json::json!({ "error": e.to_string() }),
+        ),
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&body), status))
+}
+
+#[derive(Deserialize)]
+struct QuoteQuery {
+    market_id: MarketId,
+    /// Shares per leg (defaults to the configured trade size)
+    size: Option<f64>,
+}
+
+/// Handle dry-run quote request
+async fn handle_quote(
+    query: QuoteQuery,
+    state: ApiState,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let Some(ctx) = state.trading.read().await.clone() else {
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({ "error": "agent is still starting" })),
+            warp::http::StatusCode::SERVICE_UNAVAILABLE,
+        ));
+    };
+    let (status, body) = match quote::quote_bundle(&ctx, &query.market_id, query.size).await {
+        Ok(quote) => (
+            warp::http::StatusCode::OK,
+            serde_json::to_value(&quote).unwrap_or_default(),
+        ),
+        Err(e) => {
+            let status = match e {
+                QuoteError::UnknownMarket(_) => warp::http::StatusCode::NOT_FOUND,
+                _ => warp::http::StatusCode::UNPROCESSABLE_ENTITY,
+            };
+            (status, serde_json::json!({ "error": e.to_string() }))
+        }
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&body), status))
+}
+
+#[derive(Serialize)]
+struct OrdersResponse {
+    open_orders: Vec<Order>,
+    fills: Vec<Fill>,
+    /// Orders refused by the CLOB, by reason
+    rejections: HashMap<&'static str, usize>,
+}
+
 /// Handle markets request
 async fn handle_markets(state: ApiState) -> Result<impl warp::Reply, warp::Rejection> {
     let cache = state.market_cache.read().await;
@@ -179,9 +989,14 @@ async fn handle_markets(state: ApiState) -> Result<impl warp::Reply, warp::Rejec
         .map(|t| t.elapsed().as_millis() as u64)
         .unwrap_or(0);
 
-    let markets: Vec<MarketInfo> = cache
-        .markets
-        .iter()
+    // Most traded first
+    let mut by_volume: Vec<&Market> = cache.markets.as_slice().iter().collect();
+    by_volume.sort_by(|a, b| {
+        let volume = |m: &Market| m.volume_24hr.unwrap_or(0.0);
+        volume(b).total_cmp(&volume(a))
+    });
+    let markets: Vec<MarketInfo> = by_volume
+        .into_iter()
         .take(20)
         .map(|m| MarketInfo {
             id: m.id.clone(),
@@ -190,6 +1005,8 @@ async fn handle_markets(state: ApiState) -> Result<impl warp::Reply, warp::Rejec
             outcomes: m.outcomes.clone(),
             prices: m.outcome_prices.clone(),
             active: m.active,
+            liquidity: m.liquidity,
+            volume_24hr: m.volume_24hr,
         })
         .collect();
 
@@ -224,3 +1041,113 @@ fn get_dashboard_path() -> PathBuf {
     // Fallback to current directory
     PathBuf::from("dashboard")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentBuilder;
+    use crate::config::Config;
+    use crate::order_spec::OrderType;
+    use crate::orders::OrderRequest;
+    use crate::test_util;
+    use crate::types::Side;
+    use warp::http::StatusCode;
+
+    async fn api(name: &str, read_only: bool) -> BoxedFilter<(warp::reply::Response,)> {
+        let mut config = Config::default_config();
+        config.api.headless = true;
+        config.api.read_only = read_only;
+        config.trading.shadow_mode = true;
+        let agent = AgentBuilder::new(config)
+            .with_storage(test_util::temp_storage(name))
+            .build()
+            .await
+            .unwrap();
+        routes(agent.api_state)
+    }
+
+    fn grant() -> serde_json::Value {
+        serde_json::json!({
+            "permission_id": "perm_api",
+            "token": "USDC",
+            "daily_limit": "10",
+            "spent_today": "0",
+            "expires_at": u64::MAX,
+            "granted_at": 0,
+            "revoked": false,
+        })
+    }
+
+    async fn control_statuses(api: &BoxedFilter<(warp::reply::Response,)>) -> [StatusCode; 2] {
+        let permission = warp::test::request()
+            .method("POST")
+            .path("/api/permission")
+            .json(&grant())
+            .reply(api)
+            .await;
+        let strategy = warp::test::request()
+            .method("PATCH")
+            .path("/api/config/strategy")
+            .json(&serde_json::json!({ "trade_size": 3.0 }))
+            .reply(api)
+            .await;
+        [permission.status(), strategy.status()]
+    }
+
+    #[tokio::test]
+    async fn test_read_only_refuses_control_routes() {
+        let api = api("api_read_only", true).await;
+        assert_eq!(control_statuses(&api).await, [StatusCode::FORBIDDEN; 2]);
+
+        // Simulations change nothing and stay open
+        let simulate = warp::test::request()
+            .method("POST")
+            .path("/api/simulate")
+            .json(&serde_json::json!({ "min_edge": 0.01 }))
+            .reply(&api)
+            .await;
+        assert_eq!(simulate.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_control_routes_open_when_writable() {
+        let api = api("api_writable", false).await;
+        assert_eq!(control_statuses(&api).await, [StatusCode::OK; 2]);
+    }
+
+    #[tokio::test]
+    async fn test_order_by_id() {
+        let mut config = Config::default_config();
+        config.api.headless = true;
+        let agent = AgentBuilder::new(config)
+            .with_storage(test_util::temp_storage("api_order_by_id"))
+            .build()
+            .await
+            .unwrap();
+        let request = OrderRequest {
+            market: "m1".into(),
+            token_id: "t1".into(),
+            side: Side::Buy,
+            price: 0.45,
+            size: 10.0,
+            order_type: OrderType::Limit,
+            execution_id: None,
+        };
+        agent.api_state.orders.placed(&request, "o1", 1000);
+        let api = routes(agent.api_state);
+
+        let found = warp::test::request()
+            .path("/api/orders/o1")
+            .reply(&api)
+            .await;
+        assert_eq!(found.status(), StatusCode::OK);
+        let order: serde_json::Value = serde_json::from_slice(found.body()).unwrap();
+        assert_eq!(order["token_id"], "t1");
+
+        let missing = warp::test::request()
+            .path("/api/orders/o2")
+            .reply(&api)
+            .await;
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+}
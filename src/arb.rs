@@ -1,12 +1,39 @@
 #![allow(dead_code)]
+use crate::config::StrategyParams;
 use crate::constraint::ConstraintChecker;
-use crate::types::{ArbitrageSignal, Market};
+use crate::fees::FeeModel;
+use crate::gas::GasModel;
+use crate::order_spec::OrderType;
+use crate::strategy::{Intent, Strategy};
+use crate::types::{ArbitrageSignal, Market, Side};
+use rayon::prelude::*;
+
+/// Number of legs (transactions) in a binary bundle trade
+const BUNDLE_LEGS: usize = 2;
+
+/// Markets below which a scan stays on one thread (splitting costs more)
+const PARALLEL_SCAN_MIN: usize = 256;
+
+/// Run `check` over every market, in order, across threads for large universes
+fn scan_markets<T: Send>(
+    markets: &[Market],
+    check: impl Fn(&Market) -> Option<T> + Sync + Send,
+) -> Vec<T> {
+    if markets.len() < PARALLEL_SCAN_MIN {
+        markets.iter().filter_map(check).collect()
+    } else {
+        markets.par_iter().filter_map(check).collect()
+    }
+}
 
 /// Arbitrage detector
 #[derive(Debug)]
 pub struct ArbitrageDetector {
     pub constraint_checker: ConstraintChecker,
     pub min_profit_threshold: f64, // Minimum expected profit to trade
+    pub gas_model: GasModel,       // Transaction costs per leg
+    pub trade_size: f64,           // Size per leg for intents
+    pub fee_model: FeeModel,       // Fee schedule used to cost intents
 }
 
 impl ArbitrageDetector {
@@ -14,16 +41,36 @@ impl ArbitrageDetector {
         Self {
             constraint_checker: ConstraintChecker::new(min_spread),
             min_profit_threshold: min_profit,
+            gas_model: GasModel::free(),
+            trade_size: 5.0,
+            fee_model: FeeModel::new(0, 0),
         }
     }
 
+    /// Size and fee schedule used when turning signals into intents
+    pub fn with_sizing(mut self, trade_size: f64, fee_model: FeeModel) -> Self {
+        self.trade_size = trade_size;
+        self.fee_model = fee_model;
+        self
+    }
+
+    /// Account for gas/relayer costs in expected profit
+    pub fn with_gas_model(mut self, gas_model: GasModel) -> Self {
+        self.gas_model = gas_model;
+        self
+    }
+
     /// Scan markets for arbitrage opportunities
     pub fn scan(&self, markets: &[Market]) -> Vec<ArbitrageSignal> {
-        markets
-            .iter()
-            .filter(|m| m.active && m.accepting_orders)
-            .filter_map(|m| self.constraint_checker.check_violation(m))
-            .collect()
+        scan_markets(markets, |m| self.check(m))
+    }
+
+    /// Opportunity in one market, if it is trading
+    fn check(&self, market: &Market) -> Option<ArbitrageSignal> {
+        if !(market.active && market.accepting_orders) {
+            return None;
+        }
+        self.constraint_checker.check_violation(market)
     }
 
     /// Calculate expected profit after costs
@@ -37,8 +84,9 @@ impl ArbitrageDetector {
         let gross = signal.edge * size;
         let fee_cost = size * signal.yes_price * fee_rate * 2.0; // Both legs
         let slippage_cost = size * slippage;
+        let gas_cost = self.gas_model.cost(BUNDLE_LEGS);
 
-        gross - fee_cost - slippage_cost
+        gross - fee_cost - slippage_cost - gas_cost
     }
 
     /// Decide if trade is worth taking
@@ -53,27 +101,68 @@ impl ArbitrageDetector {
     }
 }
 
+impl Strategy for ArbitrageDetector {
+    fn name(&self) -> &'static str {
+        "pure_arb"
+    }
+
+    /// Buy the bundle wherever outcome prices sum below one by enough to cover costs
+    fn scan(&self, markets: &[Market]) -> Vec<Intent> {
+        // Read once: the tier lookup locks the shared volume history
+        let fee_rate = self.fee_model.taker_rate();
+        scan_markets(markets, |market| {
+            let signal = self.check(market)?;
+            // Selling the bundle requires minting sets first, so only buy-side is traded
+            if signal.recommended_side != Side::Buy {
+                return None;
+            }
+            let fee_rate = self.fee_model.market_taker_rate(&market.id, fee_rate);
+            let expected_profit = self.expected_profit(&signal, self.trade_size, fee_rate, 0.0);
+            if expected_profit <= self.min_profit_threshold {
+                return None;
+            }
+            Some(Intent {
+                strategy: self.name(),
+                market_id: signal.market_id,
+                token_ids: market.clob_token_ids.clone(),
+                side: Side::Buy,
+                size: self.trade_size,
+                spread: signal.spread,
+                edge: signal.spread,
+                expected_profit,
+                // Each leg fills whole or not at all; legs go out one at a
+                // time, so a later leg killed still leaves the earlier ones
+                // held as positions to their exits
+                order_type: OrderType::Fok,
+            })
+        })
+    }
+
+    fn set_params(&mut self, params: &StrategyParams) {
+        self.constraint_checker.min_spread_threshold = params.min_spread_threshold;
+        self.min_profit_threshold = params.min_profit_threshold;
+        self.trade_size = params.trade_size;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::Side;
+    use crate::test_util;
 
     fn create_test_market(yes_price: f64, no_price: f64, active: bool) -> Market {
         Market {
-            id: "test_market".to_string(),
+            id: "test_market".into(),
             question: "Test question?".to_string(),
             slug: "test-market".to_string(),
-            outcomes: vec!["Yes".to_string(), "No".to_string()],
-            outcome_prices: vec![yes_price, no_price],
-            clob_token_ids: vec!["token1".to_string(), "token2".to_string()],
+            clob_token_ids: vec!["token1".into(), "token2".into()],
             best_bid: Some(yes_price - 0.01),
             best_ask: Some(yes_price + 0.01),
-            maker_base_fee: 0,
             taker_base_fee: 200,
-            liquidity: 1000.0,
-            volume_24hr: 5000.0,
+            liquidity: Some(1000.0),
+            volume_24hr: Some(5000.0),
             active,
-            accepting_orders: true,
+            ..test_util::market(yes_price, no_price)
         }
     }
 
@@ -117,7 +206,7 @@ mod tests {
         let detector = ArbitrageDetector::new(0.02, 0.10);
 
         let signal = ArbitrageSignal {
-            market_id: "test".to_string(),
+            market_id: "test".into(),
             spread: 0.05,
             edge: 0.05,
             recommended_side: Side::Buy,
@@ -135,12 +224,34 @@ mod tests {
         assert!((profit - 2.08).abs() < 0.01);
     }
 
+    #[test]
+    fn test_expected_profit_includes_gas() {
+        let detector =
+            ArbitrageDetector::new(0.02, 0.10).with_gas_model(GasModel::new("polygon", 0.25));
+
+        let signal = ArbitrageSignal {
+            market_id: "test".into(),
+            spread: 0.05,
+            edge: 0.05,
+            recommended_side: Side::Buy,
+            yes_price: 0.48,
+            no_price: 0.47,
+        };
+
+        // 2.08 without gas, minus 2 legs * 0.25
+        let profit = detector.expected_profit(&signal, 100.0, 0.02, 0.01);
+        assert!((profit - 1.58).abs() < 0.01);
+
+        // A small trade no longer survives transaction costs
+        assert!(!detector.should_trade(&signal, 5.0, 0.02, 0.0));
+    }
+
     #[test]
     fn test_should_trade_above_threshold() {
         let detector = ArbitrageDetector::new(0.02, 0.10);
 
         let signal = ArbitrageSignal {
-            market_id: "test".to_string(),
+            market_id: "test".into(),
             spread: 0.05,
             edge: 0.05,
             recommended_side: Side::Buy,
@@ -152,12 +263,59 @@ mod tests {
         assert!(detector.should_trade(&signal, 100.0, 0.02, 0.01));
     }
 
+    #[test]
+    fn test_strategy_emits_bundle_intent() {
+        let detector = ArbitrageDetector::new(0.02, 0.10).with_sizing(100.0, FeeModel::new(0, 200));
+        let markets = [create_test_market(0.48, 0.47, true)];
+
+        let intents = Strategy::scan(&detector, &markets);
+        assert_eq!(intents.len(), 1);
+        assert_eq!(intents[0].strategy, "pure_arb");
+        assert_eq!(intents[0].token_ids, vec!["token1", "token2"]);
+        assert_eq!(intents[0].size, 100.0);
+        assert!(intents[0].expected_profit > 0.10);
+
+        // Too small to cover the profit threshold: no intent
+        let small = ArbitrageDetector::new(0.02, 0.10).with_sizing(1.0, FeeModel::new(0, 200));
+        assert!(Strategy::scan(&small, &markets).is_empty());
+    }
+
+    #[test]
+    fn test_large_universe_scan_matches_sequential() {
+        let detector = ArbitrageDetector::new(0.02, 0.10).with_sizing(100.0, FeeModel::new(0, 200));
+        let markets: Vec<Market> = (0..1_000)
+            .map(|i| {
+                let mut market = create_test_market(0.48, 0.40 + (i % 20) as f64 * 0.01, true);
+                market.id = format!("m{}", i).into();
+                market
+            })
+            .collect();
+
+        let intents = Strategy::scan(&detector, &markets);
+
+        let sequential: Vec<_> = markets
+            .iter()
+            .filter_map(|m| detector.check(m))
+            .filter(|s| s.recommended_side == Side::Buy)
+            .filter(|s| detector.should_trade(s, 100.0, 0.02, 0.0))
+            .map(|s| s.market_id)
+            .collect();
+        assert!(!sequential.is_empty());
+        assert_eq!(
+            intents
+                .iter()
+                .map(|i| i.market_id.clone())
+                .collect::<Vec<_>>(),
+            sequential
+        );
+    }
+
     #[test]
     fn test_should_not_trade_below_threshold() {
         let detector = ArbitrageDetector::new(0.02, 5.0); // High threshold
 
         let signal = ArbitrageSignal {
-            market_id: "test".to_string(),
+            market_id: "test".into(),
             spread: 0.05,
             edge: 0.05,
             recommended_side: Side::Buy,
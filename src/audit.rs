@@ -0,0 +1,246 @@
+//! Permission audit log
+//!
+//! Append-only record of every permission grant, revoke, spend, daily reset,
+//! and denial. Each entry carries the hash of the one before it, so editing,
+//! reordering, or dropping an entry breaks the chain and `verify` catches it.
+
+use crate::money::Decimal;
+use crate::storage::{Storage, StorageError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+
+/// Storage log holding the audit trail
+pub const AUDIT_LOG: &str = "audit";
+
+/// `prev_hash` of the first entry
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Something that happened to the spend permission
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuditEvent {
+    Grant {
+        permission_id: String,
+        token: String,
+        daily_limit: Decimal,
+        expires_at: u64,
+    },
+    Revoke {
+        permission_id: String,
+    },
+    Spend {
+        permission_id: String,
+        amount: Decimal,
+        spent_today: Decimal,
+    },
+    Reset {
+        permission_id: String,
+    },
+    Denial {
+        amount: Decimal,
+        reason: String,
+    },
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub event: AuditEvent,
+    /// Hash of the previous entry
+    pub prev_hash: String,
+    /// SHA-256 over this entry's fields and `prev_hash`
+    pub hash: String,
+}
+
+/// Hash chaining an entry to its predecessor
+fn entry_hash(prev_hash: &str, seq: u64, timestamp: u64, event: &AuditEvent) -> String {
+    let event = serde_json::to_string(event).unwrap_or_default();
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}|{}|{}|{}", prev_hash, seq, timestamp, event));
+    hex::encode(hasher.finalize())
+}
+
+/// Check that `entries` form an unbroken chain from the genesis hash
+pub fn verify_chain(entries: &[AuditEntry]) -> Result<(), AuditError> {
+    let mut prev_hash = GENESIS_HASH;
+    for (expected_seq, entry) in entries.iter().enumerate() {
+        if entry.seq != expected_seq as u64 {
+            return Err(AuditError::Broken(entry.seq, "out of sequence".to_string()));
+        }
+        if entry.prev_hash != prev_hash {
+            return Err(AuditError::Broken(
+                entry.seq,
+                "previous hash mismatch".to_string(),
+            ));
+        }
+        if entry.hash != entry_hash(&entry.prev_hash, entry.seq, entry.timestamp, &entry.event) {
+            return Err(AuditError::Broken(
+                entry.seq,
+                "entry was modified".to_string(),
+            ));
+        }
+        prev_hash = &entry.hash;
+    }
+    Ok(())
+}
+
+/// Tip of the chain the next entry links to
+#[derive(Debug)]
+struct ChainHead {
+    next_seq: u64,
+    last_hash: String,
+}
+
+/// Hash-chained, append-only audit trail
+#[derive(Debug)]
+pub struct AuditLog {
+    storage: Storage,
+    head: Mutex<ChainHead>,
+}
+
+impl AuditLog {
+    /// Open the log, continuing the chain from its last entry
+    pub fn open(storage: Storage) -> Result<Self, AuditError> {
+        let entries: Vec<AuditEntry> = storage.read_log(AUDIT_LOG)?;
+        let head = match entries.last() {
+            Some(last) => ChainHead {
+                next_seq: last.seq + 1,
+                last_hash: last.hash.clone(),
+            },
+            None => ChainHead {
+                next_seq: 0,
+                last_hash: GENESIS_HASH.to_string(),
+            },
+        };
+        Ok(Self {
+            storage,
+            head: Mutex::new(head),
+        })
+    }
+
+    /// Append one event, linking it to the previous entry
+    pub fn record(&self, event: AuditEvent, timestamp: u64) -> Result<(), AuditError> {
+        // Held across the write so entries land in chain order
+        let mut head = self.head.lock().unwrap();
+        let hash = entry_hash(&head.last_hash, head.next_seq, timestamp, &event);
+        let entry = AuditEntry {
+            seq: head.next_seq,
+            timestamp,
+            event,
+            prev_hash: head.last_hash.clone(),
+            hash,
+        };
+        self.storage.append(AUDIT_LOG, &entry)?;
+        head.next_seq += 1;
+        head.last_hash = entry.hash;
+        Ok(())
+    }
+
+    /// Re-read the whole log and check its chain, returning the entry count
+    pub fn verify(&self) -> Result<usize, AuditError> {
+        let entries: Vec<AuditEntry> = self.storage.read_log(AUDIT_LOG)?;
+        verify_chain(&entries)?;
+        Ok(entries.len())
+    }
+}
+
+#[derive(Debug)]
+pub enum AuditError {
+    Storage(StorageError),
+    /// Chain broken at the given sequence number
+    Broken(u64, String),
+}
+
+impl From<StorageError> for AuditError {
+    fn from(e: StorageError) -> Self {
+        Self::Storage(e)
+    }
+}
+
+impl std::fmt::Display for AuditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Storage(e) => write!(f, "Audit log unavailable: {}", e),
+            Self::Broken(seq, reason) => {
+                write!(f, "Audit chain broken at entry {}: {}", seq, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AuditError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::usdc;
+    use crate::test_util::temp_storage;
+    use std::fs;
+
+    #[test]
+    fn test_chain_survives_reopen_and_detects_tampering() {
+        let storage = temp_storage("audit");
+        let log = AuditLog::open(storage.clone()).unwrap();
+        log.record(
+            AuditEvent::Grant {
+                permission_id: "perm_1".to_string(),
+                token: "USDC".to_string(),
+                daily_limit: usdc(10.0),
+                expires_at: 2_000,
+            },
+            1_000,
+        )
+        .unwrap();
+        log.record(
+            AuditEvent::Spend {
+                permission_id: "perm_1".to_string(),
+                amount: usdc(2.5),
+                spent_today: usdc(2.5),
+            },
+            1_001,
+        )
+        .unwrap();
+
+        // A restart continues the same chain
+        let log = AuditLog::open(storage.clone()).unwrap();
+        log.record(
+            AuditEvent::Denial {
+                amount: usdc(9.0),
+                reason: "Insufficient daily allowance".to_string(),
+            },
+            1_002,
+        )
+        .unwrap();
+        assert_eq!(log.verify().unwrap(), 3);
+
+        // Rewriting a spend amount breaks the chain at that entry
+        let path = storage.log_path(AUDIT_LOG);
+        let tampered = fs::read_to_string(&path)
+            .unwrap()
+            .replace("\"amount\":2.5", "\"amount\":0.5");
+        fs::write(&path, tampered).unwrap();
+        assert!(matches!(log.verify(), Err(AuditError::Broken(1, _))));
+    }
+
+    #[test]
+    fn test_dropped_entry_breaks_chain() {
+        let storage = temp_storage("audit_drop");
+        let log = AuditLog::open(storage.clone()).unwrap();
+        for id in ["a", "b", "c"] {
+            log.record(
+                AuditEvent::Reset {
+                    permission_id: id.to_string(),
+                },
+                0,
+            )
+            .unwrap();
+        }
+        let mut entries: Vec<AuditEntry> = storage.read_log(AUDIT_LOG).unwrap();
+        entries.remove(1);
+        assert!(verify_chain(&entries).is_err());
+    }
+}
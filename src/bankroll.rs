@@ -0,0 +1,250 @@
+//! Bankroll tracking
+//!
+//! The capital behind the agent: an initial bankroll plus the deposits and
+//! withdrawals recorded through the API, kept apart from the daily
+//! permission limit (which caps spend, not capital). With realized and
+//! unrealized PnL on top it gives equity and the total return on the capital
+//! actually put in, rather than only per-trade PnL.
+
+use crate::money::{self, Decimal};
+use crate::storage::{Storage, StorageError};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// Storage document holding funding events
+pub const BANKROLL_DOCUMENT: &str = "bankroll";
+
+/// Kind of capital movement
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FundingKind {
+    /// Starting capital, recorded once
+    Initial,
+    Deposit,
+    Withdrawal,
+}
+
+/// One capital movement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingEvent {
+    pub kind: FundingKind,
+    pub amount: Decimal,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub note: String,
+}
+
+/// Capital movement posted to the API
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FundingRequest {
+    pub kind: FundingKind,
+    /// USDC, positive
+    pub amount: f64,
+    #[serde(default)]
+    pub note: String,
+}
+
+#[derive(Debug)]
+pub enum FundingError {
+    /// Amounts must be positive and finite
+    InvalidAmount(f64),
+    /// The initial bankroll has already been recorded
+    InitialRecorded,
+    /// Withdrawing more than the current equity
+    Overdrawn {
+        requested: Decimal,
+        equity: Decimal,
+    },
+    Storage(StorageError),
+}
+
+impl std::fmt::Display for FundingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidAmount(amount) => write!(f, "amount must be positive, got {}", amount),
+            Self::InitialRecorded => write!(f, "initial bankroll is already recorded"),
+            Self::Overdrawn { requested, equity } => write!(
+                f,
+                "withdrawal of ${:.2} exceeds equity of ${:.2}",
+                requested, equity
+            ),
+            Self::Storage(e) => write!(f, "Bankroll not saved: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FundingError {}
+
+/// Capital, PnL, and return, for the API
+#[derive(Debug, Clone, Serialize)]
+pub struct BankrollReport {
+    pub initial: Decimal,
+    pub deposits: Decimal,
+    pub withdrawals: Decimal,
+    /// Initial plus deposits less withdrawals
+    pub net_contributed: Decimal,
+    pub realized_pnl: Decimal,
+    pub unrealized_pnl: Decimal,
+    /// Net contributed plus all PnL
+    pub equity: Decimal,
+    /// PnL over net contributed capital (None before any capital is recorded)
+    pub total_return: Option<f64>,
+    pub events: Vec<FundingEvent>,
+}
+
+/// Funding history, persisted on every change
+#[derive(Debug)]
+pub struct Bankroll {
+    storage: Storage,
+    events: Mutex<Vec<FundingEvent>>,
+}
+
+impl Bankroll {
+    /// Open the bankroll, resuming recorded events
+    pub fn open(storage: Storage) -> Result<Self, StorageError> {
+        let events = storage.load(BANKROLL_DOCUMENT)?.unwrap_or_default();
+        Ok(Self {
+            storage,
+            events: Mutex::new(events),
+        })
+    }
+
+    /// Record a capital movement; withdrawals may not exceed `equity`
+    pub fn record(
+        &self,
+        request: &FundingRequest,
+        equity: Decimal,
+        now: u64,
+    ) -> Result<FundingEvent, FundingError> {
+        if !(request.amount > 0.0 && request.amount.is_finite()) {
+            return Err(FundingError::InvalidAmount(request.amount));
+        }
+        let amount = money::usdc(request.amount);
+        let mut events = self.events.lock().unwrap();
+        match request.kind {
+            FundingKind::Initial if events.iter().any(|e| e.kind == FundingKind::Initial) => {
+                return Err(FundingError::InitialRecorded);
+            }
+            FundingKind::Withdrawal if amount > equity => {
+                return Err(FundingError::Overdrawn {
+                    requested: amount,
+                    equity,
+                });
+            }
+            _ => {}
+        }
+        let event = FundingEvent {
+            kind: request.kind,
+            amount,
+            timestamp: now,
+            note: request.note.clone(),
+        };
+        events.push(event.clone());
+        if let Err(e) = self.storage.save(BANKROLL_DOCUMENT, &*events) {
+            events.pop();
+            return Err(FundingError::Storage(e));
+        }
+        Ok(event)
+    }
+
+    /// Capital net of withdrawals
+    pub fn net_contributed(&self) -> Decimal {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|e| match e.kind {
+                FundingKind::Withdrawal => -e.amount,
+                _ => e.amount,
+            })
+            .sum()
+    }
+
+    /// Capital and return given the agent's PnL so far
+    pub fn report(&self, realized_pnl: Decimal, unrealized_pnl: Decimal) -> BankrollReport {
+        let events = self.events.lock().unwrap().clone();
+        let total = |kind| -> Decimal {
+            events
+                .iter()
+                .filter(|e| e.kind == kind)
+                .map(|e| e.amount)
+                .sum()
+        };
+        let (initial, deposits, withdrawals) = (
+            total(FundingKind::Initial),
+            total(FundingKind::Deposit),
+            total(FundingKind::Withdrawal),
+        );
+        let net_contributed = initial + deposits - withdrawals;
+        let pnl = realized_pnl + unrealized_pnl;
+        BankrollReport {
+            initial,
+            deposits,
+            withdrawals,
+            net_contributed,
+            realized_pnl,
+            unrealized_pnl,
+            equity: net_contributed + pnl,
+            total_return: (net_contributed > Decimal::ZERO)
+                .then(|| money::to_f64(pnl / net_contributed)),
+            events,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::usdc;
+    use crate::test_util::temp_storage;
+
+    fn request(kind: FundingKind, amount: f64) -> FundingRequest {
+        FundingRequest {
+            kind,
+            amount,
+            note: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_return_on_contributed_capital() {
+        let storage = temp_storage("bankroll");
+        let bankroll = Bankroll::open(storage.clone()).unwrap();
+        assert_eq!(bankroll.report(usdc(1.0), Decimal::ZERO).total_return, None);
+
+        bankroll
+            .record(&request(FundingKind::Initial, 100.0), Decimal::ZERO, 10)
+            .unwrap();
+        assert!(matches!(
+            bankroll.record(&request(FundingKind::Initial, 50.0), usdc(100.0), 20),
+            Err(FundingError::InitialRecorded)
+        ));
+        assert!(matches!(
+            bankroll.record(&request(FundingKind::Deposit, -5.0), usdc(100.0), 20),
+            Err(FundingError::InvalidAmount(_))
+        ));
+        bankroll
+            .record(&request(FundingKind::Deposit, 60.0), usdc(100.0), 30)
+            .unwrap();
+        assert!(matches!(
+            bankroll.record(&request(FundingKind::Withdrawal, 200.0), usdc(170.0), 40),
+            Err(FundingError::Overdrawn { .. })
+        ));
+        bankroll
+            .record(&request(FundingKind::Withdrawal, 40.0), usdc(170.0), 40)
+            .unwrap();
+
+        // Reopened from storage
+        let bankroll = Bankroll::open(storage).unwrap();
+        assert_eq!(bankroll.net_contributed(), usdc(120.0));
+        let report = bankroll.report(usdc(8.0), usdc(-2.0));
+        assert_eq!(
+            (report.initial, report.deposits, report.withdrawals),
+            (usdc(100.0), usdc(60.0), usdc(40.0))
+        );
+        assert_eq!(report.equity, usdc(126.0));
+        assert_eq!(report.total_return, Some(0.05));
+        assert_eq!(report.events.len(), 3);
+    }
+}
@@ -0,0 +1,280 @@
+//! PnL breakdown by origin and mode
+//!
+//! Every entry is tagged with where it came from (pure arb, mean reversion,
+//! external signal, maker quote, or demo) and the strategy mode active when
+//! it was entered, and realized PnL is totalled per tag, so users can see
+//! which strategy and mode actually earns. Tags and totals persist across
+//! restarts.
+
+use crate::metamask::StrategyMode;
+use crate::money::Decimal;
+use crate::order_spec::OrderType;
+use crate::positions::TradeStats;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Storage document holding the PnL breakdown
+pub const BREAKDOWN_DOCUMENT: &str = "pnl_breakdown";
+
+/// What produced a trade
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeOrigin {
+    /// Taker bundle arbitrage
+    PureArb,
+    MeanReversion,
+    /// Third-party signal
+    External,
+    /// Resting post-only quote
+    Maker,
+    /// Simulated demo-mode trade
+    Demo,
+}
+
+impl TradeOrigin {
+    pub const ALL: [TradeOrigin; 5] = [
+        Self::PureArb,
+        Self::MeanReversion,
+        Self::External,
+        Self::Maker,
+        Self::Demo,
+    ];
+
+    /// Origin of an entry by the strategy behind it and how it met the book;
+    /// post-only entries are maker trades whatever their strategy
+    pub fn of(strategy: &str, order_type: OrderType) -> Self {
+        if order_type.post_only() {
+            return Self::Maker;
+        }
+        match strategy {
+            "mean_reversion" => Self::MeanReversion,
+            "external" => Self::External,
+            _ => Self::PureArb,
+        }
+    }
+
+    /// Label used in API output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PureArb => "pure_arb",
+            Self::MeanReversion => "mean_reversion",
+            Self::External => "external",
+            Self::Maker => "maker",
+            Self::Demo => "demo",
+        }
+    }
+}
+
+/// Origin and strategy mode of one entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TradeTag {
+    pub origin: TradeOrigin,
+    pub mode: StrategyMode,
+}
+
+/// Realized results of one origin in one mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Bucket {
+    tag: TradeTag,
+    stats: TradeStats,
+}
+
+/// Persisted tags and totals
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BreakdownState {
+    /// Tag of each entry execution, until its position closes
+    tags: HashMap<String, TradeTag>,
+    buckets: Vec<Bucket>,
+}
+
+/// Results of one origin/mode pair, for the API
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BreakdownRow {
+    pub origin: &'static str,
+    pub mode: &'static str,
+    pub trades: usize,
+    pub wins: usize,
+    pub win_rate: f64,
+    pub total_pnl: Decimal,
+}
+
+/// Realized PnL split by origin, by mode, and by both
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakdownSnapshot {
+    pub by_origin: HashMap<&'static str, TradeStats>,
+    pub by_mode: HashMap<&'static str, TradeStats>,
+    /// Every pair with at least one closed trade
+    pub rows: Vec<BreakdownRow>,
+}
+
+/// Tags entries and totals realized PnL per tag; shared by the pipeline,
+/// engine, and API
+#[derive(Debug, Default)]
+pub struct PnlBreakdown {
+    state: Mutex<BreakdownState>,
+}
+
+impl PnlBreakdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resume tags and totals loaded from storage
+    pub fn with_state(self, state: BreakdownState) -> Self {
+        *self.state.lock().unwrap() = state;
+        self
+    }
+
+    /// Copy of the tags and totals, for persisting
+    pub fn state(&self) -> BreakdownState {
+        self.state.lock().unwrap().clone()
+    }
+
+    /// Remember how an entry execution came about
+    pub fn tag(&self, execution_id: &str, tag: TradeTag) {
+        self.state
+            .lock()
+            .unwrap()
+            .tags
+            .insert(execution_id.to_string(), tag);
+    }
+
+    /// Count a closed position under the tag of its first tagged entry
+    ///
+    /// Positions entered before tagging (or whose tag is gone) are not
+    /// counted; returns the tag the PnL went to.
+    pub fn record_exit(&self, entry_executions: &[String], pnl: Decimal) -> Option<TradeTag> {
+        let mut state = self.state.lock().unwrap();
+        let tag = entry_executions
+            .iter()
+            .find_map(|id| state.tags.get(id).copied())?;
+        state.add(tag, pnl);
+        Some(tag)
+    }
+
+    /// Count a simulated demo trade, which has no position to close
+    pub fn record_demo(&self, mode: StrategyMode, pnl: Decimal) {
+        let tag = TradeTag {
+            origin: TradeOrigin::Demo,
+            mode,
+        };
+        self.state.lock().unwrap().add(tag, pnl);
+    }
+
+    /// Forget the tags of entries whose positions are fully closed
+    pub fn untag(&self, entry_executions: &[String]) {
+        let mut state = self.state.lock().unwrap();
+        for id in entry_executions {
+            state.tags.remove(id);
+        }
+    }
+
+    pub fn snapshot(&self) -> BreakdownSnapshot {
+        let state = self.state.lock().unwrap();
+        let mut snapshot = BreakdownSnapshot {
+            by_origin: TradeOrigin::ALL
+                .iter()
+                .map(|o| (o.as_str(), TradeStats::default()))
+                .collect(),
+            by_mode: StrategyMode::ALL
+                .iter()
+                .map(|m| (m.as_str(), TradeStats::default()))
+                .collect(),
+            rows: Vec::new(),
+        };
+        for bucket in &state.buckets {
+            let (origin, mode) = (bucket.tag.origin.as_str(), bucket.tag.mode.as_str());
+            for totals in [
+                snapshot.by_origin.get_mut(origin),
+                snapshot.by_mode.get_mut(mode),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                totals.trades += bucket.stats.trades;
+                totals.wins += bucket.stats.wins;
+                totals.total_pnl += bucket.stats.total_pnl;
+            }
+            snapshot.rows.push(BreakdownRow {
+                origin,
+                mode,
+                trades: bucket.stats.trades,
+                wins: bucket.stats.wins,
+                win_rate: bucket.stats.win_rate(),
+                total_pnl: bucket.stats.total_pnl,
+            });
+        }
+        snapshot
+            .rows
+            .sort_by_key(|row| std::cmp::Reverse(row.total_pnl));
+        snapshot
+    }
+}
+
+impl BreakdownState {
+    fn add(&mut self, tag: TradeTag, pnl: Decimal) {
+        match self.buckets.iter_mut().find(|b| b.tag == tag) {
+            Some(bucket) => bucket.stats.record(pnl),
+            None => {
+                let mut stats = TradeStats::default();
+                stats.record(pnl);
+                self.buckets.push(Bucket { tag, stats });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::usdc;
+
+    #[test]
+    fn test_splits_pnl_by_origin_and_mode() {
+        assert_eq!(
+            TradeOrigin::of("pure_arb", OrderType::Fok),
+            TradeOrigin::PureArb
+        );
+        assert_eq!(
+            TradeOrigin::of("mean_reversion", OrderType::PostOnly),
+            TradeOrigin::Maker
+        );
+
+        let breakdown = PnlBreakdown::new();
+        let tag = |origin, mode| TradeTag { origin, mode };
+        breakdown.tag("a1", tag(TradeOrigin::PureArb, StrategyMode::Aggressive));
+        breakdown.tag("a2", tag(TradeOrigin::PureArb, StrategyMode::Aggressive));
+        breakdown.tag("m1", tag(TradeOrigin::MeanReversion, StrategyMode::Normal));
+
+        assert!(breakdown
+            .record_exit(&["a1".into(), "a2".into()], usdc(0.40))
+            .is_some());
+        breakdown.record_exit(&["a2".into()], usdc(-0.10));
+        breakdown.record_exit(&["m1".into()], usdc(-0.25));
+        // Never tagged: not counted
+        assert!(breakdown.record_exit(&["old".into()], usdc(5.0)).is_none());
+        breakdown.record_demo(StrategyMode::Normal, usdc(0.05));
+
+        // Survives a save and load
+        let restored: BreakdownState =
+            serde_json::from_str(&serde_json::to_string(&breakdown.state()).unwrap()).unwrap();
+        let breakdown = PnlBreakdown::new().with_state(restored);
+        breakdown.untag(&["a1".into(), "a2".into()]);
+        assert!(breakdown.record_exit(&["a1".into()], usdc(1.0)).is_none());
+
+        let snapshot = breakdown.snapshot();
+        assert_eq!(snapshot.by_origin["pure_arb"].trades, 2);
+        assert_eq!(snapshot.by_origin["pure_arb"].total_pnl, usdc(0.30));
+        assert_eq!(snapshot.by_origin["maker"].trades, 0);
+        assert_eq!(snapshot.by_mode["Normal"].trades, 2);
+        assert_eq!(snapshot.by_mode["Normal"].total_pnl, usdc(-0.20));
+        assert_eq!(snapshot.rows.len(), 3);
+        // Best earner first
+        assert_eq!(
+            (snapshot.rows[0].origin, snapshot.rows[0].mode),
+            ("pure_arb", "Aggressive")
+        );
+        assert_eq!(snapshot.rows[0].win_rate, 0.5);
+    }
+}
@@ -0,0 +1,190 @@
+//! Adaptive polling cadence
+//!
+//! Markets with recent signals or fast-moving prices are polled at the
+//! fastest cadence; quiet markets back off towards the slowest one. A shared
+//! rate limiter keeps the combined book request rate within budget.
+
+use crate::config::CadenceConfig;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Smoothing factor for the volatility average
+const VOLATILITY_ALPHA: f64 = 0.3;
+
+/// Recent activity of one market
+#[derive(Debug, Clone, Default)]
+pub struct MarketActivity {
+    last_signal: Option<Instant>,
+    last_prices: Vec<f64>,
+    last_observed: Option<Instant>,
+    /// Smoothed largest outcome price move per minute
+    volatility: f64,
+}
+
+impl MarketActivity {
+    /// Record fresh outcome prices
+    pub fn observe(&mut self, prices: &[f64], now: Instant) {
+        if let Some(last) = self.last_observed {
+            let elapsed = now.duration_since(last).as_secs_f64().max(1.0);
+            let max_move = prices
+                .iter()
+                .zip(&self.last_prices)
+                .map(|(p, q)| (p - q).abs())
+                .fold(0.0, f64::max);
+            let per_minute = max_move * 60.0 / elapsed;
+            self.volatility =
+                VOLATILITY_ALPHA * per_minute + (1.0 - VOLATILITY_ALPHA) * self.volatility;
+        }
+        self.last_prices = prices.to_vec();
+        self.last_observed = Some(now);
+    }
+
+    /// Record that a strategy produced a signal
+    pub fn signal(&mut self, now: Instant) {
+        self.last_signal = Some(now);
+    }
+}
+
+/// Picks a poll interval from market activity
+#[derive(Debug, Clone)]
+pub struct AdaptiveCadence {
+    min_interval: Duration,
+    max_interval: Duration,
+    hot_window: Duration,
+    volatility_threshold: f64,
+}
+
+impl AdaptiveCadence {
+    pub fn from_config(config: &CadenceConfig) -> Self {
+        let min_interval = Duration::from_secs(config.min_interval_secs.max(1));
+        Self {
+            min_interval,
+            max_interval: Duration::from_secs(config.max_interval_secs).max(min_interval),
+            hot_window: Duration::from_secs(config.hot_window_secs),
+            volatility_threshold: config.volatility_threshold,
+        }
+    }
+
+    /// Poll interval for a market given its activity
+    pub fn interval(&self, activity: &MarketActivity, now: Instant) -> Duration {
+        let recently_signalled = activity
+            .last_signal
+            .is_some_and(|t| now.duration_since(t) <= self.hot_window);
+
+        // 1.0 = hottest (fastest polling), 0.0 = quiet
+        let heat = if recently_signalled {
+            1.0
+        } else if self.volatility_threshold > 0.0 {
+            (activity.volatility / self.volatility_threshold).min(1.0)
+        } else {
+            0.0
+        };
+
+        self.max_interval
+            .mul_f64(1.0 - heat)
+            .saturating_add(self.min_interval.mul_f64(heat))
+    }
+}
+
+/// Spaces requests evenly to stay within a requests-per-second budget
+#[derive(Debug)]
+pub struct RateLimiter {
+    spacing: Duration,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_sec: f64) -> Self {
+        let spacing = if requests_per_sec > 0.0 {
+            Duration::from_secs_f64(1.0 / requests_per_sec)
+        } else {
+            Duration::ZERO
+        };
+        Self {
+            spacing,
+            next_slot: Mutex::new(None),
+        }
+    }
+
+    /// Reserve the next free request slot at or after `now`
+    async fn reserve(&self, now: Instant) -> Instant {
+        let mut next_slot = self.next_slot.lock().await;
+        let slot = next_slot.map_or(now, |next| next.max(now));
+        *next_slot = Some(slot + self.spacing);
+        slot
+    }
+
+    /// Wait until a request may be sent
+    pub async fn acquire(&self) {
+        let slot = self.reserve(Instant::now()).await;
+        tokio::time::sleep_until(slot.into()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cadence() -> AdaptiveCadence {
+        AdaptiveCadence::from_config(&CadenceConfig::default())
+    }
+
+    #[test]
+    fn test_quiet_market_polls_slowly() {
+        let now = Instant::now();
+        let mut activity = MarketActivity::default();
+        activity.observe(&[0.5, 0.5], now);
+        activity.observe(&[0.5, 0.5], now + Duration::from_secs(60));
+
+        assert_eq!(
+            cadence().interval(&activity, now + Duration::from_secs(60)),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn test_signal_polls_fast_until_window_expires() {
+        let now = Instant::now();
+        let mut activity = MarketActivity::default();
+        activity.signal(now);
+
+        assert_eq!(cadence().interval(&activity, now), Duration::from_secs(1));
+        assert_eq!(
+            cadence().interval(&activity, now + Duration::from_secs(121)),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn test_volatility_shortens_interval() {
+        let now = Instant::now();
+        let mut activity = MarketActivity::default();
+        activity.observe(&[0.50, 0.50], now);
+        // 5c move in 30s = 10c/min, well above the 2c/min threshold
+        activity.observe(&[0.55, 0.45], now + Duration::from_secs(30));
+        assert!(activity.volatility > 0.02);
+        assert_eq!(cadence().interval(&activity, now), Duration::from_secs(1));
+
+        // Small moves land between the bounds
+        let mut calm = MarketActivity::default();
+        calm.observe(&[0.50], now);
+        calm.observe(&[0.505], now + Duration::from_secs(60));
+        let interval = cadence().interval(&calm, now);
+        assert!(interval > Duration::from_secs(1) && interval < Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_spaces_slots() {
+        let limiter = RateLimiter::new(10.0);
+        let now = Instant::now();
+
+        let first = limiter.reserve(now).await;
+        let second = limiter.reserve(now).await;
+        assert_eq!(first, now);
+        assert_eq!(second - first, Duration::from_millis(100));
+
+        // Idle time doesn't bank extra slots
+        let later = now + Duration::from_secs(5);
+        assert_eq!(limiter.reserve(later).await, later);
+    }
+}
@@ -0,0 +1,204 @@
+//! OHLC candles
+//!
+//! Aggregates each tracked token's price (from streamed or polled books) into
+//! 1m, 5m, and 1h candles, keeping a bounded number per interval for the
+//! dashboard's charts. The series can be persisted across restarts.
+
+use crate::types::TokenId;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// Storage document holding persisted candles
+pub const CANDLES_DOCUMENT: &str = "candles";
+
+/// Candle width
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CandleInterval {
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+}
+
+impl CandleInterval {
+    pub const ALL: [CandleInterval; 3] = [Self::OneMinute, Self::FiveMinutes, Self::OneHour];
+
+    pub fn secs(&self) -> u64 {
+        match self {
+            Self::OneMinute => 60,
+            Self::FiveMinutes => 300,
+            Self::OneHour => 3_600,
+        }
+    }
+
+    /// Label used in API paths and output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::OneMinute => "1m",
+            Self::FiveMinutes => "5m",
+            Self::OneHour => "1h",
+        }
+    }
+
+    /// Parse an API label ("1m", "5m", "1h")
+    pub fn parse(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|i| i.as_str() == label)
+    }
+}
+
+/// Open, high, low, and close over one interval
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Candle {
+    /// Unix time in seconds the interval starts at
+    pub start: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Price observations folded in
+    pub samples: u32,
+}
+
+impl Candle {
+    fn new(start: u64, price: f64) -> Self {
+        Self {
+            start,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            samples: 1,
+        }
+    }
+
+    fn update(&mut self, price: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.samples += 1;
+    }
+}
+
+/// Candles per token and interval, oldest first
+pub type CandleSeries = HashMap<TokenId, HashMap<CandleInterval, VecDeque<Candle>>>;
+
+/// Bounded candle buffers shared by the workers, engine, and API
+#[derive(Debug)]
+pub struct CandleStore {
+    series: Mutex<CandleSeries>,
+    /// Candles kept per token and interval
+    capacity: usize,
+}
+
+impl CandleStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            series: Mutex::new(HashMap::new()),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Continue from previously persisted candles
+    pub fn with_series(self, series: CandleSeries) -> Self {
+        *self.series.lock().unwrap() = series;
+        self
+    }
+
+    /// Fold a price observed at `timestamp` into every interval
+    pub fn record(&self, token_id: &TokenId, timestamp: u64, price: f64) {
+        if !price.is_finite() || price <= 0.0 {
+            return;
+        }
+        let mut series = self.series.lock().unwrap();
+        let token = series.entry(token_id.clone()).or_default();
+        for interval in CandleInterval::ALL {
+            let start = timestamp - timestamp % interval.secs();
+            let candles = token.entry(interval).or_default();
+            match candles.back_mut() {
+                Some(last) if last.start == start => last.update(price),
+                // Late observations for a closed candle are dropped
+                Some(last) if last.start > start => {}
+                _ => {
+                    if candles.len() == self.capacity {
+                        candles.pop_front();
+                    }
+                    candles.push_back(Candle::new(start, price));
+                }
+            }
+        }
+    }
+
+    /// A token's candles for one interval, oldest first
+    pub fn candles(&self, token_id: &TokenId, interval: CandleInterval) -> Vec<Candle> {
+        self.series
+            .lock()
+            .unwrap()
+            .get(token_id)
+            .and_then(|token| token.get(&interval))
+            .map(|candles| candles.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Copy of every series, for persisting
+    pub fn series(&self) -> CandleSeries {
+        self.series.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregates_into_intervals() {
+        let store = CandleStore::new(10);
+        let token: TokenId = "yes".into();
+        for (t, price) in [(0, 0.50), (20, 0.55), (40, 0.45), (59, 0.52), (60, 0.53)] {
+            store.record(&token, t, price);
+        }
+
+        let minutes = store.candles(&token, CandleInterval::OneMinute);
+        assert_eq!(minutes.len(), 2);
+        assert_eq!(
+            minutes[0],
+            Candle {
+                start: 0,
+                open: 0.50,
+                high: 0.55,
+                low: 0.45,
+                close: 0.52,
+                samples: 4,
+            }
+        );
+        assert_eq!(minutes[1].start, 60);
+
+        let hours = store.candles(&token, CandleInterval::OneHour);
+        assert_eq!(hours.len(), 1);
+        assert_eq!(hours[0].close, 0.53);
+        assert_eq!(hours[0].samples, 5);
+    }
+
+    #[test]
+    fn test_bounded_and_round_trips() {
+        let store = CandleStore::new(3);
+        let token: TokenId = "yes".into();
+        for minute in 0..5 {
+            store.record(&token, minute * 60, 0.5);
+        }
+        let minutes = store.candles(&token, CandleInterval::OneMinute);
+        assert_eq!(minutes.len(), 3);
+        assert_eq!(minutes[0].start, 120);
+
+        let json = serde_json::to_string(&store.series()).unwrap();
+        let restored = CandleStore::new(3).with_series(serde_json::from_str(&json).unwrap());
+        assert_eq!(restored.candles(&token, CandleInterval::OneMinute), minutes);
+        assert_eq!(
+            CandleInterval::parse("5m"),
+            Some(CandleInterval::FiveMinutes)
+        );
+        assert_eq!(CandleInterval::parse("2m"), None);
+    }
+}
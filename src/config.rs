@@ -2,13 +2,28 @@
 //!
 //! Loads settings from config.toml instead of hardcoded values.
 
-use serde::Deserialize;
+use crate::alerts::AlertRule;
+use crate::allocator::AllocationMode;
+use crate::candles::CandleInterval;
+use crate::fees::FeeTier;
+use crate::market::HydrationMode;
+use crate::order_spec::SizingBasis;
+use crate::positions::{DuplicateEntryPolicy, ExitLimits, ProfitTarget};
+use crate::secrets::SecretSource;
+use crate::signer::SignatureType;
+use crate::solana::Commitment;
+use crate::types::Market;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 
 /// Root configuration structure
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
     pub permission: PermissionConfig,
+    /// Smart accounts traded through besides the `[permission]` one
+    #[serde(default)]
+    pub accounts: Vec<AccountConfig>,
     pub trading: TradingConfig,
     pub timing: TimingConfig,
     pub api: ApiConfig,
@@ -16,7 +31,65 @@ pub struct Config {
     #[serde(default)]
     pub strategy: StrategyConfig,
     #[serde(default)]
+    pub strategies: StrategiesConfig,
+    #[serde(default)]
+    pub allocation: AllocationConfig,
+    #[serde(default)]
     pub safety: SafetyConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub positions: PositionsConfig,
+    #[serde(default)]
+    pub gas: GasConfig,
+    #[serde(default)]
+    pub secrets: SecretsConfig,
+    #[serde(default)]
+    pub reconciliation: ReconciliationConfig,
+    #[serde(default)]
+    pub orders: OrdersConfig,
+    #[serde(default)]
+    pub routing: RoutingConfig,
+    #[serde(default)]
+    pub builder: BuilderConfig,
+    #[serde(default)]
+    pub cadence: CadenceConfig,
+    #[serde(default)]
+    pub quarantine: QuarantineConfig,
+    #[serde(default)]
+    pub anomaly: AnomalyConfig,
+    #[serde(default)]
+    pub losing_streak: LosingStreakConfig,
+    #[serde(default)]
+    pub spread_history: SpreadHistoryConfig,
+    #[serde(default)]
+    pub candles: CandlesConfig,
+    #[serde(default)]
+    pub correlation: CorrelationConfig,
+    #[serde(default)]
+    pub fees: FeesConfig,
+    #[serde(default)]
+    pub polygon: PolygonConfig,
+    #[serde(default)]
+    pub solana: SolanaConfig,
+    #[serde(default)]
+    pub ctf: CtfConfig,
+    #[serde(default)]
+    pub data_source: DataSourceConfig,
+    #[serde(default)]
+    pub tui: TuiConfig,
+    #[serde(default)]
+    pub webhook: WebhookConfig,
+    #[serde(default)]
+    pub script: ScriptConfig,
+    #[serde(default)]
+    pub discovery: DiscoveryConfig,
+    #[serde(default)]
+    pub tuning: TuningConfig,
+    #[serde(default)]
+    pub watchdog: WatchdogConfig,
+    #[serde(default)]
+    pub alerts: AlertsConfig,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -24,6 +97,51 @@ pub struct PermissionConfig {
     pub daily_limit_usdc: f64,
     pub duration_days: u32,
     pub token: String,
+    /// Daily limits for additional collateral tokens (e.g. "USDC.e")
+    #[serde(default)]
+    pub token_limits: HashMap<String, f64>,
+    /// JSON permission grant to load at startup (headless runs)
+    #[serde(default)]
+    pub grant_file: String,
+    /// Environment variable holding a JSON grant (wins over `grant_file`)
+    #[serde(default = "default_grant_env")]
+    pub grant_env: String,
+    /// Seconds before the grant expires to send a renewal warning, per horizon
+    #[serde(default = "default_expiry_warning_secs")]
+    pub expiry_warning_secs: Vec<u64>,
+}
+
+fn default_grant_env() -> String {
+    "POLYSHARK_PERMISSION_GRANT".to_string()
+}
+
+fn default_expiry_warning_secs() -> Vec<u64> {
+    vec![86_400, 3_600]
+}
+
+impl PermissionConfig {
+    /// Widest warning horizon; the API flags grants expiring within it
+    pub fn expiry_warning_window(&self) -> u64 {
+        self.expiry_warning_secs.iter().copied().max().unwrap_or(0)
+    }
+}
+
+/// An additional smart account with its own permission grant
+#[derive(Debug, Deserialize, Clone)]
+pub struct AccountConfig {
+    /// Name the account is reported under
+    pub name: String,
+    /// Daily limit of its grant, added to the local spend cap
+    pub daily_limit_usdc: f64,
+    /// JSON permission grant for the account
+    #[serde(default)]
+    pub grant_file: String,
+    /// Environment variable holding its JSON grant (wins over `grant_file`)
+    #[serde(default)]
+    pub grant_env: String,
+    /// Cap on the cost of positions held through the account (0 = no cap)
+    #[serde(default)]
+    pub max_exposure: f64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -31,23 +149,102 @@ pub struct TradingConfig {
     pub min_spread_threshold: f64,
     pub min_profit_threshold: f64,
     pub trade_size: f64,
+    /// Whether `trade_size` is "shares" per leg or USDC "notional" per leg
+    #[serde(default)]
+    pub sizing: SizingBasis,
+    /// Cap on net directional exposure in USDC (0 disables)
     pub max_position_value: f64,
+    /// How aggressively competing arbitrageurs take top-of-book (0 = none)
+    #[serde(default)]
+    pub competitor_intensity: f64,
+    /// Competitors per second racing for each detected edge (0 = none)
+    #[serde(default)]
+    pub competitor_arrival_rate: f64,
+    /// Price increment for markets that don't report one
+    #[serde(default = "default_tick_size")]
+    pub tick_size: f64,
+    /// Minimum order size (shares) for markets that don't report one
+    #[serde(default = "default_min_order_size")]
+    pub min_order_size: f64,
+    /// Worst walked price accepted, in basis points from the price the
+    /// signal saw; larger orders are cut down to fit (0 disables)
+    #[serde(default)]
+    pub max_slippage_bps: f64,
+    /// Skip entries on markets with less USDC on the books than this (0 disables)
+    #[serde(default)]
+    pub min_liquidity: f64,
+    /// Skip entries on markets that traded less than this in 24h (0 disables)
+    #[serde(default)]
+    pub min_volume_24hr: f64,
+    /// Largest share of a market's liquidity one leg may cost (0 disables)
+    #[serde(default)]
+    pub max_liquidity_fraction: f64,
+    /// Fill signals on paper against live books without spending allowance
+    #[serde(default)]
+    pub shadow_mode: bool,
+    /// Fabricate demo trades when no signals are found (hackathon demos only)
+    #[serde(default)]
+    pub demo_mode: bool,
+}
+
+fn default_tick_size() -> f64 {
+    0.01
+}
+
+fn default_min_order_size() -> f64 {
+    5.0
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct TimingConfig {
+    /// Retry delay after a failed market fetch
     pub poll_interval_secs: u64,
-    pub position_timeout_secs: u64,
+    /// How often the supervisor re-discovers markets
+    #[serde(default = "default_discovery_interval_secs")]
+    pub discovery_interval_secs: u64,
     pub latency_base_ms: u64,
     pub adverse_selection_std: f64,
 }
 
+fn default_discovery_interval_secs() -> u64 {
+    60
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct ApiConfig {
     pub gamma_url: String,
     pub clob_url: String,
     pub websocket_url: String,
     pub market_limit: u32,
+    /// Maintain local order books from the WebSocket market channel
+    #[serde(default)]
+    pub stream_books: bool,
+    /// Recent trades kept per token on the trade tape
+    #[serde(default = "default_tape_size")]
+    pub tape_size: usize,
+    /// Stream order acknowledgements and fills from the CLOB user channel
+    #[serde(default)]
+    pub stream_user: bool,
+    /// Seconds without any WebSocket frame before reconnecting
+    #[serde(default = "default_ws_idle_timeout_secs")]
+    pub ws_idle_timeout_secs: u64,
+    /// Run without the API server and dashboard (also `--headless`)
+    #[serde(default)]
+    pub headless: bool,
+    /// Serve only read routes, refusing every control route (public dashboards)
+    #[serde(default)]
+    pub read_only: bool,
+    /// Price hydration for the scan: "books" (default) or "midpoints" (bulk, light)
+    #[serde(default)]
+    pub hydration: HydrationMode,
+}
+
+fn default_tape_size() -> usize {
+    200
+}
+
+fn default_ws_idle_timeout_secs() -> u64 {
+    15
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -69,6 +266,9 @@ pub struct StrategyConfig {
     pub normal_min_edge: f64,
     /// Minimum edge required in aggressive mode
     pub aggressive_min_edge: f64,
+    /// Per-category or per-slug parameters; the first match wins
+    #[serde(default)]
+    pub overrides: Vec<StrategyOverride>,
 }
 
 impl Default for StrategyConfig {
@@ -79,10 +279,326 @@ impl Default for StrategyConfig {
             conservative_min_edge: 0.05,
             normal_min_edge: 0.02,
             aggressive_min_edge: 0.01,
+            overrides: Vec::new(),
+        }
+    }
+}
+
+impl StrategyConfig {
+    /// First override matching the market, if any
+    pub fn override_for(&self, market: &Market) -> Option<&StrategyOverride> {
+        self.overrides.iter().find(|o| o.matches(market))
+    }
+
+    /// Check every override's size and min edges
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.overrides
+            .iter()
+            .try_for_each(StrategyOverride::validate)
+    }
+
+    /// Thresholds for a market, with its override's min edges applied
+    pub fn for_market(&self, market: &Market) -> StrategyConfig {
+        let mut config = self.clone();
+        config.overrides.clear();
+        if let Some(o) = self.override_for(market) {
+            config.conservative_min_edge = o
+                .conservative_min_edge
+                .unwrap_or(config.conservative_min_edge);
+            config.normal_min_edge = o.normal_min_edge.unwrap_or(config.normal_min_edge);
+            config.aggressive_min_edge =
+                o.aggressive_min_edge.unwrap_or(config.aggressive_min_edge);
+        }
+        config
+    }
+}
+
+/// Strategy parameters the dashboard can change while the agent runs
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StrategyParams {
+    pub conservative_threshold: f64,
+    pub aggressive_threshold: f64,
+    pub conservative_min_edge: f64,
+    pub normal_min_edge: f64,
+    pub aggressive_min_edge: f64,
+    pub trade_size: f64,
+    pub min_spread_threshold: f64,
+    pub min_profit_threshold: f64,
+}
+
+/// A partial update to `StrategyParams`; unset fields keep their value
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StrategyPatch {
+    pub conservative_threshold: Option<f64>,
+    pub aggressive_threshold: Option<f64>,
+    pub conservative_min_edge: Option<f64>,
+    pub normal_min_edge: Option<f64>,
+    pub aggressive_min_edge: Option<f64>,
+    pub trade_size: Option<f64>,
+    pub min_spread_threshold: Option<f64>,
+    pub min_profit_threshold: Option<f64>,
+}
+
+impl StrategyParams {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            conservative_threshold: config.strategy.conservative_threshold,
+            aggressive_threshold: config.strategy.aggressive_threshold,
+            conservative_min_edge: config.strategy.conservative_min_edge,
+            normal_min_edge: config.strategy.normal_min_edge,
+            aggressive_min_edge: config.strategy.aggressive_min_edge,
+            trade_size: config.trading.trade_size,
+            min_spread_threshold: config.trading.min_spread_threshold,
+            min_profit_threshold: config.trading.min_profit_threshold,
+        }
+    }
+
+    /// `base` with these thresholds and min edges (overrides kept)
+    pub fn strategy(&self, base: &StrategyConfig) -> StrategyConfig {
+        StrategyConfig {
+            conservative_threshold: self.conservative_threshold,
+            aggressive_threshold: self.aggressive_threshold,
+            conservative_min_edge: self.conservative_min_edge,
+            normal_min_edge: self.normal_min_edge,
+            aggressive_min_edge: self.aggressive_min_edge,
+            overrides: base.overrides.clone(),
+        }
+    }
+
+    /// These parameters with `patch` applied, if the result is in bounds
+    pub fn patched(&self, patch: &StrategyPatch) -> Result<Self, ConfigError> {
+        let params = Self {
+            conservative_threshold: patch
+                .conservative_threshold
+                .unwrap_or(self.conservative_threshold),
+            aggressive_threshold: patch
+                .aggressive_threshold
+                .unwrap_or(self.aggressive_threshold),
+            conservative_min_edge: patch
+                .conservative_min_edge
+                .unwrap_or(self.conservative_min_edge),
+            normal_min_edge: patch.normal_min_edge.unwrap_or(self.normal_min_edge),
+            aggressive_min_edge: patch
+                .aggressive_min_edge
+                .unwrap_or(self.aggressive_min_edge),
+            trade_size: patch.trade_size.unwrap_or(self.trade_size),
+            min_spread_threshold: patch
+                .min_spread_threshold
+                .unwrap_or(self.min_spread_threshold),
+            min_profit_threshold: patch
+                .min_profit_threshold
+                .unwrap_or(self.min_profit_threshold),
+        };
+        params.validate()?;
+        Ok(params)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        fraction("conservative_threshold", self.conservative_threshold)?;
+        fraction("aggressive_threshold", self.aggressive_threshold)?;
+        fraction("conservative_min_edge", self.conservative_min_edge)?;
+        fraction("normal_min_edge", self.normal_min_edge)?;
+        fraction("aggressive_min_edge", self.aggressive_min_edge)?;
+        fraction("min_spread_threshold", self.min_spread_threshold)?;
+        if self.conservative_threshold > self.aggressive_threshold {
+            return Err(ConfigError::Invalid(
+                "conservative_threshold must not exceed aggressive_threshold".to_string(),
+            ));
+        }
+        if !(self.aggressive_min_edge <= self.normal_min_edge
+            && self.normal_min_edge <= self.conservative_min_edge)
+        {
+            return Err(ConfigError::Invalid(
+                "min edges must rise from aggressive to normal to conservative".to_string(),
+            ));
+        }
+        positive("trade_size", self.trade_size)?;
+        if !(self.min_profit_threshold >= 0.0 && self.min_profit_threshold.is_finite()) {
+            return Err(ConfigError::Invalid(format!(
+                "min_profit_threshold must not be negative, got {}",
+                self.min_profit_threshold
+            )));
         }
+        Ok(())
     }
 }
 
+/// `value` must lie in [0, 1]
+fn fraction(name: &str, value: f64) -> Result<(), ConfigError> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid(format!(
+            "{} must be between 0 and 1, got {}",
+            name, value
+        )))
+    }
+}
+
+/// `value` must be positive and finite
+fn positive(name: &str, value: f64) -> Result<(), ConfigError> {
+    if value > 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid(format!(
+            "{} must be positive, got {}",
+            name, value
+        )))
+    }
+}
+
+/// Strategy parameters for markets of one category or slug pattern
+///
+/// Unset parameters fall back to the global values.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct StrategyOverride {
+    /// Gamma category, case-insensitive (empty: any)
+    #[serde(default)]
+    pub category: String,
+    /// Slug pattern where `*` matches any run of characters (empty: any)
+    #[serde(default)]
+    pub slug: String,
+    pub conservative_min_edge: Option<f64>,
+    pub normal_min_edge: Option<f64>,
+    pub aggressive_min_edge: Option<f64>,
+    /// Size per leg, replacing `trading.trade_size`
+    pub trade_size: Option<f64>,
+    /// Hold limit, replacing `positions.max_hold_time_secs` and any
+    /// category override of it
+    pub position_timeout_secs: Option<u64>,
+}
+
+impl StrategyOverride {
+    fn validate(&self) -> Result<(), ConfigError> {
+        let name = |field: &str| {
+            format!(
+                "override (category {:?}, slug {:?}) {}",
+                self.category, self.slug, field
+            )
+        };
+        let edges = [
+            ("conservative_min_edge", self.conservative_min_edge),
+            ("normal_min_edge", self.normal_min_edge),
+            ("aggressive_min_edge", self.aggressive_min_edge),
+        ];
+        for (field, edge) in edges {
+            if let Some(edge) = edge {
+                fraction(&name(field), edge)?;
+            }
+        }
+        if let Some(size) = self.trade_size {
+            positive(&name("trade_size"), size)?;
+        }
+        Ok(())
+    }
+
+    /// Whether the override applies; one with no category or slug never does
+    pub fn matches(&self, market: &Market) -> bool {
+        if self.category.is_empty() && self.slug.is_empty() {
+            return false;
+        }
+        (self.category.is_empty() || self.category.eq_ignore_ascii_case(&market.category))
+            && (self.slug.is_empty() || glob_match(&self.slug, &market.slug))
+    }
+}
+
+/// Match `text` against a pattern where `*` stands for any run of characters
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+    if text.len() < first.len() + last.len() || !text.starts_with(first) || !text.ends_with(last) {
+        return false;
+    }
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Trading strategies and their enable flags
+#[derive(Debug, Deserialize, Clone)]
+pub struct StrategiesConfig {
+    /// Sum-to-one arbitrage across binary outcomes
+    pub pure_arb: StrategyToggle,
+    /// Statistical entry on unusually wide spreads
+    #[serde(default)]
+    pub mean_reversion: MeanReversionConfig,
+    /// Signals posted to POST /api/signals/external
+    #[serde(default)]
+    pub external: ExternalSignalsConfig,
+}
+
+impl Default for StrategiesConfig {
+    fn default() -> Self {
+        Self {
+            pure_arb: StrategyToggle { enabled: true },
+            mean_reversion: MeanReversionConfig::default(),
+            external: ExternalSignalsConfig::default(),
+        }
+    }
+}
+
+/// Mean-reversion entry strategy settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct MeanReversionConfig {
+    pub enabled: bool,
+    /// Spread samples (ticks) per market used for the rolling mean
+    pub window: usize,
+    /// Spread z-score required to enter
+    pub z_threshold: f64,
+}
+
+impl Default for MeanReversionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: 30,
+            z_threshold: 2.0,
+        }
+    }
+}
+
+/// Third-party signal ingestion settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct ExternalSignalsConfig {
+    pub enabled: bool,
+    /// Signals older than this when their market is scanned are dropped
+    pub ttl_secs: u64,
+}
+
+impl Default for ExternalSignalsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: 60,
+        }
+    }
+}
+
+/// Per-strategy enable flag
+#[derive(Debug, Deserialize, Clone)]
+pub struct StrategyToggle {
+    pub enabled: bool,
+}
+
+/// Daily allowance split across strategies
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct AllocationConfig {
+    /// "fixed" weights or "performance"-weighted
+    pub mode: AllocationMode,
+    /// Base weight per strategy name (unlisted strategies weigh 1.0)
+    #[serde(default)]
+    pub weights: HashMap<String, f64>,
+}
+
 /// Safety configuration for failure handling
 #[derive(Debug, Deserialize, Clone)]
 pub struct SafetyConfig {
@@ -94,6 +610,34 @@ pub struct SafetyConfig {
     pub safe_mode_cooldown_secs: u64,
     /// Assume zero allowance if permission query fails
     pub assume_zero_on_perm_error: bool,
+    /// Suspend trading while any endpoint's p99 latency exceeds this (ms)
+    #[serde(default = "default_max_latency_p99_ms")]
+    pub max_latency_p99_ms: u64,
+    /// Signals older than this (ms) at execution only trade with a high edge (0 disables)
+    #[serde(default = "default_max_signal_age_ms")]
+    pub max_signal_age_ms: u64,
+    /// Edge at which a signal still executes after exceeding `max_signal_age_ms`
+    #[serde(default = "default_stale_signal_min_edge")]
+    pub stale_signal_min_edge: f64,
+    /// Trades all workers together may execute per discovery cycle, counted
+    /// as they clear risk checks (0 disables)
+    #[serde(default)]
+    pub max_trades_per_cycle: usize,
+    /// Trades executed in any rolling minute (0 disables)
+    #[serde(default)]
+    pub max_trades_per_minute: usize,
+}
+
+fn default_max_latency_p99_ms() -> u64 {
+    3000
+}
+
+fn default_max_signal_age_ms() -> u64 {
+    1000
+}
+
+fn default_stale_signal_min_edge() -> f64 {
+    0.05
 }
 
 impl Default for SafetyConfig {
@@ -103,6 +647,738 @@ impl Default for SafetyConfig {
             max_consecutive_failures: 3,
             safe_mode_cooldown_secs: 300,
             assume_zero_on_perm_error: true,
+            max_latency_p99_ms: default_max_latency_p99_ms(),
+            max_signal_age_ms: default_max_signal_age_ms(),
+            stale_signal_min_edge: default_stale_signal_min_edge(),
+            max_trades_per_cycle: 0,
+            max_trades_per_minute: 0,
+        }
+    }
+}
+
+/// Position exit configuration
+#[derive(Debug, Deserialize, Clone)]
+pub struct PositionsConfig {
+    /// What takes profit: "spread" (`profit_target_spread`) or "break_even"
+    /// (entry plus round-trip fees plus `break_even_buffer`)
+    #[serde(default)]
+    pub profit_target: ProfitTarget,
+    /// Spread below which a position has reverted and is closed
+    #[serde(default = "default_profit_target_spread")]
+    pub profit_target_spread: f64,
+    /// Profit per share over round-trip fees a break-even exit waits for
+    #[serde(default = "default_break_even_buffer")]
+    pub break_even_buffer: f64,
+    /// Spread widening from entry that stops a position out
+    #[serde(default = "default_stop_loss_spread")]
+    pub stop_loss_spread: f64,
+    /// Longest a position is held before it is closed
+    #[serde(default = "default_max_hold_time_secs")]
+    pub max_hold_time_secs: u64,
+    /// Spread narrowing from entry that arms the trailing stop (0 disables)
+    pub trailing_stop_activation: f64,
+    /// Spread re-widening from its best level that triggers the trailing stop
+    pub trailing_stop_distance: f64,
+    /// Fraction of a position closed once its spread has half-reverted (0 disables)
+    pub scale_out_fraction: f64,
+    /// Handling of repeated entries on a token already held ("merge" or "reject")
+    pub duplicate_entry: DuplicateEntryPolicy,
+    /// Exit limits by Gamma category (case-insensitive), replacing the ones above
+    #[serde(default)]
+    pub categories: HashMap<String, ExitOverride>,
+}
+
+fn default_profit_target_spread() -> f64 {
+    0.005
+}
+
+fn default_break_even_buffer() -> f64 {
+    0.005
+}
+
+fn default_stop_loss_spread() -> f64 {
+    0.02
+}
+
+fn default_max_hold_time_secs() -> u64 {
+    3600
+}
+
+impl Default for PositionsConfig {
+    fn default() -> Self {
+        Self {
+            profit_target: ProfitTarget::Spread,
+            profit_target_spread: default_profit_target_spread(),
+            break_even_buffer: default_break_even_buffer(),
+            stop_loss_spread: default_stop_loss_spread(),
+            max_hold_time_secs: default_max_hold_time_secs(),
+            trailing_stop_activation: 0.0,
+            trailing_stop_distance: 0.005,
+            scale_out_fraction: 0.0,
+            duplicate_entry: DuplicateEntryPolicy::Merge,
+            categories: HashMap::new(),
+        }
+    }
+}
+
+impl PositionsConfig {
+    /// Check that a scale-out, when enabled, leaves part of the position open
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let fraction = self.scale_out_fraction;
+        if fraction == 0.0 || (fraction > 0.0 && fraction < 1.0) {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(format!(
+                "scale_out_fraction must be between 0 and 1 (exclusive, 0 disables), got {}",
+                fraction
+            )))
+        }
+    }
+
+    /// Exit limits for positions outside any category override
+    pub fn exits(&self) -> ExitLimits {
+        ExitLimits {
+            profit_target_spread: self.profit_target_spread,
+            stop_loss_spread: self.stop_loss_spread,
+            max_hold_time: self.max_hold_time_secs,
+        }
+    }
+
+    /// Exit limits for positions in a market of `category`
+    pub fn exits_for(&self, category: &str) -> ExitLimits {
+        let exits = self.exits();
+        let Some(o) = self
+            .categories
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(category))
+            .map(|(_, o)| o)
+        else {
+            return exits;
+        };
+        ExitLimits {
+            profit_target_spread: o.profit_target_spread.unwrap_or(exits.profit_target_spread),
+            stop_loss_spread: o.stop_loss_spread.unwrap_or(exits.stop_loss_spread),
+            max_hold_time: o.max_hold_time_secs.unwrap_or(exits.max_hold_time),
+        }
+    }
+}
+
+/// Exit limits for one category; unset limits keep the global value
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ExitOverride {
+    pub profit_target_spread: Option<f64>,
+    pub stop_loss_spread: Option<f64>,
+    pub max_hold_time_secs: Option<u64>,
+}
+
+/// Gas/relayer cost configuration
+#[derive(Debug, Deserialize, Clone)]
+pub struct GasConfig {
+    /// Chain that trades settle on
+    pub chain: String,
+    /// Cost per transaction in USDC, by chain
+    pub cost_per_tx: HashMap<String, f64>,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        let mut cost_per_tx = HashMap::new();
+        cost_per_tx.insert("polygon".to_string(), 0.01);
+        cost_per_tx.insert("solana".to_string(), 0.0005);
+        Self {
+            chain: "polygon".to_string(),
+            cost_per_tx,
+        }
+    }
+}
+
+/// Polygon RPC for on-chain balance checks
+#[derive(Debug, Deserialize, Clone)]
+pub struct PolygonConfig {
+    /// JSON-RPC endpoint (empty disables balance checks)
+    pub rpc_url: String,
+    /// Trading (smart) account; empty uses the EOA from the secret store.
+    /// With a proxy signature type, the proxy wallet that funds orders
+    pub account: String,
+    /// How orders are signed: "eoa", or "poly_proxy" / "poly_gnosis_safe"
+    /// when the EOA signs for a Polymarket proxy wallet
+    #[serde(default)]
+    pub signature_type: SignatureType,
+    /// USDC collateral token
+    pub usdc_address: String,
+    /// CTF exchange that pulls USDC on fills
+    pub exchange_address: String,
+    /// How often to refresh balance and allowance
+    pub refresh_secs: u64,
+}
+
+impl Default for PolygonConfig {
+    fn default() -> Self {
+        Self {
+            rpc_url: "https://polygon-rpc.com".to_string(),
+            account: String::new(),
+            signature_type: SignatureType::Eoa,
+            usdc_address: "0x2791Bca1f2de4661ED88A7Fc5317b2dB0CaFe0d0".to_string(),
+            exchange_address: "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E".to_string(),
+            refresh_secs: 30,
+        }
+    }
+}
+
+/// Solana RPC, transaction fees, and the market program
+#[derive(Debug, Deserialize, Clone)]
+pub struct SolanaConfig {
+    /// JSON-RPC endpoint
+    pub rpc_url: String,
+    /// Commitment waited for on reads and submitted transactions
+    pub commitment: Commitment,
+    /// Priority fee per compute unit in micro-lamports (0 pays none)
+    pub priority_fee_micro_lamports: u64,
+    /// Compute units requested per transaction (0 keeps the runtime default)
+    pub compute_unit_limit: u32,
+    /// Give up on an RPC call (confirmation included) after this long
+    pub timeout_secs: u64,
+    /// SOL the agent may spend on transaction fees per day (0 = unlimited)
+    pub daily_limit_sol: f64,
+    /// How often to refresh the wallet balance
+    pub refresh_secs: u64,
+    /// Binary-market program to read markets from and trade on (empty disables)
+    #[serde(default)]
+    pub market_program: String,
+}
+
+impl Default for SolanaConfig {
+    fn default() -> Self {
+        Self {
+            rpc_url: "https://api.devnet.solana.com".to_string(),
+            commitment: Commitment::Confirmed,
+            priority_fee_micro_lamports: 0,
+            compute_unit_limit: 0,
+            timeout_secs: 30,
+            daily_limit_sol: 0.05,
+            refresh_secs: 30,
+            market_program: String::new(),
+        }
+    }
+}
+
+/// Stream health thresholds for the REST fallback
+#[derive(Debug, Deserialize, Clone)]
+pub struct DataSourceConfig {
+    /// Stream counts as unhealthy after this long without a frame
+    pub stale_after_secs: u64,
+    /// Healthy time required before switching back from REST
+    pub recover_after_secs: u64,
+    /// How often stream health is checked
+    pub check_interval_ms: u64,
+}
+
+impl Default for DataSourceConfig {
+    fn default() -> Self {
+        Self {
+            stale_after_secs: 10,
+            recover_after_secs: 5,
+            check_interval_ms: 500,
+        }
+    }
+}
+
+/// Heartbeat watchdog for the trading loop
+#[derive(Debug, Deserialize, Clone)]
+pub struct WatchdogConfig {
+    /// Watch the trading loop for missed heartbeats
+    pub enabled: bool,
+    /// Loop counts as stalled after this long without a heartbeat
+    pub timeout_secs: u64,
+    /// How often the heartbeat is checked
+    pub check_interval_secs: u64,
+    /// Abort and respawn a stalled loop (otherwise only alert; a panicked
+    /// loop is always respawned)
+    pub restart: bool,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timeout_secs: 120,
+            check_interval_secs: 5,
+            restart: false,
+        }
+    }
+}
+
+/// User-defined alert rules, checked every tick
+#[derive(Debug, Deserialize, Clone)]
+pub struct AlertsConfig {
+    /// Minimum seconds between two alerts from the same rule
+    pub cooldown_secs: u64,
+    #[serde(default)]
+    pub rules: Vec<AlertRule>,
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            cooldown_secs: 900,
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Terminal UI settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct TuiConfig {
+    /// Draw the terminal UI instead of plain logs (also `--tui`)
+    pub enabled: bool,
+    /// Redraw interval
+    pub refresh_ms: u64,
+    /// Agent output is written here while the UI owns the terminal
+    pub log_file: String,
+    /// Lines kept in the event log pane
+    pub log_lines: usize,
+}
+
+impl Default for TuiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            refresh_ms: 250,
+            log_file: "data/agent.log".to_string(),
+            log_lines: 500,
+        }
+    }
+}
+
+/// Outbound webhook for signals and executions
+#[derive(Debug, Deserialize, Clone)]
+pub struct WebhookConfig {
+    /// Endpoint every signal and execution is POSTed to (empty disables)
+    pub url: String,
+    /// Per-request timeout
+    pub timeout_ms: u64,
+    /// Events buffered while the endpoint is slow; newer ones are dropped past this
+    pub queue_size: usize,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            timeout_ms: 5000,
+            queue_size: 1000,
+        }
+    }
+}
+
+/// Min-edge auto-tuning from realized outcomes
+#[derive(Debug, Deserialize, Clone)]
+pub struct TuningConfig {
+    /// Adjust the strategy-mode min edges as trades close
+    pub enabled: bool,
+    /// Closed trades considered
+    pub window: usize,
+    /// Width of the edge bucket just above each threshold
+    pub bucket_width: f64,
+    /// Trades a bucket needs before its threshold moves
+    pub min_bucket_trades: usize,
+    /// Marginal trades winning less often than this raise the threshold
+    pub target_win_rate: f64,
+    /// Change per adjustment
+    pub step: f64,
+    /// Lowest min edge tuning may set
+    pub min_edge_floor: f64,
+    /// Highest min edge tuning may set
+    pub min_edge_cap: f64,
+}
+
+impl Default for TuningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: 100,
+            bucket_width: 0.01,
+            min_bucket_trades: 5,
+            target_win_rate: 0.5,
+            step: 0.0025,
+            min_edge_floor: 0.005,
+            min_edge_cap: 0.10,
+        }
+    }
+}
+
+/// Alerts for markets listed since the previous fetch
+#[derive(Debug, Deserialize, Clone)]
+pub struct DiscoveryConfig {
+    /// Announce newly listed markets that pass the trading filters (log and webhook)
+    pub alerts: bool,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self { alerts: true }
+    }
+}
+
+/// User-supplied trade filter script
+#[derive(Debug, Deserialize, Clone)]
+pub struct ScriptConfig {
+    /// rhai script run on every intent (empty disables)
+    pub path: String,
+    /// Operation budget per run; scripts exceeding it reject the intent
+    pub max_operations: u64,
+}
+
+impl Default for ScriptConfig {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            max_operations: 100_000,
+        }
+    }
+}
+
+/// Conditional Token Framework settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct CtfConfig {
+    /// Merge both legs of a filled bundle back into USDC right away
+    pub merge_bundles: bool,
+    /// Redeem resolved markets on-chain (otherwise settled on paper)
+    pub redeem_resolved: bool,
+    /// Conditional Tokens contract
+    pub ctf_address: String,
+    /// Gas limit for merge and redeem transactions
+    pub gas_limit: u64,
+}
+
+impl Default for CtfConfig {
+    fn default() -> Self {
+        Self {
+            merge_bundles: false,
+            redeem_resolved: false,
+            ctf_address: "0x4D97DCd97eC945f40cF65F87097ACe5EA0476045".to_string(),
+            gas_limit: 300_000,
+        }
+    }
+}
+
+/// Fee schedule for one venue
+#[derive(Debug, Deserialize, Clone)]
+pub struct VenueFees {
+    pub maker_fee_bps: u32,
+    /// Base taker fee, before volume tiers
+    pub taker_fee_bps: u32,
+    #[serde(default)]
+    pub maker_rebate_bps: u32,
+    /// Taker fees by 30-day traded volume
+    #[serde(default)]
+    pub taker_tiers: Vec<FeeTier>,
+}
+
+/// Fee schedules, by venue
+#[derive(Debug, Deserialize, Clone)]
+pub struct FeesConfig {
+    /// Venue whose schedule is applied
+    pub venue: String,
+    pub venues: HashMap<String, VenueFees>,
+    /// Taker fee in bps by market id or slug pattern (`*` wildcard),
+    /// replacing the schedule (e.g. promotional fee-free markets)
+    #[serde(default)]
+    pub overrides: HashMap<String, u32>,
+}
+
+impl Default for FeesConfig {
+    fn default() -> Self {
+        let mut venues = HashMap::new();
+        venues.insert(
+            "polymarket".to_string(),
+            VenueFees {
+                maker_fee_bps: 0,
+                taker_fee_bps: 200,
+                maker_rebate_bps: 0,
+                taker_tiers: Vec::new(),
+            },
+        );
+        Self {
+            venue: "polymarket".to_string(),
+            venues,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+/// Secrets configuration
+///
+/// Only selects where keys and credentials are loaded from; the secrets
+/// themselves never live in config.toml.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SecretsConfig {
+    /// Secret source: "env", "keystore", or "keyring"
+    pub source: SecretSource,
+    /// Path to the encrypted keystore file (keystore source)
+    pub keystore_path: String,
+    /// Env var holding the keystore passphrase (keystore source)
+    pub passphrase_env: String,
+    /// Encrypted JSON (V3) keystore holding the EVM key; empty loads the key
+    /// from the source like any other secret
+    #[serde(default)]
+    pub evm_keystore_path: String,
+    /// Env var holding its passphrase; prompted for at startup when unset
+    #[serde(default = "default_evm_keystore_passphrase_env")]
+    pub evm_keystore_passphrase_env: String,
+}
+
+fn default_evm_keystore_passphrase_env() -> String {
+    "POLYSHARK_EVM_KEYSTORE_PASSPHRASE".to_string()
+}
+
+impl Default for SecretsConfig {
+    fn default() -> Self {
+        Self {
+            source: SecretSource::Env,
+            keystore_path: "keystore.json".to_string(),
+            passphrase_env: "POLYSHARK_KEYSTORE_PASSPHRASE".to_string(),
+            evm_keystore_path: String::new(),
+            evm_keystore_passphrase_env: default_evm_keystore_passphrase_env(),
+        }
+    }
+}
+
+/// Post-trade reconciliation against exchange fills
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReconciliationConfig {
+    /// How often to compare local positions with exchange fills
+    pub interval_secs: u64,
+    /// Size difference treated as a match
+    pub tolerance: f64,
+    /// Overwrite local position sizes with exchange-reported sizes
+    pub auto_correct: bool,
+}
+
+impl Default for ReconciliationConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 60,
+            tolerance: 0.01,
+            auto_correct: false,
+        }
+    }
+}
+
+/// Resting order housekeeping
+#[derive(Debug, Deserialize, Clone)]
+pub struct OrdersConfig {
+    /// How often good-til-date orders are checked for expiry
+    pub expiry_sweep_secs: u64,
+    /// Distance the touch may move from a resting quote before it is
+    /// cancel-replaced (0 leaves quotes alone)
+    #[serde(default)]
+    pub requote_threshold: f64,
+    /// Sign and post live (non-shadow) orders to the CLOB; off, live trades
+    /// fill against the paper wallet only
+    #[serde(default)]
+    pub submit: bool,
+}
+
+impl Default for OrdersConfig {
+    fn default() -> Self {
+        Self {
+            expiry_sweep_secs: 5,
+            requote_threshold: 0.0,
+            submit: false,
+        }
+    }
+}
+
+/// Per-leg routing of bundle buys
+#[derive(Debug, Deserialize, Clone)]
+pub struct RoutingConfig {
+    /// Route each leg (take, rest at the bid, or split) instead of taking both
+    pub enabled: bool,
+    /// Saving per share, fees included, that justifies resting over taking
+    pub min_saving: f64,
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_saving: 0.01,
+        }
+    }
+}
+
+/// Order attribution for integrators running their own deployment
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct BuilderConfig {
+    /// Attribute posted orders to the builder whose API credentials are in
+    /// the secret store
+    pub enabled: bool,
+}
+
+/// Adaptive per-market polling cadence
+#[derive(Debug, Deserialize, Clone)]
+pub struct CadenceConfig {
+    /// Poll interval for markets with recent signals or high volatility
+    pub min_interval_secs: u64,
+    /// Poll interval for quiet markets
+    pub max_interval_secs: u64,
+    /// How long a signal keeps a market on the fastest cadence
+    pub hot_window_secs: u64,
+    /// Midpoint move per minute at which a market is polled at the fastest cadence
+    pub volatility_threshold: f64,
+    /// Order book requests per second allowed across all workers
+    pub max_book_requests_per_sec: f64,
+}
+
+impl Default for CadenceConfig {
+    fn default() -> Self {
+        Self {
+            min_interval_secs: 1,
+            max_interval_secs: 60,
+            hot_window_secs: 120,
+            volatility_threshold: 0.02,
+            max_book_requests_per_sec: 20.0,
+        }
+    }
+}
+
+/// Backoff for tokens whose book requests keep failing
+#[derive(Debug, Deserialize, Clone)]
+pub struct QuarantineConfig {
+    /// Wait after the first failure, doubled with each further failure
+    pub base_backoff_secs: u64,
+    /// Longest wait between retries
+    pub max_backoff_secs: u64,
+    /// Consecutive failures after which a token is reported quarantined
+    pub quarantine_after: u32,
+}
+
+impl Default for QuarantineConfig {
+    fn default() -> Self {
+        Self {
+            base_backoff_secs: 5,
+            max_backoff_secs: 600,
+            quarantine_after: 3,
+        }
+    }
+}
+
+/// Circuit breaker for implausible feed data
+#[derive(Debug, Deserialize, Clone)]
+pub struct AnomalyConfig {
+    /// Largest price move in one tick before the data is distrusted
+    /// (absolute, as prices run from 0 to 1)
+    pub max_jump: f64,
+    /// How long a market is held out of trading after an anomaly
+    pub cooldown_secs: u64,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            max_jump: 0.30,
+            cooldown_secs: 300,
+        }
+    }
+}
+
+/// Entry pause after consecutive losing exits
+#[derive(Debug, Deserialize, Clone)]
+pub struct LosingStreakConfig {
+    /// Losing exits in a row that pause new entries (0 disables)
+    pub max_losses: u32,
+    /// How long new entries stay paused
+    pub cooldown_secs: u64,
+}
+
+impl Default for LosingStreakConfig {
+    fn default() -> Self {
+        Self {
+            max_losses: 3,
+            cooldown_secs: 1800,
+        }
+    }
+}
+
+/// Rolling per-market spread history
+#[derive(Debug, Deserialize, Clone)]
+pub struct SpreadHistoryConfig {
+    /// Samples kept per market
+    pub capacity: usize,
+    /// Samples the entry volatility filter looks at
+    pub volatility_window: usize,
+    /// Skip entries while a market's spread std-dev exceeds this (0 disables)
+    pub max_volatility: f64,
+}
+
+impl Default for SpreadHistoryConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 720,
+            volatility_window: 30,
+            max_volatility: 0.0,
+        }
+    }
+}
+
+/// OHLC candles per token for charting
+#[derive(Debug, Deserialize, Clone)]
+pub struct CandlesConfig {
+    /// Candles kept per token and interval
+    pub capacity: usize,
+    /// Save candles with the stats and reload them at startup
+    pub persist: bool,
+}
+
+impl Default for CandlesConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 500,
+            persist: false,
+        }
+    }
+}
+
+/// Rolling correlations between markets and the correlated-exposure cap
+#[derive(Debug, Deserialize, Clone)]
+pub struct CorrelationConfig {
+    /// Candles whose returns are correlated
+    pub interval: CandleInterval,
+    /// Most recent candles per market looked at
+    pub window: usize,
+    /// Returns two markets must share before they are correlated
+    pub min_samples: usize,
+    /// Correlation at which markets count as one exposure
+    pub threshold: f64,
+    /// Cap on net exposure across correlated markets in USDC (0 = no cap)
+    pub max_exposure: f64,
+}
+
+impl Default for CorrelationConfig {
+    fn default() -> Self {
+        Self {
+            interval: CandleInterval::FiveMinutes,
+            window: 48,
+            min_samples: 12,
+            threshold: 0.7,
+            max_exposure: 0.0,
+        }
+    }
+}
+
+/// Storage configuration for persisted agent state
+#[derive(Debug, Deserialize, Clone)]
+pub struct StorageConfig {
+    /// Directory where persisted state (stats, ledgers) is written
+    pub data_dir: String,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            data_dir: "data".to_string(),
         }
     }
 }
@@ -118,7 +1394,11 @@ impl Config {
         let contents = fs::read_to_string(path)
             .map_err(|e| ConfigError::FileNotFound(path.to_string(), e.to_string()))?;
 
-        toml::from_str(&contents).map_err(|e| ConfigError::ParseError(e.to_string()))
+        let config: Self =
+            toml::from_str(&contents).map_err(|e| ConfigError::ParseError(e.to_string()))?;
+        config.strategy.validate()?;
+        config.positions.validate()?;
+        Ok(config)
     }
 
     /// Create default configuration
@@ -128,16 +1408,32 @@ impl Config {
                 daily_limit_usdc: 10.0,
                 duration_days: 30,
                 token: "USDC".to_string(),
+                token_limits: HashMap::new(),
+                grant_file: String::new(),
+                grant_env: default_grant_env(),
+                expiry_warning_secs: default_expiry_warning_secs(),
             },
+            accounts: Vec::new(),
             trading: TradingConfig {
                 min_spread_threshold: 0.02,
                 min_profit_threshold: 0.10,
                 trade_size: 5.0,
+                sizing: SizingBasis::default(),
                 max_position_value: 50.0,
+                competitor_intensity: 0.0,
+                competitor_arrival_rate: 0.0,
+                tick_size: default_tick_size(),
+                min_order_size: default_min_order_size(),
+                max_slippage_bps: 0.0,
+                min_liquidity: 0.0,
+                min_volume_24hr: 0.0,
+                max_liquidity_fraction: 0.0,
+                shadow_mode: false,
+                demo_mode: false,
             },
             timing: TimingConfig {
                 poll_interval_secs: 5,
-                position_timeout_secs: 3600,
+                discovery_interval_secs: default_discovery_interval_secs(),
                 latency_base_ms: 50,
                 adverse_selection_std: 0.001,
             },
@@ -145,6 +1441,13 @@ impl Config {
                 gamma_url: "https://gamma-api.polymarket.com/events".to_string(),
                 clob_url: "https://clob.polymarket.com".to_string(),
                 websocket_url: "wss://ws-subscriptions-clob.polymarket.com/ws".to_string(),
+                stream_books: false,
+                tape_size: default_tape_size(),
+                stream_user: false,
+                ws_idle_timeout_secs: default_ws_idle_timeout_secs(),
+                headless: false,
+                read_only: false,
+                hydration: HydrationMode::default(),
                 market_limit: 20,
             },
             logging: LoggingConfig {
@@ -152,7 +1455,36 @@ impl Config {
                 colorize: true,
             },
             strategy: StrategyConfig::default(),
+            strategies: StrategiesConfig::default(),
+            allocation: AllocationConfig::default(),
             safety: SafetyConfig::default(),
+            storage: StorageConfig::default(),
+            positions: PositionsConfig::default(),
+            gas: GasConfig::default(),
+            secrets: SecretsConfig::default(),
+            reconciliation: ReconciliationConfig::default(),
+            orders: OrdersConfig::default(),
+            routing: RoutingConfig::default(),
+            builder: BuilderConfig::default(),
+            cadence: CadenceConfig::default(),
+            quarantine: QuarantineConfig::default(),
+            anomaly: AnomalyConfig::default(),
+            losing_streak: LosingStreakConfig::default(),
+            spread_history: SpreadHistoryConfig::default(),
+            candles: CandlesConfig::default(),
+            correlation: CorrelationConfig::default(),
+            fees: FeesConfig::default(),
+            polygon: PolygonConfig::default(),
+            solana: SolanaConfig::default(),
+            ctf: CtfConfig::default(),
+            data_source: DataSourceConfig::default(),
+            tui: TuiConfig::default(),
+            webhook: WebhookConfig::default(),
+            script: ScriptConfig::default(),
+            discovery: DiscoveryConfig::default(),
+            tuning: TuningConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            alerts: AlertsConfig::default(),
         }
     }
 }
@@ -161,6 +1493,8 @@ impl Config {
 pub enum ConfigError {
     FileNotFound(String, String),
     ParseError(String),
+    /// A value outside its allowed range
+    Invalid(String),
 }
 
 impl std::fmt::Display for ConfigError {
@@ -168,6 +1502,7 @@ impl std::fmt::Display for ConfigError {
         match self {
             Self::FileNotFound(path, err) => write!(f, "Config file not found: {} ({})", path, err),
             Self::ParseError(err) => write!(f, "Config parse error: {}", err),
+            Self::Invalid(err) => write!(f, "Invalid config: {}", err),
         }
     }
 }
@@ -177,6 +1512,7 @@ impl std::error::Error for ConfigError {}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util;
 
     #[test]
     fn test_default_config() {
@@ -184,4 +1520,165 @@ mod tests {
         assert_eq!(config.permission.daily_limit_usdc, 10.0);
         assert_eq!(config.trading.min_spread_threshold, 0.02);
     }
+
+    #[test]
+    fn test_shipped_config_parses() {
+        let config = Config::load_from(concat!(env!("CARGO_MANIFEST_DIR"), "/config.toml"))
+            .expect("config.toml should parse");
+        assert_eq!(config.permission.token, "USDC");
+        assert_eq!(config.permission.token_limits.get("USDC.e"), Some(&5.0));
+    }
+
+    #[test]
+    fn test_position_exits_by_category() {
+        let positions: PositionsConfig = toml::from_str(
+            r#"
+            stop_loss_spread = 0.03
+            trailing_stop_activation = 0.0
+            trailing_stop_distance = 0.005
+            scale_out_fraction = 0.0
+            duplicate_entry = "merge"
+
+            [categories]
+            Sports = { stop_loss_spread = 0.01, max_hold_time_secs = 900 }
+            "#,
+        )
+        .unwrap();
+        let global = positions.exits();
+        assert_eq!(global.profit_target_spread, 0.005);
+        assert_eq!(global.stop_loss_spread, 0.03);
+        assert_eq!(global.max_hold_time, 3600);
+
+        let sports = positions.exits_for("sports");
+        assert_eq!(sports.profit_target_spread, 0.005);
+        assert_eq!(sports.stop_loss_spread, 0.01);
+        assert_eq!(sports.max_hold_time, 900);
+        assert_eq!(positions.exits_for("politics"), global);
+    }
+
+    #[test]
+    fn test_strategy_patch_bounds() {
+        let params = StrategyParams::from_config(&Config::default_config());
+
+        let patched = params
+            .patched(&StrategyPatch {
+                normal_min_edge: Some(0.03),
+                trade_size: Some(10.0),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(patched.normal_min_edge, 0.03);
+        assert_eq!(patched.trade_size, 10.0);
+        assert_eq!(patched.aggressive_min_edge, params.aggressive_min_edge);
+
+        let rejected = [
+            StrategyPatch {
+                trade_size: Some(0.0),
+                ..Default::default()
+            },
+            StrategyPatch {
+                min_spread_threshold: Some(1.5),
+                ..Default::default()
+            },
+            StrategyPatch {
+                normal_min_edge: Some(0.5),
+                ..Default::default()
+            },
+            StrategyPatch {
+                conservative_threshold: Some(0.9),
+                ..Default::default()
+            },
+        ];
+        for patch in &rejected {
+            assert!(params.patched(patch).is_err(), "{:?}", patch);
+        }
+    }
+
+    #[test]
+    fn test_strategy_overrides() {
+        let strategy: StrategyConfig = toml::from_str(
+            r#"
+            conservative_threshold = 0.3
+            aggressive_threshold = 0.7
+            conservative_min_edge = 0.05
+            normal_min_edge = 0.02
+            aggressive_min_edge = 0.01
+
+            [[overrides]]
+            category = "sports"
+            normal_min_edge = 0.04
+            trade_size = 2.0
+
+            [[overrides]]
+            slug = "*-election-*"
+            position_timeout_secs = 3600
+            "#,
+        )
+        .unwrap();
+
+        let mut market = Market {
+            category: "Sports".to_string(),
+            ..test_util::market(0.5, 0.5)
+        };
+        let sports = strategy.for_market(&market);
+        assert_eq!(sports.normal_min_edge, 0.04);
+        assert_eq!(sports.aggressive_min_edge, 0.01);
+        assert_eq!(
+            strategy.override_for(&market).unwrap().trade_size,
+            Some(2.0)
+        );
+
+        market.category = "Politics".to_string();
+        market.slug = "us-election-2028-winner".to_string();
+        let politics = strategy.override_for(&market).unwrap();
+        assert_eq!(politics.position_timeout_secs, Some(3600));
+        assert_eq!(strategy.for_market(&market).normal_min_edge, 0.02);
+
+        market.slug = "election-2028".to_string();
+        assert!(strategy.override_for(&market).is_none());
+        assert!(strategy.validate().is_ok());
+
+        let invalid = [
+            StrategyOverride {
+                trade_size: Some(0.0),
+                ..StrategyOverride::default()
+            },
+            StrategyOverride {
+                trade_size: Some(-2.0),
+                ..StrategyOverride::default()
+            },
+            StrategyOverride {
+                trade_size: Some(f64::INFINITY),
+                ..StrategyOverride::default()
+            },
+            StrategyOverride {
+                normal_min_edge: Some(1.5),
+                ..StrategyOverride::default()
+            },
+            StrategyOverride {
+                aggressive_min_edge: Some(-0.01),
+                ..StrategyOverride::default()
+            },
+        ];
+        for o in invalid {
+            let config = StrategyConfig {
+                overrides: vec![o.clone()],
+                ..StrategyConfig::default()
+            };
+            assert!(config.validate().is_err(), "{:?}", o);
+        }
+    }
+
+    #[test]
+    fn test_scale_out_fraction_leaves_part_open() {
+        let mut positions = PositionsConfig::default();
+        for fraction in [0.0, 0.25, 0.5] {
+            positions.scale_out_fraction = fraction;
+            assert!(positions.validate().is_ok(), "{}", fraction);
+        }
+        for fraction in [1.0, 1.5, -0.5, f64::NAN] {
+            positions.scale_out_fraction = fraction;
+            assert!(positions.validate().is_err(), "{}", fraction);
+        }
+    }
 }
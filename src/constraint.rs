@@ -14,7 +14,16 @@ impl ConstraintChecker {
     }
 
     /// Check if market has arbitrage opportunity
+    ///
+    /// With a book for every outcome, the bundle is priced where it would
+    /// actually trade: buying crosses the asks, selling hits the bids.
+    /// Midpoints are only used until books are available.
     pub fn check_violation(&self, market: &Market) -> Option<ArbitrageSignal> {
+        let (ask, bid) = (market.bundle_ask(), market.bundle_bid());
+        if ask.is_some() || bid.is_some() {
+            return self.check_executable(market, ask, bid);
+        }
+
         // Calculate sum of all outcome prices
         let sum: f64 = market.outcome_prices.iter().sum();
         let spread = (sum - 1.0).abs();
@@ -40,28 +49,58 @@ impl ConstraintChecker {
             no_price: market.no_price(),   // Legacy field
         })
     }
+
+    /// Violation at executable prices: bundle asks below 1, or bids above 1
+    fn check_executable(
+        &self,
+        market: &Market,
+        ask: Option<f64>,
+        bid: Option<f64>,
+    ) -> Option<ArbitrageSignal> {
+        let (spread, recommended_side) = match (ask, bid) {
+            (Some(ask), _) if 1.0 - ask > self.min_spread_threshold => (1.0 - ask, Side::Buy),
+            (_, Some(bid)) if bid - 1.0 > self.min_spread_threshold => (bid - 1.0, Side::Sell),
+            _ => return None,
+        };
+        let leg = |idx: usize| {
+            market
+                .outcome_quotes
+                .get(idx)
+                .and_then(|q| match recommended_side {
+                    Side::Buy => q.ask,
+                    Side::Sell => q.bid,
+                })
+        };
+
+        Some(ArbitrageSignal {
+            market_id: market.id.clone(),
+            spread,
+            edge: spread, // Gross edge before costs
+            recommended_side,
+            yes_price: leg(0).unwrap_or(0.0),
+            no_price: leg(1).unwrap_or(0.0),
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util;
+    use crate::types::Quote;
 
     fn create_test_market(yes_price: f64, no_price: f64) -> Market {
         Market {
-            id: "test_market".to_string(),
+            id: "test_market".into(),
             question: "Test question?".to_string(),
             slug: "test-market".to_string(),
-            outcomes: vec!["Yes".to_string(), "No".to_string()],
-            outcome_prices: vec![yes_price, no_price],
-            clob_token_ids: vec!["token1".to_string(), "token2".to_string()],
+            clob_token_ids: vec!["token1".into(), "token2".into()],
             best_bid: Some(yes_price - 0.01),
             best_ask: Some(yes_price + 0.01),
-            maker_base_fee: 0,
             taker_base_fee: 200,
-            liquidity: 1000.0,
-            volume_24hr: 5000.0,
-            active: true,
-            accepting_orders: true,
+            liquidity: Some(1000.0),
+            volume_24hr: Some(5000.0),
+            ..test_util::market(yes_price, no_price)
         }
     }
 
@@ -112,6 +151,45 @@ mod tests {
         assert_eq!(signal.recommended_side, Side::Sell);
     }
 
+    fn with_quotes(mut market: Market, quotes: [(f64, f64); 2]) -> Market {
+        market.outcome_quotes = quotes
+            .iter()
+            .map(|&(bid, ask)| Quote {
+                bid: Some(bid),
+                ask: Some(ask),
+            })
+            .collect();
+        market
+    }
+
+    #[test]
+    fn test_midpoint_arb_vanishes_at_the_asks() {
+        let checker = ConstraintChecker::new(0.02);
+        // Midpoints sum to 0.95, but buying both legs costs 0.50 + 0.49
+        let market = with_quotes(create_test_market(0.48, 0.47), [(0.46, 0.50), (0.45, 0.49)]);
+
+        assert!(checker.check_violation(&market).is_none());
+    }
+
+    #[test]
+    fn test_violation_priced_at_asks_and_bids() {
+        let checker = ConstraintChecker::new(0.02);
+
+        // Asks sum to 0.95
+        let market = with_quotes(create_test_market(0.47, 0.47), [(0.45, 0.48), (0.44, 0.47)]);
+        let signal = checker.check_violation(&market).unwrap();
+        assert_eq!(signal.recommended_side, Side::Buy);
+        assert!((signal.spread - 0.05).abs() < 1e-9);
+        assert_eq!((signal.yes_price, signal.no_price), (0.48, 0.47));
+
+        // Bids sum to 1.05
+        let market = with_quotes(create_test_market(0.53, 0.53), [(0.53, 0.55), (0.52, 0.54)]);
+        let signal = checker.check_violation(&market).unwrap();
+        assert_eq!(signal.recommended_side, Side::Sell);
+        assert!((signal.spread - 0.05).abs() < 1e-9);
+        assert_eq!((signal.yes_price, signal.no_price), (0.53, 0.52));
+    }
+
     #[test]
     fn test_spread_at_threshold_boundary() {
         let checker = ConstraintChecker::new(0.02);
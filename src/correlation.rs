@@ -0,0 +1,241 @@
+//! Market correlations
+//!
+//! Rolling correlations of candle-to-candle returns between tracked markets,
+//! each market represented by its first (YES) outcome. Markets that move
+//! together are one bet as far as risk goes, so their exposure is capped
+//! together as well as per market.
+
+use crate::candles::{Candle, CandleInterval, CandleStore};
+use crate::config::CorrelationConfig;
+use crate::exposure::ExposureReport;
+use crate::types::{Market, MarketId};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Pairwise return correlations, row and column order given by `markets`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CorrelationMatrix {
+    pub interval: Option<CandleInterval>,
+    pub markets: Vec<MarketId>,
+    /// None where two markets have too few returns in common
+    pub values: Vec<Vec<Option<f64>>>,
+    /// Unix time in seconds the matrix was computed
+    pub computed_at: u64,
+}
+
+impl CorrelationMatrix {
+    /// Correlation between two markets, if both are tracked and overlap enough
+    pub fn get(&self, a: &MarketId, b: &MarketId) -> Option<f64> {
+        let i = self.markets.iter().position(|m| m == a)?;
+        let j = self.markets.iter().position(|m| m == b)?;
+        self.values[i][j]
+    }
+}
+
+/// Returns keyed by the start of the candle they close
+fn returns(candles: &[Candle]) -> HashMap<u64, f64> {
+    candles
+        .windows(2)
+        .filter(|pair| pair[0].close > 0.0)
+        .map(|pair| (pair[1].start, pair[1].close / pair[0].close - 1.0))
+        .collect()
+}
+
+/// Pearson correlation; None for fewer than two points or a flat series
+pub fn pearson(a: &[f64], b: &[f64]) -> Option<f64> {
+    let n = a.len().min(b.len());
+    if n < 2 {
+        return None;
+    }
+    let mean_a = a[..n].iter().sum::<f64>() / n as f64;
+    let mean_b = b[..n].iter().sum::<f64>() / n as f64;
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a[..n].iter().zip(&b[..n]) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+    // Flat up to rounding
+    if var_a < 1e-12 || var_b < 1e-12 {
+        return None;
+    }
+    Some((cov / (var_a * var_b).sqrt()).clamp(-1.0, 1.0))
+}
+
+/// Correlate each pair of series over the returns they have in common
+///
+/// Only the last `window` candles of each series are used; pairs sharing
+/// fewer than `min_samples` returns are left as None.
+pub fn correlate(
+    series: &[(MarketId, Vec<Candle>)],
+    window: usize,
+    min_samples: usize,
+) -> (Vec<MarketId>, Vec<Vec<Option<f64>>>) {
+    let returns: Vec<HashMap<u64, f64>> = series
+        .iter()
+        .map(|(_, candles)| returns(&candles[candles.len().saturating_sub(window)..]))
+        .collect();
+    let n = series.len();
+    let mut values = vec![vec![None; n]; n];
+    for i in 0..n {
+        values[i][i] = Some(1.0);
+        for j in i + 1..n {
+            let (mut a, mut b) = (Vec::new(), Vec::new());
+            for (start, r) in &returns[i] {
+                if let Some(other) = returns[j].get(start) {
+                    a.push(*r);
+                    b.push(*other);
+                }
+            }
+            let value = if a.len() >= min_samples.max(2) {
+                pearson(&a, &b)
+            } else {
+                None
+            };
+            values[i][j] = value;
+            values[j][i] = value;
+        }
+    }
+    (series.iter().map(|(id, _)| id.clone()).collect(), values)
+}
+
+/// Latest correlation matrix, shared by the engine, pipeline, and API
+#[derive(Debug)]
+pub struct CorrelationTracker {
+    matrix: Mutex<CorrelationMatrix>,
+    config: CorrelationConfig,
+}
+
+impl CorrelationTracker {
+    pub fn new(config: &CorrelationConfig) -> Self {
+        Self {
+            matrix: Mutex::new(CorrelationMatrix::default()),
+            config: config.clone(),
+        }
+    }
+
+    /// Recompute the matrix from the markets' candles
+    pub fn refresh(&self, candles: &CandleStore, markets: &[Market], now: u64) {
+        let series: Vec<(MarketId, Vec<Candle>)> = markets
+            .iter()
+            .filter_map(|m| {
+                let token_id = m.clob_token_ids.first()?;
+                Some((
+                    m.id.clone(),
+                    candles.candles(token_id, self.config.interval),
+                ))
+            })
+            .collect();
+        let (markets, values) = correlate(&series, self.config.window, self.config.min_samples);
+        *self.matrix.lock().unwrap() = CorrelationMatrix {
+            interval: Some(self.config.interval),
+            markets,
+            values,
+            computed_at: now,
+        };
+    }
+
+    pub fn matrix(&self) -> CorrelationMatrix {
+        self.matrix.lock().unwrap().clone()
+    }
+
+    /// Net exposure in `market_id` and every market correlated with it at
+    /// or above the threshold
+    ///
+    /// Negatively correlated holdings offset rather than add to the risk, so
+    /// only positive correlations count.
+    pub fn correlated_exposure(&self, market_id: &MarketId, report: &ExposureReport) -> f64 {
+        let matrix = self.matrix.lock().unwrap();
+        report
+            .markets
+            .iter()
+            .filter(|m| {
+                m.market_id == *market_id
+                    || matrix
+                        .get(market_id, &m.market_id)
+                        .is_some_and(|c| c >= self.config.threshold)
+            })
+            .map(|m| m.net_value)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candles(closes: &[f64]) -> Vec<Candle> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| Candle {
+                start: i as u64 * 300,
+                open: *close,
+                high: *close,
+                low: *close,
+                close: *close,
+                samples: 1,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_correlates_common_returns() {
+        let up_down = [0.50, 0.55, 0.52, 0.58, 0.54, 0.60];
+        let series = vec![
+            (MarketId::from("a"), candles(&up_down)),
+            (
+                MarketId::from("b"),
+                candles(&up_down.map(|p| p * 0.5 + 0.1)),
+            ),
+            (MarketId::from("c"), candles(&up_down.map(|p| 1.0 - p))),
+            (MarketId::from("d"), candles(&[0.5, 0.6])),
+        ];
+        let (markets, values) = correlate(&series, 10, 3);
+        assert_eq!(markets.len(), 4);
+        assert_eq!(values[0][0], Some(1.0));
+        assert!(values[0][1].unwrap() > 0.99);
+        assert!(values[0][2].unwrap() < -0.99);
+        assert_eq!(values[2][0], values[0][2]);
+        // One return in common is too few
+        assert_eq!(values[0][3], None);
+        assert_eq!(pearson(&[0.1, 0.1, 0.1], &[0.2, 0.3, 0.4]), None);
+    }
+
+    #[test]
+    fn test_correlated_exposure() {
+        use crate::exposure::MarketExposure;
+
+        let tracker = CorrelationTracker::new(&CorrelationConfig {
+            threshold: 0.7,
+            ..Default::default()
+        });
+        *tracker.matrix.lock().unwrap() = CorrelationMatrix {
+            interval: None,
+            markets: vec!["a".into(), "b".into(), "c".into()],
+            values: vec![
+                vec![Some(1.0), Some(0.8), Some(-0.9)],
+                vec![Some(0.8), Some(1.0), None],
+                vec![Some(-0.9), None, Some(1.0)],
+            ],
+            computed_at: 0,
+        };
+        let exposure = |id: &str, value: f64| MarketExposure {
+            market_id: id.into(),
+            net_delta: value,
+            hedged_size: 0.0,
+            gross_value: value,
+            net_value: value,
+        };
+        let report = ExposureReport {
+            markets: vec![exposure("a", 10.0), exposure("b", 5.0), exposure("c", 20.0)],
+            gross_value: 35.0,
+            net_value: 35.0,
+        };
+        assert_eq!(tracker.correlated_exposure(&"a".into(), &report), 15.0);
+        assert_eq!(tracker.correlated_exposure(&"c".into(), &report), 20.0);
+        // Untracked markets only count themselves
+        assert_eq!(tracker.correlated_exposure(&"z".into(), &report), 0.0);
+    }
+}
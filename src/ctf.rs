@@ -0,0 +1,192 @@
+//! Conditional Token Framework module
+//!
+//! Encodes and submits CTF `mergePositions` / `redeemPositions` calls. Merging a held YES+NO pair redeems it for $1 of
+//! USDC immediately, so a bundle bought below $1 is realized without waiting
+//! for resolution; winning tokens are redeemed once the market resolves.
+
+use crate::evm::{selector, PolygonRpc, RpcError};
+use crate::signer::{address_word, parse_address, u256_word, EvmSigner};
+use tokio::sync::Mutex;
+
+/// USDC has 6 decimals
+const USDC_UNIT: f64 = 1_000_000.0;
+
+/// Index sets for the two outcomes of a binary condition (0b01, 0b10)
+const BINARY_PARTITION: [u64; 2] = [1, 2];
+
+/// ABI-encode a merge call on a binary condition
+///
+/// `mergePositions` (like `splitPosition`) takes
+/// `(address collateralToken, bytes32 parentCollectionId, bytes32 conditionId,
+/// uint256[] partition, uint256 amount)`.
+fn encode_position_call(
+    function: &str,
+    collateral: &[u8; 20],
+    condition_id: &[u8; 32],
+    amount: u64,
+) -> Vec<u8> {
+    let signature = format!("{}(address,bytes32,bytes32,uint256[],uint256)", function);
+    let mut data = selector(&signature).to_vec();
+    data.extend_from_slice(&address_word(collateral));
+    // Top-level positions have no parent collection
+    data.extend_from_slice(&[0u8; 32]);
+    data.extend_from_slice(condition_id);
+    // Offset of the partition array: five head words
+    data.extend_from_slice(&u256_word(5 * 32));
+    data.extend_from_slice(&u256_word(amount));
+    data.extend_from_slice(&u256_word(BINARY_PARTITION.len() as u64));
+    for index_set in BINARY_PARTITION {
+        data.extend_from_slice(&u256_word(index_set));
+    }
+    data
+}
+
+/// ABI-encode `redeemPositions(address,bytes32,bytes32,uint256[])` for both outcomes
+fn encode_redeem_call(collateral: &[u8; 20], condition_id: &[u8; 32]) -> Vec<u8> {
+    let mut data = selector("redeemPositions(address,bytes32,bytes32,uint256[])").to_vec();
+    data.extend_from_slice(&address_word(collateral));
+    data.extend_from_slice(&[0u8; 32]);
+    data.extend_from_slice(condition_id);
+    // Offset of the index set array: four head words
+    data.extend_from_slice(&u256_word(4 * 32));
+    data.extend_from_slice(&u256_word(BINARY_PARTITION.len() as u64));
+    for index_set in BINARY_PARTITION {
+        data.extend_from_slice(&u256_word(index_set));
+    }
+    data
+}
+
+/// Parse a 0x-prefixed bytes32 condition id
+fn parse_condition_id(condition_id: &str) -> Result<[u8; 32], RpcError> {
+    hex::decode(condition_id.trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| RpcError::Decode(format!("invalid condition id '{}'", condition_id)))
+}
+
+/// Whole USDC base units in `sets` complete sets (rounded down)
+fn set_units(sets: f64) -> u64 {
+    (sets * USDC_UNIT).floor().max(0.0) as u64
+}
+
+/// Complete sets that can be merged from held YES/NO sizes
+pub fn mergeable_sets(sizes: &[f64]) -> f64 {
+    if sizes.len() != BINARY_PARTITION.len() {
+        return 0.0;
+    }
+    sizes.iter().copied().fold(f64::INFINITY, f64::min).max(0.0)
+}
+
+/// Submits merge and redeem transactions against the CTF contract
+#[derive(Debug)]
+pub struct CtfClient {
+    rpc: PolygonRpc,
+    signer: EvmSigner,
+    ctf_address: String,
+    collateral: [u8; 20],
+    gas_limit: u64,
+    /// Serializes submissions so concurrent workers don't reuse a nonce
+    nonce_lock: Mutex<()>,
+}
+
+impl CtfClient {
+    pub fn new(
+        rpc: PolygonRpc,
+        signer: EvmSigner,
+        ctf_address: &str,
+        collateral: &str,
+        gas_limit: u64,
+    ) -> Result<Self, RpcError> {
+        let collateral = parse_address(collateral).map_err(|e| RpcError::Decode(e.to_string()))?;
+        Ok(Self {
+            rpc,
+            signer,
+            ctf_address: ctf_address.to_string(),
+            collateral,
+            gas_limit,
+            nonce_lock: Mutex::new(()),
+        })
+    }
+
+    /// Redeem `sets` held YES+NO pairs for USDC, returning the tx hash
+    pub async fn merge(&self, condition_id: &str, sets: f64) -> Result<String, RpcError> {
+        self.submit("mergePositions", condition_id, sets).await
+    }
+
+    /// Redeem all held tokens of a resolved condition for USDC, returning the tx hash
+    pub async fn redeem(&self, condition_id: &str) -> Result<String, RpcError> {
+        let condition = parse_condition_id(condition_id)?;
+        let data = encode_redeem_call(&self.collateral, &condition);
+        self.send(data).await
+    }
+
+    async fn submit(
+        &self,
+        function: &str,
+        condition_id: &str,
+        sets: f64,
+    ) -> Result<String, RpcError> {
+        let condition = parse_condition_id(condition_id)?;
+        let amount = set_units(sets);
+        if amount == 0 {
+            return Err(RpcError::Decode(format!(
+                "nothing to {} ({} sets)",
+                function, sets
+            )));
+        }
+        let data = encode_position_call(function, &self.collateral, &condition, amount);
+        self.send(data).await
+    }
+
+    async fn send(&self, data: Vec<u8>) -> Result<String, RpcError> {
+        let _guard = self.nonce_lock.lock().await;
+        self.rpc
+            .send_transaction(&self.signer, &self.ctf_address, data, self.gas_limit)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_merge_positions() {
+        let collateral = parse_address("0x2791Bca1f2de4661ED88A7Fc5317b2dB0CaFe0d0").unwrap();
+        let condition = parse_condition_id(&format!("0x{}", "ab".repeat(32))).unwrap();
+        let data = encode_position_call("mergePositions", &collateral, &condition, 5_000_000);
+
+        // selector + 5 head words + array length + 2 index sets
+        assert_eq!(data.len(), 4 + 8 * 32);
+        assert_eq!(hex::encode(&data[..4]), "9e7212ad");
+        let word = |i: usize| &data[4 + i * 32..4 + (i + 1) * 32];
+        assert_eq!(&word(0)[12..], &collateral);
+        assert_eq!(word(1), &[0u8; 32]);
+        assert_eq!(word(2), &condition);
+        assert_eq!(word(3), &u256_word(160));
+        assert_eq!(word(4), &u256_word(5_000_000));
+        assert_eq!(word(5), &u256_word(2));
+        assert_eq!(word(6), &u256_word(1));
+        assert_eq!(word(7), &u256_word(2));
+    }
+
+    #[test]
+    fn test_encode_redeem_positions() {
+        let collateral = [0x11; 20];
+        let condition = [0xcd; 32];
+        let data = encode_redeem_call(&collateral, &condition);
+
+        assert_eq!(hex::encode(&data[..4]), "01b7037c");
+        assert_eq!(data.len(), 4 + 7 * 32);
+        assert_eq!(&data[4 + 3 * 32..4 + 4 * 32], &u256_word(128));
+        assert_eq!(&data[data.len() - 32..], &u256_word(2));
+    }
+
+    #[test]
+    fn test_mergeable_sets() {
+        assert_eq!(mergeable_sets(&[10.0, 7.5]), 7.5);
+        assert_eq!(mergeable_sets(&[10.0]), 0.0);
+        assert_eq!(set_units(7.5), 7_500_000);
+        assert!(parse_condition_id("0x1234").is_err());
+    }
+}
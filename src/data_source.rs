@@ -0,0 +1,158 @@
+//! Price source supervisor
+//!
+//! Watches the WebSocket book stream and switches workers to REST polling
+//! while it is unhealthy, then back to the stream once it has stayed healthy
+//! for a while, so the detector always has a price source.
+
+use crate::config::DataSourceConfig;
+use crate::websocket::{WebSocketClient, WsStatus};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Where workers read order books from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataSource {
+    /// Local books maintained from the WebSocket stream
+    Stream,
+    /// Per-token CLOB REST snapshots
+    Rest,
+}
+
+/// Active source, shared with the workers and API
+pub type DataSourceState = Arc<RwLock<DataSource>>;
+
+/// Source selection with hysteresis: fall back at once, recover only after
+/// the stream has been healthy for `recover_after`
+#[derive(Debug, Clone)]
+struct SourceSelector {
+    active: DataSource,
+    healthy_since: Option<Instant>,
+    recover_after: Duration,
+}
+
+impl SourceSelector {
+    fn new(recover_after: Duration) -> Self {
+        Self {
+            active: DataSource::Rest,
+            healthy_since: None,
+            recover_after,
+        }
+    }
+
+    /// Feed one health observation; returns the new source on a switch
+    fn observe(&mut self, healthy: bool, now: Instant) -> Option<DataSource> {
+        let next = if !healthy {
+            self.healthy_since = None;
+            DataSource::Rest
+        } else {
+            let since = *self.healthy_since.get_or_insert(now);
+            if self.active == DataSource::Stream || now.duration_since(since) >= self.recover_after
+            {
+                DataSource::Stream
+            } else {
+                DataSource::Rest
+            }
+        };
+        if next == self.active {
+            return None;
+        }
+        self.active = next;
+        Some(next)
+    }
+}
+
+/// Switches the shared data source as the stream's health changes
+pub struct DataSourceSupervisor {
+    stream: Arc<WebSocketClient>,
+    state: DataSourceState,
+    selector: SourceSelector,
+    stale_after: Duration,
+    check_interval: Duration,
+}
+
+impl DataSourceSupervisor {
+    pub fn new(
+        stream: Arc<WebSocketClient>,
+        state: DataSourceState,
+        config: &DataSourceConfig,
+    ) -> Self {
+        Self {
+            stream,
+            state,
+            selector: SourceSelector::new(Duration::from_secs(config.recover_after_secs)),
+            stale_after: Duration::from_secs(config.stale_after_secs),
+            check_interval: Duration::from_millis(config.check_interval_ms),
+        }
+    }
+
+    /// Stream is connected and has delivered a frame recently
+    async fn healthy(&self) -> bool {
+        self.stream.get_status().await == WsStatus::Connected
+            && self
+                .stream
+                .last_frame_age()
+                .is_some_and(|age| age < self.stale_after)
+    }
+
+    /// Monitor the stream forever
+    pub async fn run(mut self) {
+        loop {
+            let healthy = self.healthy().await;
+            if let Some(source) = self.selector.observe(healthy, Instant::now()) {
+                match source {
+                    DataSource::Rest => {
+                        println!("⚠️ [DataSource] Stream unhealthy, falling back to REST books");
+                        // Books may have missed deltas while the stream lagged
+                        self.stream.order_books().mark_all_stale().await;
+                    }
+                    DataSource::Stream => {
+                        println!("✅ [DataSource] Stream healthy again, switching back");
+                        // Fresh snapshots replace any books marked stale
+                        if let Err(e) = self.stream.resync_all().await {
+                            println!("⚠️ [DataSource] Resync failed: {}", e);
+                        }
+                    }
+                }
+                *self.state.write().await = source;
+            }
+            tokio::time::sleep(self.check_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_falls_back_at_once_and_recovers_after_delay() {
+        let start = Instant::now();
+        let mut selector = SourceSelector::new(Duration::from_secs(5));
+
+        // Healthy, but not for long enough yet
+        assert_eq!(selector.observe(true, start), None);
+        assert_eq!(
+            selector.observe(true, start + Duration::from_secs(5)),
+            Some(DataSource::Stream)
+        );
+
+        // One bad check falls back immediately
+        assert_eq!(
+            selector.observe(false, start + Duration::from_secs(6)),
+            Some(DataSource::Rest)
+        );
+        // A flap restarts the recovery clock
+        assert_eq!(selector.observe(true, start + Duration::from_secs(7)), None);
+        assert_eq!(
+            selector.observe(true, start + Duration::from_secs(11)),
+            None
+        );
+        assert_eq!(
+            selector.observe(true, start + Duration::from_secs(12)),
+            Some(DataSource::Stream)
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! New-market discovery
+//!
+//! Polls Gamma for the newest listings, ordered by creation time, so a new
+//! market shows up however many older ones fill the trading slice. Listings
+//! created since the previous poll are announced: fresh markets are often
+//! the most mispriced.
+
+use crate::types::{Market, MarketId};
+use std::collections::HashSet;
+
+/// A market and the Gamma `createdAt` it was listed at
+#[derive(Debug, Clone)]
+pub struct Listing {
+    pub market: Market,
+    /// ISO-8601 UTC, e.g. "2024-05-22T18:39:29.56Z"
+    pub created_at: String,
+}
+
+/// Tracks the newest creation time seen across polls
+#[derive(Debug, Default)]
+pub struct MarketDiscovery {
+    /// Creation time of the newest listing seen; `None` until primed
+    newest: Option<String>,
+    /// Listings created exactly at `newest`, already reported
+    seen: HashSet<MarketId>,
+}
+
+impl MarketDiscovery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a poll, returning listings created since the previous one
+    ///
+    /// The first poll only sets the watermark: everything in it predates the
+    /// agent rather than being newly listed. An older market that drifts into
+    /// the newest-first window (because newer ones closed) is not reported.
+    pub fn observe<'a>(&mut self, listings: &'a [Listing]) -> Vec<&'a Market> {
+        let fresh = match &self.newest {
+            None => Vec::new(),
+            Some(newest) => listings
+                .iter()
+                .filter(|l| {
+                    l.created_at > *newest
+                        || (l.created_at == *newest && !self.seen.contains(&l.market.id))
+                })
+                .map(|l| &l.market)
+                .collect(),
+        };
+
+        if let Some(latest) = listings.iter().map(|l| l.created_at.as_str()).max() {
+            if self.newest.as_deref().is_none_or(|newest| latest > newest) {
+                self.newest = Some(latest.to_string());
+                self.seen.clear();
+            }
+        }
+        if let Some(newest) = &self.newest {
+            self.seen.extend(
+                listings
+                    .iter()
+                    .filter(|l| l.created_at == *newest)
+                    .map(|l| l.market.id.clone()),
+            );
+        }
+        fresh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn listing(id: &str, created_at: &str) -> Listing {
+        Listing {
+            market: Market {
+                id: id.into(),
+                ..test_util::market(0.5, 0.5)
+            },
+            created_at: created_at.to_string(),
+        }
+    }
+
+    fn ids(markets: Vec<&Market>) -> Vec<&str> {
+        markets.iter().map(|m| m.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_reports_only_listings_created_since_last_poll() {
+        let mut discovery = MarketDiscovery::new();
+        let first = [
+            listing("m2", "2026-10-01T12:00:00Z"),
+            listing("m1", "2026-09-30T08:00:00Z"),
+        ];
+        assert!(discovery.observe(&first).is_empty());
+
+        // m0 is old and only entered the window because another market closed
+        let second = [
+            listing("m4", "2026-10-02T09:30:00Z"),
+            listing("m3", "2026-10-02T09:30:00Z"),
+            listing("m2", "2026-10-01T12:00:00Z"),
+            listing("m0", "2026-09-01T00:00:00Z"),
+        ];
+        assert_eq!(ids(discovery.observe(&second)), vec!["m4", "m3"]);
+
+        // A listing sharing the watermark's timestamp is still new
+        let third = [
+            listing("m5", "2026-10-02T09:30:00Z"),
+            listing("m4", "2026-10-02T09:30:00Z"),
+            listing("m3", "2026-10-02T09:30:00Z"),
+        ];
+        assert_eq!(ids(discovery.observe(&third)), vec!["m5"]);
+        assert!(discovery.observe(&third).is_empty());
+    }
+}
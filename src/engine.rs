@@ -1,15 +1,38 @@
 //! Trading Engine Module
 //!
-//! Orchestrates the main trading loop with safety controls and failure handling.
+//! Orchestrates the trading loop with safety controls and failure handling.
+//! Each tick gates on the permission grant and safety state, refreshes the
+//! market list, keeps one worker per tradeable market (workers detect
+//! signals and run exits on their own cadence; the pipeline executes),
+//! settles resolved markets, and persists stats. Nothing executes unless the
+//! engine reports `Running`.
 
-use crate::arb::ArbitrageDetector;
-use crate::config::SafetyConfig;
-use crate::execution::ExecutionEngine;
-use crate::market::MarketDataProvider;
-use crate::types::Side;
+use crate::alerts::{AlertEngine, AlertInputs};
+use crate::breakdown::BREAKDOWN_DOCUMENT;
+use crate::candles::CANDLES_DOCUMENT;
+use crate::discovery::MarketDiscovery;
+use crate::expiry::ExpiryWarnings;
+use crate::fees::VOLUME_DOCUMENT;
+use crate::ledger::LEDGER_DOCUMENT;
+use crate::metrics::Endpoint;
+use crate::money;
+use crate::positions::STATS_DOCUMENT;
+use crate::redemption;
+use crate::types::Market;
 use crate::wallet::Wallet;
+use crate::webhook::WebhookEvent;
+use crate::websocket::WebSocketClient;
+use crate::workers::{
+    get_min_edge_for_allowance, get_strategy_mode, get_strategy_mode_name, WorkerContext,
+    WorkerPool,
+};
+use colored::Colorize;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+/// How often a missing permission grant is rechecked
+const PERMISSION_RECHECK: Duration = Duration::from_secs(1);
+
 /// Agent operational status for monitoring
 #[derive(Debug, Clone, PartialEq)]
 pub enum EngineStatus {
@@ -19,105 +42,200 @@ pub enum EngineStatus {
     SafeMode { reason: String, until: Instant },
     /// Engine suspended due to data delay
     DataDelaySuspended { delay_ms: u64 },
+    /// Engine suspended because an endpoint's p99 latency is too high
+    LatencySuspended { endpoint: Endpoint, p99_ms: f64 },
     /// Engine stopped - permission expired or revoked
     Stopped,
 }
 
-#[allow(dead_code)]
 pub struct TradingEngine {
-    pub wallet: Wallet,
-    pub market_provider: MarketDataProvider,
-    pub detector: ArbitrageDetector,
-    pub execution_engine: ExecutionEngine,
-    /// Current engine status
-    status: EngineStatus,
+    ctx: Arc<WorkerContext>,
+    workers: WorkerPool,
+    /// Newly listed markets are announced from the second fetch on
+    discovery: MarketDiscovery,
+    /// Renewal warnings already sent for the current grant
+    expiry: ExpiryWarnings,
+    /// User alert rules and their cooldowns
+    alerts: AlertEngine,
     /// Consecutive API failure count
     consecutive_failures: u32,
-    /// Safety configuration
-    safety_config: SafetyConfig,
-    /// Last successful data fetch timestamp
+    /// Last successful market fetch
     last_data_fetch: Option<Instant>,
 }
 
 impl TradingEngine {
-    pub fn new(
-        wallet: Wallet,
-        market_provider: MarketDataProvider,
-        detector: ArbitrageDetector,
-        execution_engine: ExecutionEngine,
-    ) -> Self {
+    pub fn new(ctx: Arc<WorkerContext>) -> Self {
         Self {
-            wallet,
-            market_provider,
-            detector,
-            execution_engine,
-            status: EngineStatus::Running,
+            workers: WorkerPool::new(ctx.clone()),
+            discovery: MarketDiscovery::new(),
+            expiry: ExpiryWarnings::new(&ctx.config.permission.expiry_warning_secs),
+            alerts: AlertEngine::new(
+                ctx.config.alerts.rules.clone(),
+                ctx.config.alerts.cooldown_secs,
+            ),
+            ctx,
             consecutive_failures: 0,
-            safety_config: SafetyConfig::default(),
             last_data_fetch: None,
         }
     }
 
-    /// Create engine with custom safety configuration
-    pub fn with_safety_config(mut self, config: SafetyConfig) -> Self {
-        self.safety_config = config;
+    /// Keep the book stream subscribed to running markets
+    pub fn with_stream(mut self, stream: Arc<WebSocketClient>) -> Self {
+        self.workers = self.workers.with_stream(stream);
         self
     }
 
     /// Get current engine status
-    pub fn get_status(&self) -> &EngineStatus {
-        &self.status
+    pub async fn get_status(&self) -> EngineStatus {
+        self.ctx.status.read().await.clone()
+    }
+
+    async fn set_status(&self, status: EngineStatus) {
+        *self.ctx.status.write().await = status;
+    }
+
+    /// Stop trading and retire every worker; the pipeline drains once they exit
+    pub async fn shutdown(mut self) {
+        self.set_status(EngineStatus::Stopped).await;
+        self.workers.sync(&[]).await;
     }
 
     /// Check if engine should enter safe mode
     ///
-    /// SAFETY: This is called before each tick to ensure we don't trade
-    /// under dangerous conditions.
-    fn check_safety_conditions(&mut self) -> bool {
+    /// SAFETY: This is called before each tick so workers never trade under
+    /// dangerous conditions. Returns how long to wait when the tick must be
+    /// skipped entirely.
+    async fn check_safety_conditions(&mut self) -> Option<Duration> {
+        let safety = &self.ctx.config.safety;
+
         // Check if we're in safe mode cooldown
-        if let EngineStatus::SafeMode { until, .. } = self.status {
-            if Instant::now() < until {
-                return false; // Still in cooldown
+        if let EngineStatus::SafeMode { until, .. } = self.get_status().await {
+            let now = Instant::now();
+            if now < until {
+                return Some(until - now); // Still in cooldown
             }
             // Cooldown expired, try to resume
             println!("🔄 [Engine] Safe mode cooldown expired, attempting to resume...");
-            self.status = EngineStatus::Running;
             self.consecutive_failures = 0;
         }
 
-        // Check data staleness
-        // FAILURE HANDLING: If data is stale (> max_data_delay_ms), suspend trading
-        // to prevent trading on outdated market information.
-        if let Some(last_fetch) = self.last_data_fetch {
-            let delay = last_fetch.elapsed().as_millis() as u64;
-            if delay > self.safety_config.max_data_delay_ms {
-                println!(
-                    "⚠️ [Engine] Data delay {}ms exceeds threshold {}ms - suspending",
-                    delay, self.safety_config.max_data_delay_ms
-                );
-                self.status = EngineStatus::DataDelaySuspended { delay_ms: delay };
-                return false;
-            }
-        }
-
         // Check consecutive failures
         // FAILURE HANDLING: If we have N consecutive API failures, enter safe mode
         // with a cooldown period to prevent hammering failing APIs.
-        if self.consecutive_failures >= self.safety_config.max_consecutive_failures {
-            let cooldown = Duration::from_secs(self.safety_config.safe_mode_cooldown_secs);
+        if self.consecutive_failures >= safety.max_consecutive_failures {
+            let cooldown = Duration::from_secs(safety.safe_mode_cooldown_secs);
             println!(
                 "🛑 [Engine] {} consecutive failures - entering safe mode for {}s",
                 self.consecutive_failures,
                 cooldown.as_secs()
             );
-            self.status = EngineStatus::SafeMode {
+            self.set_status(EngineStatus::SafeMode {
                 reason: format!("{} consecutive API failures", self.consecutive_failures),
                 until: Instant::now() + cooldown,
-            };
-            return false;
+            })
+            .await;
+            return Some(cooldown);
         }
 
-        true
+        // Check data staleness
+        // FAILURE HANDLING: If the market list is overdue by more than
+        // max_data_delay_ms, suspend trading to prevent trading on outdated
+        // market information. The fetch is still attempted.
+        let expected = Duration::from_secs(self.ctx.config.timing.discovery_interval_secs)
+            + Duration::from_millis(safety.max_data_delay_ms);
+        let overdue = self
+            .last_data_fetch
+            .map(|last| last.elapsed())
+            .filter(|delay| *delay > expected);
+
+        // Check upstream latency
+        // FAILURE HANDLING: If any endpoint's p99 latency explodes, suspend trading
+        // until it recovers; fills at stale prices are worse than no fills.
+        let status = match (overdue, self.ctx.latency.degraded()) {
+            (Some(delay), _) => {
+                println!(
+                    "⚠️ [Engine] Market data {}s old exceeds {}s - suspending",
+                    delay.as_secs(),
+                    expected.as_secs()
+                );
+                EngineStatus::DataDelaySuspended {
+                    delay_ms: delay.as_millis() as u64,
+                }
+            }
+            (None, Some((endpoint, p99_ms))) => {
+                println!(
+                    "⚠️ [Engine] {} p99 latency {:.0}ms exceeds threshold {}ms - suspending",
+                    endpoint.as_str(),
+                    p99_ms,
+                    safety.max_latency_p99_ms
+                );
+                EngineStatus::LatencySuspended { endpoint, p99_ms }
+            }
+            (None, None) => {
+                if self.get_status().await != EngineStatus::Running {
+                    println!("🔄 [Engine] Resuming trading");
+                }
+                EngineStatus::Running
+            }
+        };
+        self.set_status(status).await;
+        None
+    }
+
+    /// Log and notify when the grant comes within a warning horizon
+    async fn warn_expiry(&mut self) {
+        let Some(grant) = self.ctx.metamask.get_permission().await else {
+            return;
+        };
+        let now = Wallet::current_timestamp();
+        if self.expiry.check(&grant, now).is_none() {
+            return;
+        }
+        let remaining = grant.secs_until_expiry(now);
+        println!(
+            "⏰ [Engine] Permission {} expires in {}h{:02}m - renew it to keep trading",
+            grant.permission_id,
+            remaining / 3600,
+            remaining % 3600 / 60
+        );
+        if let Some(webhook) = &self.ctx.webhook {
+            webhook.publish(WebhookEvent::PermissionExpiring {
+                timestamp: now,
+                permission_id: grant.permission_id,
+                expires_at: grant.expires_at,
+                secs_until_expiry: remaining,
+            });
+        }
+    }
+
+    /// Evaluate the alert rules, logging and notifying any that fire
+    async fn check_alerts(&mut self) {
+        if self.alerts.is_empty() {
+            return;
+        }
+        let (total_pnl, recent_pnls) = {
+            let pm = self.ctx.position_manager.read().await;
+            (pm.total_pnl(), pm.recent_pnls(self.alerts.lookback()))
+        };
+        let (remaining, daily_limit) = self.ctx.allowance().await;
+        let inputs = AlertInputs {
+            total_pnl: money::to_f64(total_pnl),
+            recent_pnls: recent_pnls.into_iter().map(money::to_f64).collect(),
+            allowance_used: (daily_limit > money::Decimal::ZERO)
+                .then(|| 1.0 - money::to_f64(remaining) / money::to_f64(daily_limit)),
+            data_age_secs: self.last_data_fetch.map(|last| last.elapsed().as_secs()),
+        };
+        let now = Wallet::current_timestamp();
+        for alert in self.alerts.evaluate(&inputs, now) {
+            println!("🔔 [Alert] {}: {}", alert.rule, alert.message);
+            if let Some(webhook) = &self.ctx.webhook {
+                webhook.publish(WebhookEvent::Alert {
+                    timestamp: now,
+                    rule: alert.rule,
+                    message: alert.message,
+                });
+            }
+        }
     }
 
     /// Handle API failure with proper tracking
@@ -137,73 +255,292 @@ impl TradingEngine {
         self.last_data_fetch = Some(Instant::now());
     }
 
-    /// Run a single tick of the trading loop
+    /// Run a single tick of the trading loop, returning the wait until the next
     ///
     /// SAFETY GUARANTEES:
-    /// 1. Checks safety conditions before any trading
-    /// 2. Tracks API failures and enters safe mode after threshold
-    /// 3. Suspends on stale data
-    /// 4. All errors are caught and handled gracefully
-    pub async fn tick(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    /// 1. No trading without a valid permission grant (shadow mode needs none)
+    /// 2. Checks safety conditions before any trading
+    /// 3. Tracks API failures and enters safe mode after threshold
+    /// 4. Suspends on stale data
+    /// 5. All errors are caught and handled gracefully
+    pub async fn tick(&mut self) -> Duration {
+        let ctx = self.ctx.clone();
+        let config = &ctx.config;
+        let shadow_mode = config.trading.shadow_mode;
+
+        self.warn_expiry().await;
+        self.check_alerts().await;
+
+        // Wait for active permission if not present
+        if !shadow_mode && !ctx.accounts.any_active().await {
+            self.set_status(EngineStatus::Stopped).await;
+            return PERMISSION_RECHECK;
+        }
+
         // Pre-tick safety check
-        if !self.check_safety_conditions() {
-            return Ok(()); // Skip this tick, we're in a safety state
+        if let Some(wait) = self.check_safety_conditions().await {
+            return wait; // Skip this tick, we're in a safety state
         }
 
         // Fetch markets with failure handling
-        let markets = match self.market_provider.fetch_markets().await {
+        println!("\n{}", "📡 Fetching markets from Gamma API...".cyan());
+        let markets = match ctx.market_provider.fetch_markets().await {
             Ok(m) => {
                 self.handle_success();
                 m
             }
             Err(e) => {
                 self.handle_failure(&*e);
-                return Err(e);
+                return Duration::from_secs(config.timing.poll_interval_secs);
             }
         };
+        println!(
+            "   Found {} active markets (Limit {})",
+            markets.len(),
+            config.api.market_limit
+        );
+        self.announce_new_markets().await;
+        let markets = self.update_market_cache(markets).await;
+        ctx.execution_engine.fee_model.resolve_overrides(&markets);
+        ctx.correlations
+            .refresh(&ctx.candles, &markets, Wallet::current_timestamp());
 
-        // Scan for signals
-        let signals = self.detector.scan(&markets);
-
-        for signal in signals {
-            // Simplified execution logic from main.rs
-            if signal.recommended_side == Side::Buy {
-                // Find market
-                if let Some(market) = markets.iter().find(|m| m.id == signal.market_id) {
-                    let size_per_leg = 5.0; // Fixed for now
-
-                    // Execute on all outcomes (Buy Bundle behavior)
-                    for token_id in &market.clob_token_ids {
-                        match self.market_provider.fetch_order_book(token_id).await {
-                            Ok(book) => {
-                                self.execution_engine.execute(
-                                    &book,
-                                    size_per_leg,
-                                    Side::Buy,
-                                    &mut self.wallet,
-                                );
-                            }
-                            Err(e) => {
-                                // Log but don't fail entire tick for single order book fetch
-                                println!("⚠️ [Engine] Order book fetch failed: {}", e);
-                            }
-                        }
-                    }
-                }
+        // Split the daily allowance across strategies before workers trade on
+        // it, and give them a fresh trade cap
+        ctx.allocate_budgets().await;
+        ctx.throttle.start_cycle(Instant::now());
+        self.workers.sync(&markets).await;
+
+        // Settle positions in markets that resolved since they were traded
+        redemption::redeem_resolved(&ctx, &markets, Wallet::current_timestamp()).await;
+        ctx.sample_utilization().await;
+
+        let (remaining_allowance, daily_limit) = ctx.allowance().await;
+        let strategy = ctx.strategy_config().await;
+        let strategy = ctx.tuner.lock().await.tuned(&strategy);
+        let min_edge = get_min_edge_for_allowance(remaining_allowance, daily_limit, &strategy);
+        println!(
+            "   📈 Strategy Mode: {} (min edge: {:.1}%) | {} workers",
+            get_strategy_mode_name(remaining_allowance, daily_limit, &strategy).cyan(),
+            min_edge * 100.0,
+            self.workers.len()
+        );
+
+        let intent_count = ctx.take_intent_count();
+        if intent_count > 0 {
+            println!(
+                "⚡ Workers detected {} trade intents since last discovery",
+                intent_count
+            );
+        } else {
+            println!("   No trade intents found.");
+            if config.trading.demo_mode && !shadow_mode && !markets.is_empty() {
+                self.simulate_demo_trade(&markets[0]).await;
             }
         }
-        Ok(())
+
+        self.report_and_persist().await;
+
+        println!(
+            "💤 Next discovery in {}s...",
+            config.timing.discovery_interval_secs
+        );
+        Duration::from_secs(config.timing.discovery_interval_secs)
     }
 
-    /// Run the loop for a specific duration or number of ticks
-    pub async fn run(&mut self, ticks: usize) {
-        for tick_num in 0..ticks {
-            if let Err(e) = self.tick().await {
-                eprintln!("Error in tick {}: {}", tick_num, e);
+    /// Log (and mirror to the webhook) markets listed since the last fetch
+    ///
+    /// Polls the newest listings rather than diffing the trading slice, which
+    /// only ever holds the same top events.
+    async fn announce_new_markets(&mut self) {
+        let config = &self.ctx.config;
+        if !config.discovery.alerts {
+            return;
+        }
+        let listings = match self
+            .ctx
+            .market_provider
+            .fetch_newest_listings(config.api.market_limit)
+            .await
+        {
+            Ok(listings) => listings,
+            Err(e) => {
+                println!("⚠️  [Discovery] Failed to fetch new listings: {}", e);
+                return;
+            }
+        };
+        let trading = &config.trading;
+        let fresh = self
+            .discovery
+            .observe(&listings)
+            .into_iter()
+            .filter(|market| market.meets_activity(trading.min_liquidity, trading.min_volume_24hr));
+        for market in fresh {
+            println!(
+                "🆕 [Discovery] New market: {} ({})",
+                market.question, market.id
+            );
+            if let Some(webhook) = &self.ctx.webhook {
+                webhook.publish(WebhookEvent::NewMarket {
+                    timestamp: Wallet::current_timestamp(),
+                    market_id: market.id.clone(),
+                    question: market.question.clone(),
+                    slug: market.slug.clone(),
+                });
             }
-            // In simulation we might not want to sleep strictly, or sleep 0 for speed
-            // simulating "ticks"
-            tokio::time::sleep(Duration::from_millis(100)).await;
         }
     }
+
+    /// Update market cache for API, keeping prices workers already refreshed
+    async fn update_market_cache(&self, markets: Vec<Market>) -> Arc<Vec<Market>> {
+        self.ctx
+            .quarantine
+            .retain_listed(markets.iter().flat_map(|market| &market.clob_token_ids));
+        let mut cache = self.ctx.market_cache.write().await;
+        cache.markets.replace(markets);
+        cache.last_update = Some(Instant::now());
+        cache.markets.snapshot()
+    }
+
+    // ======== DEMO MODE: Simulate trades for hackathon demos (opt-in) ========
+    // This shows the system working even when no real arbitrage exists.
+    // Simulated PnL is tracked in its own stats bucket, never as live trades,
+    // and the made-up cost is never charged to the grant.
+    async fn simulate_demo_trade(&self, demo_market: &Market) {
+        let simulated_pnl = money::usdc((rand::random::<f64>() - 0.3) * 0.50); // Slight positive bias
+        let trade_cost = money::usdc(2.0 + rand::random::<f64>() * 3.0);
+
+        // Record in the demo stats bucket
+        let (remaining, daily_limit) = self.ctx.allowance().await;
+        let strategy = self.ctx.strategy_config().await;
+        self.ctx.pnl_breakdown.record_demo(
+            get_strategy_mode(remaining, daily_limit, &strategy),
+            simulated_pnl,
+        );
+        let mut pm = self.ctx.position_manager.write().await;
+        pm.record_simulated_trade(simulated_pnl);
+
+        println!(
+            "   🎭 [DEMO] Simulated trade on '{}' | Cost: ${:.2} | PnL: ${:.4}",
+            demo_market.question.chars().take(40).collect::<String>(),
+            trade_cost,
+            simulated_pnl
+        );
+    }
+    // ======== END DEMO MODE ========
+
+    /// Show stats and persist lifetime totals
+    async fn report_and_persist(&self) {
+        let ctx = &self.ctx;
+        let storage = &ctx.storage;
+        let pm = ctx.position_manager.read().await;
+        println!(
+            "\n📊 Stats: {} trades | Win rate: {:.0}% | PnL: ${:.2} | Open: {}",
+            pm.trade_count(),
+            pm.win_rate() * 100.0,
+            pm.total_pnl(),
+            pm.get_positions().len(),
+        );
+        let utilization = ctx.utilization.report();
+        if utilization.session_secs > 0 {
+            println!(
+                "💼 Capital: {:.0}% utilized (avg ${:.2} in positions, ${:.2} idle) | In market {:.0}% | Avg hold {:.0}s",
+                utilization.utilization * 100.0,
+                utilization.avg_deployed,
+                utilization.avg_idle,
+                utilization.time_in_market * 100.0,
+                utilization.avg_hold_secs
+            );
+        }
+        if ctx.config.trading.shadow_mode {
+            let shadow = ctx.shadow.lock().await;
+            println!(
+                "👻 Shadow: {} hypothetical fills | Cost: ${:.2} | Expected PnL: ${:.2}",
+                shadow.fills(),
+                shadow.total_cost(),
+                shadow.expected_profit()
+            );
+        } else {
+            let ledger = ctx.ledger.lock().await;
+            let budgets: Vec<String> = ctx
+                .strategies
+                .lock()
+                .await
+                .names()
+                .iter()
+                .map(|name| format!("{} ${:.2} left", name, ledger.remaining(name)))
+                .collect();
+            println!(
+                "💰 Spent today: ${:.2} | {}",
+                ledger.total_spent(),
+                budgets.join(" | ")
+            );
+            if let Err(e) = storage.save(LEDGER_DOCUMENT, &*ledger) {
+                println!("⚠️ Failed to persist spend ledger: {}", e);
+            }
+        }
+        if let Err(e) = storage.save(VOLUME_DOCUMENT, &*ctx.volume.lock().unwrap()) {
+            println!("⚠️ Failed to persist traded volume: {}", e);
+        }
+        if let Err(e) = storage.save(STATS_DOCUMENT, pm.lifetime_stats()) {
+            println!("⚠️ Failed to persist stats: {}", e);
+        }
+        if let Err(e) = storage.save(BREAKDOWN_DOCUMENT, &ctx.pnl_breakdown.state()) {
+            println!("⚠️ Failed to persist PnL breakdown: {}", e);
+        }
+        if ctx.config.candles.persist {
+            if let Err(e) = storage.save(CANDLES_DOCUMENT, &ctx.candles.series()) {
+                println!("⚠️ Failed to persist candles: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentBuilder;
+    use crate::config::Config;
+    use crate::metamask::{GrantScope, PermissionGrant};
+    use crate::test_util;
+
+    #[tokio::test]
+    async fn test_demo_trade_leaves_allowance_alone() {
+        let mut config = Config::default_config();
+        config.api.headless = true;
+        config.trading.demo_mode = true;
+        let agent = AgentBuilder::new(config)
+            .with_storage(test_util::temp_storage("engine_demo"))
+            .build()
+            .await
+            .unwrap();
+        agent
+            .ctx
+            .metamask
+            .set_permission(PermissionGrant {
+                permission_id: "perm_demo".into(),
+                token: "USDC".into(),
+                daily_limit: money::usdc(10.0),
+                spent_today: money::usdc(0.0),
+                expires_at: u64::MAX,
+                granted_at: 0,
+                revoked: false,
+                scope: GrantScope::default(),
+            })
+            .await;
+
+        let engine = agent.engine();
+        engine
+            .simulate_demo_trade(&test_util::market(0.5, 0.5))
+            .await;
+
+        assert_eq!(
+            agent.ctx.metamask.get_remaining_allowance().await,
+            money::usdc(10.0)
+        );
+        assert_eq!(
+            agent.ctx.position_manager.read().await.demo_stats().trades,
+            1
+        );
+    }
 }
@@ -0,0 +1,417 @@
+//! Polygon RPC module
+//!
+//! Reads the trading account's USDC balance and its allowance to the CTF
+//! exchange over raw JSON-RPC `eth_call`, so trading stops when the account
+//! can't actually fund a trade. Also signs and submits legacy (EIP-155)
+//! transactions for on-chain operations such as CTF merges.
+
+use crate::signer::{address_word, keccak256, parse_address, EvmSigner, POLYGON_CHAIN_ID};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// USDC has 6 decimals
+const USDC_DECIMALS: i32 = 6;
+
+/// On-chain funding state of the trading account
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct OnChainAccount {
+    /// USDC balance
+    pub usdc_balance: f64,
+    /// USDC the CTF exchange may pull
+    pub exchange_allowance: f64,
+    /// Unix time of the read
+    pub updated_at: u64,
+}
+
+/// Latest on-chain snapshot, shared with the workers and API
+pub type OnChainState = Arc<RwLock<Option<OnChainAccount>>>;
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    result: Option<String>,
+    error: Option<RpcErrorBody>,
+}
+
+#[derive(Deserialize)]
+struct RpcErrorBody {
+    message: String,
+}
+
+/// 4-byte function selector
+pub fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// ABI-encode a call with address arguments
+fn encode_call(signature: &str, args: &[[u8; 20]]) -> String {
+    let mut data = selector(signature).to_vec();
+    for arg in args {
+        data.extend_from_slice(&address_word(arg));
+    }
+    format!("0x{}", hex::encode(data))
+}
+
+/// Decode a uint256 return value scaled by `decimals`
+fn decode_amount(result: &str, decimals: i32) -> Result<f64, RpcError> {
+    let bytes = hex::decode(result.trim_start_matches("0x"))
+        .map_err(|e| RpcError::Decode(e.to_string()))?;
+    if bytes.len() != 32 {
+        return Err(RpcError::Decode(format!(
+            "expected 32-byte word, got {}",
+            bytes.len()
+        )));
+    }
+    // f64 keeps unlimited (2^256 - 1) approvals representable
+    let raw = bytes.iter().fold(0.0, |acc, b| acc * 256.0 + *b as f64);
+    Ok(raw / 10f64.powi(decimals))
+}
+
+/// Minimal Polygon JSON-RPC client
+#[derive(Debug, Clone)]
+pub struct PolygonRpc {
+    client: reqwest::Client,
+    rpc_url: String,
+}
+
+impl PolygonRpc {
+    pub fn new(rpc_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            rpc_url: rpc_url.to_string(),
+        }
+    }
+
+    /// Send a JSON-RPC request whose result is a hex string
+    async fn request(&self, method: &str, params: Value) -> Result<String, RpcError> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let resp: RpcResponse = self
+            .client
+            .post(&self.rpc_url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| RpcError::Http(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| RpcError::Http(e.to_string()))?;
+
+        match (resp.result, resp.error) {
+            (_, Some(err)) => Err(RpcError::Rpc(err.message)),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(RpcError::Rpc("empty response".to_string())),
+        }
+    }
+
+    /// eth_call against the latest block
+    async fn call(&self, to: &str, data: String) -> Result<String, RpcError> {
+        self.request("eth_call", json!([{ "to": to, "data": data }, "latest"]))
+            .await
+    }
+
+    /// Next nonce for `address`, counting pending transactions
+    pub async fn transaction_count(&self, address: &str) -> Result<u64, RpcError> {
+        let result = self
+            .request("eth_getTransactionCount", json!([address, "pending"]))
+            .await?;
+        Ok(decode_quantity(&result)? as u64)
+    }
+
+    /// Current gas price in wei
+    pub async fn gas_price(&self) -> Result<u128, RpcError> {
+        decode_quantity(&self.request("eth_gasPrice", json!([])).await?)
+    }
+
+    /// Sign a contract call from `signer` and broadcast it, returning the tx hash
+    pub async fn send_transaction(
+        &self,
+        signer: &EvmSigner,
+        to: &str,
+        data: Vec<u8>,
+        gas_limit: u64,
+    ) -> Result<String, RpcError> {
+        let tx = LegacyTransaction {
+            nonce: self.transaction_count(&signer.address()).await?,
+            gas_price: self.gas_price().await?,
+            gas_limit,
+            to: address(to)?,
+            value: 0,
+            data,
+        };
+        let raw = tx.sign(signer, POLYGON_CHAIN_ID);
+        self.request(
+            "eth_sendRawTransaction",
+            json!([format!("0x{}", hex::encode(raw))]),
+        )
+        .await
+    }
+
+    /// ERC-20 balanceOf
+    pub async fn usdc_balance(&self, token: &str, owner: &str) -> Result<f64, RpcError> {
+        let data = encode_call("balanceOf(address)", &[address(owner)?]);
+        decode_amount(&self.call(token, data).await?, USDC_DECIMALS)
+    }
+
+    /// ERC-20 allowance
+    pub async fn usdc_allowance(
+        &self,
+        token: &str,
+        owner: &str,
+        spender: &str,
+    ) -> Result<f64, RpcError> {
+        let data = encode_call(
+            "allowance(address,address)",
+            &[address(owner)?, address(spender)?],
+        );
+        decode_amount(&self.call(token, data).await?, USDC_DECIMALS)
+    }
+}
+
+fn address(address: &str) -> Result<[u8; 20], RpcError> {
+    parse_address(address).map_err(|e| RpcError::Decode(e.to_string()))
+}
+
+/// Decode a hex quantity (e.g. "0x1a")
+fn decode_quantity(result: &str) -> Result<u128, RpcError> {
+    u128::from_str_radix(result.trim_start_matches("0x"), 16)
+        .map_err(|e| RpcError::Decode(e.to_string()))
+}
+
+/// RLP-encode a byte string
+fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        return bytes.to_vec();
+    }
+    let mut out = rlp_length(bytes.len(), 0x80);
+    out.extend_from_slice(bytes);
+    out
+}
+
+/// RLP-encode an unsigned integer (big-endian, no leading zeros)
+fn rlp_uint(value: u128) -> Vec<u8> {
+    rlp_scalar(&value.to_be_bytes())
+}
+
+/// RLP-encode a big-endian scalar, stripping leading zeros
+fn rlp_scalar(bytes: &[u8]) -> Vec<u8> {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    rlp_bytes(&bytes[start..])
+}
+
+/// RLP-encode a list of already-encoded items
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload = items.concat();
+    let mut out = rlp_length(payload.len(), 0xc0);
+    out.extend_from_slice(&payload);
+    out
+}
+
+/// RLP length prefix for a string (offset 0x80) or list (offset 0xc0)
+fn rlp_length(len: usize, offset: u8) -> Vec<u8> {
+    if len <= 55 {
+        return vec![offset + len as u8];
+    }
+    let bytes = (len as u64).to_be_bytes();
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    let mut out = vec![offset + 55 + (bytes.len() - start) as u8];
+    out.extend_from_slice(&bytes[start..]);
+    out
+}
+
+/// Pre-London transaction, replay-protected with EIP-155
+#[derive(Debug, Clone)]
+pub struct LegacyTransaction {
+    pub nonce: u64,
+    pub gas_price: u128,
+    pub gas_limit: u64,
+    pub to: [u8; 20],
+    pub value: u128,
+    pub data: Vec<u8>,
+}
+
+impl LegacyTransaction {
+    fn fields(&self) -> Vec<Vec<u8>> {
+        vec![
+            rlp_uint(self.nonce as u128),
+            rlp_uint(self.gas_price),
+            rlp_uint(self.gas_limit as u128),
+            rlp_bytes(&self.to),
+            rlp_uint(self.value),
+            rlp_bytes(&self.data),
+        ]
+    }
+
+    /// Hash signed under EIP-155 for `chain_id`
+    pub fn signing_hash(&self, chain_id: u64) -> [u8; 32] {
+        let mut fields = self.fields();
+        fields.extend([rlp_uint(chain_id as u128), rlp_uint(0), rlp_uint(0)]);
+        keccak256(&rlp_list(&fields))
+    }
+
+    /// Signed, RLP-encoded transaction ready for eth_sendRawTransaction
+    pub fn sign(&self, signer: &EvmSigner, chain_id: u64) -> Vec<u8> {
+        let signature = signer.sign_hash(&self.signing_hash(chain_id));
+        let recovery_id = (signature[64] - 27) as u128;
+        let mut fields = self.fields();
+        fields.extend([
+            rlp_uint(recovery_id + chain_id as u128 * 2 + 35),
+            rlp_scalar(&signature[..32]),
+            rlp_scalar(&signature[32..64]),
+        ]);
+        rlp_list(&fields)
+    }
+}
+
+/// Periodically reads the account's balance and exchange allowance
+#[derive(Debug, Clone)]
+pub struct BalanceMonitor {
+    rpc: PolygonRpc,
+    account: String,
+    usdc_address: String,
+    exchange_address: String,
+}
+
+impl BalanceMonitor {
+    pub fn new(rpc: PolygonRpc, account: &str, usdc_address: &str, exchange_address: &str) -> Self {
+        Self {
+            rpc,
+            account: account.to_string(),
+            usdc_address: usdc_address.to_string(),
+            exchange_address: exchange_address.to_string(),
+        }
+    }
+
+    /// The account being watched
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    /// Read the current on-chain state
+    pub async fn read(&self) -> Result<OnChainAccount, RpcError> {
+        let usdc_balance = self
+            .rpc
+            .usdc_balance(&self.usdc_address, &self.account)
+            .await?;
+        let exchange_allowance = self
+            .rpc
+            .usdc_allowance(&self.usdc_address, &self.account, &self.exchange_address)
+            .await?;
+        Ok(OnChainAccount {
+            usdc_balance,
+            exchange_allowance,
+            updated_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        })
+    }
+
+    /// Refresh the shared snapshot forever on a fixed interval
+    pub async fn run_periodic(self, state: OnChainState, interval: Duration) {
+        loop {
+            match self.read().await {
+                Ok(account) => *state.write().await = Some(account),
+                Err(e) => println!("⚠️ [Polygon] Balance read failed: {}", e),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+/// Polygon RPC errors
+#[derive(Debug, Clone)]
+pub enum RpcError {
+    Http(String),
+    Rpc(String),
+    Decode(String),
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Http(msg) => write!(f, "RPC request failed: {}", msg),
+            Self::Rpc(msg) => write!(f, "RPC error: {}", msg),
+            Self::Decode(msg) => write!(f, "Invalid RPC data: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::Secret;
+
+    #[test]
+    fn test_encode_erc20_calls() {
+        let owner = "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23";
+        let data = encode_call("balanceOf(address)", &[address(owner).unwrap()]);
+        assert_eq!(
+            data,
+            "0x70a082310000000000000000000000002c7536e3605d9c16a7a3d7b1898e529396a65c23"
+        );
+        assert_eq!(
+            hex::encode(selector("allowance(address,address)")),
+            "dd62ed3e"
+        );
+    }
+
+    #[test]
+    fn test_decode_usdc_amount() {
+        // 12.5 USDC
+        let word = format!("0x{:064x}", 12_500_000u64);
+        assert_eq!(decode_amount(&word, USDC_DECIMALS).unwrap(), 12.5);
+
+        // Unlimited approval
+        let max = format!("0x{}", "f".repeat(64));
+        assert!(decode_amount(&max, USDC_DECIMALS).unwrap() > 1e70);
+
+        assert!(decode_amount("0x1234", USDC_DECIMALS).is_err());
+    }
+
+    #[test]
+    fn test_rlp_encoding() {
+        assert_eq!(rlp_bytes(b"dog"), vec![0x83, b'd', b'o', b'g']);
+        assert_eq!(rlp_uint(0), vec![0x80]);
+        assert_eq!(rlp_uint(15), vec![0x0f]);
+        assert_eq!(rlp_uint(1024), vec![0x82, 0x04, 0x00]);
+        assert_eq!(
+            rlp_list(&[rlp_bytes(b"cat"), rlp_bytes(b"dog")]),
+            hex::decode("c88363617483646f67").unwrap()
+        );
+        assert_eq!(rlp_bytes(&[0xaa; 56])[..2], [0xb8, 56]);
+    }
+
+    #[test]
+    fn test_eip155_signing() {
+        // Example transaction from the EIP-155 specification
+        let signer =
+            EvmSigner::from_private_key(&Secret::new(format!("0x{}", "46".repeat(32)))).unwrap();
+        let tx = LegacyTransaction {
+            nonce: 9,
+            gas_price: 20_000_000_000,
+            gas_limit: 21_000,
+            to: [0x35; 20],
+            value: 1_000_000_000_000_000_000,
+            data: Vec::new(),
+        };
+        assert_eq!(
+            hex::encode(tx.signing_hash(1)),
+            "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+        );
+        assert_eq!(
+            hex::encode(tx.sign(&signer, 1)),
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        );
+        assert_eq!(decode_quantity("0x1a").unwrap(), 26);
+    }
+}
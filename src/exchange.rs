@@ -0,0 +1,412 @@
+//! Exchange adapters
+//!
+//! One interface over venues other than the Polymarket CLOB, which the
+//! pipeline trades on directly. Solana reads the market accounts of a
+//! binary-market program (e.g. a devnet deployment) and places orders as
+//! instructions to it. Markets come back as the shared `Market` type, so a
+//! question listed on both chains can be compared directly with the
+//! Polymarket cache. The agent only reads Solana markets, to compare prices:
+//! it never attaches a trader, so placing orders there is library-only and
+//! not wired into the pipeline.
+//!
+//! The Solana program is expected to use Anchor's encoding: an 8-byte
+//! discriminator (`sha256("account:Market")[..8]`) followed by
+//!
+//! | field        | type   | notes                                  |
+//! |--------------|--------|----------------------------------------|
+//! | `yes_mint`   | Pubkey | outcome token, traded as the token id  |
+//! | `no_mint`    | Pubkey |                                        |
+//! | `yes_price`  | u64    | micro-USDC per share (1_000_000 = $1)  |
+//! | `no_price`   | u64    | micro-USDC per share                   |
+//! | `liquidity`  | u64    | micro-USDC resting on the book         |
+//! | `volume_24h` | u64    | micro-USDC traded in the last 24 hours |
+//! | `closed`     | bool   |                                        |
+//! | `question`   | string | u32 length, then UTF-8                 |
+//!
+//! and a `place_order` instruction (`sha256("global:place_order")[..8]`)
+//! taking the outcome mint, side (0 buy, 1 sell), limit price in micro-USDC
+//! and size in micro-shares, against the market (writable) and the trader
+//! (signer).
+
+use crate::orders::OrderRequest;
+use crate::solana::SolanaManager;
+use crate::types::{Market, MarketId, Side};
+use sha2::{Digest, Sha256};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Micro-units per USDC (prices) or per share (sizes) on the Solana program
+const MICRO: f64 = 1_000_000.0;
+
+/// Boxed future returned by adapter calls
+pub type AdapterFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, AdapterError>> + Send + 'a>>;
+
+#[derive(Debug)]
+pub enum AdapterError {
+    /// Markets couldn't be read from the venue
+    Fetch(String),
+    /// The venue refused (or never received) the order
+    Rejected(String),
+    /// The adapter isn't set up to trade (missing credentials or keypair)
+    Unavailable(&'static str),
+}
+
+impl std::fmt::Display for AdapterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fetch(e) => write!(f, "Market fetch failed: {}", e),
+            Self::Rejected(e) => write!(f, "Order rejected: {}", e),
+            Self::Unavailable(why) => write!(f, "Trading unavailable: {}", why),
+        }
+    }
+}
+
+impl std::error::Error for AdapterError {}
+
+/// A venue's market data and order entry
+pub trait ExchangeAdapter: Send + Sync {
+    /// Venue name, for logs and cross-venue comparison
+    fn venue(&self) -> &'static str;
+
+    /// Open markets on the venue
+    fn fetch_markets(&self) -> AdapterFuture<'_, Vec<Market>>;
+
+    /// Place an order, returning the venue's reference for it
+    /// (order id or transaction signature)
+    fn place_order<'a>(&'a self, request: &'a OrderRequest) -> AdapterFuture<'a, String>;
+}
+
+/// A binary-market program on Solana
+pub struct SolanaAdapter {
+    rpc: Arc<SolanaManager>,
+    program: Pubkey,
+    /// Signs and pays for orders; markets are readable without it
+    trader: Option<Keypair>,
+}
+
+impl SolanaAdapter {
+    pub fn new(rpc: Arc<SolanaManager>, program: &str) -> Result<Self, AdapterError> {
+        let program = Pubkey::from_str(program)
+            .map_err(|_| AdapterError::Fetch(format!("invalid program id {}", program)))?;
+        Ok(Self {
+            rpc,
+            program,
+            trader: None,
+        })
+    }
+
+    /// Place orders from this wallet
+    pub fn with_trader(mut self, trader: Keypair) -> Self {
+        self.trader = Some(trader);
+        self
+    }
+
+    /// The `place_order` instruction for `request`
+    pub fn order_instruction(
+        &self,
+        trader: &Pubkey,
+        request: &OrderRequest,
+    ) -> Result<Instruction, AdapterError> {
+        let pubkey = |value: &str| {
+            Pubkey::from_str(value)
+                .map_err(|_| AdapterError::Rejected(format!("not a Solana address: {}", value)))
+        };
+        let (market, mint) = (
+            pubkey(request.market.as_str())?,
+            pubkey(request.token_id.as_str())?,
+        );
+
+        let mut data = discriminator("global:place_order").to_vec();
+        data.extend_from_slice(mint.as_ref());
+        data.push(match request.side {
+            Side::Buy => 0,
+            Side::Sell => 1,
+        });
+        data.extend_from_slice(&micro(request.price).to_le_bytes());
+        data.extend_from_slice(&micro(request.size).to_le_bytes());
+        Ok(Instruction::new_with_bytes(
+            self.program,
+            &data,
+            vec![
+                AccountMeta::new(market, false),
+                AccountMeta::new(*trader, true),
+            ],
+        ))
+    }
+}
+
+impl ExchangeAdapter for SolanaAdapter {
+    fn venue(&self) -> &'static str {
+        "solana"
+    }
+
+    fn fetch_markets(&self) -> AdapterFuture<'_, Vec<Market>> {
+        Box::pin(async move {
+            let accounts = self
+                .rpc
+                .program_accounts(&self.program, &discriminator("account:Market"))
+                .await
+                .map_err(|e| AdapterError::Fetch(e.to_string()))?;
+            Ok(accounts
+                .iter()
+                .filter_map(|(address, data)| decode_market(address, data))
+                .filter(|market| market.active)
+                .collect())
+        })
+    }
+
+    fn place_order<'a>(&'a self, request: &'a OrderRequest) -> AdapterFuture<'a, String> {
+        Box::pin(async move {
+            let trader = self
+                .trader
+                .as_ref()
+                .ok_or(AdapterError::Unavailable("no Solana keypair"))?;
+            let instruction = self.order_instruction(&trader.pubkey(), request)?;
+            let signature = self
+                .rpc
+                .send_transaction(trader, &[instruction])
+                .await
+                .map_err(|e| AdapterError::Rejected(e.to_string()))?;
+            println!(
+                "📤 [Solana] Placed {:?} {:.2} {} @ {} ({})",
+                request.side, request.size, request.token_id, request.price, signature
+            );
+            Ok(signature.to_string())
+        })
+    }
+}
+
+/// Anchor discriminator: the first 8 bytes of `sha256(preimage)`
+fn discriminator(preimage: &str) -> [u8; 8] {
+    let hash = Sha256::digest(preimage.as_bytes());
+    let mut out = [0u8; 8];
+    out.copy_from_slice(&hash[..8]);
+    out
+}
+
+/// A price or size in the program's micro-units
+fn micro(value: f64) -> u64 {
+    (value * MICRO).round().max(0.0) as u64
+}
+
+/// Reads the market account layout field by field
+struct AccountReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> AccountReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(head)
+    }
+
+    fn pubkey(&mut self) -> Option<Pubkey> {
+        Pubkey::try_from(self.take(32)?).ok()
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = u32::from_le_bytes(self.take(4)?.try_into().ok()?) as usize;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+}
+
+/// A market account as a `Market`, or `None` if it doesn't fit the layout
+fn decode_market(address: &Pubkey, data: &[u8]) -> Option<Market> {
+    let mut reader = AccountReader { data };
+    if reader.take(8)? != discriminator("account:Market") {
+        return None;
+    }
+    let (yes_mint, no_mint) = (reader.pubkey()?, reader.pubkey()?);
+    let (yes_price, no_price) = (reader.u64()?, reader.u64()?);
+    let (liquidity, volume_24h) = (reader.u64()?, reader.u64()?);
+    let closed = reader.take(1)?[0] != 0;
+    let question = reader.string()?;
+
+    let usdc = |micro: u64| micro as f64 / MICRO;
+    Some(Market {
+        id: MarketId::from(address.to_string()),
+        question,
+        slug: address.to_string(),
+        outcomes: vec!["Yes".to_string(), "No".to_string()],
+        outcome_prices: vec![usdc(yes_price), usdc(no_price)],
+        clob_token_ids: vec![yes_mint.to_string().into(), no_mint.to_string().into()],
+        best_bid: None,
+        best_ask: None,
+        maker_base_fee: 0,
+        taker_base_fee: 0,
+        liquidity: Some(usdc(liquidity)),
+        volume_24hr: Some(usdc(volume_24h)),
+        active: !closed,
+        accepting_orders: !closed,
+        condition_id: String::new(),
+        category: "solana".to_string(),
+        tick_size: 1.0 / MICRO,
+        min_order_size: 0.0,
+        outcome_quotes: Vec::new(),
+    })
+}
+
+/// One question listed on two venues
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceGap {
+    pub question: String,
+    pub left: MarketId,
+    pub right: MarketId,
+    /// Right venue's YES price minus the left's
+    pub gap: f64,
+}
+
+/// Questions listed on both venues, widest YES price gap first
+///
+/// Questions are matched on their letters and digits, case-insensitively.
+pub fn price_gaps(left: &[Market], right: &[Market]) -> Vec<PriceGap> {
+    let normalize = |question: &str| -> String {
+        question
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let mut gaps: Vec<PriceGap> = left
+        .iter()
+        .filter_map(|l| {
+            let key = normalize(&l.question);
+            let r = right.iter().find(|r| normalize(&r.question) == key)?;
+            Some(PriceGap {
+                question: l.question.clone(),
+                left: l.id.clone(),
+                right: r.id.clone(),
+                gap: r.outcome_prices.first()? - l.outcome_prices.first()?,
+            })
+        })
+        .collect();
+    gaps.sort_by(|a, b| b.gap.abs().total_cmp(&a.gap.abs()));
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SolanaConfig;
+    use crate::order_spec::OrderType;
+    use crate::test_util;
+
+    fn account(yes_mint: &Pubkey, question: &str, closed: bool) -> Vec<u8> {
+        let mut data = discriminator("account:Market").to_vec();
+        data.extend_from_slice(yes_mint.as_ref());
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        for value in [420_000u64, 600_000, 2_500_000_000, 75_000_000] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.push(closed as u8);
+        data.extend_from_slice(&(question.len() as u32).to_le_bytes());
+        data.extend_from_slice(question.as_bytes());
+        data
+    }
+
+    #[test]
+    fn test_decodes_market_accounts() {
+        let (address, yes_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let market =
+            decode_market(&address, &account(&yes_mint, "Will SOL flip ETH?", false)).unwrap();
+        assert_eq!(market.id, MarketId::from(address.to_string()));
+        assert_eq!(market.question, "Will SOL flip ETH?");
+        assert_eq!(market.outcome_prices, vec![0.42, 0.60]);
+        assert_eq!(market.clob_token_ids[0].as_str(), yes_mint.to_string());
+        assert_eq!(market.liquidity, Some(2_500.0));
+        assert!(market.accepting_orders);
+
+        let closed = decode_market(&address, &account(&yes_mint, "Done?", true)).unwrap();
+        assert!(!closed.active);
+
+        // Truncated, or another account type
+        let data = account(&yes_mint, "Cut short", false);
+        assert!(decode_market(&address, &data[..data.len() - 3]).is_none());
+        assert!(decode_market(&address, &[0u8; 128]).is_none());
+    }
+
+    #[test]
+    fn test_encodes_place_order_instruction() {
+        let rpc = Arc::new(SolanaManager::new(&SolanaConfig::default()));
+        let program = Pubkey::new_unique();
+        let adapter = SolanaAdapter::new(rpc, &program.to_string()).unwrap();
+        let (market, mint, trader) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let request = OrderRequest {
+            market: market.to_string().into(),
+            token_id: mint.to_string().into(),
+            side: Side::Sell,
+            price: 0.43,
+            size: 12.5,
+            order_type: OrderType::Limit,
+            execution_id: None,
+        };
+
+        let instruction = adapter.order_instruction(&trader, &request).unwrap();
+        assert_eq!(instruction.program_id, program);
+        assert_eq!(
+            instruction.accounts,
+            vec![
+                AccountMeta::new(market, false),
+                AccountMeta::new(trader, true)
+            ]
+        );
+        let data = &instruction.data;
+        assert_eq!(data[..8], discriminator("global:place_order"));
+        assert_eq!(data[8..40], *mint.as_ref());
+        assert_eq!(data[40], 1);
+        assert_eq!(data[41..49], 430_000u64.to_le_bytes());
+        assert_eq!(data[49..57], 12_500_000u64.to_le_bytes());
+
+        let polymarket_token = OrderRequest {
+            token_id: "7132".into(),
+            ..request
+        };
+        assert!(adapter
+            .order_instruction(&trader, &polymarket_token)
+            .is_err());
+    }
+
+    #[test]
+    fn test_matches_questions_across_venues() {
+        let market = |id: &str, question: &str, yes: f64| Market {
+            id: id.into(),
+            question: question.to_string(),
+            outcome_prices: vec![yes, 1.0 - yes],
+            ..test_util::market(yes, 1.0 - yes)
+        };
+        let polymarket = [
+            market("p1", "Will BTC close above $100k?", 0.40),
+            market("p2", "Will it rain in Paris?", 0.70),
+            market("p3", "Fed cuts in March?", 0.20),
+        ];
+        let solana = [
+            market("s1", "will btc close above 100k", 0.46),
+            market("s2", "Fed cuts in March?", 0.21),
+        ];
+
+        let gaps = price_gaps(&polymarket, &solana);
+        let pairs: Vec<(&str, &str)> = gaps
+            .iter()
+            .map(|g| (g.left.as_str(), g.right.as_str()))
+            .collect();
+        assert_eq!(pairs, vec![("p1", "s1"), ("p3", "s2")]);
+        assert!((gaps[0].gap - 0.06).abs() < 1e-9);
+    }
+}
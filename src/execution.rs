@@ -1,15 +1,71 @@
 use crate::fees::FeeModel;
-use crate::fills::FillModel;
+use crate::fills::{EdgeDecay, FillModel};
+use crate::gas::GasModel;
 use crate::latency::LatencyModel;
-use crate::types::{ExecutionResult, OrderBook, Side};
+use crate::money::{self, Decimal};
+use crate::order_spec::{OrderTerms, OrderType};
+use crate::pretrade::PreTradeChecks;
+use crate::types::{ExecutionResult, MarketId, OrderBook, Side, TokenId};
 use crate::wallet::Wallet;
-use std::thread;
+use crate::websocket::OrderBookStore;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Sequence number that keeps execution ids unique within a millisecond
+static EXECUTION_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Current unix time in milliseconds
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Generate a unique execution id
+fn next_execution_id(timestamp_ms: u64) -> String {
+    let seq = EXECUTION_SEQ.fetch_add(1, Ordering::Relaxed);
+    format!("exec-{}-{}", timestamp_ms, seq)
+}
+
+/// Allocate an execution id ahead of time (spend intents are keyed by it)
+pub fn new_execution_id() -> String {
+    next_execution_id(now_ms())
+}
+
+/// Expected cost of one leg at the current book, without placing it
+#[derive(Debug, Clone, Serialize)]
+pub struct LegQuote {
+    pub token_id: TokenId,
+    pub side: Side,
+    pub requested_size: f64,
+    /// Fill expected under the fill model's race for the top of book
+    pub expected_fill: f64,
+    /// Average price walking the book for the requested size
+    pub execution_price: f64,
+    pub midpoint: f64,
+    pub slippage: f64,
+    pub price_impact: Decimal,
+    pub fee: Decimal,
+    pub gas_cost: Decimal,
+    pub total_cost: Decimal,
+}
 
 /// Execution simulator
 #[derive(Debug)]
 pub struct ExecutionEngine {
     pub fee_model: FeeModel,
     pub latency_model: LatencyModel,
+    pub gas_model: GasModel,
+    pub fill_model: FillModel,
+    /// Competitors racing for the edge while orders are in flight
+    pub edge_decay: EdgeDecay,
+    /// Streamed books preferred over the caller's snapshot
+    pub order_books: Option<Arc<OrderBookStore>>,
+    /// Allowance, exposure, slippage, and other limits checked before placing
+    pub checks: PreTradeChecks,
 }
 
 impl ExecutionEngine {
@@ -17,30 +73,177 @@ impl ExecutionEngine {
         Self {
             fee_model,
             latency_model,
+            gas_model: GasModel::free(),
+            fill_model: FillModel::default(),
+            edge_decay: EdgeDecay::default(),
+            order_books: None,
+            checks: PreTradeChecks::default(),
         }
     }
 
-    /// Simulate order execution
-    pub fn execute(
+    /// Simulate fills with competition for top-of-book liquidity
+    pub fn with_fill_model(mut self, fill_model: FillModel) -> Self {
+        self.fill_model = fill_model;
+        self
+    }
+
+    /// Let competitors take the top of book while orders are in flight
+    pub fn with_edge_decay(mut self, edge_decay: EdgeDecay) -> Self {
+        self.edge_decay = edge_decay;
+        self
+    }
+
+    /// Price orders against locally maintained WebSocket books when live
+    pub fn with_order_books(mut self, order_books: Arc<OrderBookStore>) -> Self {
+        self.order_books = Some(order_books);
+        self
+    }
+
+    /// Charge gas/relayer costs on every execution
+    pub fn with_gas_model(mut self, gas_model: GasModel) -> Self {
+        self.gas_model = gas_model;
+        self
+    }
+
+    /// Limits every trade is checked against before it is placed
+    pub fn with_checks(mut self, checks: PreTradeChecks) -> Self {
+        self.checks = checks;
+        self
+    }
+
+    /// Delay between submitting an order and its acknowledgement
+    pub fn expected_delay(&self) -> Duration {
+        Duration::from_millis(self.latency_model.mean_delay_ms)
+    }
+
+    /// Price a leg as `simulate` would, with expected values in place of
+    /// random draws and without waiting out the latency
+    pub fn quote(
         &self,
+        market_id: &MarketId,
+        book: &OrderBook,
+        size: f64,
+        side: Side,
+    ) -> Option<LegQuote> {
+        let execution_price = book.execution_price(size, side)?;
+        let expected_fill = self.fill_model.estimate(book, size, side).expected_size();
+        if expected_fill <= 0.0 {
+            return None;
+        }
+
+        let midpoint = book.midpoint().unwrap_or(execution_price);
+        let price_impact = money::usdc(match side {
+            Side::Buy => (execution_price - midpoint) * expected_fill,
+            Side::Sell => (midpoint - execution_price) * expected_fill,
+        });
+        let notional =
+            money::charge(money::from_f64(execution_price) * money::from_f64(expected_fill));
+        let fee = self.fee_model.calculate_in(market_id, notional, false); // Taker
+        let gas_cost = money::charge(money::from_f64(self.gas_model.cost(1)));
+
+        Some(LegQuote {
+            token_id: book.token_id.clone(),
+            side,
+            requested_size: size,
+            expected_fill,
+            execution_price,
+            midpoint,
+            slippage: ((execution_price - midpoint) / midpoint).abs(),
+            price_impact,
+            fee,
+            gas_cost,
+            total_cost: notional + fee + gas_cost,
+        })
+    }
+
+    /// Simulate a market order fill against the book without touching the wallet
+    pub async fn simulate(
+        &self,
+        market_id: &MarketId,
         book: &OrderBook,
         size: f64,
         side: Side,
-        wallet: &mut Wallet,
     ) -> Option<ExecutionResult> {
+        self.simulate_order(market_id, book, size, OrderTerms::market(side))
+            .await
+    }
+
+    /// Simulate an order of any type without touching the wallet
+    pub async fn simulate_order(
+        &self,
+        market_id: &MarketId,
+        book: &OrderBook,
+        size: f64,
+        terms: OrderTerms,
+    ) -> Option<ExecutionResult> {
+        self.simulate_as(new_execution_id(), market_id, book, size, terms)
+            .await
+    }
+
+    async fn simulate_as(
+        &self,
+        execution_id: String,
+        market_id: &MarketId,
+        book: &OrderBook,
+        size: f64,
+        terms: OrderTerms,
+    ) -> Option<ExecutionResult> {
+        let submitted_at_ms = now_ms();
+        let side = terms.side;
+
+        // Makers are not simulated: a post-only order never fills on arrival
+        if terms.order_type == OrderType::PostOnly {
+            return None;
+        }
+
+        // Prefer the live streamed book over the caller's (possibly older) snapshot
+        let streamed = match &self.order_books {
+            Some(books) => books.book(&book.token_id).await,
+            None => None,
+        };
+        let reachable = terms.reachable(streamed.as_ref().unwrap_or(book));
+        let book = &reachable;
+
+        // Limit and IOC orders take what is there within their price; market
+        // and FOK orders need the full size
+        let take = match terms.order_type {
+            OrderType::Limit | OrderType::Ioc => {
+                let depth = match side {
+                    Side::Buy => book.total_ask_liquidity(),
+                    Side::Sell => book.total_bid_liquidity(),
+                };
+                size.min(depth)
+            }
+            _ => size,
+        };
+        if take <= 0.0 {
+            return None;
+        }
+
         // 1. Calculate initial theoretical price
-        let initial_price = book.execution_price(size, side)?;
+        let initial_price = book.execution_price(take, side)?;
 
         // 2. Apply latency and adverse selection
-        let (exec_price, delay) = self.latency_model.apply(initial_price);
+        let (mut exec_price, delay) = self.latency_model.apply(initial_price);
 
         // Simulate the delay
         if !delay.is_zero() {
-            thread::sleep(delay);
+            tokio::time::sleep(delay).await;
         }
+        let acked_at_ms = now_ms();
+
+        // A competitor that arrived first took the top level; we walk what's left
+        let raced;
+        let book = if self.edge_decay.captured(delay) {
+            raced = book.without_top_level(side);
+            exec_price *= raced.execution_price(take, side)? / initial_price;
+            &raced
+        } else {
+            book
+        };
 
         // 3. Check fill ratio
-        let filled_size = FillModel::filled_size(book, size, side);
+        let filled_size = self.fill_model.estimate_order(book, take, &terms).sample();
         if filled_size <= 0.0 {
             return None;
         }
@@ -48,52 +251,107 @@ impl ExecutionEngine {
         // 4. Calculate execution metrics
         let midpoint = book.midpoint().unwrap_or(exec_price);
         let slippage = ((exec_price - midpoint) / midpoint).abs();
+        let price_impact = money::usdc(match side {
+            Side::Buy => (exec_price - midpoint) * filled_size,
+            Side::Sell => (midpoint - exec_price) * filled_size,
+        });
 
-        // 5. Calculate costs
-        let notional = exec_price * filled_size;
-        let fee = self.fee_model.calculate(notional, false); // Taker
-        let total_cost = notional + fee;
+        // 5. Calculate costs (exact once in USDC, each part rounded up as a charge)
+        let notional = money::charge(money::from_f64(exec_price) * money::from_f64(filled_size));
+        let fee = self.fee_model.calculate_in(market_id, notional, false); // Taker
+        let gas_cost = money::charge(money::from_f64(self.gas_model.cost(1)));
+        let total_cost = notional + fee + gas_cost;
+
+        Some(ExecutionResult {
+            execution_id,
+            market_id: market_id.clone(),
+            token_id: book.token_id.clone(),
+            side,
+            requested_size: size,
+            filled_size,
+            execution_price: exec_price,
+            price_impact,
+            fee_paid: fee,
+            gas_cost,
+            slippage,
+            total_cost,
+            maker: None,
+            submitted_at_ms,
+            acked_at_ms,
+            filled_at_ms: now_ms(),
+            success: true,
+        })
+    }
+
+    /// Simulate market order execution
+    pub async fn execute(
+        &self,
+        market_id: &MarketId,
+        book: &OrderBook,
+        size: f64,
+        side: Side,
+        wallet: &mut Wallet,
+    ) -> Option<ExecutionResult> {
+        let terms = OrderTerms::market(side);
+        self.execute_as(new_execution_id(), market_id, book, size, terms, wallet)
+            .await
+    }
+
+    /// Simulate order execution under a pre-allocated execution id
+    pub async fn execute_as(
+        &self,
+        execution_id: String,
+        market_id: &MarketId,
+        book: &OrderBook,
+        size: f64,
+        terms: OrderTerms,
+        wallet: &mut Wallet,
+    ) -> Option<ExecutionResult> {
+        let side = terms.side;
+        let result = self
+            .simulate_as(execution_id, market_id, book, size, terms)
+            .await?;
+        let total_cost = result.total_cost;
 
         // 6. Check permission (ERC-7715)
-        if !wallet.check_permission(total_cost) {
-            let remaining = wallet.daily_limit - wallet.spent_today;
-            println!("❌ [Smart Account] Permission Denied: Trade value ${:.2} exceeds remaining Daily Allowance (${:.2})", 
-                total_cost, remaining);
+        wallet.check_reset();
+        if let Err(rejection) = self.checks.allowance(total_cost, wallet.remaining()) {
+            println!("❌ [Smart Account] Permission Denied: Trade {}", rejection);
             return None;
         }
 
         // 7. Execute via Smart Account
         if wallet.record_spend(total_cost) {
-            let remaining = wallet.daily_limit - wallet.spent_today;
+            let remaining = wallet.remaining();
             println!(
                 "✅ [Smart Account] Batch Executed: Swap {:.2} USDC -> Tokens",
                 total_cost
             );
             println!(
-                "   ↳ Cost: ${:.2} | Latency: {:?} | Remaining Allowance: ${:.2}",
-                total_cost, delay, remaining
+                "   ↳ Cost: ${:.2} | Latency: {}ms | Remaining Allowance: ${:.2}",
+                total_cost,
+                result.acked_at_ms - result.submitted_at_ms,
+                remaining
             );
 
             // Track position
-            let token_id = &book.token_id;
             wallet.open_position(
-                token_id.clone(),
+                result.token_id.clone(),
                 side,
-                filled_size,
-                exec_price,
+                result.filled_size,
+                result.execution_price,
                 crate::wallet::Wallet::current_timestamp(),
             );
 
             wallet.record_trade(true);
 
-            Some(ExecutionResult {
-                filled_size: filled_size,
-                execution_price: exec_price,
-                fee_paid: fee,
-                slippage,
-                total_cost,
-                success: true,
-            })
+            // Count towards the 30-day volume that sets the taker tier
+            self.fee_model.record_volume(
+                crate::wallet::Wallet::current_timestamp(),
+                result.execution_price * result.filled_size,
+            );
+
+            Some(result)
         } else {
             None
         }
@@ -104,20 +362,18 @@ impl ExecutionEngine {
 mod tests {
     use super::*;
     use crate::latency::LatencyModel;
+    use crate::money::usdc;
     use crate::types::{OrderBook, PriceLevel};
 
-    #[test]
-    fn test_execution_permission_logic() {
-        let fee_model = FeeModel {
-            maker_fee_bps: 0,
-            taker_fee_bps: 0,
-        };
+    #[tokio::test]
+    async fn test_execution_permission_logic() {
+        let fee_model = FeeModel::new(0, 0);
         let latency_model = LatencyModel::new(0, 0.0);
         let engine = ExecutionEngine::new(fee_model, latency_model);
 
-        let mut wallet = Wallet::new(10.0);
+        let mut wallet = Wallet::new(usdc(10.0));
         let book = OrderBook {
-            token_id: "t1".to_string(),
+            token_id: "t1".into(),
             bids: vec![],
             asks: vec![PriceLevel {
                 price: 0.5,
@@ -127,13 +383,207 @@ mod tests {
         };
 
         // 1. Valid trade ($5 cost)
-        let res = engine.execute(&book, 10.0, Side::Buy, &mut wallet);
+        let res = engine
+            .execute(&"m1".into(), &book, 10.0, Side::Buy, &mut wallet)
+            .await;
         assert!(res.is_some());
-        assert_eq!(wallet.spent_today, 5.0);
+        assert_eq!(wallet.spent_today(), usdc(5.0));
 
         // 2. Invalid trade ($6 cost, remaining limit $5)
-        let res_fail = engine.execute(&book, 12.0, Side::Buy, &mut wallet);
+        let res_fail = engine
+            .execute(&"m1".into(), &book, 12.0, Side::Buy, &mut wallet)
+            .await;
         assert!(res_fail.is_none());
-        assert_eq!(wallet.spent_today, 5.0);
+        assert_eq!(wallet.spent_today(), usdc(5.0));
+    }
+
+    #[test]
+    fn test_quote_walks_book_and_charges_fees() {
+        let engine = ExecutionEngine::new(FeeModel::new(0, 200), LatencyModel::new(150, 0.0));
+        let book = OrderBook {
+            token_id: "t1".into(),
+            bids: vec![PriceLevel {
+                price: 0.48,
+                size: 100.0,
+            }],
+            asks: vec![
+                PriceLevel {
+                    price: 0.50,
+                    size: 50.0,
+                },
+                PriceLevel {
+                    price: 0.52,
+                    size: 50.0,
+                },
+            ],
+            timestamp: 0,
+        };
+
+        let quote = engine.quote(&"m1".into(), &book, 100.0, Side::Buy).unwrap();
+        assert_eq!(quote.expected_fill, 100.0);
+        assert!((quote.execution_price - 0.51).abs() < 1e-9);
+        assert!((quote.midpoint - 0.49).abs() < 1e-9);
+        assert_eq!(quote.fee, usdc(1.02));
+        assert_eq!(quote.total_cost, usdc(52.02));
+        assert_eq!(engine.expected_delay(), Duration::from_millis(150));
+
+        // Nothing to buy against
+        let empty = OrderBook {
+            asks: vec![],
+            ..book
+        };
+        assert!(engine
+            .quote(&"m1".into(), &empty, 10.0, Side::Buy)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_simulate_leaves_wallet_untouched() {
+        let fee_model = FeeModel::new(0, 0);
+        let engine = ExecutionEngine::new(fee_model, LatencyModel::new(0, 0.0));
+        let book = OrderBook {
+            token_id: "t1".into(),
+            bids: vec![],
+            asks: vec![PriceLevel {
+                price: 0.5,
+                size: 100.0,
+            }],
+            timestamp: 0,
+        };
+
+        // Far beyond any allowance, but simulation never checks the wallet
+        let res = engine
+            .simulate(&"m1".into(), &book, 80.0, Side::Buy)
+            .await
+            .unwrap();
+        assert_eq!(res.filled_size, 80.0);
+        assert_eq!(res.total_cost, usdc(40.0));
+    }
+
+    #[tokio::test]
+    async fn test_competitor_takes_top_of_book_in_flight() {
+        let engine = ExecutionEngine::new(FeeModel::new(0, 0), LatencyModel::new(1, 0.0))
+            .with_edge_decay(EdgeDecay::new(1e9));
+        let book = OrderBook {
+            token_id: "t1".into(),
+            bids: vec![],
+            asks: vec![
+                PriceLevel {
+                    price: 0.45,
+                    size: 100.0,
+                },
+                PriceLevel {
+                    price: 0.55,
+                    size: 100.0,
+                },
+            ],
+            timestamp: 0,
+        };
+
+        // The 0.45 level is gone by the time the order lands
+        let res = engine
+            .simulate(&"m1".into(), &book, 10.0, Side::Buy)
+            .await
+            .unwrap();
+        assert!((res.execution_price - 0.55).abs() < 1e-9);
+
+        // Only one level deep: nothing left to fill
+        let mut shallow = book.clone();
+        shallow.asks.truncate(1);
+        assert!(engine
+            .simulate(&"m1".into(), &shallow, 10.0, Side::Buy)
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_execution_includes_gas_cost() {
+        let fee_model = FeeModel::new(0, 0);
+        let engine = ExecutionEngine::new(fee_model, LatencyModel::new(0, 0.0))
+            .with_gas_model(GasModel::new("polygon", 0.01));
+
+        let mut wallet = Wallet::new(usdc(10.0));
+        let book = OrderBook {
+            token_id: "t1".into(),
+            bids: vec![],
+            asks: vec![PriceLevel {
+                price: 0.5,
+                size: 100.0,
+            }],
+            timestamp: 0,
+        };
+
+        let res = engine
+            .execute(&"m1".into(), &book, 10.0, Side::Buy, &mut wallet)
+            .await
+            .unwrap();
+        assert_eq!(res.gas_cost, usdc(0.01));
+        assert_eq!(res.market_id, "m1");
+        assert_eq!(res.token_id, "t1");
+        assert_eq!(res.requested_size, 10.0);
+        assert!(res.submitted_at_ms <= res.acked_at_ms && res.acked_at_ms <= res.filled_at_ms);
+        assert_eq!(res.total_cost, usdc(5.01));
+        assert_eq!(wallet.spent_today(), usdc(5.01));
+    }
+
+    #[tokio::test]
+    async fn test_execution_ids_and_timing() {
+        let engine = ExecutionEngine::new(FeeModel::new(0, 200), LatencyModel::new(20, 0.0));
+        let mut wallet = Wallet::new(usdc(100.0));
+        let book = OrderBook {
+            token_id: "t1".into(),
+            bids: vec![PriceLevel {
+                price: 0.48,
+                size: 100.0,
+            }],
+            asks: vec![PriceLevel {
+                price: 0.5,
+                size: 100.0,
+            }],
+            timestamp: 0,
+        };
+
+        let first = engine
+            .execute(&"m1".into(), &book, 10.0, Side::Buy, &mut wallet)
+            .await
+            .unwrap();
+        let second = engine
+            .execute(&"m1".into(), &book, 10.0, Side::Buy, &mut wallet)
+            .await
+            .unwrap();
+        assert!(first.execution_id.starts_with("exec-"));
+        assert_ne!(first.execution_id, second.execution_id);
+        assert_eq!(first.side, Side::Buy);
+        assert_eq!(first.filled_size, 10.0);
+
+        // A pre-allocated id is kept
+        let execution_id = new_execution_id();
+        let res = engine
+            .execute_as(
+                execution_id.clone(),
+                &"m1".into(),
+                &book,
+                10.0,
+                OrderTerms::market(Side::Buy),
+                &mut wallet,
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.execution_id, execution_id);
+
+        // Network latency then matching make up the whole round trip
+        assert!(res.submitted_at_ms > 0);
+        let ack_latency = res.acked_at_ms - res.submitted_at_ms;
+        let fill_latency = res.filled_at_ms - res.acked_at_ms;
+        assert!(ack_latency >= 20);
+        assert_eq!(
+            ack_latency + fill_latency,
+            res.filled_at_ms - res.submitted_at_ms
+        );
+
+        // Price impact is paid on top of the midpoint, fees on the notional
+        assert_eq!(res.price_impact, usdc(0.1));
+        assert_eq!(res.fee_paid, usdc(0.1));
+        assert_eq!(res.total_cost, usdc(5.1));
     }
 }
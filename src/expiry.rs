@@ -0,0 +1,94 @@
+//! Permission expiry warnings
+//!
+//! The agent stops trading the moment its permission grant expires. The
+//! engine checks the grant every tick and raises one notification per
+//! configured horizon (24h and 1h out by default) so users renew in time.
+
+use crate::metamask::PermissionGrant;
+
+/// Tracks which expiry horizons have already been announced for a grant
+#[derive(Debug)]
+pub struct ExpiryWarnings {
+    /// Seconds before expiry to warn at, widest first
+    horizons: Vec<u64>,
+    /// Grant (id, expiry) and the narrowest horizon announced for it
+    announced: Option<(String, u64, u64)>,
+}
+
+impl ExpiryWarnings {
+    pub fn new(horizons_secs: &[u64]) -> Self {
+        let mut horizons: Vec<u64> = horizons_secs.iter().copied().filter(|h| *h > 0).collect();
+        horizons.sort_unstable_by(|a, b| b.cmp(a));
+        horizons.dedup();
+        Self {
+            horizons,
+            announced: None,
+        }
+    }
+
+    /// Horizon the grant has newly come within, if one is due
+    ///
+    /// Each horizon fires once per grant; a renewed grant (new id or
+    /// expiry) starts over. Skipped horizons collapse into the narrowest one
+    /// reached, so a grant loaded 30 minutes before expiry warns once.
+    pub fn check(&mut self, grant: &PermissionGrant, now: u64) -> Option<u64> {
+        if grant.revoked || grant.expires_at <= now {
+            return None;
+        }
+        let remaining = grant.secs_until_expiry(now);
+        let reached = self.horizons.iter().copied().rfind(|h| remaining <= *h)?;
+        if let Some((id, expires_at, horizon)) = &self.announced {
+            if *id == grant.permission_id && *expires_at == grant.expires_at && *horizon <= reached
+            {
+                return None;
+            }
+        }
+        self.announced = Some((grant.permission_id.clone(), grant.expires_at, reached));
+        Some(reached)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metamask::GrantScope;
+    use crate::money::Decimal;
+
+    fn grant(id: &str, expires_at: u64) -> PermissionGrant {
+        PermissionGrant {
+            permission_id: id.to_string(),
+            token: "USDC".to_string(),
+            daily_limit: Decimal::ZERO,
+            spent_today: Decimal::ZERO,
+            expires_at,
+            granted_at: 0,
+            revoked: false,
+            scope: GrantScope::default(),
+        }
+    }
+
+    #[test]
+    fn test_each_horizon_fires_once() {
+        let mut warnings = ExpiryWarnings::new(&[3_600, 86_400]);
+        let g = grant("p1", 100_000);
+
+        assert_eq!(warnings.check(&g, 0), None);
+        assert_eq!(warnings.check(&g, 100_000 - 86_400), Some(86_400));
+        assert_eq!(warnings.check(&g, 100_000 - 50_000), None);
+        assert_eq!(warnings.check(&g, 100_000 - 3_600), Some(3_600));
+        assert_eq!(warnings.check(&g, 100_000 - 60), None);
+        assert_eq!(warnings.check(&g, 100_000), None);
+    }
+
+    #[test]
+    fn test_renewal_and_late_load() {
+        let mut warnings = ExpiryWarnings::new(&[86_400, 3_600]);
+
+        // Loaded 30 minutes out: only the 1h warning
+        assert_eq!(warnings.check(&grant("p1", 10_000), 8_200), Some(3_600));
+        assert_eq!(warnings.check(&grant("p1", 10_000), 8_300), None);
+
+        // Renewed grant warns again
+        assert_eq!(warnings.check(&grant("p2", 90_000), 8_300), Some(86_400));
+    }
+}
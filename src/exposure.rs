@@ -0,0 +1,162 @@
+//! Exposure netting
+//!
+//! Offsetting YES and NO holdings in the same market pay out $1 per pair
+//! whatever the outcome, so they are hedged rather than directional. This
+//! module nets them to report the true directional exposure per market.
+
+use crate::positions::Position;
+use crate::types::{Market, MarketId, Side};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Net exposure in one market
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketExposure {
+    pub market_id: MarketId,
+    /// Net YES-equivalent shares (negative: net NO)
+    pub net_delta: f64,
+    /// Shares held as offsetting YES+NO pairs
+    pub hedged_size: f64,
+    /// Value of every holding at current prices
+    pub gross_value: f64,
+    /// Value of the unhedged remainder at current prices
+    pub net_value: f64,
+}
+
+/// Exposure across all markets
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExposureReport {
+    pub markets: Vec<MarketExposure>,
+    pub gross_value: f64,
+    pub net_value: f64,
+}
+
+impl ExposureReport {
+    /// Exposure in one market, if anything is held there
+    pub fn market(&self, market_id: &MarketId) -> Option<&MarketExposure> {
+        self.markets.iter().find(|m| m.market_id == *market_id)
+    }
+}
+
+/// Per-market running totals while netting
+#[derive(Default)]
+struct Netting {
+    delta: f64,
+    gross_shares: f64,
+    gross_value: f64,
+    yes_price: Option<f64>,
+    no_price: Option<f64>,
+}
+
+/// Net positions into per-market directional exposure
+///
+/// The first outcome of a market counts as YES and any other as NO; a short
+/// position counts against its outcome. Holdings are valued at the cached
+/// outcome price, or at entry for markets no longer cached.
+pub fn net_exposure<'a>(
+    positions: impl IntoIterator<Item = &'a Position>,
+    markets: &[Market],
+) -> ExposureReport {
+    let mut netting: BTreeMap<&MarketId, Netting> = BTreeMap::new();
+    for position in positions {
+        let market = markets.iter().find(|m| m.id == position.market_id);
+        let is_yes = market
+            .and_then(|m| {
+                m.clob_token_ids
+                    .iter()
+                    .position(|t| *t == position.token_id)
+            })
+            .is_none_or(|idx| idx == 0);
+        let price = market
+            .and_then(|m| m.token_price(&position.token_id))
+            .unwrap_or(position.entry_price);
+
+        let long = position.side == Side::Buy;
+        let entry = netting.entry(&position.market_id).or_default();
+        entry.delta += if is_yes == long {
+            position.size
+        } else {
+            -position.size
+        };
+        entry.gross_shares += position.size;
+        entry.gross_value += position.size * price;
+        if is_yes {
+            entry.yes_price = Some(price);
+        } else {
+            entry.no_price = Some(price);
+        }
+    }
+
+    let mut report = ExposureReport::default();
+    for (market_id, n) in netting {
+        // Price of the side the remainder is on; the complement if only the other is known
+        let price = if n.delta >= 0.0 {
+            n.yes_price.or(n.no_price.map(|p| 1.0 - p))
+        } else {
+            n.no_price.or(n.yes_price.map(|p| 1.0 - p))
+        }
+        .unwrap_or(0.0);
+        let net_value = n.delta.abs() * price;
+
+        report.gross_value += n.gross_value;
+        report.net_value += net_value;
+        report.markets.push(MarketExposure {
+            market_id: market_id.clone(),
+            net_delta: n.delta,
+            hedged_size: ((n.gross_shares - n.delta.abs()) / 2.0).max(0.0),
+            gross_value: n.gross_value,
+            net_value,
+        });
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn market() -> Market {
+        test_util::market(0.6, 0.4)
+    }
+
+    fn position(token_id: &str, size: f64) -> Position {
+        Position {
+            market_id: "m1".into(),
+            token_id: token_id.into(),
+            side: Side::Buy,
+            size,
+            entry_price: 0.5,
+            entry_time: 0,
+            entry_spread: 0.0,
+            entry_executions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_offsetting_holdings_net_out() {
+        let markets = [market()];
+        let positions = [position("yes", 10.0), position("no", 6.0)];
+        let report = net_exposure(&positions, &markets);
+
+        let m1 = report.market(&"m1".into()).unwrap();
+        assert_eq!(m1.net_delta, 4.0);
+        assert_eq!(m1.hedged_size, 6.0);
+        assert!((m1.gross_value - 8.4).abs() < 1e-9);
+        // Only the 4 unhedged YES shares are directional
+        assert!((report.net_value - 2.4).abs() < 1e-9);
+
+        // A fully hedged bundle carries no directional exposure
+        let bundle = [position("yes", 5.0), position("no", 5.0)];
+        assert_eq!(net_exposure(&bundle, &markets).net_value, 0.0);
+    }
+
+    #[test]
+    fn test_net_no_exposure_uses_no_price() {
+        let markets = [market()];
+        let positions = [position("yes", 2.0), position("no", 7.0)];
+        let m1 = net_exposure(&positions, &markets).markets.remove(0);
+        assert_eq!(m1.net_delta, -5.0);
+        assert!((m1.net_value - 2.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,294 @@
+//! External signal ingestion
+//!
+//! Third-party models POST signals (market, direction, confidence) to the
+//! API; valid ones wait in a queue until the market's worker next scans,
+//! where the `external` strategy turns them into intents that go through
+//! the same edge, budget, and exposure checks as built-in signals.
+
+use crate::config::StrategyParams;
+use crate::fees::FeeModel;
+use crate::gas::GasModel;
+use crate::order_spec::OrderType;
+use crate::strategy::{Intent, Strategy};
+use crate::types::{Market, MarketId, Side};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Outcome a signal expects to win
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    #[serde(alias = "YES")]
+    Yes,
+    #[serde(alias = "NO")]
+    No,
+}
+
+impl Direction {
+    /// Index of the outcome in a binary market
+    fn outcome_index(self) -> usize {
+        match self {
+            Self::Yes => 0,
+            Self::No => 1,
+        }
+    }
+}
+
+/// Signal as posted by a third party
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExternalSignal {
+    pub market_id: MarketId,
+    pub direction: Direction,
+    /// Probability (0-1] the sender assigns to `direction` winning
+    pub confidence: f64,
+    /// Free-form name of the sending model
+    #[serde(default)]
+    pub source: String,
+}
+
+/// Why a posted signal was rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignalError {
+    Disabled,
+    InvalidConfidence(f64),
+    UnknownMarket(MarketId),
+    NotTradeable(MarketId),
+}
+
+impl std::fmt::Display for SignalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Disabled => write!(f, "External signals are disabled"),
+            Self::InvalidConfidence(c) => write!(f, "Confidence {} is not in (0, 1]", c),
+            Self::UnknownMarket(id) => write!(f, "Unknown market: {}", id),
+            Self::NotTradeable(id) => write!(f, "Market {} is not a tradeable binary market", id),
+        }
+    }
+}
+
+impl std::error::Error for SignalError {}
+
+/// Check a signal against the markets currently being traded
+pub fn validate(signal: &ExternalSignal, markets: &[Market]) -> Result<(), SignalError> {
+    if !signal.confidence.is_finite() || signal.confidence <= 0.0 || signal.confidence > 1.0 {
+        return Err(SignalError::InvalidConfidence(signal.confidence));
+    }
+    let market = markets
+        .iter()
+        .find(|m| m.id == signal.market_id)
+        .ok_or_else(|| SignalError::UnknownMarket(signal.market_id.clone()))?;
+    if !market.active || !market.accepting_orders || market.clob_token_ids.len() != 2 {
+        return Err(SignalError::NotTradeable(signal.market_id.clone()));
+    }
+    Ok(())
+}
+
+/// Signal waiting for its market's next scan
+#[derive(Debug, Clone)]
+struct QueuedSignal {
+    signal: ExternalSignal,
+    received_at: u64,
+}
+
+/// Latest external signal per market, dropped once stale
+#[derive(Debug)]
+pub struct ExternalSignalQueue {
+    pending: Mutex<HashMap<MarketId, QueuedSignal>>,
+    ttl_secs: u64,
+}
+
+impl ExternalSignalQueue {
+    pub fn new(ttl_secs: u64) -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+            ttl_secs,
+        }
+    }
+
+    /// Queue a signal, replacing any earlier one for the same market
+    pub fn push(&self, signal: ExternalSignal, now: u64) {
+        self.pending.lock().unwrap().insert(
+            signal.market_id.clone(),
+            QueuedSignal {
+                signal,
+                received_at: now,
+            },
+        );
+    }
+
+    /// Take the market's signal if it is still fresh
+    pub fn take(&self, market_id: &MarketId, now: u64) -> Option<ExternalSignal> {
+        let queued = self.pending.lock().unwrap().remove(market_id)?;
+        (now.saturating_sub(queued.received_at) <= self.ttl_secs).then_some(queued.signal)
+    }
+
+    /// Signals waiting to be scanned
+    pub fn len(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    /// No signals are waiting
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Turns queued external signals into intents
+#[derive(Debug)]
+pub struct ExternalSignalStrategy {
+    queue: Arc<ExternalSignalQueue>,
+    trade_size: f64,
+    fee_model: FeeModel,
+    gas_model: GasModel,
+    /// Time of the latest tick, for signal expiry
+    now: u64,
+}
+
+impl ExternalSignalStrategy {
+    pub fn new(queue: Arc<ExternalSignalQueue>) -> Self {
+        Self {
+            queue,
+            trade_size: 5.0,
+            fee_model: FeeModel::new(0, 0),
+            gas_model: GasModel::free(),
+            now: 0,
+        }
+    }
+
+    /// Size and fee schedule used when turning signals into intents
+    pub fn with_sizing(mut self, trade_size: f64, fee_model: FeeModel) -> Self {
+        self.trade_size = trade_size;
+        self.fee_model = fee_model;
+        self
+    }
+
+    /// Account for gas/relayer costs in expected profit
+    pub fn with_gas_model(mut self, gas_model: GasModel) -> Self {
+        self.gas_model = gas_model;
+        self
+    }
+}
+
+impl Strategy for ExternalSignalStrategy {
+    fn name(&self) -> &'static str {
+        "external"
+    }
+
+    fn scan(&self, markets: &[Market]) -> Vec<Intent> {
+        let fee_rate = self.fee_model.taker_rate();
+        markets
+            .iter()
+            .filter_map(|market| {
+                let signal = self.queue.take(&market.id, self.now)?;
+                let idx = signal.direction.outcome_index();
+                let token_id = market.clob_token_ids.get(idx)?;
+                let price = *market.outcome_prices.get(idx)?;
+
+                // Edge: the sender's probability over the market's
+                let edge = signal.confidence - price;
+                let fee_rate = self.fee_model.market_taker_rate(&market.id, fee_rate);
+                let expected_profit = edge * self.trade_size
+                    - self.trade_size * price * fee_rate
+                    - self.gas_model.cost(1);
+                if expected_profit <= 0.0 {
+                    println!(
+                        "   ⏭️ [External] {} signal on {} has no edge at {:.3}",
+                        if signal.source.is_empty() {
+                            "Unnamed"
+                        } else {
+                            &signal.source
+                        },
+                        market.id,
+                        price
+                    );
+                    return None;
+                }
+
+                Some(Intent {
+                    strategy: self.name(),
+                    market_id: market.id.clone(),
+                    token_ids: vec![token_id.clone()],
+                    side: Side::Buy,
+                    size: self.trade_size,
+                    spread: market.get_spread(),
+                    edge,
+                    expected_profit,
+                    order_type: OrderType::Market,
+                })
+            })
+            .collect()
+    }
+
+    fn on_tick(&mut self, _markets: &[Market], now: u64) {
+        self.now = now;
+    }
+
+    fn set_params(&mut self, params: &StrategyParams) {
+        self.trade_size = params.trade_size;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn market() -> Market {
+        test_util::market(0.40, 0.60)
+    }
+
+    fn signal(direction: Direction, confidence: f64) -> ExternalSignal {
+        ExternalSignal {
+            market_id: "m1".into(),
+            direction,
+            confidence,
+            source: "model".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let markets = [market()];
+        assert!(validate(&signal(Direction::Yes, 0.7), &markets).is_ok());
+        assert_eq!(
+            validate(&signal(Direction::Yes, 1.5), &markets),
+            Err(SignalError::InvalidConfidence(1.5))
+        );
+        let mut unknown = signal(Direction::No, 0.7);
+        unknown.market_id = "m2".into();
+        assert!(matches!(
+            validate(&unknown, &markets),
+            Err(SignalError::UnknownMarket(_))
+        ));
+
+        let parsed: ExternalSignal =
+            serde_json::from_str(r#"{"market_id":"m1","direction":"NO","confidence":0.8}"#)
+                .unwrap();
+        assert_eq!(parsed.direction, Direction::No);
+    }
+
+    #[test]
+    fn test_signal_becomes_one_intent_until_stale() {
+        let queue = Arc::new(ExternalSignalQueue::new(60));
+        let mut strategy = ExternalSignalStrategy::new(queue.clone());
+        let markets = [market()];
+
+        queue.push(signal(Direction::Yes, 0.70), 1_000);
+        strategy.on_tick(&markets, 1_010);
+        let intents = strategy.scan(&markets);
+        assert_eq!(intents.len(), 1);
+        assert_eq!(intents[0].token_ids, vec!["yes"]);
+        assert!((intents[0].edge - 0.30).abs() < 1e-9);
+        // Consumed by the scan
+        assert!(strategy.scan(&markets).is_empty());
+
+        // Stale signals are dropped; signals against the price are ignored
+        queue.push(signal(Direction::Yes, 0.70), 1_000);
+        strategy.on_tick(&markets, 1_100);
+        assert!(strategy.scan(&markets).is_empty());
+        queue.push(signal(Direction::No, 0.55), 1_100);
+        assert!(strategy.scan(&markets).is_empty());
+        assert!(queue.is_empty());
+    }
+}
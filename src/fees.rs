@@ -1,35 +1,325 @@
-use crate::types::Market;
+use crate::config::{glob_match, FeesConfig};
+use crate::money::{self, Decimal};
+use crate::types::{Market, MarketId};
+use crate::wallet::Wallet;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// Storage document holding daily traded volume
+pub const VOLUME_DOCUMENT: &str = "volume";
+
+/// Seconds per day
+const DAY_SECS: u64 = 86_400;
+
+/// Days of volume that determine the taker tier
+const TIER_WINDOW_DAYS: u64 = 30;
+
+/// Taker fee applied once 30-day volume reaches `min_volume`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FeeTier {
+    /// 30-day traded notional (USDC) that unlocks this tier
+    pub min_volume: f64,
+    pub taker_fee_bps: u32,
+}
+
+/// Traded notional per day, used to place the account in a fee tier
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VolumeHistory {
+    /// Unix day -> notional traded that day
+    days: BTreeMap<u64, f64>,
+}
+
+impl VolumeHistory {
+    /// Record traded notional at `now` (unix seconds)
+    pub fn record(&mut self, now: u64, notional: f64) {
+        *self.days.entry(now / DAY_SECS).or_insert(0.0) += notional;
+        // Nothing older than the tier window is ever needed
+        let cutoff = (now / DAY_SECS).saturating_sub(TIER_WINDOW_DAYS);
+        self.days.retain(|day, _| *day >= cutoff);
+    }
+
+    /// Notional traded over the trailing 30 days, including today
+    pub fn trailing_30d(&self, now: u64) -> f64 {
+        let today = now / DAY_SECS;
+        self.days
+            .range(today.saturating_sub(TIER_WINDOW_DAYS - 1)..=today)
+            .map(|(_, v)| v)
+            .sum()
+    }
+}
 
 /// Fee model based on Polymarket fee structure
 #[derive(Debug, Clone)]
 pub struct FeeModel {
     pub maker_fee_bps: u32, // Basis points (usually 0)
-    pub taker_fee_bps: u32, // Basis points (usually ~200)
+    pub taker_fee_bps: u32, // Basis points (usually ~200), before volume tiers
+    /// Rebate paid back on maker fills (basis points)
+    pub maker_rebate_bps: u32,
+    /// Volume-discounted taker fees, ascending by min_volume
+    pub taker_tiers: Vec<FeeTier>,
+    /// Shared 30-day volume that selects the taker tier
+    volume: Option<Arc<Mutex<VolumeHistory>>>,
+    /// Configured taker fees by market id or slug pattern
+    overrides: Vec<(String, u32)>,
+    /// Overrides resolved against the tracked markets, shared by every clone
+    market_overrides: Arc<Mutex<HashMap<MarketId, u32>>>,
 }
 
 impl FeeModel {
+    /// Flat maker/taker fees with no tiers or rebates
+    pub fn new(maker_fee_bps: u32, taker_fee_bps: u32) -> Self {
+        Self {
+            maker_fee_bps,
+            taker_fee_bps,
+            maker_rebate_bps: 0,
+            taker_tiers: Vec::new(),
+            volume: None,
+            overrides: Vec::new(),
+            market_overrides: Arc::default(),
+        }
+    }
+
+    /// Create from the schedule configured for the active venue
+    pub fn from_config(config: &FeesConfig) -> Self {
+        let mut overrides: Vec<(String, u32)> = config
+            .overrides
+            .iter()
+            .map(|(key, bps)| (key.clone(), *bps))
+            .collect();
+        overrides.sort();
+        let Some(venue) = config.venues.get(&config.venue) else {
+            println!(
+                "⚠️ No fee schedule for venue '{}', using 0/200 bps",
+                config.venue
+            );
+            return Self {
+                overrides,
+                ..Self::new(0, 200)
+            };
+        };
+        let mut taker_tiers = venue.taker_tiers.clone();
+        taker_tiers.sort_by(|a, b| a.min_volume.total_cmp(&b.min_volume));
+        Self {
+            maker_fee_bps: venue.maker_fee_bps,
+            taker_fee_bps: venue.taker_fee_bps,
+            maker_rebate_bps: venue.maker_rebate_bps,
+            taker_tiers,
+            volume: None,
+            overrides,
+            market_overrides: Arc::default(),
+        }
+    }
+
     /// Create from market data
     #[allow(dead_code)]
     pub fn from_market(market: &Market) -> Self {
-        Self {
-            maker_fee_bps: market.maker_base_fee,
-            taker_fee_bps: market.taker_base_fee,
+        Self::new(market.maker_base_fee, market.taker_base_fee)
+    }
+
+    /// Select taker tiers from a shared volume history
+    pub fn with_volume(mut self, volume: Arc<Mutex<VolumeHistory>>) -> Self {
+        self.volume = Some(volume);
+        self
+    }
+
+    /// Record traded notional towards the 30-day volume
+    pub fn record_volume(&self, now: u64, notional: f64) {
+        if let Some(volume) = &self.volume {
+            volume.lock().unwrap().record(now, notional);
         }
     }
 
-    /// Calculate fee for a trade
-    pub fn calculate(&self, notional: f64, is_maker: bool) -> f64 {
+    /// Taker fee for the current 30-day volume (basis points)
+    pub fn taker_bps(&self) -> u32 {
+        let volume = match &self.volume {
+            Some(volume) => volume
+                .lock()
+                .unwrap()
+                .trailing_30d(Wallet::current_timestamp()),
+            None => 0.0,
+        };
+        self.taker_bps_at(volume)
+    }
+
+    /// Taker fee for a given 30-day volume (basis points)
+    pub fn taker_bps_at(&self, volume_30d: f64) -> u32 {
+        self.taker_tiers
+            .iter()
+            .rev()
+            .find(|tier| volume_30d >= tier.min_volume)
+            .map_or(self.taker_fee_bps, |tier| tier.taker_fee_bps)
+    }
+
+    /// Match the configured overrides against the tracked markets
+    ///
+    /// A market id match wins over slug patterns, and the longest matching
+    /// pattern over shorter ones.
+    pub fn resolve_overrides(&self, markets: &[Market]) {
+        if self.overrides.is_empty() {
+            return;
+        }
+        let resolved = markets
+            .iter()
+            .filter_map(|market| {
+                let bps = self
+                    .overrides
+                    .iter()
+                    .find(|(key, _)| key == market.id.as_str())
+                    .or_else(|| {
+                        self.overrides
+                            .iter()
+                            .filter(|(pattern, _)| glob_match(pattern, &market.slug))
+                            .max_by_key(|(pattern, _)| pattern.len())
+                    })?
+                    .1;
+                Some((market.id.clone(), bps))
+            })
+            .collect();
+        *self.market_overrides.lock().unwrap() = resolved;
+    }
+
+    /// Taker fee configured for a market in place of the schedule (basis points)
+    pub fn taker_override(&self, market_id: &MarketId) -> Option<u32> {
+        self.market_overrides
+            .lock()
+            .unwrap()
+            .get(market_id)
+            .copied()
+    }
+
+    /// Taker fee for a market as decimal: its override, else `scheduled`
+    /// (the rate from `taker_rate`, passed in so a scan looks tiers up once)
+    pub fn market_taker_rate(&self, market_id: &MarketId, scheduled: f64) -> f64 {
+        self.taker_override(market_id)
+            .map_or(scheduled, |bps| bps as f64 / 10000.0)
+    }
+
+    /// Net maker fee in basis points (negative when the rebate exceeds the fee)
+    pub fn maker_net_bps(&self) -> f64 {
+        self.maker_fee_bps as f64 - self.maker_rebate_bps as f64
+    }
+
+    /// Calculate fee for a trade (negative for net maker rebates)
+    ///
+    /// Rounded as a charge: fees round up to the next micro-USDC, rebates
+    /// toward zero.
+    pub fn calculate(&self, notional: Decimal, is_maker: bool) -> Decimal {
         let bps = if is_maker {
-            self.maker_fee_bps
+            i64::from(self.maker_fee_bps) - i64::from(self.maker_rebate_bps)
         } else {
-            self.taker_fee_bps
+            i64::from(self.taker_bps())
         };
-        notional * (bps as f64 / 10000.0)
+        money::charge(notional * Decimal::new(bps, 4))
+    }
+
+    /// Calculate fee for a trade in a market, applying its taker override
+    pub fn calculate_in(&self, market_id: &MarketId, notional: Decimal, is_maker: bool) -> Decimal {
+        match self.taker_override(market_id) {
+            Some(bps) if !is_maker => money::charge(notional * Decimal::new(i64::from(bps), 4)),
+            _ => self.calculate(notional, is_maker),
+        }
     }
 
     /// Get taker fee as decimal
-    #[allow(dead_code)]
     pub fn taker_rate(&self) -> f64 {
-        self.taker_fee_bps as f64 / 10000.0
+        self.taker_bps() as f64 / 10000.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn tiered() -> FeeModel {
+        let mut model = FeeModel::new(0, 200);
+        model.maker_rebate_bps = 10;
+        model.taker_tiers = vec![
+            FeeTier {
+                min_volume: 10_000.0,
+                taker_fee_bps: 150,
+            },
+            FeeTier {
+                min_volume: 100_000.0,
+                taker_fee_bps: 100,
+            },
+        ];
+        model
+    }
+
+    #[test]
+    fn test_taker_tier_by_volume() {
+        let model = tiered();
+        assert_eq!(model.taker_bps_at(0.0), 200);
+        assert_eq!(model.taker_bps_at(10_000.0), 150);
+        assert_eq!(model.taker_bps_at(250_000.0), 100);
+    }
+
+    #[test]
+    fn test_maker_rebate_is_negative_fee() {
+        let model = tiered();
+        let notional = Decimal::from(1000);
+        assert_eq!(model.calculate(notional, true), Decimal::from(-1));
+        assert_eq!(model.calculate(notional, false), Decimal::from(20));
+        // Sub-micro-USDC fees are still charged
+        assert_eq!(
+            model.calculate(money::usdc(0.00001), false),
+            Decimal::new(1, 6)
+        );
+    }
+
+    #[test]
+    fn test_overrides_by_market_id_and_slug() {
+        let mut config = FeesConfig::default();
+        config.overrides.insert("nba-*".into(), 0);
+        config.overrides.insert("nba-finals-*".into(), 50);
+        config.overrides.insert("m3".into(), 100);
+        let model = FeeModel::from_config(&config);
+
+        let market = |id: &str, slug: &str| Market {
+            id: id.into(),
+            slug: slug.to_string(),
+            ..test_util::market(0.5, 0.5)
+        };
+        let copy = model.clone();
+        model.resolve_overrides(&[
+            market("m1", "nba-lakers-celtics"),
+            market("m2", "nba-finals-game-7"),
+            market("m3", "nba-finals-game-6"),
+            market("m4", "election-2028"),
+        ]);
+        // Clones share the resolved overrides
+        assert_eq!(copy.taker_override(&"m1".into()), Some(0));
+        assert_eq!(copy.taker_override(&"m2".into()), Some(50));
+        assert_eq!(copy.taker_override(&"m3".into()), Some(100));
+        assert_eq!(copy.taker_override(&"m4".into()), None);
+
+        let notional = Decimal::from(1000);
+        assert_eq!(
+            model.calculate_in(&"m1".into(), notional, false),
+            Decimal::ZERO
+        );
+        assert_eq!(
+            model.calculate_in(&"m4".into(), notional, false),
+            Decimal::from(20)
+        );
+        assert_eq!(model.market_taker_rate(&"m2".into(), 0.02), 0.005);
+        assert_eq!(model.market_taker_rate(&"m4".into(), 0.02), 0.02);
+    }
+
+    #[test]
+    fn test_volume_history_selects_tier() {
+        let now = Wallet::current_timestamp();
+        let volume = Arc::new(Mutex::new(VolumeHistory::default()));
+        let model = tiered().with_volume(volume.clone());
+
+        model.record_volume(now - 40 * DAY_SECS, 500_000.0);
+        model.record_volume(now - 5 * DAY_SECS, 8_000.0);
+        assert_eq!(model.taker_bps(), 200);
+
+        model.record_volume(now, 3_000.0);
+        assert_eq!(model.taker_bps(), 150);
+        assert!((volume.lock().unwrap().trailing_30d(now) - 11_000.0).abs() < 1e-9);
     }
 }
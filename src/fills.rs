@@ -1,27 +1,236 @@
+use crate::order_spec::{OrderTerms, OrderType};
 use crate::types::{OrderBook, Side};
+use std::time::Duration;
 
-/// Fill rate estimator
+/// Probabilistic fill model
+///
+/// Competing arbitrageurs race us for the top of book. The more of the top
+/// level our order needs, and the more intense the competition, the more
+/// likely that level is gone by the time we arrive and we only fill against
+/// the deeper levels.
 #[derive(Debug, Clone)]
-pub struct FillModel;
+pub struct FillModel {
+    /// How aggressively competitors take top-of-book liquidity (0 = no competition)
+    pub competitor_intensity: f64,
+}
+
+/// Distribution of filled size for one order
+#[derive(Debug, Clone, PartialEq)]
+pub struct FillEstimate {
+    /// Probability we win the race for the top of book
+    pub fill_probability: f64,
+    /// Filled size if we win (limited by total depth)
+    pub full_size: f64,
+    /// Filled size if competitors take the top level first
+    pub residual_size: f64,
+}
+
+impl FillEstimate {
+    /// Expected filled size
+    pub fn expected_size(&self) -> f64 {
+        self.fill_probability * self.full_size + (1.0 - self.fill_probability) * self.residual_size
+    }
+
+    /// Draw one filled size from the distribution
+    pub fn sample(&self) -> f64 {
+        if rand::random::<f64>() < self.fill_probability {
+            self.full_size
+        } else {
+            self.residual_size
+        }
+    }
+}
 
 impl FillModel {
-    /// Estimate how much of the order can fill
-    pub fn estimate_fill_ratio(book: &OrderBook, size: f64, side: Side) -> f64 {
-        let available = match side {
-            Side::Buy => book.total_ask_liquidity(),
-            Side::Sell => book.total_bid_liquidity(),
+    pub fn new(competitor_intensity: f64) -> Self {
+        Self {
+            competitor_intensity: competitor_intensity.max(0.0),
+        }
+    }
+
+    /// Estimate the fill distribution for an order of `size`
+    pub fn estimate(&self, book: &OrderBook, size: f64, side: Side) -> FillEstimate {
+        let levels = match side {
+            Side::Buy => &book.asks,
+            Side::Sell => &book.bids,
         };
+        let available: f64 = levels.iter().map(|l| l.size).sum();
+        let top_size = levels.first().map(|l| l.size).unwrap_or(0.0);
 
-        if available >= size {
-            1.0
-        } else {
-            available / size
+        if size <= 0.0 || available <= 0.0 {
+            return FillEstimate {
+                fill_probability: 0.0,
+                full_size: 0.0,
+                residual_size: 0.0,
+            };
+        }
+
+        // Share of the top level we need: small orders rarely lose the race
+        let queue_pressure = size / top_size.max(f64::EPSILON);
+        let fill_probability = (-self.competitor_intensity * queue_pressure).exp();
+
+        FillEstimate {
+            fill_probability,
+            full_size: size.min(available),
+            residual_size: size.min(available - top_size).max(0.0),
+        }
+    }
+
+    /// Estimate the fill distribution for an order with the given terms
+    ///
+    /// Limit prices cap the levels the order can take from; a fill-or-kill
+    /// order that cannot fill in full fills nothing. Post-only orders never
+    /// take liquidity, so they fill nothing on arrival.
+    pub fn estimate_order(&self, book: &OrderBook, size: f64, terms: &OrderTerms) -> FillEstimate {
+        let none = FillEstimate {
+            fill_probability: 0.0,
+            full_size: 0.0,
+            residual_size: 0.0,
+        };
+        if terms.order_type == OrderType::PostOnly {
+            return none;
+        }
+        let estimate = self.estimate(&terms.reachable(book), size, terms.side);
+        if terms.order_type != OrderType::Fok {
+            return estimate;
+        }
+        let all_or_nothing = |filled: f64| if filled >= size { size } else { 0.0 };
+        FillEstimate {
+            full_size: all_or_nothing(estimate.full_size),
+            residual_size: all_or_nothing(estimate.residual_size),
+            ..estimate
+        }
+    }
+
+    /// Sample a filled size for an order
+    pub fn filled_size(&self, book: &OrderBook, requested_size: f64, side: Side) -> f64 {
+        self.estimate(book, requested_size, side).sample()
+    }
+}
+
+impl Default for FillModel {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+/// Decay of a detected edge while our order is in flight
+///
+/// Competing arbitrageurs arrive as a Poisson process. The first to arrive
+/// takes the mispriced top of book, so an edge is still there after `t`
+/// seconds with probability `exp(-arrival_rate * t)`.
+#[derive(Debug, Clone, Default)]
+pub struct EdgeDecay {
+    /// Competitors per second racing for each edge (0 = edges never decay)
+    pub arrival_rate: f64,
+}
+
+impl EdgeDecay {
+    pub fn new(arrival_rate: f64) -> Self {
+        Self {
+            arrival_rate: arrival_rate.max(0.0),
+        }
+    }
+
+    /// Probability no competitor has taken the edge after `elapsed`
+    pub fn survival(&self, elapsed: Duration) -> f64 {
+        (-self.arrival_rate * elapsed.as_secs_f64()).exp()
+    }
+
+    /// Draw whether a competitor beat us to the edge within `elapsed`
+    pub fn captured(&self, elapsed: Duration) -> bool {
+        rand::random::<f64>() >= self.survival(elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PriceLevel;
+
+    fn create_test_book() -> OrderBook {
+        OrderBook {
+            token_id: "t1".into(),
+            bids: vec![],
+            asks: vec![
+                PriceLevel {
+                    price: 0.50,
+                    size: 100.0,
+                },
+                PriceLevel {
+                    price: 0.51,
+                    size: 50.0,
+                },
+            ],
+            timestamp: 0,
         }
     }
 
-    /// Get filled size based on available liquidity
-    pub fn filled_size(book: &OrderBook, requested_size: f64, side: Side) -> f64 {
-        let ratio = Self::estimate_fill_ratio(book, requested_size, side);
-        requested_size * ratio
+    #[test]
+    fn test_no_competition_always_fills() {
+        let estimate = FillModel::new(0.0).estimate(&create_test_book(), 120.0, Side::Buy);
+        assert_eq!(estimate.fill_probability, 1.0);
+        assert_eq!(estimate.expected_size(), 120.0);
+        assert_eq!(
+            FillModel::default().filled_size(&create_test_book(), 120.0, Side::Buy),
+            120.0
+        );
+    }
+
+    #[test]
+    fn test_competition_reduces_expected_fill() {
+        let model = FillModel::new(1.0);
+        let small = model.estimate(&create_test_book(), 10.0, Side::Buy);
+        let large = model.estimate(&create_test_book(), 120.0, Side::Buy);
+
+        // Larger share of top-of-book means lower chance of winning the race
+        assert!(small.fill_probability > large.fill_probability);
+        // Losing the race leaves only the second level
+        assert_eq!(large.residual_size, 50.0);
+        assert!(large.expected_size() < 120.0 && large.expected_size() > 50.0);
+    }
+
+    #[test]
+    fn test_edge_decays_with_time_in_flight() {
+        let decay = EdgeDecay::new(2.0);
+        assert_eq!(decay.survival(Duration::ZERO), 1.0);
+        let half_second = decay.survival(Duration::from_millis(500));
+        assert!((half_second - (-1.0f64).exp()).abs() < 1e-12);
+        assert!(decay.survival(Duration::from_secs(2)) < half_second);
+
+        // Without competitors an edge waits forever
+        assert!(!EdgeDecay::default().captured(Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_order_types() {
+        let model = FillModel::new(0.0);
+        let book = create_test_book();
+        let terms = |order_type, limit_price| OrderTerms {
+            side: Side::Buy,
+            order_type,
+            limit_price,
+        };
+
+        // Only the 0.50 level is within the limit
+        let ioc = model.estimate_order(&book, 120.0, &terms(OrderType::Ioc, Some(0.50)));
+        assert_eq!(ioc.expected_size(), 100.0);
+        let fok = model.estimate_order(&book, 120.0, &terms(OrderType::Fok, Some(0.50)));
+        assert_eq!(fok.expected_size(), 0.0);
+        let fok = model.estimate_order(&book, 120.0, &terms(OrderType::Fok, Some(0.51)));
+        assert_eq!(fok.expected_size(), 120.0);
+        let market = model.estimate_order(&book, 120.0, &OrderTerms::market(Side::Buy));
+        assert_eq!(market.expected_size(), 120.0);
+        let post = model.estimate_order(&book, 10.0, &terms(OrderType::PostOnly, Some(0.49)));
+        assert_eq!(post.expected_size(), 0.0);
+    }
+
+    #[test]
+    fn test_fill_limited_by_depth() {
+        let estimate = FillModel::new(0.5).estimate(&create_test_book(), 500.0, Side::Buy);
+        assert_eq!(estimate.full_size, 150.0);
+
+        let empty = FillModel::new(0.5).estimate(&create_test_book(), 10.0, Side::Sell);
+        assert_eq!(empty.expected_size(), 0.0);
     }
 }
@@ -0,0 +1,251 @@
+//! Gamma API response models
+//!
+//! Typed views of the `/events` and `/markets` payloads. Gamma encodes some
+//! arrays (`outcomes`, `outcomePrices`, `clobTokenIds`) as stringified JSON,
+//! e.g. `"[\"123\", \"456\"]"`; those are decoded here, and a value that is
+//! neither that nor a real array fails the parse instead of reading as empty.
+//! Liquidity and volume come as numbers or numeric strings depending on the
+//! endpoint; both are accepted.
+
+use crate::types::{Market, MarketId, Resolution, TokenId};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+
+/// One event from `/events`, grouping related markets
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GammaEvent {
+    #[serde(default)]
+    pub slug: String,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub markets: Vec<GammaMarket>,
+}
+
+/// One market, as nested in an event or returned by `/markets/{id}`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GammaMarket {
+    pub id: MarketId,
+    #[serde(default)]
+    pub question: String,
+    /// The market's own slug (`/markets` has no enclosing event)
+    #[serde(default)]
+    pub slug: String,
+    #[serde(default)]
+    pub condition_id: String,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default, deserialize_with = "stringified_array")]
+    pub outcomes: Vec<String>,
+    #[serde(default, deserialize_with = "stringified_array")]
+    pub outcome_prices: Vec<String>,
+    #[serde(default, deserialize_with = "stringified_array")]
+    pub clob_token_ids: Vec<TokenId>,
+    #[serde(default)]
+    pub closed: bool,
+    #[serde(default)]
+    pub order_price_min_tick_size: Option<f64>,
+    #[serde(default)]
+    pub order_min_size: Option<f64>,
+    /// USDC resting on the market's books
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub liquidity: Option<f64>,
+    /// USDC traded over the last 24 hours
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub volume_24hr: Option<f64>,
+    /// ISO-8601 UTC listing time
+    #[serde(default)]
+    pub created_at: String,
+}
+
+impl GammaMarket {
+    /// Tradeable market, or `None` if it lacks the tokens to execute against
+    ///
+    /// Prices start at 0.5 and are filled in by book hydration.
+    pub fn to_market(&self, event: &GammaEvent) -> Option<Market> {
+        if self.clob_token_ids.len() < 2 {
+            return None;
+        }
+        Some(Market {
+            id: self.id.clone(),
+            question: self.question.clone(),
+            slug: event.slug.clone(),
+            outcomes: self.outcomes.clone(),
+            outcome_prices: vec![0.5, 0.5],
+            clob_token_ids: self.clob_token_ids.clone(),
+            best_bid: None,
+            best_ask: None,
+            maker_base_fee: 0,
+            taker_base_fee: 200, // Standard 2%
+            liquidity: self.liquidity,
+            volume_24hr: self.volume_24hr,
+            active: true,
+            accepting_orders: true,
+            condition_id: self.condition_id.clone(),
+            category: self
+                .category
+                .clone()
+                .or_else(|| event.category.clone())
+                .unwrap_or_default(),
+            tick_size: self.order_price_min_tick_size.unwrap_or(0.0),
+            min_order_size: self.order_min_size.unwrap_or(0.0),
+            outcome_quotes: Vec::new(),
+        })
+    }
+
+    /// Resolution, if the market is closed with a definite 0/1 payout
+    pub fn resolution(&self) -> Option<Resolution> {
+        if !self.closed {
+            return None;
+        }
+        let prices: Vec<f64> = self
+            .outcome_prices
+            .iter()
+            .map(|p| p.parse().ok())
+            .collect::<Option<_>>()?;
+        // Closed but not yet settled (or voided): prices are still fractional
+        let settled = prices.iter().all(|p| *p == 0.0 || *p == 1.0)
+            && (prices.iter().sum::<f64>() - 1.0).abs() < f64::EPSILON;
+        if self.clob_token_ids.len() < 2 || self.clob_token_ids.len() != prices.len() || !settled {
+            return None;
+        }
+
+        Some(Resolution {
+            market_id: self.id.clone(),
+            condition_id: self.condition_id.clone(),
+            payouts: self.clob_token_ids.iter().cloned().zip(prices).collect(),
+        })
+    }
+}
+
+/// A string array sent either as real JSON or stringified JSON
+fn stringified_array<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw<T> {
+        Stringified(String),
+        Array(Vec<T>),
+    }
+
+    match Option::<Raw<T>>::deserialize(deserializer)? {
+        None => Ok(Vec::new()),
+        Some(Raw::Array(values)) => Ok(values),
+        Some(Raw::Stringified(s)) => serde_json::from_str(&s).map_err(serde::de::Error::custom),
+    }
+}
+
+/// A number sent either as JSON or as a numeric string
+///
+/// Anything else reads as unknown rather than failing the whole event.
+fn number_or_string<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            Some(serde_json::Value::Number(n)) => n.as_f64(),
+            Some(serde_json::Value::String(s)) => s.trim().parse().ok(),
+            _ => None,
+        }
+        .filter(|n: &f64| n.is_finite()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_event_with_stringified_arrays() {
+        let events: Vec<GammaEvent> = serde_json::from_value(json!([{
+            "slug": "election",
+            "category": "Politics",
+            "markets": [
+                {
+                    "id": "m1",
+                    "question": "Who wins?",
+                    "conditionId": "0xabc",
+                    "outcomes": "[\"Yes\", \"No\"]",
+                    "clobTokenIds": "[\"t1\", \"t2\"]",
+                    "orderPriceMinTickSize": 0.001,
+                    "orderMinSize": 5,
+                    "liquidity": "12500.5",
+                    "volume24hr": 3400.25
+                },
+                { "id": "m2", "clobTokenIds": ["t3"], "outcomes": null }
+            ]
+        }]))
+        .unwrap();
+        let event = &events[0];
+
+        let market = event.markets[0].to_market(event).unwrap();
+        assert_eq!(market.outcomes, vec!["Yes", "No"]);
+        assert_eq!(market.clob_token_ids, vec!["t1", "t2"]);
+        assert_eq!(market.slug, "election");
+        assert_eq!(market.category, "Politics");
+        assert_eq!(market.tick_size, 0.001);
+        assert_eq!(market.min_order_size, 5.0);
+        assert_eq!(market.liquidity, Some(12500.5));
+        assert_eq!(market.volume_24hr, Some(3400.25));
+
+        // Real arrays are accepted too, but one token is not tradeable
+        assert!(event.markets[1].outcomes.is_empty());
+        assert!(event.markets[1].to_market(event).is_none());
+        assert_eq!(event.markets[1].liquidity, None);
+    }
+
+    #[test]
+    fn test_malformed_fields_fail_the_parse() {
+        let garbled = json!({ "id": "m1", "clobTokenIds": "[\"t1\", " });
+        assert!(serde_json::from_value::<GammaMarket>(garbled).is_err());
+        let missing_id = json!({ "question": "Who wins?" });
+        assert!(serde_json::from_value::<GammaMarket>(missing_id).is_err());
+    }
+
+    #[test]
+    fn test_unparseable_activity_is_unknown() {
+        let market: GammaMarket = serde_json::from_value(json!({
+            "id": "m1",
+            "liquidity": "lots",
+            "volume24hr": { "amount": 5 }
+        }))
+        .unwrap();
+        assert_eq!(market.liquidity, None);
+        assert_eq!(market.volume_24hr, None);
+    }
+
+    #[test]
+    fn test_resolution() {
+        let mut market = json!({
+            "id": "m1",
+            "conditionId": "0xabc",
+            "closed": true,
+            "clobTokenIds": "[\"t1\", \"t2\"]",
+            "outcomePrices": "[\"0\", \"1\"]",
+        });
+        let parse = |m: &serde_json::Value| {
+            serde_json::from_value::<GammaMarket>(m.clone())
+                .unwrap()
+                .resolution()
+        };
+        let resolution = parse(&market).unwrap();
+        assert_eq!(resolution.condition_id, "0xabc");
+        assert_eq!(resolution.payouts["t1"], 0.0);
+        assert_eq!(resolution.payouts["t2"], 1.0);
+
+        // Closed but awaiting settlement
+        market["outcomePrices"] = json!("[\"0.97\", \"0.03\"]");
+        assert!(parse(&market).is_none());
+
+        market["closed"] = json!(false);
+        market["outcomePrices"] = json!("[\"0\", \"1\"]");
+        assert!(parse(&market).is_none());
+    }
+}
@@ -0,0 +1,72 @@
+//! Gas cost model
+//!
+//! Estimates the per-transaction gas or relayer fee paid to settle trades
+//! on-chain, so thin edges that don't survive transaction costs are skipped.
+
+use crate::config::GasConfig;
+
+/// Gas/relayer cost model for a single chain
+#[derive(Debug, Clone)]
+pub struct GasModel {
+    pub chain: String,
+    pub cost_per_tx: f64, // USDC per submitted transaction
+}
+
+impl GasModel {
+    pub fn new(chain: &str, cost_per_tx: f64) -> Self {
+        Self {
+            chain: chain.to_string(),
+            cost_per_tx,
+        }
+    }
+
+    /// Zero-cost model (e.g. relayer-sponsored transactions)
+    pub fn free() -> Self {
+        Self::new("none", 0.0)
+    }
+
+    /// Create from config, using the cost configured for the active chain
+    pub fn from_config(config: &GasConfig) -> Self {
+        let cost = config
+            .cost_per_tx
+            .get(&config.chain)
+            .copied()
+            .unwrap_or(0.0);
+        Self::new(&config.chain, cost)
+    }
+
+    /// Total cost of submitting `transactions` transactions
+    pub fn cost(&self, transactions: usize) -> f64 {
+        self.cost_per_tx * transactions as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_from_config_selects_active_chain() {
+        let mut cost_per_tx = HashMap::new();
+        cost_per_tx.insert("polygon".to_string(), 0.01);
+        cost_per_tx.insert("solana".to_string(), 0.0005);
+        let config = GasConfig {
+            chain: "polygon".to_string(),
+            cost_per_tx,
+        };
+
+        let model = GasModel::from_config(&config);
+        assert_eq!(model.chain, "polygon");
+        assert!((model.cost(2) - 0.02).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_unknown_chain_is_free() {
+        let config = GasConfig {
+            chain: "unknown".to_string(),
+            cost_per_tx: HashMap::new(),
+        };
+        assert_eq!(GasModel::from_config(&config).cost(2), 0.0);
+    }
+}
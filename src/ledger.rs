@@ -0,0 +1,490 @@
+//! Spend ledger module
+//!
+//! Records every allowance spend by strategy and execution, enforces
+//! per-strategy daily budgets, records final settlements of resolved
+//! markets, and persists across restarts.
+//!
+//! Spends are two-phase: an intent reserves budget before the order goes
+//! out and is confirmed (or released) once the execution is known, so a
+//! crash in between never burns allowance without a trade or trades
+//! without recording the spend. Reserving checks the strategy's budget and
+//! the account's allowance net of every order still in flight in one step,
+//! so concurrent signals can't both pass against the same remaining budget.
+
+use crate::money::Decimal;
+use crate::types::MarketId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Storage document holding the spend ledger
+pub const LEDGER_DOCUMENT: &str = "spend_ledger";
+
+/// Storage log of completed executions, written before their spend is confirmed
+pub const EXECUTIONS_LOG: &str = "executions";
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// One allowance spend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendEntry {
+    pub execution_id: String,
+    pub strategy: String,
+    pub amount: Decimal,
+    pub timestamp: u64,
+}
+
+/// Budget reserved for an order that has not completed yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendIntent {
+    pub execution_id: String,
+    pub strategy: String,
+    /// Account whose allowance the order spends
+    #[serde(default)]
+    pub account: String,
+    /// Upper bound on what the order may spend
+    pub amount: Decimal,
+    pub timestamp: u64,
+}
+
+/// Execution journaled once an order completes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutedSpend {
+    pub execution_id: String,
+    pub amount: Decimal,
+}
+
+/// USDC received when a resolved market's tokens were redeemed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settlement {
+    pub market_id: MarketId,
+    /// USDC paid out for the redeemed tokens
+    pub payout: Decimal,
+    /// Realized PnL of the settled positions
+    pub pnl: Decimal,
+    /// Redemption transaction (None when simulated)
+    pub tx_hash: Option<String>,
+    pub timestamp: u64,
+}
+
+/// Why a reservation was refused
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shortfall {
+    /// The strategy's daily budget has this much left
+    Budget(Decimal),
+    /// The account's allowance has this much left after orders in flight
+    Allowance(Decimal),
+}
+
+impl std::fmt::Display for Shortfall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Budget(left) => write!(f, "budget exhausted (${:.2} left)", left),
+            Self::Allowance(left) => {
+                write!(
+                    f,
+                    "${:.2} of the allowance left after orders in flight",
+                    left
+                )
+            }
+        }
+    }
+}
+
+/// Daily spend ledger with per-strategy budgets
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpendLedger {
+    /// Day (unix time / 86400) the entries belong to
+    day: u64,
+    /// Spends for the current day
+    entries: Vec<SpendEntry>,
+    /// Strategy behind each execution, kept across days so exits can be attributed
+    strategies: HashMap<String, String>,
+    /// Final settlements of resolved markets, kept across days
+    #[serde(default)]
+    settlements: Vec<Settlement>,
+    /// Reservations for orders in flight, keyed by execution id
+    #[serde(default)]
+    intents: HashMap<String, SpendIntent>,
+    /// Daily budget per strategy (strategies without one are unlimited)
+    #[serde(skip)]
+    budgets: HashMap<String, Decimal>,
+}
+
+impl SpendLedger {
+    /// Replace the per-strategy daily budgets
+    pub fn set_budgets(&mut self, budgets: HashMap<String, Decimal>) {
+        self.budgets = budgets;
+    }
+
+    /// Start a fresh day of spends if `now` is past the current day
+    pub fn roll_over(&mut self, now: u64) {
+        let day = now / SECONDS_PER_DAY;
+        if day != self.day {
+            self.day = day;
+            self.entries.clear();
+        }
+    }
+
+    /// Amount a strategy has spent today
+    pub fn spent(&self, strategy: &str) -> Decimal {
+        self.entries
+            .iter()
+            .filter(|e| e.strategy == strategy)
+            .map(|e| e.amount)
+            .sum()
+    }
+
+    /// Amount spent today across all strategies
+    pub fn total_spent(&self) -> Decimal {
+        self.entries.iter().map(|e| e.amount).sum()
+    }
+
+    /// Amount a strategy has reserved for orders in flight
+    pub fn reserved(&self, strategy: &str) -> Decimal {
+        self.intents
+            .values()
+            .filter(|i| i.strategy == strategy)
+            .map(|i| i.amount)
+            .sum()
+    }
+
+    /// Allowance reserved through each account for orders in flight
+    pub fn reservations(&self) -> HashMap<String, Decimal> {
+        let mut reserved = HashMap::new();
+        for intent in self.intents.values() {
+            *reserved
+                .entry(intent.account.clone())
+                .or_insert(Decimal::ZERO) += intent.amount;
+        }
+        reserved
+    }
+
+    /// Allowance reserved through an account by orders other than `except`
+    pub fn reserved_through(&self, account: &str, except: &[&str]) -> Decimal {
+        self.intents
+            .values()
+            .filter(|i| i.account == account && !except.contains(&i.execution_id.as_str()))
+            .map(|i| i.amount)
+            .sum()
+    }
+
+    /// Budget left for a strategy today, net of reservations
+    pub fn remaining(&self, strategy: &str) -> Decimal {
+        match self.budgets.get(strategy) {
+            Some(budget) => {
+                (budget - self.spent(strategy) - self.reserved(strategy)).max(Decimal::ZERO)
+            }
+            None => Decimal::MAX,
+        }
+    }
+
+    /// Check whether a strategy may spend `amount` now
+    pub fn can_spend(&mut self, strategy: &str, amount: Decimal, now: u64) -> bool {
+        self.roll_over(now);
+        amount <= self.remaining(strategy)
+    }
+
+    /// Reserve `amount` for an order about to be sent (phase one)
+    pub fn begin(
+        &mut self,
+        execution_id: &str,
+        strategy: &str,
+        account: &str,
+        amount: Decimal,
+        now: u64,
+    ) {
+        self.roll_over(now);
+        self.intents.insert(
+            execution_id.to_string(),
+            SpendIntent {
+                execution_id: execution_id.to_string(),
+                strategy: strategy.to_string(),
+                account: account.to_string(),
+                amount,
+                timestamp: now,
+            },
+        );
+    }
+
+    /// Reserve every leg of an order if the strategy's budget and the
+    /// account's allowance still cover all of them
+    ///
+    /// `allowance` is what the account's grant has left; reservations already
+    /// made through the account are taken off it. Either every leg is
+    /// reserved or none is.
+    pub fn reserve(
+        &mut self,
+        strategy: &str,
+        account: &str,
+        allowance: Decimal,
+        legs: &[(String, Decimal)],
+        now: u64,
+    ) -> Result<(), Shortfall> {
+        self.roll_over(now);
+        let amount: Decimal = legs.iter().map(|(_, amount)| *amount).sum();
+        let budget = self.remaining(strategy);
+        if amount > budget {
+            return Err(Shortfall::Budget(budget));
+        }
+        let left = (allowance - self.reserved_through(account, &[])).max(Decimal::ZERO);
+        if amount > left {
+            return Err(Shortfall::Allowance(left));
+        }
+        for (execution_id, amount) in legs {
+            self.begin(execution_id, strategy, account, *amount, now);
+        }
+        Ok(())
+    }
+
+    /// Turn a reservation into a spend of what the order actually cost (phase two)
+    ///
+    /// Idempotent: returns false if the execution has no open intent.
+    pub fn confirm(&mut self, execution_id: &str, amount: Decimal, now: u64) -> bool {
+        match self.intents.remove(execution_id) {
+            Some(intent) => {
+                self.record(execution_id, &intent.strategy, amount, now);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Record one fill of a resting order against its reservation
+    ///
+    /// The reservation shrinks by the fill and stays open for the rest of the
+    /// order; the fill is recorded under its own id so repeats are ignored.
+    pub fn confirm_fill(
+        &mut self,
+        execution_id: &str,
+        fill_id: &str,
+        amount: Decimal,
+        now: u64,
+    ) -> bool {
+        self.roll_over(now);
+        if self.entries.iter().any(|e| e.execution_id == fill_id) {
+            return true;
+        }
+        let strategy = match self.intents.get_mut(execution_id) {
+            Some(intent) => {
+                intent.amount = (intent.amount - amount).max(Decimal::ZERO);
+                intent.strategy.clone()
+            }
+            // Fills reported after the order closed still count
+            None => match self.strategies.get(execution_id) {
+                Some(strategy) => strategy.clone(),
+                None => return false,
+            },
+        };
+        self.record(fill_id, &strategy, amount, now);
+        self.strategies.insert(execution_id.to_string(), strategy);
+        true
+    }
+
+    /// Drop a reservation whose order never executed
+    pub fn release(&mut self, execution_id: &str) -> bool {
+        self.intents.remove(execution_id).is_some()
+    }
+
+    /// Intents left open, e.g. by a crash between reserve and confirm
+    pub fn dangling_intents(&self) -> Vec<&SpendIntent> {
+        self.intents.values().collect()
+    }
+
+    /// Settle intents left open by a previous run against the execution journal
+    ///
+    /// Journaled executions are confirmed at their actual cost; the rest never
+    /// completed and are released. Returns (confirmed, released).
+    pub fn reconcile_intents(&mut self, executed: &[ExecutedSpend], now: u64) -> (usize, usize) {
+        let ids: Vec<String> = self.intents.keys().cloned().collect();
+        let (mut confirmed, mut released) = (0, 0);
+        for id in ids {
+            match executed.iter().find(|e| e.execution_id == id) {
+                Some(execution) => {
+                    self.confirm(&id, execution.amount, now);
+                    confirmed += 1;
+                }
+                None => {
+                    self.release(&id);
+                    released += 1;
+                }
+            }
+        }
+        (confirmed, released)
+    }
+
+    /// Record a spend for an execution (ignored if already recorded)
+    pub fn record(&mut self, execution_id: &str, strategy: &str, amount: Decimal, now: u64) {
+        self.roll_over(now);
+        if self.entries.iter().any(|e| e.execution_id == execution_id) {
+            return;
+        }
+        self.entries.push(SpendEntry {
+            execution_id: execution_id.to_string(),
+            strategy: strategy.to_string(),
+            amount,
+            timestamp: now,
+        });
+        self.strategies
+            .insert(execution_id.to_string(), strategy.to_string());
+    }
+
+    /// Strategy that paid for an execution
+    pub fn strategy_for(&self, execution_id: &str) -> Option<&str> {
+        self.strategies.get(execution_id).map(|s| s.as_str())
+    }
+
+    /// Record the redemption of a resolved market
+    pub fn record_settlement(&mut self, settlement: Settlement) {
+        self.settlements.push(settlement);
+    }
+
+    /// All recorded settlements, oldest first
+    pub fn settlements(&self) -> &[Settlement] {
+        &self.settlements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::usdc;
+
+    const DAY: u64 = 86_400;
+
+    #[test]
+    fn test_budget_enforced_per_strategy() {
+        let mut ledger = SpendLedger::default();
+        ledger.set_budgets(HashMap::from([("arb".to_string(), usdc(10.0))]));
+
+        assert!(ledger.can_spend("arb", usdc(6.0), DAY));
+        ledger.record("e1", "arb", usdc(6.0), DAY);
+        assert!(!ledger.can_spend("arb", usdc(6.0), DAY));
+        assert_eq!(ledger.remaining("arb"), usdc(4.0));
+
+        // No budget configured: unlimited
+        assert!(ledger.can_spend("other", usdc(100.0), DAY));
+        assert_eq!(ledger.strategy_for("e1"), Some("arb"));
+    }
+
+    #[test]
+    fn test_new_day_resets_spend() {
+        let mut ledger = SpendLedger::default();
+        ledger.set_budgets(HashMap::from([("arb".to_string(), usdc(10.0))]));
+        ledger.record("e1", "arb", usdc(10.0), DAY + 10);
+        assert!(!ledger.can_spend("arb", usdc(1.0), DAY + 20));
+
+        assert!(ledger.can_spend("arb", usdc(10.0), 2 * DAY + 5));
+        assert_eq!(ledger.total_spent(), usdc(0.0));
+        // Attribution survives the reset
+        assert_eq!(ledger.strategy_for("e1"), Some("arb"));
+    }
+
+    #[test]
+    fn test_two_phase_spend() {
+        let mut ledger = SpendLedger::default();
+        ledger.set_budgets(HashMap::from([("arb".to_string(), usdc(10.0))]));
+
+        // Reservations count against the budget until resolved
+        ledger.begin("e1", "arb", "primary", usdc(5.0), DAY);
+        ledger.begin("e2", "arb", "primary", usdc(5.0), DAY);
+        assert!(!ledger.can_spend("arb", usdc(1.0), DAY));
+
+        assert!(ledger.confirm("e1", usdc(4.5), DAY));
+        assert!(!ledger.confirm("e1", usdc(4.5), DAY));
+        assert!(ledger.release("e2"));
+        assert_eq!(ledger.spent("arb"), usdc(4.5));
+        assert_eq!(ledger.remaining("arb"), usdc(5.5));
+        assert_eq!(ledger.strategy_for("e1"), Some("arb"));
+    }
+
+    #[test]
+    fn test_reserve_counts_orders_in_flight() {
+        let mut ledger = SpendLedger::default();
+        ledger.set_budgets(HashMap::from([("arb".to_string(), usdc(10.0))]));
+        let legs = |a: &str, b: &str| vec![(a.to_string(), usdc(3.0)), (b.to_string(), usdc(3.0))];
+
+        // Two orders racing for $8 of allowance: only the first gets it
+        assert_eq!(
+            ledger.reserve("arb", "primary", usdc(8.0), &legs("a1", "a2"), DAY),
+            Ok(())
+        );
+        assert_eq!(
+            ledger.reserve("other", "primary", usdc(8.0), &legs("b1", "b2"), DAY),
+            Err(Shortfall::Allowance(usdc(2.0)))
+        );
+        assert_eq!(ledger.dangling_intents().len(), 2);
+        // Another account's allowance is separate, the strategy budget isn't
+        assert_eq!(
+            ledger.reserve("arb", "second", usdc(50.0), &legs("c1", "c2"), DAY),
+            Err(Shortfall::Budget(usdc(4.0)))
+        );
+        assert_eq!(ledger.reservations()["primary"], usdc(6.0));
+        assert_eq!(ledger.reserved_through("primary", &["a1"]), usdc(3.0));
+
+        // A failed leg frees its share; a fill moves it onto the grant
+        ledger.release("a2");
+        ledger.confirm("a1", usdc(2.9), DAY);
+        assert_eq!(ledger.reserved_through("primary", &[]), Decimal::ZERO);
+        assert_eq!(
+            ledger.reserve("other", "primary", usdc(5.1), &legs("b1", "b2"), DAY),
+            Err(Shortfall::Allowance(usdc(5.1)))
+        );
+        assert_eq!(
+            ledger.reserve("other", "primary", usdc(6.0), &legs("b1", "b2"), DAY),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_partial_fills_shrink_reservation() {
+        let mut ledger = SpendLedger::default();
+        ledger.begin("e1", "arb", "primary", usdc(5.0), DAY);
+
+        assert!(ledger.confirm_fill("e1", "t1:o1", usdc(2.0), DAY));
+        assert!(ledger.confirm_fill("e1", "t1:o1", usdc(2.0), DAY));
+        assert_eq!(ledger.spent("arb"), usdc(2.0));
+        assert_eq!(ledger.reserved_through("primary", &[]), usdc(3.0));
+
+        // The rest is freed when the order closes; late fills still count
+        assert!(ledger.release("e1"));
+        assert!(ledger.confirm_fill("e1", "t2:o1", usdc(1.0), DAY));
+        assert_eq!(ledger.spent("arb"), usdc(3.0));
+        assert_eq!(ledger.strategy_for("e1"), Some("arb"));
+        assert!(!ledger.confirm_fill("unknown", "t3:o2", usdc(1.0), DAY));
+    }
+
+    #[test]
+    fn test_reconcile_dangling_intents() {
+        let mut ledger = SpendLedger::default();
+        ledger.begin("done", "arb", "primary", usdc(5.0), DAY);
+        ledger.begin("lost", "arb", "primary", usdc(5.0), DAY);
+        let restored: SpendLedger =
+            serde_json::from_str(&serde_json::to_string(&ledger).unwrap()).unwrap();
+        let mut ledger = restored;
+        assert_eq!(ledger.dangling_intents().len(), 2);
+
+        let journal = vec![ExecutedSpend {
+            execution_id: "done".to_string(),
+            amount: usdc(4.8),
+        }];
+        assert_eq!(ledger.reconcile_intents(&journal, DAY), (1, 1));
+        assert!(ledger.dangling_intents().is_empty());
+        assert_eq!(ledger.total_spent(), usdc(4.8));
+    }
+
+    #[test]
+    fn test_settlements_survive_day_roll() {
+        let mut ledger = SpendLedger::default();
+        ledger.record_settlement(Settlement {
+            market_id: MarketId::from("m1"),
+            payout: usdc(10.0),
+            pnl: usdc(2.0),
+            tx_hash: None,
+            timestamp: DAY,
+        });
+        ledger.roll_over(3 * DAY);
+
+        assert_eq!(ledger.settlements().len(), 1);
+        assert_eq!(ledger.settlements()[0].payout, usdc(10.0));
+    }
+}
@@ -0,0 +1,86 @@
+//! PolyShark
+//!
+//! Arbitrage agent for Polymarket binary markets: market data providers,
+//! signal detection and strategies, execution, position management, and
+//! configuration. The `polyshark` binary wires these into the live agent.
+
+pub mod accounts;
+pub mod agent;
+pub mod alerts;
+pub mod allocator;
+pub mod allowance_history;
+pub mod anomaly;
+pub mod api;
+pub mod arb;
+pub mod audit;
+pub mod auth;
+pub mod bankroll;
+pub mod breakdown;
+pub mod cadence;
+pub mod candles;
+pub mod config;
+pub mod constraint;
+pub mod correlation;
+pub mod ctf;
+pub mod data_source;
+pub mod discovery;
+pub mod engine;
+pub mod evm;
+pub mod exchange;
+pub mod execution;
+pub mod expiry;
+pub mod exposure;
+pub mod external;
+pub mod fee_calibrator;
+pub mod fees;
+pub mod fills;
+pub mod gamma;
+pub mod gas;
+pub mod latency;
+pub mod ledger;
+pub mod market;
+pub mod market_store;
+pub mod mean_reversion;
+pub mod metamask;
+pub mod metrics;
+pub mod money;
+pub mod order_spec;
+pub mod orders;
+pub mod panics;
+pub mod pipeline;
+pub mod policy;
+pub mod positions;
+pub mod pretrade;
+pub mod quarantine;
+pub mod quote;
+pub mod reconcile;
+pub mod redemption;
+pub mod routing;
+pub mod script;
+pub mod secrets;
+pub mod shadow;
+pub mod signer;
+pub mod simulate;
+pub mod simulation;
+pub mod skips;
+pub mod slippage;
+pub mod solana;
+pub mod spread_history;
+pub mod storage;
+pub mod strategy;
+pub mod streak;
+pub mod tape;
+pub mod throttle;
+pub mod tui;
+pub mod tuning;
+pub mod types;
+pub mod user_stream;
+pub mod utilization;
+pub mod wallet;
+pub mod watchdog;
+pub mod webhook;
+pub mod websocket;
+pub mod workers;
+
+#[cfg(test)]
+mod test_util;
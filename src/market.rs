@@ -1,12 +1,40 @@
-use crate::types::{Market, OrderBook, PriceLevel};
+use crate::cadence::RateLimiter;
+use crate::discovery::Listing;
+use crate::gamma::{GammaEvent, GammaMarket};
+use crate::metrics::{Endpoint, LatencyTracker};
+use crate::types::{Market, MarketId, OrderBook, PriceLevel, Resolution, TokenId};
+use serde::Deserialize;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::error::Error;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Token ids per batched `/books` or `/midpoints` request
+const BOOKS_PER_REQUEST: usize = 100;
+
+/// What `hydrate_market_prices` fetches to mark outcome prices
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HydrationMode {
+    /// Full order books, marked at their midpoints
+    #[default]
+    Books,
+    /// Midpoints only; books are fetched just for markets that signal
+    Midpoints,
+}
 
 #[allow(dead_code)]
 pub struct MarketDataProvider {
     client: reqwest::Client,
     gamma_url: String,
+    gamma_markets_url: String,
     clob_url: String,
+    clob_books_url: String,
+    clob_midpoints_url: String,
+    hydration: HydrationMode,
+    latency: Option<Arc<LatencyTracker>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 impl MarketDataProvider {
@@ -15,13 +43,49 @@ impl MarketDataProvider {
             client: reqwest::Client::new(),
             gamma_url: "https://gamma-api.polymarket.com/events?limit=20&active=true&closed=false"
                 .to_string(),
+            gamma_markets_url: "https://gamma-api.polymarket.com/markets".to_string(),
             clob_url: "https://clob.polymarket.com/book".to_string(),
+            clob_books_url: "https://clob.polymarket.com/books".to_string(),
+            clob_midpoints_url: "https://clob.polymarket.com/midpoints".to_string(),
+            hydration: HydrationMode::default(),
+            latency: None,
+            rate_limiter: None,
+        }
+    }
+
+    /// Record request latencies in a shared tracker
+    pub fn with_latency_tracker(mut self, latency: Arc<LatencyTracker>) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Throttle order book requests through a shared rate limiter
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Choose between midpoint-only and full-book price hydration
+    pub fn with_hydration(mut self, hydration: HydrationMode) -> Self {
+        self.hydration = hydration;
+        self
+    }
+
+    /// Get the latency tracker, if one is attached
+    pub fn latency_tracker(&self) -> Option<&Arc<LatencyTracker>> {
+        self.latency.as_ref()
+    }
+
+    fn record_latency(&self, endpoint: Endpoint, start: Instant) {
+        if let Some(latency) = &self.latency {
+            latency.record_since(endpoint, start);
         }
     }
 
     /// Fetch all active markets from Gamma API
     pub async fn fetch_markets(&self) -> Result<Vec<Market>, Box<dyn Error>> {
         println!("🌐 Fetching LIVE market data from Gamma API...");
+        let start = Instant::now();
         let resp = self
             .client
             .get(&self.gamma_url)
@@ -29,118 +93,84 @@ impl MarketDataProvider {
             .await?
             .text()
             .await?;
-        let json: Value = serde_json::from_str(&resp)?;
-
-        let mut markets = Vec::new();
-
-        if let Some(events) = json.as_array() {
-            for event in events {
-                if let Some(event_markets) = event["markets"].as_array() {
-                    for m in event_markets {
-                        // Extract basic fields
-                        let id = m["id"].as_str().unwrap_or("").to_string();
-                        let question = m["question"].as_str().unwrap_or("").to_string();
-                        let slug = event["slug"].as_str().unwrap_or("").to_string();
-
-                        // Extract outcomes
-                        let outcomes: Vec<String> = m["outcomes"]
-                            .as_array()
-                            .map(|arr| {
-                                arr.iter()
-                                    .map(|v| v.as_str().unwrap_or("").to_string())
-                                    .collect()
-                            })
-                            .unwrap_or_default();
-
-                        // Extract CLOB Token IDs (Critical)
-                        // Note: Gamma API returns this as a STRINGIFIED JSON array, e.g. "[\"123\", \"456\"]"
-                        let clob_token_ids: Vec<String> =
-                            if let Some(s) = m["clobTokenIds"].as_str() {
-                                serde_json::from_str(s).unwrap_or_default()
-                            } else {
-                                // Fallback if it somehow is an actual array (future proofing)
-                                m["clobTokenIds"]
-                                    .as_array()
-                                    .map(|arr| {
-                                        arr.iter()
-                                            .map(|v| v.as_str().unwrap_or("").to_string())
-                                            .collect()
-                                    })
-                                    .unwrap_or_default()
-                            };
-
-                        // Debug: Print what we found
-                        // println!("DEBUG: Found market '{}' with {} tokens", slug, clob_token_ids.len());
-
-                        // Skip if incomplete execution data
-                        if clob_token_ids.len() < 2 {
-                            // println!("DEBUG: Skipping {} (Not enough tokens)", slug);
-                            continue;
-                        }
-
-                        markets.push(Market {
-                            id,
-                            question,
-                            slug,
-                            outcomes,
-                            outcome_prices: vec![0.5, 0.5], // Will be updated by book fetch
-                            clob_token_ids,
-                            best_bid: None,
-                            best_ask: None,
-                            maker_base_fee: 0,
-                            taker_base_fee: 200, // Standard 2%
-                            liquidity: 0.0,      // Updated lazily
-                            volume_24hr: 0.0,
-                            active: true,
-                            accepting_orders: true,
-                        });
-                    }
-                }
-            }
-        }
+        self.record_latency(Endpoint::GammaFetch, start);
+        let events: Vec<GammaEvent> = serde_json::from_str(&resp)?;
 
+        // Markets without a full set of CLOB tokens can't be executed against
+        let markets = events
+            .iter()
+            .flat_map(|event| event.markets.iter().filter_map(|m| m.to_market(event)))
+            .collect();
         Ok(markets)
     }
 
-    /// concurrently hydrate prices for all markets (Batch/Parallel)
-    pub async fn hydrate_market_prices(&self, markets: &mut Vec<Market>) {
-        use futures_util::stream::{self, StreamExt};
+    /// Fetch the most recently created open markets, newest first
+    pub async fn fetch_newest_listings(&self, limit: u32) -> Result<Vec<Listing>, Box<dyn Error>> {
+        let url = format!(
+            "{}?order=createdAt&ascending=false&active=true&closed=false&limit={}",
+            self.gamma_markets_url, limit
+        );
+        let start = Instant::now();
+        let resp = self.client.get(&url).send().await?.text().await?;
+        self.record_latency(Endpoint::GammaFetch, start);
+        let markets: Vec<GammaMarket> = serde_json::from_str(&resp)?;
+
+        let listings = markets
+            .into_iter()
+            .filter_map(|m| {
+                let event = GammaEvent {
+                    slug: m.slug.clone(),
+                    category: None,
+                    markets: Vec::new(),
+                };
+                let market = m.to_market(&event)?;
+                Some(Listing {
+                    market,
+                    created_at: m.created_at,
+                })
+            })
+            .collect();
+        Ok(listings)
+    }
 
-        println!("⚡ Hydrating prices concurrently (Concurrency: 50)...");
+    /// Hydrate outcome prices for all markets in batched requests
+    pub async fn hydrate_market_prices(&self, markets: &mut [Market]) {
+        let token_ids: Vec<TokenId> = markets
+            .iter()
+            .flat_map(|m| m.clob_token_ids.iter().cloned())
+            .collect();
+        println!(
+            "⚡ Hydrating {} token prices in {} batched {:?} requests...",
+            token_ids.len(),
+            token_ids.len().div_ceil(BOOKS_PER_REQUEST),
+            self.hydration
+        );
         let start = std::time::Instant::now();
 
-        // 1. Flatten all tasks: (market_idx, token_idx, token_id)
-        let mut tasks = Vec::new();
-        for (m_idx, market) in markets.iter().enumerate() {
-            for (t_idx, token_id) in market.clob_token_ids.iter().enumerate() {
-                tasks.push((m_idx, t_idx, token_id.clone()));
+        let fetched = match self.hydration {
+            HydrationMode::Midpoints => self.fetch_midpoints(&token_ids).await,
+            HydrationMode::Books => self.fetch_order_books(&token_ids).await.map(|books| {
+                books
+                    .into_iter()
+                    .filter_map(|(token_id, book)| Some((token_id, book.midpoint()?)))
+                    .collect()
+            }),
+        };
+        let midpoints = match fetched {
+            Ok(midpoints) => midpoints,
+            Err(e) => {
+                println!("   ⚠️ Batched price fetch failed: {}", e);
+                return;
             }
-        }
-
-        // 2. Create stream
-        let fetches = stream::iter(tasks)
-            .map(|(m_idx, t_idx, token_id)| {
-                let client = &self; // Ref to self
-                async move {
-                    let res = client.fetch_order_book(&token_id).await;
-                    (m_idx, t_idx, res)
-                }
-            })
-            .buffer_unordered(50); // Concurrent limit
-
-        // 3. Collect results
-        let results: Vec<_> = fetches.collect().await;
+        };
 
-        // 4. Update markets
         let mut update_count = 0;
-        for (m_idx, t_idx, res) in results {
-            if let Ok(book) = res {
-                let price = book.midpoint().unwrap_or(0.0);
-                if price > 0.0 {
-                    // println!("   CTX: Market {} | Token {} | Price: {:.3}", markets[m_idx].slug, t_idx, price);
-                    // Ensure vector is sized (it should be 2, but let's be safe)
-                    if t_idx < markets[m_idx].outcome_prices.len() {
-                        markets[m_idx].outcome_prices[t_idx] = price;
+        for market in markets.iter_mut() {
+            for (idx, token_id) in market.clob_token_ids.iter().enumerate() {
+                let mid = midpoints.get(token_id).copied();
+                if let (Some(mid), Some(price)) = (mid, market.outcome_prices.get_mut(idx)) {
+                    if mid > 0.0 {
+                        *price = mid;
                         update_count += 1;
                     }
                 }
@@ -154,34 +184,214 @@ impl MarketDataProvider {
         );
     }
 
+    /// Fetch a single market and return its resolution once it has settled
+    pub async fn fetch_resolution(
+        &self,
+        market_id: &MarketId,
+    ) -> Result<Option<Resolution>, Box<dyn Error>> {
+        let url = format!("{}/{}", self.gamma_markets_url, market_id);
+        let start = Instant::now();
+        let resp = self.client.get(&url).send().await?.text().await?;
+        self.record_latency(Endpoint::GammaFetch, start);
+        let market: GammaMarket = serde_json::from_str(&resp)?;
+        Ok(market.resolution())
+    }
+
     /// Fetch order book for a market from CLOB API
-    pub async fn fetch_order_book(&self, token_id: &str) -> Result<OrderBook, Box<dyn Error>> {
+    pub async fn fetch_order_book(&self, token_id: &TokenId) -> Result<OrderBook, Box<dyn Error>> {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
         let url = format!("{}?token_id={}", self.clob_url, token_id);
+        let start = Instant::now();
         let resp = self.client.get(&url).send().await?.text().await?;
-        let json: Value = serde_json::from_str(&resp)?;
+        self.record_latency(Endpoint::ClobBook, start);
+        let book: ClobBook = serde_json::from_str(&resp)?;
+        Ok(book.into_order_book(token_id))
+    }
 
-        // Helper to parse price/size strings
-        let parse_level = |level: &Value| -> Option<PriceLevel> {
-            let p = level["price"].as_str()?.parse::<f64>().ok()?;
-            let s = level["size"].as_str()?.parse::<f64>().ok()?;
-            Some(PriceLevel { price: p, size: s })
-        };
+    /// Fetch books for many tokens, a batch of tokens per request
+    ///
+    /// Tokens the CLOB has no book for are missing from the result.
+    pub async fn fetch_order_books(
+        &self,
+        token_ids: &[TokenId],
+    ) -> Result<HashMap<TokenId, OrderBook>, Box<dyn Error>> {
+        let mut books = HashMap::new();
+        for chunk in token_ids.chunks(BOOKS_PER_REQUEST) {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+            let body: Vec<Value> = chunk
+                .iter()
+                .map(|token_id| serde_json::json!({ "token_id": token_id }))
+                .collect();
+            let start = Instant::now();
+            let resp = self
+                .client
+                .post(&self.clob_books_url)
+                .json(&body)
+                .send()
+                .await?
+                .text()
+                .await?;
+            self.record_latency(Endpoint::ClobBook, start);
+            books.extend(parse_books(&resp)?);
+        }
+        Ok(books)
+    }
+
+    /// Fetch midpoints for many tokens, a batch of tokens per request
+    ///
+    /// Much lighter than full books when only prices are needed.
+    pub async fn fetch_midpoints(
+        &self,
+        token_ids: &[TokenId],
+    ) -> Result<HashMap<TokenId, f64>, Box<dyn Error>> {
+        let mut midpoints = HashMap::new();
+        for chunk in token_ids.chunks(BOOKS_PER_REQUEST) {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+            let body: Vec<Value> = chunk
+                .iter()
+                .map(|token_id| serde_json::json!({ "token_id": token_id }))
+                .collect();
+            let start = Instant::now();
+            let resp = self
+                .client
+                .post(&self.clob_midpoints_url)
+                .json(&body)
+                .send()
+                .await?
+                .text()
+                .await?;
+            self.record_latency(Endpoint::ClobMidpoint, start);
+            midpoints.extend(parse_midpoints(&resp)?);
+        }
+        Ok(midpoints)
+    }
+}
 
-        let bids: Vec<PriceLevel> = json["bids"]
-            .as_array()
-            .map(|arr| arr.iter().filter_map(parse_level).collect())
-            .unwrap_or_default();
+/// A CLOB number; prices and sizes arrive as strings
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "ClobNumberRaw")]
+struct ClobNumber(f64);
 
-        let asks: Vec<PriceLevel> = json["asks"]
-            .as_array()
-            .map(|arr| arr.iter().filter_map(parse_level).collect())
-            .unwrap_or_default();
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ClobNumberRaw {
+    Text(String),
+    Number(f64),
+}
 
-        Ok(OrderBook {
-            token_id: token_id.to_string(),
-            bids,
-            asks,
+impl TryFrom<ClobNumberRaw> for ClobNumber {
+    type Error = String;
+
+    fn try_from(raw: ClobNumberRaw) -> Result<Self, Self::Error> {
+        match raw {
+            ClobNumberRaw::Number(n) => Ok(Self(n)),
+            ClobNumberRaw::Text(s) => s
+                .parse()
+                .map(Self)
+                .map_err(|_| format!("invalid number {:?}", s)),
+        }
+    }
+}
+
+/// One price level of a CLOB book
+#[derive(Debug, Deserialize)]
+struct ClobLevel {
+    price: ClobNumber,
+    size: ClobNumber,
+}
+
+/// A CLOB book snapshot from `/book` or `/books`
+#[derive(Debug, Deserialize)]
+struct ClobBook {
+    /// Missing on the error entries `/books` returns for unknown tokens
+    asset_id: Option<TokenId>,
+    #[serde(default)]
+    bids: Vec<ClobLevel>,
+    #[serde(default)]
+    asks: Vec<ClobLevel>,
+}
+
+impl ClobBook {
+    fn into_order_book(self, token_id: &TokenId) -> OrderBook {
+        let levels = |side: Vec<ClobLevel>| -> Vec<PriceLevel> {
+            side.into_iter()
+                .map(|level| PriceLevel {
+                    price: level.price.0,
+                    size: level.size.0,
+                })
+                .collect()
+        };
+        OrderBook {
+            token_id: token_id.clone(),
+            bids: levels(self.bids),
+            asks: levels(self.asks),
             timestamp: 0, // Not provided by snapshot endpoint cleanly
+        }
+    }
+}
+
+/// Parse a batched `/midpoints` response (token id -> midpoint string)
+fn parse_midpoints(body: &str) -> Result<HashMap<TokenId, f64>, serde_json::Error> {
+    let mids: HashMap<TokenId, ClobNumber> = serde_json::from_str(body)?;
+    Ok(mids
+        .into_iter()
+        .map(|(token_id, mid)| (token_id, mid.0))
+        .collect())
+}
+
+/// Parse a batched `/books` response, keyed by token id
+fn parse_books(body: &str) -> Result<HashMap<TokenId, OrderBook>, serde_json::Error> {
+    let books: Vec<ClobBook> = serde_json::from_str(body)?;
+    Ok(books
+        .into_iter()
+        .filter_map(|book| {
+            let token_id = book.asset_id.clone()?;
+            Some((token_id.clone(), book.into_order_book(&token_id)))
         })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_books() {
+        let body = json!([
+            {
+                "asset_id": "t1",
+                "bids": [{ "price": "0.47", "size": "100" }],
+                "asks": [{ "price": "0.49", "size": "50" }],
+            },
+            { "asset_id": "t2", "bids": [], "asks": [] },
+            { "error": "no book" },
+        ]);
+        let books = parse_books(&body.to_string()).unwrap();
+        assert_eq!(books.len(), 2);
+        assert_eq!(books["t1"].token_id, "t1");
+        assert!((books["t1"].midpoint().unwrap() - 0.48).abs() < 1e-9);
+        assert!(books["t2"].midpoint().is_none());
+
+        // A level that isn't a number is an error, not an empty book
+        let garbled = json!([{ "asset_id": "t1", "bids": [{ "price": "n/a", "size": "1" }] }]);
+        assert!(parse_books(&garbled.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_parse_midpoints() {
+        let mids = parse_midpoints(&json!({ "t1": "0.455", "t2": 0.5 }).to_string()).unwrap();
+        assert_eq!(mids.len(), 2);
+        assert_eq!(mids["t1"], 0.455);
+        assert_eq!(mids["t2"], 0.5);
+
+        assert!(parse_midpoints(&json!({ "t3": "n/a" }).to_string()).is_err());
+        assert!(parse_midpoints("[]").is_err());
     }
 }
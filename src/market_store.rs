@@ -0,0 +1,112 @@
+//! Shared market store
+//!
+//! The tracked markets behind one `Arc`, with an id index, so readers (the
+//! API, the terminal UI, simulations) take a pointer copy instead of cloning
+//! every question, slug, and outcome name. Writes are copy-on-write: they
+//! only copy the markets while an older snapshot is still held.
+
+use crate::types::{Market, MarketId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Markets in discovery order, indexed by id
+#[derive(Debug, Clone, Default)]
+pub struct MarketStore {
+    markets: Arc<Vec<Market>>,
+    index: Arc<HashMap<MarketId, usize>>,
+}
+
+impl MarketStore {
+    pub fn new(markets: Vec<Market>) -> Self {
+        let index = markets
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (m.id.clone(), i))
+            .collect();
+        Self {
+            markets: Arc::new(markets),
+            index: Arc::new(index),
+        }
+    }
+
+    pub fn as_slice(&self) -> &[Market] {
+        &self.markets
+    }
+
+    /// The current markets, shared rather than copied
+    pub fn snapshot(&self) -> Arc<Vec<Market>> {
+        self.markets.clone()
+    }
+
+    pub fn get(&self, market_id: &MarketId) -> Option<&Market> {
+        self.index.get(market_id).map(|&i| &self.markets[i])
+    }
+
+    pub fn len(&self) -> usize {
+        self.markets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.markets.is_empty()
+    }
+
+    /// Update a market's prices; false if the market isn't tracked
+    pub fn set_prices(&mut self, market_id: &MarketId, prices: &[f64]) -> bool {
+        let Some(&i) = self.index.get(market_id) else {
+            return false;
+        };
+        let market = &mut Arc::make_mut(&mut self.markets)[i];
+        market.outcome_prices.clear();
+        market.outcome_prices.extend_from_slice(prices);
+        true
+    }
+
+    /// Replace the tracked markets, keeping prices already refreshed for
+    /// markets that are still listed
+    pub fn replace(&mut self, mut markets: Vec<Market>) {
+        for market in &mut markets {
+            if let Some(known) = self.get(&market.id) {
+                market.outcome_prices.clone_from(&known.outcome_prices);
+            }
+        }
+        *self = Self::new(markets);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn test_snapshots_are_copy_on_write() {
+        let mut store = MarketStore::new(vec![
+            test_util::named_market("a", &[0.5, 0.5]),
+            test_util::named_market("b", &[0.4, 0.6]),
+        ]);
+        let before = store.snapshot();
+        assert!(Arc::ptr_eq(&before, &store.snapshot()));
+
+        assert!(store.set_prices(&"b".into(), &[0.3, 0.7]));
+        assert!(!store.set_prices(&"z".into(), &[0.3, 0.7]));
+        assert_eq!(
+            store.get(&"b".into()).unwrap().outcome_prices,
+            vec![0.3, 0.7]
+        );
+        // The snapshot taken before the write is unchanged
+        assert_eq!(before[1].outcome_prices, vec![0.4, 0.6]);
+
+        // Refreshed prices survive rediscovery; dropped markets go
+        store.replace(vec![
+            test_util::named_market("b", &[0.4, 0.6]),
+            test_util::named_market("c", &[0.5, 0.5]),
+        ]);
+        assert_eq!(store.len(), 2);
+        assert_eq!(
+            store.get(&"b".into()).unwrap().outcome_prices,
+            vec![0.3, 0.7]
+        );
+        assert!(store.get(&"a".into()).is_none());
+        assert_eq!(store.as_slice()[1].id, MarketId::from("c"));
+    }
+}
@@ -0,0 +1,203 @@
+//! Mean-reversion strategy module
+//!
+//! Reads each market's sum-to-one spread from the shared spread history and
+//! enters when the current spread is an outlier (z-score above threshold),
+//! buying the outcome that fell furthest below its recent average. Exits are
+//! handled by the PositionManager's mean-reversion rules.
+
+use crate::config::StrategyParams;
+use crate::fees::FeeModel;
+use crate::gas::GasModel;
+use crate::order_spec::OrderType;
+use crate::spread_history::SpreadHistory;
+use crate::strategy::{Intent, Strategy};
+use crate::types::{Market, MarketId, Side};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// Entry strategy for statistically wide spreads
+#[derive(Debug)]
+pub struct MeanReversionStrategy {
+    /// Spread samples (and outcome price samples) per market
+    window: usize,
+    /// Spread z-score required to enter
+    z_threshold: f64,
+    trade_size: f64,
+    fee_model: FeeModel,
+    gas_model: GasModel,
+    /// Recent outcome prices per market id, oldest first
+    history: HashMap<MarketId, VecDeque<Vec<f64>>>,
+    /// Shared spread history (recorded by the market workers)
+    spread_history: Arc<SpreadHistory>,
+}
+
+impl MeanReversionStrategy {
+    pub fn new(window: usize, z_threshold: f64, spread_history: Arc<SpreadHistory>) -> Self {
+        Self {
+            window: window.max(2),
+            z_threshold,
+            trade_size: 5.0,
+            fee_model: FeeModel::new(0, 0),
+            gas_model: GasModel::free(),
+            history: HashMap::new(),
+            spread_history,
+        }
+    }
+
+    /// Size and fee schedule used when turning signals into intents
+    pub fn with_sizing(mut self, trade_size: f64, fee_model: FeeModel) -> Self {
+        self.trade_size = trade_size;
+        self.fee_model = fee_model;
+        self
+    }
+
+    /// Account for gas/relayer costs in expected profit
+    pub fn with_gas_model(mut self, gas_model: GasModel) -> Self {
+        self.gas_model = gas_model;
+        self
+    }
+
+    /// Z-score of the market's current spread against its history
+    ///
+    /// None until a full window has been observed or if spreads never moved.
+    pub fn spread_z_score(&self, market: &Market) -> Option<f64> {
+        let stats = self.spread_history.stats(&market.id, self.window)?;
+        if stats.std < f64::EPSILON {
+            return None;
+        }
+
+        Some((market.get_spread() - stats.mean) / stats.std)
+    }
+
+    /// Outcome furthest below its rolling average as (index, expected reversion)
+    fn most_depressed_outcome(&self, market: &Market) -> Option<(usize, f64)> {
+        let history = self.history.get(&market.id)?;
+        let n = history.len() as f64;
+        market
+            .outcome_prices
+            .iter()
+            .enumerate()
+            .map(|(idx, price)| {
+                let mean = history.iter().filter_map(|p| p.get(idx)).sum::<f64>() / n;
+                (idx, mean - price)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+}
+
+impl Strategy for MeanReversionStrategy {
+    fn name(&self) -> &'static str {
+        "mean_reversion"
+    }
+
+    fn scan(&self, markets: &[Market]) -> Vec<Intent> {
+        let fee_rate = self.fee_model.taker_rate();
+        markets
+            .iter()
+            .filter(|m| m.active && m.accepting_orders)
+            // Only underpriced bundles: some outcome is cheap and should recover
+            .filter(|m| m.outcome_prices.iter().sum::<f64>() < 1.0)
+            .filter(|m| {
+                self.spread_z_score(m)
+                    .is_some_and(|z| z >= self.z_threshold)
+            })
+            .filter_map(|market| {
+                let (idx, reversion) = self.most_depressed_outcome(market)?;
+                let token_id = market.clob_token_ids.get(idx)?;
+                let price = market.outcome_prices[idx];
+                let fee_rate = self.fee_model.market_taker_rate(&market.id, fee_rate);
+
+                let expected_profit = reversion * self.trade_size
+                    - self.trade_size * price * fee_rate * 2.0 // Entry and exit
+                    - self.gas_model.cost(2);
+                if expected_profit <= 0.0 {
+                    return None;
+                }
+
+                Some(Intent {
+                    strategy: self.name(),
+                    market_id: market.id.clone(),
+                    token_ids: vec![token_id.clone()],
+                    side: Side::Buy,
+                    size: self.trade_size,
+                    spread: market.get_spread(),
+                    edge: market.get_spread(),
+                    expected_profit,
+                    order_type: OrderType::Market,
+                })
+            })
+            .collect()
+    }
+
+    fn on_tick(&mut self, markets: &[Market], _now: u64) {
+        for market in markets {
+            let history = self.history.entry(market.id.clone()).or_default();
+            if history.len() == self.window {
+                history.pop_front();
+            }
+            history.push_back(market.outcome_prices.clone());
+        }
+    }
+
+    fn set_params(&mut self, params: &StrategyParams) {
+        self.trade_size = params.trade_size;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    /// Record a tick the way a market worker does
+    fn tick(strategy: &mut MeanReversionStrategy, history: &SpreadHistory, market: Market) {
+        history.record(&market.id, 0, market.get_spread());
+        strategy.on_tick(&[market], 0);
+    }
+
+    /// Strategy warmed up with spreads oscillating between 0% and 1%
+    fn warmed_up() -> MeanReversionStrategy {
+        let history = Arc::new(SpreadHistory::new(100));
+        let mut strategy = MeanReversionStrategy::new(10, 2.0, history.clone())
+            .with_sizing(100.0, FeeModel::new(0, 0));
+        for i in 0..10 {
+            let no = if i % 2 == 0 { 0.50 } else { 0.49 };
+            tick(&mut strategy, &history, test_util::fee_market(0.50, no));
+        }
+        strategy
+    }
+
+    #[test]
+    fn test_no_intent_until_window_filled() {
+        let history = Arc::new(SpreadHistory::new(100));
+        let mut strategy = MeanReversionStrategy::new(10, 2.0, history.clone());
+        tick(&mut strategy, &history, test_util::fee_market(0.50, 0.50));
+        assert!(strategy
+            .spread_z_score(&test_util::fee_market(0.40, 0.50))
+            .is_none());
+        assert!(strategy
+            .scan(&[test_util::fee_market(0.40, 0.50)])
+            .is_empty());
+    }
+
+    #[test]
+    fn test_enters_depressed_outcome_on_wide_spread() {
+        let strategy = warmed_up();
+
+        // Normal spread: nothing to do
+        assert!(strategy
+            .scan(&[test_util::fee_market(0.50, 0.495)])
+            .is_empty());
+
+        // YES drops to 0.44: 6% spread is far outside the 0-1% range
+        let market = test_util::fee_market(0.44, 0.50);
+        assert!(strategy.spread_z_score(&market).unwrap() > 2.0);
+
+        let intents = strategy.scan(&[market]);
+        assert_eq!(intents.len(), 1);
+        assert_eq!(intents[0].strategy, "mean_reversion");
+        assert_eq!(intents[0].token_ids, vec!["t1"]);
+        // Expected reversion back to the 0.50 average on 100 shares
+        assert!((intents[0].expected_profit - 6.0).abs() < 1e-9);
+    }
+}
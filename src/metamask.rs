@@ -3,6 +3,10 @@
 //! Provides ERC-7715 Advanced Permissions integration for the PolyShark agent.
 //! This module handles permission requests, allowance tracking, and transaction submission.
 
+use crate::allowance_history::{AllowanceEvent, AllowanceHistory, AllowancePoint};
+use crate::audit::{AuditEvent, AuditLog};
+use crate::money::{self, Decimal};
+use crate::policy::{PolicyEngine, PolicyViolation, SpendRequest};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -12,11 +16,56 @@ use tokio::sync::RwLock;
 pub struct PermissionGrant {
     pub permission_id: String,
     pub token: String,
-    pub daily_limit: f64,
-    pub spent_today: f64,
+    pub daily_limit: Decimal,
+    #[serde(default)]
+    pub spent_today: Decimal,
     pub expires_at: u64,
+    #[serde(default)]
     pub granted_at: u64,
+    #[serde(default)]
     pub revoked: bool,
+    /// Per-spend limits beyond the daily allowance
+    #[serde(default, flatten)]
+    pub scope: GrantScope,
+}
+
+/// Session-key scoping carried by a grant, as an ERC-7715 policy would encode it
+///
+/// Empty lists allow anything; `PolicyEngine` checks every spend against it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GrantScope {
+    /// Largest single trade (None: only the daily limit applies)
+    #[serde(default)]
+    pub max_per_trade: Option<Decimal>,
+    /// Contracts the agent may spend through (e.g. the CTF exchange)
+    #[serde(default)]
+    pub allowed_contracts: Vec<String>,
+    /// Market categories the agent may trade
+    #[serde(default)]
+    pub allowed_categories: Vec<String>,
+}
+
+impl PermissionGrant {
+    /// Seconds left before the grant expires (0 once expired)
+    pub fn secs_until_expiry(&self, now: u64) -> u64 {
+        self.expires_at.saturating_sub(now)
+    }
+}
+
+/// Load a permission grant supplied out of band (headless runs)
+///
+/// The JSON grant in `env_var` wins over the one in `file`; returns `None`
+/// when neither is set.
+pub fn load_grant(file: &str, env_var: &str) -> Result<Option<PermissionGrant>, MetaMaskError> {
+    let json = match std::env::var(env_var) {
+        Ok(json) if !json.trim().is_empty() => json,
+        _ if file.is_empty() => return Ok(None),
+        _ => std::fs::read_to_string(file)
+            .map_err(|e| MetaMaskError::InvalidGrant(format!("{}: {}", file, e)))?,
+    };
+    serde_json::from_str(&json)
+        .map(Some)
+        .map_err(|e| MetaMaskError::InvalidGrant(e.to_string()))
 }
 
 /// MetaMask connection status
@@ -32,7 +81,7 @@ pub enum ConnectionStatus {
 
 /// Strategy mode based on remaining allowance
 /// Adapts trading behavior to available resources
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum StrategyMode {
     /// < 30% allowance remaining - only high-edge trades
     Conservative,
@@ -42,6 +91,19 @@ pub enum StrategyMode {
     Aggressive,
 }
 
+impl StrategyMode {
+    pub const ALL: [StrategyMode; 3] = [Self::Conservative, Self::Normal, Self::Aggressive];
+
+    /// Name used in logs and API output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Conservative => "Conservative",
+            Self::Normal => "Normal",
+            Self::Aggressive => "Aggressive",
+        }
+    }
+}
+
 /// Agent operational status
 #[derive(Debug, Clone, PartialEq)]
 pub enum AgentStatus {
@@ -70,8 +132,12 @@ pub struct MetaMaskClient {
     permission: Arc<RwLock<Option<PermissionGrant>>>,
     /// User's wallet address
     wallet_address: Arc<RwLock<Option<String>>>,
-    /// Snap ID for communication (demo value)
-    snap_id: String,
+    /// Hash-chained record of permission and spend events
+    audit: Option<Arc<AuditLog>>,
+    /// Grant constraints checked before every spend
+    policy: PolicyEngine,
+    /// Spends and resets, for charting intraday usage
+    history: AllowanceHistory,
 }
 
 impl MetaMaskClient {
@@ -81,8 +147,60 @@ impl MetaMaskClient {
             status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
             permission: Arc::new(RwLock::new(None)),
             wallet_address: Arc::new(RwLock::new(None)),
-            snap_id: "npm:polyshark-metamask-snap".to_string(),
+            audit: None,
+            policy: PolicyEngine::default(),
+            history: AllowanceHistory::default(),
+        }
+    }
+
+    /// Check spends with `policy` (it knows the contract trades go through)
+    pub fn with_policy(mut self, policy: PolicyEngine) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Record every permission and spend event in an audit log
+    pub fn with_audit_log(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    fn audit(&self, event: AuditEvent) {
+        if let Some(audit) = &self.audit {
+            if let Err(e) = audit.record(event, Self::current_timestamp()) {
+                println!("⚠️ Failed to write audit log: {}", e);
+            }
+        }
+    }
+
+    fn audit_grant(&self, grant: &PermissionGrant) {
+        self.audit(AuditEvent::Grant {
+            permission_id: grant.permission_id.clone(),
+            token: grant.token.clone(),
+            daily_limit: grant.daily_limit,
+            expires_at: grant.expires_at,
+        });
+    }
+
+    /// Evaluate a spend against the grant's policy, recording a denial if refused
+    pub async fn authorize(&self, spend: &SpendRequest<'_>) -> Result<(), PolicyViolation> {
+        let result = self.policy.evaluate(
+            self.permission.read().await.as_ref(),
+            spend,
+            Self::current_timestamp(),
+        );
+        if let Err(e) = &result {
+            self.record_denial(spend.amount, &e.to_string());
         }
+        result
+    }
+
+    /// Record a trade refused for lack of allowance before it reached `record_spend`
+    pub fn record_denial(&self, amount: Decimal, reason: &str) {
+        self.audit(AuditEvent::Denial {
+            amount,
+            reason: reason.to_string(),
+        });
     }
 
     /// Get current connection status
@@ -92,22 +210,28 @@ impl MetaMaskClient {
 
     /// Check if we have a valid permission
     pub async fn has_valid_permission(&self) -> bool {
-        let perm = self.permission.read().await;
-        match &*perm {
-            Some(p) => !p.revoked && p.expires_at > Self::current_timestamp(),
-            None => false,
-        }
+        self.policy
+            .check_active(
+                self.permission.read().await.as_ref(),
+                Self::current_timestamp(),
+            )
+            .is_ok()
     }
 
     /// Get remaining daily allowance
-    pub async fn get_remaining_allowance(&self) -> f64 {
+    pub async fn get_remaining_allowance(&self) -> Decimal {
         let perm = self.permission.read().await;
         match &*perm {
-            Some(p) => (p.daily_limit - p.spent_today).max(0.0),
-            None => 0.0,
+            Some(p) => (p.daily_limit - p.spent_today).max(Decimal::ZERO),
+            None => Decimal::ZERO,
         }
     }
 
+    /// Timeline of spends and resets against the grant
+    pub fn allowance_history(&self) -> &AllowanceHistory {
+        &self.history
+    }
+
     /// Get current permission grant
     pub async fn get_permission(&self) -> Option<PermissionGrant> {
         self.permission.read().await.clone()
@@ -122,8 +246,8 @@ impl MetaMaskClient {
         let perm = self.permission.read().await;
         match &*perm {
             Some(p) => {
-                let remaining = (p.daily_limit - p.spent_today).max(0.0);
-                let percent = remaining / p.daily_limit;
+                let remaining = (p.daily_limit - p.spent_today).max(Decimal::ZERO);
+                let percent = money::to_f64(remaining) / money::to_f64(p.daily_limit);
 
                 if percent < 0.30 {
                     StrategyMode::Conservative
@@ -140,22 +264,19 @@ impl MetaMaskClient {
     /// Get current agent status
     pub async fn get_agent_status(&self) -> AgentStatus {
         let perm = self.permission.read().await;
-        match &*perm {
-            Some(p) => {
-                if p.revoked {
-                    AgentStatus::Idle
-                } else if p.expires_at < Self::current_timestamp() {
-                    AgentStatus::PermissionExpired
-                } else {
-                    AgentStatus::Running
-                }
-            }
-            None => AgentStatus::Idle,
+        match self
+            .policy
+            .check_active(perm.as_ref(), Self::current_timestamp())
+        {
+            Ok(()) => AgentStatus::Running,
+            Err(PolicyViolation::Expired) => AgentStatus::PermissionExpired,
+            Err(_) => AgentStatus::Idle,
         }
     }
 
     /// Set permission from external source (API)
     pub async fn set_permission(&self, grant: PermissionGrant) {
+        self.audit_grant(&grant);
         *self.permission.write().await = Some(grant.clone());
         *self.status.write().await = ConnectionStatus::PermissionGranted;
         println!(
@@ -177,7 +298,7 @@ impl MetaMaskClient {
         // Demo: Generate a fake address
         let address = format!(
             "0x{}",
-            hex::encode(&[
+            hex::encode([
                 0xDE, 0xAD, 0xBE, 0xEF, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09,
                 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F
             ])
@@ -201,7 +322,7 @@ impl MetaMaskClient {
     pub async fn request_permission(
         &self,
         token: &str,
-        daily_limit: f64,
+        daily_limit: Decimal,
         duration_days: u32,
     ) -> Result<PermissionGrant, MetaMaskError> {
         // Must be connected first
@@ -225,12 +346,14 @@ impl MetaMaskClient {
             permission_id: format!("perm_{}", now),
             token: token.to_string(),
             daily_limit,
-            spent_today: 0.0,
+            spent_today: Decimal::ZERO,
             expires_at: now + (duration_days as u64 * 86400),
             granted_at: now,
             revoked: false,
+            scope: GrantScope::default(),
         };
 
+        self.audit_grant(&grant);
         *self.permission.write().await = Some(grant.clone());
         *self.status.write().await = ConnectionStatus::PermissionGranted;
 
@@ -242,33 +365,78 @@ impl MetaMaskClient {
     }
 
     /// Record a spend against the permission
-    pub async fn record_spend(&self, amount: f64) -> Result<(), MetaMaskError> {
+    pub async fn record_spend(&self, spend: &SpendRequest<'_>) -> Result<(), MetaMaskError> {
+        let amount = spend.amount;
+        let result = self.apply_spend(spend).await;
+        match &result {
+            Ok((permission_id, spent_today)) => self.audit(AuditEvent::Spend {
+                permission_id: permission_id.clone(),
+                amount,
+                spent_today: *spent_today,
+            }),
+            Err(e) => self.record_denial(amount, &e.to_string()),
+        }
+        result.map(|_| ())
+    }
+
+    /// Charge the permission, returning its id and new daily total
+    async fn apply_spend(
+        &self,
+        spend: &SpendRequest<'_>,
+    ) -> Result<(String, Decimal), MetaMaskError> {
         let mut perm = self.permission.write().await;
+        let now = Self::current_timestamp();
+        self.policy.evaluate(perm.as_ref(), spend, now)?;
+        let p = perm.as_mut().ok_or(MetaMaskError::NoPermission)?;
+        self.charge(p, spend.amount, now);
+        Ok((p.permission_id.clone(), p.spent_today))
+    }
 
-        match &mut *perm {
-            Some(p) => {
-                if p.revoked {
-                    return Err(MetaMaskError::PermissionRevoked);
-                }
-                if p.expires_at < Self::current_timestamp() {
-                    return Err(MetaMaskError::PermissionExpired);
-                }
-                if p.spent_today + amount > p.daily_limit {
-                    return Err(MetaMaskError::InsufficientAllowance);
-                }
+    /// Charge a fill that already happened to the permission
+    ///
+    /// Unlike `record_spend` the policy is not re-checked: the USDC is spent
+    /// whether or not the grant still allows it, so an expired or revoked
+    /// grant, or one past its daily limit, is charged too. Fails only when
+    /// there is no grant to charge.
+    pub async fn record_fill(&self, amount: Decimal) -> Result<(), MetaMaskError> {
+        let mut perm = self.permission.write().await;
+        let p = perm.as_mut().ok_or(MetaMaskError::NoPermission)?;
+        self.charge(p, amount, Self::current_timestamp());
+        self.audit(AuditEvent::Spend {
+            permission_id: p.permission_id.clone(),
+            amount,
+            spent_today: p.spent_today,
+        });
+        Ok(())
+    }
 
-                p.spent_today += amount;
-                Ok(())
-            }
-            None => Err(MetaMaskError::NoPermission),
-        }
+    /// Add to the grant's daily spend and its allowance history
+    fn charge(&self, grant: &mut PermissionGrant, amount: Decimal, now: u64) {
+        grant.spent_today += amount;
+        self.history.record(AllowancePoint {
+            timestamp: now,
+            event: AllowanceEvent::Spend,
+            amount,
+            spent_today: grant.spent_today,
+            daily_limit: grant.daily_limit,
+        });
     }
 
     /// Reset daily spend (called at midnight UTC)
     pub async fn reset_daily_spend(&self) {
         let mut perm = self.permission.write().await;
         if let Some(p) = &mut *perm {
-            p.spent_today = 0.0;
+            p.spent_today = Decimal::ZERO;
+            self.history.record(AllowancePoint {
+                timestamp: Self::current_timestamp(),
+                event: AllowanceEvent::Reset,
+                amount: Decimal::ZERO,
+                spent_today: Decimal::ZERO,
+                daily_limit: p.daily_limit,
+            });
+            self.audit(AuditEvent::Reset {
+                permission_id: p.permission_id.clone(),
+            });
             println!("🔄 [MetaMask] Daily allowance reset");
         }
     }
@@ -280,6 +448,9 @@ impl MetaMaskClient {
         match &mut *perm {
             Some(p) => {
                 p.revoked = true;
+                self.audit(AuditEvent::Revoke {
+                    permission_id: p.permission_id.clone(),
+                });
                 *self.status.write().await = ConnectionStatus::Connected;
                 println!("🚫 [MetaMask] Permission Revoked: {}", p.permission_id);
                 Ok(())
@@ -321,6 +492,9 @@ pub enum MetaMaskError {
     InsufficientAllowance,
     TransactionFailed(String),
     ConnectionFailed(String),
+    InvalidGrant(String),
+    /// Outside the grant's per-trade, contract, or category scope
+    OutOfScope(String),
 }
 
 impl std::fmt::Display for MetaMaskError {
@@ -334,12 +508,26 @@ impl std::fmt::Display for MetaMaskError {
             Self::InsufficientAllowance => write!(f, "Insufficient daily allowance"),
             Self::TransactionFailed(msg) => write!(f, "Transaction failed: {}", msg),
             Self::ConnectionFailed(msg) => write!(f, "Connection failed: {}", msg),
+            Self::InvalidGrant(msg) => write!(f, "Invalid permission grant: {}", msg),
+            Self::OutOfScope(msg) => write!(f, "Outside permission scope: {}", msg),
         }
     }
 }
 
 impl std::error::Error for MetaMaskError {}
 
+impl From<PolicyViolation> for MetaMaskError {
+    fn from(violation: PolicyViolation) -> Self {
+        match violation {
+            PolicyViolation::NoGrant => Self::NoPermission,
+            PolicyViolation::Revoked => Self::PermissionRevoked,
+            PolicyViolation::Expired => Self::PermissionExpired,
+            PolicyViolation::DailyLimit { .. } => Self::InsufficientAllowance,
+            other => Self::OutOfScope(other.to_string()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -354,23 +542,69 @@ mod tests {
         assert_eq!(client.get_status().await, ConnectionStatus::Connected);
 
         // Request permission
-        let perm = client.request_permission("USDC", 10.0, 30).await.unwrap();
-        assert_eq!(perm.daily_limit, 10.0);
+        let perm = client
+            .request_permission("USDC", money::usdc(10.0), 30)
+            .await
+            .unwrap();
+        assert_eq!(perm.daily_limit, money::usdc(10.0));
         assert!(client.has_valid_permission().await);
 
         // Check allowance
-        assert_eq!(client.get_remaining_allowance().await, 10.0);
+        assert_eq!(client.get_remaining_allowance().await, money::usdc(10.0));
 
         // Record spend
-        client.record_spend(3.0).await.unwrap();
-        assert_eq!(client.get_remaining_allowance().await, 7.0);
+        let spend = |amount: f64| SpendRequest {
+            amount: money::usdc(amount),
+            category: "",
+        };
+        client.record_spend(&spend(3.0)).await.unwrap();
+        assert_eq!(client.get_remaining_allowance().await, money::usdc(7.0));
 
         // Try to overspend
-        let result = client.record_spend(8.0).await;
+        let result = client.record_spend(&spend(8.0)).await;
         assert!(matches!(result, Err(MetaMaskError::InsufficientAllowance)));
 
+        // A fill that already happened is charged regardless
+        client.record_fill(money::usdc(8.0)).await.unwrap();
+        assert_eq!(client.get_remaining_allowance().await, money::usdc(0.0));
+
         // Revoke
         client.revoke_permission().await.unwrap();
         assert!(!client.has_valid_permission().await);
+        client.record_fill(money::usdc(1.0)).await.unwrap();
+        assert_eq!(
+            client.get_permission().await.unwrap().spent_today,
+            money::usdc(12.0)
+        );
+        assert!(matches!(
+            MetaMaskClient::new().record_fill(money::usdc(1.0)).await,
+            Err(MetaMaskError::NoPermission)
+        ));
+    }
+
+    #[test]
+    fn test_load_grant_from_file() {
+        let path =
+            std::env::temp_dir().join(format!("polyshark-grant-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"{"permission_id":"perm_cli","token":"USDC","daily_limit":25.0,"expires_at":4102444800}"#,
+        )
+        .unwrap();
+
+        let grant = load_grant(path.to_str().unwrap(), "POLYSHARK_TEST_UNSET_GRANT")
+            .unwrap()
+            .unwrap();
+        assert_eq!(grant.permission_id, "perm_cli");
+        assert_eq!(grant.daily_limit, money::usdc(25.0));
+        assert_eq!(grant.spent_today, money::usdc(0.0));
+        assert!(!grant.revoked);
+
+        assert!(load_grant("", "POLYSHARK_TEST_UNSET_GRANT")
+            .unwrap()
+            .is_none());
+        std::fs::write(&path, "not json").unwrap();
+        assert!(load_grant(path.to_str().unwrap(), "POLYSHARK_TEST_UNSET_GRANT").is_err());
+        std::fs::remove_file(path).unwrap();
     }
 }
@@ -0,0 +1,192 @@
+//! Latency metrics module
+//!
+//! Tracks rolling latency percentiles per upstream endpoint so the agent can
+//! expose them to monitoring and stop trading when the exchange slows down.
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Samples kept per endpoint
+const WINDOW_SIZE: usize = 500;
+
+/// Upstream calls whose latency is tracked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    /// Gamma API market fetch
+    GammaFetch,
+    /// CLOB order book fetch
+    ClobBook,
+    /// CLOB batched midpoint fetch
+    ClobMidpoint,
+    /// Order submission
+    OrderSubmit,
+}
+
+impl Endpoint {
+    pub const ALL: [Endpoint; 4] = [
+        Self::GammaFetch,
+        Self::ClobBook,
+        Self::ClobMidpoint,
+        Self::OrderSubmit,
+    ];
+
+    /// Label used in metrics output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GammaFetch => "gamma_fetch",
+            Self::ClobBook => "clob_book",
+            Self::ClobMidpoint => "clob_midpoint",
+            Self::OrderSubmit => "order_submit",
+        }
+    }
+}
+
+/// Latency percentiles for one endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LatencyPercentiles {
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub samples: usize,
+}
+
+/// Rolling latency tracker shared between the fetchers, engine, and API
+#[derive(Debug)]
+pub struct LatencyTracker {
+    samples: Mutex<HashMap<Endpoint, VecDeque<f64>>>,
+    /// p99 above which trading is suspended (ms)
+    max_p99_ms: f64,
+}
+
+impl LatencyTracker {
+    pub fn new(max_p99_ms: u64) -> Self {
+        Self {
+            samples: Mutex::new(HashMap::new()),
+            max_p99_ms: max_p99_ms as f64,
+        }
+    }
+
+    /// Record one request duration
+    pub fn record(&self, endpoint: Endpoint, elapsed: Duration) {
+        let mut samples = self.samples.lock().unwrap();
+        let window = samples.entry(endpoint).or_default();
+        if window.len() == WINDOW_SIZE {
+            window.pop_front();
+        }
+        window.push_back(elapsed.as_secs_f64() * 1000.0);
+    }
+
+    /// Record the time elapsed since `start`
+    pub fn record_since(&self, endpoint: Endpoint, start: Instant) {
+        self.record(endpoint, start.elapsed());
+    }
+
+    /// Current percentiles for an endpoint (None until it has samples)
+    pub fn percentiles(&self, endpoint: Endpoint) -> Option<LatencyPercentiles> {
+        let samples = self.samples.lock().unwrap();
+        let mut sorted: Vec<f64> = samples.get(&endpoint)?.iter().copied().collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        Some(LatencyPercentiles {
+            p50_ms: percentile(&sorted, 0.50),
+            p95_ms: percentile(&sorted, 0.95),
+            p99_ms: percentile(&sorted, 0.99),
+            samples: sorted.len(),
+        })
+    }
+
+    /// Percentiles for every endpoint with samples, keyed by label
+    pub fn snapshot(&self) -> HashMap<&'static str, LatencyPercentiles> {
+        Endpoint::ALL
+            .iter()
+            .filter_map(|e| Some((e.as_str(), self.percentiles(*e)?)))
+            .collect()
+    }
+
+    /// First endpoint whose p99 exceeds the suspension threshold
+    pub fn degraded(&self) -> Option<(Endpoint, f64)> {
+        Endpoint::ALL.iter().find_map(|e| {
+            let p99 = self.percentiles(*e)?.p99_ms;
+            (p99 > self.max_p99_ms).then_some((*e, p99))
+        })
+    }
+
+    /// Render percentiles in Prometheus text exposition format
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# HELP polyshark_latency_ms Request latency by endpoint\n");
+        out.push_str("# TYPE polyshark_latency_ms summary\n");
+        for endpoint in Endpoint::ALL {
+            let Some(p) = self.percentiles(endpoint) else {
+                continue;
+            };
+            let label = endpoint.as_str();
+            for (quantile, value) in [("0.5", p.p50_ms), ("0.95", p.p95_ms), ("0.99", p.p99_ms)] {
+                out.push_str(&format!(
+                    "polyshark_latency_ms{{endpoint=\"{}\",quantile=\"{}\"}} {:.3}\n",
+                    label, quantile, value
+                ));
+            }
+            out.push_str(&format!(
+                "polyshark_latency_ms_count{{endpoint=\"{}\"}} {}\n",
+                label, p.samples
+            ));
+        }
+        out
+    }
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles() {
+        let tracker = LatencyTracker::new(1000);
+        for ms in 1..=100 {
+            tracker.record(Endpoint::ClobBook, Duration::from_millis(ms));
+        }
+
+        let p = tracker.percentiles(Endpoint::ClobBook).unwrap();
+        assert_eq!(p.samples, 100);
+        assert_eq!(p.p50_ms, 50.0);
+        assert_eq!(p.p95_ms, 95.0);
+        assert_eq!(p.p99_ms, 99.0);
+        assert!(tracker.percentiles(Endpoint::GammaFetch).is_none());
+    }
+
+    #[test]
+    fn test_degraded_when_p99_exceeds_threshold() {
+        let tracker = LatencyTracker::new(500);
+        tracker.record(Endpoint::GammaFetch, Duration::from_millis(100));
+        assert!(tracker.degraded().is_none());
+
+        tracker.record(Endpoint::OrderSubmit, Duration::from_millis(2000));
+        let (endpoint, p99) = tracker.degraded().unwrap();
+        assert_eq!(endpoint, Endpoint::OrderSubmit);
+        assert_eq!(p99, 2000.0);
+    }
+
+    #[test]
+    fn test_prometheus_output() {
+        let tracker = LatencyTracker::new(1000);
+        tracker.record(Endpoint::GammaFetch, Duration::from_millis(20));
+
+        let text = tracker.render_prometheus();
+        assert!(text
+            .contains("polyshark_latency_ms{endpoint=\"gamma_fetch\",quantile=\"0.99\"} 20.000"));
+        assert!(text.contains("polyshark_latency_ms_count{endpoint=\"gamma_fetch\"} 1"));
+        assert!(!text.contains("clob_book"));
+    }
+}
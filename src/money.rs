@@ -0,0 +1,71 @@
+//! USDC amounts
+//!
+//! Allowance, spend, fees, execution costs, and PnL are `Decimal` rather
+//! than `f64`, so thousands of small debits and trade results sum exactly
+//! instead of drifting.
+//! Amounts are kept at USDC's on-chain precision of 6 decimals, and every
+//! conversion into that precision uses an explicit rule: charges round up,
+//! so spend is never under-recorded, and everything else rounds half to even.
+//! Prices and sizes stay `f64`; they enter money math through `from_f64`.
+
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::RoundingStrategy;
+
+pub use rust_decimal::Decimal;
+
+/// Decimal places of the USDC token
+pub const USDC_DECIMALS: u32 = 6;
+
+/// Shortest decimal that round-trips `value` (0.1 is 0.1, not its binary expansion)
+pub fn from_f64(value: f64) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default()
+}
+
+/// A float amount (config value, price x size) at USDC precision, half to even
+pub fn usdc(value: f64) -> Decimal {
+    round(from_f64(value))
+}
+
+/// Round to USDC precision, half to even
+pub fn round(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(USDC_DECIMALS, RoundingStrategy::MidpointNearestEven)
+}
+
+/// Round an amount owed up to the next micro-USDC
+///
+/// Rounds toward positive infinity, so rebates (negative charges) round
+/// toward zero: both directions favour recording more spend, never less.
+pub fn charge(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(USDC_DECIMALS, RoundingStrategy::ToPositiveInfinity)
+}
+
+/// Lossy conversion for ratios and display
+pub fn to_f64(amount: Decimal) -> f64 {
+    amount.to_f64().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rounding_rules() {
+        // 0.1 + 0.2 is exact, unlike in f64
+        assert_eq!(usdc(0.1) + usdc(0.2), usdc(0.3));
+        assert_eq!(usdc(0.0000005), Decimal::ZERO);
+        assert_eq!(usdc(0.0000015), Decimal::new(2, 6));
+
+        // Fees round up a whole micro-USDC; rebates round toward zero
+        let fee = from_f64(0.4837) * from_f64(7.12) * Decimal::new(2, 2);
+        assert_eq!(charge(fee), Decimal::new(68_879, 6));
+        assert_eq!(charge(-fee), Decimal::new(-68_878, 6));
+    }
+
+    #[test]
+    fn test_many_small_spends_sum_exactly() {
+        let spent: Decimal = (0..10_000).map(|_| usdc(0.01)).sum();
+        assert_eq!(spent, Decimal::from(100));
+        let drift: f64 = (0..10_000).map(|_| 0.01).sum();
+        assert_ne!(drift, 100.0);
+    }
+}
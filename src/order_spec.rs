@@ -0,0 +1,336 @@
+//! Order sizing and precision rules
+//!
+//! Trade sizes are configured in shares or in USDC and converted to shares
+//! here. The CLOB rejects orders whose price is off the market's tick grid
+//! or whose size is below its minimum, so orders are also conformed before
+//! execution: prices snap to the tick in the direction that keeps them
+//! marketable, sizes round down to the CLOB's share precision.
+
+use crate::config::TradingConfig;
+use crate::types::{Market, OrderBook, Side};
+use serde::{Deserialize, Serialize};
+
+/// Decimal places the CLOB accepts for order sizes
+const SIZE_DECIMALS: i32 = 2;
+
+/// Tolerance for float noise when snapping to a grid
+const GRID_EPSILON: f64 = 1e-9;
+
+/// What `trading.trade_size` measures
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SizingBasis {
+    /// Outcome-token shares per leg
+    #[default]
+    Shares,
+    /// USDC per leg, converted to shares at current prices
+    Notional,
+}
+
+impl SizingBasis {
+    /// Shares per leg for `size`, given each leg's current price
+    ///
+    /// Legs of one intent always trade equal shares (a YES+NO bundle must
+    /// stay balanced), so notional sizing spends `size` per leg on average.
+    pub fn shares_per_leg(self, size: f64, leg_prices: &[f64]) -> Option<f64> {
+        match self {
+            Self::Shares => Some(size),
+            Self::Notional => {
+                let total: f64 = leg_prices.iter().sum();
+                (total > 0.0).then(|| size * leg_prices.len() as f64 / total)
+            }
+        }
+    }
+}
+
+/// How an order meets the book
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderType {
+    /// Take the full size from the book at whatever it costs
+    #[default]
+    Market,
+    /// Take levels up to the limit price; the rest rests on the book
+    Limit,
+    /// Take levels up to the limit price and cancel the rest
+    Ioc,
+    /// Fill the full size within the limit price, or nothing
+    Fok,
+    /// Rest at the limit price; cancelled rather than taking liquidity
+    PostOnly,
+}
+
+impl OrderType {
+    /// Time in force the CLOB order is posted with
+    pub fn time_in_force(self) -> &'static str {
+        match self {
+            Self::Market | Self::Fok => "FOK",
+            Self::Ioc => "IOC",
+            Self::Limit | Self::PostOnly => "GTC",
+        }
+    }
+
+    /// Whether the CLOB order carries the post-only flag
+    pub fn post_only(self) -> bool {
+        self == Self::PostOnly
+    }
+
+    /// Limit price for an order of `size` against `book` as it stands
+    ///
+    /// Taking orders are limited to the deepest level the size needs, so
+    /// they fill no worse than the book they were priced on; post-only
+    /// orders join the best price on their own side. None for market orders.
+    pub fn limit_price(self, book: &OrderBook, size: f64, side: Side) -> Option<f64> {
+        match self {
+            Self::Market => None,
+            Self::PostOnly => match side {
+                Side::Buy => book.best_bid(),
+                Side::Sell => book.best_ask(),
+            },
+            Self::Limit | Self::Ioc | Self::Fok => {
+                let levels = match side {
+                    Side::Buy => &book.asks,
+                    Side::Sell => &book.bids,
+                };
+                let mut remaining = size;
+                let mut deepest = None;
+                for level in levels {
+                    if remaining <= 0.0 {
+                        break;
+                    }
+                    remaining -= level.size;
+                    deepest = Some(level.price);
+                }
+                deepest
+            }
+        }
+    }
+}
+
+/// Side, type, and limit price of one order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrderTerms {
+    pub side: Side,
+    pub order_type: OrderType,
+    /// Worst price the order may trade at (None for market orders)
+    pub limit_price: Option<f64>,
+}
+
+impl OrderTerms {
+    /// A market order
+    pub fn market(side: Side) -> Self {
+        Self {
+            side,
+            order_type: OrderType::Market,
+            limit_price: None,
+        }
+    }
+
+    /// The part of `book` the order may take from on arrival
+    pub fn reachable(&self, book: &OrderBook) -> OrderBook {
+        match self.limit_price {
+            Some(limit) => book.within_limit(self.side, limit),
+            None => book.clone(),
+        }
+    }
+}
+
+/// Tick size and minimum size for one market
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrderSpec {
+    pub tick_size: f64,
+    pub min_size: f64,
+}
+
+/// A price and size the CLOB will accept
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConformedOrder {
+    pub price: f64,
+    pub size: f64,
+}
+
+impl OrderSpec {
+    /// The market's reported spec, with configured defaults for anything missing
+    pub fn for_market(market: &Market, trading: &TradingConfig) -> Self {
+        let pick = |reported: f64, fallback: f64| {
+            if reported > 0.0 {
+                reported
+            } else {
+                fallback
+            }
+        };
+        Self {
+            tick_size: pick(market.tick_size, trading.tick_size),
+            min_size: pick(market.min_order_size, trading.min_order_size),
+        }
+    }
+
+    /// Snap a price to the tick grid: buys round up, sells round down
+    pub fn round_price(&self, price: f64, side: Side) -> f64 {
+        if self.tick_size <= 0.0 {
+            return price;
+        }
+        let ticks = price / self.tick_size;
+        let ticks = match side {
+            Side::Buy => (ticks - GRID_EPSILON).ceil(),
+            Side::Sell => (ticks + GRID_EPSILON).floor(),
+        };
+        round_to(ticks * self.tick_size, decimals(self.tick_size))
+    }
+
+    /// Round a size down to the CLOB's share precision
+    pub fn round_size(&self, size: f64) -> f64 {
+        let scale = 10f64.powi(SIZE_DECIMALS);
+        ((size * scale) + GRID_EPSILON).floor() / scale
+    }
+
+    /// Conform an order, or explain why the CLOB would reject it
+    pub fn conform(
+        &self,
+        price: f64,
+        size: f64,
+        side: Side,
+    ) -> Result<ConformedOrder, OrderSpecError> {
+        let size = self.round_size(size);
+        if size < self.min_size {
+            return Err(OrderSpecError::BelowMinSize {
+                size,
+                min_size: self.min_size,
+            });
+        }
+        let price = self.round_price(price, side);
+        // Outcome prices trade strictly inside (0, 1)
+        if price < self.tick_size - GRID_EPSILON || price > 1.0 - self.tick_size + GRID_EPSILON {
+            return Err(OrderSpecError::PriceOutOfRange(price));
+        }
+        Ok(ConformedOrder { price, size })
+    }
+}
+
+/// Decimal places in a tick size (0.01 -> 2, 0.001 -> 3)
+fn decimals(tick_size: f64) -> i32 {
+    (-tick_size.log10()).ceil().max(0.0) as i32
+}
+
+fn round_to(value: f64, decimals: i32) -> f64 {
+    let scale = 10f64.powi(decimals);
+    (value * scale).round() / scale
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderSpecError {
+    BelowMinSize { size: f64, min_size: f64 },
+    PriceOutOfRange(f64),
+}
+
+impl std::fmt::Display for OrderSpecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BelowMinSize { size, min_size } => {
+                write!(f, "Size {} below market minimum {}", size, min_size)
+            }
+            Self::PriceOutOfRange(price) => write!(f, "Price {} outside tradeable range", price),
+        }
+    }
+}
+
+impl std::error::Error for OrderSpecError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PriceLevel;
+
+    #[test]
+    fn test_conform_rounds_to_tick_and_size_precision() {
+        let spec = OrderSpec {
+            tick_size: 0.01,
+            min_size: 5.0,
+        };
+        let buy = spec.conform(0.4837, 7.129, Side::Buy).unwrap();
+        assert_eq!(buy.price, 0.49);
+        assert_eq!(buy.size, 7.12);
+        assert_eq!(spec.round_price(0.4837, Side::Sell), 0.48);
+        // Already on the grid stays put despite float noise
+        assert_eq!(spec.round_price(0.1 + 0.2, Side::Buy), 0.3);
+
+        let fine = OrderSpec {
+            tick_size: 0.001,
+            min_size: 5.0,
+        };
+        assert_eq!(fine.round_price(0.4837, Side::Buy), 0.484);
+    }
+
+    #[test]
+    fn test_notional_sizing_converts_at_current_prices() {
+        assert_eq!(SizingBasis::Shares.shares_per_leg(5.0, &[0.25]), Some(5.0));
+        assert_eq!(
+            SizingBasis::Notional.shares_per_leg(5.0, &[0.25]),
+            Some(20.0)
+        );
+
+        // $5 per leg across a 0.40/0.60 bundle: 10 shares each, $10 in total
+        let shares = SizingBasis::Notional
+            .shares_per_leg(5.0, &[0.40, 0.60])
+            .unwrap();
+        assert!((shares - 10.0).abs() < 1e-9);
+        assert_eq!(SizingBasis::Notional.shares_per_leg(5.0, &[0.0]), None);
+    }
+
+    #[test]
+    fn test_order_type_limit_prices() {
+        let book = OrderBook {
+            token_id: "t1".into(),
+            bids: vec![PriceLevel {
+                price: 0.47,
+                size: 100.0,
+            }],
+            asks: vec![
+                PriceLevel {
+                    price: 0.50,
+                    size: 50.0,
+                },
+                PriceLevel {
+                    price: 0.52,
+                    size: 50.0,
+                },
+            ],
+            timestamp: 0,
+        };
+        assert_eq!(OrderType::Market.limit_price(&book, 60.0, Side::Buy), None);
+        assert_eq!(
+            OrderType::Fok.limit_price(&book, 50.0, Side::Buy),
+            Some(0.50)
+        );
+        assert_eq!(
+            OrderType::Fok.limit_price(&book, 60.0, Side::Buy),
+            Some(0.52)
+        );
+        assert_eq!(
+            OrderType::PostOnly.limit_price(&book, 60.0, Side::Buy),
+            Some(0.47)
+        );
+        assert_eq!(OrderType::Ioc.time_in_force(), "IOC");
+        assert!(OrderType::PostOnly.post_only());
+        assert_eq!(OrderType::PostOnly.time_in_force(), "GTC");
+    }
+
+    #[test]
+    fn test_conform_rejects_unplaceable_orders() {
+        let spec = OrderSpec {
+            tick_size: 0.01,
+            min_size: 5.0,
+        };
+        assert_eq!(
+            spec.conform(0.5, 4.999, Side::Buy),
+            Err(OrderSpecError::BelowMinSize {
+                size: 4.99,
+                min_size: 5.0
+            })
+        );
+        assert_eq!(
+            spec.conform(0.995, 10.0, Side::Buy),
+            Err(OrderSpecError::PriceOutOfRange(1.0))
+        );
+    }
+}
@@ -0,0 +1,1616 @@
+//! Order manager
+//!
+//! Tracks the account's orders and fills from CLOB user-channel events
+//! (order placements, updates, cancellations, and trade status changes) so
+//! order state is known in real time instead of by polling.
+//!
+//! Good-til-date orders are registered with their expiry; a background sweep
+//! cancels any still resting past it, and closes their executions once the
+//! exchange confirms the cancel. Resting quotes the market has moved away
+//! from are flagged for cancel-replace at the new touch.
+//!
+//! Orders are signed as CTF Exchange orders for the account's maker (the
+//! EOA, or the proxy wallet it signs for) and posted to the CLOB; each
+//! token's exchange and fee rate are looked up once and cached.
+//!
+//! Orders the CLOB refuses are classified by their error into a rejection
+//! reason, which decides the recovery: resize to what the account can fund,
+//! reprice onto the grid and the current touch, or quarantine the market.
+//!
+//! An order placed for an execution is tracked until the execution is over:
+//! each of its fills, and then its close (fully filled, cancelled, or
+//! expired), is queued as an `OrderUpdate` for the pipeline to book against
+//! the execution's reservation.
+
+use crate::auth::{AuthError, ClobAuth};
+use crate::money::{self, Decimal};
+use crate::order_spec::{OrderSpec, OrderType};
+use crate::signer::{ExchangeOrder, OrderMaker, ZERO_ADDRESS};
+use crate::types::{MarketId, OrderBook, Side, TokenId};
+use rust_decimal::prelude::ToPrimitive;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use tokio::sync::Notify;
+
+/// Fills kept for the API
+const MAX_FILLS: usize = 500;
+
+/// Size left unfilled that still counts as a full fill (float noise)
+const FILL_EPSILON: f64 = 1e-9;
+
+/// A maker order matched by a trade
+#[derive(Debug, Clone, Deserialize)]
+pub struct MakerOrder {
+    pub order_id: String,
+    pub matched_amount: String,
+    pub price: String,
+}
+
+/// Messages on the user channel
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "event_type", rename_all = "snake_case")]
+pub enum UserEvent {
+    /// Order placed, partially matched, or cancelled
+    Order {
+        id: String,
+        asset_id: TokenId,
+        market: String,
+        side: String,
+        price: String,
+        original_size: String,
+        size_matched: String,
+        /// PLACEMENT, UPDATE, or CANCELLATION
+        #[serde(rename = "type")]
+        kind: String,
+        #[serde(default)]
+        timestamp: String,
+    },
+    /// Trade involving one of our orders, re-sent on each status change
+    Trade {
+        id: String,
+        asset_id: TokenId,
+        side: String,
+        price: String,
+        size: String,
+        status: TradeStatus,
+        taker_order_id: String,
+        #[serde(default)]
+        maker_orders: Vec<MakerOrder>,
+        /// TAKER or MAKER, when the server includes it
+        #[serde(default)]
+        trader_side: Option<String>,
+        #[serde(default)]
+        timestamp: String,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+/// Lifecycle of a matched trade
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TradeStatus {
+    Matched,
+    Mined,
+    Confirmed,
+    Retrying,
+    Failed,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Order lifecycle as seen on the user channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum OrderStatus {
+    Live,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    /// Refused by the CLOB
+    Rejected,
+}
+
+/// Why the CLOB refused an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionReason {
+    /// Balance or exchange allowance can't fund the order
+    InsufficientBalance,
+    /// Price outside the tradeable range
+    PriceOutOfRange,
+    /// Price off the market's tick grid
+    TickSize,
+    BelowMinSize,
+    /// Market closed, resolved, or not accepting orders
+    MarketClosed,
+    /// A fill-or-kill order found too little liquidity
+    NotFilled,
+    Duplicate,
+    Other,
+}
+
+/// What to do about a rejected order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Recovery {
+    /// Retry at a size the account can fund and the market accepts
+    Resize,
+    /// Retry at a conforming price at the current touch
+    Reprice,
+    /// Stop trading the market's token
+    Quarantine,
+    /// Give up on the order
+    Drop,
+}
+
+impl RejectionReason {
+    /// Classify a venue error code or message (`errorMsg` of POST /order)
+    pub fn classify(error: &str) -> Self {
+        let error = error.to_ascii_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|n| error.contains(n));
+        if has(&["not_enough_balance", "not enough balance", "allowance"]) {
+            Self::InsufficientBalance
+        } else if has(&["tick_size", "tick size"]) {
+            Self::TickSize
+        } else if has(&["min_size", "minimum"]) {
+            Self::BelowMinSize
+        } else if has(&["invalid price", "price out of range", "price_out_of_range"]) {
+            Self::PriceOutOfRange
+        } else if has(&[
+            "market_not_ready",
+            "not yet ready",
+            "closed",
+            "not accepting orders",
+            "resolved",
+        ]) {
+            Self::MarketClosed
+        } else if has(&["fok_order_not_filled", "fully filled or killed"]) {
+            Self::NotFilled
+        } else if has(&["duplicated", "duplicate"]) {
+            Self::Duplicate
+        } else {
+            Self::Other
+        }
+    }
+
+    pub fn recovery(self) -> Recovery {
+        match self {
+            Self::InsufficientBalance | Self::BelowMinSize => Recovery::Resize,
+            Self::PriceOutOfRange | Self::TickSize | Self::NotFilled => Recovery::Reprice,
+            Self::MarketClosed => Recovery::Quarantine,
+            Self::Duplicate | Self::Other => Recovery::Drop,
+        }
+    }
+
+    /// Label used in logs and API output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InsufficientBalance => "insufficient_balance",
+            Self::PriceOutOfRange => "price_out_of_range",
+            Self::TickSize => "tick_size",
+            Self::BelowMinSize => "below_min_size",
+            Self::MarketClosed => "market_closed",
+            Self::NotFilled => "not_filled",
+            Self::Duplicate => "duplicate",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// A rejected order and how to recover from it
+#[derive(Debug, Clone)]
+pub struct Rejection {
+    pub order: Order,
+    pub reason: RejectionReason,
+    pub recovery: Recovery,
+    /// Venue error as received
+    pub error: String,
+    /// Execution the order was placed for
+    pub execution_id: Option<String>,
+}
+
+impl Rejection {
+    /// Replacement order for a resize or reprice recovery
+    ///
+    /// Resizing caps a buy at what `balance` (USDC) funds at the order's
+    /// price; repricing moves the order to the touch. Either way the result
+    /// is conformed to the market spec, and None if it still can't be placed.
+    pub fn retry(
+        &self,
+        spec: &OrderSpec,
+        book: &OrderBook,
+        balance: Option<f64>,
+    ) -> Option<Requote> {
+        let order = &self.order;
+        let (price, size) = match self.recovery {
+            Recovery::Resize => {
+                let affordable = match (order.side, balance) {
+                    (Side::Buy, Some(balance)) if order.price > 0.0 => balance / order.price,
+                    _ => f64::INFINITY,
+                };
+                (
+                    order.price,
+                    order.remaining().min(affordable).max(spec.min_size),
+                )
+            }
+            Recovery::Reprice => {
+                let touch = match order.side {
+                    Side::Buy => book.best_ask(),
+                    Side::Sell => book.best_bid(),
+                }?;
+                (touch, order.remaining())
+            }
+            Recovery::Quarantine | Recovery::Drop => return None,
+        };
+        let conformed = spec.conform(price, size, order.side).ok()?;
+        if order.side == Side::Buy
+            && balance.is_some_and(|balance| conformed.price * conformed.size > balance)
+        {
+            return None;
+        }
+        Some(Requote {
+            order_id: order.id.clone(),
+            token_id: order.token_id.clone(),
+            side: order.side,
+            from: order.price,
+            to: conformed.price,
+            size: conformed.size,
+        })
+    }
+}
+
+/// One of the account's orders
+#[derive(Debug, Clone, Serialize)]
+pub struct Order {
+    pub id: String,
+    /// Market the order was placed for; empty for orders this agent didn't
+    /// place
+    pub market: MarketId,
+    /// CLOB condition id, once the user channel reports the order
+    pub condition_id: String,
+    pub token_id: TokenId,
+    pub side: Side,
+    pub price: f64,
+    pub original_size: f64,
+    pub size_matched: f64,
+    pub status: OrderStatus,
+    pub updated_at: u64,
+    /// Good-til-date expiry (unix seconds), when registered
+    pub expires_at: Option<u64>,
+    /// Why the CLOB refused it, when rejected
+    pub rejection: Option<RejectionReason>,
+    /// Execution whose reservation the order spends, when placed for one
+    pub execution_id: Option<String>,
+}
+
+impl Order {
+    fn is_open(&self) -> bool {
+        matches!(
+            self.status,
+            OrderStatus::Live | OrderStatus::PartiallyFilled
+        )
+    }
+
+    /// Size still resting on the book
+    pub fn remaining(&self) -> f64 {
+        (self.original_size - self.size_matched).max(0.0)
+    }
+
+    /// Replacement quote if the touch on our side has moved more than
+    /// `threshold` from our price
+    ///
+    /// A bid the ask has come down to (or an ask the bid has reached) is
+    /// about to be picked off and is always stale.
+    pub fn requote(&self, book: &OrderBook, threshold: f64) -> Option<Requote> {
+        if !self.is_open() || self.remaining() <= 0.0 {
+            return None;
+        }
+        let to = OrderType::PostOnly.limit_price(book, self.remaining(), self.side)?;
+        let crossed = match self.side {
+            Side::Buy => book.best_ask().is_some_and(|ask| ask <= self.price),
+            Side::Sell => book.best_bid().is_some_and(|bid| bid >= self.price),
+        };
+        if !crossed && (to - self.price).abs() <= threshold {
+            return None;
+        }
+        Some(Requote {
+            order_id: self.id.clone(),
+            token_id: self.token_id.clone(),
+            side: self.side,
+            from: self.price,
+            to,
+            size: self.remaining(),
+        })
+    }
+}
+
+/// A resting quote to cancel and re-post at the current touch
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Requote {
+    pub order_id: String,
+    pub token_id: TokenId,
+    pub side: Side,
+    pub from: f64,
+    pub to: f64,
+    pub size: f64,
+}
+
+/// An order to sign and post to the CLOB
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderRequest {
+    /// Market the order trades, as tracked locally
+    pub market: MarketId,
+    pub token_id: TokenId,
+    pub side: Side,
+    /// Limit price, on the market's tick grid
+    pub price: f64,
+    /// Shares, at the CLOB's size precision
+    pub size: f64,
+    pub order_type: OrderType,
+    /// Execution whose reservation the order spends; its fills are booked
+    /// against it
+    pub execution_id: Option<String>,
+}
+
+impl OrderRequest {
+    /// The same order at a requote's price and size
+    pub fn requoted(&self, requote: &Requote) -> Self {
+        Self {
+            price: requote.to,
+            size: requote.size,
+            ..self.clone()
+        }
+    }
+
+    /// Unsigned exchange order funded by `maker`
+    fn exchange_order(&self, maker: &OrderMaker, fee_rate_bps: u64, salt: u64) -> ExchangeOrder {
+        let shares = money::from_f64(self.size);
+        let usdc = shares * money::from_f64(self.price);
+        let (maker_amount, taker_amount) = match self.side {
+            Side::Buy => (base_units(usdc), base_units(shares)),
+            Side::Sell => (base_units(shares), base_units(usdc)),
+        };
+        ExchangeOrder {
+            salt,
+            maker: maker.maker.clone(),
+            signer: maker.signer.clone(),
+            taker: ZERO_ADDRESS.to_string(),
+            token_id: self.token_id.to_string(),
+            maker_amount,
+            taker_amount,
+            expiration: 0,
+            nonce: 0,
+            fee_rate_bps,
+            side: self.side,
+            signature_type: maker.signature_type,
+        }
+    }
+
+    /// The order as tracked until the user channel reports on it
+    fn order(&self, id: String, now: u64) -> Order {
+        Order {
+            id,
+            market: self.market.clone(),
+            condition_id: String::new(),
+            token_id: self.token_id.clone(),
+            side: self.side,
+            price: self.price,
+            original_size: self.size,
+            size_matched: 0.0,
+            status: OrderStatus::Live,
+            updated_at: now,
+            expires_at: None,
+            rejection: None,
+            execution_id: self.execution_id.clone(),
+        }
+    }
+}
+
+/// An amount in 6-decimal base units, rounded down
+fn base_units(amount: Decimal) -> u64 {
+    (amount * Decimal::from(10u64.pow(money::USDC_DECIMALS)))
+        .trunc()
+        .to_u64()
+        .unwrap_or(0)
+}
+
+/// Exchange and fee rate a token's orders are signed for
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TokenTerms {
+    /// Settled on the neg-risk exchange (multi-outcome markets)
+    neg_risk: bool,
+    fee_rate_bps: u64,
+}
+
+/// CLOB answer to POST /order
+#[derive(Debug, Clone, Deserialize)]
+pub struct PostedOrder {
+    #[serde(default)]
+    pub success: bool,
+    #[serde(default, rename = "errorMsg")]
+    pub error_msg: String,
+    #[serde(default, rename = "orderID")]
+    pub order_id: String,
+    /// live, matched, delayed, or unmatched
+    #[serde(default)]
+    pub status: String,
+}
+
+/// A good-til-date order's expiry and the execution that reserved for it
+#[derive(Debug, Clone, PartialEq)]
+struct Expiry {
+    execution_id: Option<String>,
+    expires_at: u64,
+}
+
+/// A good-til-date order swept past its expiry
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpiredOrder {
+    pub order_id: String,
+    /// Execution whose ledger reservation should be released
+    pub execution_id: Option<String>,
+}
+
+/// Our side of one trade
+#[derive(Debug, Clone, Serialize)]
+pub struct Fill {
+    pub trade_id: String,
+    pub order_id: String,
+    pub token_id: TokenId,
+    pub side: Side,
+    pub price: f64,
+    pub size: f64,
+    pub status: TradeStatus,
+    /// Whether our order was resting (maker) rather than taking
+    pub maker: bool,
+    pub timestamp: u64,
+}
+
+impl Fill {
+    /// Whether the trade has gone through (a retrying trade may still fail)
+    fn is_matched(&self) -> bool {
+        matches!(
+            self.status,
+            TradeStatus::Matched | TradeStatus::Mined | TradeStatus::Confirmed
+        )
+    }
+}
+
+/// Progress of an order placed for an execution
+#[derive(Debug, Clone)]
+struct Tracked {
+    execution_id: String,
+    token_id: TokenId,
+    side: Side,
+    size: f64,
+    filled: f64,
+    /// Trades already booked
+    trades: HashSet<String>,
+    /// Still working for its execution (not yet closed or replaced)
+    open: bool,
+}
+
+/// What happened to an order placed for an execution
+#[derive(Debug, Clone)]
+pub enum OrderUpdate {
+    /// The order traded; book the fill against the execution
+    Fill { execution_id: String, fill: Fill },
+    /// The execution is over: its order filled completely, or was cancelled
+    /// or expired with whatever it filled. Nothing more is spent through it.
+    Closed {
+        execution_id: String,
+        order_id: String,
+    },
+}
+
+#[derive(Debug, Default)]
+struct OrderBookkeeping {
+    orders: HashMap<String, Order>,
+    /// Most recent fills, oldest first
+    fills: VecDeque<Fill>,
+    /// Good-til-date expiries by order id, kept until swept
+    expiries: HashMap<String, Expiry>,
+    /// Rejected orders by reason
+    rejections: HashMap<RejectionReason, usize>,
+    /// Exchange and fee rate by token, fetched on first order
+    terms: HashMap<TokenId, TokenTerms>,
+    /// Orders placed for executions, by order id
+    tracked: HashMap<String, Tracked>,
+    /// Fills and closes of tracked orders not yet booked, oldest first
+    updates: VecDeque<OrderUpdate>,
+}
+
+impl OrderBookkeeping {
+    /// Whether an order is one of ours, acknowledged or not
+    fn is_ours(&self, order_id: &str) -> bool {
+        self.orders.contains_key(order_id) || self.tracked.contains_key(order_id)
+    }
+
+    /// Queue the close of a tracked order's execution, once
+    fn close(&mut self, order_id: &str) {
+        if let Some(tracked) = self.tracked.get_mut(order_id).filter(|t| t.open) {
+            tracked.open = false;
+            self.updates.push_back(OrderUpdate::Closed {
+                execution_id: tracked.execution_id.clone(),
+                order_id: order_id.to_string(),
+            });
+        }
+    }
+
+    /// Queue a tracked order's fill for booking, and its close once filled
+    fn book(&mut self, fill: &Fill) {
+        let Some(tracked) = self.tracked.get_mut(&fill.order_id) else {
+            return;
+        };
+        if !fill.is_matched() || !tracked.trades.insert(fill.trade_id.clone()) {
+            return;
+        }
+        tracked.filled += fill.size;
+        let (execution_id, done) = (
+            tracked.execution_id.clone(),
+            tracked.filled + FILL_EPSILON >= tracked.size,
+        );
+        self.updates.push_back(OrderUpdate::Fill {
+            execution_id,
+            fill: fill.clone(),
+        });
+        if done {
+            self.close(&fill.order_id);
+        }
+    }
+}
+
+/// Orders and fills fed by the user channel
+#[derive(Debug, Default)]
+pub struct OrderManager {
+    state: Mutex<OrderBookkeeping>,
+    /// Woken when updates are queued
+    updated: Notify,
+}
+
+fn parse_side(side: &str) -> Side {
+    if side.eq_ignore_ascii_case("SELL") {
+        Side::Sell
+    } else {
+        Side::Buy
+    }
+}
+
+fn parse_f64(value: &str) -> f64 {
+    value.parse().unwrap_or(0.0)
+}
+
+impl OrderManager {
+    /// Apply one user-channel event
+    pub fn apply(&self, event: &UserEvent) {
+        let mut state = self.state.lock().unwrap();
+        match event {
+            UserEvent::Order {
+                id,
+                asset_id,
+                market,
+                side,
+                price,
+                original_size,
+                size_matched,
+                kind,
+                timestamp,
+            } => {
+                let original_size = parse_f64(original_size);
+                let size_matched = parse_f64(size_matched);
+                let status = if kind.eq_ignore_ascii_case("CANCELLATION") {
+                    OrderStatus::Canceled
+                } else if size_matched > 0.0 && size_matched >= original_size {
+                    OrderStatus::Filled
+                } else if size_matched > 0.0 {
+                    OrderStatus::PartiallyFilled
+                } else {
+                    OrderStatus::Live
+                };
+                if kind.eq_ignore_ascii_case("PLACEMENT") {
+                    println!(
+                        "🧾 [Orders] Acknowledged {} {} {:.2} @ {}",
+                        side, asset_id, original_size, price
+                    );
+                }
+                // The event's market is the condition id; keep the local one
+                let local_market = state.orders.get(id).map(|o| o.market.clone());
+                let expires_at = state.expiries.get(id).map(|e| e.expires_at);
+                let execution_id = state.tracked.get(id).map(|t| t.execution_id.clone());
+                if status == OrderStatus::Canceled {
+                    state.close(id);
+                }
+                state.orders.insert(
+                    id.clone(),
+                    Order {
+                        id: id.clone(),
+                        market: local_market.unwrap_or_default(),
+                        condition_id: market.clone(),
+                        token_id: asset_id.clone(),
+                        side: parse_side(side),
+                        price: parse_f64(price),
+                        original_size,
+                        size_matched,
+                        status,
+                        updated_at: timestamp.parse().unwrap_or(0),
+                        expires_at,
+                        rejection: None,
+                        execution_id,
+                    },
+                );
+            }
+            UserEvent::Trade {
+                id,
+                asset_id,
+                side,
+                price,
+                size,
+                status,
+                taker_order_id,
+                maker_orders,
+                trader_side,
+                timestamp,
+            } => {
+                let timestamp = timestamp.parse().unwrap_or(0);
+                let ours_as_maker: Vec<&MakerOrder> = maker_orders
+                    .iter()
+                    .filter(|m| state.is_ours(&m.order_id))
+                    .collect();
+                let is_maker = match trader_side.as_deref() {
+                    Some(s) => s.eq_ignore_ascii_case("MAKER"),
+                    None => !state.is_ours(taker_order_id) && !ours_as_maker.is_empty(),
+                };
+
+                let fills: Vec<Fill> = if is_maker {
+                    ours_as_maker
+                        .iter()
+                        .filter_map(|m| {
+                            // Placed orders may not be acknowledged yet
+                            let (token_id, side) = match state.orders.get(&m.order_id) {
+                                Some(order) => (order.token_id.clone(), order.side),
+                                None => {
+                                    let tracked = state.tracked.get(&m.order_id)?;
+                                    (tracked.token_id.clone(), tracked.side)
+                                }
+                            };
+                            Some(Fill {
+                                trade_id: id.clone(),
+                                order_id: m.order_id.clone(),
+                                token_id,
+                                side,
+                                price: parse_f64(&m.price),
+                                size: parse_f64(&m.matched_amount),
+                                status: *status,
+                                maker: true,
+                                timestamp,
+                            })
+                        })
+                        .collect()
+                } else {
+                    vec![Fill {
+                        trade_id: id.clone(),
+                        order_id: taker_order_id.clone(),
+                        token_id: asset_id.clone(),
+                        side: parse_side(side),
+                        price: parse_f64(price),
+                        size: parse_f64(size),
+                        status: *status,
+                        maker: false,
+                        timestamp,
+                    }]
+                };
+
+                for fill in fills {
+                    // Status changes re-send the trade: update in place
+                    match state
+                        .fills
+                        .iter_mut()
+                        .find(|f| f.trade_id == fill.trade_id && f.order_id == fill.order_id)
+                    {
+                        Some(existing) => existing.status = fill.status,
+                        None => {
+                            println!(
+                                "💱 [Orders] Fill {:?} {:.2} {} @ {:.3} ({:?})",
+                                fill.side, fill.size, fill.token_id, fill.price, fill.status
+                            );
+                            if state.fills.len() == MAX_FILLS {
+                                state.fills.pop_front();
+                            }
+                            state.fills.push_back(fill.clone());
+                        }
+                    }
+                    state.book(&fill);
+                }
+            }
+            UserEvent::Unknown => {}
+        }
+        if !state.updates.is_empty() {
+            self.updated.notify_one();
+        }
+    }
+
+    /// Orders still resting on the book
+    pub fn open_orders(&self) -> Vec<Order> {
+        let state = self.state.lock().unwrap();
+        state
+            .orders
+            .values()
+            .filter(|o| o.is_open())
+            .cloned()
+            .collect()
+    }
+
+    /// Look up an order by id
+    pub fn order(&self, order_id: &str) -> Option<Order> {
+        self.state.lock().unwrap().orders.get(order_id).cloned()
+    }
+
+    /// Recent fills, oldest first
+    pub fn fills(&self) -> Vec<Fill> {
+        self.state.lock().unwrap().fills.iter().cloned().collect()
+    }
+
+    /// Sign and post an order for `auth`'s maker, tracking it as live
+    ///
+    /// An order the CLOB refuses, or that can't be signed or sent, is
+    /// recorded through `reject` and handed back with its recovery.
+    pub async fn submit(
+        &self,
+        auth: &ClobAuth,
+        request: &OrderRequest,
+        now: u64,
+    ) -> Result<Order, Rejection> {
+        let mut order = request.order(String::new(), now);
+        let posted = match self.post(auth, request, &mut order.id, now).await {
+            Ok(posted) => posted,
+            Err(e) => {
+                // Failed before signing, so there is no exchange hash to key it by
+                if order.id.is_empty() {
+                    order.id = format!("local-{}-{:08x}", now, rand::random::<u32>());
+                }
+                return Err(self.reject(order, &e.to_string()));
+            }
+        };
+        println!(
+            "📤 [Orders] Posted {:?} {:.2} {} @ {} ({})",
+            order.side, order.original_size, order.token_id, order.price, posted.status
+        );
+        let mut state = self.state.lock().unwrap();
+        let order = state.orders.entry(order.id.clone()).or_insert(order);
+        if posted.status.eq_ignore_ascii_case("matched") {
+            order.size_matched = order.original_size;
+            order.status = OrderStatus::Filled;
+        }
+        Ok(order.clone())
+    }
+
+    /// Track an order as placed under `order_id`, before it is sent
+    ///
+    /// An order placed for an execution is tracked from here on, so fills
+    /// reported before the post returns are still booked against it.
+    pub fn placed(&self, request: &OrderRequest, order_id: &str, now: u64) -> Order {
+        let mut state = self.state.lock().unwrap();
+        if let Some(execution_id) = &request.execution_id {
+            state.tracked.insert(
+                order_id.to_string(),
+                Tracked {
+                    execution_id: execution_id.clone(),
+                    token_id: request.token_id.clone(),
+                    side: request.side,
+                    size: request.size,
+                    filled: 0.0,
+                    trades: HashSet::new(),
+                    open: true,
+                },
+            );
+        }
+        // The user channel may have reported the placement first, without
+        // knowing the local market
+        state
+            .orders
+            .entry(order_id.to_string())
+            .and_modify(|order| order.market = request.market.clone())
+            .or_insert_with(|| request.order(order_id.to_string(), now))
+            .clone()
+    }
+
+    /// Sign and send `request`, setting `id` to the order hash once signed
+    async fn post(
+        &self,
+        auth: &ClobAuth,
+        request: &OrderRequest,
+        id: &mut String,
+        now: u64,
+    ) -> Result<PostedOrder, AuthError> {
+        let owner = auth
+            .credentials()
+            .ok_or(AuthError::MissingCredentials)?
+            .api_key
+            .clone();
+        let terms = self.token_terms(auth, &request.token_id).await?;
+        let order = request.exchange_order(
+            &auth.order_maker(),
+            terms.fee_rate_bps,
+            rand::random::<u32>().into(),
+        );
+        let (hash, signature) = auth.sign_order(&order, terms.neg_risk)?;
+        self.placed(request, &hash, now);
+        *id = hash;
+        post_order(
+            auth,
+            order_body(&order, &signature, &owner, request.order_type),
+        )
+        .await
+    }
+
+    /// Exchange and fee rate for a token's orders, fetched once
+    async fn token_terms(
+        &self,
+        auth: &ClobAuth,
+        token_id: &TokenId,
+    ) -> Result<TokenTerms, AuthError> {
+        let cached = self.state.lock().unwrap().terms.get(token_id).copied();
+        if let Some(terms) = cached {
+            return Ok(terms);
+        }
+
+        #[derive(Deserialize)]
+        struct NegRisk {
+            neg_risk: bool,
+        }
+        #[derive(Deserialize)]
+        struct FeeRate {
+            #[serde(default)]
+            base_fee: u64,
+        }
+        let NegRisk { neg_risk } =
+            get_json(auth, &format!("/neg-risk?token_id={}", token_id)).await?;
+        let FeeRate { base_fee } =
+            get_json(auth, &format!("/fee-rate?token_id={}", token_id)).await?;
+        let terms = TokenTerms {
+            neg_risk,
+            fee_rate_bps: base_fee,
+        };
+        self.state
+            .lock()
+            .unwrap()
+            .terms
+            .insert(token_id.clone(), terms);
+        Ok(terms)
+    }
+
+    /// Record an order the CLOB refused with `error`
+    ///
+    /// The order is kept as rejected, its expiry dropped, and the reason
+    /// counted; the returned rejection says how to recover.
+    pub fn reject(&self, mut order: Order, error: &str) -> Rejection {
+        let reason = RejectionReason::classify(error);
+        let recovery = reason.recovery();
+        println!(
+            "🚫 [Orders] Rejected {:?} {:.2} {} @ {}: {} ({}, {:?})",
+            order.side,
+            order.original_size,
+            order.token_id,
+            order.price,
+            error,
+            reason.as_str(),
+            recovery
+        );
+        let mut state = self.state.lock().unwrap();
+        let expired = state
+            .expiries
+            .remove(&order.id)
+            .and_then(|e| e.execution_id);
+        // Whoever placed it decides whether the execution retries or is over
+        if let Some(tracked) = state.tracked.get_mut(&order.id) {
+            tracked.open = false;
+        }
+        let execution_id = order.execution_id.clone().or(expired);
+        *state.rejections.entry(reason).or_default() += 1;
+        order.status = OrderStatus::Rejected;
+        order.rejection = Some(reason);
+        state.orders.insert(order.id.clone(), order.clone());
+        Rejection {
+            order,
+            reason,
+            recovery,
+            error: error.to_string(),
+            execution_id,
+        }
+    }
+
+    /// Rejected orders counted by reason
+    pub fn rejection_counts(&self) -> HashMap<&'static str, usize> {
+        self.state
+            .lock()
+            .unwrap()
+            .rejections
+            .iter()
+            .map(|(reason, count)| (reason.as_str(), *count))
+            .collect()
+    }
+
+    /// Register a good-til-date order's expiry (unix seconds)
+    ///
+    /// May be called before the placement is acknowledged on the user channel.
+    pub fn set_expiry(&self, order_id: &str, execution_id: Option<&str>, expires_at: u64) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(order) = state.orders.get_mut(order_id) {
+            order.expires_at = Some(expires_at);
+        }
+        state.expiries.insert(
+            order_id.to_string(),
+            Expiry {
+                execution_id: execution_id.map(str::to_string),
+                expires_at,
+            },
+        );
+    }
+
+    /// Take the good-til-date orders past their expiry that are still resting
+    ///
+    /// Orders not yet acknowledged count as resting. Expiries of orders that
+    /// filled or were cancelled are dropped once due.
+    pub fn take_expired(&self, now: u64) -> Vec<ExpiredOrder> {
+        let mut state = self.state.lock().unwrap();
+        let due: Vec<String> = state
+            .expiries
+            .iter()
+            .filter(|(_, e)| e.expires_at <= now)
+            .map(|(id, _)| id.clone())
+            .collect();
+        let mut expired = Vec::new();
+        for order_id in due {
+            let Some(expiry) = state.expiries.remove(&order_id) else {
+                continue;
+            };
+            if state.orders.get(&order_id).is_none_or(Order::is_open) {
+                expired.push(ExpiredOrder {
+                    order_id,
+                    execution_id: expiry.execution_id,
+                });
+            }
+        }
+        expired
+    }
+
+    /// Cancel expired good-til-date orders, returning those cancelled
+    ///
+    /// An order's execution is only closed once the exchange confirms the
+    /// cancel; a failed cancel is retried on the next sweep. Without
+    /// credentials the order is left to the exchange's own expiry, which the
+    /// user channel reports as a cancellation.
+    pub async fn sweep_expired(&self, auth: Option<&ClobAuth>, now: u64) -> Vec<ExpiredOrder> {
+        let mut cancelled = Vec::new();
+        for order in self.take_expired(now) {
+            let Some(auth) = auth else {
+                println!("⌛ [Orders] Order {} expired", order.order_id);
+                continue;
+            };
+            match cancel_order(auth, &order.order_id).await {
+                Ok(()) => {
+                    println!("⌛ [Orders] Cancelled expired order {}", order.order_id);
+                    self.cancelled(&order);
+                    cancelled.push(order);
+                }
+                Err(e) => {
+                    println!(
+                        "⚠️ [Orders] Failed to cancel expired order {}, retrying: {}",
+                        order.order_id, e
+                    );
+                    self.set_expiry(&order.order_id, order.execution_id.as_deref(), now);
+                }
+            }
+        }
+        cancelled
+    }
+
+    /// Mark an expired order cancelled and close its execution
+    fn cancelled(&self, expired: &ExpiredOrder) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(order) = state.orders.get_mut(&expired.order_id) {
+            order.status = OrderStatus::Canceled;
+        }
+        match (
+            &expired.execution_id,
+            state.tracked.contains_key(&expired.order_id),
+        ) {
+            (Some(execution_id), false) => state.updates.push_back(OrderUpdate::Closed {
+                execution_id: execution_id.clone(),
+                order_id: expired.order_id.clone(),
+            }),
+            _ => state.close(&expired.order_id),
+        }
+        if !state.updates.is_empty() {
+            self.updated.notify_one();
+        }
+    }
+
+    /// Cancel a stale quote so it can be re-posted at `requote.to`
+    ///
+    /// The quote is only marked cancelled once the exchange confirms it, so
+    /// a failed cancel leaves it to be retried rather than quoted twice.
+    /// Returns the execution whose reservation the cancelled quote held.
+    pub async fn cancel_replace(
+        &self,
+        auth: &ClobAuth,
+        requote: &Requote,
+    ) -> Result<Option<String>, AuthError> {
+        // The replacement takes over the execution: the cancellation must
+        // not close it, even if the user channel reports it first
+        let replaced = self.hand_over(&requote.order_id, false);
+        if let Err(e) = cancel_order(auth, &requote.order_id).await {
+            self.hand_over(&requote.order_id, replaced);
+            return Err(e);
+        }
+        let mut state = self.state.lock().unwrap();
+        if let Some(order) = state.orders.get_mut(&requote.order_id) {
+            order.status = OrderStatus::Canceled;
+        }
+        println!(
+            "♻️ [Orders] Requoting {:?} {:.2} {} {:.3} -> {:.3}",
+            requote.side, requote.size, requote.token_id, requote.from, requote.to
+        );
+        let expired = state
+            .expiries
+            .remove(&requote.order_id)
+            .and_then(|e| e.execution_id);
+        let tracked = state
+            .tracked
+            .get(&requote.order_id)
+            .map(|t| t.execution_id.clone());
+        Ok(tracked.or(expired))
+    }
+
+    /// Set whether a tracked order still works for its execution, returning
+    /// whether it did
+    fn hand_over(&self, order_id: &str, open: bool) -> bool {
+        let mut state = self.state.lock().unwrap();
+        match state.tracked.get_mut(order_id) {
+            Some(tracked) => std::mem::replace(&mut tracked.open, open),
+            None => false,
+        }
+    }
+
+    /// Close the execution of a cancelled order whose replacement never
+    /// made it onto the book
+    pub fn give_up(&self, order_id: &str) {
+        self.hand_over(order_id, true);
+        self.close(order_id);
+    }
+
+    /// Close a tracked order's execution (it was cancelled or expired)
+    pub fn close(&self, order_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.close(order_id);
+        if !state.updates.is_empty() {
+            self.updated.notify_one();
+        }
+    }
+
+    /// Take the fills and closes of tracked orders queued since the last call
+    pub fn take_updates(&self) -> Vec<OrderUpdate> {
+        self.state.lock().unwrap().updates.drain(..).collect()
+    }
+
+    /// Wait until updates are queued
+    pub async fn updated(&self) {
+        self.updated.notified().await;
+    }
+}
+
+/// Body of POST /order: the signed order, its owner's API key, and time in force
+fn order_body(
+    order: &ExchangeOrder,
+    signature: &str,
+    owner: &str,
+    order_type: OrderType,
+) -> String {
+    let side = match order.side {
+        Side::Buy => "BUY",
+        Side::Sell => "SELL",
+    };
+    serde_json::json!({
+        "order": {
+            "salt": order.salt,
+            "maker": order.maker,
+            "signer": order.signer,
+            "taker": order.taker,
+            "tokenId": order.token_id,
+            "makerAmount": order.maker_amount.to_string(),
+            "takerAmount": order.taker_amount.to_string(),
+            "expiration": order.expiration.to_string(),
+            "nonce": order.nonce.to_string(),
+            "feeRateBps": order.fee_rate_bps.to_string(),
+            "side": side,
+            "signatureType": order.signature_type.code(),
+            "signature": signature,
+        },
+        "owner": owner,
+        "orderType": order_type.time_in_force(),
+        "postOnly": order_type.post_only(),
+    })
+    .to_string()
+}
+
+/// Post a signed order to the CLOB, attributed to the builder if one is set
+///
+/// A refusal, whether an HTTP error or `success: false`, comes back as
+/// `AuthError::Refused` with the venue's message.
+pub async fn post_order(auth: &ClobAuth, body: String) -> Result<PostedOrder, AuthError> {
+    let resp = auth
+        .attributed(reqwest::Method::POST, "/order", body)?
+        .send()
+        .await
+        .map_err(|e| AuthError::Http(e.to_string()))?;
+    let status = resp.status();
+    let text = resp
+        .text()
+        .await
+        .map_err(|e| AuthError::Http(e.to_string()))?;
+
+    #[derive(Deserialize)]
+    struct Refusal {
+        #[serde(default)]
+        error: String,
+    }
+    match serde_json::from_str::<PostedOrder>(&text) {
+        Ok(posted) if status.is_success() && posted.success => Ok(posted),
+        Ok(posted) if !posted.error_msg.is_empty() => Err(AuthError::Refused(posted.error_msg)),
+        _ => match serde_json::from_str::<Refusal>(&text) {
+            Ok(refusal) if !refusal.error.is_empty() => Err(AuthError::Refused(refusal.error)),
+            _ => Err(AuthError::Http(format!("/order returned {}", status))),
+        },
+    }
+}
+
+/// GET a CLOB endpoint and parse its JSON
+async fn get_json<T: DeserializeOwned>(auth: &ClobAuth, path: &str) -> Result<T, AuthError> {
+    auth.authenticated(reqwest::Method::GET, path, None)?
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| AuthError::Http(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| AuthError::Http(e.to_string()))
+}
+
+/// Cancel one order on the CLOB
+pub async fn cancel_order(auth: &ClobAuth, order_id: &str) -> Result<(), AuthError> {
+    let body = serde_json::json!({ "orderID": order_id }).to_string();
+    auth.authenticated(reqwest::Method::DELETE, "/order", Some(body))?
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| AuthError::Http(e.to_string()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::Secret;
+    use crate::signer::{EvmSigner, SignatureType};
+    use crate::types::PriceLevel;
+    use crate::websocket::parse_events;
+
+    fn order_event(kind: &str, size_matched: &str) -> String {
+        format!(
+            r#"{{"event_type":"order","id":"o1","asset_id":"t1","market":"0xabc","side":"BUY",
+            "price":"0.45","original_size":"10","size_matched":"{}","type":"{}","timestamp":"1000"}}"#,
+            size_matched, kind
+        )
+    }
+
+    #[test]
+    fn test_order_lifecycle() {
+        let orders = OrderManager::default();
+        for event in parse_events::<UserEvent>(&order_event("PLACEMENT", "0")) {
+            orders.apply(&event);
+        }
+        assert_eq!(orders.open_orders().len(), 1);
+        assert_eq!(orders.order("o1").unwrap().status, OrderStatus::Live);
+
+        for event in parse_events::<UserEvent>(&order_event("UPDATE", "4")) {
+            orders.apply(&event);
+        }
+        assert_eq!(
+            orders.order("o1").unwrap().status,
+            OrderStatus::PartiallyFilled
+        );
+
+        for event in parse_events::<UserEvent>(&order_event("CANCELLATION", "4")) {
+            orders.apply(&event);
+        }
+        assert!(orders.open_orders().is_empty());
+    }
+
+    #[test]
+    fn test_maker_fill_updates_status_in_place() {
+        let orders = OrderManager::default();
+        for event in parse_events::<UserEvent>(&order_event("PLACEMENT", "0")) {
+            orders.apply(&event);
+        }
+
+        let trade = |status: &str| {
+            format!(
+                r#"[{{"event_type":"trade","id":"tr1","asset_id":"t2","side":"SELL","price":"0.55",
+                "size":"6","status":"{}","taker_order_id":"someone","trader_side":"MAKER",
+                "maker_orders":[{{"order_id":"o1","matched_amount":"6","price":"0.45","asset_id":"t1"}}]}}]"#,
+                status
+            )
+        };
+        for event in parse_events::<UserEvent>(&trade("MATCHED")) {
+            orders.apply(&event);
+        }
+        for event in parse_events::<UserEvent>(&trade("CONFIRMED")) {
+            orders.apply(&event);
+        }
+
+        let fills = orders.fills();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].order_id, "o1");
+        assert_eq!(fills[0].token_id, "t1");
+        assert_eq!(fills[0].side, Side::Buy);
+        assert_eq!(fills[0].size, 6.0);
+        assert_eq!(fills[0].status, TradeStatus::Confirmed);
+    }
+
+    #[test]
+    fn test_fills_of_placed_orders_queued_for_their_execution() {
+        let orders = OrderManager::default();
+        let request = OrderRequest {
+            market: "m1".into(),
+            token_id: "t1".into(),
+            side: Side::Buy,
+            price: 0.45,
+            size: 10.0,
+            order_type: OrderType::PostOnly,
+            execution_id: Some("exec-1".to_string()),
+        };
+        // Fills may arrive before the placement is acknowledged
+        orders.placed(&request, "o1", 1000);
+        let trade = |id: &str, amount: &str| {
+            format!(
+                r#"[{{"event_type":"trade","id":"{}","asset_id":"t2","side":"SELL","price":"0.55",
+                "size":"{}","status":"MATCHED","taker_order_id":"someone","trader_side":"MAKER",
+                "maker_orders":[{{"order_id":"o1","matched_amount":"{}","price":"0.45","asset_id":"t1"}}]}}]"#,
+                id, amount, amount
+            )
+        };
+        for event in parse_events::<UserEvent>(&trade("tr1", "4")) {
+            orders.apply(&event);
+            orders.apply(&event);
+        }
+        let updates = orders.take_updates();
+        assert_eq!(updates.len(), 1, "a trade is booked once");
+        assert!(matches!(
+            &updates[0],
+            OrderUpdate::Fill { execution_id, fill } if execution_id == "exec-1" && fill.maker && fill.size == 4.0
+        ));
+
+        // The last fill closes the execution
+        for event in parse_events::<UserEvent>(&trade("tr2", "6")) {
+            orders.apply(&event);
+        }
+        for event in parse_events::<UserEvent>(&order_event("CANCELLATION", "10")) {
+            orders.apply(&event);
+        }
+        let updates = orders.take_updates();
+        assert_eq!(updates.len(), 2);
+        assert!(matches!(
+            &updates[1],
+            OrderUpdate::Closed { execution_id, order_id } if execution_id == "exec-1" && order_id == "o1"
+        ));
+        assert_eq!(
+            orders.order("o1").unwrap().execution_id.as_deref(),
+            Some("exec-1")
+        );
+    }
+
+    #[test]
+    fn test_placement_event_keeps_local_market() {
+        let orders = OrderManager::default();
+        let request = OrderRequest {
+            market: "m1".into(),
+            token_id: "t1".into(),
+            side: Side::Buy,
+            price: 0.45,
+            size: 10.0,
+            order_type: OrderType::PostOnly,
+            execution_id: Some("exec-1".to_string()),
+        };
+        orders.placed(&request, "o1", 1000);
+        // The user channel reports the placement before the quote goes stale
+        for event in parse_events::<UserEvent>(&order_event("PLACEMENT", "0")) {
+            orders.apply(&event);
+        }
+        let open = orders.open_orders();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].market, "m1");
+        assert_eq!(open[0].condition_id, "0xabc");
+
+        // A requote of it, refused, still looks up the local market
+        let book = OrderBook {
+            token_id: "t1".into(),
+            bids: vec![PriceLevel {
+                price: 0.47,
+                size: 100.0,
+            }],
+            asks: vec![PriceLevel {
+                price: 0.50,
+                size: 100.0,
+            }],
+            timestamp: 0,
+        };
+        let requote = open[0].requote(&book, 0.01).unwrap();
+        let replacement = request.requoted(&requote);
+        assert_eq!(replacement.market, "m1");
+        let rejection = orders.reject(
+            replacement.order("o2".to_string(), 1001),
+            "not enough balance",
+        );
+        assert_eq!(rejection.order.market, "m1");
+
+        // Reported before it was tracked, the order takes the local market on placement
+        let other = OrderManager::default();
+        for event in parse_events::<UserEvent>(&order_event("PLACEMENT", "0")) {
+            other.apply(&event);
+        }
+        assert_eq!(other.order("o1").unwrap().market, "");
+        assert_eq!(other.placed(&request, "o1", 1000).market, "m1");
+    }
+
+    #[test]
+    fn test_takes_expired_resting_orders() {
+        let orders = OrderManager::default();
+        // Registered before the placement is acknowledged
+        orders.set_expiry("o1", Some("exec-1"), 2_000);
+        for event in parse_events::<UserEvent>(&order_event("PLACEMENT", "0")) {
+            orders.apply(&event);
+        }
+        assert_eq!(orders.order("o1").unwrap().expires_at, Some(2_000));
+        // Never acknowledged, still owed a sweep
+        orders.set_expiry("o2", None, 2_500);
+
+        assert!(orders.take_expired(1_999).is_empty());
+        assert_eq!(
+            orders.take_expired(2_000),
+            vec![ExpiredOrder {
+                order_id: "o1".into(),
+                execution_id: Some("exec-1".into()),
+            }]
+        );
+        // Swept once only
+        assert!(orders.take_expired(2_000).is_empty());
+
+        orders.set_expiry("o1", None, 3_000);
+        for event in parse_events::<UserEvent>(&order_event("UPDATE", "10")) {
+            orders.apply(&event);
+        }
+        let expired = orders.take_expired(3_000);
+        assert_eq!(expired.len(), 1, "filled orders are not swept");
+        assert_eq!(expired[0].order_id, "o2");
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_rejection_gets_local_id() {
+        let orders = OrderManager::default();
+        let signer = EvmSigner::from_private_key(&Secret::new(
+            "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+        ))
+        .unwrap();
+        // No API credentials, so the post fails before anything is signed
+        let auth = ClobAuth::new("http://127.0.0.1:9", signer);
+        let request = OrderRequest {
+            market: "m1".into(),
+            token_id: "t1".into(),
+            side: Side::Buy,
+            price: 0.45,
+            size: 10.0,
+            order_type: OrderType::Limit,
+            execution_id: Some("exec-1".to_string()),
+        };
+
+        let rejection = orders.submit(&auth, &request, 1000).await.unwrap_err();
+        assert!(rejection.order.id.starts_with("local-"));
+        assert_eq!(rejection.execution_id.as_deref(), Some("exec-1"));
+        assert!(orders.order("").is_none());
+        let stored = orders.order(&rejection.order.id).unwrap();
+        assert_eq!(stored.status, OrderStatus::Rejected);
+    }
+
+    #[tokio::test]
+    async fn test_uncancelled_expiry_keeps_execution_open() {
+        let orders = OrderManager::default();
+        let request = OrderRequest {
+            market: "m1".into(),
+            token_id: "t1".into(),
+            side: Side::Buy,
+            price: 0.45,
+            size: 10.0,
+            order_type: OrderType::PostOnly,
+            execution_id: Some("exec-1".to_string()),
+        };
+        orders.placed(&request, "o1", 1000);
+        orders.set_expiry("o1", Some("exec-1"), 2_000);
+
+        // Nothing confirmed the order gone: its reservation stays held
+        assert!(orders.sweep_expired(None, 2_000).await.is_empty());
+        assert!(orders.take_updates().is_empty());
+
+        // Until the exchange's own expiry is reported
+        for event in parse_events::<UserEvent>(&order_event("CANCELLATION", "0")) {
+            orders.apply(&event);
+        }
+        assert!(matches!(
+            orders.take_updates().as_slice(),
+            [OrderUpdate::Closed { execution_id, .. }] if execution_id == "exec-1"
+        ));
+    }
+
+    #[test]
+    fn test_requotes_stale_quotes() {
+        let orders = OrderManager::default();
+        for event in parse_events::<UserEvent>(&order_event("UPDATE", "4")) {
+            orders.apply(&event);
+        }
+        let bid = orders.order("o1").unwrap();
+        let book = |bid: f64, ask: f64| OrderBook {
+            token_id: "t1".into(),
+            bids: vec![PriceLevel {
+                price: bid,
+                size: 100.0,
+            }],
+            asks: vec![PriceLevel {
+                price: ask,
+                size: 100.0,
+            }],
+            timestamp: 0,
+        };
+
+        // Our 0.45 bid is within 0.02 of the 0.46 touch
+        assert_eq!(bid.requote(&book(0.46, 0.50), 0.02), None);
+        assert_eq!(
+            bid.requote(&book(0.40, 0.44), 0.02),
+            Some(Requote {
+                order_id: "o1".into(),
+                token_id: "t1".into(),
+                side: Side::Buy,
+                from: 0.45,
+                to: 0.40,
+                size: 6.0,
+            })
+        );
+        // Ask down to our bid: about to be picked off
+        assert!(bid.requote(&book(0.44, 0.45), 0.02).is_some());
+    }
+
+    #[test]
+    fn test_rejections_drive_recovery() {
+        use RejectionReason::*;
+        for (error, reason) in [
+            ("not enough balance / allowance", InsufficientBalance),
+            ("INVALID_ORDER_NOT_ENOUGH_BALANCE", InsufficientBalance),
+            (
+                "invalid price (0.001), min: 0.01 - max: 0.99",
+                PriceOutOfRange,
+            ),
+            ("order 0.4555 breaks minimum tick size rule: 0.01", TickSize),
+            ("Size (3) lower than the minimum: 5", BelowMinSize),
+            (
+                "the market is not yet ready to process new orders",
+                MarketClosed,
+            ),
+            ("FOK_ORDER_NOT_FILLED_ERROR", NotFilled),
+            ("INVALID_ORDER_DUPLICATED", Duplicate),
+            ("something else", Other),
+        ] {
+            assert_eq!(RejectionReason::classify(error), reason, "{}", error);
+        }
+
+        let orders = OrderManager::default();
+        orders.set_expiry("o1", Some("exec-1"), 5_000);
+        for event in parse_events::<UserEvent>(&order_event("PLACEMENT", "0")) {
+            orders.apply(&event);
+        }
+        let order = orders.order("o1").unwrap();
+        let rejection = orders.reject(order.clone(), "not enough balance / allowance");
+        assert_eq!(rejection.recovery, Recovery::Resize);
+        assert_eq!(rejection.execution_id.as_deref(), Some("exec-1"));
+        assert_eq!(orders.order("o1").unwrap().status, OrderStatus::Rejected);
+        assert!(orders.open_orders().is_empty());
+        assert_eq!(orders.rejection_counts()["insufficient_balance"], 1);
+
+        // $3 funds 6.66 of the 10 shares at 0.45
+        let spec = OrderSpec {
+            tick_size: 0.01,
+            min_size: 5.0,
+        };
+        let book = OrderBook {
+            token_id: "t1".into(),
+            bids: vec![PriceLevel {
+                price: 0.44,
+                size: 100.0,
+            }],
+            asks: vec![PriceLevel {
+                price: 0.47,
+                size: 100.0,
+            }],
+            timestamp: 0,
+        };
+        let retry = rejection.retry(&spec, &book, Some(3.0)).unwrap();
+        assert_eq!((retry.to, retry.size), (0.45, 6.66));
+        // Can't fund the minimum size
+        assert!(rejection.retry(&spec, &book, Some(2.0)).is_none());
+
+        let rejection = orders.reject(order.clone(), "FOK_ORDER_NOT_FILLED_ERROR");
+        let retry = rejection.retry(&spec, &book, None).unwrap();
+        assert_eq!((retry.to, retry.size), (0.47, 10.0));
+
+        let rejection = orders.reject(order, "market is closed");
+        assert_eq!(rejection.recovery, Recovery::Quarantine);
+        assert!(rejection.retry(&spec, &book, None).is_none());
+    }
+
+    #[test]
+    fn test_order_request_signed_for_maker() {
+        let maker = OrderMaker {
+            maker: "0x00000000000000000000000000000000000000ab".to_string(),
+            signer: "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23".to_string(),
+            signature_type: SignatureType::PolyGnosisSafe,
+        };
+        let request = OrderRequest {
+            market: "m1".into(),
+            token_id: "1234".into(),
+            side: Side::Buy,
+            price: 0.45,
+            size: 10.0,
+            order_type: OrderType::PostOnly,
+            execution_id: None,
+        };
+
+        // A buy gives USDC for shares, a sell the reverse
+        let order = request.exchange_order(&maker, 0, 7);
+        assert_eq!(
+            (order.maker_amount, order.taker_amount),
+            (4_500_000, 10_000_000)
+        );
+        assert_eq!(order.maker, maker.maker);
+        assert_eq!(order.signer, maker.signer);
+        let sell = OrderRequest {
+            side: Side::Sell,
+            ..request.clone()
+        }
+        .exchange_order(&maker, 0, 7);
+        assert_eq!(
+            (sell.maker_amount, sell.taker_amount),
+            (10_000_000, 4_500_000)
+        );
+
+        let body: serde_json::Value =
+            serde_json::from_str(&order_body(&order, "0xsig", "key-123", request.order_type))
+                .unwrap();
+        assert_eq!(body["order"]["maker"], maker.maker.as_str());
+        assert_eq!(body["order"]["makerAmount"], "4500000");
+        assert_eq!(body["order"]["side"], "BUY");
+        assert_eq!(body["order"]["signatureType"], 2);
+        assert_eq!(body["owner"], "key-123");
+        assert_eq!(body["orderType"], "GTC");
+        assert_eq!(body["postOnly"], true);
+
+        let requote = Requote {
+            order_id: "o1".to_string(),
+            token_id: "1234".into(),
+            side: Side::Buy,
+            from: 0.45,
+            to: 0.44,
+            size: 6.0,
+        };
+        let requoted = request.requoted(&requote);
+        assert_eq!((requoted.price, requoted.size), (0.44, 6.0));
+    }
+}
@@ -0,0 +1,136 @@
+//! Panic capture and subsystem restarts
+//!
+//! The panic hook logs every panic with its location and a backtrace and
+//! sends a webhook alert. Long-running subsystems (API server, streams,
+//! monitors, market workers) are spawned through `spawn_supervised`, which
+//! respawns a task that panicked after a short backoff instead of letting it
+//! vanish. Positions, allowance, and the spend ledger live in shared state
+//! outside those tasks, so a restarted subsystem picks up where it left off.
+
+use crate::wallet::Wallet;
+use crate::webhook::{WebhookEvent, WebhookPublisher};
+use crate::websocket::{next_backoff, MIN_BACKOFF};
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// A run that lasted this long resets the restart backoff
+const HEALTHY_RUN: Duration = Duration::from_secs(60);
+
+/// Log panics with a backtrace and alert the webhook
+pub fn install_hook(webhook: Option<WebhookPublisher>) {
+    std::panic::set_hook(Box::new(move |info| {
+        let thread = std::thread::current();
+        let thread = thread.name().unwrap_or("unnamed").to_string();
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}", l.file(), l.line()))
+            .unwrap_or_default();
+        let message = panic_message(info.payload());
+        eprintln!(
+            "💥 [Panic] Thread '{}' panicked at {}: {}\n{}",
+            thread,
+            location,
+            message,
+            Backtrace::force_capture()
+        );
+        if let Some(webhook) = &webhook {
+            webhook.publish(WebhookEvent::Panic {
+                timestamp: Wallet::current_timestamp(),
+                thread,
+                location,
+                message,
+            });
+        }
+    }));
+}
+
+/// The `panic!` message, when it is a string
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
+
+/// Spawn a task built by `start`, respawning it whenever it panics
+///
+/// The returned handle finishes once a run returns normally; aborting it
+/// stops the current run and any restarts.
+pub fn spawn_supervised<F, Fut>(name: &'static str, mut start: F) -> JoinHandle<()>
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut backoff = MIN_BACKOFF;
+        loop {
+            let started = Instant::now();
+            // Aborting this supervisor must take the current run with it
+            let mut run = AbortOnDrop(tokio::spawn(start()));
+            match (&mut run.0).await {
+                Err(e) if e.is_panic() => {
+                    if started.elapsed() >= HEALTHY_RUN {
+                        backoff = MIN_BACKOFF;
+                    }
+                    println!(
+                        "🔁 [{}] Panicked, restarting in {}s",
+                        name,
+                        backoff.as_secs()
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = next_backoff(backoff);
+                }
+                _ => break,
+            }
+        }
+    })
+}
+
+/// Aborts the wrapped task if dropped before it finishes
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_panicked_task_is_restarted() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counted = runs.clone();
+        let handle = spawn_supervised("test", move || {
+            let runs = counted.clone();
+            async move {
+                if runs.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("first run dies");
+                }
+            }
+        });
+
+        // The second run returns normally, ending supervision
+        tokio::time::timeout(Duration::from_secs(10), handle)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_panic_message() {
+        let payload = std::panic::catch_unwind(|| panic!("static")).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "static");
+        let payload = std::panic::catch_unwind(|| panic!("{} formatted", 1)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "1 formatted");
+    }
+}
@@ -0,0 +1,1595 @@
+//! Detection → execution pipeline
+//!
+//! Market workers ingest books and detect signals; everything after that
+//! runs in stages connected by bounded channels: risk filtering, execution,
+//! and settlement. A slow order (latency sleeps, placement round trips, CTF
+//! merges) only backs up the stages behind it, never price ingestion. When
+//! the risk queue is full, new signals are dropped rather than queued stale.
+//!
+//! Legs posted to the CLOB are not filled by the simulator: their positions
+//! and spend are booked as the user channel reports each fill, and their
+//! reservations stay held until the order closes. Live exits likewise close
+//! their positions only as their fills are reported.
+
+use crate::accounts::PRIMARY_ACCOUNT;
+use crate::auth::ClobAuth;
+use crate::breakdown::{TradeOrigin, TradeTag};
+use crate::ctf::mergeable_sets;
+use crate::execution::new_execution_id;
+use crate::exposure::net_exposure;
+use crate::ledger::{ExecutedSpend, Shortfall, EXECUTIONS_LOG};
+use crate::metamask::StrategyMode;
+use crate::metrics::Endpoint;
+use crate::money::{self, Decimal};
+use crate::order_spec::{OrderSpec, OrderTerms, OrderType, SizingBasis};
+use crate::orders::{self, OrderRequest, OrderUpdate};
+use crate::panics::spawn_supervised;
+use crate::policy::{PolicyViolation, SpendRequest};
+use crate::positions::{DueExit, ExitLimits, Position};
+use crate::pretrade::{PreTrade, PreTradeDecision, PreTradeLeg, Rejection, SignalAge};
+use crate::routing::{LegRoute, LegRouter};
+use crate::script::{FilterDecision, FilterInput};
+use crate::skips::{SkipCounter, SkipReason};
+use crate::strategy::Intent;
+use crate::throttle::CapReached;
+use crate::types::{ExecutionResult, Market, OrderBook, Side, TokenId};
+use crate::wallet::Wallet;
+use crate::webhook::WebhookEvent;
+use crate::workers::{get_min_edge_for_allowance, get_strategy_mode, WorkerContext};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Instant;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::Mutex;
+
+/// Signals (and orders) buffered between two stages
+const QUEUE_DEPTH: usize = 64;
+
+/// A detected signal with the books it was detected against
+#[derive(Debug)]
+pub struct Candidate {
+    pub market: Market,
+    pub intent: Intent,
+    /// Books for the intent's legs
+    pub books: HashMap<TokenId, OrderBook>,
+    pub now: u64,
+    /// When the strategy produced the intent
+    pub detected_at: Instant,
+}
+
+/// A leg cleared by risk filtering
+#[derive(Debug)]
+struct Leg {
+    token_id: TokenId,
+    book: OrderBook,
+    /// Size conformed to the market's tick and minimum
+    size: f64,
+    /// Average price walking the book the signal was detected on
+    signal_price: f64,
+    /// Worst price the leg may trade at, for limit order types
+    limit_price: Option<f64>,
+    /// How the leg is filled (always taken unless routing is enabled)
+    route: LegRoute,
+    /// Reserved in the spend ledger (live only)
+    execution_id: Option<String>,
+}
+
+impl Leg {
+    fn terms(&self, intent: &Intent) -> OrderTerms {
+        match self.route {
+            LegRoute::Post { price } => OrderTerms {
+                side: intent.side,
+                order_type: OrderType::PostOnly,
+                limit_price: Some(price),
+            },
+            _ => OrderTerms {
+                side: intent.side,
+                order_type: intent.order_type,
+                limit_price: self.limit_price,
+            },
+        }
+    }
+}
+
+/// An intent cleared for execution
+#[derive(Debug)]
+struct Order {
+    market: Market,
+    intent: Intent,
+    legs: Vec<Leg>,
+    /// Smart account the order is charged to
+    account: String,
+    /// Strategy mode the intent was approved under
+    mode: StrategyMode,
+    /// Exit limits for positions it opens
+    exits: ExitLimits,
+    now: u64,
+    detected_at: Instant,
+    /// When it took its slots in the trade caps (none in shadow mode)
+    admitted_at: Option<Instant>,
+}
+
+/// An executed intent, which its legs' fills are booked against
+#[derive(Debug)]
+struct Entry {
+    /// CTF condition of the market, for merging bundles
+    condition_id: String,
+    /// Smart account the fills are charged to
+    account: String,
+    /// Strategy mode at entry
+    mode: StrategyMode,
+    intent: Intent,
+    now: u64,
+}
+
+/// What execution hands to settlement
+#[derive(Debug)]
+enum Executed {
+    /// Paper fills against live books
+    Shadow {
+        fills: Vec<ExecutionResult>,
+        expected_profit: f64,
+    },
+    /// Legs filled against the paper wallet, positions already open
+    Live {
+        entry: Arc<Entry>,
+        fills: Vec<ExecutionResult>,
+    },
+}
+
+/// Executions posted to the CLOB and still working, by execution id
+#[derive(Debug, Default)]
+pub struct LiveOrders {
+    executions: StdMutex<HashMap<String, (TokenId, Arc<Entry>)>>,
+    /// Exits of open positions, with the fee rate they are booked at
+    exits: StdMutex<HashMap<String, (DueExit, f64)>>,
+}
+
+impl LiveOrders {
+    fn insert(&self, execution_id: &str, token_id: &TokenId, entry: &Arc<Entry>) {
+        self.executions
+            .lock()
+            .unwrap()
+            .insert(execution_id.to_string(), (token_id.clone(), entry.clone()));
+    }
+
+    fn get(&self, execution_id: &str) -> Option<Arc<Entry>> {
+        let executions = self.executions.lock().unwrap();
+        executions.get(execution_id).map(|(_, entry)| entry.clone())
+    }
+
+    fn remove(&self, execution_id: &str) -> Option<Arc<Entry>> {
+        let removed = self.executions.lock().unwrap().remove(execution_id);
+        removed.map(|(_, entry)| entry)
+    }
+
+    /// Whether an order is working on the token
+    fn working_on(&self, token_id: &TokenId) -> bool {
+        let executions = self.executions.lock().unwrap();
+        executions.values().any(|(token, _)| token == token_id)
+    }
+
+    /// Whether any leg of the entry is still working
+    fn working_for(&self, entry: &Arc<Entry>) -> bool {
+        let executions = self.executions.lock().unwrap();
+        executions.values().any(|(_, e)| Arc::ptr_eq(e, entry))
+    }
+
+    fn insert_exit(&self, execution_id: &str, exit: DueExit, fee_rate: f64) {
+        self.exits
+            .lock()
+            .unwrap()
+            .insert(execution_id.to_string(), (exit, fee_rate));
+    }
+
+    fn exit(&self, execution_id: &str) -> Option<(DueExit, f64)> {
+        self.exits.lock().unwrap().get(execution_id).cloned()
+    }
+
+    fn remove_exit(&self, execution_id: &str) -> Option<(DueExit, f64)> {
+        self.exits.lock().unwrap().remove(execution_id)
+    }
+
+    /// Whether an exit is working on the token
+    fn exiting(&self, token_id: &TokenId) -> bool {
+        let exits = self.exits.lock().unwrap();
+        exits.values().any(|(exit, _)| &exit.token_id == token_id)
+    }
+}
+
+/// Entry point of the pipeline; stages run until every sender is dropped
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    candidates: mpsc::Sender<Candidate>,
+    skips: Arc<SkipCounter>,
+}
+
+impl Pipeline {
+    /// Spawn the risk, execution, and settlement stages
+    ///
+    /// Each stage is supervised, so a panic loses at most the item in hand.
+    pub fn spawn(ctx: Arc<WorkerContext>) -> Self {
+        let skips = ctx.skips.clone();
+        let (candidates, candidate_rx) = mpsc::channel(QUEUE_DEPTH);
+        let (orders, order_rx) = mpsc::channel(QUEUE_DEPTH);
+        let (executed, executed_rx) = mpsc::channel(QUEUE_DEPTH);
+
+        let candidate_rx = Arc::new(Mutex::new(candidate_rx));
+        let risk_ctx = ctx.clone();
+        spawn_supervised("Risk", move || {
+            risk_stage(risk_ctx.clone(), candidate_rx.clone(), orders.clone())
+        });
+
+        let order_rx = Arc::new(Mutex::new(order_rx));
+        let execution_ctx = ctx.clone();
+        spawn_supervised("Execution", move || {
+            execution_stage(execution_ctx.clone(), order_rx.clone(), executed.clone())
+        });
+
+        let booking = ctx.clone();
+        spawn_supervised("Fills", move || fill_stage(booking.clone()));
+
+        let executed_rx = Arc::new(Mutex::new(executed_rx));
+        spawn_supervised("Settlement", move || {
+            settlement_stage(ctx.clone(), executed_rx.clone())
+        });
+
+        Self { candidates, skips }
+    }
+
+    /// Queue a signal for risk filtering without waiting
+    ///
+    /// Returns false once the pipeline has shut down.
+    pub fn submit(&self, candidate: Candidate) -> bool {
+        match self.candidates.try_send(candidate) {
+            Ok(()) => true,
+            Err(TrySendError::Full(candidate)) => {
+                println!(
+                    "⚠️ [Pipeline] Queue full, dropping {} intent on {}",
+                    candidate.intent.strategy, candidate.intent.market_id
+                );
+                self.skips.record(SkipReason::QueueFull);
+                true
+            }
+            Err(TrySendError::Closed(_)) => false,
+        }
+    }
+}
+
+async fn risk_stage(
+    ctx: Arc<WorkerContext>,
+    candidates: Arc<Mutex<mpsc::Receiver<Candidate>>>,
+    orders: mpsc::Sender<Order>,
+) {
+    let mut candidates = candidates.lock().await;
+    while let Some(candidate) = candidates.recv().await {
+        match approve(&ctx, candidate).await {
+            Ok(order) => {
+                if orders.send(order).await.is_err() {
+                    break;
+                }
+            }
+            Err(reason) => ctx.skips.record(reason),
+        }
+    }
+}
+
+async fn execution_stage(
+    ctx: Arc<WorkerContext>,
+    orders: Arc<Mutex<mpsc::Receiver<Order>>>,
+    executed: mpsc::Sender<Executed>,
+) {
+    let mut orders = orders.lock().await;
+    while let Some(order) = orders.recv().await {
+        if let Some(result) = execute(&ctx, order).await {
+            if executed.send(result).await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+async fn settlement_stage(ctx: Arc<WorkerContext>, executed: Arc<Mutex<mpsc::Receiver<Executed>>>) {
+    let mut executed = executed.lock().await;
+    while let Some(result) = executed.recv().await {
+        settle(&ctx, result).await;
+    }
+}
+
+async fn fill_stage(ctx: Arc<WorkerContext>) {
+    loop {
+        book_order_updates(&ctx).await;
+        ctx.orders.updated().await;
+    }
+}
+
+/// Rescale the intent's legs to `size`, with expected profit in proportion
+///
+/// Sizes are validated upstream; an intent that somehow has no size keeps
+/// its expected profit rather than flipping or blowing it up.
+fn resize(intent: &mut Intent, size: f64) {
+    debug_assert!(
+        intent.size > 0.0,
+        "resizing an intent of size {}",
+        intent.size
+    );
+    if intent.size > 0.0 {
+        intent.expected_profit *= size / intent.size;
+    }
+    intent.size = size;
+}
+
+/// Filter, size-check, and reserve one intent, or say why it was dropped
+async fn approve(ctx: &WorkerContext, candidate: Candidate) -> Result<Order, SkipReason> {
+    let Candidate {
+        market,
+        mut intent,
+        books,
+        now,
+        detected_at,
+    } = candidate;
+    println!(
+        "   [{}] Intent on Market {}: Edge {:.2}%, Expected ${:.2}",
+        intent.strategy,
+        intent.market_id,
+        intent.edge * 100.0,
+        intent.expected_profit
+    );
+
+    // Category/slug overrides replace the global size, min edges, and timeout
+    let overrides = ctx.config.strategy.override_for(&market);
+    if let Some(size) = overrides.and_then(|o| o.trade_size) {
+        resize(&mut intent, size);
+    }
+
+    // Size in shares at current prices; the estimated USDC cost drives the
+    // allowance, budget, and balance checks
+    let leg_prices: Option<Vec<f64>> = intent
+        .token_ids
+        .iter()
+        .map(|token_id| market.token_price(token_id))
+        .collect();
+    let Some(leg_prices) = leg_prices else {
+        println!("   ⏭️ Skipping: no price to size legs against");
+        return Err(SkipReason::NoLiquidity);
+    };
+    let basis = ctx.config.trading.sizing;
+    let Some(shares) = basis.shares_per_leg(intent.size, &leg_prices) else {
+        println!("   ⏭️ Skipping: no price to size legs against");
+        return Err(SkipReason::NoLiquidity);
+    };
+    if basis == SizingBasis::Notional {
+        resize(&mut intent, shares);
+    }
+
+    // Thin, quiet, or unreported markets are left alone
+    let trading = &ctx.config.trading;
+    if !market.meets_activity(trading.min_liquidity, trading.min_volume_24hr) {
+        let usd = |value: Option<f64>| value.map_or("n/a".to_string(), |v| format!("${:.0}", v));
+        println!(
+            "   ⏭️ Skipping: liquidity {}, 24h volume {} below ${:.0}, ${:.0}",
+            usd(market.liquidity),
+            usd(market.volume_24hr),
+            trading.min_liquidity,
+            trading.min_volume_24hr
+        );
+        return Err(SkipReason::ThinMarket);
+    }
+
+    // Filter intents based on strategy mode minimum edge
+    let (remaining, daily_limit) = ctx.allowance().await;
+    let strategy = ctx.strategy_config().await;
+    let strategy = ctx.tuner.lock().await.tuned(&strategy).for_market(&market);
+    let min_edge = get_min_edge_for_allowance(remaining, daily_limit, &strategy);
+    let mode = get_strategy_mode(remaining, daily_limit, &strategy);
+    if intent.edge < min_edge {
+        println!(
+            "   ⏭️ Skipping: edge {:.2}% below min edge {:.2}% for {} mode",
+            intent.edge * 100.0,
+            min_edge * 100.0,
+            mode.as_str()
+        );
+        return Err(SkipReason::BelowMinEdge);
+    }
+
+    // Volatility filter: skip markets whose spread is whipsawing
+    let filter = &ctx.config.spread_history;
+    if filter.max_volatility > 0.0 {
+        if let Some(stats) = ctx
+            .spread_history
+            .stats(&intent.market_id, filter.volatility_window)
+        {
+            if stats.std > filter.max_volatility {
+                println!(
+                    "   ⏭️ Skipping: spread volatility {:.2}% above {:.2}%",
+                    stats.std * 100.0,
+                    filter.max_volatility * 100.0
+                );
+                return Err(SkipReason::Volatility);
+            }
+        }
+    }
+
+    // User filter script: accept, reject, or resize
+    if let Some(filter) = &ctx.trade_filter {
+        let decision = filter.evaluate(&FilterInput {
+            market: &market,
+            intent: &intent,
+            books: intent
+                .token_ids
+                .iter()
+                .filter_map(|token_id| books.get(token_id))
+                .collect(),
+            remaining_allowance: money::to_f64(remaining),
+            daily_limit: money::to_f64(daily_limit),
+        });
+        match decision {
+            Ok(FilterDecision::Accept) => {}
+            Ok(FilterDecision::Reject) => {
+                println!("   ⏭️ Skipping: rejected by filter script");
+                return Err(SkipReason::FilterScript);
+            }
+            Ok(FilterDecision::Resize(size)) => {
+                println!(
+                    "   📐 Filter script resized legs: {:.2} -> {:.2} shares per leg",
+                    intent.size, size
+                );
+                resize(&mut intent, size);
+            }
+            Err(e) => {
+                println!("   ⚠️ Skipping: {}", e);
+                return Err(SkipReason::FilterScript);
+            }
+        }
+    }
+
+    // A leg takes at most a share of what the market holds, whatever the
+    // filter script asked for
+    if let Some(cap) = market.liquidity_cap(trading.max_liquidity_fraction, &leg_prices) {
+        if intent.size > cap {
+            println!(
+                "   📐 Sized down to market liquidity: {:.2} -> {:.2} shares per leg",
+                intent.size, cap
+            );
+            resize(&mut intent, cap);
+        }
+    }
+
+    // The category's exit limits, with a strategy override's hold limit on top
+    let mut exits = ctx.config.positions.exits_for(&market.category);
+    if let Some(secs) = overrides.and_then(|o| o.position_timeout_secs) {
+        exits.max_hold_time = secs;
+    }
+
+    // Shadow mode: every leg is filled on paper, no money checks
+    if ctx.config.trading.shadow_mode {
+        let legs = conform_legs(ctx, &market, &intent, books);
+        return Ok(Order {
+            market,
+            intent,
+            legs,
+            account: PRIMARY_ACCOUNT.to_string(),
+            mode,
+            exits,
+            now,
+            detected_at,
+            admitted_at: None,
+        });
+    }
+
+    let required = money::charge(
+        money::from_f64(intent.size) * money::from_f64(leg_prices.iter().sum::<f64>()),
+    );
+
+    // The account must actually hold enough USDC to fund a trade
+    if let Some(account) = *ctx.on_chain.read().await {
+        if money::usdc(account.usdc_balance) < required {
+            println!(
+                "   ⚠️ On-chain USDC balance ${:.2} below trade cost ${:.2}",
+                account.usdc_balance, required
+            );
+            return Err(SkipReason::InsufficientBalance);
+        }
+    }
+
+    // Some account must have the allowance and exposure room for the trade
+    let reserved = ctx.ledger.lock().await.reservations();
+    let account = {
+        let pm = ctx.position_manager.read().await;
+        ctx.accounts
+            .route(required, &pm.get_positions(), &reserved)
+            .await
+            .cloned()
+    };
+    let Some(account) = account else {
+        println!(
+            "   ⚠️ No account can take ${:.2} (allowance or exposure cap)",
+            required
+        );
+        return Err(SkipReason::InsufficientAllowance);
+    };
+
+    // The grant's policy (limit, per-trade cap, venue, category, expiry)
+    // must allow this trade
+    let spend = SpendRequest {
+        amount: required,
+        category: &market.category,
+    };
+    if let Err(e) = account.metamask.authorize(&spend).await {
+        println!("   ⚠️ Permission policy refused ${:.2}: {}", required, e);
+        return Err(match e {
+            PolicyViolation::PerTradeCap { .. }
+            | PolicyViolation::Contract(_)
+            | PolicyViolation::Category(_) => SkipReason::OutOfScope,
+            _ => SkipReason::InsufficientAllowance,
+        });
+    }
+
+    // Reserve each leg's estimated cost now, so intents queued behind this
+    // one (or running next to it) are checked against what it will spend
+    let mut legs = conform_legs(ctx, &market, &intent, books);
+    if legs.is_empty() {
+        return Err(SkipReason::NoLiquidity);
+    }
+
+    // However many signals arrive, only so many trades go out a cycle and
+    // a minute
+    let admitted_at = Instant::now();
+    if let Err(cap) = ctx.throttle.admit(admitted_at) {
+        let safety = &ctx.config.safety;
+        match cap {
+            CapReached::Cycle => println!(
+                "   ⏸️ Trade cap: {} trades this cycle",
+                safety.max_trades_per_cycle
+            ),
+            CapReached::Minute => println!(
+                "   ⏸️ Trade cap: {} trades in the last minute",
+                safety.max_trades_per_minute
+            ),
+        }
+        return Err(SkipReason::TradeCap);
+    }
+    let estimates: Vec<(String, Decimal)> = legs
+        .iter()
+        .map(|leg| {
+            let price = market.token_price(&leg.token_id).unwrap_or(1.0);
+            let estimate = money::charge(money::from_f64(leg.size) * money::from_f64(price));
+            (new_execution_id(), estimate)
+        })
+        .collect();
+    {
+        let mut ledger = ctx.ledger.lock().await;
+        // Read under the ledger lock: settling moves a spend from its
+        // reservation onto the grant while holding it
+        let allowance = account.metamask.get_remaining_allowance().await;
+        if let Err(shortfall) =
+            ledger.reserve(intent.strategy, &account.name, allowance, &estimates, now)
+        {
+            println!(
+                "   ⚠️ [{}] Can't reserve ${:.2}: {}",
+                intent.strategy,
+                estimates.iter().map(|(_, amount)| *amount).sum::<Decimal>(),
+                shortfall
+            );
+            ctx.throttle.refund(admitted_at);
+            return Err(match shortfall {
+                Shortfall::Budget(_) => SkipReason::BudgetExhausted,
+                Shortfall::Allowance(_) => SkipReason::InsufficientAllowance,
+            });
+        }
+        ctx.persist_ledger(&ledger);
+    }
+    for (leg, (execution_id, _)) in legs.iter_mut().zip(estimates) {
+        leg.execution_id = Some(execution_id);
+    }
+    Ok(Order {
+        market,
+        intent,
+        legs,
+        account: account.name,
+        mode,
+        exits,
+        now,
+        detected_at,
+        admitted_at: Some(admitted_at),
+    })
+}
+
+/// Legs with a book, no open position, and a size the market accepts
+fn conform_legs(
+    ctx: &WorkerContext,
+    market: &Market,
+    intent: &Intent,
+    mut books: HashMap<TokenId, OrderBook>,
+) -> Vec<Leg> {
+    let spec = OrderSpec::for_market(market, &ctx.config.trading);
+    intent
+        .token_ids
+        .iter()
+        .filter_map(|token_id| {
+            let book = books.remove(token_id)?;
+            let price = book.execution_price(intent.size, intent.side)?;
+            match spec.conform(price, intent.size, intent.side) {
+                Ok(order) => Some(Leg {
+                    token_id: token_id.clone(),
+                    limit_price: intent
+                        .order_type
+                        .limit_price(&book, order.size, intent.side),
+                    book,
+                    size: order.size,
+                    signal_price: price,
+                    route: LegRoute::Take,
+                    execution_id: None,
+                }),
+                Err(e) => {
+                    println!("   ⏭️ Skipping {}: {}", token_id, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Fill an order's legs; releases the reservation of any leg not filled
+async fn execute(ctx: &WorkerContext, order: Order) -> Option<Executed> {
+    let Order {
+        market,
+        intent,
+        legs,
+        account,
+        mode,
+        exits,
+        now,
+        detected_at,
+        admitted_at,
+    } = order;
+
+    // A marginal edge seen this long ago has probably been taken; stale
+    // signals are rejected with the other pre-trade checks below
+    let checks = &ctx.execution_engine.checks;
+    let age = detected_at.elapsed();
+    let aged = match checks.signal_age(age, intent.edge) {
+        SignalAge::Fresh if !checks.max_signal_age.is_zero() => Some(""),
+        SignalAge::HighEdge => Some(" with a high edge"),
+        _ => None,
+    };
+    if let Some(note) = aged {
+        println!(
+            "   ⏱️ [{}] Signal on {} is {}ms old (limit {}ms), executing{}",
+            intent.strategy,
+            intent.market_id,
+            age.as_millis(),
+            checks.max_signal_age.as_millis(),
+            note
+        );
+    }
+
+    // Re-price the legs on fresh books before spending allowance
+    let mut legs = match revalidate(ctx, &market, &intent, legs).await {
+        Ok(legs) => legs,
+        Err(legs) => {
+            release_legs(ctx, &legs).await;
+            if let Some(at) = admitted_at {
+                ctx.throttle.refund(at);
+            }
+            ctx.skips.record(SkipReason::StaleSignal);
+            return None;
+        }
+    };
+    match pre_trade_check(ctx, &market, &intent, &account, &legs, detected_at, now).await {
+        PreTradeDecision::Accept { size } => {
+            let largest = legs.iter().map(|leg| leg.size).fold(0.0, f64::max);
+            if size < largest {
+                println!(
+                    "   ✂️ [{}] Cut {} from {:.2} to {:.2} shares per leg to stay within {}bps slippage",
+                    intent.strategy, intent.market_id, largest, size, checks.max_slippage_bps
+                );
+            }
+            for leg in &mut legs {
+                leg.size = leg.size.min(size);
+            }
+            if ctx.config.routing.enabled {
+                route_legs(ctx, &market, &intent, &mut legs);
+            }
+        }
+        PreTradeDecision::Reject(rejections) => {
+            for rejection in &rejections {
+                println!(
+                    "   ⏭️ [{}] Skipping {}: {}",
+                    intent.strategy, intent.market_id, rejection
+                );
+            }
+            release_legs(ctx, &legs).await;
+            if let Some(at) = admitted_at {
+                ctx.throttle.refund(at);
+            }
+            let reason = rejections
+                .first()
+                .map_or(SkipReason::NoLiquidity, Rejection::skip_reason);
+            ctx.skips.record(reason);
+            return None;
+        }
+    }
+
+    if ctx.config.trading.shadow_mode {
+        let mut fills = Vec::new();
+        for leg in &legs {
+            if let Some(result) = ctx
+                .execution_engine
+                .simulate_order(&intent.market_id, &leg.book, leg.size, leg.terms(&intent))
+                .await
+            {
+                fills.push(result);
+            }
+        }
+        return Some(Executed::Shadow {
+            fills,
+            expected_profit: intent.expected_profit,
+        });
+    }
+
+    // Conditions may have changed while the order was queued
+    let tradeable = ctx.can_trade().await;
+    if tradeable {
+        println!("   Attempting to execute {} strategy...", intent.strategy);
+    }
+
+    // Counted when no leg fills or is posted
+    let mut skip = if tradeable {
+        SkipReason::NoLiquidity
+    } else {
+        SkipReason::Suspended
+    };
+    let entry = Arc::new(Entry {
+        condition_id: market.condition_id,
+        account,
+        mode,
+        intent,
+        now,
+    });
+    let intent = &entry.intent;
+    let (mut fills, mut posted) = (Vec::new(), 0);
+    for leg in legs {
+        let terms = leg.terms(intent);
+        let Some(execution_id) = leg.execution_id.clone() else {
+            continue;
+        };
+        let held = !ctx
+            .position_manager
+            .read()
+            .await
+            .accepts_entry(&leg.token_id);
+        // An order still working on the token is an entry on its way
+        let working = ctx.live_orders.working_on(&leg.token_id);
+        let accepted = tradeable && !held && !working;
+        if tradeable && !accepted {
+            let why = if held {
+                "position already open"
+            } else {
+                "order already working"
+            };
+            println!("   ⏭️ Skipping {}: {}", leg.token_id, why);
+            skip = SkipReason::PositionOpen;
+        }
+        let start = Instant::now();
+        match &ctx.clob {
+            // Posted orders are booked as the user channel reports their
+            // fills; the reservation is held until the order closes
+            Some(auth) if accepted => {
+                ctx.position_manager
+                    .write()
+                    .await
+                    .set_market_exits(&intent.market_id, exits);
+                ctx.live_orders.insert(&execution_id, &leg.token_id, &entry);
+                let order = post_leg(ctx, auth, intent, &leg, terms, &execution_id).await;
+                ctx.latency.record_since(Endpoint::OrderSubmit, start);
+                if order.is_some() {
+                    posted += 1;
+                    continue;
+                }
+                ctx.live_orders.remove(&execution_id);
+            }
+            // Without a CLOB to post to, legs fill against the paper wallet
+            None if accepted => {
+                let execution = {
+                    let mut wallet = ctx.wallet.lock().await;
+                    ctx.execution_engine
+                        .execute_as(
+                            execution_id.clone(),
+                            &intent.market_id,
+                            &leg.book,
+                            leg.size,
+                            terms,
+                            &mut wallet,
+                        )
+                        .await
+                };
+                ctx.latency.record_since(Endpoint::OrderSubmit, start);
+                if let Some(result) = execution {
+                    // Journal the execution first so a crash before the
+                    // confirm is settled from the journal on restart
+                    if let Err(e) = ctx.storage.append(
+                        EXECUTIONS_LOG,
+                        &ExecutedSpend {
+                            execution_id: result.execution_id.clone(),
+                            amount: result.total_cost,
+                        },
+                    ) {
+                        println!("⚠️ Failed to journal execution: {}", e);
+                    }
+
+                    // Open the position here so the next order's entry check sees it
+                    {
+                        let mut pm = ctx.position_manager.write().await;
+                        pm.set_market_exits(&intent.market_id, exits);
+                        pm.open_position(Position::from_execution(&result, now, intent.spread));
+                    }
+                    ctx.accounts.assign(&result.execution_id, &entry.account);
+                    fills.push(result);
+                    continue;
+                }
+            }
+            _ => {}
+        }
+
+        // Refused, skipped, or not filled on paper: nothing was spent
+        ctx.update_ledger(|ledger| {
+            ledger.release(&execution_id);
+        })
+        .await;
+    }
+
+    if fills.is_empty() {
+        if posted == 0 {
+            if let Some(at) = admitted_at {
+                ctx.throttle.refund(at);
+            }
+            ctx.skips.record(skip);
+        }
+        return None;
+    }
+    Some(Executed::Live { entry, fills })
+}
+
+/// Post a leg's order to the CLOB at its limit price, for its execution
+///
+/// Market orders go out fill-or-kill at the deepest level their size reaches.
+async fn post_leg(
+    ctx: &WorkerContext,
+    auth: &ClobAuth,
+    intent: &Intent,
+    leg: &Leg,
+    terms: OrderTerms,
+    execution_id: &str,
+) -> Option<orders::Order> {
+    let price = terms
+        .limit_price
+        .or_else(|| OrderType::Fok.limit_price(&leg.book, leg.size, terms.side))?;
+    let request = OrderRequest {
+        market: intent.market_id.clone(),
+        token_id: leg.token_id.clone(),
+        side: terms.side,
+        price,
+        size: leg.size,
+        order_type: terms.order_type,
+        execution_id: Some(execution_id.to_string()),
+    };
+    ctx.place_order(auth, request).await
+}
+
+/// Post orders closing positions that hit an exit condition
+///
+/// Exits go out fill-or-kill at the depth their size reaches. A position
+/// stays open until the user channel reports its exit filled, and is not
+/// exited again while an exit is working; one that isn't filled is retried
+/// on a later tick.
+pub async fn post_exits(ctx: &WorkerContext, auth: &ClobAuth, due: Vec<DueExit>, fee_rate: f64) {
+    for exit in due {
+        if ctx.live_orders.exiting(&exit.token_id) {
+            continue;
+        }
+        let side = match exit.side {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        };
+        let Some(price) = ctx
+            .fresh_book(&exit.market_id, &exit.token_id)
+            .await
+            .and_then(|book| OrderType::Fok.limit_price(&book, exit.size, side))
+        else {
+            println!(
+                "   ⏭️ No book deep enough to exit {:.2} of {}, retrying next tick",
+                exit.size, exit.token_id
+            );
+            continue;
+        };
+        let execution_id = new_execution_id();
+        let request = OrderRequest {
+            market: exit.market_id.clone(),
+            token_id: exit.token_id.clone(),
+            side,
+            price,
+            size: exit.size,
+            order_type: OrderType::Fok,
+            execution_id: Some(execution_id.clone()),
+        };
+        println!(
+            "   📤 Exiting {:.2} of {} at {:.4} ({:?}, {})",
+            exit.size, exit.token_id, price, exit.reason, execution_id
+        );
+        ctx.live_orders.insert_exit(&execution_id, exit, fee_rate);
+        let start = Instant::now();
+        let order = ctx.place_order(auth, request).await;
+        ctx.latency.record_since(Endpoint::OrderSubmit, start);
+        if order.is_none() {
+            ctx.live_orders.remove_exit(&execution_id);
+        }
+    }
+}
+
+/// Swap in fresh books for every leg and check that a bundle still prices
+/// through the $1 payout after taker fees; hands the legs back if not
+async fn revalidate(
+    ctx: &WorkerContext,
+    market: &Market,
+    intent: &Intent,
+    mut legs: Vec<Leg>,
+) -> Result<Vec<Leg>, Vec<Leg>> {
+    let mut missing = None;
+    for leg in &mut legs {
+        match ctx.fresh_book(&market.id, &leg.token_id).await {
+            Some(book) => leg.book = book,
+            None => {
+                missing = Some(leg.token_id.clone());
+                break;
+            }
+        }
+    }
+    if let Some(token_id) = missing {
+        println!(
+            "   ⏭️ [{}] No fresh book for {}, skipping",
+            intent.strategy, token_id
+        );
+        return Err(legs);
+    }
+
+    if !full_bundle(market, &legs) {
+        return Ok(legs);
+    }
+    let sum: Option<f64> = legs
+        .iter()
+        .map(|leg| leg.book.execution_price(leg.size, intent.side))
+        .sum();
+    let fee_model = &ctx.execution_engine.fee_model;
+    let fee_rate = fee_model.market_taker_rate(&intent.market_id, fee_model.taker_rate());
+    match sum {
+        Some(sum) if bundle_clears(sum, fee_rate, intent.side) => Ok(legs),
+        _ => {
+            println!(
+                "   ⏭️ [{}] Bundle on {} no longer clears $1 after {:.2}% fees (legs sum to {})",
+                intent.strategy,
+                intent.market_id,
+                fee_rate * 100.0,
+                sum.map_or("n/a".to_string(), |sum| format!("{:.4}", sum))
+            );
+            Err(legs)
+        }
+    }
+}
+
+/// Whether the legs are a full set of outcomes (only those are worth exactly $1)
+fn full_bundle(market: &Market, legs: &[Leg]) -> bool {
+    legs.len() == market.clob_token_ids.len()
+        && market
+            .clob_token_ids
+            .iter()
+            .all(|token_id| legs.iter().any(|leg| &leg.token_id == token_id))
+}
+
+/// Choose per leg of a bundle buy whether to take, rest at the bid, or split
+///
+/// A split leg takes its taken part now; the rested part, like a posted
+/// leg, waits for a seller (makers are not simulated, so on paper it never
+/// fills).
+fn route_legs(ctx: &WorkerContext, market: &Market, intent: &Intent, legs: &mut [Leg]) {
+    if intent.side != Side::Buy || !full_bundle(market, legs) {
+        return;
+    }
+    let fee_model = &ctx.execution_engine.fee_model;
+    let taker_rate = fee_model.market_taker_rate(&intent.market_id, fee_model.taker_rate());
+    let maker_rate = fee_model.maker_net_bps() / 10000.0;
+    let router = LegRouter::new(&ctx.config.routing);
+    let spec = OrderSpec::for_market(market, &ctx.config.trading);
+    for leg in legs.iter_mut() {
+        let Some(plan) = router.route_leg(&leg.book, leg.size, taker_rate, maker_rate) else {
+            continue;
+        };
+        if plan.route == LegRoute::Take {
+            continue;
+        }
+        println!(
+            "   🧭 [{}] {} leg {}: {} ({:.4}/share vs {:.4} taking)",
+            intent.strategy,
+            intent.market_id,
+            leg.token_id,
+            plan.route,
+            plan.unit_cost,
+            plan.take_cost
+        );
+        if let LegRoute::Split { take, .. } = plan.route {
+            leg.size = spec.round_size(take);
+            leg.limit_price = intent
+                .order_type
+                .limit_price(&leg.book, leg.size, intent.side);
+        }
+        leg.route = plan.route;
+    }
+}
+
+/// Run the pre-trade checks against the legs' fresh books
+async fn pre_trade_check(
+    ctx: &WorkerContext,
+    market: &Market,
+    intent: &Intent,
+    account: &str,
+    legs: &[Leg],
+    detected_at: Instant,
+    now: u64,
+) -> PreTradeDecision {
+    let shadow = ctx.config.trading.shadow_mode;
+    // Other orders in flight through the account have a claim on its allowance
+    let remaining_allowance = match ctx.accounts.get(account) {
+        Some(account) if !shadow => {
+            let own: Vec<&str> = legs
+                .iter()
+                .filter_map(|leg| leg.execution_id.as_deref())
+                .collect();
+            let ledger = ctx.ledger.lock().await;
+            let remaining = account.metamask.get_remaining_allowance().await;
+            Some((remaining - ledger.reserved_through(&account.name, &own)).max(Decimal::ZERO))
+        }
+        _ => None,
+    };
+    let (exposure, correlated_exposure) = if shadow {
+        (None, None)
+    } else {
+        projected_exposure(ctx, intent, now).await
+    };
+    let trade = PreTrade {
+        side: intent.side,
+        legs: legs
+            .iter()
+            .map(|leg| PreTradeLeg {
+                token_id: &leg.token_id,
+                book: &leg.book,
+                size: leg.size,
+                signal_price: leg.signal_price,
+            })
+            .collect(),
+        spec: OrderSpec::for_market(market, &ctx.config.trading),
+        edge: intent.edge,
+        age: detected_at.elapsed(),
+        remaining_allowance,
+        exposure,
+        correlated_exposure,
+        cooldown: ctx.anomalies.held_for(&market.id, Instant::now()),
+    };
+    ctx.execution_engine.checks.check(&trade)
+}
+
+/// Net exposure, and exposure across markets correlated with the intent's,
+/// now and with the intent filled; each only when capped
+///
+/// Hedged YES+NO pairs don't count against either cap.
+async fn projected_exposure(
+    ctx: &WorkerContext,
+    intent: &Intent,
+    now: u64,
+) -> (Option<(f64, f64)>, Option<(f64, f64)>) {
+    let checks = &ctx.execution_engine.checks;
+    let net_capped = checks.max_position_value > 0.0;
+    let correlated_capped = checks.max_correlated_exposure > 0.0;
+    if !net_capped && !correlated_capped {
+        return (None, None);
+    }
+    let cache = ctx.market_cache.read().await;
+    let pm = ctx.position_manager.read().await;
+    let market = cache.markets.get(&intent.market_id);
+    let legs: Vec<Position> = intent
+        .token_ids
+        .iter()
+        .filter_map(|token_id| {
+            Some(Position {
+                market_id: intent.market_id.clone(),
+                token_id: token_id.clone(),
+                side: intent.side,
+                size: intent.size,
+                entry_price: market?.token_price(token_id)?,
+                entry_time: now,
+                entry_spread: intent.spread,
+                entry_executions: Vec::new(),
+            })
+        })
+        .collect();
+    let markets = cache.markets.as_slice();
+    let current = pm.exposure(markets);
+    let projected = net_exposure(pm.get_positions().into_iter().chain(&legs), markets);
+    let correlated = |report| {
+        ctx.correlations
+            .correlated_exposure(&intent.market_id, report)
+    };
+    (
+        net_capped.then_some((current.net_value, projected.net_value)),
+        correlated_capped.then(|| (correlated(&current), correlated(&projected))),
+    )
+}
+
+/// Whether a full set at `sum` per set still beats the $1 payout after fees
+fn bundle_clears(sum: f64, fee_rate: f64, side: Side) -> bool {
+    match side {
+        Side::Buy => sum * (1.0 + fee_rate) < 1.0,
+        Side::Sell => sum * (1.0 - fee_rate) > 1.0,
+    }
+}
+
+/// Release the spend reservations of legs that will not be executed
+async fn release_legs(ctx: &WorkerContext, legs: &[Leg]) {
+    let ids: Vec<&String> = legs
+        .iter()
+        .filter_map(|leg| leg.execution_id.as_ref())
+        .collect();
+    if ids.is_empty() {
+        return;
+    }
+    ctx.update_ledger(|ledger| {
+        for id in ids {
+            ledger.release(id);
+        }
+    })
+    .await;
+}
+
+/// Book spend, notify, and feed fills back to strategies
+async fn settle(ctx: &WorkerContext, executed: Executed) {
+    let (entry, fills) = match executed {
+        Executed::Shadow {
+            fills,
+            expected_profit,
+        } => {
+            if let Err(e) = ctx.shadow.lock().await.record(&fills, expected_profit) {
+                println!("⚠️ Failed to log shadow fills: {}", e);
+            }
+            return;
+        }
+        Executed::Live { entry, fills } => (entry, fills),
+    };
+    for result in &fills {
+        book_fill(ctx, &entry, result, &result.execution_id).await;
+    }
+    ctx.sample_utilization().await;
+    if fills.len() == entry.intent.token_ids.len() {
+        merge_bundle(ctx, &entry).await;
+    }
+}
+
+/// Charge one fill to its account and feed it back to strategies
+///
+/// `fill_id` dedupes the spend; a paper fill is its own execution, a CLOB
+/// order may fill several times under one execution.
+async fn book_fill(ctx: &WorkerContext, entry: &Entry, result: &ExecutionResult, fill_id: &str) {
+    let account = ctx
+        .accounts
+        .get(&entry.account)
+        .unwrap_or_else(|| ctx.accounts.primary());
+    {
+        // Spend and confirm under one lock, so a reservation check never
+        // sees the fill both on the grant and reserved, or on neither
+        let mut ledger = ctx.ledger.lock().await;
+        if let Err(e) = account.metamask.record_fill(result.total_cost).await {
+            println!(
+                "⚠️ [{}] Fill {} of ${:.2} not charged to the grant, which now disagrees with the ledger: {}",
+                entry.account, fill_id, result.total_cost, e
+            );
+        }
+        if fill_id == result.execution_id {
+            // Paper fills were charged to the wallet as they were simulated
+            ledger.confirm(&result.execution_id, result.total_cost, entry.now);
+        } else {
+            if !ctx.wallet.lock().await.record_spend(result.total_cost) {
+                println!(
+                    "⚠️ [{}] Fill {} of ${:.2} is over the local spend cap",
+                    entry.account, fill_id, result.total_cost
+                );
+            }
+            ledger.confirm_fill(&result.execution_id, fill_id, result.total_cost, entry.now);
+        }
+        ctx.persist_ledger(&ledger);
+    }
+    let intent = &entry.intent;
+    if let Some(webhook) = &ctx.webhook {
+        webhook.publish(WebhookEvent::Execution {
+            timestamp: entry.now,
+            strategy: intent.strategy,
+            result: Box::new(result.clone()),
+        });
+    }
+
+    ctx.pnl_breakdown.tag(
+        &result.execution_id,
+        TradeTag {
+            origin: TradeOrigin::of(intent.strategy, intent.order_type),
+            mode: entry.mode,
+        },
+    );
+    ctx.strategies.lock().await.on_fill(intent.strategy, result);
+    ctx.tuner
+        .lock()
+        .await
+        .record_entry(&result.execution_id, intent.edge);
+}
+
+/// Book fills and closes the user channel reported for posted orders
+///
+/// Entry fills open positions and move spend from the reservation onto the
+/// grant; exit fills close positions and realize their PnL. A close (cancel,
+/// rejection, or expiry) frees what an entry did not spend.
+pub async fn book_order_updates(ctx: &WorkerContext) {
+    for update in ctx.orders.take_updates() {
+        match update {
+            OrderUpdate::Fill { execution_id, fill } => {
+                if let Some((exit, fee_rate)) = ctx.live_orders.exit(&execution_id) {
+                    book_exit(ctx, &exit, fee_rate, &fill).await;
+                    continue;
+                }
+                let Some(entry) = ctx.live_orders.get(&execution_id) else {
+                    println!(
+                        "⚠️ [Orders] Fill {} of {} has no live execution, left to reconciliation",
+                        fill.trade_id, execution_id
+                    );
+                    continue;
+                };
+                let result = fill_result(ctx, &entry, &execution_id, &fill);
+                println!(
+                    "✅ [Orders] Filled {:.2} of {} at {:.4} ({})",
+                    fill.size, entry.intent.market_id, fill.price, execution_id
+                );
+                {
+                    let mut pm = ctx.position_manager.write().await;
+                    pm.open_position(Position::from_execution(
+                        &result,
+                        Wallet::current_timestamp(),
+                        entry.intent.spread,
+                    ));
+                }
+                ctx.accounts.assign(&execution_id, &entry.account);
+                let fill_id = format!("{}:{}", fill.trade_id, fill.order_id);
+                book_fill(ctx, &entry, &result, &fill_id).await;
+                ctx.sample_utilization().await;
+            }
+            OrderUpdate::Closed {
+                execution_id,
+                order_id,
+            } => {
+                if let Some((exit, _)) = ctx.live_orders.remove_exit(&execution_id) {
+                    println!(
+                        "📕 [Orders] Exit {} of {} closed ({})",
+                        order_id, exit.token_id, execution_id
+                    );
+                    continue;
+                }
+                ctx.update_ledger(|ledger| {
+                    ledger.release(&execution_id);
+                })
+                .await;
+                let Some(entry) = ctx.live_orders.remove(&execution_id) else {
+                    continue;
+                };
+                println!(
+                    "📕 [Orders] Order {} on {} closed ({})",
+                    order_id, entry.intent.market_id, execution_id
+                );
+                if !ctx.live_orders.working_for(&entry) {
+                    merge_bundle(ctx, &entry).await;
+                }
+            }
+        }
+    }
+}
+
+/// Close the part of a position its exit order filled
+async fn book_exit(ctx: &WorkerContext, exit: &DueExit, fee_rate: f64, fill: &orders::Fill) {
+    let closed = ctx.position_manager.write().await.fill_exit(
+        &exit.token_id,
+        fill.size,
+        fill.price,
+        fill.timestamp,
+        exit.reason.clone(),
+        fee_rate,
+    );
+    match closed {
+        Some(result) => {
+            println!(
+                "✅ [Orders] Exited {:.2} of {} at {:.4}",
+                fill.size, exit.token_id, fill.price
+            );
+            ctx.record_exits(&exit.market_id, &[result]).await;
+        }
+        None => println!(
+            "⚠️ [Orders] Exit fill {} on {} has no open position, left to reconciliation",
+            fill.trade_id, exit.token_id
+        ),
+    }
+}
+
+/// The fill as an execution of the leg, with the fee it was charged
+fn fill_result(
+    ctx: &WorkerContext,
+    entry: &Entry,
+    execution_id: &str,
+    fill: &orders::Fill,
+) -> ExecutionResult {
+    let market_id = &entry.intent.market_id;
+    let notional = money::charge(money::from_f64(fill.price * fill.size));
+    let fee = ctx
+        .execution_engine
+        .fee_model
+        .calculate_in(market_id, notional, fill.maker);
+    let at_ms = fill.timestamp * 1000;
+    ExecutionResult {
+        execution_id: execution_id.to_string(),
+        market_id: market_id.clone(),
+        token_id: fill.token_id.clone(),
+        side: fill.side,
+        requested_size: fill.size,
+        filled_size: fill.size,
+        execution_price: fill.price,
+        price_impact: Decimal::ZERO,
+        fee_paid: fee,
+        gas_cost: Decimal::ZERO,
+        slippage: 0.0,
+        total_cost: notional + fee,
+        maker: ctx.clob.as_ref().map(|auth| auth.order_maker()),
+        submitted_at_ms: at_ms,
+        acked_at_ms: at_ms,
+        filled_at_ms: at_ms,
+        success: true,
+    }
+}
+
+/// Redeem the complete YES+NO sets a bought bundle ended up holding
+///
+/// A set is worth exactly $1, so it is merged once every leg has closed.
+async fn merge_bundle(ctx: &WorkerContext, entry: &Entry) {
+    let intent = &entry.intent;
+    if !ctx.config.ctf.merge_bundles || ctx.config.trading.shadow_mode || intent.side != Side::Buy {
+        return;
+    }
+    let Some(ctf) = &ctx.ctf else {
+        return;
+    };
+    let sets = {
+        let pm = ctx.position_manager.read().await;
+        let held: Vec<f64> = intent
+            .token_ids
+            .iter()
+            .map(|token| pm.get_position(token).map_or(0.0, |p| p.size))
+            .collect();
+        mergeable_sets(&held)
+    };
+    if entry.condition_id.is_empty() || sets <= 0.0 {
+        return;
+    }
+    match ctf.merge(&entry.condition_id, sets).await {
+        Ok(tx_hash) => {
+            println!(
+                "🔀 [CTF] Merged {:.2} sets of {} (tx {})",
+                sets, intent.market_id, tx_hash
+            );
+            let exits = ctx.position_manager.write().await.merge_sets(
+                &intent.token_ids,
+                sets,
+                Wallet::current_timestamp(),
+            );
+            ctx.record_exits(&intent.market_id, &exits).await;
+        }
+        Err(e) => println!("⚠️ [CTF] Merge of {} failed: {}", intent.market_id, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentBuilder;
+    use crate::config::Config;
+    use crate::data_source::DataSource;
+    use crate::orders::UserEvent;
+    use crate::test_util;
+    use crate::websocket::{parse_events, parse_messages};
+
+    /// Context of an agent filling on paper
+    async fn paper_context(name: &str, configure: impl FnOnce(&mut Config)) -> Arc<WorkerContext> {
+        let mut config = Config::default_config();
+        config.api.headless = true;
+        configure(&mut config);
+        let agent = AgentBuilder::new(config)
+            .with_storage(test_util::temp_storage(name))
+            .build()
+            .await
+            .unwrap();
+        agent.ctx
+    }
+
+    /// An arb intent buying `size` of each token in "m1"
+    fn intent(token_ids: &[&str], size: f64) -> Intent {
+        Intent {
+            strategy: "arb",
+            market_id: "m1".into(),
+            token_ids: token_ids.iter().map(|&id| id.into()).collect(),
+            side: Side::Buy,
+            size,
+            spread: 0.05,
+            edge: 0.03,
+            expected_profit: 0.3,
+            order_type: OrderType::PostOnly,
+        }
+    }
+
+    fn entry(token_ids: &[&str], size: f64) -> Arc<Entry> {
+        Arc::new(Entry {
+            condition_id: String::new(),
+            account: PRIMARY_ACCOUNT.to_string(),
+            mode: StrategyMode::Normal,
+            intent: intent(token_ids, size),
+            now: 1000,
+        })
+    }
+
+    /// Reserve and track order `order_id` as posted for an execution of the
+    /// entry's first leg
+    async fn post(ctx: &WorkerContext, entry: &Arc<Entry>, execution_id: &str, order_id: &str) {
+        let token_id = &entry.intent.token_ids[0];
+        ctx.ledger.lock().await.begin(
+            execution_id,
+            entry.intent.strategy,
+            &entry.account,
+            money::usdc(0.45 * entry.intent.size),
+            entry.now,
+        );
+        ctx.live_orders.insert(execution_id, token_id, entry);
+        let request = OrderRequest {
+            market: entry.intent.market_id.clone(),
+            token_id: token_id.clone(),
+            side: Side::Buy,
+            price: 0.45,
+            size: entry.intent.size,
+            order_type: OrderType::PostOnly,
+            execution_id: Some(execution_id.to_string()),
+        };
+        ctx.orders.placed(&request, order_id, 1000);
+    }
+
+    /// Report trade `trade_id` filling `amount` of our resting order at 0.45
+    fn trade(ctx: &WorkerContext, trade_id: &str, order_id: &str, amount: f64) {
+        let json = format!(
+            r#"[{{"event_type":"trade","id":"{}","asset_id":"t2","side":"SELL","price":"0.55",
+            "size":"{}","status":"MATCHED","taker_order_id":"someone","trader_side":"MAKER",
+            "maker_orders":[{{"order_id":"{}","matched_amount":"{}","price":"0.45","asset_id":"t1"}}]}}]"#,
+            trade_id, amount, order_id, amount
+        );
+        for event in parse_events::<UserEvent>(&json) {
+            ctx.orders.apply(&event);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_clob_fill_charges_wallet() {
+        let ctx = paper_context("pipeline_wallet", |_| {}).await;
+        let entry = entry(&["t1"], 10.0);
+        post(&ctx, &entry, "exec-1", "o1").await;
+        let before = ctx.wallet.lock().await.remaining();
+
+        trade(&ctx, "tr1", "o1", 4.0);
+        book_order_updates(&ctx).await;
+
+        let charged = before - ctx.wallet.lock().await.remaining();
+        assert!(charged >= money::usdc(1.80));
+        assert_eq!(charged, ctx.ledger.lock().await.total_spent());
+    }
+
+    #[test]
+    fn test_bundle_clears_after_fees() {
+        // 0.97 + 2% fees = 0.9894 < 1
+        assert!(bundle_clears(0.97, 0.02, Side::Buy));
+        // 0.985 + 2% fees = 1.0047 > 1
+        assert!(!bundle_clears(0.985, 0.02, Side::Buy));
+        assert!(bundle_clears(1.03, 0.02, Side::Sell));
+        assert!(!bundle_clears(1.01, 0.02, Side::Sell));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_trade_refunds_slot_and_releases_reservation() {
+        let ctx = paper_context("pipeline_rejected", |config| {
+            config.api.stream_books = true;
+            config.safety.max_trades_per_cycle = 1;
+        })
+        .await;
+        *ctx.data_source.write().await = DataSource::Stream;
+        let books = ctx.order_books.as_ref().unwrap();
+        let book = r#"{"event_type":"book","asset_id":"yes","market":"0xabc",
+            "bids":[{"price":"0.44","size":"100"}],"asks":[{"price":"0.45","size":"100"}],
+            "timestamp":"1000"}"#;
+        for message in parse_messages(book) {
+            books.apply(&message).await;
+        }
+
+        let admitted_at = Instant::now();
+        ctx.throttle.admit(admitted_at).unwrap();
+        ctx.ledger
+            .lock()
+            .await
+            .begin("exec-1", "arb", PRIMARY_ACCOUNT, money::usdc(4.5), 1000);
+        let order = Order {
+            market: test_util::market(0.45, 0.55),
+            intent: intent(&["yes"], 10.0),
+            legs: vec![Leg {
+                token_id: "yes".into(),
+                book: books.book(&"yes".into()).await.unwrap(),
+                size: 10.0,
+                signal_price: 0.45,
+                limit_price: None,
+                route: LegRoute::Take,
+                execution_id: Some("exec-1".to_string()),
+            }],
+            account: PRIMARY_ACCOUNT.to_string(),
+            mode: StrategyMode::Normal,
+            exits: ctx.position_manager.read().await.exit_limits(),
+            now: 1000,
+            detected_at: Instant::now(),
+            admitted_at: Some(admitted_at),
+        };
+
+        // Without a grant there is no allowance to spend
+        assert!(execute(&ctx, order).await.is_none());
+        assert_eq!(ctx.skips.count(SkipReason::InsufficientAllowance), 1);
+        assert!(ctx.ledger.lock().await.dangling_intents().is_empty());
+        assert!(ctx.throttle.admit(Instant::now()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_partial_fill_then_close_releases_the_rest() {
+        let ctx = paper_context("pipeline_partial", |_| {}).await;
+        let entry = entry(&["t1"], 10.0);
+        post(&ctx, &entry, "exec-1", "o1").await;
+
+        trade(&ctx, "tr1", "o1", 4.0);
+        ctx.orders.close("o1");
+        book_order_updates(&ctx).await;
+
+        let ledger = ctx.ledger.lock().await;
+        assert_eq!(ledger.total_spent(), money::usdc(1.80));
+        assert!(ledger.dangling_intents().is_empty());
+        assert!(!ctx.live_orders.working_on(&"t1".into()));
+        let pm = ctx.position_manager.read().await;
+        assert_eq!(pm.get_position(&"t1".into()).unwrap().size, 4.0);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_trade_booked_once() {
+        let ctx = paper_context("pipeline_duplicate", |_| {}).await;
+        let entry = entry(&["t1"], 10.0);
+        post(&ctx, &entry, "exec-1", "o1").await;
+        let before = ctx.wallet.lock().await.remaining();
+
+        trade(&ctx, "tr1", "o1", 4.0);
+        trade(&ctx, "tr1", "o1", 4.0);
+        book_order_updates(&ctx).await;
+
+        assert_eq!(ctx.ledger.lock().await.total_spent(), money::usdc(1.80));
+        assert_eq!(
+            before - ctx.wallet.lock().await.remaining(),
+            money::usdc(1.80)
+        );
+        let pm = ctx.position_manager.read().await;
+        assert_eq!(pm.get_position(&"t1".into()).unwrap().size, 4.0);
+    }
+}
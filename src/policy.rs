@@ -0,0 +1,222 @@
+//! Permission policy engine
+//!
+//! Evaluates a spend against the active grant in one place: present, not
+//! revoked, not expired, within the daily limit, under the per-trade cap,
+//! through an allowed contract, and in an allowed market category. The same
+//! evaluation runs when a trade is approved and when its fills are charged.
+
+use crate::metamask::PermissionGrant;
+use crate::money::Decimal;
+
+/// A spend about to be made under the grant
+#[derive(Debug, Clone, Copy)]
+pub struct SpendRequest<'a> {
+    pub amount: Decimal,
+    /// Category of the market traded
+    pub category: &'a str,
+}
+
+/// The constraint a spend broke
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolicyViolation {
+    NoGrant,
+    Revoked,
+    Expired,
+    DailyLimit { remaining: Decimal },
+    PerTradeCap { max: Decimal },
+    Contract(String),
+    Category(String),
+}
+
+impl std::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoGrant => write!(f, "no permission granted"),
+            Self::Revoked => write!(f, "permission has been revoked"),
+            Self::Expired => write!(f, "permission has expired"),
+            Self::DailyLimit { remaining } => {
+                write!(f, "insufficient allowance (${:.2} left)", remaining)
+            }
+            Self::PerTradeCap { max } => write!(f, "over the ${:.2} per-trade limit", max),
+            Self::Contract(contract) => write!(f, "contract {} not allowed", contract),
+            Self::Category(category) => write!(f, "category \"{}\" not allowed", category),
+        }
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+/// Checks spends against a grant's constraints
+#[derive(Debug, Clone, Default)]
+pub struct PolicyEngine {
+    /// Contract the agent's USDC is spent through (the CTF exchange)
+    contract: String,
+}
+
+impl PolicyEngine {
+    pub fn new(contract: &str) -> Self {
+        Self {
+            contract: contract.to_string(),
+        }
+    }
+
+    /// Whether the grant can be spent under at all
+    pub fn check_active(
+        &self,
+        grant: Option<&PermissionGrant>,
+        now: u64,
+    ) -> Result<(), PolicyViolation> {
+        let grant = grant.ok_or(PolicyViolation::NoGrant)?;
+        Self::active(grant, now)
+    }
+
+    fn active(grant: &PermissionGrant, now: u64) -> Result<(), PolicyViolation> {
+        if grant.revoked {
+            return Err(PolicyViolation::Revoked);
+        }
+        if grant.expires_at <= now {
+            return Err(PolicyViolation::Expired);
+        }
+        Ok(())
+    }
+
+    /// Evaluate a spend against every constraint of the grant
+    pub fn evaluate(
+        &self,
+        grant: Option<&PermissionGrant>,
+        spend: &SpendRequest,
+        now: u64,
+    ) -> Result<(), PolicyViolation> {
+        let grant = grant.ok_or(PolicyViolation::NoGrant)?;
+        Self::active(grant, now)?;
+
+        let remaining = (grant.daily_limit - grant.spent_today).max(Decimal::ZERO);
+        if spend.amount > remaining {
+            return Err(PolicyViolation::DailyLimit { remaining });
+        }
+
+        let scope = &grant.scope;
+        if let Some(max) = scope.max_per_trade {
+            if spend.amount > max {
+                return Err(PolicyViolation::PerTradeCap { max });
+            }
+        }
+        if !scope.allowed_contracts.is_empty()
+            && !scope
+                .allowed_contracts
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(&self.contract))
+        {
+            return Err(PolicyViolation::Contract(self.contract.clone()));
+        }
+        if !scope.allowed_categories.is_empty()
+            && !scope
+                .allowed_categories
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(spend.category))
+        {
+            return Err(PolicyViolation::Category(spend.category.to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money;
+
+    fn grant() -> PermissionGrant {
+        serde_json::from_str(
+            r#"{"permission_id":"p","token":"USDC","daily_limit":25.0,"spent_today":18.0,
+                "expires_at":2000,"max_per_trade":5.0,"allowed_contracts":["0xABC"],
+                "allowed_categories":["Sports"]}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_evaluates_every_constraint() {
+        let policy = PolicyEngine::new("0xabc");
+        let g = grant();
+        let spend = SpendRequest {
+            amount: money::usdc(4.0),
+            category: "sports",
+        };
+        assert_eq!(policy.evaluate(Some(&g), &spend, 1000), Ok(()));
+
+        assert_eq!(
+            policy.evaluate(None, &spend, 1000),
+            Err(PolicyViolation::NoGrant)
+        );
+        assert_eq!(
+            policy.evaluate(Some(&g), &spend, 2000),
+            Err(PolicyViolation::Expired)
+        );
+        let revoked = PermissionGrant {
+            revoked: true,
+            ..grant()
+        };
+        assert_eq!(
+            policy.evaluate(Some(&revoked), &spend, 1000),
+            Err(PolicyViolation::Revoked)
+        );
+        assert_eq!(
+            policy.evaluate(
+                Some(&g),
+                &SpendRequest {
+                    amount: money::usdc(8.0),
+                    ..spend
+                },
+                1000
+            ),
+            Err(PolicyViolation::DailyLimit {
+                remaining: money::usdc(7.0)
+            })
+        );
+        assert_eq!(
+            policy.evaluate(
+                Some(&g),
+                &SpendRequest {
+                    amount: money::usdc(6.0),
+                    ..spend
+                },
+                1000
+            ),
+            Err(PolicyViolation::PerTradeCap {
+                max: money::usdc(5.0)
+            })
+        );
+        assert_eq!(
+            policy.evaluate(
+                Some(&g),
+                &SpendRequest {
+                    category: "Politics",
+                    ..spend
+                },
+                1000
+            ),
+            Err(PolicyViolation::Category("Politics".to_string()))
+        );
+        assert_eq!(
+            PolicyEngine::new("0xdef").evaluate(Some(&g), &spend, 1000),
+            Err(PolicyViolation::Contract("0xdef".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_unscoped_grant_only_checks_limit() {
+        let g: PermissionGrant = serde_json::from_str(
+            r#"{"permission_id":"p","token":"USDC","daily_limit":25.0,"expires_at":2000}"#,
+        )
+        .unwrap();
+        let spend = SpendRequest {
+            amount: money::usdc(25.0),
+            category: "",
+        };
+        assert_eq!(
+            PolicyEngine::default().evaluate(Some(&g), &spend, 1000),
+            Ok(())
+        );
+    }
+}
@@ -2,31 +2,65 @@
 //!
 //! Handles position tracking, mean reversion exits, and PnL calculation.
 
-use crate::types::{Market, Side};
-use std::collections::HashMap;
+use crate::exposure::{net_exposure, ExposureReport};
+use crate::money::{self, Decimal};
+use crate::types::{ExecutionResult, Market, MarketId, Side, TokenId};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Storage document holding lifetime trade stats
+pub const STATS_DOCUMENT: &str = "stats";
 
 /// An open position in the market
 #[derive(Debug, Clone)]
 pub struct Position {
-    pub market_id: String,
-    pub token_id: String,
+    pub market_id: MarketId,
+    pub token_id: TokenId,
     pub side: Side,
     pub size: f64,
     pub entry_price: f64,
     pub entry_time: u64,
     pub entry_spread: f64, // Spread at entry for mean reversion tracking
+    /// Executions that opened (or were merged into) this position
+    pub entry_executions: Vec<String>,
+}
+
+impl Position {
+    /// Open a position from an entry execution
+    pub fn from_execution(result: &ExecutionResult, entry_time: u64, entry_spread: f64) -> Self {
+        Self {
+            market_id: result.market_id.clone(),
+            token_id: result.token_id.clone(),
+            side: result.side,
+            size: result.filled_size,
+            entry_price: result.execution_price,
+            entry_time,
+            entry_spread,
+            entry_executions: vec![result.execution_id.clone()],
+        }
+    }
+
+    /// Exit price at which the position clears `buffer` per share after
+    /// paying `fee_rate` on both the entry and the exit
+    pub fn break_even_price(&self, fee_rate: f64, buffer: f64) -> f64 {
+        match self.side {
+            Side::Buy => (self.entry_price * (1.0 + fee_rate) + buffer) / (1.0 - fee_rate),
+            Side::Sell => (self.entry_price * (1.0 - fee_rate) - buffer) / (1.0 + fee_rate),
+        }
+    }
 }
 
 /// Position exit reason
 #[derive(Debug, Clone)]
 pub enum ExitReason {
     MeanReversion, // Spread normalized
-    #[allow(dead_code)]
-    ProfitTarget, // Hit profit target
+    ProfitTarget,  // Cleared break-even plus fees and buffer
+    TrailingStop,  // Spread re-widened after narrowing
+    ScaleOut,      // Partial close at half reversion
     StopLoss,      // Hit stop loss
     Timeout,       // Position held too long
-    #[allow(dead_code)]
-    Manual, // Manual close
+    Merged,        // YES+NO pair redeemed for $1 via the CTF
+    Resolved,      // Market settled; tokens redeemed at their payout
 }
 
 /// Position exit result
@@ -36,46 +70,271 @@ pub struct ExitResult {
     pub exit_price: f64,
     pub exit_time: u64,
     pub reason: ExitReason,
-    pub pnl: f64,
-    #[allow(dead_code)]
-    pub fees: f64,
+    pub pnl: Decimal,
+    pub fees: Decimal,
+}
+
+/// A position that hit an exit condition, not yet closed
+#[derive(Debug, Clone)]
+pub struct DueExit {
+    pub market_id: MarketId,
+    pub token_id: TokenId,
+    /// Side of the position (the exit trades the other way)
+    pub side: Side,
+    /// Shares to close
+    pub size: f64,
+    /// Price of the held token when the exit came due
+    pub price: f64,
+    pub reason: ExitReason,
+}
+
+/// How to handle a new entry on a token that already has an open position
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateEntryPolicy {
+    /// Combine into one position at the size-weighted average entry
+    #[default]
+    Merge,
+    /// Keep the existing position and refuse the new entry
+    Reject,
+}
+
+/// What closes a position at a profit
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfitTarget {
+    /// The spread reverting below `profit_target_spread`
+    #[default]
+    Spread,
+    /// The price covering entry, round-trip fees, and a buffer
+    BreakEven,
+}
+
+/// Trailing stop that ratchets the exit threshold as the spread narrows
+#[derive(Debug, Clone, Copy)]
+pub struct TrailingStop {
+    /// Spread narrowing from entry required to arm the stop
+    pub activation: f64,
+    /// Allowed re-widening from the tightest spread seen before exiting
+    pub distance: f64,
+}
+
+/// Aggregate trade statistics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TradeStats {
+    pub trades: usize,
+    pub wins: usize,
+    pub total_pnl: Decimal,
+}
+
+impl TradeStats {
+    /// Record a closed trade
+    pub fn record(&mut self, pnl: Decimal) {
+        self.trades += 1;
+        if pnl > Decimal::ZERO {
+            self.wins += 1;
+        }
+        self.total_pnl += pnl;
+    }
+
+    /// Get win rate
+    pub fn win_rate(&self) -> f64 {
+        if self.trades == 0 {
+            return 0.0;
+        }
+        self.wins as f64 / self.trades as f64
+    }
+}
+
+/// Cumulative stats persisted across restarts, split by live and demo trades
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LifetimeStats {
+    pub live: TradeStats,
+    pub demo: TradeStats,
+}
+
+/// When a position is closed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExitLimits {
+    /// Spread below which the position has reverted and is closed
+    pub profit_target_spread: f64,
+    /// Spread widening from entry that stops the position out
+    pub stop_loss_spread: f64,
+    /// Maximum hold time in seconds
+    pub max_hold_time: u64,
 }
 
 /// Position manager for tracking and closing positions
 #[derive(Debug)]
 pub struct PositionManager {
     /// Open positions by token_id
-    positions: HashMap<String, Position>,
-    /// Profit target (spread must narrow by this much)
-    profit_target_spread: f64,
-    /// Stop loss threshold
-    stop_loss_spread: f64,
-    /// Maximum hold time in seconds
-    max_hold_time: u64,
-    /// Closed positions history
+    positions: HashMap<TokenId, Position>,
+    /// Profit target, stop loss, and hold limit
+    exits: ExitLimits,
+    /// Limits overriding `exits`, by market_id
+    market_exits: HashMap<MarketId, ExitLimits>,
+    /// Closed positions history, including partial closes
     history: Vec<ExitResult>,
+    /// Trades closed this session, each counted once fully closed
+    live: TradeStats,
+    /// PnL realized by partial closes of positions still open, by token_id
+    partial_pnl: HashMap<TokenId, Decimal>,
+    /// Simulated demo trades this session (kept out of history)
+    demo: TradeStats,
+    /// Cumulative stats across all runs
+    lifetime: LifetimeStats,
+    /// Optional trailing stop
+    trailing_stop: Option<TrailingStop>,
+    /// Tightest spread seen per position, by token_id
+    best_spreads: HashMap<TokenId, f64>,
+    /// Fraction of a position closed at half reversion (None = disabled)
+    scale_out_fraction: Option<f64>,
+    /// Positions that have already been scaled out, by token_id
+    scaled_out: HashSet<TokenId>,
+    /// Handling of repeated entries on the same token
+    duplicate_entry: DuplicateEntryPolicy,
+    /// Profit per share over round-trip fees that closes a position, in
+    /// place of the spread target (None = spread target)
+    break_even_buffer: Option<f64>,
 }
 
 impl PositionManager {
     pub fn new(profit_target_spread: f64, stop_loss_spread: f64, max_hold_time: u64) -> Self {
         Self {
             positions: HashMap::new(),
-            profit_target_spread,
-            stop_loss_spread,
-            max_hold_time,
+            exits: ExitLimits {
+                profit_target_spread,
+                stop_loss_spread,
+                max_hold_time,
+            },
+            market_exits: HashMap::new(),
             history: Vec::new(),
+            live: TradeStats::default(),
+            partial_pnl: HashMap::new(),
+            demo: TradeStats::default(),
+            lifetime: LifetimeStats::default(),
+            trailing_stop: None,
+            best_spreads: HashMap::new(),
+            scale_out_fraction: None,
+            scaled_out: HashSet::new(),
+            duplicate_entry: DuplicateEntryPolicy::default(),
+            break_even_buffer: None,
         }
     }
 
-    /// Add a new position
-    pub fn open_position(&mut self, position: Position) {
+    /// Exit limits positions are held to unless their market overrides them
+    pub fn exit_limits(&self) -> ExitLimits {
+        self.exits
+    }
+
+    /// Close positions in one market at different limits
+    pub fn set_market_exits(&mut self, market_id: &MarketId, exits: ExitLimits) {
+        if exits == self.exits {
+            self.market_exits.remove(market_id);
+        } else {
+            self.market_exits.insert(market_id.clone(), exits);
+        }
+    }
+
+    /// Set how repeated entries on the same token are handled
+    pub fn with_duplicate_entry(mut self, policy: DuplicateEntryPolicy) -> Self {
+        self.duplicate_entry = policy;
+        self
+    }
+
+    /// Close `fraction` of each position once its spread has half-reverted
+    pub fn with_scale_out(mut self, fraction: f64) -> Self {
+        self.scale_out_fraction = Some(fraction.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Take profit once a position's price clears entry plus round-trip
+    /// fees plus `buffer` per share, instead of at the spread target
+    pub fn with_break_even(mut self, buffer: f64) -> Self {
+        self.break_even_buffer = Some(buffer.max(0.0));
+        self
+    }
+
+    /// Enable a trailing stop on all positions
+    pub fn with_trailing_stop(mut self, trailing_stop: TrailingStop) -> Self {
+        self.trailing_stop = Some(trailing_stop);
+        self
+    }
+
+    /// Resume cumulative stats loaded from storage
+    pub fn with_lifetime_stats(mut self, lifetime: LifetimeStats) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    /// Get cumulative stats across all runs
+    pub fn lifetime_stats(&self) -> &LifetimeStats {
+        &self.lifetime
+    }
+
+    /// Check whether a new entry on this token would be accepted
+    pub fn accepts_entry(&self, token_id: &TokenId) -> bool {
+        self.duplicate_entry == DuplicateEntryPolicy::Merge
+            || !self.positions.contains_key(token_id)
+    }
+
+    /// Add a new position, merging or rejecting repeated entries per policy
+    ///
+    /// Further fills of an order already in the position always merge.
+    /// Returns false if the entry was rejected.
+    pub fn open_position(&mut self, position: Position) -> bool {
+        if let Some(existing) = self.positions.get_mut(&position.token_id) {
+            let same_entry = !position.entry_executions.is_empty()
+                && position
+                    .entry_executions
+                    .iter()
+                    .all(|id| existing.entry_executions.contains(id));
+            if self.duplicate_entry == DuplicateEntryPolicy::Reject && !same_entry {
+                println!(
+                    "⚠️ [Position] Rejected duplicate entry on {}",
+                    position.token_id
+                );
+                return false;
+            }
+
+            // Size-weighted average of entry price and spread
+            let total_size = existing.size + position.size;
+            if total_size > 0.0 {
+                existing.entry_price = (existing.entry_price * existing.size
+                    + position.entry_price * position.size)
+                    / total_size;
+                existing.entry_spread = (existing.entry_spread * existing.size
+                    + position.entry_spread * position.size)
+                    / total_size;
+            }
+            existing.size = total_size;
+            existing.entry_time = existing.entry_time.min(position.entry_time);
+            for id in position.entry_executions {
+                if !existing.entry_executions.contains(&id) {
+                    existing.entry_executions.push(id);
+                }
+            }
+
+            println!(
+                "📈 [Position] Merged: {} -> {:.2} @ ${:.4} (spread: {:.2}%)",
+                existing.token_id,
+                existing.size,
+                existing.entry_price,
+                existing.entry_spread * 100.0
+            );
+            return true;
+        }
+
         println!(
             "📈 [Position] Opened: {} @ ${:.4} (spread: {:.2}%)",
             position.token_id,
             position.entry_price,
             position.entry_spread * 100.0
         );
+        self.best_spreads
+            .insert(position.token_id.clone(), position.entry_spread);
         self.positions.insert(position.token_id.clone(), position);
+        true
     }
 
     /// Get all open positions
@@ -83,191 +342,815 @@ impl PositionManager {
         self.positions.values().collect()
     }
 
+    /// Markets with at least one open position
+    pub fn held_markets(&self) -> HashSet<MarketId> {
+        self.positions
+            .values()
+            .map(|p| p.market_id.clone())
+            .collect()
+    }
+
     /// Get position by token_id
-    #[allow(dead_code)]
-    pub fn get_position(&self, token_id: &str) -> Option<&Position> {
+    pub fn get_position(&self, token_id: &TokenId) -> Option<&Position> {
         self.positions.get(token_id)
     }
 
-    /// Check positions for exit conditions
+    /// Overwrite a position's size (e.g. from exchange reconciliation)
+    ///
+    /// A size of zero or less removes the position.
+    pub fn correct_size(&mut self, token_id: &TokenId, size: f64) {
+        if size <= 0.0 {
+            self.remove(token_id);
+            self.partial_pnl.remove(token_id);
+        } else if let Some(position) = self.positions.get_mut(token_id) {
+            position.size = size;
+        }
+        println!(
+            "🔧 [Position] Corrected {} size to {:.4}",
+            token_id,
+            size.max(0.0)
+        );
+    }
+
+    /// Check positions for exit conditions and close them at market prices
     pub fn check_exits(
         &mut self,
         markets: &[Market],
         current_time: u64,
         fee_rate: f64,
     ) -> Vec<ExitResult> {
-        let mut exits = Vec::new();
-        let mut to_remove = Vec::new();
+        self.due_exits(markets, current_time, fee_rate)
+            .into_iter()
+            .filter_map(|due| {
+                self.fill_exit(
+                    &due.token_id,
+                    due.size,
+                    due.price,
+                    current_time,
+                    due.reason,
+                    fee_rate,
+                )
+            })
+            .collect()
+    }
+
+    /// Positions that hit an exit condition, and how much of each to close
+    ///
+    /// Nothing is closed: an exit is booked with `fill_exit` once it fills.
+    pub fn due_exits(
+        &mut self,
+        markets: &[Market],
+        current_time: u64,
+        fee_rate: f64,
+    ) -> Vec<DueExit> {
+        let mut due = Vec::new();
 
         for (token_id, position) in &self.positions {
-            // Find current market state
-            if let Some(market) = markets.iter().find(|m| m.id == position.market_id) {
-                let current_spread = market.get_spread();
-                let current_price = if position.side == Side::Buy {
-                    market.yes_price() // Simplified - should match token
-                } else {
-                    market.no_price()
-                };
-
-                let hold_time = current_time.saturating_sub(position.entry_time);
-
-                // Check exit conditions
-                let exit_reason = if current_spread < self.profit_target_spread {
-                    // Spread normalized - mean reversion complete
-                    Some(ExitReason::MeanReversion)
-                } else if current_spread > position.entry_spread + self.stop_loss_spread {
-                    // Spread widened - stop loss
-                    Some(ExitReason::StopLoss)
-                } else if hold_time > self.max_hold_time {
-                    // Position timeout
-                    Some(ExitReason::Timeout)
-                } else {
-                    None
-                };
-
-                if let Some(reason) = exit_reason {
-                    // Calculate PnL
-                    let gross_pnl = match position.side {
-                        Side::Buy => (current_price - position.entry_price) * position.size,
-                        Side::Sell => (position.entry_price - current_price) * position.size,
-                    };
-                    let fees = position.size * current_price * fee_rate;
-                    let net_pnl = gross_pnl - fees;
-
-                    let exit_result = ExitResult {
-                        position: position.clone(),
-                        exit_price: current_price,
-                        exit_time: current_time,
-                        reason: reason.clone(),
-                        pnl: net_pnl,
-                        fees,
-                    };
-
-                    println!(
-                        "📉 [Position] Closed: {} | Reason: {:?} | PnL: ${:.4}",
-                        token_id, reason, net_pnl
-                    );
-
-                    exits.push(exit_result);
-                    to_remove.push(token_id.clone());
+            // Find current market state and the price of the held token
+            let Some((market, current_price)) = markets
+                .iter()
+                .find(|m| m.id == position.market_id)
+                .and_then(|m| Some((m, m.token_price(&position.token_id)?)))
+            else {
+                continue;
+            };
+            let current_spread = market.get_spread();
+
+            let hold_time = current_time.saturating_sub(position.entry_time);
+            let limits = self
+                .market_exits
+                .get(&position.market_id)
+                .copied()
+                .unwrap_or(self.exits);
+
+            // Ratchet the tightest spread seen for the trailing stop
+            let best_spread = self
+                .best_spreads
+                .entry(token_id.clone())
+                .or_insert(position.entry_spread);
+            *best_spread = best_spread.min(current_spread);
+            let trailing_triggered = self.trailing_stop.is_some_and(|ts| {
+                position.entry_spread - *best_spread >= ts.activation
+                    && current_spread > *best_spread + ts.distance
+            });
+
+            // Check exit conditions
+            let profit_taken = self.break_even_buffer.map(|buffer| {
+                let target = position.break_even_price(fee_rate, buffer);
+                match position.side {
+                    Side::Buy => current_price >= target,
+                    Side::Sell => current_price <= target,
                 }
-            }
-        }
+            });
+            let exit_reason = if profit_taken == Some(true) {
+                // Price covers entry, both fees, and the buffer
+                Some(ExitReason::ProfitTarget)
+            } else if profit_taken.is_none() && current_spread < limits.profit_target_spread {
+                // Spread normalized - mean reversion complete
+                Some(ExitReason::MeanReversion)
+            } else if trailing_triggered {
+                // Spread narrowed then re-widened - lock in gains
+                Some(ExitReason::TrailingStop)
+            } else if current_spread > position.entry_spread + limits.stop_loss_spread {
+                // Spread widened - stop loss
+                Some(ExitReason::StopLoss)
+            } else if hold_time > limits.max_hold_time {
+                // Position timeout
+                Some(ExitReason::Timeout)
+            } else {
+                None
+            };
 
-        // Remove closed positions
-        for token_id in to_remove {
-            if let Some(pos) = self.positions.remove(&token_id) {
-                // Already added to exits above
-                let _ = pos;
-            }
-        }
+            // Partial close once the spread is halfway back to the profit target
+            let half_reversion = (position.entry_spread + limits.profit_target_spread) / 2.0;
+            let scale_out = match self.scale_out_fraction {
+                Some(fraction)
+                    if exit_reason.is_none()
+                        && current_spread <= half_reversion
+                        && !self.scaled_out.contains(token_id) =>
+                {
+                    Some(position.size * fraction)
+                }
+                _ => None,
+            };
 
-        // Add to history
-        self.history.extend(exits.clone());
+            let (size, reason) = match (exit_reason, scale_out) {
+                (Some(reason), _) => (position.size, reason),
+                (None, Some(size)) => (size, ExitReason::ScaleOut),
+                (None, None) => continue,
+            };
+            due.push(DueExit {
+                market_id: position.market_id.clone(),
+                token_id: token_id.clone(),
+                side: position.side,
+                size,
+                price: current_price,
+                reason,
+            });
+        }
 
-        exits
+        due
     }
 
-    /// Force close a position
-    #[allow(dead_code)]
-    pub fn close_position(
+    /// Close `size` of a position at `exit_price`
+    ///
+    /// Closing the whole size removes the position; a scale-out leaves the
+    /// rest open and is not repeated. Returns None if the token isn't held.
+    pub fn fill_exit(
         &mut self,
-        token_id: &str,
+        token_id: &TokenId,
+        size: f64,
         exit_price: f64,
+        current_time: u64,
+        reason: ExitReason,
         fee_rate: f64,
     ) -> Option<ExitResult> {
-        if let Some(position) = self.positions.remove(token_id) {
-            let current_time = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
-
-            let gross_pnl = match position.side {
-                Side::Buy => (exit_price - position.entry_price) * position.size,
-                Side::Sell => (position.entry_price - exit_price) * position.size,
-            };
-            let fees = position.size * exit_price * fee_rate;
-
-            let result = ExitResult {
-                position,
-                exit_price,
-                exit_time: current_time,
-                reason: ExitReason::Manual,
-                pnl: gross_pnl - fees,
-                fees,
-            };
+        let position = self.positions.get_mut(token_id)?;
+        let size = size.min(position.size);
+        let exit = close(position, size, exit_price, current_time, reason, fee_rate);
 
-            self.history.push(result.clone());
-            Some(result)
+        let closed = position.size - size <= f64::EPSILON;
+        if closed {
+            println!(
+                "📉 [Position] Closed: {} | Reason: {:?} | PnL: ${:.4}",
+                token_id, exit.reason, exit.pnl
+            );
+            self.remove(token_id);
         } else {
-            None
+            println!(
+                "📉 [Position] Scaled out {:.2} of {}: {} | PnL: ${:.4}",
+                size, position.size, token_id, exit.pnl
+            );
+            position.size -= size;
+            if matches!(exit.reason, ExitReason::ScaleOut) {
+                self.scaled_out.insert(token_id.clone());
+            }
         }
+
+        self.book(&exit, closed);
+        Some(exit)
+    }
+
+    /// Record an exit, counting the trade once its position is fully closed
+    ///
+    /// A partial close's PnL is carried on the open trade until then.
+    fn book(&mut self, exit: &ExitResult, closed: bool) {
+        let token_id = &exit.position.token_id;
+        if closed {
+            let pnl = exit.pnl + self.partial_pnl.remove(token_id).unwrap_or_default();
+            self.live.record(pnl);
+            self.lifetime.live.record(pnl);
+        } else {
+            *self.partial_pnl.entry(token_id.clone()).or_default() += exit.pnl;
+        }
+        self.history.push(exit.clone());
+    }
+
+    /// Stop tracking a position
+    fn remove(&mut self, token_id: &TokenId) -> Option<Position> {
+        self.best_spreads.remove(token_id);
+        self.scaled_out.remove(token_id);
+        self.positions.remove(token_id)
+    }
+
+    /// Close `sets` of a held YES+NO pair merged back into $1 of USDC each
+    ///
+    /// The dollar is split between the legs in proportion to their entry
+    /// prices, so the pair's combined PnL is `sets * (1 - entry sum)`.
+    pub fn merge_sets(
+        &mut self,
+        token_ids: &[TokenId],
+        sets: f64,
+        current_time: u64,
+    ) -> Vec<ExitResult> {
+        let legs: Vec<Position> = token_ids
+            .iter()
+            .filter_map(|id| self.positions.get(id))
+            .filter(|p| p.side == Side::Buy && p.size >= sets)
+            .cloned()
+            .collect();
+        let entry_sum: f64 = legs.iter().map(|p| p.entry_price).sum();
+        if legs.len() != token_ids.len() || sets <= 0.0 || entry_sum <= 0.0 {
+            return Vec::new();
+        }
+
+        let exits: Vec<ExitResult> = legs
+            .iter()
+            .map(|position| {
+                close(
+                    position,
+                    sets,
+                    position.entry_price / entry_sum,
+                    current_time,
+                    ExitReason::Merged,
+                    0.0,
+                )
+            })
+            .collect();
+
+        for (position, exit) in legs.iter().zip(&exits) {
+            let closed = position.size - sets <= f64::EPSILON;
+            if closed {
+                self.remove(&position.token_id);
+            } else if let Some(pos) = self.positions.get_mut(&position.token_id) {
+                pos.size -= sets;
+            }
+            self.book(exit, closed);
+        }
+        exits
+    }
+
+    /// Close every position in a resolved market at its token's payout
+    pub fn settle(
+        &mut self,
+        market_id: &MarketId,
+        payouts: &HashMap<TokenId, f64>,
+        current_time: u64,
+    ) -> Vec<ExitResult> {
+        let token_ids: Vec<TokenId> = self
+            .positions
+            .values()
+            .filter(|p| p.market_id == *market_id && payouts.contains_key(&p.token_id))
+            .map(|p| p.token_id.clone())
+            .collect();
+
+        let mut exits = Vec::new();
+        for token_id in token_ids {
+            let Some(position) = self.remove(&token_id) else {
+                continue;
+            };
+            let exit = close(
+                &position,
+                position.size,
+                payouts[&token_id],
+                current_time,
+                ExitReason::Resolved,
+                0.0,
+            );
+            self.book(&exit, true);
+            exits.push(exit);
+        }
+        exits
     }
 
     /// Get total PnL from history
-    pub fn total_pnl(&self) -> f64 {
+    pub fn total_pnl(&self) -> Decimal {
         self.history.iter().map(|e| e.pnl).sum()
     }
 
-    /// Get win rate
+    /// Get mark-to-market PnL of open positions at current market prices
+    ///
+    /// Positions whose market or token price is unavailable are skipped.
+    pub fn unrealized_pnl(&self, markets: &[Market]) -> Decimal {
+        self.positions
+            .values()
+            .filter_map(|position| {
+                let market = markets.iter().find(|m| m.id == position.market_id)?;
+                let price = market.token_price(&position.token_id)?;
+                Some(gross_pnl(position, position.size, price))
+            })
+            .sum()
+    }
+
+    /// Directional exposure with offsetting YES/NO holdings netted out
+    pub fn exposure(&self, markets: &[Market]) -> ExposureReport {
+        net_exposure(self.positions.values(), markets)
+    }
+
+    /// Get win rate of trades closed this session
     pub fn win_rate(&self) -> f64 {
-        if self.history.is_empty() {
-            return 0.0;
-        }
-        let wins = self.history.iter().filter(|e| e.pnl > 0.0).count();
-        wins as f64 / self.history.len() as f64
+        self.live.win_rate()
+    }
+
+    /// PnL of the last `n` closed trades, oldest first
+    pub fn recent_pnls(&self, n: usize) -> Vec<Decimal> {
+        let start = self.history.len().saturating_sub(n);
+        self.history[start..].iter().map(|e| e.pnl).collect()
     }
 
-    /// Get trade count
+    /// Get the number of trades closed this session
     pub fn trade_count(&self) -> usize {
-        self.history.len()
+        self.live.trades
     }
 
     /// Record a simulated trade (for demo mode only)
-    pub fn record_simulated_trade(&mut self, pnl: f64) {
-        // Create a dummy exit result for stats tracking
-        let dummy = ExitResult {
-            position: Position {
-                market_id: "demo".to_string(),
-                token_id: "demo".to_string(),
-                side: crate::types::Side::Buy,
-                size: 5.0,
-                entry_price: 0.5,
-                entry_time: 0,
-                entry_spread: 0.01,
-            },
-            exit_price: 0.5,
-            exit_time: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-            reason: ExitReason::MeanReversion,
-            pnl,
-            fees: 0.0,
-        };
-        self.history.push(dummy);
+    ///
+    /// Demo trades go to their own bucket so they never affect live win rate or PnL.
+    pub fn record_simulated_trade(&mut self, pnl: Decimal) {
+        self.demo.record(pnl);
+        self.lifetime.demo.record(pnl);
+    }
+
+    /// Get this session's simulated demo trade stats
+    pub fn demo_stats(&self) -> &TradeStats {
+        &self.demo
     }
 }
 
+/// Build the exit result for closing `size` of a position at `exit_price`
+fn close(
+    position: &Position,
+    size: f64,
+    exit_price: f64,
+    exit_time: u64,
+    reason: ExitReason,
+    fee_rate: f64,
+) -> ExitResult {
+    let fees = money::charge(money::from_f64(size * exit_price) * money::from_f64(fee_rate));
+
+    ExitResult {
+        position: Position {
+            size,
+            ..position.clone()
+        },
+        exit_price,
+        exit_time,
+        reason,
+        pnl: gross_pnl(position, size, exit_price) - fees,
+        fees,
+    }
+}
+
+/// Profit on `size` of a position marked at `price`, before fees
+fn gross_pnl(position: &Position, size: f64, price: f64) -> Decimal {
+    let (entry, price) = (
+        money::from_f64(position.entry_price),
+        money::from_f64(price),
+    );
+    let per_share = match position.side {
+        Side::Buy => price - entry,
+        Side::Sell => entry - price,
+    };
+    money::round(per_share * money::from_f64(size))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util;
 
     #[test]
     fn test_position_manager() {
         let mut pm = PositionManager::new(0.01, 0.05, 3600);
 
         let pos = Position {
-            market_id: "m1".to_string(),
-            token_id: "t1".to_string(),
+            market_id: "m1".into(),
+            token_id: "t1".into(),
             side: Side::Buy,
             size: 10.0,
             entry_price: 0.50,
             entry_time: 1000,
             entry_spread: 0.03,
+            entry_executions: vec![],
         };
 
         pm.open_position(pos);
         assert_eq!(pm.get_positions().len(), 1);
     }
+
+    #[test]
+    fn test_market_exit_limits_override_defaults() {
+        let mut pm = PositionManager::new(0.005, 0.05, 3600);
+        let tight = ExitLimits {
+            stop_loss_spread: 0.01,
+            max_hold_time: 60,
+            ..pm.exit_limits()
+        };
+        pm.set_market_exits(&"m1".into(), tight);
+        for (market_id, token_id) in [("m1", "t1"), ("m2", "t3")] {
+            pm.open_position(Position {
+                market_id: market_id.into(),
+                token_id: token_id.into(),
+                side: Side::Buy,
+                size: 10.0,
+                entry_price: 0.45,
+                entry_time: 1000,
+                entry_spread: 0.05,
+                entry_executions: vec![],
+            });
+        }
+        let m2 = Market {
+            id: "m2".into(),
+            ..test_util::fee_market(0.46, 0.47)
+        };
+
+        // 2% wider than entry: only m1's tighter stop fires
+        let exits = pm.check_exits(&[test_util::fee_market(0.46, 0.47), m2.clone()], 1010, 0.0);
+        assert_eq!(exits.len(), 1);
+        assert_eq!(exits[0].position.market_id, MarketId::from("m1"));
+        assert!(matches!(exits[0].reason, ExitReason::StopLoss));
+
+        // Setting the defaults back drops the override
+        pm.set_market_exits(&"m2".into(), pm.exit_limits());
+        assert!(pm.check_exits(&[m2], 1100, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_break_even_exit_covers_round_trip_fees() {
+        let mut pm = PositionManager::new(0.005, 0.5, 3600).with_break_even(0.01);
+        let position = Position {
+            market_id: "m1".into(),
+            token_id: "t1".into(),
+            side: Side::Buy,
+            size: 10.0,
+            entry_price: 0.45,
+            entry_time: 1000,
+            entry_spread: 0.05,
+            entry_executions: vec![],
+        };
+        // (0.45 * 1.02 + 0.01) / 0.98
+        let target = position.break_even_price(0.02, 0.01);
+        assert!((target - 0.47857).abs() < 1e-5);
+        pm.open_position(position);
+
+        // Spread fully reverted, but the price doesn't cover the fees yet
+        let exits = pm.check_exits(&[test_util::fee_market(0.47, 0.53)], 1010, 0.02);
+        assert!(exits.is_empty());
+
+        let exits = pm.check_exits(&[test_util::fee_market(0.48, 0.52)], 1020, 0.02);
+        assert_eq!(exits.len(), 1);
+        assert!(matches!(exits[0].reason, ExitReason::ProfitTarget));
+        // Exit fee paid and the entry fee covered, with the buffer left over
+        assert!(exits[0].pnl - money::usdc(10.0 * 0.45 * 0.02) >= money::usdc(10.0 * 0.01));
+    }
+
+    #[test]
+    fn test_break_even_prices_the_held_token() {
+        let mut pm = PositionManager::new(0.005, 0.5, 3600).with_break_even(0.01);
+        pm.open_position(Position {
+            market_id: "m1".into(),
+            token_id: "t2".into(),
+            side: Side::Buy,
+            size: 10.0,
+            entry_price: 0.45,
+            entry_time: 1000,
+            entry_spread: 0.05,
+            entry_executions: vec![],
+        });
+
+        // YES is past the target but the NO token held is not
+        let exits = pm.check_exits(&[test_util::fee_market(0.55, 0.44)], 1010, 0.02);
+        assert!(exits.is_empty());
+
+        let exits = pm.check_exits(&[test_util::fee_market(0.51, 0.49)], 1020, 0.02);
+        assert_eq!(exits.len(), 1);
+        assert!(matches!(exits[0].reason, ExitReason::ProfitTarget));
+        assert_eq!(exits[0].exit_price, 0.49);
+
+        // A token the market no longer lists is left alone
+        pm.open_position(Position {
+            market_id: "m1".into(),
+            token_id: "t9".into(),
+            side: Side::Buy,
+            size: 10.0,
+            entry_price: 0.45,
+            entry_time: 1000,
+            entry_spread: 0.05,
+            entry_executions: vec![],
+        });
+        assert!(pm
+            .check_exits(&[test_util::fee_market(0.99, 0.99)], 99999, 0.02)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_trailing_stop_locks_in_narrowed_spread() {
+        let mut pm = PositionManager::new(0.005, 0.05, 3600).with_trailing_stop(TrailingStop {
+            activation: 0.02,
+            distance: 0.01,
+        });
+        pm.open_position(Position {
+            market_id: "m1".into(),
+            token_id: "t1".into(),
+            side: Side::Buy,
+            size: 10.0,
+            entry_price: 0.45,
+            entry_time: 1000,
+            entry_spread: 0.05,
+            entry_executions: vec![],
+        });
+
+        // Spread narrows from 5% to 2%: stop is armed but not triggered
+        let exits = pm.check_exits(&[test_util::fee_market(0.49, 0.49)], 1010, 0.0);
+        assert!(exits.is_empty());
+
+        // Spread re-widens to 4%, more than 1% above its best: exit
+        let exits = pm.check_exits(&[test_util::fee_market(0.48, 0.48)], 1020, 0.0);
+        assert_eq!(exits.len(), 1);
+        assert!(matches!(exits[0].reason, ExitReason::TrailingStop));
+    }
+
+    #[test]
+    fn test_due_exit_closes_only_once_filled() {
+        let mut pm = PositionManager::new(0.01, 0.05, 3600);
+        pm.open_position(Position {
+            market_id: "m1".into(),
+            token_id: "t1".into(),
+            side: Side::Buy,
+            size: 10.0,
+            entry_price: 0.45,
+            entry_time: 1000,
+            entry_spread: 0.05,
+            entry_executions: vec![],
+        });
+
+        // Spread reverted below 1%: the exit is due but the position stays open
+        let due = pm.due_exits(&[test_util::fee_market(0.50, 0.495)], 1010, 0.0);
+        assert_eq!(due.len(), 1);
+        assert!(matches!(due[0].reason, ExitReason::MeanReversion));
+        assert_eq!(pm.get_positions().len(), 1);
+        assert_eq!(pm.trade_count(), 0);
+
+        // Booked at the fill's price, not the price the exit came due at
+        let exit = pm
+            .fill_exit(&"t1".into(), 10.0, 0.48, 1020, due[0].reason.clone(), 0.0)
+            .unwrap();
+        assert_eq!(exit.pnl, money::usdc(0.30));
+        assert!(pm.get_positions().is_empty());
+        assert_eq!(pm.trade_count(), 1);
+    }
+
+    #[test]
+    fn test_scale_out_at_half_reversion() {
+        let mut pm = PositionManager::new(0.01, 0.05, 3600).with_scale_out(0.5);
+        pm.open_position(Position {
+            market_id: "m1".into(),
+            token_id: "t1".into(),
+            side: Side::Buy,
+            size: 10.0,
+            entry_price: 0.45,
+            entry_time: 1000,
+            entry_spread: 0.05,
+            entry_executions: vec![],
+        });
+
+        // Spread 2% is past the 3% half-reversion level: close half
+        let exits = pm.check_exits(&[test_util::fee_market(0.49, 0.49)], 1010, 0.0);
+        assert_eq!(exits.len(), 1);
+        assert!(matches!(exits[0].reason, ExitReason::ScaleOut));
+        assert_eq!(exits[0].position.size, 5.0);
+        assert_eq!(pm.get_position(&"t1".into()).unwrap().size, 5.0);
+        // A partial close is not a trade of its own
+        assert_eq!(pm.trade_count(), 0);
+        assert_eq!(pm.lifetime_stats().live.trades, 0);
+
+        // Only scales out once
+        let exits = pm.check_exits(&[test_util::fee_market(0.49, 0.49)], 1020, 0.0);
+        assert!(exits.is_empty());
+
+        // Remainder exits on full mean reversion
+        let exits = pm.check_exits(&[test_util::fee_market(0.50, 0.50)], 1030, 0.0);
+        assert_eq!(exits.len(), 1);
+        assert!(matches!(exits[0].reason, ExitReason::MeanReversion));
+        assert_eq!(exits[0].position.size, 5.0);
+        assert!(pm.get_positions().is_empty());
+
+        // One trade, carrying both closes' PnL: 5 * 0.04 + 5 * 0.05
+        let live = &pm.lifetime_stats().live;
+        assert_eq!(pm.trade_count(), 1);
+        assert_eq!((live.trades, live.wins), (1, 1));
+        assert_eq!(live.total_pnl, money::usdc(0.45));
+        assert_eq!(pm.total_pnl(), money::usdc(0.45));
+    }
+
+    fn create_test_position(size: f64, entry_price: f64, entry_time: u64) -> Position {
+        Position {
+            market_id: "m1".into(),
+            token_id: "t1".into(),
+            side: Side::Buy,
+            size,
+            entry_price,
+            entry_time,
+            entry_spread: 0.04,
+            entry_executions: vec![],
+        }
+    }
+
+    #[test]
+    fn test_duplicate_entry_merges_average_price() {
+        let mut pm = PositionManager::new(0.01, 0.05, 3600);
+
+        assert!(pm.open_position(Position {
+            entry_executions: vec!["exec-a".to_string()],
+            ..create_test_position(10.0, 0.40, 1000)
+        }));
+        assert!(pm.open_position(Position {
+            entry_executions: vec!["exec-b".to_string()],
+            ..create_test_position(30.0, 0.48, 2000)
+        }));
+
+        let pos = pm.get_position(&"t1".into()).unwrap();
+        assert_eq!(pm.get_positions().len(), 1);
+        assert_eq!(pos.size, 40.0);
+        assert!((pos.entry_price - 0.46).abs() < 1e-9);
+        assert_eq!(pos.entry_time, 1000);
+        assert_eq!(pos.entry_executions, vec!["exec-a", "exec-b"]);
+    }
+
+    #[test]
+    fn test_duplicate_entry_rejected() {
+        let mut pm = PositionManager::new(0.01, 0.05, 3600)
+            .with_duplicate_entry(DuplicateEntryPolicy::Reject);
+
+        assert!(pm.accepts_entry(&"t1".into()));
+        assert!(pm.open_position(create_test_position(10.0, 0.40, 1000)));
+        assert!(!pm.accepts_entry(&"t1".into()));
+        assert!(!pm.open_position(create_test_position(30.0, 0.48, 2000)));
+
+        let pos = pm.get_position(&"t1".into()).unwrap();
+        assert_eq!(pos.size, 10.0);
+        assert_eq!(pos.entry_price, 0.40);
+
+        // A later fill of the same order is not a new entry
+        let fill = |size| Position {
+            entry_executions: vec!["exec-a".to_string()],
+            ..create_test_position(size, 0.40, 3000)
+        };
+        let mut pm = PositionManager::new(0.01, 0.05, 3600)
+            .with_duplicate_entry(DuplicateEntryPolicy::Reject);
+        assert!(pm.open_position(fill(4.0)));
+        assert!(pm.open_position(fill(6.0)));
+        let pos = pm.get_position(&"t1".into()).unwrap();
+        assert_eq!(pos.size, 10.0);
+        assert_eq!(pos.entry_executions, vec!["exec-a"]);
+    }
+
+    #[test]
+    fn test_correct_size() {
+        let mut pm = PositionManager::new(0.01, 0.05, 3600);
+        pm.open_position(create_test_position(10.0, 0.40, 1000));
+
+        pm.correct_size(&"t1".into(), 7.5);
+        assert_eq!(pm.get_position(&"t1".into()).unwrap().size, 7.5);
+
+        pm.correct_size(&"t1".into(), 0.0);
+        assert!(pm.get_position(&"t1".into()).is_none());
+    }
+
+    #[test]
+    fn test_unrealized_pnl_marks_open_positions() {
+        let mut pm = PositionManager::new(0.01, 0.05, 3600);
+        pm.open_position(create_test_position(10.0, 0.40, 1000));
+        pm.open_position(Position {
+            token_id: "t2".into(),
+            ..create_test_position(20.0, 0.50, 1000)
+        });
+
+        // t1 marked at 0.45 (+0.50), t2 at 0.48 (-0.40)
+        let markets = [test_util::fee_market(0.45, 0.48)];
+        assert_eq!(pm.unrealized_pnl(&markets), money::usdc(0.10));
+        assert_eq!(pm.total_pnl(), Decimal::ZERO);
+
+        // Unknown market contributes nothing
+        assert_eq!(pm.unrealized_pnl(&[]), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_lifetime_stats_accumulate_across_runs() {
+        let mut previous = LifetimeStats::default();
+        previous.live.record(money::usdc(1.0));
+        previous.live.record(money::usdc(-0.5));
+
+        let mut pm = PositionManager::new(0.01, 0.05, 3600).with_lifetime_stats(previous);
+        pm.open_position(Position {
+            market_id: "m1".into(),
+            token_id: "t1".into(),
+            side: Side::Buy,
+            size: 10.0,
+            entry_price: 0.50,
+            entry_time: 1000,
+            entry_spread: 0.03,
+            entry_executions: vec![],
+        });
+        pm.fill_exit(
+            &"t1".into(),
+            10.0,
+            0.60,
+            2000,
+            ExitReason::ProfitTarget,
+            0.0,
+        );
+        pm.record_simulated_trade(money::usdc(0.25));
+
+        // Session covers only this run, lifetime includes the previous one
+        assert_eq!(pm.trade_count(), 1);
+        assert_eq!(pm.demo_stats().trades, 1);
+        let lifetime = pm.lifetime_stats();
+        assert_eq!(lifetime.live.trades, 3);
+        assert_eq!(lifetime.live.wins, 2);
+        assert_eq!(lifetime.live.total_pnl, money::usdc(1.5));
+        assert_eq!(lifetime.demo.trades, 1);
+    }
+
+    #[test]
+    fn test_demo_trades_kept_out_of_live_stats() {
+        let mut pm = PositionManager::new(0.01, 0.05, 3600);
+        pm.record_simulated_trade(money::usdc(-1.0));
+        pm.record_simulated_trade(money::usdc(0.5));
+
+        assert_eq!(pm.trade_count(), 0);
+        assert_eq!(pm.total_pnl(), Decimal::ZERO);
+        assert_eq!(pm.win_rate(), 0.0);
+        assert_eq!(pm.demo_stats().trades, 2);
+        assert_eq!(pm.demo_stats().total_pnl, money::usdc(-0.5));
+    }
+
+    #[test]
+    fn test_merge_sets_realizes_bundle_edge() {
+        let mut pm = PositionManager::new(0.01, 0.05, 3600);
+        for (token_id, entry_price) in [("t1", 0.45), ("t2", 0.50)] {
+            pm.open_position(Position {
+                market_id: "m1".into(),
+                token_id: token_id.into(),
+                side: Side::Buy,
+                size: 10.0,
+                entry_price,
+                entry_time: 1000,
+                entry_spread: 0.05,
+                entry_executions: vec![],
+            });
+        }
+        let token_ids = vec![TokenId::from("t1"), TokenId::from("t2")];
+
+        // Can't merge more sets than either leg holds
+        assert!(pm.merge_sets(&token_ids, 12.0, 1010).is_empty());
+
+        let exits = pm.merge_sets(&token_ids, 6.0, 1010);
+        assert_eq!(exits.len(), 2);
+        assert!(exits.iter().all(|e| matches!(e.reason, ExitReason::Merged)));
+        let pnl: Decimal = exits.iter().map(|e| e.pnl).sum();
+        assert_eq!(pnl, money::usdc(6.0 * 0.05));
+        assert_eq!(pm.get_position(&"t1".into()).unwrap().size, 4.0);
+
+        pm.merge_sets(&token_ids, 4.0, 1020);
+        assert!(pm.get_positions().is_empty());
+    }
+
+    #[test]
+    fn test_settle_pays_out_resolved_market() {
+        let mut pm = PositionManager::new(0.01, 0.05, 3600);
+        for (market_id, token_id) in [("m1", "t1"), ("m1", "t2"), ("m2", "t3")] {
+            pm.open_position(Position {
+                market_id: market_id.into(),
+                token_id: token_id.into(),
+                side: Side::Buy,
+                size: 10.0,
+                entry_price: 0.40,
+                entry_time: 1000,
+                entry_spread: 0.05,
+                entry_executions: vec![],
+            });
+        }
+
+        let payouts = HashMap::from([("t1".into(), 1.0), ("t2".into(), 0.0)]);
+        let exits = pm.settle(&"m1".into(), &payouts, 2000);
+        assert_eq!(exits.len(), 2);
+        assert!(exits
+            .iter()
+            .all(|e| matches!(e.reason, ExitReason::Resolved)));
+        let pnl: Decimal = exits.iter().map(|e| e.pnl).sum();
+        // Winner pays 10 * 0.60, loser costs 10 * 0.40
+        assert_eq!(pnl, money::usdc(2.0));
+        assert_eq!(pm.held_markets(), HashSet::from(["m2".into()]));
+    }
 }
@@ -0,0 +1,506 @@
+//! Pre-trade checks
+//!
+//! The limits a trade must clear right before it is placed, in one place:
+//! allowance, net and correlated exposure, slippage from the signal's price, signal age,
+//! the market's tick and minimum size, and market cooldowns. A check either
+//! accepts the trade at the size it may go ahead at (the slippage limit can
+//! cut it down) or rejects it with every reason it failed.
+
+use crate::config::Config;
+use crate::money::{self, Decimal};
+use crate::order_spec::{OrderSpec, OrderSpecError};
+use crate::skips::SkipReason;
+use crate::types::{OrderBook, Side, TokenId};
+use std::time::Duration;
+
+/// A limit a trade failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rejection {
+    /// Costs more than the allowance left
+    Allowance {
+        required: Decimal,
+        remaining: Decimal,
+    },
+    /// Would push net exposure over the cap
+    Exposure { projected: f64, max: f64 },
+    /// Would push exposure across correlated markets over the cap
+    CorrelatedExposure { projected: f64, max: f64 },
+    /// Not even the best level is within the slippage limit any more
+    Slippage {
+        token_id: TokenId,
+        signal_price: f64,
+        max_bps: f64,
+    },
+    /// Too old for its edge
+    Stale { age_ms: u64, edge: f64 },
+    /// A leg the market would not accept
+    OrderSpec {
+        token_id: TokenId,
+        error: OrderSpecError,
+    },
+    /// The market is held out of trading for this long
+    Cooldown { secs: u64 },
+}
+
+impl Rejection {
+    /// Skip reason the rejection is counted under
+    pub fn skip_reason(&self) -> SkipReason {
+        match self {
+            Self::Allowance { .. } => SkipReason::InsufficientAllowance,
+            Self::Exposure { .. } | Self::CorrelatedExposure { .. } => SkipReason::ExposureCap,
+            Self::Slippage { .. } => SkipReason::Slippage,
+            Self::Stale { .. } => SkipReason::StaleSignal,
+            Self::OrderSpec { .. } => SkipReason::NoLiquidity,
+            Self::Cooldown { .. } => SkipReason::Suspended,
+        }
+    }
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Allowance {
+                required,
+                remaining,
+            } => write!(
+                f,
+                "costs ${:.2} with ${:.2} of the allowance left",
+                required, remaining
+            ),
+            Self::Exposure { projected, max } => {
+                write!(
+                    f,
+                    "net exposure ${:.2} would exceed ${:.2} cap",
+                    projected, max
+                )
+            }
+            Self::CorrelatedExposure { projected, max } => write!(
+                f,
+                "exposure ${:.2} across correlated markets would exceed ${:.2} cap",
+                projected, max
+            ),
+            Self::Slippage {
+                token_id,
+                signal_price,
+                max_bps,
+            } => write!(
+                f,
+                "{} moved more than {}bps from {:.4} since the signal",
+                token_id, max_bps, signal_price
+            ),
+            Self::Stale { age_ms, edge } => {
+                write!(
+                    f,
+                    "signal is {}ms old with edge {:.2}%",
+                    age_ms,
+                    edge * 100.0
+                )
+            }
+            Self::OrderSpec { token_id, error } => write!(f, "{}: {}", token_id, error),
+            Self::Cooldown { secs } => write!(f, "market held out of trading for {}s", secs),
+        }
+    }
+}
+
+/// How a signal's age bears on executing it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SignalAge {
+    /// Within the age limit (or the limit is off)
+    Fresh,
+    /// Too old, but the edge is large enough to survive the wait
+    HighEdge,
+    /// Too old for its edge
+    Stale,
+}
+
+/// One leg as it would be placed
+#[derive(Debug, Clone, Copy)]
+pub struct PreTradeLeg<'a> {
+    pub token_id: &'a TokenId,
+    /// Freshest book for the leg
+    pub book: &'a OrderBook,
+    pub size: f64,
+    /// Average price walking the book the signal was detected on
+    pub signal_price: f64,
+}
+
+/// Everything known about a trade about to be placed
+#[derive(Debug, Clone)]
+pub struct PreTrade<'a> {
+    pub side: Side,
+    pub legs: Vec<PreTradeLeg<'a>>,
+    /// The market's tick and minimum size
+    pub spec: OrderSpec,
+    pub edge: f64,
+    /// Time since the signal was detected
+    pub age: Duration,
+    /// Allowance left, when the trade spends allowance
+    pub remaining_allowance: Option<Decimal>,
+    /// Net exposure now and with the trade, when capped
+    pub exposure: Option<(f64, f64)>,
+    /// Exposure across the market and those correlated with it, now and
+    /// with the trade, when capped
+    pub correlated_exposure: Option<(f64, f64)>,
+    /// Time left on a market cooldown
+    pub cooldown: Option<Duration>,
+}
+
+/// Outcome of the pre-trade checks
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreTradeDecision {
+    /// Go ahead at this size per leg
+    Accept {
+        size: f64,
+    },
+    Reject(Vec<Rejection>),
+}
+
+/// The configured pre-trade limits
+#[derive(Debug, Clone, Default)]
+pub struct PreTradeChecks {
+    /// Worst walked price accepted, in basis points from the signal's (0 = no limit)
+    pub max_slippage_bps: f64,
+    /// Cap on net directional exposure in USDC (0 = no cap)
+    pub max_position_value: f64,
+    /// Cap on net exposure across correlated markets in USDC (0 = no cap)
+    pub max_correlated_exposure: f64,
+    /// Age past which only high-edge signals execute (zero = no limit)
+    pub max_signal_age: Duration,
+    pub stale_signal_min_edge: f64,
+}
+
+impl PreTradeChecks {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_slippage_bps: config.trading.max_slippage_bps,
+            max_position_value: config.trading.max_position_value,
+            max_correlated_exposure: config.correlation.max_exposure,
+            max_signal_age: Duration::from_millis(config.safety.max_signal_age_ms),
+            stale_signal_min_edge: config.safety.stale_signal_min_edge,
+        }
+    }
+
+    /// Run every check, collecting each reason the trade fails
+    pub fn check(&self, trade: &PreTrade) -> PreTradeDecision {
+        let mut rejections = Vec::new();
+        if let Some(left) = trade.cooldown.filter(|left| !left.is_zero()) {
+            rejections.push(Rejection::Cooldown {
+                secs: left.as_secs().max(1),
+            });
+        }
+        if self.signal_age(trade.age, trade.edge) == SignalAge::Stale {
+            rejections.push(Rejection::Stale {
+                age_ms: trade.age.as_millis() as u64,
+                edge: trade.edge,
+            });
+        }
+        if let Some((current, projected)) = trade.exposure {
+            if let Err(rejection) = self.exposure(current, projected) {
+                rejections.push(rejection);
+            }
+        }
+        if let Some((current, projected)) = trade.correlated_exposure {
+            if let Err(rejection) = self.correlated_exposure(current, projected) {
+                rejections.push(rejection);
+            }
+        }
+
+        // Every leg trades the size all of them fill within the slippage
+        // limit, so bundles stay balanced
+        let mut size = trade
+            .legs
+            .iter()
+            .map(|leg| leg.size)
+            .fold(f64::INFINITY, f64::min);
+        for leg in &trade.legs {
+            match self.slippage_limited_size(leg.book, leg.size, trade.side, leg.signal_price) {
+                Some(limited) => size = size.min(limited),
+                None => rejections.push(Rejection::Slippage {
+                    token_id: leg.token_id.clone(),
+                    signal_price: leg.signal_price,
+                    max_bps: self.max_slippage_bps,
+                }),
+            }
+        }
+        // No size to check the rest at
+        if !size.is_finite()
+            || rejections
+                .iter()
+                .any(|r| matches!(r, Rejection::Slippage { .. }))
+        {
+            return PreTradeDecision::Reject(rejections);
+        }
+
+        let size = trade.spec.round_size(size);
+        let mut cost = Decimal::ZERO;
+        for leg in &trade.legs {
+            let price = leg
+                .book
+                .execution_price(size, trade.side)
+                .unwrap_or(leg.signal_price);
+            if let Err(error) = trade.spec.conform(price, size, trade.side) {
+                rejections.push(Rejection::OrderSpec {
+                    token_id: leg.token_id.clone(),
+                    error,
+                });
+            }
+            cost += money::charge(money::from_f64(size) * money::from_f64(price));
+        }
+        if let Some(remaining) = trade.remaining_allowance {
+            if let Err(rejection) = self.allowance(cost, remaining) {
+                rejections.push(rejection);
+            }
+        }
+
+        if rejections.is_empty() {
+            PreTradeDecision::Accept { size }
+        } else {
+            PreTradeDecision::Reject(rejections)
+        }
+    }
+
+    /// Whether `required` fits in the allowance left
+    pub fn allowance(&self, required: Decimal, remaining: Decimal) -> Result<(), Rejection> {
+        if required > remaining {
+            return Err(Rejection::Allowance {
+                required,
+                remaining,
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether net exposure may move from `current` to `projected`
+    ///
+    /// Trades that reduce exposure are always allowed.
+    pub fn exposure(&self, current: f64, projected: f64) -> Result<(), Rejection> {
+        let max = self.max_position_value;
+        if max > 0.0 && projected > max && projected > current {
+            return Err(Rejection::Exposure { projected, max });
+        }
+        Ok(())
+    }
+
+    /// Whether exposure across correlated markets may move from `current`
+    /// to `projected`, on the same terms as `exposure`
+    pub fn correlated_exposure(&self, current: f64, projected: f64) -> Result<(), Rejection> {
+        let max = self.max_correlated_exposure;
+        if max > 0.0 && projected > max && projected > current {
+            return Err(Rejection::CorrelatedExposure { projected, max });
+        }
+        Ok(())
+    }
+
+    /// Whether a signal this old is still worth executing
+    pub fn signal_age(&self, age: Duration, edge: f64) -> SignalAge {
+        if self.max_signal_age.is_zero() || age <= self.max_signal_age {
+            SignalAge::Fresh
+        } else if edge >= self.stale_signal_min_edge {
+            SignalAge::HighEdge
+        } else {
+            SignalAge::Stale
+        }
+    }
+
+    /// Largest size, up to `size`, whose walked price stays within
+    /// `max_slippage_bps` of the price the signal was detected at
+    ///
+    /// The full size when it fits (or there is no limit), less when only
+    /// part of the book is in bounds, None when not even the best level is.
+    pub fn slippage_limited_size(
+        &self,
+        book: &OrderBook,
+        size: f64,
+        side: Side,
+        signal_price: f64,
+    ) -> Option<f64> {
+        if self.max_slippage_bps <= 0.0 {
+            return Some(size);
+        }
+        let tolerance = self.max_slippage_bps / 10_000.0;
+        let (levels, limit) = match side {
+            Side::Buy => (&book.asks, signal_price * (1.0 + tolerance)),
+            Side::Sell => (&book.bids, signal_price * (1.0 - tolerance)),
+        };
+
+        // The average only worsens level by level: take whole levels while
+        // they are in bounds, then as much of the next as keeps the average there
+        let (mut filled, mut cost) = (0.0, 0.0);
+        for level in levels {
+            let remaining = size - filled;
+            if remaining <= 0.0 {
+                break;
+            }
+            let in_bounds = match side {
+                Side::Buy => level.price <= limit,
+                Side::Sell => level.price >= limit,
+            };
+            let take = if in_bounds {
+                remaining.min(level.size)
+            } else {
+                ((limit * filled - cost) / (level.price - limit))
+                    .max(0.0)
+                    .min(remaining.min(level.size))
+            };
+            filled += take;
+            cost += take * level.price;
+            if !in_bounds {
+                break;
+            }
+        }
+        (filled > 0.0).then_some(filled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::usdc;
+    use crate::types::PriceLevel;
+
+    fn checks() -> PreTradeChecks {
+        PreTradeChecks {
+            max_slippage_bps: 200.0,
+            max_position_value: 50.0,
+            max_correlated_exposure: 80.0,
+            max_signal_age: Duration::from_millis(500),
+            stale_signal_min_edge: 0.05,
+        }
+    }
+
+    fn book() -> OrderBook {
+        OrderBook {
+            token_id: "t1".into(),
+            bids: vec![],
+            asks: vec![
+                PriceLevel {
+                    price: 0.50,
+                    size: 50.0,
+                },
+                PriceLevel {
+                    price: 0.53,
+                    size: 100.0,
+                },
+            ],
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_slippage_limit_caps_size() {
+        let checks = checks();
+        let book = book();
+
+        // Within 2% of 0.50 (0.51): all of the first level, then 25 more at 0.53
+        assert_eq!(
+            checks.slippage_limited_size(&book, 20.0, Side::Buy, 0.50),
+            Some(20.0)
+        );
+        let capped = checks
+            .slippage_limited_size(&book, 120.0, Side::Buy, 0.50)
+            .unwrap();
+        assert!((capped - 75.0).abs() < 1e-9);
+        assert!(book.execution_price(capped, Side::Buy).unwrap() <= 0.51 + 1e-9);
+
+        // The book moved away since the signal
+        assert_eq!(
+            checks.slippage_limited_size(&book, 20.0, Side::Buy, 0.45),
+            None
+        );
+        // No limit configured
+        assert_eq!(
+            PreTradeChecks::default().slippage_limited_size(&book, 120.0, Side::Buy, 0.45),
+            Some(120.0)
+        );
+    }
+
+    #[test]
+    fn test_signal_age() {
+        let checks = checks();
+        let ms = Duration::from_millis;
+
+        assert_eq!(checks.signal_age(ms(200), 0.01), SignalAge::Fresh);
+        assert_eq!(checks.signal_age(ms(500), 0.01), SignalAge::Fresh);
+        assert_eq!(checks.signal_age(ms(800), 0.01), SignalAge::Stale);
+        assert_eq!(checks.signal_age(ms(800), 0.05), SignalAge::HighEdge);
+
+        let disabled = PreTradeChecks {
+            max_signal_age: Duration::ZERO,
+            ..checks
+        };
+        assert_eq!(disabled.signal_age(ms(60_000), 0.0), SignalAge::Fresh);
+    }
+
+    #[test]
+    fn test_check_accepts_capped_or_lists_every_rejection() {
+        let checks = checks();
+        let book = book();
+        let token: TokenId = "t1".into();
+        let trade = PreTrade {
+            side: Side::Buy,
+            legs: vec![PreTradeLeg {
+                token_id: &token,
+                book: &book,
+                size: 120.0,
+                signal_price: 0.50,
+            }],
+            spec: OrderSpec {
+                tick_size: 0.01,
+                min_size: 5.0,
+            },
+            edge: 0.02,
+            age: Duration::from_millis(100),
+            remaining_allowance: Some(usdc(40.0)),
+            exposure: Some((10.0, 30.0)),
+            correlated_exposure: Some((40.0, 60.0)),
+            cooldown: None,
+        };
+        assert_eq!(
+            checks.check(&trade),
+            PreTradeDecision::Accept { size: 75.0 }
+        );
+
+        let rejected = PreTrade {
+            age: Duration::from_secs(2),
+            remaining_allowance: Some(usdc(20.0)),
+            exposure: Some((10.0, 60.0)),
+            correlated_exposure: Some((60.0, 90.0)),
+            cooldown: Some(Duration::from_secs(30)),
+            ..trade.clone()
+        };
+        let PreTradeDecision::Reject(rejections) = checks.check(&rejected) else {
+            panic!("expected a rejection");
+        };
+        let reasons: Vec<SkipReason> = rejections.iter().map(Rejection::skip_reason).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                SkipReason::Suspended,
+                SkipReason::StaleSignal,
+                SkipReason::ExposureCap,
+                SkipReason::ExposureCap,
+                SkipReason::InsufficientAllowance
+            ]
+        );
+
+        // Sized below the market minimum by the slippage limit
+        let thin = PreTrade {
+            spec: OrderSpec {
+                tick_size: 0.01,
+                min_size: 100.0,
+            },
+            ..trade
+        };
+        assert!(matches!(
+            checks.check(&thin),
+            PreTradeDecision::Reject(r) if matches!(r[..], [Rejection::OrderSpec { .. }])
+        ));
+        assert_eq!(
+            checks.allowance(usdc(5.0), usdc(4.99)),
+            Err(Rejection::Allowance {
+                required: usdc(5.0),
+                remaining: usdc(4.99)
+            })
+        );
+    }
+}
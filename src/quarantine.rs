@@ -0,0 +1,232 @@
+//! Per-token book failure backoff
+//!
+//! A token whose book requests keep failing is not retried every tick.
+//! Each consecutive failure doubles the wait before the next request, up to
+//! a ceiling; past a failure threshold the token is reported as quarantined
+//! on the API. The first successful fetch clears its record. A token whose
+//! market stops accepting orders is quarantined outright, and stays so until
+//! discovery stops listing the market.
+
+use crate::config::QuarantineConfig;
+use crate::types::TokenId;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+struct FailureRecord {
+    failures: u32,
+    retry_at: Instant,
+    last_error: String,
+    /// Market closed: held regardless of `retry_at` until delisted
+    closed: bool,
+}
+
+/// A token currently held back after repeated failures
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuarantinedToken {
+    pub token_id: TokenId,
+    pub failures: u32,
+    pub retry_in_secs: u64,
+    pub last_error: String,
+    /// Held until discovery drops the market, not retried
+    pub closed: bool,
+}
+
+/// Failure counts per token, shared by the workers and the API
+#[derive(Debug)]
+pub struct TokenQuarantine {
+    records: Mutex<HashMap<TokenId, FailureRecord>>,
+    base_backoff: Duration,
+    max_backoff: Duration,
+    quarantine_after: u32,
+}
+
+impl TokenQuarantine {
+    pub fn new(config: &QuarantineConfig) -> Self {
+        let base_backoff = Duration::from_secs(config.base_backoff_secs);
+        Self {
+            records: Mutex::new(HashMap::new()),
+            base_backoff,
+            max_backoff: Duration::from_secs(config.max_backoff_secs).max(base_backoff),
+            quarantine_after: config.quarantine_after.max(1),
+        }
+    }
+
+    /// Whether a token's book may be requested now
+    pub fn allows(&self, token_id: &TokenId, now: Instant) -> bool {
+        self.records
+            .lock()
+            .unwrap()
+            .get(token_id)
+            .is_none_or(|record| !record.closed && now >= record.retry_at)
+    }
+
+    /// Whether a token's market has closed (it is not traded, even from streamed books)
+    pub fn is_closed(&self, token_id: &TokenId) -> bool {
+        self.records
+            .lock()
+            .unwrap()
+            .get(token_id)
+            .is_some_and(|record| record.closed)
+    }
+
+    /// Count a failed request, returning the wait before the next one
+    pub fn record_failure(&self, token_id: &TokenId, error: &str, now: Instant) -> Duration {
+        let mut records = self.records.lock().unwrap();
+        let record = records
+            .entry(token_id.clone())
+            .or_insert_with(|| FailureRecord {
+                failures: 0,
+                retry_at: now,
+                last_error: String::new(),
+                closed: false,
+            });
+        record.failures += 1;
+        let backoff = self
+            .base_backoff
+            .saturating_mul(2u32.saturating_pow(record.failures - 1))
+            .min(self.max_backoff);
+        record.retry_at = now + backoff;
+        record.last_error = error.to_string();
+        if record.failures == self.quarantine_after {
+            println!(
+                "🚧 [Quarantine] {} after {} failed book requests: {}",
+                token_id, record.failures, error
+            );
+        }
+        backoff
+    }
+
+    /// Quarantine a token whose market stopped accepting orders
+    ///
+    /// Successful book requests don't release it; `retain_listed` does,
+    /// once discovery no longer lists the market.
+    pub fn close(&self, token_id: &TokenId, reason: &str, now: Instant) {
+        let mut records = self.records.lock().unwrap();
+        let record = records
+            .entry(token_id.clone())
+            .or_insert_with(|| FailureRecord {
+                failures: 0,
+                retry_at: now,
+                last_error: String::new(),
+                closed: false,
+            });
+        record.failures = record.failures.max(self.quarantine_after);
+        record.last_error = reason.to_string();
+        record.closed = true;
+        println!("🚧 [Quarantine] {} until delisted: {}", token_id, reason);
+    }
+
+    /// Clear a token's failures after a successful request
+    pub fn record_success(&self, token_id: &TokenId) {
+        let mut records = self.records.lock().unwrap();
+        if records.get(token_id).is_some_and(|record| record.closed) {
+            return;
+        }
+        if let Some(record) = records.remove(token_id) {
+            if record.failures >= self.quarantine_after {
+                println!("✅ [Quarantine] {} released", token_id);
+            }
+        }
+    }
+
+    /// Forget closed tokens discovery no longer lists
+    pub fn retain_listed<'a>(&self, listed: impl IntoIterator<Item = &'a TokenId>) {
+        let listed: HashSet<&TokenId> = listed.into_iter().collect();
+        self.records.lock().unwrap().retain(|token_id, record| {
+            let keep = !record.closed || listed.contains(token_id);
+            if !keep {
+                println!("✅ [Quarantine] {} delisted", token_id);
+            }
+            keep
+        });
+    }
+
+    /// Tokens past the quarantine threshold, most failures first
+    pub fn quarantined(&self, now: Instant) -> Vec<QuarantinedToken> {
+        let records = self.records.lock().unwrap();
+        let mut tokens: Vec<QuarantinedToken> = records
+            .iter()
+            .filter(|(_, record)| record.failures >= self.quarantine_after)
+            .map(|(token_id, record)| QuarantinedToken {
+                token_id: token_id.clone(),
+                failures: record.failures,
+                retry_in_secs: record.retry_at.saturating_duration_since(now).as_secs(),
+                last_error: record.last_error.clone(),
+                closed: record.closed,
+            })
+            .collect();
+        tokens.sort_by_key(|t| std::cmp::Reverse(t.failures));
+        tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quarantine() -> TokenQuarantine {
+        TokenQuarantine::new(&QuarantineConfig {
+            base_backoff_secs: 5,
+            max_backoff_secs: 30,
+            quarantine_after: 3,
+        })
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let q = quarantine();
+        let token: TokenId = "t1".into();
+        let now = Instant::now();
+
+        let waits: Vec<u64> = (0..5)
+            .map(|_| q.record_failure(&token, "timeout", now).as_secs())
+            .collect();
+        assert_eq!(waits, vec![5, 10, 20, 30, 30]);
+
+        assert!(!q.allows(&token, now + Duration::from_secs(29)));
+        assert!(q.allows(&token, now + Duration::from_secs(30)));
+        assert!(q.allows(&"t2".into(), now));
+    }
+
+    #[test]
+    fn test_quarantine_reported_until_success() {
+        let q = quarantine();
+        let token: TokenId = "t1".into();
+        let now = Instant::now();
+
+        q.record_failure(&token, "timeout", now);
+        q.record_failure(&token, "timeout", now);
+        assert!(q.quarantined(now).is_empty());
+
+        q.record_failure(&token, "502", now);
+        let listed = q.quarantined(now);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].failures, 3);
+        assert_eq!(listed[0].retry_in_secs, 20);
+        assert_eq!(listed[0].last_error, "502");
+
+        q.record_success(&token);
+        assert!(q.quarantined(now).is_empty());
+        assert!(q.allows(&token, now));
+
+        // A closed market is held past any backoff, and through successful fetches
+        q.close(&token, "market closed", now);
+        assert!(q.quarantined(now)[0].closed);
+        assert!(!q.allows(&token, now + Duration::from_secs(3600)));
+        q.record_success(&token);
+        assert!(q.is_closed(&token));
+
+        // Released once discovery stops listing it
+        q.record_failure(&"t2".into(), "timeout", now);
+        q.retain_listed([&token]);
+        assert!(q.is_closed(&token));
+        q.retain_listed([]);
+        assert!(!q.is_closed(&token));
+        assert!(q.allows(&token, now));
+        // Backoff records are not discovery's to clear
+        assert!(!q.allows(&"t2".into(), now));
+    }
+}
@@ -0,0 +1,224 @@
+//! Dry-run trade quotes
+//!
+//! Runs the execution math for buying a market's full bundle at the current
+//! books (book walk, expected fill, fees, gas, slippage, latency and edge
+//! decay) without placing orders or touching the allowance, so the dashboard
+//! can show what trading right now would return.
+
+use crate::execution::{ExecutionEngine, LegQuote};
+use crate::money::{self, Decimal};
+use crate::types::{MarketId, Side, TokenId};
+use crate::workers::WorkerContext;
+use serde::Serialize;
+
+/// Expected outcome of buying every outcome of a market now
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleQuote {
+    pub market_id: MarketId,
+    /// Shares per leg
+    pub size: f64,
+    pub legs: Vec<LegQuote>,
+    /// Complete sets expected to fill, each redeemable for $1
+    pub sets: f64,
+    pub total_cost: Decimal,
+    /// Sets at $1 plus leftover shares at their midpoints, minus cost
+    pub expected_profit: f64,
+    /// Expected submit-to-ack delay
+    pub latency_ms: u64,
+    /// Chance the edge is still there when the orders land
+    pub edge_survival: f64,
+}
+
+impl BundleQuote {
+    pub fn new(
+        market_id: MarketId,
+        size: f64,
+        legs: Vec<LegQuote>,
+        engine: &ExecutionEngine,
+    ) -> Self {
+        let sets = legs
+            .iter()
+            .map(|leg| leg.expected_fill)
+            .fold(f64::INFINITY, f64::min);
+        let sets = if sets.is_finite() { sets } else { 0.0 };
+        let leftover: f64 = legs
+            .iter()
+            .map(|leg| (leg.expected_fill - sets) * leg.midpoint)
+            .sum();
+        let total_cost: Decimal = legs.iter().map(|leg| leg.total_cost).sum();
+        let delay = engine.expected_delay();
+
+        Self {
+            market_id,
+            size,
+            sets,
+            expected_profit: sets + leftover - money::to_f64(total_cost),
+            total_cost,
+            latency_ms: delay.as_millis() as u64,
+            edge_survival: engine.edge_decay.survival(delay),
+            legs,
+        }
+    }
+}
+
+/// Why a market could not be quoted
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuoteError {
+    UnknownMarket(MarketId),
+    NoBook(TokenId),
+    NoLiquidity(TokenId),
+}
+
+impl std::fmt::Display for QuoteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownMarket(id) => write!(f, "unknown market {}", id),
+            Self::NoBook(token_id) => write!(f, "no order book for {}", token_id),
+            Self::NoLiquidity(token_id) => write!(f, "not enough liquidity on {}", token_id),
+        }
+    }
+}
+
+impl std::error::Error for QuoteError {}
+
+/// Quote buying `size` shares of every outcome of a discovered market
+///
+/// Without a size, the configured trade size is used, converted to shares
+/// at current prices when sizing is notional.
+pub async fn quote_bundle(
+    ctx: &WorkerContext,
+    market_id: &MarketId,
+    size: Option<f64>,
+) -> Result<BundleQuote, QuoteError> {
+    let market = ctx
+        .market_cache
+        .read()
+        .await
+        .markets
+        .get(market_id)
+        .cloned()
+        .ok_or_else(|| QuoteError::UnknownMarket(market_id.clone()))?;
+
+    let trade_size = ctx.params.read().await.trade_size;
+    let size = size.unwrap_or_else(|| {
+        let prices: Option<Vec<f64>> = market
+            .clob_token_ids
+            .iter()
+            .map(|token_id| market.token_price(token_id))
+            .collect();
+        prices
+            .and_then(|prices| {
+                ctx.config
+                    .trading
+                    .sizing
+                    .shares_per_leg(trade_size, &prices)
+            })
+            .unwrap_or(trade_size)
+    });
+
+    let mut legs = Vec::new();
+    for token_id in &market.clob_token_ids {
+        let book = ctx
+            .fresh_book(&market.id, token_id)
+            .await
+            .ok_or_else(|| QuoteError::NoBook(token_id.clone()))?;
+        let leg = ctx
+            .execution_engine
+            .quote(&market.id, &book, size, Side::Buy)
+            .ok_or_else(|| QuoteError::NoLiquidity(token_id.clone()))?;
+        legs.push(leg);
+    }
+    Ok(BundleQuote::new(
+        market.id,
+        size,
+        legs,
+        &ctx.execution_engine,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fees::FeeModel;
+    use crate::fills::EdgeDecay;
+    use crate::latency::LatencyModel;
+    use crate::types::{OrderBook, PriceLevel};
+
+    fn book(token_id: &str, bid: f64, ask: f64, depth: f64) -> OrderBook {
+        OrderBook {
+            token_id: token_id.into(),
+            bids: vec![PriceLevel {
+                price: bid,
+                size: depth,
+            }],
+            asks: vec![PriceLevel {
+                price: ask,
+                size: depth,
+            }],
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_bundle_quote_profit_and_latency() {
+        let engine = ExecutionEngine::new(FeeModel::new(0, 0), LatencyModel::new(500, 0.0))
+            .with_edge_decay(EdgeDecay::new(0.0));
+        let legs = vec![
+            engine
+                .quote(
+                    &"m1".into(),
+                    &book("yes", 0.44, 0.46, 100.0),
+                    10.0,
+                    Side::Buy,
+                )
+                .unwrap(),
+            engine
+                .quote(
+                    &"m1".into(),
+                    &book("no", 0.48, 0.50, 100.0),
+                    10.0,
+                    Side::Buy,
+                )
+                .unwrap(),
+        ];
+
+        let quote = BundleQuote::new("m1".into(), 10.0, legs, &engine);
+        assert_eq!(quote.sets, 10.0);
+        assert_eq!(quote.total_cost, money::usdc(9.6));
+        // 10 sets pay $10 for $9.60
+        assert!((quote.expected_profit - 0.4).abs() < 1e-9);
+        assert_eq!(quote.latency_ms, 500);
+        assert_eq!(quote.edge_survival, 1.0);
+    }
+
+    #[test]
+    fn test_unmatched_shares_valued_at_midpoint() {
+        let engine = ExecutionEngine::new(FeeModel::new(0, 0), LatencyModel::new(0, 0.0));
+        let yes = engine
+            .quote(
+                &"m1".into(),
+                &book("yes", 0.44, 0.46, 100.0),
+                10.0,
+                Side::Buy,
+            )
+            .unwrap();
+        // Competitors are expected to leave only 6 NO shares
+        let no = LegQuote {
+            expected_fill: 6.0,
+            total_cost: money::usdc(3.0),
+            ..engine
+                .quote(
+                    &"m1".into(),
+                    &book("no", 0.48, 0.50, 100.0),
+                    10.0,
+                    Side::Buy,
+                )
+                .unwrap()
+        };
+
+        let quote = BundleQuote::new("m1".into(), 10.0, vec![yes, no], &engine);
+        assert_eq!(quote.sets, 6.0);
+        // 6 sets + 4 spare YES at 0.45, for 4.60 + 3.00
+        assert!((quote.expected_profit - (6.0 + 1.8 - 7.6)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,261 @@
+//! Post-trade reconciliation
+//!
+//! Periodically pulls the account's fills from the CLOB API and compares the
+//! net filled size per token with locally tracked positions, flagging (and
+//! optionally correcting) missing fills and size mismatches.
+
+use crate::auth::{AuthError, ClobAuth};
+use crate::positions::PositionManager;
+use crate::types::TokenId;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// A fill as reported by GET /data/trades
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExchangeFill {
+    pub asset_id: TokenId,
+    pub side: String,
+    pub size: String,
+}
+
+/// Paginated trades response
+#[derive(Debug, Deserialize)]
+struct TradesPage {
+    data: Vec<ExchangeFill>,
+    next_cursor: Option<String>,
+}
+
+/// Cursor value the CLOB API returns on the last page
+const END_CURSOR: &str = "LTE=";
+
+/// A mismatch between local state and exchange fills
+#[derive(Debug, Clone, PartialEq)]
+pub enum Discrepancy {
+    /// Exchange reports fills for a token we don't track
+    MissingLocally {
+        token_id: TokenId,
+        exchange_size: f64,
+    },
+    /// We track a position the exchange has no fills for
+    MissingOnExchange { token_id: TokenId, local_size: f64 },
+    /// Both sides know the token but disagree on size
+    SizeMismatch {
+        token_id: TokenId,
+        local_size: f64,
+        exchange_size: f64,
+    },
+}
+
+/// Net filled size per token (buys minus sells)
+pub fn net_fill_sizes(fills: &[ExchangeFill]) -> HashMap<TokenId, f64> {
+    let mut sizes = HashMap::new();
+    for fill in fills {
+        let size = fill.size.parse::<f64>().unwrap_or(0.0);
+        let signed = if fill.side.eq_ignore_ascii_case("SELL") {
+            -size
+        } else {
+            size
+        };
+        *sizes.entry(fill.asset_id.clone()).or_insert(0.0) += signed;
+    }
+    sizes
+}
+
+/// Compare local position sizes with exchange net fill sizes
+pub fn compare(
+    local: &HashMap<TokenId, f64>,
+    exchange: &HashMap<TokenId, f64>,
+    tolerance: f64,
+) -> Vec<Discrepancy> {
+    let tokens: HashSet<&TokenId> = local.keys().chain(exchange.keys()).collect();
+    tokens
+        .into_iter()
+        .filter_map(|token_id| {
+            let local_size = local.get(token_id).copied().unwrap_or(0.0);
+            let exchange_size = exchange.get(token_id).copied().unwrap_or(0.0);
+            if (local_size - exchange_size).abs() <= tolerance {
+                return None;
+            }
+
+            Some(if local_size.abs() <= tolerance {
+                Discrepancy::MissingLocally {
+                    token_id: token_id.clone(),
+                    exchange_size,
+                }
+            } else if exchange_size.abs() <= tolerance {
+                Discrepancy::MissingOnExchange {
+                    token_id: token_id.clone(),
+                    local_size,
+                }
+            } else {
+                Discrepancy::SizeMismatch {
+                    token_id: token_id.clone(),
+                    local_size,
+                    exchange_size,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Reconciles local positions with exchange fills
+#[derive(Debug, Clone)]
+pub struct Reconciler {
+    /// Only fills after this unix timestamp are considered
+    since: u64,
+    /// Size difference treated as a match
+    tolerance: f64,
+    /// Overwrite local sizes with exchange sizes
+    auto_correct: bool,
+}
+
+impl Reconciler {
+    pub fn new(since: u64, tolerance: f64, auto_correct: bool) -> Self {
+        Self {
+            since,
+            tolerance,
+            auto_correct,
+        }
+    }
+
+    /// Fetch all fills for the account since the reconciler started
+    pub async fn fetch_fills(&self, auth: &ClobAuth) -> Result<Vec<ExchangeFill>, AuthError> {
+        let mut fills = Vec::new();
+        let mut cursor = String::new();
+
+        loop {
+            let mut path = format!("/data/trades?after={}", self.since);
+            if !cursor.is_empty() {
+                path.push_str(&format!("&next_cursor={}", cursor));
+            }
+
+            let page: TradesPage = auth
+                .authenticated(reqwest::Method::GET, &path, None)?
+                .send()
+                .await
+                .map_err(|e| AuthError::Http(e.to_string()))?
+                .json()
+                .await
+                .map_err(|e| AuthError::Http(e.to_string()))?;
+            fills.extend(page.data);
+
+            match page.next_cursor {
+                Some(next) if !next.is_empty() && next != END_CURSOR => cursor = next,
+                _ => break,
+            }
+        }
+
+        Ok(fills)
+    }
+
+    /// Run one reconciliation pass
+    pub async fn run(
+        &self,
+        auth: &ClobAuth,
+        position_manager: &RwLock<PositionManager>,
+    ) -> Result<Vec<Discrepancy>, AuthError> {
+        let exchange = net_fill_sizes(&self.fetch_fills(auth).await?);
+
+        let mut pm = position_manager.write().await;
+        let local: HashMap<TokenId, f64> = pm
+            .get_positions()
+            .iter()
+            .map(|p| (p.token_id.clone(), p.size))
+            .collect();
+        let discrepancies = compare(&local, &exchange, self.tolerance);
+
+        for discrepancy in &discrepancies {
+            println!("⚠️ [Reconcile] {:?}", discrepancy);
+            if !self.auto_correct {
+                continue;
+            }
+            match discrepancy {
+                Discrepancy::SizeMismatch {
+                    token_id,
+                    exchange_size,
+                    ..
+                } => pm.correct_size(token_id, *exchange_size),
+                Discrepancy::MissingOnExchange { token_id, .. } => pm.correct_size(token_id, 0.0),
+                // Can't open a position without knowing its market
+                Discrepancy::MissingLocally { .. } => {}
+            }
+        }
+
+        Ok(discrepancies)
+    }
+
+    /// Run reconciliation forever on a fixed interval
+    pub async fn run_periodic(
+        self,
+        auth: Arc<ClobAuth>,
+        position_manager: Arc<RwLock<PositionManager>>,
+        interval: Duration,
+    ) {
+        loop {
+            tokio::time::sleep(interval).await;
+            match self.run(&auth, &position_manager).await {
+                Ok(d) if d.is_empty() => println!("✅ [Reconcile] Positions match exchange fills"),
+                Ok(d) => println!("⚠️ [Reconcile] {} discrepancies found", d.len()),
+                Err(e) => println!("❌ [Reconcile] Failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(asset_id: &str, side: &str, size: &str) -> ExchangeFill {
+        ExchangeFill {
+            asset_id: asset_id.into(),
+            side: side.to_string(),
+            size: size.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_net_fill_sizes() {
+        let fills = vec![
+            fill("t1", "BUY", "10"),
+            fill("t1", "SELL", "4"),
+            fill("t2", "BUY", "5"),
+        ];
+        let sizes = net_fill_sizes(&fills);
+        assert_eq!(sizes.get("t1"), Some(&6.0));
+        assert_eq!(sizes.get("t2"), Some(&5.0));
+    }
+
+    #[test]
+    fn test_compare_flags_each_discrepancy_kind() {
+        let local = HashMap::from([
+            ("ok".into(), 5.0),
+            ("mismatch".into(), 10.0),
+            ("local_only".into(), 3.0),
+        ]);
+        let exchange = HashMap::from([
+            ("ok".into(), 5.005),
+            ("mismatch".into(), 7.5),
+            ("exchange_only".into(), 2.0),
+        ]);
+
+        let discrepancies = compare(&local, &exchange, 0.01);
+        assert_eq!(discrepancies.len(), 3);
+        assert!(discrepancies.contains(&Discrepancy::SizeMismatch {
+            token_id: "mismatch".into(),
+            local_size: 10.0,
+            exchange_size: 7.5,
+        }));
+        assert!(discrepancies.contains(&Discrepancy::MissingOnExchange {
+            token_id: "local_only".into(),
+            local_size: 3.0,
+        }));
+        assert!(discrepancies.contains(&Discrepancy::MissingLocally {
+            token_id: "exchange_only".into(),
+            exchange_size: 2.0,
+        }));
+    }
+}
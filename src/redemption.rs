@@ -0,0 +1,88 @@
+//! Redemption module
+//!
+//! Finds held markets that have resolved, redeems their tokens for USDC
+//! through the CTF (or settles them on paper when on-chain redemption is
+//! off), and records the final settlement in the spend ledger.
+
+use crate::ledger::{Settlement, LEDGER_DOCUMENT};
+use crate::money::{self, Decimal};
+use crate::types::{Market, MarketId};
+use crate::workers::WorkerContext;
+use std::collections::HashSet;
+
+/// Settle every held market that dropped out of discovery and has resolved
+pub async fn redeem_resolved(ctx: &WorkerContext, markets: &[Market], now: u64) {
+    // Markets still being discovered are trading, not resolved
+    let live: HashSet<&MarketId> = markets.iter().map(|m| &m.id).collect();
+    let held: Vec<MarketId> = ctx
+        .position_manager
+        .read()
+        .await
+        .held_markets()
+        .into_iter()
+        .filter(|id| !live.contains(id))
+        .collect();
+
+    for market_id in held {
+        let resolution = match ctx.market_provider.fetch_resolution(&market_id).await {
+            Ok(Some(resolution)) => resolution,
+            Ok(None) => continue,
+            Err(e) => {
+                println!(
+                    "⚠️ [Redeem] Resolution check for {} failed: {}",
+                    market_id, e
+                );
+                continue;
+            }
+        };
+
+        // Redeem on-chain first; positions stay open for a retry if it fails
+        let tx_hash = match &ctx.ctf {
+            Some(ctf) if ctx.config.ctf.redeem_resolved && !ctx.config.trading.shadow_mode => {
+                match ctf.redeem(&resolution.condition_id).await {
+                    Ok(tx_hash) => Some(tx_hash),
+                    Err(e) => {
+                        println!("⚠️ [Redeem] Redemption of {} failed: {}", market_id, e);
+                        continue;
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        let exits = ctx
+            .position_manager
+            .write()
+            .await
+            .settle(&market_id, &resolution.payouts, now);
+        if exits.is_empty() {
+            continue;
+        }
+        ctx.record_exits(&market_id, &exits).await;
+
+        let payout: Decimal = exits
+            .iter()
+            .map(|e| money::usdc(e.position.size * e.exit_price))
+            .sum();
+        let pnl: Decimal = exits.iter().map(|e| e.pnl).sum();
+        println!(
+            "🏁 [Redeem] {} resolved: ${:.2} paid out | PnL: ${:.4} | {}",
+            market_id,
+            payout,
+            pnl,
+            tx_hash.as_deref().unwrap_or("simulated")
+        );
+
+        let mut ledger = ctx.ledger.lock().await;
+        ledger.record_settlement(Settlement {
+            market_id,
+            payout,
+            pnl,
+            tx_hash,
+            timestamp: now,
+        });
+        if let Err(e) = ctx.storage.save(LEDGER_DOCUMENT, &*ledger) {
+            println!("⚠️ Failed to persist spend ledger: {}", e);
+        }
+    }
+}
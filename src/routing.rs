@@ -0,0 +1,187 @@
+//! Bundle leg routing
+//!
+//! Buying a bundle by crossing both asks pays the full spread on every leg.
+//! The router looks at each leg's book and decides whether to take the ask,
+//! rest at the bid, or take what the best ask level holds and rest the
+//! remainder, so the bundle is bought at the lowest expected total cost.
+
+use crate::config::RoutingConfig;
+use crate::types::{OrderBook, Side, TokenId};
+use serde::Serialize;
+
+/// How one leg of a bundle is filled
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "route", rename_all = "snake_case")]
+pub enum LegRoute {
+    /// Cross the spread for the whole size
+    Take,
+    /// Rest the whole size at the best bid
+    Post { price: f64 },
+    /// Take `take` shares from the best ask level, rest `post` at the bid
+    Split { take: f64, post: f64, price: f64 },
+}
+
+impl std::fmt::Display for LegRoute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Take => write!(f, "take"),
+            Self::Post { price } => write!(f, "post @ {:.3}", price),
+            Self::Split { take, post, price } => {
+                write!(f, "take {:.2}, post {:.2} @ {:.3}", take, post, price)
+            }
+        }
+    }
+}
+
+/// Route chosen for one leg
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LegPlan {
+    pub token_id: TokenId,
+    pub route: LegRoute,
+    /// Expected cost per share with fees, counting rested shares at the bid
+    pub unit_cost: f64,
+    /// Cost per share with fees of taking the whole size
+    pub take_cost: f64,
+}
+
+/// Decides per leg how a bundle buy is filled
+#[derive(Debug, Clone)]
+pub struct LegRouter {
+    /// Saving per share that justifies resting instead of taking
+    min_saving: f64,
+}
+
+impl LegRouter {
+    pub fn new(config: &RoutingConfig) -> Self {
+        Self {
+            min_saving: config.min_saving,
+        }
+    }
+
+    /// Route buying `size` shares of one leg
+    ///
+    /// A spread wider than the saving threshold is rested at the bid; a
+    /// tight spread over a thin best level takes that level and rests the
+    /// rest rather than walking the book; anything else is taken.
+    pub fn route_leg(
+        &self,
+        book: &OrderBook,
+        size: f64,
+        taker_rate: f64,
+        maker_rate: f64,
+    ) -> Option<LegPlan> {
+        let take_cost = book.execution_price(size, Side::Buy)? * (1.0 + taker_rate);
+        let plan = |route, unit_cost| LegPlan {
+            token_id: book.token_id.clone(),
+            route,
+            unit_cost,
+            take_cost,
+        };
+        let (Some(bid), Some(ask)) = (book.best_bid(), book.best_ask()) else {
+            return Some(plan(LegRoute::Take, take_cost));
+        };
+        let post_cost = bid * (1.0 + maker_rate);
+        if post_cost >= take_cost - self.min_saving {
+            return Some(plan(LegRoute::Take, take_cost));
+        }
+        let top_cost = ask * (1.0 + taker_rate);
+        if top_cost - post_cost >= self.min_saving {
+            return Some(plan(LegRoute::Post { price: bid }, post_cost));
+        }
+        let top: f64 = book
+            .asks
+            .iter()
+            .take_while(|level| level.price <= ask)
+            .map(|level| level.size)
+            .sum();
+        let take = top.min(size);
+        let post = size - take;
+        Some(plan(
+            LegRoute::Split {
+                take,
+                post,
+                price: bid,
+            },
+            (take * top_cost + post * post_cost) / size,
+        ))
+    }
+
+    /// Route every leg of a bundle buy; None if any leg can't be priced
+    pub fn route(
+        &self,
+        books: &[&OrderBook],
+        size: f64,
+        taker_rate: f64,
+        maker_rate: f64,
+    ) -> Option<Vec<LegPlan>> {
+        books
+            .iter()
+            .map(|book| self.route_leg(book, size, taker_rate, maker_rate))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PriceLevel;
+
+    fn book(token: &str, bid: f64, asks: &[(f64, f64)]) -> OrderBook {
+        OrderBook {
+            token_id: token.into(),
+            bids: vec![PriceLevel {
+                price: bid,
+                size: 100.0,
+            }],
+            asks: asks
+                .iter()
+                .map(|&(price, size)| PriceLevel { price, size })
+                .collect(),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_routes_each_leg_by_spread_and_depth() {
+        let router = LegRouter::new(&RoutingConfig {
+            enabled: true,
+            min_saving: 0.02,
+        });
+
+        // Tight and deep: take
+        let tight = book("yes", 0.45, &[(0.46, 100.0)]);
+        let plan = router.route_leg(&tight, 10.0, 0.0, 0.0).unwrap();
+        assert_eq!(plan.route, LegRoute::Take);
+        assert!((plan.unit_cost - 0.46).abs() < 1e-9);
+
+        // Wide: rest at the bid
+        let wide = book("no", 0.40, &[(0.50, 100.0)]);
+        let plan = router.route_leg(&wide, 10.0, 0.0, 0.0).unwrap();
+        assert_eq!(plan.route, LegRoute::Post { price: 0.40 });
+        assert_eq!(plan.unit_cost, 0.40);
+
+        // Tight but thin: take the best level, rest the remainder
+        let thin = book("yes", 0.45, &[(0.46, 4.0), (0.55, 100.0)]);
+        let plan = router.route_leg(&thin, 10.0, 0.0, 0.0).unwrap();
+        assert_eq!(
+            plan.route,
+            LegRoute::Split {
+                take: 4.0,
+                post: 6.0,
+                price: 0.45
+            }
+        );
+        assert!((plan.unit_cost - (4.0 * 0.46 + 6.0 * 0.45) / 10.0).abs() < 1e-9);
+        assert!(plan.unit_cost < plan.take_cost);
+
+        // A taker fee can make resting worth it on an otherwise tight leg
+        let plan = router.route_leg(&tight, 10.0, 0.05, 0.0).unwrap();
+        assert_eq!(plan.route, LegRoute::Post { price: 0.45 });
+
+        let plans = router.route(&[&tight, &wide], 10.0, 0.0, 0.0).unwrap();
+        assert_eq!(plans.len(), 2);
+        assert!(router
+            .route(&[&tight, &book("no", 0.40, &[])], 10.0, 0.0, 0.0)
+            .is_none());
+    }
+}
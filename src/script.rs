@@ -0,0 +1,286 @@
+//! Scripted trade filter
+//!
+//! Runs a user-supplied rhai script against every intent that passes the
+//! built-in filters, so custom rules can be added without recompiling. The
+//! script sees the market, the intent, the books of its legs, and the
+//! remaining allowance, and its final value decides the trade:
+//!
+//! - `true` or no value: accept
+//! - `false`: reject
+//! - a number: shrink each leg to that size (zero or less rejects; a size
+//!   at or above the intent's accepts it as is, so a script cannot size up
+//!   past the built-in limits)
+//!
+//! `market.liquidity` and `market.volume_24hr` are `()` when Gamma did not
+//! report them.
+//!
+//! ```rhai
+//! if market.liquidity != () && market.liquidity < 1000.0 { return false; }
+//! if allowance.remaining < 10.0 { return intent.size / 2.0; }
+//! true
+//! ```
+
+use crate::strategy::Intent;
+use crate::types::{Market, OrderBook};
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+
+/// What the script decided for an intent
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterDecision {
+    Accept,
+    Reject,
+    /// New size per leg, below the intent's
+    Resize(f64),
+}
+
+/// Everything a script can look at
+#[derive(Debug)]
+pub struct FilterInput<'a> {
+    pub market: &'a Market,
+    pub intent: &'a Intent,
+    /// Books of the intent's legs, where available
+    pub books: Vec<&'a OrderBook>,
+    pub remaining_allowance: f64,
+    pub daily_limit: f64,
+}
+
+impl FilterInput<'_> {
+    /// Bind the input as script variables
+    fn scope(&self) -> Scope<'static> {
+        let market = self.market;
+        let mut market_map = Map::new();
+        market_map.insert("id".into(), market.id.to_string().into());
+        market_map.insert("question".into(), market.question.clone().into());
+        market_map.insert("slug".into(), market.slug.clone().into());
+        market_map.insert("yes_price".into(), market.yes_price().into());
+        market_map.insert("no_price".into(), market.no_price().into());
+        market_map.insert("spread".into(), market.get_spread().into());
+        let unknown_as_unit = |value: Option<f64>| value.map_or(Dynamic::UNIT, Dynamic::from);
+        market_map.insert("liquidity".into(), unknown_as_unit(market.liquidity));
+        market_map.insert("volume_24hr".into(), unknown_as_unit(market.volume_24hr));
+
+        let intent = self.intent;
+        let mut intent_map = Map::new();
+        intent_map.insert("strategy".into(), intent.strategy.into());
+        intent_map.insert("side".into(), format!("{:?}", intent.side).into());
+        intent_map.insert("size".into(), intent.size.into());
+        intent_map.insert("legs".into(), (intent.token_ids.len() as i64).into());
+        intent_map.insert("edge".into(), intent.edge.into());
+        intent_map.insert("expected_profit".into(), intent.expected_profit.into());
+
+        let books: Array = self
+            .books
+            .iter()
+            .map(|book| {
+                let mut map = Map::new();
+                map.insert("token_id".into(), book.token_id.to_string().into());
+                map.insert("best_bid".into(), optional(book.best_bid()));
+                map.insert("best_ask".into(), optional(book.best_ask()));
+                map.insert("midpoint".into(), optional(book.midpoint()));
+                map.insert("spread".into(), optional(book.spread()));
+                map.insert("bid_depth".into(), book.total_bid_liquidity().into());
+                map.insert("ask_depth".into(), book.total_ask_liquidity().into());
+                map.into()
+            })
+            .collect();
+
+        let mut allowance = Map::new();
+        allowance.insert("remaining".into(), self.remaining_allowance.into());
+        allowance.insert("daily_limit".into(), self.daily_limit.into());
+
+        let mut scope = Scope::new();
+        scope.push_constant("market", market_map);
+        scope.push_constant("intent", intent_map);
+        scope.push_constant("books", books);
+        scope.push_constant("allowance", allowance);
+        scope
+    }
+}
+
+/// Missing book values show up as `()` in scripts
+fn optional(value: Option<f64>) -> Dynamic {
+    value.map(Dynamic::from).unwrap_or(Dynamic::UNIT)
+}
+
+/// Compiled filter script
+#[derive(Debug)]
+pub struct TradeFilter {
+    engine: Engine,
+    ast: AST,
+}
+
+impl TradeFilter {
+    /// Compile a script; `max_operations` bounds each run (0 is unbounded)
+    pub fn compile(source: &str, max_operations: u64) -> Result<Self, ScriptError> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(max_operations);
+        let ast = engine
+            .compile(source)
+            .map_err(|e| ScriptError::Compile(e.to_string()))?;
+        Ok(Self { engine, ast })
+    }
+
+    /// Load and compile a script file
+    pub fn from_file(path: &str, max_operations: u64) -> Result<Self, ScriptError> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| ScriptError::Io(format!("{}: {}", path, e)))?;
+        Self::compile(&source, max_operations)
+    }
+
+    /// Run the script for one intent
+    pub fn evaluate(&self, input: &FilterInput) -> Result<FilterDecision, ScriptError> {
+        let mut scope = input.scope();
+        let result: Dynamic = self
+            .engine
+            .eval_ast_with_scope(&mut scope, &self.ast)
+            .map_err(|e| ScriptError::Runtime(e.to_string()))?;
+
+        if result.is_unit() {
+            return Ok(FilterDecision::Accept);
+        }
+        if let Ok(accept) = result.as_bool() {
+            return Ok(if accept {
+                FilterDecision::Accept
+            } else {
+                FilterDecision::Reject
+            });
+        }
+        let size = match (result.as_float(), result.as_int()) {
+            (Ok(size), _) => size,
+            (_, Ok(size)) => size as f64,
+            _ => return Err(ScriptError::BadResult(result.type_name().to_string())),
+        };
+        Ok(if size <= 0.0 {
+            FilterDecision::Reject
+        } else if size < input.intent.size {
+            FilterDecision::Resize(size)
+        } else {
+            FilterDecision::Accept
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptError {
+    Io(String),
+    Compile(String),
+    Runtime(String),
+    /// The script ended on a value that is not a decision
+    BadResult(String),
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to read filter script {}", e),
+            Self::Compile(e) => write!(f, "Filter script does not compile: {}", e),
+            Self::Runtime(e) => write!(f, "Filter script failed: {}", e),
+            Self::BadResult(t) => {
+                write!(f, "Filter script returned {}, expected bool or number", t)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_spec::OrderType;
+    use crate::test_util;
+    use crate::types::{PriceLevel, Side};
+
+    fn market() -> Market {
+        Market {
+            liquidity: Some(500.0),
+            ..test_util::market(0.48, 0.47)
+        }
+    }
+
+    fn intent() -> Intent {
+        Intent {
+            strategy: "arbitrage",
+            market_id: "m1".into(),
+            token_ids: vec!["yes".into(), "no".into()],
+            side: Side::Buy,
+            size: 10.0,
+            spread: -0.05,
+            edge: 0.05,
+            expected_profit: 0.4,
+            order_type: OrderType::Fok,
+        }
+    }
+
+    fn decide(source: &str) -> Result<FilterDecision, ScriptError> {
+        decide_on(&market(), source)
+    }
+
+    fn decide_on(market: &Market, source: &str) -> Result<FilterDecision, ScriptError> {
+        let intent = intent();
+        let book = OrderBook {
+            token_id: "yes".into(),
+            bids: vec![PriceLevel {
+                price: 0.47,
+                size: 100.0,
+            }],
+            asks: vec![PriceLevel {
+                price: 0.49,
+                size: 20.0,
+            }],
+            timestamp: 0,
+        };
+        TradeFilter::compile(source, 10_000)?.evaluate(&FilterInput {
+            market,
+            intent: &intent,
+            books: vec![&book],
+            remaining_allowance: 8.0,
+            daily_limit: 50.0,
+        })
+    }
+
+    #[test]
+    fn test_script_decisions() {
+        assert_eq!(decide("true"), Ok(FilterDecision::Accept));
+        assert_eq!(decide("let x = 1;"), Ok(FilterDecision::Accept));
+        assert_eq!(
+            decide("market.liquidity >= 1000.0"),
+            Ok(FilterDecision::Reject)
+        );
+        assert_eq!(
+            decide("if allowance.remaining < intent.size { allowance.remaining / intent.legs } else { true }"),
+            Ok(FilterDecision::Resize(4.0))
+        );
+        assert_eq!(
+            decide("books[0].ask_depth < 50.0 && intent.strategy == \"arbitrage\""),
+            Ok(FilterDecision::Accept)
+        );
+        assert_eq!(decide("0"), Ok(FilterDecision::Reject));
+        // Never upsized past what the built-in sizing allowed
+        assert_eq!(decide("intent.size * 3.0"), Ok(FilterDecision::Accept));
+        assert_eq!(decide("10"), Ok(FilterDecision::Accept));
+    }
+
+    #[test]
+    fn test_unreported_liquidity_guard() {
+        let guard = "if market.liquidity != () && market.liquidity < 1000.0 { return false; } true";
+        assert_eq!(decide(guard), Ok(FilterDecision::Reject));
+        let unreported = Market {
+            liquidity: None,
+            ..market()
+        };
+        assert_eq!(decide_on(&unreported, guard), Ok(FilterDecision::Accept));
+    }
+
+    #[test]
+    fn test_script_errors() {
+        assert!(matches!(decide("let = ;"), Err(ScriptError::Compile(_))));
+        assert!(matches!(
+            decide("market.nope.field"),
+            Err(ScriptError::Runtime(_))
+        ));
+        assert!(matches!(decide("\"yes\""), Err(ScriptError::BadResult(_))));
+        // Runaway scripts are cut off
+        assert!(matches!(decide("loop { }"), Err(ScriptError::Runtime(_))));
+    }
+}
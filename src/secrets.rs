@@ -0,0 +1,636 @@
+//! Secrets management module
+//!
+//! Loads private keys and API credentials from environment variables, an
+//! encrypted keystore file, or the OS keyring. Secrets are never read from
+//! config.toml — the config only selects where to look. The EVM key can
+//! instead come from a standard encrypted JSON (V3) keystore exported by a
+//! wallet, unlocked once at startup.
+
+use crate::signer::{keccak256, EvmSigner};
+use aes::cipher::{NewCipher, StreamCipher};
+use aes::{Aes128Ctr, Aes256Ctr};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fs;
+use std::io::IsTerminal;
+use std::process::Command;
+
+/// EVM private key used for order signing
+pub const EVM_PRIVATE_KEY: &str = "evm_private_key";
+/// Polymarket CLOB API credentials
+pub const CLOB_API_KEY: &str = "clob_api_key";
+pub const CLOB_API_SECRET: &str = "clob_api_secret";
+pub const CLOB_API_PASSPHRASE: &str = "clob_api_passphrase";
+/// Builder API credentials that posted orders are attributed to
+pub const BUILDER_API_KEY: &str = "builder_api_key";
+pub const BUILDER_API_SECRET: &str = "builder_api_secret";
+pub const BUILDER_API_PASSPHRASE: &str = "builder_api_passphrase";
+/// Solana wallet keypair
+pub const SOLANA_KEYPAIR: &str = "solana_keypair";
+
+/// Env var prefix for secrets (e.g. POLYSHARK_EVM_PRIVATE_KEY)
+const ENV_PREFIX: &str = "POLYSHARK_";
+/// Keyring service name
+const KEYRING_SERVICE: &str = "polyshark";
+/// PBKDF2 rounds for new keystores
+const KDF_ITERATIONS: u32 = 100_000;
+
+/// A secret value that is never printed
+#[derive(Clone)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// Access the raw value (only at the point of use)
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret(***)")
+    }
+}
+
+/// Where secrets are loaded from
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretSource {
+    /// POLYSHARK_<NAME> environment variables
+    #[default]
+    Env,
+    /// Passphrase-encrypted keystore file
+    Keystore,
+    /// OS keyring (macOS Keychain or freedesktop Secret Service); not
+    /// available on Windows
+    Keyring,
+}
+
+/// Loads secrets from the configured source
+#[derive(Debug, Clone)]
+pub struct SecretStore {
+    source: SecretSource,
+    keystore_path: String,
+    passphrase_env: String,
+    /// EVM key unlocked from a V3 keystore, used instead of the source
+    evm_key: Option<Secret>,
+}
+
+impl SecretStore {
+    pub fn new(source: SecretSource, keystore_path: &str, passphrase_env: &str) -> Self {
+        Self {
+            source,
+            keystore_path: keystore_path.to_string(),
+            passphrase_env: passphrase_env.to_string(),
+            evm_key: None,
+        }
+    }
+
+    /// Serve the EVM key from an unlocked V3 keystore
+    pub fn with_evm_key(mut self, key: Secret) -> Self {
+        self.evm_key = Some(key);
+        self
+    }
+
+    /// Get the configured source
+    pub fn source(&self) -> SecretSource {
+        self.source
+    }
+
+    /// Load a secret by name (see the constants in this module)
+    pub fn get(&self, name: &str) -> Result<Secret, SecretsError> {
+        if let Some(key) = self.evm_key.as_ref().filter(|_| name == EVM_PRIVATE_KEY) {
+            return Ok(key.clone());
+        }
+        match self.source {
+            SecretSource::Env => Self::from_env(name),
+            SecretSource::Keystore => {
+                let passphrase = std::env::var(&self.passphrase_env)
+                    .map_err(|_| SecretsError::NotFound(self.passphrase_env.clone()))?;
+                Keystore::load(&self.keystore_path)?.get(name, &passphrase)
+            }
+            SecretSource::Keyring => Self::from_keyring(name),
+        }
+    }
+
+    /// Check whether a secret is available without exposing it
+    pub fn has(&self, name: &str) -> bool {
+        self.get(name).is_ok()
+    }
+
+    fn from_env(name: &str) -> Result<Secret, SecretsError> {
+        let var = format!("{}{}", ENV_PREFIX, name.to_uppercase());
+        match std::env::var(&var) {
+            Ok(value) if !value.is_empty() => Ok(Secret(value)),
+            _ => Err(SecretsError::NotFound(var)),
+        }
+    }
+
+    fn from_keyring(name: &str) -> Result<Secret, SecretsError> {
+        if cfg!(windows) {
+            return Err(SecretsError::Keyring(
+                "Windows Credential Manager is not supported; use the env or keystore source"
+                    .to_string(),
+            ));
+        }
+        let output = if cfg!(target_os = "macos") {
+            Command::new("security")
+                .args(["find-generic-password", "-s", KEYRING_SERVICE])
+                .args(["-a", name, "-w"])
+                .output()
+        } else {
+            Command::new("secret-tool")
+                .args(["lookup", "service", KEYRING_SERVICE, "account", name])
+                .output()
+        }
+        .map_err(|e| SecretsError::Keyring(e.to_string()))?;
+
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !output.status.success() || value.is_empty() {
+            return Err(SecretsError::NotFound(format!(
+                "keyring {}/{}",
+                KEYRING_SERVICE, name
+            )));
+        }
+        Ok(Secret(value))
+    }
+}
+
+/// One encrypted entry in a keystore
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EncryptedSecret {
+    iv: String,
+    ciphertext: String,
+    mac: String,
+}
+
+/// Passphrase-encrypted keystore file
+///
+/// Each secret is encrypted with AES-256-CTR under a key derived from the
+/// passphrase with PBKDF2-HMAC-SHA256, and authenticated with HMAC-SHA256.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keystore {
+    salt: String,
+    iterations: u32,
+    secrets: HashMap<String, EncryptedSecret>,
+}
+
+impl Keystore {
+    /// Create an empty keystore with a fresh salt
+    pub fn new() -> Self {
+        Self {
+            salt: hex::encode(rand::random::<[u8; 16]>()),
+            iterations: KDF_ITERATIONS,
+            secrets: HashMap::new(),
+        }
+    }
+
+    /// Load a keystore file
+    pub fn load(path: &str) -> Result<Self, SecretsError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| SecretsError::Keystore(format!("{} ({})", path, e)))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| SecretsError::Keystore(format!("{} ({})", path, e)))
+    }
+
+    /// Save the keystore file
+    pub fn save(&self, path: &str) -> Result<(), SecretsError> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| SecretsError::Keystore(e.to_string()))?;
+        fs::write(path, contents).map_err(|e| SecretsError::Keystore(format!("{} ({})", path, e)))
+    }
+
+    /// Encrypt and store a secret
+    pub fn insert(
+        &mut self,
+        name: &str,
+        value: &str,
+        passphrase: &str,
+    ) -> Result<(), SecretsError> {
+        let (enc_key, mac_key) = self.derive_keys(passphrase)?;
+        let iv: [u8; 16] = rand::random();
+
+        let mut ciphertext = value.as_bytes().to_vec();
+        Aes256Ctr::new(&enc_key.into(), &iv.into()).apply_keystream(&mut ciphertext);
+        let mac = compute_mac(&mac_key, &iv, &ciphertext)?;
+
+        self.secrets.insert(
+            name.to_string(),
+            EncryptedSecret {
+                iv: hex::encode(iv),
+                ciphertext: hex::encode(ciphertext),
+                mac: hex::encode(mac),
+            },
+        );
+        Ok(())
+    }
+
+    /// Decrypt a secret, failing if the passphrase is wrong
+    pub fn get(&self, name: &str, passphrase: &str) -> Result<Secret, SecretsError> {
+        let entry = self
+            .secrets
+            .get(name)
+            .ok_or_else(|| SecretsError::NotFound(format!("keystore entry {}", name)))?;
+        let (enc_key, mac_key) = self.derive_keys(passphrase)?;
+
+        let iv: [u8; 16] = decode_hex(&entry.iv)?
+            .try_into()
+            .map_err(|_| SecretsError::Keystore("invalid iv length".to_string()))?;
+        let mut plaintext = decode_hex(&entry.ciphertext)?;
+        let expected_mac = decode_hex(&entry.mac)?;
+
+        if !constant_time_eq(&compute_mac(&mac_key, &iv, &plaintext)?, &expected_mac) {
+            return Err(SecretsError::BadPassphrase);
+        }
+
+        Aes256Ctr::new(&enc_key.into(), &iv.into()).apply_keystream(&mut plaintext);
+        String::from_utf8(plaintext)
+            .map(Secret)
+            .map_err(|_| SecretsError::Keystore(format!("entry {} is not UTF-8", name)))
+    }
+
+    /// Derive the encryption and MAC keys from a passphrase
+    fn derive_keys(&self, passphrase: &str) -> Result<([u8; 32], [u8; 32]), SecretsError> {
+        let salt = decode_hex(&self.salt)?;
+        let mut derived = [0u8; 64];
+        pbkdf2::pbkdf2::<Hmac<Sha256>>(passphrase.as_bytes(), &salt, self.iterations, &mut derived);
+
+        let mut enc_key = [0u8; 32];
+        let mut mac_key = [0u8; 32];
+        enc_key.copy_from_slice(&derived[..32]);
+        mac_key.copy_from_slice(&derived[32..]);
+        Ok((enc_key, mac_key))
+    }
+}
+
+impl Default for Keystore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Passphrase from `env`, or prompted for when stdin is a terminal
+pub fn read_passphrase(env: &str, prompt: &str) -> Result<String, SecretsError> {
+    if let Ok(passphrase) = std::env::var(env) {
+        return Ok(passphrase);
+    }
+    if !std::io::stdin().is_terminal() {
+        return Err(SecretsError::NotFound(env.to_string()));
+    }
+    rpassword::prompt_password(prompt).map_err(|e| SecretsError::Keystore(e.to_string()))
+}
+
+/// Key derivation of a V3 keystore
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kdf", content = "kdfparams", rename_all = "lowercase")]
+enum KeystoreKdf {
+    Scrypt {
+        dklen: usize,
+        n: u64,
+        r: u32,
+        p: u32,
+        salt: String,
+    },
+    Pbkdf2 {
+        dklen: usize,
+        c: u32,
+        prf: String,
+        salt: String,
+    },
+}
+
+impl KeystoreKdf {
+    fn derive(&self, passphrase: &str) -> Result<Vec<u8>, SecretsError> {
+        let derived = match self {
+            Self::Scrypt {
+                dklen,
+                n,
+                r,
+                p,
+                salt,
+            } => {
+                if !n.is_power_of_two() {
+                    return Err(SecretsError::Keystore(format!(
+                        "scrypt n {} not a power of two",
+                        n
+                    )));
+                }
+                let params = scrypt::Params::new(n.trailing_zeros() as u8, *r, *p)
+                    .map_err(|e| SecretsError::Keystore(e.to_string()))?;
+                let mut derived = vec![0u8; *dklen];
+                scrypt::scrypt(
+                    passphrase.as_bytes(),
+                    &decode_hex(salt)?,
+                    &params,
+                    &mut derived,
+                )
+                .map_err(|e| SecretsError::Keystore(e.to_string()))?;
+                derived
+            }
+            Self::Pbkdf2 {
+                dklen,
+                c,
+                prf,
+                salt,
+            } => {
+                if prf != "hmac-sha256" {
+                    return Err(SecretsError::Keystore(format!("unsupported prf {}", prf)));
+                }
+                let mut derived = vec![0u8; *dklen];
+                pbkdf2::pbkdf2::<Hmac<Sha256>>(
+                    passphrase.as_bytes(),
+                    &decode_hex(salt)?,
+                    *c,
+                    &mut derived,
+                );
+                derived
+            }
+        };
+        if derived.len() < 32 {
+            return Err(SecretsError::Keystore("dklen below 32".to_string()));
+        }
+        Ok(derived)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct KeystoreCipherParams {
+    iv: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct KeystoreCrypto {
+    cipher: String,
+    cipherparams: KeystoreCipherParams,
+    ciphertext: String,
+    #[serde(flatten)]
+    kdf: KeystoreKdf,
+    mac: String,
+}
+
+/// Ethereum V3 keystore (Web3 Secret Storage), as exported by geth,
+/// MetaMask, and most wallets
+///
+/// The key is AES-128-CTR encrypted under a scrypt- or PBKDF2-derived key
+/// and authenticated with keccak256 over the MAC key and ciphertext.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvmKeystore {
+    version: u32,
+    /// Address the key belongs to, checked after decryption when present
+    #[serde(default)]
+    address: String,
+    #[serde(alias = "Crypto")]
+    crypto: KeystoreCrypto,
+}
+
+impl EvmKeystore {
+    /// Load a V3 keystore file
+    pub fn load(path: &str) -> Result<Self, SecretsError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| SecretsError::Keystore(format!("{} ({})", path, e)))?;
+        let keystore: Self = serde_json::from_str(&contents)
+            .map_err(|e| SecretsError::Keystore(format!("{} ({})", path, e)))?;
+        if keystore.version != 3 {
+            return Err(SecretsError::Keystore(format!(
+                "{} is a version {} keystore, expected 3",
+                path, keystore.version
+            )));
+        }
+        Ok(keystore)
+    }
+
+    /// Decrypt the private key as 0x-prefixed hex, failing if the passphrase
+    /// is wrong
+    pub fn decrypt(&self, passphrase: &str) -> Result<Secret, SecretsError> {
+        let crypto = &self.crypto;
+        if crypto.cipher != "aes-128-ctr" {
+            return Err(SecretsError::Keystore(format!(
+                "unsupported cipher {}",
+                crypto.cipher
+            )));
+        }
+        let derived = crypto.kdf.derive(passphrase)?;
+        let mut plaintext = decode_hex(&crypto.ciphertext)?;
+
+        let mut mac_input = derived[16..32].to_vec();
+        mac_input.extend_from_slice(&plaintext);
+        if !constant_time_eq(&keccak256(&mac_input), &decode_hex(&crypto.mac)?) {
+            return Err(SecretsError::BadPassphrase);
+        }
+
+        let iv: [u8; 16] = decode_hex(&crypto.cipherparams.iv)?
+            .try_into()
+            .map_err(|_| SecretsError::Keystore("invalid iv length".to_string()))?;
+        let key: [u8; 16] = derived[..16].try_into().unwrap();
+        Aes128Ctr::new(&key.into(), &iv.into()).apply_keystream(&mut plaintext);
+        let secret = Secret(format!("0x{}", hex::encode(plaintext)));
+
+        if !self.address.is_empty() {
+            let signer = EvmSigner::from_private_key(&secret)
+                .map_err(|e| SecretsError::Keystore(e.to_string()))?;
+            let expected = self.address.trim_start_matches("0x").to_lowercase();
+            if signer.address().trim_start_matches("0x") != expected {
+                return Err(SecretsError::Keystore(format!(
+                    "key does not match address 0x{}",
+                    expected
+                )));
+            }
+        }
+        Ok(secret)
+    }
+}
+
+fn compute_mac(key: &[u8], iv: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, SecretsError> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key).map_err(|e| SecretsError::Keystore(e.to_string()))?;
+    mac.update(iv);
+    mac.update(ciphertext);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+/// Compare digests without exiting at the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, SecretsError> {
+    hex::decode(value).map_err(|e| SecretsError::Keystore(e.to_string()))
+}
+
+/// Secrets-related errors
+#[derive(Debug, Clone)]
+pub enum SecretsError {
+    NotFound(String),
+    BadPassphrase,
+    Keystore(String),
+    Keyring(String),
+}
+
+impl std::fmt::Display for SecretsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(what) => write!(f, "Secret not found: {}", what),
+            Self::BadPassphrase => write!(f, "Keystore passphrase is incorrect"),
+            Self::Keystore(msg) => write!(f, "Keystore error: {}", msg),
+            Self::Keyring(msg) => write!(f, "Keyring error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SecretsError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keystore_roundtrip() {
+        let mut keystore = Keystore::new();
+        keystore
+            .insert(EVM_PRIVATE_KEY, "0xdeadbeef", "hunter2")
+            .unwrap();
+
+        let secret = keystore.get(EVM_PRIVATE_KEY, "hunter2").unwrap();
+        assert_eq!(secret.expose(), "0xdeadbeef");
+
+        // Ciphertext never contains the plaintext
+        let json = serde_json::to_string(&keystore).unwrap();
+        assert!(!json.contains("deadbeef"));
+    }
+
+    #[test]
+    fn test_keystore_rejects_wrong_passphrase() {
+        let mut keystore = Keystore::new();
+        keystore.insert(CLOB_API_SECRET, "s3cret", "right").unwrap();
+
+        let result = keystore.get(CLOB_API_SECRET, "wrong");
+        assert!(matches!(result, Err(SecretsError::BadPassphrase)));
+    }
+
+    #[test]
+    fn test_evm_keystore_v3() {
+        // Test vectors from the Web3 Secret Storage definition
+        let private_key = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+        let address = EvmSigner::from_private_key(&Secret::new(private_key))
+            .unwrap()
+            .address();
+        let vector = |kdf: &str, kdfparams, iv: &str, ciphertext: &str, mac: &str| {
+            serde_json::json!({
+                "version": 3,
+                "address": address.trim_start_matches("0x"),
+                "crypto": {
+                    "cipher": "aes-128-ctr",
+                    "cipherparams": { "iv": iv },
+                    "ciphertext": ciphertext,
+                    "kdf": kdf,
+                    "kdfparams": kdfparams,
+                    "mac": mac,
+                }
+            })
+        };
+
+        let pbkdf2 = vector(
+            "pbkdf2",
+            serde_json::json!({
+                "c": 262144,
+                "dklen": 32,
+                "prf": "hmac-sha256",
+                "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd",
+            }),
+            "6087dab2f9fdbbfaddc31a909735c1e6",
+            "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+            "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2",
+        );
+        let keystore: EvmKeystore = serde_json::from_value(pbkdf2.clone()).unwrap();
+        let secret = keystore.decrypt("testpassword").unwrap();
+        assert_eq!(secret.expose(), format!("0x{}", private_key));
+        assert!(matches!(
+            keystore.decrypt("wrong"),
+            Err(SecretsError::BadPassphrase)
+        ));
+
+        // The published scrypt vector uses r = 1 with n = 2^18, outside
+        // RFC 7914's n < 2^(16r) bound, so it is refused rather than derived
+        let scrypt_vector = vector(
+            "scrypt",
+            serde_json::json!({
+                "dklen": 32,
+                "n": 262144,
+                "p": 8,
+                "r": 1,
+                "salt": "ab0c7876052600dd703518d6fc3fe8984592145b591fc8fb5c6d43190334ba19",
+            }),
+            "83dbcc02d8ccb40e466191a123791e0e",
+            "d172bf743a674da9cdad04534d56926ef8358534d458fffccd4e6ad2fbde479c",
+            "2103ac29920d71da29f15d75b4a16dbe95cfd7ff8faea1056c33131d846e3097",
+        );
+        let keystore: EvmKeystore = serde_json::from_value(scrypt_vector).unwrap();
+        assert!(matches!(
+            keystore.decrypt("testpassword"),
+            Err(SecretsError::Keystore(_))
+        ));
+
+        // Wallets export scrypt with r = 8 and p = 1 (a lower n keeps this fast)
+        let salt = "ab0c7876052600dd703518d6fc3fe8984592145b591fc8fb5c6d43190334ba19";
+        let iv = "83dbcc02d8ccb40e466191a123791e0e";
+        let mut derived = [0u8; 32];
+        let params = scrypt::Params::new(12, 8, 1).unwrap();
+        scrypt::scrypt(
+            b"testpassword",
+            &hex::decode(salt).unwrap(),
+            &params,
+            &mut derived,
+        )
+        .unwrap();
+        let mut ciphertext = hex::decode(private_key).unwrap();
+        let key: [u8; 16] = derived[..16].try_into().unwrap();
+        let iv_bytes: [u8; 16] = hex::decode(iv).unwrap().try_into().unwrap();
+        Aes128Ctr::new(&key.into(), &iv_bytes.into()).apply_keystream(&mut ciphertext);
+        let mac = keccak256(&[&derived[16..32], &ciphertext[..]].concat());
+        let wallet = vector(
+            "scrypt",
+            serde_json::json!({ "dklen": 32, "n": 4096, "p": 1, "r": 8, "salt": salt }),
+            iv,
+            &hex::encode(&ciphertext),
+            &hex::encode(mac),
+        );
+        let keystore: EvmKeystore = serde_json::from_value(wallet).unwrap();
+        assert_eq!(
+            keystore.decrypt("testpassword").unwrap().expose(),
+            format!("0x{}", private_key)
+        );
+
+        // A key for another address is refused
+        let mut other = pbkdf2;
+        other["address"] = "0000000000000000000000000000000000000001".into();
+        let keystore: EvmKeystore = serde_json::from_value(other).unwrap();
+        assert!(matches!(
+            keystore.decrypt("testpassword"),
+            Err(SecretsError::Keystore(_))
+        ));
+
+        // The unlocked key wins over the configured source
+        let store = SecretStore::new(SecretSource::Env, "", "").with_evm_key(secret);
+        assert_eq!(
+            store.get(EVM_PRIVATE_KEY).unwrap().expose(),
+            format!("0x{}", private_key)
+        );
+    }
+
+    #[test]
+    fn test_env_source_and_redacted_debug() {
+        std::env::set_var("POLYSHARK_TEST_ONLY_SECRET", "abc123");
+        let store = SecretStore::new(SecretSource::Env, "", "");
+
+        let secret = store.get("test_only_secret").unwrap();
+        assert_eq!(secret.expose(), "abc123");
+        assert_eq!(format!("{:?}", secret), "Secret(***)");
+        assert!(!store.has("test_only_missing"));
+    }
+}
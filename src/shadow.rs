@@ -0,0 +1,71 @@
+//! Shadow trading module
+//!
+//! Executes qualifying signals against live books on paper: no allowance is
+//! spent and no orders are placed. Hypothetical fills are logged separately
+//! so the strategy can be validated before granting real permissions.
+
+use crate::money::Decimal;
+use crate::storage::{Storage, StorageError};
+use crate::types::ExecutionResult;
+
+/// Storage log holding hypothetical fills
+pub const SHADOW_LOG: &str = "shadow_fills";
+
+/// Hypothetical fill log and running totals
+#[derive(Debug)]
+pub struct ShadowLedger {
+    storage: Storage,
+    fills: usize,
+    total_cost: Decimal,
+    expected_profit: f64,
+}
+
+impl ShadowLedger {
+    pub fn new(storage: Storage) -> Self {
+        Self {
+            storage,
+            fills: 0,
+            total_cost: Decimal::ZERO,
+            expected_profit: 0.0,
+        }
+    }
+
+    /// Log the hypothetical fills for one signal and the profit it expected
+    pub fn record(
+        &mut self,
+        fills: &[ExecutionResult],
+        expected_profit: f64,
+    ) -> Result<(), StorageError> {
+        self.expected_profit += expected_profit;
+        for result in fills {
+            self.fills += 1;
+            self.total_cost += result.total_cost;
+
+            println!(
+                "   👻 [Shadow] {} {:.2} @ ${:.4} | Cost: ${:.2} | Impact: ${:.4}",
+                result.token_id,
+                result.filled_size,
+                result.execution_price,
+                result.total_cost,
+                result.price_impact
+            );
+            self.storage.append(SHADOW_LOG, result)?;
+        }
+        Ok(())
+    }
+
+    /// Number of hypothetical fills this session
+    pub fn fills(&self) -> usize {
+        self.fills
+    }
+
+    /// Capital the hypothetical fills would have spent
+    pub fn total_cost(&self) -> Decimal {
+        self.total_cost
+    }
+
+    /// Profit the detector expected from the shadowed signals
+    pub fn expected_profit(&self) -> f64 {
+        self.expected_profit
+    }
+}
@@ -0,0 +1,446 @@
+//! EVM signing module
+//!
+//! secp256k1 key handling, address derivation, and EIP-712 typed-data
+//! hashing used to authenticate with Polymarket and sign orders.
+
+use crate::secrets::Secret;
+use crate::types::Side;
+use libsecp256k1::{Message, PublicKey, SecretKey};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// Polygon mainnet chain id
+pub const POLYGON_CHAIN_ID: u64 = 137;
+
+/// CTF Exchange, the verifying contract of binary-market orders
+pub const CTF_EXCHANGE: &str = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E";
+/// Exchange for negative-risk (multi-outcome) markets
+pub const NEG_RISK_CTF_EXCHANGE: &str = "0xC5d563A36AE78145C45a50134d48A1215220f80a";
+
+/// Taker of a public order: anyone
+pub const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// EIP-712 type of a CTF Exchange order
+const ORDER_TYPE: &[u8] = b"Order(uint256 salt,address maker,address signer,address taker,uint256 tokenId,uint256 makerAmount,uint256 takerAmount,uint256 expiration,uint256 nonce,uint256 feeRateBps,uint8 side,uint8 signatureType)";
+
+/// How the exchange checks an order's signature against its maker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureType {
+    /// The signing EOA holds the funds
+    #[default]
+    Eoa,
+    /// Polymarket proxy wallet (email and Magic accounts)
+    PolyProxy,
+    /// Gnosis Safe proxy (browser-wallet accounts)
+    PolyGnosisSafe,
+}
+
+impl SignatureType {
+    /// `signatureType` value in the signed order
+    pub fn code(self) -> u8 {
+        match self {
+            Self::Eoa => 0,
+            Self::PolyProxy => 1,
+            Self::PolyGnosisSafe => 2,
+        }
+    }
+}
+
+/// Who funds and who signs an order
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OrderMaker {
+    /// Funder: the address whose USDC and tokens the order trades
+    pub maker: String,
+    /// EOA that signs on the maker's behalf
+    pub signer: String,
+    pub signature_type: SignatureType,
+}
+
+/// A CTF Exchange order before signing, amounts in 6-decimal base units
+///
+/// A buy gives `maker_amount` USDC for `taker_amount` outcome tokens; a
+/// sell gives tokens for USDC.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExchangeOrder {
+    pub salt: u64,
+    pub maker: String,
+    pub signer: String,
+    pub taker: String,
+    /// Outcome token id, a decimal uint256
+    pub token_id: String,
+    pub maker_amount: u64,
+    pub taker_amount: u64,
+    /// Unix seconds after which the order is void (0 for never)
+    pub expiration: u64,
+    pub nonce: u64,
+    pub fee_rate_bps: u64,
+    pub side: Side,
+    pub signature_type: SignatureType,
+}
+
+impl ExchangeOrder {
+    /// EIP-712 digest of the order on the exchange that settles it
+    ///
+    /// This is what the signer signs, and the order id the CLOB reports.
+    pub fn digest(&self, neg_risk: bool) -> Result<[u8; 32], SignerError> {
+        let exchange = if neg_risk {
+            NEG_RISK_CTF_EXCHANGE
+        } else {
+            CTF_EXCHANGE
+        };
+        let mut domain = Vec::with_capacity(160);
+        domain.extend_from_slice(&keccak256(
+            b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)",
+        ));
+        domain.extend_from_slice(&keccak256(b"Polymarket CTF Exchange"));
+        domain.extend_from_slice(&keccak256(b"1"));
+        domain.extend_from_slice(&u256_word(POLYGON_CHAIN_ID));
+        domain.extend_from_slice(&address_word(&parse_address(exchange)?));
+
+        let side = match self.side {
+            Side::Buy => 0,
+            Side::Sell => 1,
+        };
+        let mut order = Vec::with_capacity(416);
+        order.extend_from_slice(&keccak256(ORDER_TYPE));
+        order.extend_from_slice(&u256_word(self.salt));
+        order.extend_from_slice(&address_word(&parse_address(&self.maker)?));
+        order.extend_from_slice(&address_word(&parse_address(&self.signer)?));
+        order.extend_from_slice(&address_word(&parse_address(&self.taker)?));
+        order.extend_from_slice(&decimal_word(&self.token_id)?);
+        order.extend_from_slice(&u256_word(self.maker_amount));
+        order.extend_from_slice(&u256_word(self.taker_amount));
+        order.extend_from_slice(&u256_word(self.expiration));
+        order.extend_from_slice(&u256_word(self.nonce));
+        order.extend_from_slice(&u256_word(self.fee_rate_bps));
+        order.extend_from_slice(&u256_word(side));
+        order.extend_from_slice(&u256_word(u64::from(self.signature_type.code())));
+
+        Ok(eip712_digest(&keccak256(&domain), &keccak256(&order)))
+    }
+}
+
+/// Keccak-256 hash
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// Left-pad a u64 into a 32-byte ABI word
+pub fn u256_word(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Left-pad a 20-byte address into a 32-byte ABI word
+pub fn address_word(address: &[u8; 20]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(address);
+    word
+}
+
+/// Parse a decimal uint256, such as an outcome token id, into a 32-byte ABI word
+pub fn decimal_word(value: &str) -> Result<[u8; 32], SignerError> {
+    let invalid = || SignerError::InvalidUint(value.to_string());
+    if value.is_empty() {
+        return Err(invalid());
+    }
+    let mut word = [0u8; 32];
+    for digit in value.chars() {
+        let mut carry = digit.to_digit(10).ok_or_else(invalid)?;
+        for byte in word.iter_mut().rev() {
+            let next = u32::from(*byte) * 10 + carry;
+            *byte = next as u8;
+            carry = next >> 8;
+        }
+        if carry != 0 {
+            return Err(invalid());
+        }
+    }
+    Ok(word)
+}
+
+/// Parse a 0x-prefixed hex address
+pub fn parse_address(address: &str) -> Result<[u8; 20], SignerError> {
+    let bytes = hex::decode(address.trim_start_matches("0x"))
+        .map_err(|e| SignerError::InvalidAddress(e.to_string()))?;
+    bytes
+        .try_into()
+        .map_err(|_| SignerError::InvalidAddress(address.to_string()))
+}
+
+/// EIP-712 digest: keccak256(0x1901 || domainSeparator || structHash)
+pub fn eip712_digest(domain_separator: &[u8; 32], struct_hash: &[u8; 32]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(66);
+    data.extend_from_slice(&[0x19, 0x01]);
+    data.extend_from_slice(domain_separator);
+    data.extend_from_slice(struct_hash);
+    keccak256(&data)
+}
+
+/// Signs hashes with an EOA private key
+#[derive(Clone)]
+pub struct EvmSigner {
+    secret_key: SecretKey,
+    address: [u8; 20],
+}
+
+impl EvmSigner {
+    /// Create from a hex private key (with or without 0x prefix)
+    pub fn from_private_key(private_key: &Secret) -> Result<Self, SignerError> {
+        let bytes = hex::decode(private_key.expose().trim().trim_start_matches("0x"))
+            .map_err(|_| SignerError::InvalidKey)?;
+        let secret_key = SecretKey::parse_slice(&bytes).map_err(|_| SignerError::InvalidKey)?;
+
+        // Address = last 20 bytes of keccak256(uncompressed pubkey without prefix)
+        let public_key = PublicKey::from_secret_key(&secret_key).serialize();
+        let hash = keccak256(&public_key[1..]);
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&hash[12..]);
+
+        Ok(Self {
+            secret_key,
+            address,
+        })
+    }
+
+    /// Raw 20-byte address
+    pub fn address_bytes(&self) -> [u8; 20] {
+        self.address
+    }
+
+    /// 0x-prefixed lowercase hex address
+    pub fn address(&self) -> String {
+        format!("0x{}", hex::encode(self.address))
+    }
+
+    /// Sign a 32-byte hash, returning a 65-byte r || s || v signature (v = 27/28)
+    pub fn sign_hash(&self, hash: &[u8; 32]) -> [u8; 65] {
+        let (signature, recovery_id) = libsecp256k1::sign(&Message::parse(hash), &self.secret_key);
+        let mut out = [0u8; 65];
+        out[..64].copy_from_slice(&signature.serialize());
+        out[64] = recovery_id.serialize() + 27;
+        out
+    }
+}
+
+impl std::fmt::Debug for EvmSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EvmSigner")
+            .field("address", &self.address())
+            .finish()
+    }
+}
+
+/// Signing-related errors
+#[derive(Debug, Clone)]
+pub enum SignerError {
+    InvalidKey,
+    InvalidAddress(String),
+    InvalidUint(String),
+}
+
+impl std::fmt::Display for SignerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidKey => write!(f, "Invalid private key"),
+            Self::InvalidAddress(addr) => write!(f, "Invalid address: {}", addr),
+            Self::InvalidUint(value) => write!(f, "Invalid uint256: {}", value),
+        }
+    }
+}
+
+impl std::error::Error for SignerError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libsecp256k1::{RecoveryId, Signature};
+
+    // Well-known test key from the web3.js documentation
+    const TEST_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    #[test]
+    fn test_keccak256_empty() {
+        assert_eq!(
+            hex::encode(keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+    }
+
+    #[test]
+    fn test_address_derivation() {
+        let signer = EvmSigner::from_private_key(&Secret::new(TEST_KEY)).unwrap();
+        assert_eq!(
+            signer.address(),
+            "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23"
+        );
+    }
+
+    #[test]
+    fn test_signature_recovers_signer() {
+        let signer = EvmSigner::from_private_key(&Secret::new(TEST_KEY)).unwrap();
+        let hash = keccak256(b"polyshark");
+        let sig = signer.sign_hash(&hash);
+
+        let signature = Signature::parse_standard_slice(&sig[..64]).unwrap();
+        let recovery_id = RecoveryId::parse(sig[64] - 27).unwrap();
+        let recovered = libsecp256k1::recover(&Message::parse(&hash), &signature, &recovery_id)
+            .unwrap()
+            .serialize();
+        assert_eq!(keccak256(&recovered[1..])[12..], signer.address_bytes());
+    }
+
+    #[test]
+    fn test_decimal_word() {
+        assert_eq!(decimal_word("256").unwrap(), u256_word(256));
+        // A real outcome token id, checked against Python's int.to_bytes
+        let token_id =
+            "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+        assert_eq!(
+            hex::encode(decimal_word(token_id).unwrap()),
+            "9dae480511c4c0cb5d6c7937924c1db5be221e758b7135fec2a1977a1c130af3"
+        );
+        assert!(decimal_word("").is_err());
+        assert!(decimal_word("12a").is_err());
+        // 2^256 overflows
+        assert!(decimal_word(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_order_signature_recovers_signer() {
+        let signer = EvmSigner::from_private_key(&Secret::new(TEST_KEY)).unwrap();
+        let order = ExchangeOrder {
+            salt: 479249096354,
+            maker: signer.address(),
+            signer: signer.address(),
+            taker: ZERO_ADDRESS.to_string(),
+            token_id: "1234".to_string(),
+            maker_amount: 4_500_000,
+            taker_amount: 10_000_000,
+            expiration: 0,
+            nonce: 0,
+            fee_rate_bps: 0,
+            side: Side::Buy,
+            signature_type: SignatureType::Eoa,
+        };
+        let digest = order.digest(false).unwrap();
+        // Each exchange verifies against its own domain
+        assert_ne!(digest, order.digest(true).unwrap());
+
+        let sig = signer.sign_hash(&digest);
+        let signature = Signature::parse_standard_slice(&sig[..64]).unwrap();
+        let recovery_id = RecoveryId::parse(sig[64] - 27).unwrap();
+        let recovered = libsecp256k1::recover(&Message::parse(&digest), &signature, &recovery_id)
+            .unwrap()
+            .serialize();
+        assert_eq!(keccak256(&recovered[1..])[12..], signer.address_bytes());
+
+        let sell = ExchangeOrder {
+            side: Side::Sell,
+            ..order.clone()
+        };
+        assert_ne!(sell.digest(false).unwrap(), digest);
+    }
+
+    #[test]
+    fn test_signature_matches_web3_reference() {
+        // web3.eth.accounts.sign("Some data", TEST_KEY) from the web3.js docs
+        let signer = EvmSigner::from_private_key(&Secret::new(TEST_KEY)).unwrap();
+        let hash = keccak256(b"\x19Ethereum Signed Message:\n9Some data");
+        assert_eq!(
+            hex::encode(hash),
+            "1da44b586eb0729ff70a73c326926f6ed5a25f5b056e7f47fbc6e58d86871655"
+        );
+        assert_eq!(
+            hex::encode(signer.sign_hash(&hash)),
+            "b91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd\
+             6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c"
+        );
+    }
+
+    // Reference digests and signatures below were computed outside this
+    // crate, hashing the order as py-order-utils does and signing with
+    // RFC 6979 as eth_account does (checked against the web3.js vector above)
+    const TOKEN_ID: &str =
+        "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+
+    #[test]
+    fn test_eoa_order_matches_reference() {
+        let signer = EvmSigner::from_private_key(&Secret::new(TEST_KEY)).unwrap();
+        let order = ExchangeOrder {
+            salt: 479249096354,
+            maker: signer.address(),
+            signer: signer.address(),
+            taker: ZERO_ADDRESS.to_string(),
+            token_id: TOKEN_ID.to_string(),
+            maker_amount: 4_500_000,
+            taker_amount: 10_000_000,
+            expiration: 0,
+            nonce: 0,
+            fee_rate_bps: 0,
+            side: Side::Buy,
+            signature_type: SignatureType::Eoa,
+        };
+        let digest = order.digest(false).unwrap();
+        assert_eq!(
+            hex::encode(digest),
+            "0f2208e5249b81e4a42f5af04d56611e2042e88f9cf5c366bbde8ddcdc4328d2"
+        );
+        assert_eq!(
+            hex::encode(signer.sign_hash(&digest)),
+            "88a594d4064e94e1cee9dacd024d2be71a6c60e4e9eb076db6d4931af1debab2\
+             7669efd316a36a93755fc4f850c21ed8a609e47f632b46cb894052b418a6523c1b"
+        );
+    }
+
+    #[test]
+    fn test_poly_proxy_order_matches_reference() {
+        let signer = EvmSigner::from_private_key(&Secret::new(TEST_KEY)).unwrap();
+        let order = ExchangeOrder {
+            salt: 479249096354,
+            maker: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
+            signer: signer.address(),
+            taker: ZERO_ADDRESS.to_string(),
+            token_id: TOKEN_ID.to_string(),
+            maker_amount: 10_000_000,
+            taker_amount: 5_500_000,
+            expiration: 1700000000,
+            nonce: 0,
+            fee_rate_bps: 100,
+            side: Side::Sell,
+            signature_type: SignatureType::PolyProxy,
+        };
+        let digest = order.digest(false).unwrap();
+        assert_eq!(
+            hex::encode(digest),
+            "a7865715109e4052b75fb0840bc4fbf838cd54f5991ac6570e0c2836aabd6a3b"
+        );
+        assert_eq!(
+            hex::encode(signer.sign_hash(&digest)),
+            "d6fcfda80f95dccabe65b48ed8d788bcf8e6559097dd376da5ec13ac42c709a4\
+             67dcdb8aca1a6d54c364c0739c8b3cd2fb123f299aa23af51c3bb92d0b478abc1b"
+        );
+
+        // Signed for the neg-risk exchange's domain instead
+        let digest = order.digest(true).unwrap();
+        assert_eq!(
+            hex::encode(digest),
+            "f24871263a321ab39278c6e12923700136f9a83a1a44062ebde3a4682592e665"
+        );
+        assert_eq!(
+            hex::encode(signer.sign_hash(&digest)),
+            "bf2d133a51f3eb187587c7a3e8d39f8b35061cf9911e26302a51d7c3cdb1127a\
+             651a7479a7fc4b9d86289ad8346967b841bfd264172ed861b2ee7686e1bfbded1b"
+        );
+    }
+
+    #[test]
+    fn test_invalid_key_rejected() {
+        assert!(EvmSigner::from_private_key(&Secret::new("0xnothex")).is_err());
+    }
+}
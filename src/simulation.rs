@@ -1,62 +1,133 @@
-use crate::arb::ArbitrageDetector;
-use crate::engine::TradingEngine;
-use crate::execution::ExecutionEngine;
-use crate::fees::FeeModel;
-use crate::latency::LatencyModel;
-use crate::market::MarketDataProvider;
-use crate::wallet::Wallet;
-// use crate::types::Side; // Unused import
-
-#[allow(dead_code)]
-pub async fn run_monte_carlo(iterations: usize) {
-    println!(
-        "🎲 Starting Monte Carlo Simulation ({} runs)...",
-        iterations
-    );
+//! Monte Carlo runs of the trading engine
+//!
+//! Each run builds a fresh shadow-mode agent with its own latency, adverse
+//! selection and competitor arrival rate (edge decay), drives the real
+//! `TradingEngine` against live markets for a number of ticks, and reports
+//! what its shadow ledger recorded. Runs persist to their own temporary
+//! directory so they neither share nor pollute the agent's state.
 
-    let mut total_pnl = 0.0;
-    let mut wins = 0;
-    let mut losses = 0;
+use crate::agent::AgentBuilder;
+use crate::config::Config;
+use crate::money::Decimal;
+use crate::storage::Storage;
 
-    for i in 0..iterations {
-        // Setup fresh environment for each run
-        let wallet = Wallet::new(100.0); // Higher limit for sim
-                                         // In a real MC, we'd vary these parameters randomly
-        let fee_model = FeeModel {
-            maker_fee_bps: 0,
-            taker_fee_bps: 200,
-        };
-        let latency_model = LatencyModel::new(
-            50 + (i as u64 % 50),     // Vary latency: 50-100ms
-            0.001 * (i as f64 % 5.0), // Vary adverse move: 0% - 0.5%
-        );
+/// What one run's shadow ledger recorded
+#[derive(Debug, Clone, Copy)]
+pub struct RunOutcome {
+    pub fills: usize,
+    pub total_cost: Decimal,
+    pub expected_profit: f64,
+}
 
-        let market_provider = MarketDataProvider::new("https://indexer.envio.dev/graphql");
-        let detector = ArbitrageDetector::new(0.01, 0.05); // tighter spreads
-        let execution_engine = ExecutionEngine::new(fee_model, latency_model);
+/// The base configuration with run `i`'s market conditions applied
+///
+/// Shadow mode only: nothing is posted, spent or settled on-chain.
+pub fn run_config(base: &Config, i: usize) -> Config {
+    let mut config = base.clone();
+    // Vary latency: 50-100ms
+    config.timing.latency_base_ms = 50 + (i as u64 % 50);
+    // Vary adverse move: 0% - 0.4%
+    config.timing.adverse_selection_std = 0.001 * (i % 5) as f64;
+    // Vary competition: 0-4 rivals/sec racing each edge
+    config.trading.competitor_arrival_rate = (i % 5) as f64;
 
-        let mut engine = TradingEngine::new(wallet, market_provider, detector, execution_engine);
+    config.trading.shadow_mode = true;
+    config.orders.submit = false;
+    config.ctf.merge_bundles = false;
+    config.ctf.redeem_resolved = false;
+    config.api.headless = true;
+    config.api.stream_books = false;
+    config.api.stream_user = false;
+    config.webhook.url.clear();
+    config
+}
 
-        // Run for 10 ticks
-        engine.run(10).await;
+/// Drive `iterations` independent runs of `ticks` engine ticks each
+///
+/// Ticks are spaced as the engine asks, so `timing.discovery_interval_secs`
+/// sets how long a run takes.
+pub async fn run_monte_carlo(base: &Config, iterations: usize, ticks: usize) -> Vec<RunOutcome> {
+    println!(
+        "🎲 Starting Monte Carlo Simulation ({} runs x {} ticks)...",
+        iterations, ticks
+    );
 
-        let pnl = engine.wallet.spent_today; // simplified "pnl" as "money deployed" for this demo
-                                             // Real PnL requires closing positions which we haven't implemented logic for
+    let mut outcomes = Vec::with_capacity(iterations);
+    for i in 0..iterations {
+        let dir = std::env::temp_dir().join(format!(
+            "polyshark_monte_carlo_{}_{}",
+            std::process::id(),
+            i
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let agent = match AgentBuilder::new(run_config(base, i))
+            .with_storage(Storage::new(&dir))
+            .build()
+            .await
+        {
+            Ok(agent) => agent,
+            Err(e) => {
+                println!("⚠️ [Monte Carlo] Run {} failed to start: {}", i, e);
+                continue;
+            }
+        };
 
-        total_pnl += pnl;
-        if pnl > 0.0 {
-            wins += 1;
-        } else {
-            losses += 1;
+        let mut engine = agent.engine();
+        for _ in 0..ticks {
+            let wait = engine.tick().await;
+            tokio::time::sleep(wait).await;
         }
+        engine.shutdown().await;
 
-        if i % 10 == 0 {
-            println!("Run {}: Deployed ${:.2}", i, pnl);
-        }
+        let shadow = agent.ctx.shadow.lock().await;
+        let outcome = RunOutcome {
+            fills: shadow.fills(),
+            total_cost: shadow.total_cost(),
+            expected_profit: shadow.expected_profit(),
+        };
+        drop(shadow);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        println!(
+            "Run {}: {} fills | Deployed ${:.2} | Expected ${:.4}",
+            i, outcome.fills, outcome.total_cost, outcome.expected_profit
+        );
+        outcomes.push(outcome);
     }
 
+    let deployed: Decimal = outcomes.iter().map(|o| o.total_cost).sum();
+    let expected: f64 = outcomes.iter().map(|o| o.expected_profit).sum();
+    let active = outcomes.iter().filter(|o| o.fills > 0).count();
     println!("🏁 Simulation Complete!");
-    println!("   Total Runs: {}", iterations);
-    println!("   Total Volume: ${:.2}", total_pnl);
-    println!("   Active runs: {} | Inactive runs: {}", wins, losses);
+    println!("   Total Runs: {}", outcomes.len());
+    println!("   Total Volume: ${:.2}", deployed);
+    println!("   Expected Profit: ${:.4}", expected);
+    println!(
+        "   Active runs: {} | Inactive runs: {}",
+        active,
+        outcomes.len() - active
+    );
+    outcomes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runs_vary_conditions_and_stay_in_shadow_mode() {
+        let mut base = Config::default_config();
+        base.orders.submit = true;
+        base.webhook.url = "https://example.com/hook".to_string();
+
+        let first = run_config(&base, 1);
+        let fourth = run_config(&base, 4);
+        assert_eq!(first.timing.latency_base_ms, 51);
+        assert_eq!(fourth.trading.competitor_arrival_rate, 4.0);
+        assert!(first.trading.competitor_arrival_rate < fourth.trading.competitor_arrival_rate);
+
+        assert!(first.trading.shadow_mode);
+        assert!(!first.orders.submit);
+        assert!(first.webhook.url.is_empty());
+    }
 }
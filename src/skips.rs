@@ -0,0 +1,192 @@
+//! Skipped signal analytics
+//!
+//! Counts every detected signal that did not trade, by the reason it was
+//! dropped, so users can tell whether they are missing trades to limits,
+//! thresholds, risk checks, or data problems.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Why a signal was dropped before (or during) execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SkipReason {
+    /// Edge below the mode's minimum edge
+    BelowMinEdge,
+    /// Allowance left on the permission grant is too small
+    InsufficientAllowance,
+    /// The strategy's share of the daily limit is used up
+    BudgetExhausted,
+    /// Outside the grant's per-trade, contract, or category scope
+    OutOfScope,
+    /// On-chain USDC balance below the trade cost
+    InsufficientBalance,
+    /// No price or book to size and fill the legs against
+    NoLiquidity,
+    /// Spread volatility above the configured maximum
+    Volatility,
+    /// Rejected (or errored) in the user filter script
+    FilterScript,
+    /// Would push net exposure over the cap
+    ExposureCap,
+    /// A position is already open on every leg
+    PositionOpen,
+    /// Trading suspended (no permission, safe mode, stale data, latency)
+    Suspended,
+    /// Gone by execution: too old for its edge, or no longer priced
+    /// through the payout on fresh books
+    StaleSignal,
+    /// The book moved past the slippage limit since the signal
+    Slippage,
+    /// The pipeline was too backed up to queue it
+    QueueFull,
+    /// Over the per-cycle or per-minute trade cap
+    TradeCap,
+    /// Entries paused after a losing streak
+    LosingStreak,
+    /// Market liquidity or 24h volume below the minimum
+    ThinMarket,
+}
+
+impl SkipReason {
+    pub const ALL: [SkipReason; 17] = [
+        Self::BelowMinEdge,
+        Self::InsufficientAllowance,
+        Self::BudgetExhausted,
+        Self::OutOfScope,
+        Self::InsufficientBalance,
+        Self::NoLiquidity,
+        Self::Volatility,
+        Self::FilterScript,
+        Self::ExposureCap,
+        Self::PositionOpen,
+        Self::Suspended,
+        Self::StaleSignal,
+        Self::Slippage,
+        Self::QueueFull,
+        Self::TradeCap,
+        Self::LosingStreak,
+        Self::ThinMarket,
+    ];
+
+    /// Label used in API output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BelowMinEdge => "below_min_edge",
+            Self::InsufficientAllowance => "insufficient_allowance",
+            Self::BudgetExhausted => "budget_exhausted",
+            Self::OutOfScope => "out_of_scope",
+            Self::InsufficientBalance => "insufficient_balance",
+            Self::NoLiquidity => "no_liquidity",
+            Self::Volatility => "volatility",
+            Self::FilterScript => "filter_script",
+            Self::ExposureCap => "exposure_cap",
+            Self::PositionOpen => "position_open",
+            Self::Suspended => "suspended",
+            Self::StaleSignal => "stale_signal",
+            Self::Slippage => "slippage",
+            Self::QueueFull => "queue_full",
+            Self::TradeCap => "trade_cap",
+            Self::LosingStreak => "losing_streak",
+            Self::ThinMarket => "thin_market",
+        }
+    }
+
+    /// Broad cause: "limit", "threshold", "risk", or "data"
+    pub fn category(&self) -> &'static str {
+        match self {
+            Self::InsufficientAllowance
+            | Self::BudgetExhausted
+            | Self::OutOfScope
+            | Self::InsufficientBalance
+            | Self::TradeCap => "limit",
+            Self::BelowMinEdge => "threshold",
+            Self::Volatility
+            | Self::FilterScript
+            | Self::ExposureCap
+            | Self::PositionOpen
+            | Self::Slippage
+            | Self::LosingStreak
+            | Self::ThinMarket => "risk",
+            Self::NoLiquidity | Self::Suspended | Self::StaleSignal | Self::QueueFull => "data",
+        }
+    }
+}
+
+/// Skip counts since startup
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkipSnapshot {
+    pub total: u64,
+    pub by_reason: HashMap<&'static str, u64>,
+    pub by_category: HashMap<&'static str, u64>,
+}
+
+/// Skip counters shared between the workers, pipeline, and API
+#[derive(Debug, Default)]
+pub struct SkipCounter {
+    counts: Mutex<HashMap<SkipReason, u64>>,
+}
+
+impl SkipCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count one skipped signal
+    pub fn record(&self, reason: SkipReason) {
+        *self.counts.lock().unwrap().entry(reason).or_default() += 1;
+    }
+
+    /// Skips so far for one reason
+    pub fn count(&self, reason: SkipReason) -> u64 {
+        self.counts
+            .lock()
+            .unwrap()
+            .get(&reason)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Counts for every reason (zeros included) and per category
+    pub fn snapshot(&self) -> SkipSnapshot {
+        let counts = self.counts.lock().unwrap();
+        let mut snapshot = SkipSnapshot {
+            total: 0,
+            by_reason: HashMap::new(),
+            by_category: HashMap::new(),
+        };
+        for reason in SkipReason::ALL {
+            let count = counts.get(&reason).copied().unwrap_or(0);
+            snapshot.total += count;
+            snapshot.by_reason.insert(reason.as_str(), count);
+            *snapshot.by_category.entry(reason.category()).or_default() += count;
+        }
+        snapshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_by_reason_and_category() {
+        let skips = SkipCounter::new();
+        skips.record(SkipReason::BelowMinEdge);
+        skips.record(SkipReason::BelowMinEdge);
+        skips.record(SkipReason::BudgetExhausted);
+        skips.record(SkipReason::InsufficientAllowance);
+
+        assert_eq!(skips.count(SkipReason::BelowMinEdge), 2);
+        assert_eq!(skips.count(SkipReason::NoLiquidity), 0);
+
+        let snapshot = skips.snapshot();
+        assert_eq!(snapshot.total, 4);
+        assert_eq!(snapshot.by_reason["below_min_edge"], 2);
+        assert_eq!(snapshot.by_reason["queue_full"], 0);
+        assert_eq!(snapshot.by_reason.len(), SkipReason::ALL.len());
+        assert_eq!(snapshot.by_category["limit"], 2);
+        assert_eq!(snapshot.by_category["threshold"], 2);
+        assert_eq!(snapshot.by_category["data"], 0);
+    }
+}
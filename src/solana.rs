@@ -1,32 +1,279 @@
-use solana_client::rpc_client::RpcClient;
+//! Solana RPC
+//!
+//! Connection to the Solana cluster set under `[solana]`: RPC endpoint,
+//! commitment level, and the compute-budget priority fee prepended to every
+//! transaction the agent submits. The client is nonblocking and every call
+//! is bounded by `timeout_secs`, so a slow cluster can't stall the runtime.
+//! Fees paid are counted per UTC day against `daily_limit_sol`.
+
+use crate::config::SolanaConfig;
+use crate::wallet::Wallet;
+use serde::{Deserialize, Serialize};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
 use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::native_token::lamports_to_sol;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
 use std::error::Error;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+pub type SolanaError = Box<dyn Error + Send + Sync>;
+
+/// How settled a transaction must be before it counts
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Commitment {
+    /// Seen by the connected node; may still be dropped
+    Processed,
+    /// Voted on by a supermajority
+    Confirmed,
+    /// Rooted; cannot be rolled back
+    Finalized,
+}
+
+impl Commitment {
+    pub fn config(self) -> CommitmentConfig {
+        match self {
+            Self::Processed => CommitmentConfig::processed(),
+            Self::Confirmed => CommitmentConfig::confirmed(),
+            Self::Finalized => CommitmentConfig::finalized(),
+        }
+    }
+}
+
+/// Wallet balance and fee spend on Solana
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SolanaAccount {
+    pub address: String,
+    /// SOL balance
+    pub sol_balance: f64,
+    /// SOL paid in transaction fees today
+    pub spent_today: f64,
+    /// SOL allowed in fees per day (0 is unlimited)
+    pub daily_limit: f64,
+    /// Unix time of the read
+    pub updated_at: u64,
+}
+
+/// Latest Solana snapshot, shared with the API
+pub type SolanaState = Arc<RwLock<Option<SolanaAccount>>>;
+
+/// Keypair from a base58-encoded secret key (the format wallets export)
+pub fn parse_keypair(secret: &str) -> Result<Keypair, SolanaError> {
+    let bytes = solana_sdk::bs58::decode(secret.trim()).into_vec()?;
+    Ok(Keypair::from_bytes(&bytes)?)
+}
 
 pub struct SolanaManager {
     client: RpcClient,
+    /// Price per compute unit in micro-lamports (0 adds no priority fee)
+    priority_fee_micro_lamports: u64,
+    /// Compute units requested per transaction (0 keeps the runtime default)
+    compute_unit_limit: u32,
+    /// Bound on each call, confirmation included
+    timeout: Duration,
+    /// Fees allowed per day in lamports (0 is unlimited)
+    daily_limit: u64,
+    /// Day number and lamports paid in fees that day
+    fees: Mutex<(u64, u64)>,
 }
 
 impl SolanaManager {
-    /// Connect to Solana Devnet
-    pub fn new() -> Self {
-        // Use standard Devnet URL
-        let url = "https://api.devnet.solana.com".to_string();
-        // Commitment: confirmed is usually good balance of speed/safety for bots
-        let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+    /// Connect to the configured cluster
+    pub fn new(config: &SolanaConfig) -> Self {
+        let timeout = Duration::from_secs(config.timeout_secs);
+        let client = RpcClient::new_with_timeout_and_commitment(
+            config.rpc_url.clone(),
+            timeout,
+            config.commitment.config(),
+        );
+
+        Self {
+            client,
+            priority_fee_micro_lamports: config.priority_fee_micro_lamports,
+            compute_unit_limit: config.compute_unit_limit,
+            timeout,
+            daily_limit: solana_sdk::native_token::sol_to_lamports(config.daily_limit_sol),
+            fees: Mutex::new((0, 0)),
+        }
+    }
 
-        Self { client }
+    /// Run an RPC call, giving up after the configured timeout
+    async fn bounded<T, E: Into<SolanaError>>(
+        &self,
+        call: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, SolanaError> {
+        match tokio::time::timeout(self.timeout, call).await {
+            Ok(result) => result.map_err(Into::into),
+            Err(_) => Err(format!("Solana RPC timed out after {:?}", self.timeout).into()),
+        }
     }
 
     /// Verify connection by fetching cluster version
-    pub fn check_connection(&self) -> Result<String, Box<dyn Error>> {
-        let version = self.client.get_version()?;
+    pub async fn check_connection(&self) -> Result<String, SolanaError> {
+        let version = self.bounded(self.client.get_version()).await?;
         Ok(version.solana_core)
     }
 
+    /// SOL balance of `address`
+    pub async fn balance(&self, address: &Pubkey) -> Result<f64, SolanaError> {
+        let lamports = self.bounded(self.client.get_balance(address)).await?;
+        Ok(lamports_to_sol(lamports))
+    }
+
+    /// Data of every account owned by `program` that starts with `discriminator`
+    pub async fn program_accounts(
+        &self,
+        program: &Pubkey,
+        discriminator: &[u8],
+    ) -> Result<Vec<(Pubkey, Vec<u8>)>, SolanaError> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                0,
+                discriminator.to_vec(),
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                // base58 (the default) refuses accounts over 128 bytes
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            with_context: None,
+        };
+        let accounts = self
+            .bounded(
+                self.client
+                    .get_program_accounts_with_config(program, config),
+            )
+            .await?;
+        Ok(accounts
+            .into_iter()
+            .map(|(address, account)| (address, account.data))
+            .collect())
+    }
+
+    /// Lamports paid in fees on the day containing `now`
+    fn fees_on(&self, now: u64) -> u64 {
+        let (day, lamports) = *self.fees.lock().unwrap();
+        if day == now / 86400 {
+            lamports
+        } else {
+            0
+        }
+    }
+
+    /// Count `lamports` against the day containing `now`, refusing them if
+    /// they would take the day past the limit
+    fn reserve_fee(&self, now: u64, lamports: u64) -> Result<(), SolanaError> {
+        let mut fees = self.fees.lock().unwrap();
+        if fees.0 != now / 86400 {
+            *fees = (now / 86400, 0);
+        }
+        if self.daily_limit > 0 && fees.1 + lamports > self.daily_limit {
+            return Err(format!(
+                "fee of {} SOL exceeds the daily Solana limit ({} of {} SOL spent)",
+                lamports_to_sol(lamports),
+                lamports_to_sol(fees.1),
+                lamports_to_sol(self.daily_limit)
+            )
+            .into());
+        }
+        fees.1 += lamports;
+        Ok(())
+    }
+
+    /// Give back a fee reserved for a transaction that didn't land
+    fn release_fee(&self, now: u64, lamports: u64) {
+        let mut fees = self.fees.lock().unwrap();
+        if fees.0 == now / 86400 {
+            fees.1 = fees.1.saturating_sub(lamports);
+        }
+    }
+
+    /// SOL paid in fees today
+    pub fn spent_today(&self) -> f64 {
+        lamports_to_sol(self.fees_on(Wallet::current_timestamp()))
+    }
+
+    /// Compute-budget instructions prepended to every transaction
+    pub fn budget_instructions(&self) -> Vec<Instruction> {
+        let mut instructions = Vec::new();
+        if self.compute_unit_limit > 0 {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(
+                self.compute_unit_limit,
+            ));
+        }
+        if self.priority_fee_micro_lamports > 0 {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(
+                self.priority_fee_micro_lamports,
+            ));
+        }
+        instructions
+    }
+
+    /// Sign and submit `instructions` with the configured priority fee,
+    /// waiting for the configured commitment; refused once the fee would
+    /// take today's spend past the daily limit
+    pub async fn send_transaction(
+        &self,
+        payer: &Keypair,
+        instructions: &[Instruction],
+    ) -> Result<Signature, SolanaError> {
+        let mut all = self.budget_instructions();
+        all.extend_from_slice(instructions);
+        let blockhash = self.bounded(self.client.get_latest_blockhash()).await?;
+        let transaction =
+            Transaction::new_signed_with_payer(&all, Some(&payer.pubkey()), &[payer], blockhash);
+        let fee = self
+            .bounded(self.client.get_fee_for_message(&transaction.message))
+            .await?;
+        // Reserved before sending so concurrent transactions can't both
+        // squeeze under the limit
+        let now = Wallet::current_timestamp();
+        self.reserve_fee(now, fee)?;
+        let signature = self
+            .bounded(self.client.send_and_confirm_transaction(&transaction))
+            .await
+            .inspect_err(|_| self.release_fee(now, fee))?;
+        Ok(signature)
+    }
+
+    /// Refresh the shared snapshot for `wallet` forever on a fixed interval
+    pub async fn run_periodic(
+        self: Arc<Self>,
+        wallet: Pubkey,
+        state: SolanaState,
+        interval: Duration,
+    ) {
+        loop {
+            match self.balance(&wallet).await {
+                Ok(sol_balance) => {
+                    *state.write().await = Some(SolanaAccount {
+                        address: wallet.to_string(),
+                        sol_balance,
+                        spent_today: self.spent_today(),
+                        daily_limit: lamports_to_sol(self.daily_limit),
+                        updated_at: Wallet::current_timestamp(),
+                    })
+                }
+                Err(e) => println!("⚠️ [Solana] Balance read failed: {}", e),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
     /// (Mock) Get demo wallet balance or real if pubkey provided
     /// For this hackathon, we just show we *can* talk to the chain.
     #[allow(dead_code)]
-    pub fn get_demo_balance(&self) -> Result<f64, Box<dyn Error>> {
+    pub fn get_demo_balance(&self) -> Result<f64, SolanaError> {
         // Just checking a known active devnet account or random would be flaky if empty.
         // For the demo "Readiness", getting the version is the proof of connectivity.
         // But let's implement a dummy balance fetch for a known faucet or similar if we wanted.
@@ -34,3 +281,68 @@ impl SolanaManager {
         Ok(0.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_instructions_follow_config() {
+        let mut config = SolanaConfig::default();
+        assert_eq!(config.commitment.config(), CommitmentConfig::confirmed());
+        assert!(SolanaManager::new(&config).budget_instructions().is_empty());
+
+        config.priority_fee_micro_lamports = 5_000;
+        config.compute_unit_limit = 200_000;
+        let instructions = SolanaManager::new(&config).budget_instructions();
+        assert_eq!(
+            instructions,
+            vec![
+                ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+                ComputeBudgetInstruction::set_compute_unit_price(5_000),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fees_count_per_day() {
+        let manager = SolanaManager::new(&SolanaConfig::default());
+        manager.reserve_fee(86_400 + 10, 5_000).unwrap();
+        manager.reserve_fee(86_400 + 20, 7_000).unwrap();
+        assert_eq!(manager.fees_on(86_400 + 30), 12_000);
+        assert_eq!(manager.fees_on(2 * 86_400), 0);
+        manager.reserve_fee(2 * 86_400, 1_000).unwrap();
+        assert_eq!(manager.fees_on(2 * 86_400 + 1), 1_000);
+        manager.release_fee(2 * 86_400 + 2, 1_000);
+        assert_eq!(manager.fees_on(2 * 86_400 + 3), 0);
+    }
+
+    #[test]
+    fn test_fees_past_daily_limit_are_refused() {
+        let config = SolanaConfig {
+            daily_limit_sol: 0.00001,
+            ..SolanaConfig::default()
+        };
+        let manager = SolanaManager::new(&config);
+        manager.reserve_fee(86_400, 5_000).unwrap();
+        manager.reserve_fee(86_400, 5_000).unwrap();
+        assert!(manager.reserve_fee(86_400, 1).is_err());
+        assert_eq!(manager.fees_on(86_400), 10_000);
+        // A new day starts from zero
+        manager.reserve_fee(2 * 86_400, 5_000).unwrap();
+
+        let unlimited = SolanaManager::new(&SolanaConfig {
+            daily_limit_sol: 0.0,
+            ..SolanaConfig::default()
+        });
+        unlimited.reserve_fee(86_400, u64::MAX / 2).unwrap();
+    }
+
+    #[test]
+    fn test_parse_keypair() {
+        let keypair = Keypair::new();
+        let parsed = parse_keypair(&keypair.to_base58_string()).unwrap();
+        assert_eq!(parsed.pubkey(), keypair.pubkey());
+        assert!(parse_keypair("not a key").is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! Spread history module
+//!
+//! Rolling, timestamped history of each market's sum-to-one spread, shared
+//! by the mean-reversion strategy, the entry volatility filter, and the API.
+
+use crate::types::MarketId;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// One spread observation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SpreadSample {
+    /// Unix time in seconds
+    pub timestamp: u64,
+    pub spread: f64,
+}
+
+/// Mean and standard deviation over recent samples
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpreadStats {
+    pub mean: f64,
+    pub std: f64,
+    pub samples: usize,
+}
+
+/// Rolling spread history per market id
+#[derive(Debug)]
+pub struct SpreadHistory {
+    samples: Mutex<HashMap<MarketId, VecDeque<SpreadSample>>>,
+    /// Samples kept per market
+    capacity: usize,
+}
+
+impl SpreadHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: Mutex::new(HashMap::new()),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Record a market's spread at `timestamp`
+    pub fn record(&self, market_id: &MarketId, timestamp: u64, spread: f64) {
+        let mut samples = self.samples.lock().unwrap();
+        let history = samples.entry(market_id.clone()).or_default();
+        if history.len() == self.capacity {
+            history.pop_front();
+        }
+        history.push_back(SpreadSample { timestamp, spread });
+    }
+
+    /// All retained samples for a market, oldest first
+    pub fn samples(&self, market_id: &MarketId) -> Vec<SpreadSample> {
+        let samples = self.samples.lock().unwrap();
+        samples
+            .get(market_id)
+            .map(|h| h.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Stats over the last `window` samples (None until that many exist)
+    pub fn stats(&self, market_id: &MarketId, window: usize) -> Option<SpreadStats> {
+        let samples = self.samples.lock().unwrap();
+        let history = samples.get(market_id)?;
+        if window == 0 || history.len() < window {
+            return None;
+        }
+
+        let recent = history.iter().skip(history.len() - window);
+        let n = window as f64;
+        let mean = recent.clone().map(|s| s.spread).sum::<f64>() / n;
+        let variance = recent.map(|s| (s.spread - mean).powi(2)).sum::<f64>() / n;
+        Some(SpreadStats {
+            mean,
+            std: variance.sqrt(),
+            samples: window,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_is_bounded_and_ordered() {
+        let history = SpreadHistory::new(3);
+        for (ts, spread) in [(1, 0.01), (2, 0.02), (3, 0.03), (4, 0.04)] {
+            history.record(&"m1".into(), ts, spread);
+        }
+
+        let samples = history.samples(&"m1".into());
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[0].timestamp, 2);
+        assert_eq!(samples[2].spread, 0.04);
+        assert!(history.samples(&"m2".into()).is_empty());
+    }
+
+    #[test]
+    fn test_stats_use_most_recent_window() {
+        let history = SpreadHistory::new(10);
+        history.record(&"m1".into(), 1, 0.50);
+        history.record(&"m1".into(), 2, 0.01);
+        history.record(&"m1".into(), 3, 0.03);
+        assert!(history.stats(&"m1".into(), 4).is_none());
+
+        let stats = history.stats(&"m1".into(), 2).unwrap();
+        assert!((stats.mean - 0.02).abs() < 1e-12);
+        assert!((stats.std - 0.01).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,157 @@
+//! Storage module for PolyShark
+//!
+//! Persists agent state as JSON documents in a local data directory so
+//! it survives restarts.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// File-backed JSON document store
+#[derive(Debug, Clone)]
+pub struct Storage {
+    data_dir: PathBuf,
+}
+
+impl Storage {
+    /// Create a store rooted at `data_dir` (created on first write)
+    pub fn new(data_dir: impl AsRef<Path>) -> Self {
+        Self {
+            data_dir: data_dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Path of the document stored under `name`
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.data_dir.join(format!("{}.json", name))
+    }
+
+    /// Load a document, returning `Ok(None)` if it has never been saved
+    pub fn load<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, StorageError> {
+        let path = self.path(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| StorageError::Io(path.display().to_string(), e.to_string()))?;
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| StorageError::Parse(path.display().to_string(), e.to_string()))
+    }
+
+    /// Save a document, replacing any previous version atomically
+    pub fn save<T: Serialize>(&self, name: &str, value: &T) -> Result<(), StorageError> {
+        fs::create_dir_all(&self.data_dir)
+            .map_err(|e| StorageError::Io(self.data_dir.display().to_string(), e.to_string()))?;
+
+        let path = self.path(name);
+        let contents = serde_json::to_string_pretty(value)
+            .map_err(|e| StorageError::Parse(path.display().to_string(), e.to_string()))?;
+
+        // Write to a temp file first so a crash mid-write never corrupts the document
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, contents)
+            .map_err(|e| StorageError::Io(tmp_path.display().to_string(), e.to_string()))?;
+        fs::rename(&tmp_path, &path)
+            .map_err(|e| StorageError::Io(path.display().to_string(), e.to_string()))
+    }
+
+    /// Path of the append-only log stored under `name`
+    pub fn log_path(&self, name: &str) -> PathBuf {
+        self.data_dir.join(format!("{}.jsonl", name))
+    }
+
+    /// Append one record as a JSON line to a log
+    pub fn append<T: Serialize>(&self, name: &str, value: &T) -> Result<(), StorageError> {
+        fs::create_dir_all(&self.data_dir)
+            .map_err(|e| StorageError::Io(self.data_dir.display().to_string(), e.to_string()))?;
+
+        let path = self.log_path(name);
+        let line = serde_json::to_string(value)
+            .map_err(|e| StorageError::Parse(path.display().to_string(), e.to_string()))?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| writeln!(file, "{}", line))
+            .map_err(|e| StorageError::Io(path.display().to_string(), e.to_string()))
+    }
+
+    /// Read every record of a log, returning an empty list if it doesn't exist
+    pub fn read_log<T: DeserializeOwned>(&self, name: &str) -> Result<Vec<T>, StorageError> {
+        let path = self.log_path(name);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| StorageError::Io(path.display().to_string(), e.to_string()))?;
+        contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line)
+                    .map_err(|e| StorageError::Parse(path.display().to_string(), e.to_string()))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub enum StorageError {
+    Io(String, String),
+    Parse(String, String),
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "Storage I/O error: {} ({})", path, err),
+            Self::Parse(path, err) => write!(f, "Storage parse error: {} ({})", path, err),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_storage;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_load_missing_document() {
+        let storage = temp_storage("missing");
+        let loaded: Option<HashMap<String, f64>> = storage.load("nothing").unwrap();
+        assert!(loaded.is_none());
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let storage = temp_storage("roundtrip");
+        let mut doc = HashMap::new();
+        doc.insert("pnl".to_string(), 1.25);
+
+        storage.save("doc", &doc).unwrap();
+        let loaded: HashMap<String, f64> = storage.load("doc").unwrap().unwrap();
+        assert_eq!(loaded.get("pnl"), Some(&1.25));
+    }
+
+    #[test]
+    fn test_append_writes_json_lines() {
+        let storage = temp_storage("append");
+        storage.append("log", &vec![1]).unwrap();
+        storage.append("log", &vec![2, 3]).unwrap();
+
+        let contents = fs::read_to_string(storage.log_path("log")).unwrap();
+        assert_eq!(contents, "[1]\n[2,3]\n");
+
+        let records: Vec<Vec<u32>> = storage.read_log("log").unwrap();
+        assert_eq!(records, vec![vec![1], vec![2, 3]]);
+        assert!(storage.read_log::<u32>("nothing").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,201 @@
+//! Strategy module
+//!
+//! Pluggable trading strategies. Each strategy turns market snapshots into
+//! trade intents; the engine runs every enabled strategy on each tick and
+//! reports fills back to the strategy that asked for them.
+
+use crate::arb::ArbitrageDetector;
+use crate::config::{Config, StrategyParams};
+use crate::external::{ExternalSignalQueue, ExternalSignalStrategy};
+use crate::fees::FeeModel;
+use crate::gas::GasModel;
+use crate::mean_reversion::MeanReversionStrategy;
+use crate::order_spec::OrderType;
+use crate::spread_history::SpreadHistory;
+use crate::tape::TapeMetrics;
+use crate::types::{ExecutionResult, Market, MarketId, Side, TokenId};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// A trade a strategy wants executed
+#[derive(Debug, Clone, Serialize)]
+pub struct Intent {
+    /// Name of the strategy that produced it
+    pub strategy: &'static str,
+    pub market_id: MarketId,
+    /// Tokens to trade, one leg each
+    pub token_ids: Vec<TokenId>,
+    pub side: Side,
+    /// Size per leg
+    pub size: f64,
+    /// Market spread when the intent was produced
+    pub spread: f64,
+    /// Edge the strategy claims, checked against the mode's minimum edge
+    pub edge: f64,
+    /// Expected profit after costs (USDC)
+    pub expected_profit: f64,
+    /// How each leg meets the book
+    pub order_type: OrderType,
+}
+
+/// A trading strategy
+pub trait Strategy: std::fmt::Debug + Send + Sync {
+    /// Unique name, used in config and logs
+    fn name(&self) -> &'static str;
+
+    /// Produce intents from the latest market snapshot
+    fn scan(&self, markets: &[Market]) -> Vec<Intent>;
+
+    /// Called when one of this strategy's intents is filled
+    fn on_fill(&mut self, _fill: &ExecutionResult) {}
+
+    /// Called once per tick with fresh market data, before scanning
+    fn on_tick(&mut self, _markets: &[Market], _now: u64) {}
+
+    /// Called with a token's latest trade tape metrics, before scanning
+    fn on_trades(&mut self, _token_id: &TokenId, _metrics: &TapeMetrics) {}
+
+    /// Called when the strategy parameters are changed at runtime
+    fn set_params(&mut self, _params: &StrategyParams) {}
+}
+
+/// The set of enabled strategies
+#[derive(Debug, Default)]
+pub struct StrategyRegistry {
+    strategies: Vec<Box<dyn Strategy>>,
+}
+
+impl StrategyRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build the strategies enabled in config
+    pub fn from_config(
+        config: &Config,
+        gas_model: &GasModel,
+        fee_model: &FeeModel,
+        spread_history: &Arc<SpreadHistory>,
+        external_signals: &Arc<ExternalSignalQueue>,
+    ) -> Self {
+        let mut registry = Self::new();
+        if config.strategies.pure_arb.enabled {
+            registry = registry.with_strategy(Box::new(
+                ArbitrageDetector::new(
+                    config.trading.min_spread_threshold,
+                    config.trading.min_profit_threshold,
+                )
+                .with_gas_model(gas_model.clone())
+                .with_sizing(config.trading.trade_size, fee_model.clone()),
+            ));
+        }
+        let mean_reversion = &config.strategies.mean_reversion;
+        if mean_reversion.enabled {
+            registry = registry.with_strategy(Box::new(
+                MeanReversionStrategy::new(
+                    mean_reversion.window,
+                    mean_reversion.z_threshold,
+                    spread_history.clone(),
+                )
+                .with_gas_model(gas_model.clone())
+                .with_sizing(config.trading.trade_size, fee_model.clone()),
+            ));
+        }
+        if config.strategies.external.enabled {
+            registry = registry.with_strategy(Box::new(
+                ExternalSignalStrategy::new(external_signals.clone())
+                    .with_gas_model(gas_model.clone())
+                    .with_sizing(config.trading.trade_size, fee_model.clone()),
+            ));
+        }
+        registry
+    }
+
+    /// Add a strategy
+    pub fn with_strategy(mut self, strategy: Box<dyn Strategy>) -> Self {
+        self.strategies.push(strategy);
+        self
+    }
+
+    /// Names of the enabled strategies
+    pub fn names(&self) -> Vec<&'static str> {
+        self.strategies.iter().map(|s| s.name()).collect()
+    }
+
+    /// Feed fresh market data to every strategy
+    pub fn on_tick(&mut self, markets: &[Market], now: u64) {
+        for strategy in &mut self.strategies {
+            strategy.on_tick(markets, now);
+        }
+    }
+
+    /// Feed trade tape metrics to every strategy
+    pub fn on_trades(&mut self, token_id: &TokenId, metrics: &TapeMetrics) {
+        for strategy in &mut self.strategies {
+            strategy.on_trades(token_id, metrics);
+        }
+    }
+
+    /// Collect intents from every strategy
+    pub fn scan(&self, markets: &[Market]) -> Vec<Intent> {
+        self.strategies
+            .iter()
+            .flat_map(|s| s.scan(markets))
+            .collect()
+    }
+
+    /// Push runtime parameter changes to every strategy
+    pub fn set_params(&mut self, params: &StrategyParams) {
+        for strategy in &mut self.strategies {
+            strategy.set_params(params);
+        }
+    }
+
+    /// Route a fill back to the strategy that produced the intent
+    pub fn on_fill(&mut self, strategy: &str, fill: &ExecutionResult) {
+        if let Some(s) = self.strategies.iter_mut().find(|s| s.name() == strategy) {
+            s.on_fill(fill);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_respects_enabled_flags() {
+        let mut config = Config::default_config();
+        let history = Arc::new(SpreadHistory::new(10));
+        let external = Arc::new(ExternalSignalQueue::new(60));
+        let registry = StrategyRegistry::from_config(
+            &config,
+            &GasModel::free(),
+            &FeeModel::new(0, 0),
+            &history,
+            &external,
+        );
+        assert_eq!(registry.names(), vec!["pure_arb"]);
+
+        config.strategies.pure_arb.enabled = false;
+        config.strategies.mean_reversion.enabled = true;
+        let registry = StrategyRegistry::from_config(
+            &config,
+            &GasModel::free(),
+            &FeeModel::new(0, 0),
+            &history,
+            &external,
+        );
+        assert_eq!(registry.names(), vec!["mean_reversion"]);
+
+        config.strategies.external.enabled = true;
+        let registry = StrategyRegistry::from_config(
+            &config,
+            &GasModel::free(),
+            &FeeModel::new(0, 0),
+            &history,
+            &external,
+        );
+        assert_eq!(registry.names(), vec!["mean_reversion", "external"]);
+    }
+}
@@ -0,0 +1,145 @@
+//! Losing streak cooldown
+//!
+//! A run of losing exits usually means the market has changed under the
+//! strategy, not bad luck. After `max_losses` losing exits in a row, new
+//! entries pause for `cooldown_secs`; exits keep running. A winning or
+//! break-even exit ends the streak.
+
+use crate::config::LosingStreakConfig;
+use crate::money::Decimal;
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// A pause started by a losing streak
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreakPause {
+    /// Losing exits in the streak
+    pub losses: u32,
+    /// Unix time entries resume
+    pub resumes_at: u64,
+}
+
+/// Streak and pause state, for the API
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StreakStatus {
+    pub consecutive_losses: u32,
+    /// Losses in a row that pause entries (0 = never)
+    pub max_losses: u32,
+    pub paused: bool,
+    /// Countdown until entries resume, while paused
+    pub resumes_in_secs: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct StreakState {
+    consecutive_losses: u32,
+    paused_until: Option<u64>,
+}
+
+/// Counts losing exits in a row and pauses entries after too many; shared
+/// by the workers and the API
+#[derive(Debug)]
+pub struct LosingStreakGuard {
+    max_losses: u32,
+    cooldown_secs: u64,
+    state: Mutex<StreakState>,
+}
+
+impl LosingStreakGuard {
+    pub fn new(config: &LosingStreakConfig) -> Self {
+        Self {
+            max_losses: config.max_losses,
+            cooldown_secs: config.cooldown_secs,
+            state: Mutex::new(StreakState::default()),
+        }
+    }
+
+    /// Count one exit; returns the pause when it ends a streak of losses
+    pub fn record_exit(&self, pnl: Decimal, now: u64) -> Option<StreakPause> {
+        let mut state = self.state.lock().unwrap();
+        if pnl >= Decimal::ZERO {
+            state.consecutive_losses = 0;
+            return None;
+        }
+        state.consecutive_losses += 1;
+        if self.max_losses == 0 || state.consecutive_losses < self.max_losses {
+            return None;
+        }
+        let pause = StreakPause {
+            losses: state.consecutive_losses,
+            resumes_at: now + self.cooldown_secs,
+        };
+        state.consecutive_losses = 0;
+        state.paused_until = Some(pause.resumes_at);
+        Some(pause)
+    }
+
+    /// Seconds until entries resume, while paused
+    pub fn paused_for(&self, now: u64) -> Option<u64> {
+        self.state
+            .lock()
+            .unwrap()
+            .paused_until
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+    }
+
+    pub fn status(&self, now: u64) -> StreakStatus {
+        let resumes_in_secs = self.paused_for(now);
+        StreakStatus {
+            consecutive_losses: self.state.lock().unwrap().consecutive_losses,
+            max_losses: self.max_losses,
+            paused: resumes_in_secs.is_some(),
+            resumes_in_secs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::usdc;
+
+    #[test]
+    fn test_pauses_entries_after_losing_streak() {
+        let guard = LosingStreakGuard::new(&LosingStreakConfig {
+            max_losses: 3,
+            cooldown_secs: 600,
+        });
+
+        // A win ends the streak
+        assert_eq!(guard.record_exit(usdc(-1.0), 100), None);
+        assert_eq!(guard.record_exit(usdc(-1.0), 110), None);
+        assert_eq!(guard.record_exit(usdc(0.5), 120), None);
+        assert_eq!(guard.status(120).consecutive_losses, 0);
+
+        assert_eq!(guard.record_exit(usdc(-1.0), 130), None);
+        assert_eq!(guard.record_exit(usdc(-0.2), 140), None);
+        assert_eq!(
+            guard.record_exit(usdc(-0.1), 150),
+            Some(StreakPause {
+                losses: 3,
+                resumes_at: 750
+            })
+        );
+        assert_eq!(
+            guard.status(450),
+            StreakStatus {
+                consecutive_losses: 0,
+                max_losses: 3,
+                paused: true,
+                resumes_in_secs: Some(300),
+            }
+        );
+        assert_eq!(guard.paused_for(750), None);
+        assert!(!guard.status(800).paused);
+
+        // Disabled
+        let off = LosingStreakGuard::new(&LosingStreakConfig {
+            max_losses: 0,
+            cooldown_secs: 600,
+        });
+        assert!((0..10).all(|i| off.record_exit(usdc(-1.0), i).is_none()));
+        assert_eq!(off.paused_for(5), None);
+    }
+}
@@ -0,0 +1,160 @@
+//! Trade tape
+//!
+//! Keeps the most recent trades per token from the market channel and derives
+//! order-flow metrics (imbalance, last-trade direction, realized volatility)
+//! for strategies and the dashboard.
+
+use crate::types::{Side, TokenId};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// One executed trade
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Trade {
+    pub price: f64,
+    pub size: f64,
+    /// Aggressor side
+    pub side: Side,
+    /// Unix time in milliseconds
+    pub timestamp: u64,
+}
+
+/// Order-flow metrics over a token's recent trades
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TapeMetrics {
+    pub trades: usize,
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    /// (buy - sell) / (buy + sell), in [-1, 1]
+    pub imbalance: f64,
+    pub last_price: f64,
+    pub last_side: Side,
+    /// Standard deviation of log returns between consecutive trades
+    pub realized_volatility: f64,
+}
+
+/// Recent trades per token, shared between the stream, workers, and API
+#[derive(Debug)]
+pub struct TradeTape {
+    trades: Mutex<HashMap<TokenId, VecDeque<Trade>>>,
+    /// Trades kept per token
+    max_trades: usize,
+}
+
+impl TradeTape {
+    pub fn new(max_trades: usize) -> Self {
+        Self {
+            trades: Mutex::new(HashMap::new()),
+            max_trades: max_trades.max(1),
+        }
+    }
+
+    /// Append a trade to a token's tape
+    pub fn record(&self, token_id: &TokenId, trade: Trade) {
+        let mut trades = self.trades.lock().unwrap();
+        let tape = trades.entry(token_id.clone()).or_default();
+        if tape.len() == self.max_trades {
+            tape.pop_front();
+        }
+        tape.push_back(trade);
+    }
+
+    /// Most recent trades for a token, oldest first
+    pub fn recent(&self, token_id: &TokenId) -> Vec<Trade> {
+        let trades = self.trades.lock().unwrap();
+        trades
+            .get(token_id)
+            .map(|t| t.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Order-flow metrics for a token (None until it has trades)
+    pub fn metrics(&self, token_id: &TokenId) -> Option<TapeMetrics> {
+        let trades = self.trades.lock().unwrap();
+        let tape = trades.get(token_id)?;
+        let last = tape.back()?;
+
+        let volume =
+            |side: Side| -> f64 { tape.iter().filter(|t| t.side == side).map(|t| t.size).sum() };
+        let buy_volume = volume(Side::Buy);
+        let sell_volume = volume(Side::Sell);
+        let total = buy_volume + sell_volume;
+
+        let returns: Vec<f64> = tape
+            .iter()
+            .zip(tape.iter().skip(1))
+            .filter(|(a, b)| a.price > 0.0 && b.price > 0.0)
+            .map(|(a, b)| (b.price / a.price).ln())
+            .collect();
+        let realized_volatility = if returns.len() < 2 {
+            0.0
+        } else {
+            let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+            let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>()
+                / (returns.len() - 1) as f64;
+            variance.sqrt()
+        };
+
+        Some(TapeMetrics {
+            trades: tape.len(),
+            buy_volume,
+            sell_volume,
+            imbalance: if total > 0.0 {
+                (buy_volume - sell_volume) / total
+            } else {
+                0.0
+            },
+            last_price: last.price,
+            last_side: last.side,
+            realized_volatility,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(price: f64, size: f64, side: Side, timestamp: u64) -> Trade {
+        Trade {
+            price,
+            size,
+            side,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_metrics() {
+        let tape = TradeTape::new(10);
+        assert!(tape.metrics(&"t1".into()).is_none());
+
+        tape.record(&"t1".into(), trade(0.50, 30.0, Side::Buy, 1));
+        tape.record(&"t1".into(), trade(0.51, 10.0, Side::Buy, 2));
+        tape.record(&"t1".into(), trade(0.50, 20.0, Side::Sell, 3));
+
+        let m = tape.metrics(&"t1".into()).unwrap();
+        assert_eq!(m.trades, 3);
+        assert_eq!(m.buy_volume, 40.0);
+        assert_eq!(m.sell_volume, 20.0);
+        assert!((m.imbalance - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(m.last_side, Side::Sell);
+        assert_eq!(m.last_price, 0.50);
+        assert!(m.realized_volatility > 0.0);
+    }
+
+    #[test]
+    fn test_tape_keeps_most_recent_trades() {
+        let tape = TradeTape::new(2);
+        for ts in 1..=3 {
+            tape.record(&"t1".into(), trade(0.5, 1.0, Side::Buy, ts));
+        }
+
+        let recent = tape.recent(&"t1".into());
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].timestamp, 2);
+        // Flat prices: no volatility
+        assert_eq!(tape.metrics(&"t1".into()).unwrap().realized_volatility, 0.0);
+    }
+}
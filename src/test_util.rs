@@ -0,0 +1,63 @@
+//! Fixtures shared by the unit tests
+
+use crate::storage::Storage;
+use crate::types::Market;
+
+/// Binary market "m1" with tokens "yes" and "no" at the given prices
+///
+/// Fee-free, with no quotes and unknown liquidity; tests override what they
+/// care about with struct update syntax.
+pub fn market(yes_price: f64, no_price: f64) -> Market {
+    Market {
+        id: "m1".into(),
+        question: "Q".to_string(),
+        slug: "q".to_string(),
+        outcomes: vec!["Yes".to_string(), "No".to_string()],
+        outcome_prices: vec![yes_price, no_price],
+        clob_token_ids: vec!["yes".into(), "no".into()],
+        best_bid: None,
+        best_ask: None,
+        maker_base_fee: 0,
+        taker_base_fee: 0,
+        liquidity: None,
+        volume_24hr: None,
+        active: true,
+        accepting_orders: true,
+        condition_id: String::new(),
+        category: String::new(),
+        tick_size: 0.0,
+        min_order_size: 0.0,
+        outcome_quotes: Vec::new(),
+    }
+}
+
+/// Market `id` with tokens "{id}-yes" and "{id}-no" at the given prices
+pub fn named_market(id: &str, prices: &[f64]) -> Market {
+    Market {
+        id: id.into(),
+        question: format!("{}?", id),
+        slug: id.to_string(),
+        outcome_prices: prices.to_vec(),
+        clob_token_ids: vec![format!("{}-yes", id).into(), format!("{}-no", id).into()],
+        ..market(0.5, 0.5)
+    }
+}
+
+/// Market "m1" with tokens "t1" and "t2", a 2% taker fee, and reported
+/// liquidity and volume
+pub fn fee_market(yes_price: f64, no_price: f64) -> Market {
+    Market {
+        clob_token_ids: vec!["t1".into(), "t2".into()],
+        taker_base_fee: 200,
+        liquidity: Some(1000.0),
+        volume_24hr: Some(5000.0),
+        ..market(yes_price, no_price)
+    }
+}
+
+/// Empty storage in a per-process temp directory
+pub fn temp_storage(name: &str) -> Storage {
+    let dir = std::env::temp_dir().join(format!("polyshark_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    Storage::new(dir)
+}
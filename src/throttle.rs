@@ -0,0 +1,179 @@
+//! Execution budget
+//!
+//! Caps how many trades go out per discovery cycle and per rolling minute,
+//! so a burst of signals (or a data glitch that produces many false ones)
+//! cannot spend the daily allowance in a single cycle. Both caps are shared
+//! by every worker and taken as trades clear risk checks, so intents risk
+//! filtering drops never use them up; workers send a tick's intents best
+//! edge first. Trades dropped before anything is filled or posted give
+//! their slots back. The engine resets the cycle cap each discovery cycle.
+
+use crate::config::SafetyConfig;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Window of the per-minute cap
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Trade caps shared by the engine and the pipeline
+#[derive(Debug)]
+pub struct TradeThrottle {
+    /// Trades all workers together may send out per discovery cycle (0 = no cap)
+    max_per_cycle: usize,
+    /// Trades admitted in any minute (0 = no cap)
+    max_per_minute: usize,
+    slots: Mutex<Slots>,
+}
+
+#[derive(Debug)]
+struct Slots {
+    /// When the current discovery cycle started
+    cycle_started: Instant,
+    /// Trades admitted since it started
+    cycle_taken: usize,
+    /// When each trade in the minute window was admitted
+    admitted: VecDeque<Instant>,
+}
+
+/// The cap a trade was refused by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapReached {
+    Cycle,
+    Minute,
+}
+
+impl TradeThrottle {
+    pub fn new(config: &SafetyConfig) -> Self {
+        Self {
+            max_per_cycle: config.max_trades_per_cycle,
+            max_per_minute: config.max_trades_per_minute,
+            slots: Mutex::new(Slots {
+                cycle_started: Instant::now(),
+                cycle_taken: 0,
+                admitted: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Reset the cycle cap; called by the engine each discovery cycle
+    pub fn start_cycle(&self, now: Instant) {
+        let mut slots = self.slots.lock().unwrap();
+        slots.cycle_started = now;
+        slots.cycle_taken = 0;
+    }
+
+    /// Take a slot in the cycle cap and one in the per-minute cap, or say
+    /// which is used up (taking neither)
+    pub fn admit(&self, now: Instant) -> Result<(), CapReached> {
+        let mut slots = self.slots.lock().unwrap();
+        if self.max_per_cycle > 0 && slots.cycle_taken >= self.max_per_cycle {
+            return Err(CapReached::Cycle);
+        }
+        if self.max_per_minute > 0 {
+            while slots
+                .admitted
+                .front()
+                .is_some_and(|at| now.saturating_duration_since(*at) >= WINDOW)
+            {
+                slots.admitted.pop_front();
+            }
+            if slots.admitted.len() >= self.max_per_minute {
+                return Err(CapReached::Minute);
+            }
+            slots.admitted.push_back(now);
+        }
+        slots.cycle_taken += 1;
+        Ok(())
+    }
+
+    /// Give back the slots admitted at `at`, for a trade that went no further
+    ///
+    /// The cycle slot only comes back while its cycle is still running.
+    pub fn refund(&self, at: Instant) {
+        let mut slots = self.slots.lock().unwrap();
+        if at >= slots.cycle_started {
+            slots.cycle_taken = slots.cycle_taken.saturating_sub(1);
+        }
+        if let Some(i) = slots
+            .admitted
+            .iter()
+            .position(|admitted_at| *admitted_at == at)
+        {
+            slots.admitted.remove(i);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caps_trades_per_cycle_and_minute() {
+        let throttle = TradeThrottle::new(&SafetyConfig {
+            max_trades_per_cycle: 3,
+            max_trades_per_minute: 10,
+            ..SafetyConfig::default()
+        });
+        let start = Instant::now();
+        throttle.start_cycle(start);
+
+        // Workers share the cycle's slots
+        assert_eq!(throttle.admit(start), Ok(()));
+        assert_eq!(throttle.admit(start), Ok(()));
+        assert_eq!(throttle.admit(start + Duration::from_secs(1)), Ok(()));
+        assert_eq!(
+            throttle.admit(start + Duration::from_secs(2)),
+            Err(CapReached::Cycle)
+        );
+        // A trade dropped before filling gives its slot back
+        throttle.refund(start + Duration::from_secs(1));
+        assert_eq!(throttle.admit(start + Duration::from_secs(2)), Ok(()));
+        assert_eq!(
+            throttle.admit(start + Duration::from_secs(3)),
+            Err(CapReached::Cycle)
+        );
+
+        // A new cycle starts empty; refunds from the last one don't add to it
+        throttle.start_cycle(start + Duration::from_secs(5));
+        throttle.refund(start);
+        assert_eq!(throttle.admit(start + Duration::from_secs(6)), Ok(()));
+        assert_eq!(throttle.admit(start + Duration::from_secs(6)), Ok(()));
+        assert_eq!(throttle.admit(start + Duration::from_secs(6)), Ok(()));
+        assert_eq!(
+            throttle.admit(start + Duration::from_secs(6)),
+            Err(CapReached::Cycle)
+        );
+    }
+
+    #[test]
+    fn test_caps_trades_per_minute() {
+        let throttle = TradeThrottle::new(&SafetyConfig {
+            max_trades_per_cycle: 0,
+            max_trades_per_minute: 3,
+            ..SafetyConfig::default()
+        });
+        let start = Instant::now();
+        assert!(throttle.admit(start).is_ok());
+        assert!(throttle.admit(start).is_ok());
+        assert!(throttle.admit(start + Duration::from_secs(30)).is_ok());
+        assert_eq!(
+            throttle.admit(start + Duration::from_secs(40)),
+            Err(CapReached::Minute)
+        );
+        throttle.refund(start + Duration::from_secs(30));
+        assert!(throttle.admit(start + Duration::from_secs(40)).is_ok());
+        // The first two slots age out of the window
+        assert!(throttle.admit(start + WINDOW).is_ok());
+        assert!(throttle.admit(start + WINDOW).is_ok());
+        assert!(throttle.admit(start + WINDOW).is_err());
+
+        let uncapped = TradeThrottle::new(&SafetyConfig {
+            max_trades_per_cycle: 0,
+            max_trades_per_minute: 0,
+            ..SafetyConfig::default()
+        });
+        assert!((0..100).all(|_| uncapped.admit(start).is_ok()));
+    }
+}
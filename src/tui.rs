@@ -0,0 +1,523 @@
+//! Terminal UI
+//!
+//! Full-screen ratatui view of live markets, recent signals, open positions,
+//! the allowance gauge, and a scrolling event log, for operators who only
+//! have SSH. Agent logs are redirected to a file and tailed into the log pane.
+
+use crate::api::RecentSignal;
+use crate::money::{self, Decimal};
+use crate::types::TokenId;
+use crate::workers::{get_strategy_mode_name, WorkerContext};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Gauge, List, ListItem, Paragraph, Row, Table};
+use ratatui::{Frame, Terminal};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Market rows shown (most mispriced first)
+const MAX_MARKET_ROWS: usize = 50;
+
+/// One market row
+#[derive(Debug, Clone)]
+struct MarketRow {
+    question: String,
+    yes: f64,
+    no: f64,
+    spread: f64,
+}
+
+/// One open position row
+#[derive(Debug, Clone)]
+struct PositionRow {
+    token_id: TokenId,
+    size: f64,
+    entry_price: f64,
+    /// Latest outcome price, when the market is still cached
+    mark: Option<f64>,
+}
+
+/// Everything one frame draws, copied out of the shared state
+#[derive(Debug, Clone, Default)]
+struct Snapshot {
+    markets: Vec<MarketRow>,
+    signals: Vec<RecentSignal>,
+    positions: Vec<PositionRow>,
+    remaining: Decimal,
+    daily_limit: Decimal,
+    mode: &'static str,
+    trades: usize,
+    pnl: Decimal,
+    shadow_mode: bool,
+}
+
+/// Follows an append-only log file, keeping its last lines
+#[derive(Debug)]
+struct LogTail {
+    path: PathBuf,
+    offset: u64,
+    partial: String,
+    lines: VecDeque<String>,
+    capacity: usize,
+}
+
+impl LogTail {
+    fn new(path: &Path, capacity: usize) -> Self {
+        // Start at the end: earlier runs are not this session's events
+        let offset = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        Self {
+            path: path.to_path_buf(),
+            offset,
+            partial: String::new(),
+            lines: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Pick up whatever was appended since the last poll
+    fn poll(&mut self) {
+        let Ok(mut file) = File::open(&self.path) else {
+            return;
+        };
+        let mut appended = Vec::new();
+        if file.seek(SeekFrom::Start(self.offset)).is_err()
+            || file.read_to_end(&mut appended).is_err()
+        {
+            return;
+        }
+        self.offset += appended.len() as u64;
+        self.push(&String::from_utf8_lossy(&appended));
+    }
+
+    fn push(&mut self, text: &str) {
+        self.partial.push_str(text);
+        while let Some(newline) = self.partial.find('\n') {
+            let line: String = self.partial.drain(..=newline).collect();
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+            if self.lines.len() == self.capacity {
+                self.lines.pop_front();
+            }
+            self.lines.push_back(line.to_string());
+        }
+    }
+}
+
+/// Redirect stdout into `log_path` so agent output doesn't draw over the UI
+///
+/// Returns a handle to the original terminal for the UI to draw on.
+#[cfg(unix)]
+fn capture_stdout(log_path: &Path) -> io::Result<File> {
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+
+    if let Some(dir) = log_path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)?;
+    io::stdout().flush()?;
+    // SAFETY: plain fd duplication; the duplicate is owned by the returned File
+    unsafe {
+        let terminal = libc::dup(libc::STDOUT_FILENO);
+        if terminal < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if libc::dup2(log.as_raw_fd(), libc::STDOUT_FILENO) < 0 {
+            let err = io::Error::last_os_error();
+            libc::close(terminal);
+            return Err(err);
+        }
+        Ok(File::from_raw_fd(terminal))
+    }
+}
+
+#[cfg(not(unix))]
+fn capture_stdout(_log_path: &Path) -> io::Result<File> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the terminal UI needs a Unix terminal",
+    ))
+}
+
+/// Put stdout back on the terminal once the UI is gone
+#[cfg(unix)]
+fn restore_stdout(terminal: &File) {
+    use std::os::unix::io::AsRawFd;
+    let _ = io::stdout().flush();
+    // SAFETY: both descriptors are open for the duration of the call
+    unsafe {
+        libc::dup2(terminal.as_raw_fd(), libc::STDOUT_FILENO);
+    }
+}
+
+#[cfg(not(unix))]
+fn restore_stdout(_terminal: &File) {}
+
+/// Full-screen terminal dashboard
+pub struct Tui {
+    ctx: Arc<WorkerContext>,
+    log: LogTail,
+    refresh: Duration,
+}
+
+impl Tui {
+    pub fn new(ctx: Arc<WorkerContext>) -> Self {
+        let config = &ctx.config.tui;
+        let log = LogTail::new(Path::new(&config.log_file), config.log_lines);
+        let refresh = Duration::from_millis(config.refresh_ms);
+        Self { ctx, log, refresh }
+    }
+
+    /// Take over the terminal until the operator quits (q, Esc, or Ctrl-C)
+    pub async fn run(mut self) -> io::Result<()> {
+        let tty = capture_stdout(&self.log.path)?;
+        let result = self.draw_loop(&tty).await;
+        restore_stdout(&tty);
+        result
+    }
+
+    async fn draw_loop(&mut self, tty: &File) -> io::Result<()> {
+        let mut terminal = Terminal::new(CrosstermBackend::new(tty.try_clone()?))?;
+        enable_raw_mode()?;
+        execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+        terminal.clear()?;
+
+        let result = self.event_loop(&mut terminal).await;
+
+        disable_raw_mode()?;
+        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+        terminal.show_cursor()?;
+        result
+    }
+
+    async fn event_loop<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        let mut ticker = tokio::time::interval(self.refresh);
+        loop {
+            ticker.tick().await;
+            self.log.poll();
+            let snapshot = self.snapshot().await;
+            terminal.draw(|frame| draw(frame, &snapshot, &self.log.lines))?;
+
+            while event::poll(Duration::ZERO)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind != KeyEventKind::Press {
+                        continue;
+                    }
+                    let ctrl_c = key.modifiers.contains(KeyModifiers::CONTROL)
+                        && key.code == KeyCode::Char('c');
+                    if ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Copy what the frame needs out of the shared state
+    async fn snapshot(&self) -> Snapshot {
+        let ctx = &self.ctx;
+        let (remaining, daily_limit) = ctx.allowance().await;
+
+        let (mut markets, signals, marks) = {
+            let cache = ctx.market_cache.read().await;
+            let rows: Vec<MarketRow> = cache
+                .markets
+                .as_slice()
+                .iter()
+                .map(|m| MarketRow {
+                    question: m.question.clone(),
+                    yes: m.yes_price(),
+                    no: m.no_price(),
+                    spread: m.get_spread(),
+                })
+                .collect();
+            let marks: Vec<(TokenId, f64)> = cache
+                .markets
+                .as_slice()
+                .iter()
+                .flat_map(|m| {
+                    m.clob_token_ids
+                        .iter()
+                        .cloned()
+                        .zip(m.outcome_prices.iter().copied())
+                })
+                .collect();
+            let signals = cache.recent_signals.iter().rev().cloned().collect();
+            (rows, signals, marks)
+        };
+        markets.sort_by(|a, b| b.spread.total_cmp(&a.spread));
+        markets.truncate(MAX_MARKET_ROWS);
+
+        let pm = ctx.position_manager.read().await;
+        let positions = pm
+            .get_positions()
+            .into_iter()
+            .map(|p| PositionRow {
+                token_id: p.token_id.clone(),
+                size: p.size,
+                entry_price: p.entry_price,
+                mark: marks
+                    .iter()
+                    .find(|(token, _)| *token == p.token_id)
+                    .map(|(_, price)| *price),
+            })
+            .collect();
+
+        let strategy = ctx.strategy_config().await;
+        let strategy = ctx.tuner.lock().await.tuned(&strategy);
+        Snapshot {
+            markets,
+            signals,
+            positions,
+            remaining,
+            daily_limit,
+            mode: get_strategy_mode_name(remaining, daily_limit, &strategy),
+            trades: pm.trade_count(),
+            pnl: pm.total_pnl(),
+            shadow_mode: ctx.config.trading.shadow_mode,
+        }
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        text.to_string()
+    } else {
+        let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+        cut.push('…');
+        cut
+    }
+}
+
+fn titled(title: &str) -> Block<'_> {
+    Block::default().borders(Borders::ALL).title(title)
+}
+
+/// Lay out and draw one frame
+fn draw(frame: &mut Frame, snapshot: &Snapshot, log: &VecDeque<String>) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(8),
+            Constraint::Length(10),
+        ])
+        .split(frame.area());
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(rows[1]);
+    let right = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(middle[1]);
+
+    draw_header(frame, rows[0], snapshot);
+    draw_markets(frame, middle[0], snapshot);
+    draw_signals(frame, right[0], snapshot);
+    draw_positions(frame, right[1], snapshot);
+    draw_log(frame, rows[2], log);
+}
+
+fn draw_header(frame: &mut Frame, area: Rect, snapshot: &Snapshot) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(area);
+
+    let mode = if snapshot.shadow_mode {
+        "Shadow".to_string()
+    } else {
+        snapshot.mode.to_string()
+    };
+    let status = Paragraph::new(Line::from(vec![
+        Span::styled(
+            "🦈 PolyShark ",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(format!(
+            "| {} | {} trades | PnL ${:.2} | q to quit",
+            mode, snapshot.trades, snapshot.pnl
+        )),
+    ]))
+    .block(titled("Agent"));
+    frame.render_widget(status, columns[0]);
+
+    let ratio = if snapshot.daily_limit > Decimal::ZERO {
+        money::to_f64(snapshot.remaining / snapshot.daily_limit).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let color = if ratio < 0.3 {
+        Color::Red
+    } else if ratio > 0.7 {
+        Color::Green
+    } else {
+        Color::Yellow
+    };
+    let gauge = Gauge::default()
+        .block(titled("Allowance"))
+        .gauge_style(Style::default().fg(color))
+        .ratio(ratio)
+        .label(format!(
+            "${:.2} / ${:.2} left",
+            snapshot.remaining, snapshot.daily_limit
+        ));
+    frame.render_widget(gauge, columns[1]);
+}
+
+fn draw_markets(frame: &mut Frame, area: Rect, snapshot: &Snapshot) {
+    let question_width = area.width.saturating_sub(26).max(10) as usize;
+    let rows = snapshot.markets.iter().map(|m| {
+        Row::new(vec![
+            Cell::from(truncate(&m.question, question_width)),
+            Cell::from(format!("{:.3}", m.yes)),
+            Cell::from(format!("{:.3}", m.no)),
+            Cell::from(format!("{:.2}%", m.spread * 100.0)),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(10),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(7),
+        ],
+    )
+    .header(
+        Row::new(vec!["Market", "YES", "NO", "Spread"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(titled("Markets"));
+    frame.render_widget(table, area);
+}
+
+fn draw_signals(frame: &mut Frame, area: Rect, snapshot: &Snapshot) {
+    let items: Vec<ListItem> = snapshot
+        .signals
+        .iter()
+        .map(|s| {
+            ListItem::new(format!(
+                "[{}] {} spread {:.2}% exp ${:.2}",
+                s.strategy,
+                s.market_id,
+                s.spread * 100.0,
+                s.expected_profit
+            ))
+        })
+        .collect();
+    frame.render_widget(List::new(items).block(titled("Signals")), area);
+}
+
+fn draw_positions(frame: &mut Frame, area: Rect, snapshot: &Snapshot) {
+    let token_width = area.width.saturating_sub(30).max(8) as usize;
+    let rows = snapshot.positions.iter().map(|p| {
+        let (mark, pnl) = match p.mark {
+            Some(mark) => (
+                format!("{:.3}", mark),
+                format!("{:+.2}", (mark - p.entry_price) * p.size),
+            ),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        Row::new(vec![
+            Cell::from(truncate(p.token_id.as_str(), token_width)),
+            Cell::from(format!("{:.2}", p.size)),
+            Cell::from(format!("{:.3}", p.entry_price)),
+            Cell::from(mark),
+            Cell::from(pnl),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(8),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(7),
+        ],
+    )
+    .header(
+        Row::new(vec!["Token", "Size", "Entry", "Mark", "uPnL"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(titled("Positions"));
+    frame.render_widget(table, area);
+}
+
+fn draw_log(frame: &mut Frame, area: Rect, log: &VecDeque<String>) {
+    // Newest lines at the bottom, like a terminal
+    let visible = area.height.saturating_sub(2) as usize;
+    let lines: Vec<Line> = log
+        .iter()
+        .skip(log.len().saturating_sub(visible))
+        .map(|line| Line::raw(line.as_str()))
+        .collect();
+    frame.render_widget(Paragraph::new(lines).block(titled("Events")), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    #[test]
+    fn test_log_tail_keeps_last_complete_lines() {
+        let mut tail = LogTail::new(Path::new("/nonexistent/polyshark.log"), 2);
+        tail.push("one\ntwo\nthr");
+        assert_eq!(tail.lines, ["one", "two"]);
+        tail.push("ee\n\nfour\n");
+        assert_eq!(tail.lines, ["three", "four"]);
+    }
+
+    #[test]
+    fn test_draw_frame() {
+        let snapshot = Snapshot {
+            markets: vec![MarketRow {
+                question: "Will it rain?".to_string(),
+                yes: 0.45,
+                no: 0.50,
+                spread: 0.05,
+            }],
+            positions: vec![PositionRow {
+                token_id: "tok1".into(),
+                size: 10.0,
+                entry_price: 0.45,
+                mark: Some(0.50),
+            }],
+            remaining: money::usdc(2.5),
+            daily_limit: money::usdc(10.0),
+            mode: "Conservative",
+            ..Default::default()
+        };
+        let log = VecDeque::from(["✅ started".to_string()]);
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| draw(frame, &snapshot, &log)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let text: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("Will it rain?"));
+        assert!(text.contains("$2.50 / $10.00 left"));
+        assert!(text.contains("+0.50"));
+        assert!(text.contains("started"));
+    }
+}
@@ -0,0 +1,206 @@
+//! Min-edge auto-tuning
+//!
+//! Feedback controller over the strategy-mode min edges. Each closed trade is
+//! filed under the edge it was entered at; the trades that only just cleared
+//! a mode's threshold show whether that threshold is too loose (they lose
+//! money) or too tight (they win comfortably), and the threshold is nudged
+//! one step accordingly, within configured bounds.
+
+use crate::config::{StrategyConfig, TuningConfig};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+/// Storage log of every min-edge adjustment
+pub const TUNING_LOG: &str = "tuning";
+
+/// Strategy modes with a tunable min edge
+const MODES: [&str; 3] = ["conservative", "normal", "aggressive"];
+
+/// One closed trade
+#[derive(Debug, Clone, Copy)]
+struct Outcome {
+    edge: f64,
+    pnl: f64,
+}
+
+/// A min-edge change and the evidence behind it
+#[derive(Debug, Clone, Serialize)]
+pub struct Adjustment {
+    pub mode: &'static str,
+    pub from: f64,
+    pub to: f64,
+    /// Trades in the bucket just above the old threshold
+    pub trades: usize,
+    pub win_rate: f64,
+    pub avg_pnl: f64,
+}
+
+/// Nudges min edges toward what realized outcomes support
+#[derive(Debug)]
+pub struct EdgeTuner {
+    config: TuningConfig,
+    /// Current conservative, normal, and aggressive min edges
+    min_edges: [f64; 3],
+    /// Entry edge per open execution id
+    entries: HashMap<String, f64>,
+    /// Most recent closed trades, oldest first
+    outcomes: VecDeque<Outcome>,
+}
+
+impl EdgeTuner {
+    pub fn new(config: &TuningConfig, strategy: &StrategyConfig) -> Self {
+        Self {
+            config: config.clone(),
+            min_edges: [
+                strategy.conservative_min_edge,
+                strategy.normal_min_edge,
+                strategy.aggressive_min_edge,
+            ],
+            entries: HashMap::new(),
+            outcomes: VecDeque::new(),
+        }
+    }
+
+    /// Restart tuning from manually set min edges
+    pub fn reset(&mut self, strategy: &StrategyConfig) {
+        self.min_edges = [
+            strategy.conservative_min_edge,
+            strategy.normal_min_edge,
+            strategy.aggressive_min_edge,
+        ];
+    }
+
+    /// `strategy` with the tuned min edges in place of the configured ones
+    pub fn tuned(&self, strategy: &StrategyConfig) -> StrategyConfig {
+        let mut tuned = strategy.clone();
+        if self.config.enabled {
+            tuned.conservative_min_edge = self.min_edges[0];
+            tuned.normal_min_edge = self.min_edges[1];
+            tuned.aggressive_min_edge = self.min_edges[2];
+        }
+        tuned
+    }
+
+    /// Remember the edge an execution was entered at
+    pub fn record_entry(&mut self, execution_id: &str, edge: f64) {
+        if self.config.enabled {
+            self.entries.insert(execution_id.to_string(), edge);
+        }
+    }
+
+    /// File a closed position under its entry edge and re-tune
+    pub fn record_exit(&mut self, entry_executions: &[String], pnl: f64) -> Vec<Adjustment> {
+        let edges: Vec<f64> = entry_executions
+            .iter()
+            .filter_map(|id| self.entries.remove(id))
+            .collect();
+        let Some(edge) = edges.first() else {
+            return Vec::new();
+        };
+        if self.outcomes.len() == self.config.window.max(1) {
+            self.outcomes.pop_front();
+        }
+        self.outcomes.push_back(Outcome { edge: *edge, pnl });
+        self.adjust()
+    }
+
+    /// Step each threshold whose marginal bucket has enough evidence
+    fn adjust(&mut self) -> Vec<Adjustment> {
+        let mut adjustments = Vec::new();
+        for (idx, mode) in MODES.iter().enumerate() {
+            let threshold = self.min_edges[idx];
+            let bucket: Vec<&Outcome> = self
+                .outcomes
+                .iter()
+                .filter(|o| o.edge >= threshold && o.edge < threshold + self.config.bucket_width)
+                .collect();
+            if bucket.len() < self.config.min_bucket_trades.max(1) {
+                continue;
+            }
+            let wins = bucket.iter().filter(|o| o.pnl > 0.0).count();
+            let win_rate = wins as f64 / bucket.len() as f64;
+            let avg_pnl = bucket.iter().map(|o| o.pnl).sum::<f64>() / bucket.len() as f64;
+
+            let target = if avg_pnl < 0.0 || win_rate < self.config.target_win_rate {
+                threshold + self.config.step
+            } else {
+                threshold - self.config.step
+            }
+            .clamp(self.config.min_edge_floor, self.config.min_edge_cap);
+            if (target - threshold).abs() < f64::EPSILON {
+                continue;
+            }
+
+            self.min_edges[idx] = target;
+            adjustments.push(Adjustment {
+                mode,
+                from: threshold,
+                to: target,
+                trades: bucket.len(),
+                win_rate,
+                avg_pnl,
+            });
+        }
+        adjustments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tuner() -> EdgeTuner {
+        let config = TuningConfig {
+            enabled: true,
+            window: 50,
+            bucket_width: 0.01,
+            min_bucket_trades: 3,
+            target_win_rate: 0.5,
+            step: 0.005,
+            min_edge_floor: 0.01,
+            min_edge_cap: 0.06,
+        };
+        EdgeTuner::new(&config, &StrategyConfig::default())
+    }
+
+    fn trade(tuner: &mut EdgeTuner, id: &str, edge: f64, pnl: f64) -> Vec<Adjustment> {
+        tuner.record_entry(id, edge);
+        tuner.record_exit(&[id.to_string()], pnl)
+    }
+
+    #[test]
+    fn test_losing_marginal_trades_raise_threshold() {
+        let mut tuner = tuner();
+        // Normal mode (2%) trades just over the line keep losing
+        assert!(trade(&mut tuner, "a", 0.021, -0.1).is_empty());
+        assert!(trade(&mut tuner, "b", 0.025, -0.2).is_empty());
+        let adjustments = trade(&mut tuner, "c", 0.022, 0.1);
+        assert_eq!(adjustments.len(), 1);
+        assert_eq!(adjustments[0].mode, "normal");
+        assert!((adjustments[0].to - 0.025).abs() < 1e-9);
+
+        let tuned = tuner.tuned(&StrategyConfig::default());
+        assert!((tuned.normal_min_edge - 0.025).abs() < 1e-9);
+        assert_eq!(tuned.conservative_min_edge, 0.05);
+    }
+
+    #[test]
+    fn test_winning_marginal_trades_lower_threshold_within_floor() {
+        let mut tuner = tuner();
+        for (i, edge) in [0.011, 0.012, 0.015].iter().enumerate() {
+            trade(&mut tuner, &i.to_string(), *edge, 0.3);
+        }
+        // Aggressive mode is already at the 1% floor
+        let tuned = tuner.tuned(&StrategyConfig::default());
+        assert_eq!(tuned.aggressive_min_edge, 0.01);
+
+        for (i, edge) in [0.021, 0.022, 0.023].iter().enumerate() {
+            trade(&mut tuner, &format!("n{}", i), *edge, 0.3);
+        }
+        let tuned = tuner.tuned(&StrategyConfig::default());
+        assert!((tuned.normal_min_edge - 0.015).abs() < 1e-9);
+
+        // Exits without a recorded entry are ignored
+        assert!(tuner.record_exit(&["unknown".to_string()], -5.0).is_empty());
+    }
+}
@@ -1,24 +1,136 @@
 #![allow(dead_code)]
+use crate::money::Decimal;
+use crate::signer::OrderMaker;
 use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt;
+
+// String identifiers get their own types so a market id can never be passed
+// where a token id is expected (or the reverse). Both serialize as plain strings.
+macro_rules! string_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn new(id: impl Into<String>) -> Self {
+                Self(id.into())
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(id: String) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(id: &str) -> Self {
+                Self(id.to_string())
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = std::convert::Infallible;
+
+            fn from_str(id: &str) -> Result<Self, Self::Err> {
+                Ok(Self::from(id))
+            }
+        }
+
+        // Lets maps keyed by the id be queried with a &str
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+    };
+}
+
+string_id!(
+    /// Gamma market id
+    MarketId
+);
+
+string_id!(
+    /// CLOB outcome token (asset) id
+    TokenId
+);
 
 // represents a polymarket prediction market
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Market {
-    pub id: String,                  // unique market ID
-    pub question: String,            // Human readable question
-    pub slug: String,                // url friendly name
-    pub outcomes: Vec<String>,       // ["yes" , "no"]
-    pub outcome_prices: Vec<f64>,    // [0.5 , 0.5]
-    pub clob_token_ids: Vec<String>, // Token Ids for trading
-    pub best_bid: Option<f64>,       // highest by price across outcomes
-    pub best_ask: Option<f64>,       // lowest sell price across outcomes
-    pub maker_base_fee: u32,         // In basis points (eg : 0) -> fees if you add liquidity
+    pub id: MarketId,                 // unique market ID
+    pub question: String,             // Human readable question
+    pub slug: String,                 // url friendly name
+    pub outcomes: Vec<String>,        // ["yes" , "no"]
+    pub outcome_prices: Vec<f64>,     // [0.5 , 0.5]
+    pub clob_token_ids: Vec<TokenId>, // Token Ids for trading
+    pub best_bid: Option<f64>,        // highest by price across outcomes
+    pub best_ask: Option<f64>,        // lowest sell price across outcomes
+    pub maker_base_fee: u32,          // In basis points (eg : 0) -> fees if you add liquidity
     pub taker_base_fee: u32, // In basis points (eg : 200 = 2%) -> fees if you remove liquidity
-    pub liquidity: f64,      // Depth of the market
-    pub volume_24hr: f64,    // trading activity
+    pub liquidity: Option<f64>, // USDC resting on the books, from Gamma (None if unknown)
+    pub volume_24hr: Option<f64>, // USDC traded in the last 24h, from Gamma (None if unknown)
     pub active: bool,
     /// is market live ?
     pub accepting_orders: bool, // can you trade right now ?
+    /// CTF condition the outcome tokens are minted from
+    #[serde(default)]
+    pub condition_id: String,
+    /// Gamma category (e.g. "Sports"), for per-category strategy overrides
+    #[serde(default)]
+    pub category: String,
+    /// Price increment orders must respect (0: unknown, use the configured default)
+    #[serde(default)]
+    pub tick_size: f64,
+    /// Smallest order size in shares (0: unknown, use the configured default)
+    #[serde(default)]
+    pub min_order_size: f64,
+    /// Top of book per outcome, aligned with `clob_token_ids` (empty until books are fetched)
+    #[serde(default)]
+    pub outcome_quotes: Vec<Quote>,
+}
+
+/// Best bid and ask of one outcome token's book
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Quote {
+    pub bid: Option<f64>,
+    pub ask: Option<f64>,
+}
+
+impl Quote {
+    pub fn from_book(book: &OrderBook) -> Self {
+        Self {
+            bid: book.best_bid(),
+            ask: book.best_ask(),
+        }
+    }
 }
 
 // Single price level in order book
@@ -50,7 +162,7 @@ pub struct PriceLevel {
 // 0.52 -> 700 tokens
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderBook {
-    pub token_id: String,
+    pub token_id: TokenId,
     pub bids: Vec<PriceLevel>,
     pub asks: Vec<PriceLevel>, // Added missing comma
     pub timestamp: u64,
@@ -62,7 +174,7 @@ pub struct OrderBook {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Trade {
     pub id: String,
-    pub token_id: String,
+    pub token_id: TokenId,
     pub price: f64, // Added missing comma
     pub size: f64,
     pub side: Side,
@@ -82,7 +194,7 @@ pub enum Side {
 // guarenteed profit = 0.05 - fees
 #[derive(Debug, Clone)]
 pub struct ArbitrageSignal {
-    pub market_id: String,
+    pub market_id: MarketId,
     pub spread: f64, // how much the price deviates from 1
     pub edge: f64,   // Expected profit per unit
     pub recommended_side: Side,
@@ -91,16 +203,36 @@ pub struct ArbitrageSignal {
 }
 
 // Execution result
-#[derive(Debug, Clone)]
+// one per order: what we asked for, what we got, what it cost, and when
+#[derive(Debug, Clone, Serialize)]
 pub struct ExecutionResult {
+    pub execution_id: String, // unique per execution, referenced by positions
+    pub market_id: MarketId,
+    pub token_id: TokenId,
+    pub side: Side,
+    pub requested_size: f64,
     pub filled_size: f64,
     pub execution_price: f64,
-    pub fee_paid: f64,
+    pub price_impact: Decimal, // cost of filling away from the midpoint (USDC)
+    pub fee_paid: Decimal,
+    pub gas_cost: Decimal,
     pub slippage: f64,
-    pub total_cost: f64,
+    pub total_cost: Decimal,       // notional + fees + gas
+    pub maker: Option<OrderMaker>, // funder and signer, when the order was posted to the CLOB
+    pub submitted_at_ms: u64,
+    pub acked_at_ms: u64, // after network latency
+    pub filled_at_ms: u64,
     pub success: bool,
 }
 
+// Final outcome of a closed market
+#[derive(Debug, Clone)]
+pub struct Resolution {
+    pub market_id: MarketId,
+    pub condition_id: String,
+    pub payouts: HashMap<TokenId, f64>, // token id -> USDC paid per token (1.0 or 0.0)
+}
+
 // Implementaion for Market
 
 impl Market {
@@ -118,7 +250,7 @@ impl Market {
 
     // get YES token price (assumes binary market)
     pub fn yes_price(&self) -> f64 {
-        self.outcome_prices.get(0).copied().unwrap_or(0.0)
+        self.outcome_prices.first().copied().unwrap_or(0.0)
     }
 
     // get No token price (assumes binary market)
@@ -126,10 +258,52 @@ impl Market {
         self.outcome_prices.get(1).copied().unwrap_or(0.0)
     }
 
+    // get the current price of a specific outcome token
+    pub fn token_price(&self, token_id: &TokenId) -> Option<f64> {
+        let idx = self.clob_token_ids.iter().position(|t| t == token_id)?;
+        self.outcome_prices.get(idx).copied()
+    }
+
+    // cost of buying one of every outcome at the best asks
+    // (None unless every outcome has an ask)
+    pub fn bundle_ask(&self) -> Option<f64> {
+        self.bundle_quote(|q| q.ask)
+    }
+
+    // proceeds of selling one of every outcome at the best bids
+    // (None unless every outcome has a bid)
+    pub fn bundle_bid(&self) -> Option<f64> {
+        self.bundle_quote(|q| q.bid)
+    }
+
+    fn bundle_quote(&self, price: impl Fn(&Quote) -> Option<f64>) -> Option<f64> {
+        if self.clob_token_ids.is_empty() || self.outcome_quotes.len() != self.clob_token_ids.len()
+        {
+            return None;
+        }
+        self.outcome_quotes.iter().map(price).sum()
+    }
+
     // get taker fee as decimal (eg : 0.02 for 2%)
     pub fn taker_fee_rate(&self) -> f64 {
         self.taker_base_fee as f64 / 10000.0
     }
+
+    // check the market is deep and active enough to enter (0 disables a
+    // minimum; an unknown figure only passes a disabled one)
+    pub fn meets_activity(&self, min_liquidity: f64, min_volume_24hr: f64) -> bool {
+        let meets = |value: Option<f64>, min: f64| min <= 0.0 || value.is_some_and(|v| v >= min);
+        meets(self.liquidity, min_liquidity) && meets(self.volume_24hr, min_volume_24hr)
+    }
+
+    // most shares per leg whose cost stays within `fraction` of the market's
+    // liquidity (None when uncapped, the liquidity is unknown or empty, or
+    // there is nothing to size against)
+    pub fn liquidity_cap(&self, fraction: f64, leg_prices: &[f64]) -> Option<f64> {
+        let price = leg_prices.iter().copied().fold(0.0, f64::max);
+        let liquidity = self.liquidity.filter(|l| *l > 0.0)?;
+        (fraction > 0.0 && price > 0.0).then(|| fraction * liquidity / price)
+    }
 }
 
 // Implemtation of OrderBook
@@ -173,6 +347,29 @@ impl OrderBook {
         self.asks.iter().map(|l| l.size).sum()
     }
 
+    // the book left once someone takes the best level on the side an order hits
+    pub fn without_top_level(&self, side: Side) -> OrderBook {
+        let mut book = self.clone();
+        let levels = match side {
+            Side::Buy => &mut book.asks,
+            Side::Sell => &mut book.bids,
+        };
+        if !levels.is_empty() {
+            levels.remove(0);
+        }
+        book
+    }
+
+    // the book an order limited to `limit` may take from
+    pub fn within_limit(&self, side: Side, limit: f64) -> OrderBook {
+        let mut book = self.clone();
+        match side {
+            Side::Buy => book.asks.retain(|l| l.price <= limit),
+            Side::Sell => book.bids.retain(|l| l.price >= limit),
+        }
+        book
+    }
+
     // calculates given price for a give size (walks the book)
     pub fn execution_price(&self, size: f64, side: Side) -> Option<f64> {
         let levels = match side {
@@ -203,29 +400,26 @@ impl OrderBook {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util;
 
     fn create_test_market(yes_price: f64, no_price: f64) -> Market {
         Market {
-            id: "test_market".to_string(),
+            id: "test_market".into(),
             question: "Test question?".to_string(),
             slug: "test-market".to_string(),
-            outcomes: vec!["Yes".to_string(), "No".to_string()],
-            outcome_prices: vec![yes_price, no_price],
-            clob_token_ids: vec!["token1".to_string(), "token2".to_string()],
+            clob_token_ids: vec!["token1".into(), "token2".into()],
             best_bid: Some(yes_price - 0.01),
             best_ask: Some(yes_price + 0.01),
-            maker_base_fee: 0,
             taker_base_fee: 200,
-            liquidity: 1000.0,
-            volume_24hr: 5000.0,
-            active: true,
-            accepting_orders: true,
+            liquidity: Some(1000.0),
+            volume_24hr: Some(5000.0),
+            ..test_util::market(yes_price, no_price)
         }
     }
 
     fn create_test_order_book() -> OrderBook {
         OrderBook {
-            token_id: "test_token".to_string(),
+            token_id: "test_token".into(),
             bids: vec![
                 PriceLevel {
                     price: 0.50,
@@ -284,12 +478,58 @@ mod tests {
         assert_eq!(market.no_price(), 0.40);
     }
 
+    #[test]
+    fn test_market_token_price() {
+        let market = create_test_market(0.60, 0.40);
+        assert_eq!(market.token_price(&"token1".into()), Some(0.60));
+        assert_eq!(market.token_price(&"token2".into()), Some(0.40));
+        assert_eq!(market.token_price(&"unknown".into()), None);
+    }
+
+    #[test]
+    fn test_ids_serialize_as_plain_strings() {
+        let market = create_test_market(0.60, 0.40);
+        let json = serde_json::to_value(&market).unwrap();
+        assert_eq!(json["id"], "test_market");
+        assert_eq!(json["clob_token_ids"][0], "token1");
+
+        let parsed: Market = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.id, MarketId::new("test_market"));
+        // Maps keyed by id can still be looked up with a &str
+        let payouts: HashMap<TokenId, f64> =
+            HashMap::from([(parsed.clob_token_ids[0].clone(), 1.0)]);
+        assert_eq!(payouts.get("token1"), Some(&1.0));
+    }
+
     #[test]
     fn test_market_taker_fee_rate() {
         let market = create_test_market(0.50, 0.50);
         assert_eq!(market.taker_fee_rate(), 0.02); // 200 bps = 2%
     }
 
+    #[test]
+    fn test_market_activity_and_liquidity_cap() {
+        let market = create_test_market(0.40, 0.50);
+        assert!(market.meets_activity(1000.0, 0.0));
+        assert!(!market.meets_activity(2000.0, 0.0));
+        assert!(!market.meets_activity(0.0, 6000.0));
+
+        // 1% of $1000 at the dearer leg's price
+        assert_eq!(market.liquidity_cap(0.01, &[0.40, 0.50]), Some(20.0));
+        assert_eq!(market.liquidity_cap(0.0, &[0.40, 0.50]), None);
+
+        // Unknown figures fail a minimum but never size a trade to zero
+        let unknown = Market {
+            liquidity: None,
+            volume_24hr: None,
+            ..market
+        };
+        assert!(unknown.meets_activity(0.0, 0.0));
+        assert!(!unknown.meets_activity(1000.0, 0.0));
+        assert!(!unknown.meets_activity(0.0, 500.0));
+        assert_eq!(unknown.liquidity_cap(0.01, &[0.40, 0.50]), None);
+    }
+
     // OrderBook tests
     #[test]
     fn test_order_book_best_bid() {
@@ -353,7 +593,7 @@ mod tests {
     #[test]
     fn test_order_book_empty() {
         let empty_book = OrderBook {
-            token_id: "empty".to_string(),
+            token_id: "empty".into(),
             bids: vec![],
             asks: vec![],
             timestamp: 0,
@@ -0,0 +1,225 @@
+//! CLOB user channel
+//!
+//! Authenticated WebSocket stream of the account's order acknowledgements
+//! and fills, fed straight into the `OrderManager`. Shares the market
+//! stream's heartbeat, idle-timeout watchdog, and reconnect backoff.
+
+use crate::auth::ApiCredentials;
+use crate::orders::{OrderManager, UserEvent};
+use crate::websocket::{
+    next_backoff, parse_events, WsError, WsStatus, DEFAULT_IDLE_TIMEOUT, MIN_BACKOFF, PING_INTERVAL,
+};
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{oneshot, RwLock};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+/// L2 credentials sent with the subscription
+#[derive(Serialize)]
+struct UserAuth<'a> {
+    #[serde(rename = "apiKey")]
+    api_key: &'a str,
+    secret: &'a str,
+    passphrase: &'a str,
+}
+
+/// Subscription request for the user channel
+#[derive(Serialize)]
+struct UserSubscribeRequest<'a> {
+    auth: UserAuth<'a>,
+    /// Condition ids to filter on (empty: every market)
+    markets: Vec<String>,
+    #[serde(rename = "type")]
+    channel: &'static str,
+}
+
+/// Streams the account's orders and fills into an `OrderManager`
+pub struct UserStream {
+    url: String,
+    credentials: ApiCredentials,
+    orders: Arc<OrderManager>,
+    status: Arc<RwLock<WsStatus>>,
+    idle_timeout: Duration,
+}
+
+impl UserStream {
+    pub fn new(url: &str, credentials: ApiCredentials, orders: Arc<OrderManager>) -> Self {
+        Self {
+            url: url.to_string(),
+            credentials,
+            orders,
+            status: Arc::new(RwLock::new(WsStatus::Disconnected)),
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+        }
+    }
+
+    /// Tear the connection down after this long without any frame
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// User channel endpoint
+    fn user_url(&self) -> String {
+        let base = self.url.trim_end_matches('/');
+        let base = base.strip_suffix("/market").unwrap_or(base);
+        if base.ends_with("/user") {
+            base.to_string()
+        } else {
+            format!("{}/user", base)
+        }
+    }
+
+    /// Keep the user channel connected, reconnecting with exponential backoff
+    pub async fn maintain(self: Arc<Self>) {
+        let mut backoff = MIN_BACKOFF;
+        loop {
+            match self.connect().await {
+                Ok(closed) => {
+                    backoff = MIN_BACKOFF;
+                    // Wait for the reader to give up on this connection, then
+                    // pause so a server that drops us right away isn't hammered
+                    let _ = closed.await;
+                    tokio::time::sleep(MIN_BACKOFF).await;
+                }
+                Err(e) => {
+                    *self.status.write().await = WsStatus::Failed(e.to_string());
+                    println!(
+                        "⚠️ [UserStream] Connect failed ({}), retrying in {}s",
+                        e,
+                        backoff.as_secs()
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = next_backoff(backoff);
+                }
+            }
+        }
+    }
+
+    /// Connect, authenticate, and stream events until the connection drops
+    ///
+    /// Returns a receiver that completes once the connection is gone.
+    async fn connect(&self) -> Result<oneshot::Receiver<()>, WsError> {
+        *self.status.write().await = WsStatus::Connecting;
+        let (ws_stream, _) = connect_async(self.user_url())
+            .await
+            .map_err(|e| WsError::ConnectionFailed(e.to_string()))?;
+        let (mut write, mut read) = ws_stream.split();
+
+        let msg = serde_json::to_string(&UserSubscribeRequest {
+            auth: UserAuth {
+                api_key: &self.credentials.api_key,
+                secret: self.credentials.secret.expose(),
+                passphrase: self.credentials.passphrase.expose(),
+            },
+            markets: Vec::new(),
+            channel: "user",
+        })
+        .map_err(|e| WsError::SerializeError(e.to_string()))?;
+        write
+            .send(Message::Text(msg.into()))
+            .await
+            .map_err(|e| WsError::SendError(e.to_string()))?;
+
+        *self.status.write().await = WsStatus::Connected;
+        println!("✅ [UserStream] Subscribed to order and fill updates");
+
+        // Writer: keepalive pings until the reader shuts it down
+        let (shutdown, mut shutdown_rx) = oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let mut ping = tokio::time::interval(PING_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = ping.tick() => {
+                        if write.send(Message::Text("PING".into())).await.is_err() {
+                            break;
+                        }
+                    }
+                    _ = &mut shutdown_rx => {
+                        let _ = write.send(Message::Close(None)).await;
+                        break;
+                    }
+                }
+            }
+        });
+
+        // Reader: apply events until the stream ends or goes quiet
+        let (closed, closed_rx) = oneshot::channel::<()>();
+        let orders = self.orders.clone();
+        let status = self.status.clone();
+        let idle_timeout = self.idle_timeout;
+        tokio::spawn(async move {
+            loop {
+                match tokio::time::timeout(idle_timeout, read.next()).await {
+                    Ok(Some(Ok(Message::Text(text)))) => {
+                        for event in parse_events::<UserEvent>(&text) {
+                            orders.apply(&event);
+                        }
+                    }
+                    Ok(Some(Ok(Message::Close(_)))) | Ok(None) => {
+                        *status.write().await = WsStatus::Disconnected;
+                        println!("📴 [UserStream] Connection closed");
+                        break;
+                    }
+                    Ok(Some(Err(e))) => {
+                        *status.write().await = WsStatus::Failed(e.to_string());
+                        println!("❌ [UserStream] Error: {}", e);
+                        break;
+                    }
+                    Ok(Some(Ok(_))) => {}
+                    Err(_) => {
+                        *status.write().await = WsStatus::Failed("idle timeout".to_string());
+                        println!(
+                            "💤 [UserStream] No frames for {}s, reconnecting",
+                            idle_timeout.as_secs()
+                        );
+                        break;
+                    }
+                }
+            }
+            drop(shutdown);
+            let _ = closed.send(());
+        });
+
+        Ok(closed_rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::Secret;
+
+    #[test]
+    fn test_user_url_and_subscription() {
+        let credentials = ApiCredentials {
+            api_key: "key".to_string(),
+            secret: Secret::new("secret"),
+            passphrase: Secret::new("pass"),
+        };
+        let stream = UserStream::new(
+            "wss://ws-subscriptions-clob.polymarket.com/ws/",
+            credentials,
+            Arc::new(OrderManager::default()),
+        );
+        assert_eq!(
+            stream.user_url(),
+            "wss://ws-subscriptions-clob.polymarket.com/ws/user"
+        );
+
+        let msg = serde_json::to_value(UserSubscribeRequest {
+            auth: UserAuth {
+                api_key: "key",
+                secret: "secret",
+                passphrase: "pass",
+            },
+            markets: Vec::new(),
+            channel: "user",
+        })
+        .unwrap();
+        assert_eq!(msg["auth"]["apiKey"], "key");
+        assert_eq!(msg["type"], "user");
+    }
+}
@@ -0,0 +1,170 @@
+//! Capital utilization
+//!
+//! Tracks over the session how much capital sits in open positions and how
+//! much allowance sits idle, time-weighted between samples, along with how
+//! long positions are held. Low utilization with short holds points at a
+//! trade size that is too small; long holds at timeouts that are too loose.
+
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// Capital at one instant
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at: u64,
+    /// Cost of open positions (USDC)
+    deployed: f64,
+    /// Allowance left to trade with (USDC)
+    idle: f64,
+}
+
+#[derive(Debug, Default)]
+struct UtilizationState {
+    started_at: Option<u64>,
+    last: Option<Sample>,
+    /// Integrals over time, in USDC-seconds
+    deployed_secs: f64,
+    idle_secs: f64,
+    /// Seconds with at least one position open
+    in_market_secs: u64,
+    peak_deployed: f64,
+    closed: usize,
+    total_hold_secs: u64,
+    max_hold_secs: u64,
+}
+
+/// Session utilization, for the API
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UtilizationReport {
+    pub session_secs: u64,
+    /// Cost of open positions at the last sample
+    pub deployed: f64,
+    /// Allowance left at the last sample
+    pub idle: f64,
+    /// Time-weighted averages over the session
+    pub avg_deployed: f64,
+    pub avg_idle: f64,
+    /// Share of the capital on hand that was in positions, time-weighted
+    pub utilization: f64,
+    pub peak_deployed: f64,
+    /// Share of the session with at least one position open
+    pub time_in_market: f64,
+    pub closed_positions: usize,
+    pub avg_hold_secs: f64,
+    pub max_hold_secs: u64,
+}
+
+/// Session capital tracker shared by the engine, pipeline, and API
+#[derive(Debug, Default)]
+pub struct UtilizationTracker {
+    state: Mutex<UtilizationState>,
+}
+
+impl UtilizationTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record capital at `now`; the previous sample is taken to have held
+    /// until then
+    pub fn sample(&self, now: u64, deployed: f64, idle: f64) {
+        let mut state = self.state.lock().unwrap();
+        state.started_at.get_or_insert(now);
+        if let Some(last) = state.last {
+            let secs = now.saturating_sub(last.at);
+            state.deployed_secs += last.deployed * secs as f64;
+            state.idle_secs += last.idle * secs as f64;
+            if last.deployed > 0.0 {
+                state.in_market_secs += secs;
+            }
+        }
+        state.peak_deployed = state.peak_deployed.max(deployed);
+        state.last = Some(Sample {
+            at: now.max(state.last.map_or(0, |l| l.at)),
+            deployed,
+            idle,
+        });
+    }
+
+    /// Record how long a closed position was held
+    pub fn record_hold(&self, entry_time: u64, exit_time: u64) {
+        let secs = exit_time.saturating_sub(entry_time);
+        let mut state = self.state.lock().unwrap();
+        state.closed += 1;
+        state.total_hold_secs += secs;
+        state.max_hold_secs = state.max_hold_secs.max(secs);
+    }
+
+    /// Utilization up to the last sample
+    pub fn report(&self) -> UtilizationReport {
+        let state = self.state.lock().unwrap();
+        let (Some(started_at), Some(last)) = (state.started_at, state.last) else {
+            return UtilizationReport::default();
+        };
+        let session_secs = last.at.saturating_sub(started_at);
+        let per_sec = |total: f64| {
+            if session_secs == 0 {
+                0.0
+            } else {
+                total / session_secs as f64
+            }
+        };
+        let (avg_deployed, avg_idle) = if session_secs == 0 {
+            (last.deployed, last.idle)
+        } else {
+            (per_sec(state.deployed_secs), per_sec(state.idle_secs))
+        };
+        let capital = avg_deployed + avg_idle;
+        UtilizationReport {
+            session_secs,
+            deployed: last.deployed,
+            idle: last.idle,
+            avg_deployed,
+            avg_idle,
+            utilization: if capital > 0.0 {
+                avg_deployed / capital
+            } else {
+                0.0
+            },
+            peak_deployed: state.peak_deployed,
+            time_in_market: per_sec(state.in_market_secs as f64),
+            closed_positions: state.closed,
+            avg_hold_secs: if state.closed == 0 {
+                0.0
+            } else {
+                state.total_hold_secs as f64 / state.closed as f64
+            },
+            max_hold_secs: state.max_hold_secs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_weighted_utilization() {
+        let tracker = UtilizationTracker::new();
+        assert_eq!(tracker.report(), UtilizationReport::default());
+
+        // 100s idle, then 300s with $30 of $100 in positions
+        tracker.sample(1_000, 0.0, 100.0);
+        tracker.sample(1_100, 30.0, 70.0);
+        tracker.sample(1_400, 0.0, 100.0);
+        tracker.record_hold(1_100, 1_400);
+        tracker.record_hold(1_200, 1_300);
+
+        let report = tracker.report();
+        assert_eq!(report.session_secs, 400);
+        assert!((report.avg_deployed - 22.5).abs() < 1e-9);
+        assert!((report.avg_idle - 77.5).abs() < 1e-9);
+        assert!((report.utilization - 0.225).abs() < 1e-9);
+        assert_eq!(report.time_in_market, 0.75);
+        assert_eq!(report.peak_deployed, 30.0);
+        assert_eq!((report.deployed, report.idle), (0.0, 100.0));
+        assert_eq!(report.closed_positions, 2);
+        assert_eq!(report.avg_hold_secs, 200.0);
+        assert_eq!(report.max_hold_secs, 300);
+    }
+}
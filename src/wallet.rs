@@ -1,15 +1,26 @@
-use crate::types::Side;
+use crate::money::Decimal;
+use crate::types::{Side, TokenId};
 use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Collateral token used when no token is specified
+pub const DEFAULT_TOKEN: &str = "USDC";
+
+/// Daily spend bucket for a single collateral token
+#[derive(Debug, Clone)]
+pub struct TokenAllowance {
+    pub daily_limit: Decimal,
+    pub spent_today: Decimal,
+}
+
 #[derive(Debug, Clone)]
 /// Represents the on-chain state of a MetaMask Smart Account (ERC-7715)
-/// Tracks the "Daily Spend Limit" permission granted to this agent.
+/// Tracks the "Daily Spend Limit" permission granted to this agent,
+/// with a separate bucket per collateral token (USDC, USDC.e, ...).
 pub struct Wallet {
-    pub daily_limit: f64,
-    pub spent_today: f64,
+    pub allowances: HashMap<String, TokenAllowance>,
     pub last_reset: u64,
-    pub positions: HashMap<String, Position>,
+    pub positions: HashMap<TokenId, Position>,
     pub total_trades: u32,
     pub winning_trades: u32,
 }
@@ -17,7 +28,7 @@ pub struct Wallet {
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct Position {
-    pub token_id: String,
+    pub token_id: TokenId,
     pub side: Side,
     pub size: f64,
     pub entry_price: f64,
@@ -25,11 +36,19 @@ pub struct Position {
 }
 
 impl Wallet {
-    /// Create new permissioned wallet adapter
-    pub fn new(daily_limit: f64) -> Self {
+    /// Create new permissioned wallet adapter with a USDC daily limit
+    pub fn new(daily_limit: Decimal) -> Self {
+        let mut allowances = HashMap::new();
+        allowances.insert(
+            DEFAULT_TOKEN.to_string(),
+            TokenAllowance {
+                daily_limit,
+                spent_today: Decimal::ZERO,
+            },
+        );
+
         Self {
-            daily_limit,
-            spent_today: 0.0,
+            allowances,
             last_reset: Self::current_timestamp(),
             positions: HashMap::new(),
             total_trades: 0,
@@ -37,6 +56,18 @@ impl Wallet {
         }
     }
 
+    /// Add (or replace) the daily limit for another collateral token
+    pub fn with_token_limit(mut self, token: &str, daily_limit: Decimal) -> Self {
+        self.allowances.insert(
+            token.to_string(),
+            TokenAllowance {
+                daily_limit,
+                spent_today: Decimal::ZERO,
+            },
+        );
+        self
+    }
+
     pub fn current_timestamp() -> u64 {
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -44,36 +75,94 @@ impl Wallet {
             .as_secs()
     }
 
-    fn check_reset(&mut self) {
+    /// Start a fresh day's allowance once 24h have passed
+    pub fn check_reset(&mut self) {
         let now = Self::current_timestamp();
         // Simple 24h reset logic
         if now - self.last_reset >= 86400 {
-            self.spent_today = 0.0;
+            for allowance in self.allowances.values_mut() {
+                allowance.spent_today = Decimal::ZERO;
+            }
             self.last_reset = now;
             println!("🔄 [ERC-7715] Daily Limit Period Reset - Allowance Refreshed");
         }
     }
 
+    /// Daily limit of the default token
+    pub fn daily_limit(&self) -> Decimal {
+        self.token_daily_limit(DEFAULT_TOKEN)
+    }
+
+    /// Amount of the default token spent today
+    pub fn spent_today(&self) -> Decimal {
+        self.token_spent_today(DEFAULT_TOKEN)
+    }
+
+    /// Remaining allowance of the default token
+    pub fn remaining(&self) -> Decimal {
+        self.token_remaining(DEFAULT_TOKEN)
+    }
+
+    /// Daily limit for a token (0 if the token has no bucket)
+    pub fn token_daily_limit(&self, token: &str) -> Decimal {
+        self.allowances
+            .get(token)
+            .map_or(Decimal::ZERO, |a| a.daily_limit)
+    }
+
+    /// Amount of a token spent today
+    pub fn token_spent_today(&self, token: &str) -> Decimal {
+        self.allowances
+            .get(token)
+            .map_or(Decimal::ZERO, |a| a.spent_today)
+    }
+
+    /// Remaining allowance for a token
+    pub fn token_remaining(&self, token: &str) -> Decimal {
+        self.allowances.get(token).map_or(Decimal::ZERO, |a| {
+            (a.daily_limit - a.spent_today).max(Decimal::ZERO)
+        })
+    }
+
     /// Check if we have sufficient permission allowance
-    pub fn check_permission(&mut self, amount: f64) -> bool {
+    pub fn check_permission(&mut self, amount: Decimal) -> bool {
+        self.check_token_permission(DEFAULT_TOKEN, amount)
+    }
+
+    /// Check if we have sufficient allowance in a specific token
+    ///
+    /// Tokens without a configured bucket are never permitted.
+    pub fn check_token_permission(&mut self, token: &str, amount: Decimal) -> bool {
         self.check_reset();
-        (self.spent_today + amount) <= self.daily_limit
+        match self.allowances.get(token) {
+            Some(a) => (a.spent_today + amount) <= a.daily_limit,
+            None => false,
+        }
     }
 
     /// Record a spend against the permission
-    pub fn record_spend(&mut self, amount: f64) -> bool {
-        if self.check_permission(amount) {
-            self.spent_today += amount;
-            true
-        } else {
-            false
+    pub fn record_spend(&mut self, amount: Decimal) -> bool {
+        self.record_token_spend(DEFAULT_TOKEN, amount)
+    }
+
+    /// Record a spend against a specific token's bucket
+    pub fn record_token_spend(&mut self, token: &str, amount: Decimal) -> bool {
+        if !self.check_token_permission(token, amount) {
+            return false;
+        }
+        match self.allowances.get_mut(token) {
+            Some(a) => {
+                a.spent_today += amount;
+                true
+            }
+            None => false,
         }
     }
 
     /// Open a new position (tracking only)
     pub fn open_position(
         &mut self,
-        token_id: String,
+        token_id: TokenId,
         side: Side,
         size: f64,
         price: f64,
@@ -93,7 +182,7 @@ impl Wallet {
 
     /// Close a position (tracking only)
     #[allow(dead_code)]
-    pub fn close_position(&mut self, token_id: &str, _exit_price: f64) {
+    pub fn close_position(&mut self, token_id: &TokenId, _exit_price: f64) {
         self.positions.remove(token_id);
     }
 
@@ -108,17 +197,37 @@ impl Wallet {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::money::usdc;
 
     #[test]
     fn test_daily_limits() {
-        let mut wallet = Wallet::new(100.0);
+        let mut wallet = Wallet::new(usdc(100.0));
 
         // Spend 50
-        assert!(wallet.record_spend(50.0));
-        assert_eq!(wallet.spent_today, 50.0);
+        assert!(wallet.record_spend(usdc(50.0)));
+        assert_eq!(wallet.spent_today(), usdc(50.0));
 
         // Try spending 60 (should fail)
-        assert!(!wallet.record_spend(60.0));
-        assert_eq!(wallet.spent_today, 50.0);
+        assert!(!wallet.record_spend(usdc(60.0)));
+        assert_eq!(wallet.spent_today(), usdc(50.0));
+    }
+
+    #[test]
+    fn test_per_token_buckets() {
+        let mut wallet = Wallet::new(usdc(100.0)).with_token_limit("USDC.e", usdc(20.0));
+
+        // Spends land in the matching bucket only
+        assert!(wallet.record_token_spend("USDC.e", usdc(15.0)));
+        assert_eq!(wallet.token_spent_today("USDC.e"), usdc(15.0));
+        assert_eq!(wallet.spent_today(), usdc(0.0));
+
+        // Each bucket enforces its own limit
+        assert!(!wallet.record_token_spend("USDC.e", usdc(10.0)));
+        assert!(wallet.record_spend(usdc(90.0)));
+        assert_eq!(wallet.token_remaining("USDC.e"), usdc(5.0));
+        assert_eq!(wallet.remaining(), usdc(10.0));
+
+        // Unknown tokens have no allowance
+        assert!(!wallet.record_token_spend("DAI", usdc(1.0)));
     }
 }
@@ -0,0 +1,240 @@
+//! Trading loop watchdog
+//!
+//! The trading loop beats a shared heartbeat on every pass. A blocked await
+//! or a panic stops the beats; the watchdog notices the silence, logs it,
+//! sends a webhook alert, and reports the agent unhealthy on `/api/health`.
+//! A loop that panicked is always respawned; a stalled one is aborted and
+//! respawned only when configured to.
+
+use crate::config::WatchdogConfig;
+use crate::wallet::Wallet;
+use crate::webhook::{WebhookEvent, WebhookPublisher};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// Longest the loop sleeps between beats while idle
+const IDLE_BEAT: Duration = Duration::from_secs(1);
+
+/// Liveness signal shared by the trading loop, the watchdog, and the API
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    inner: Arc<HeartbeatState>,
+}
+
+#[derive(Debug)]
+struct HeartbeatState {
+    started: Instant,
+    /// Milliseconds after `started` of the last beat
+    last_beat_ms: AtomicU64,
+    /// Set by the watchdog, cleared by the next beat
+    stalled: AtomicBool,
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Heartbeat {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(HeartbeatState {
+                started: Instant::now(),
+                last_beat_ms: AtomicU64::new(0),
+                stalled: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// The loop is alive
+    pub fn beat(&self) {
+        self.rearm();
+        self.inner.stalled.store(false, Ordering::Relaxed);
+    }
+
+    /// Restart the timeout without clearing a reported stall
+    fn rearm(&self) {
+        let now = self.inner.started.elapsed().as_millis() as u64;
+        self.inner.last_beat_ms.store(now, Ordering::Relaxed);
+    }
+
+    /// Time since the last beat
+    pub fn age(&self) -> Duration {
+        let now = self.inner.started.elapsed().as_millis() as u64;
+        Duration::from_millis(now.saturating_sub(self.inner.last_beat_ms.load(Ordering::Relaxed)))
+    }
+
+    /// Whether the watchdog has declared the loop stalled
+    pub fn is_stalled(&self) -> bool {
+        self.inner.stalled.load(Ordering::Relaxed)
+    }
+
+    /// Sleep, beating along the way so an idle wait never reads as a stall
+    pub async fn sleep(&self, duration: Duration) {
+        let deadline = Instant::now() + duration;
+        loop {
+            self.beat();
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                break;
+            }
+            tokio::time::sleep(left.min(IDLE_BEAT)).await;
+        }
+    }
+}
+
+/// Watches the trading loop task through its heartbeat
+pub struct Watchdog {
+    heartbeat: Heartbeat,
+    timeout: Duration,
+    check_interval: Duration,
+    restart: bool,
+    webhook: Option<WebhookPublisher>,
+}
+
+impl Watchdog {
+    pub fn new(heartbeat: Heartbeat, config: &WatchdogConfig) -> Self {
+        Self {
+            heartbeat,
+            timeout: Duration::from_secs(config.timeout_secs),
+            check_interval: Duration::from_secs(config.check_interval_secs.max(1)),
+            restart: config.restart,
+            webhook: None,
+        }
+    }
+
+    /// Send stall alerts to the webhook
+    pub fn with_webhook(mut self, webhook: Option<WebhookPublisher>) -> Self {
+        self.webhook = webhook;
+        self
+    }
+
+    /// Spawn the loop with `start` and watch it forever
+    pub async fn supervise<F, Fut>(self, mut start: F)
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.heartbeat.rearm();
+        let mut task: JoinHandle<()> = tokio::spawn(start());
+        let mut exited = false;
+        loop {
+            tokio::time::sleep(self.check_interval).await;
+
+            if !exited && task.is_finished() {
+                exited = true;
+                match (&mut task).await {
+                    Err(e) if e.is_panic() => println!("🚨 [Watchdog] Trading loop panicked"),
+                    _ => println!("🚨 [Watchdog] Trading loop exited"),
+                }
+            }
+            let age = self.heartbeat.age();
+            if !exited && age < self.timeout {
+                continue;
+            }
+
+            if !self.heartbeat.is_stalled() {
+                self.heartbeat.inner.stalled.store(true, Ordering::Relaxed);
+                println!(
+                    "🚨 [Watchdog] No heartbeat from the trading loop for {}s",
+                    age.as_secs()
+                );
+                if let Some(webhook) = &self.webhook {
+                    webhook.publish(WebhookEvent::Stalled {
+                        timestamp: Wallet::current_timestamp(),
+                        secs_since_heartbeat: age.as_secs(),
+                        restarting: self.restart || exited,
+                    });
+                }
+            }
+            if self.restart || exited {
+                println!("🔁 [Watchdog] Restarting the trading loop");
+                task.abort();
+                // The fresh loop gets a full timeout to send its first beat
+                self.heartbeat.rearm();
+                task = tokio::spawn(start());
+                exited = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    fn watchdog(heartbeat: &Heartbeat, restart: bool) -> Watchdog {
+        let mut watchdog = Watchdog::new(
+            heartbeat.clone(),
+            &WatchdogConfig {
+                enabled: true,
+                timeout_secs: 0,
+                check_interval_secs: 1,
+                restart,
+            },
+        );
+        watchdog.timeout = Duration::from_millis(50);
+        watchdog.check_interval = Duration::from_millis(10);
+        watchdog
+    }
+
+    #[tokio::test]
+    async fn test_missed_heartbeat_marks_stalled_until_next_beat() {
+        let heartbeat = Heartbeat::new();
+        let watched = heartbeat.clone();
+        // Beats once, then blocks forever
+        let supervisor = tokio::spawn(watchdog(&heartbeat, false).supervise(move || {
+            let heartbeat = watched.clone();
+            async move {
+                heartbeat.beat();
+                std::future::pending::<()>().await
+            }
+        }));
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!heartbeat.is_stalled());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(heartbeat.is_stalled());
+
+        heartbeat.beat();
+        assert!(!heartbeat.is_stalled());
+        supervisor.abort();
+    }
+
+    #[tokio::test]
+    async fn test_restarts_a_panicked_loop() {
+        let heartbeat = Heartbeat::new();
+        let starts = Arc::new(AtomicUsize::new(0));
+        let (counted, watched) = (starts.clone(), heartbeat.clone());
+        // Panics restart the loop even when stalls only alert
+        let supervisor = tokio::spawn(watchdog(&heartbeat, false).supervise(move || {
+            let (starts, heartbeat) = (counted.clone(), watched.clone());
+            async move {
+                if starts.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("first run dies");
+                }
+                loop {
+                    heartbeat.beat();
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+            }
+        }));
+
+        // Printing the panic (and any backtrace) holds up the runtime a while
+        for _ in 0..500 {
+            if starts.load(Ordering::SeqCst) > 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(starts.load(Ordering::SeqCst), 2);
+        assert!(!heartbeat.is_stalled());
+        supervisor.abort();
+    }
+}
@@ -0,0 +1,144 @@
+//! Outbound webhook
+//!
+//! POSTs every signal, execution, new-market, watchdog, permission-expiry,
+//! alert-rule, and panic alert as JSON to a user-configured URL, so they can be mirrored
+//! into other systems without using the built-in executor.
+//! Events are queued and sent from a background task; a slow or unreachable
+//! endpoint never holds up a worker.
+
+use crate::config::WebhookConfig;
+use crate::strategy::Intent;
+use crate::types::{ExecutionResult, MarketId};
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// One webhook delivery
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// A strategy detected an opportunity
+    Signal {
+        timestamp: u64,
+        #[serde(flatten)]
+        intent: Intent,
+    },
+    /// A market matching the discovery filter was newly listed
+    NewMarket {
+        timestamp: u64,
+        market_id: MarketId,
+        question: String,
+        slug: String,
+    },
+    /// An order leg was filled
+    Execution {
+        timestamp: u64,
+        strategy: &'static str,
+        #[serde(flatten)]
+        result: Box<ExecutionResult>,
+    },
+    /// The trading loop stopped sending heartbeats
+    Stalled {
+        timestamp: u64,
+        secs_since_heartbeat: u64,
+        /// Whether the watchdog is restarting the loop
+        restarting: bool,
+    },
+    /// The permission grant expires within a warning horizon
+    PermissionExpiring {
+        timestamp: u64,
+        permission_id: String,
+        expires_at: u64,
+        secs_until_expiry: u64,
+    },
+    /// A configured alert rule fired
+    Alert {
+        timestamp: u64,
+        rule: &'static str,
+        message: String,
+    },
+    /// New entries paused after a run of losing exits
+    EntriesPaused {
+        timestamp: u64,
+        consecutive_losses: u32,
+        resumes_at: u64,
+        resumes_in_secs: u64,
+    },
+    /// A task panicked (supervised subsystems restart on their own)
+    Panic {
+        timestamp: u64,
+        thread: String,
+        location: String,
+        message: String,
+    },
+}
+
+/// Handle for queueing webhook deliveries
+#[derive(Debug, Clone)]
+pub struct WebhookPublisher {
+    tx: mpsc::Sender<WebhookEvent>,
+}
+
+impl WebhookPublisher {
+    /// Start the delivery task, or `None` when no URL is configured
+    pub fn spawn(config: &WebhookConfig) -> Option<Self> {
+        if config.url.is_empty() {
+            return None;
+        }
+        let (tx, mut rx) = mpsc::channel::<WebhookEvent>(config.queue_size.max(1));
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .unwrap_or_default();
+        let url = config.url.clone();
+
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                let sent = client.post(&url).json(&event).send().await;
+                match sent.and_then(|resp| resp.error_for_status()) {
+                    Ok(_) => {}
+                    Err(e) => println!("⚠️ [Webhook] Delivery failed: {}", e),
+                }
+            }
+        });
+        Some(Self { tx })
+    }
+
+    /// Queue an event, dropping it if the endpoint has fallen too far behind
+    pub fn publish(&self, event: WebhookEvent) {
+        if let Err(mpsc::error::TrySendError::Full(_)) = self.tx.try_send(event) {
+            println!("⚠️ [Webhook] Queue full, dropping event");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_spec::OrderType;
+    use crate::types::Side;
+
+    #[test]
+    fn test_signal_payload_is_flat_and_tagged() {
+        let event = WebhookEvent::Signal {
+            timestamp: 1_000,
+            intent: Intent {
+                strategy: "arbitrage",
+                market_id: "m1".into(),
+                token_ids: vec!["yes".into(), "no".into()],
+                side: Side::Buy,
+                size: 5.0,
+                spread: -0.05,
+                edge: -0.05,
+                expected_profit: 0.2,
+                order_type: OrderType::Fok,
+            },
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "signal");
+        assert_eq!(json["timestamp"], 1_000);
+        assert_eq!(json["strategy"], "arbitrage");
+        assert_eq!(json["market_id"], "m1");
+        assert_eq!(json["side"], "Buy");
+    }
+}
@@ -1,46 +1,112 @@
-//! WebSocket streaming module for real-time price updates
+//! WebSocket streaming module for real-time order books
 //!
-//! Connects to Polymarket's WebSocket API for low-latency price feeds.
+//! Connects to Polymarket's market channel and maintains full local L2 books
+//! per token from `book` snapshots and `price_change` deltas. A book whose
+//! deltas arrive out of order, skip a sequence number, or disagree with the
+//! exchange is resynced from a REST snapshot (or by re-subscribing when no
+//! REST source is set) instead of being traded on while corrupted.
+//! A heartbeat and idle-timeout watchdog detect half-open connections, which
+//! are torn down and reconnected with backoff.
 
+use crate::market::MarketDataProvider;
+use crate::tape::{Trade, TradeTape};
+use crate::types::{OrderBook, PriceLevel, Side, TokenId};
 use futures_util::{SinkExt, StreamExt};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tokio::sync::{broadcast, RwLock};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, oneshot, Mutex, RwLock};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
-/// WebSocket message types from Polymarket
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
+/// Interval between keepalive pings (the server answers each with PONG)
+pub const PING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Default time without any frame before the connection is presumed dead
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Reconnect backoff bounds
+pub const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How often the reconnect loop checks the connection
+const RECONNECT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Price equality tolerance when matching book levels
+const PRICE_EPSILON: f64 = 1e-9;
+
+/// One price level as sent on the wire
+#[derive(Debug, Clone, Deserialize)]
+pub struct WsLevel {
+    pub price: String,
+    pub size: String,
+}
+
+/// One level change inside a `price_change` message
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriceChange {
+    pub asset_id: TokenId,
+    pub price: String,
+    pub size: String,
+    pub side: String,
+    #[serde(default)]
+    pub best_bid: Option<String>,
+    #[serde(default)]
+    pub best_ask: Option<String>,
+    /// Hash of the book after the change
+    #[serde(default)]
+    pub hash: Option<String>,
+}
+
+/// Market channel messages from Polymarket
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "event_type", rename_all = "snake_case")]
 pub enum WsMessage {
-    #[serde(rename = "price_update")]
-    PriceUpdate {
-        market_id: String,
-        token_id: String,
-        price: f64,
-        timestamp: u64,
+    /// Full book snapshot (sent on subscribe and after trades)
+    Book {
+        asset_id: TokenId,
+        market: String,
+        #[serde(alias = "buys")]
+        bids: Vec<WsLevel>,
+        #[serde(alias = "sells")]
+        asks: Vec<WsLevel>,
+        timestamp: String,
+        #[serde(default)]
+        hash: Option<String>,
     },
-    #[serde(rename = "trade")]
-    Trade {
-        market_id: String,
-        price: f64,
-        size: f64,
+    /// Level size changes for one or more tokens
+    PriceChange {
+        market: String,
+        price_changes: Vec<PriceChange>,
+        timestamp: String,
+        /// Per-connection event counter, when the feed sends one
+        #[serde(default, alias = "seq")]
+        sequence: Option<u64>,
+    },
+    /// Last trade executed on a token
+    LastTradePrice {
+        asset_id: TokenId,
+        market: String,
+        price: String,
+        size: String,
         side: String,
-        timestamp: u64,
+        timestamp: String,
     },
-    #[serde(rename = "book_update")]
-    BookUpdate { market_id: String, timestamp: u64 },
     #[serde(other)]
     Unknown,
 }
 
-/// Subscription request
-#[allow(dead_code)]
+/// Subscription request for the market channel
 #[derive(Debug, Serialize)]
 struct SubscribeRequest {
-    #[serde(rename = "type")]
-    msg_type: String,
-    channel: String,
-    markets: Vec<String>,
+    assets_ids: Vec<TokenId>,
+    /// Channel type, sent on the initial subscription only
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    channel: Option<&'static str>,
+    /// "subscribe" when adding assets to a live connection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    operation: Option<&'static str>,
 }
 
 /// WebSocket connection status
@@ -59,40 +125,343 @@ pub enum WsStatus {
 #[derive(Debug, Clone, Default)]
 pub struct PriceCache {
     /// Map of token_id -> latest price
-    pub prices: std::collections::HashMap<String, f64>,
+    pub prices: HashMap<TokenId, f64>,
     /// Last update timestamp
     pub last_update: u64,
 }
 
+/// Parse the numeric strings Polymarket uses for prices, sizes, and timestamps
+fn parse_num<T: std::str::FromStr + Default>(s: &str) -> T {
+    s.parse().unwrap_or_default()
+}
+
+/// Parse one market-channel text frame
+pub fn parse_messages(text: &str) -> Vec<WsMessage> {
+    parse_events(text)
+}
+
+/// Parse one text frame (a single event or an array of events), skipping
+/// events that don't deserialize
+pub fn parse_events<T: DeserializeOwned>(text: &str) -> Vec<T> {
+    match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(serde_json::Value::Array(events)) => events
+            .into_iter()
+            .filter_map(|e| serde_json::from_value(e).ok())
+            .collect(),
+        Ok(event) => serde_json::from_value(event).into_iter().collect(),
+        // Keepalive replies ("PONG") and other non-JSON frames
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Trade carried by a `last_trade_price` event
+pub fn to_trade(msg: &WsMessage) -> Option<(&TokenId, Trade)> {
+    match msg {
+        WsMessage::LastTradePrice {
+            asset_id,
+            price,
+            size,
+            side,
+            timestamp,
+            ..
+        } => Some((
+            asset_id,
+            Trade {
+                price: parse_num(price),
+                size: parse_num(size),
+                side: if side.eq_ignore_ascii_case("SELL") {
+                    Side::Sell
+                } else {
+                    Side::Buy
+                },
+                timestamp: parse_num(timestamp),
+            },
+        )),
+        _ => None,
+    }
+}
+
+/// Local copy of one token's book
+#[derive(Debug, Clone)]
+struct LocalBook {
+    book: OrderBook,
+    /// Timestamp (ms) of the last applied snapshot or delta
+    last_timestamp: u64,
+    /// Sequence number of the last applied delta (None since the snapshot)
+    last_sequence: Option<u64>,
+    /// Exchange hash of the book as last applied
+    hash: Option<String>,
+    /// Set when a delta couldn't be applied in order; cleared by the next snapshot
+    stale: bool,
+}
+
+/// Set the size at a price level, keeping the side sorted best-first
+fn set_level(levels: &mut Vec<PriceLevel>, price: f64, size: f64, side: Side) {
+    let existing = levels
+        .iter()
+        .position(|l| (l.price - price).abs() < PRICE_EPSILON);
+    match (existing, size > 0.0) {
+        (Some(idx), true) => levels[idx].size = size,
+        (Some(idx), false) => {
+            levels.remove(idx);
+        }
+        (None, true) => {
+            // Bids descend, asks ascend
+            let idx = levels
+                .iter()
+                .position(|l| match side {
+                    Side::Buy => l.price < price,
+                    Side::Sell => l.price > price,
+                })
+                .unwrap_or(levels.len());
+            levels.insert(idx, PriceLevel { price, size });
+        }
+        (None, false) => {}
+    }
+}
+
+fn to_levels(levels: &[WsLevel], side: Side) -> Vec<PriceLevel> {
+    let mut out = Vec::with_capacity(levels.len());
+    for level in levels {
+        set_level(
+            &mut out,
+            parse_num(&level.price),
+            parse_num(&level.size),
+            side,
+        );
+    }
+    out
+}
+
+/// Local L2 books per token, maintained from the market channel
+#[derive(Debug, Default)]
+pub struct OrderBookStore {
+    books: RwLock<HashMap<TokenId, LocalBook>>,
+}
+
+impl OrderBookStore {
+    /// Live book for a token (None if never received or awaiting resync)
+    pub async fn book(&self, token_id: &TokenId) -> Option<OrderBook> {
+        let books = self.books.read().await;
+        books
+            .get(token_id)
+            .filter(|b| !b.stale)
+            .map(|b| b.book.clone())
+    }
+
+    /// Apply one message; returns tokens whose books need a fresh snapshot
+    pub async fn apply(&self, msg: &WsMessage) -> Vec<TokenId> {
+        let mut books = self.books.write().await;
+        match msg {
+            WsMessage::Book {
+                asset_id,
+                bids,
+                asks,
+                timestamp,
+                hash,
+                ..
+            } => {
+                let timestamp = parse_num(timestamp);
+                books.insert(
+                    asset_id.clone(),
+                    LocalBook {
+                        book: OrderBook {
+                            token_id: asset_id.clone(),
+                            bids: to_levels(bids, Side::Buy),
+                            asks: to_levels(asks, Side::Sell),
+                            timestamp,
+                        },
+                        last_timestamp: timestamp,
+                        last_sequence: None,
+                        hash: hash.clone(),
+                        stale: false,
+                    },
+                );
+                Vec::new()
+            }
+            WsMessage::PriceChange {
+                price_changes,
+                timestamp,
+                sequence,
+                ..
+            } => {
+                let timestamp: u64 = parse_num(timestamp);
+                let mut resync = Vec::new();
+                for change in price_changes {
+                    let Some(local) = books.get_mut(&change.asset_id).filter(|b| !b.stale) else {
+                        // No snapshot to apply the delta to
+                        if !resync.contains(&change.asset_id) {
+                            resync.push(change.asset_id.clone());
+                        }
+                        continue;
+                    };
+                    if timestamp < local.last_timestamp {
+                        println!(
+                            "⚠️ [WebSocket] Out-of-order delta for {} ({} < {}), resyncing",
+                            change.asset_id, timestamp, local.last_timestamp
+                        );
+                        local.stale = true;
+                        resync.push(change.asset_id.clone());
+                        continue;
+                    }
+                    // Changes in one event share its sequence number
+                    if let (Some(seq), Some(last)) = (*sequence, local.last_sequence) {
+                        if seq > last + 1 || seq < last {
+                            println!(
+                                "⚠️ [WebSocket] Sequence gap for {} ({} after {}), resyncing",
+                                change.asset_id, seq, last
+                            );
+                            local.stale = true;
+                            resync.push(change.asset_id.clone());
+                            continue;
+                        }
+                    }
+                    // A replayed delta has already been applied
+                    if change.hash.is_some() && change.hash == local.hash {
+                        continue;
+                    }
+
+                    let side = if change.side.eq_ignore_ascii_case("SELL") {
+                        Side::Sell
+                    } else {
+                        Side::Buy
+                    };
+                    let levels = match side {
+                        Side::Buy => &mut local.book.bids,
+                        Side::Sell => &mut local.book.asks,
+                    };
+                    set_level(
+                        levels,
+                        parse_num(&change.price),
+                        parse_num(&change.size),
+                        side,
+                    );
+                    local.book.timestamp = timestamp;
+                    local.last_timestamp = timestamp;
+                    local.last_sequence = sequence.or(local.last_sequence);
+                    local.hash = change.hash.clone();
+
+                    // The exchange reports its top of book after the change; ours must agree
+                    let mismatch = |ours: Option<f64>, theirs: &Option<String>| {
+                        theirs.as_deref().is_some_and(|t| {
+                            let theirs: f64 = parse_num(t);
+                            theirs > 0.0 && ours.is_none_or(|o| (o - theirs).abs() > PRICE_EPSILON)
+                        })
+                    };
+                    if mismatch(local.book.best_bid(), &change.best_bid)
+                        || mismatch(local.book.best_ask(), &change.best_ask)
+                    {
+                        println!(
+                            "⚠️ [WebSocket] Book for {} diverged from exchange, resyncing",
+                            change.asset_id
+                        );
+                        local.stale = true;
+                        resync.push(change.asset_id.clone());
+                    }
+                }
+                resync
+            }
+            WsMessage::LastTradePrice { .. } | WsMessage::Unknown => Vec::new(),
+        }
+    }
+
+    /// Replace a stale or missing book with a REST snapshot
+    ///
+    /// Returns false (and keeps the local book) when a streamed snapshot
+    /// arrived first.
+    pub async fn restore(&self, book: OrderBook) -> bool {
+        let mut books = self.books.write().await;
+        if books.get(&book.token_id).is_some_and(|b| !b.stale) {
+            return false;
+        }
+        books.insert(
+            book.token_id.clone(),
+            LocalBook {
+                last_timestamp: book.timestamp,
+                last_sequence: None,
+                hash: None,
+                stale: false,
+                book,
+            },
+        );
+        true
+    }
+
+    /// Mark every book stale (e.g. after a disconnect)
+    pub async fn mark_all_stale(&self) {
+        for local in self.books.write().await.values_mut() {
+            local.stale = true;
+        }
+    }
+}
+
 /// WebSocket client for real-time Polymarket data
-#[allow(dead_code)]
 pub struct WebSocketClient {
     url: String,
     status: Arc<RwLock<WsStatus>>,
     price_cache: Arc<RwLock<PriceCache>>,
-    /// Broadcast channel for price updates
+    books: Arc<OrderBookStore>,
+    /// Trades from `last_trade_price` events
+    tape: Option<Arc<TradeTape>>,
+    /// REST books used to resync a corrupted local book
+    snapshots: Option<Arc<MarketDataProvider>>,
+    /// Tokens with a REST resync in flight
+    resyncing: Arc<StdMutex<HashSet<TokenId>>>,
+    /// Outgoing frames for the live connection
+    commands: Mutex<Option<mpsc::UnboundedSender<Message>>>,
+    /// Every token subscribed so far, replayed on reconnect
+    assets: Mutex<Vec<TokenId>>,
+    /// Silence after which the connection is torn down
+    idle_timeout: Duration,
+    /// When the last frame of any kind arrived
+    last_frame: Arc<StdMutex<Option<Instant>>>,
+    /// Broadcast channel for market events
     tx: broadcast::Sender<WsMessage>,
 }
 
 impl WebSocketClient {
-    #[allow(dead_code)]
     pub fn new(url: &str) -> Self {
         let (tx, _) = broadcast::channel(1000);
         Self {
             url: url.to_string(),
             status: Arc::new(RwLock::new(WsStatus::Disconnected)),
             price_cache: Arc::new(RwLock::new(PriceCache::default())),
+            books: Arc::new(OrderBookStore::default()),
+            tape: None,
+            snapshots: None,
+            resyncing: Arc::new(StdMutex::new(HashSet::new())),
+            commands: Mutex::new(None),
+            assets: Mutex::new(Vec::new()),
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            last_frame: Arc::new(StdMutex::new(None)),
             tx,
         }
     }
 
+    /// Tear the connection down after this long without any frame
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Record streamed trades on a shared tape
+    pub fn with_trade_tape(mut self, tape: Arc<TradeTape>) -> Self {
+        self.tape = Some(tape);
+        self
+    }
+
+    /// Resync corrupted books from REST snapshots instead of re-subscribing
+    pub fn with_snapshot_source(mut self, snapshots: Arc<MarketDataProvider>) -> Self {
+        self.snapshots = Some(snapshots);
+        self
+    }
+
     /// Get current connection status
-    #[allow(dead_code)]
     pub async fn get_status(&self) -> WsStatus {
         self.status.read().await.clone()
     }
 
-    /// Get a receiver for price updates
+    /// Get a receiver for market events
     #[allow(dead_code)]
     pub fn subscribe(&self) -> broadcast::Receiver<WsMessage> {
         self.tx.subscribe()
@@ -100,21 +469,114 @@ impl WebSocketClient {
 
     /// Get current price from cache
     #[allow(dead_code)]
-    pub async fn get_price(&self, token_id: &str) -> Option<f64> {
+    pub async fn get_price(&self, token_id: &TokenId) -> Option<f64> {
         self.price_cache.read().await.prices.get(token_id).copied()
     }
 
-    /// Connect and start streaming
-    #[allow(dead_code)]
-    pub async fn connect(&self, market_ids: Vec<String>) -> Result<(), WsError> {
+    /// Time since the last frame arrived (None before the first)
+    pub fn last_frame_age(&self) -> Option<Duration> {
+        self.last_frame.lock().unwrap().map(|at| at.elapsed())
+    }
+
+    /// Local order books maintained from the stream
+    pub fn order_books(&self) -> Arc<OrderBookStore> {
+        self.books.clone()
+    }
+
+    /// Market channel endpoint
+    fn market_url(&self) -> String {
+        let base = self.url.trim_end_matches('/');
+        if base.ends_with("/market") {
+            base.to_string()
+        } else {
+            format!("{}/market", base)
+        }
+    }
+
+    /// Remember tokens so reconnects subscribe to them again
+    async fn remember_assets(&self, asset_ids: &[TokenId]) {
+        let mut assets = self.assets.lock().await;
+        for id in asset_ids {
+            if !assets.contains(id) {
+                assets.push(id.clone());
+            }
+        }
+    }
+
+    /// Add tokens to the subscription
+    ///
+    /// While disconnected the tokens are only remembered; the next connection
+    /// subscribes to them.
+    pub async fn subscribe_assets(&self, asset_ids: Vec<TokenId>) -> Result<(), WsError> {
+        self.remember_assets(&asset_ids).await;
+        if self.get_status().await != WsStatus::Connected {
+            return Ok(());
+        }
+
+        let msg = serde_json::to_string(&SubscribeRequest {
+            assets_ids: asset_ids,
+            channel: None,
+            operation: Some("subscribe"),
+        })
+        .map_err(|e| WsError::SerializeError(e.to_string()))?;
+
+        match self.commands.lock().await.as_ref() {
+            Some(commands) => commands
+                .send(Message::Text(msg.into()))
+                .map_err(|e| WsError::SendError(e.to_string())),
+            None => Err(WsError::SendError("not connected".to_string())),
+        }
+    }
+
+    /// Re-subscribe to every token so the server resends full snapshots
+    pub async fn resync_all(&self) -> Result<(), WsError> {
+        let assets = self.assets.lock().await.clone();
+        self.subscribe_assets(assets).await
+    }
+
+    /// Keep the stream connected, reconnecting with exponential backoff
+    /// whenever the connection drops or the watchdog tears it down
+    pub async fn maintain(self: Arc<Self>) {
+        let mut backoff = MIN_BACKOFF;
+        loop {
+            let status = self.get_status().await;
+            let assets = self.assets.lock().await.clone();
+            if matches!(status, WsStatus::Connected | WsStatus::Connecting) || assets.is_empty() {
+                tokio::time::sleep(RECONNECT_CHECK_INTERVAL).await;
+                continue;
+            }
+
+            if status != WsStatus::Disconnected {
+                *self.status.write().await = WsStatus::Reconnecting;
+            }
+            match self.connect(assets).await {
+                Ok(()) => backoff = MIN_BACKOFF,
+                Err(e) => {
+                    *self.status.write().await = WsStatus::Failed(e.to_string());
+                    println!(
+                        "⚠️ [WebSocket] Reconnect failed ({}), retrying in {}s",
+                        e,
+                        backoff.as_secs()
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = next_backoff(backoff);
+                }
+            }
+        }
+    }
+
+    /// Connect and start streaming books for the given tokens
+    pub async fn connect(&self, asset_ids: Vec<TokenId>) -> Result<(), WsError> {
         *self.status.write().await = WsStatus::Connecting;
+        self.remember_assets(&asset_ids).await;
 
+        let url = self.market_url();
         println!(
             "📡 [WebSocket] Connecting to {}...",
-            &self.url[..50.min(self.url.len())]
+            &url[..50.min(url.len())]
         );
 
-        let (ws_stream, _) = connect_async(&self.url)
+        let (ws_stream, _) = connect_async(&url)
             .await
             .map_err(|e| WsError::ConnectionFailed(e.to_string()))?;
 
@@ -123,11 +585,11 @@ impl WebSocketClient {
         *self.status.write().await = WsStatus::Connected;
         println!("✅ [WebSocket] Connected!");
 
-        // Subscribe to markets
+        // Subscribe to the market channel for these tokens
         let subscribe_msg = SubscribeRequest {
-            msg_type: "subscribe".to_string(),
-            channel: "market".to_string(),
-            markets: market_ids,
+            assets_ids: asset_ids,
+            channel: Some("market"),
+            operation: None,
         };
 
         let msg = serde_json::to_string(&subscribe_msg)
@@ -140,27 +602,98 @@ impl WebSocketClient {
 
         println!("📝 [WebSocket] Subscribed to market channel");
 
-        // Start reading messages
+        // Writer: forward commands and keep the connection alive until the
+        // reader shuts it down
+        let (commands, mut outgoing) = mpsc::unbounded_channel::<Message>();
+        *self.commands.lock().await = Some(commands.clone());
+        let (shutdown, mut shutdown_rx) = oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let mut ping = tokio::time::interval(PING_INTERVAL);
+            loop {
+                let frame = tokio::select! {
+                    _ = ping.tick() => Message::Text("PING".into()),
+                    frame = outgoing.recv() => match frame {
+                        Some(frame) => frame,
+                        None => break,
+                    },
+                    _ = &mut shutdown_rx => {
+                        let _ = write.send(Message::Close(None)).await;
+                        break;
+                    }
+                };
+                if write.send(frame).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        // Reader: maintain books and broadcast events
         let tx = self.tx.clone();
         let price_cache = self.price_cache.clone();
+        let books = self.books.clone();
+        let tape = self.tape.clone();
+        let snapshots = self.snapshots.clone();
+        let resyncing = self.resyncing.clone();
         let status = self.status.clone();
+        let idle_timeout = self.idle_timeout;
+        let last_frame = self.last_frame.clone();
 
-        tokio::spawn(async move {
-            while let Some(msg) = read.next().await {
+        let reader = tokio::spawn(async move {
+            loop {
+                // Watchdog: any frame (data, PONG, ping) proves the link is alive
+                let msg = match tokio::time::timeout(idle_timeout, read.next()).await {
+                    Ok(Some(msg)) => {
+                        *last_frame.lock().unwrap() = Some(Instant::now());
+                        msg
+                    }
+                    Ok(None) => {
+                        *status.write().await = WsStatus::Disconnected;
+                        println!("📴 [WebSocket] Stream ended");
+                        break;
+                    }
+                    Err(_) => {
+                        *status.write().await = WsStatus::Failed("idle timeout".to_string());
+                        println!(
+                            "💤 [WebSocket] No frames for {}s, dropping half-open connection",
+                            idle_timeout.as_secs()
+                        );
+                        break;
+                    }
+                };
                 match msg {
                     Ok(Message::Text(text)) => {
-                        if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
-                            // Update cache
-                            if let WsMessage::PriceUpdate {
-                                ref token_id,
-                                price,
-                                timestamp,
+                        for ws_msg in parse_messages(&text) {
+                            let resync = books.apply(&ws_msg).await;
+                            if !resync.is_empty() {
+                                request_resync(
+                                    resync,
+                                    &books,
+                                    snapshots.as_ref(),
+                                    &resyncing,
+                                    &commands,
+                                );
+                            }
+
+                            // Update price cache from the book midpoint
+                            if let WsMessage::Book {
+                                ref asset_id,
+                                ref timestamp,
                                 ..
                             } = ws_msg
                             {
-                                let mut cache = price_cache.write().await;
-                                cache.prices.insert(token_id.clone(), price);
-                                cache.last_update = timestamp;
+                                if let Some(mid) =
+                                    books.book(asset_id).await.and_then(|b| b.midpoint())
+                                {
+                                    let mut cache = price_cache.write().await;
+                                    cache.prices.insert(asset_id.clone(), mid);
+                                    cache.last_update = parse_num(timestamp);
+                                }
+                            }
+
+                            if let (Some(tape), Some((token_id, trade))) =
+                                (&tape, to_trade(&ws_msg))
+                            {
+                                tape.record(token_id, trade);
                             }
 
                             // Broadcast to subscribers
@@ -180,12 +713,90 @@ impl WebSocketClient {
                     _ => {}
                 }
             }
+            drop(shutdown);
+            // Without the stream, local books can no longer be trusted
+            books.mark_all_stale().await;
+        });
+
+        // A reader that panicked never marked the connection down; do it
+        // here so `maintain` reconnects
+        let status = self.status.clone();
+        let books = self.books.clone();
+        tokio::spawn(async move {
+            if reader.await.is_err() {
+                *status.write().await = WsStatus::Failed("reader panicked".to_string());
+                books.mark_all_stale().await;
+            }
         });
 
         Ok(())
     }
 }
 
+/// Fetch fresh books for corrupted tokens
+///
+/// Each token is fetched over REST at most once at a time; without a REST
+/// source, or if the fetch fails, re-subscribing makes the server resend
+/// its snapshot.
+fn request_resync(
+    tokens: Vec<TokenId>,
+    books: &Arc<OrderBookStore>,
+    snapshots: Option<&Arc<MarketDataProvider>>,
+    resyncing: &Arc<StdMutex<HashSet<TokenId>>>,
+    commands: &mpsc::UnboundedSender<Message>,
+) {
+    let resubscribe = |commands: &mpsc::UnboundedSender<Message>, assets_ids: Vec<TokenId>| {
+        if let Ok(msg) = serde_json::to_string(&SubscribeRequest {
+            assets_ids,
+            channel: None,
+            operation: Some("subscribe"),
+        }) {
+            let _ = commands.send(Message::Text(msg.into()));
+        }
+    };
+    let Some(snapshots) = snapshots else {
+        resubscribe(commands, tokens);
+        return;
+    };
+    for token_id in tokens {
+        if !resyncing.lock().unwrap().insert(token_id.clone()) {
+            continue;
+        }
+        let (books, snapshots, resyncing, commands) = (
+            books.clone(),
+            snapshots.clone(),
+            resyncing.clone(),
+            commands.clone(),
+        );
+        tokio::spawn(async move {
+            let fetched = snapshots
+                .fetch_order_book(&token_id)
+                .await
+                .map_err(|e| e.to_string());
+            match fetched {
+                Ok(book) => {
+                    if books.restore(book).await {
+                        println!("🔄 [WebSocket] Resynced {} from REST snapshot", token_id);
+                    }
+                }
+                Err(e) => {
+                    println!(
+                        "⚠️ [WebSocket] REST snapshot for {} failed ({}), re-subscribing",
+                        token_id, e
+                    );
+                    resubscribe(&commands, vec![token_id.clone()]);
+                }
+            }
+            resyncing.lock().unwrap().remove(&token_id);
+        });
+    }
+}
+
+/// Double the reconnect delay, capped at `MAX_BACKOFF`
+pub fn next_backoff(current: Duration) -> Duration {
+    (current * 2).min(MAX_BACKOFF)
+}
+
 #[derive(Debug)]
 pub enum WsError {
     ConnectionFailed(String),
@@ -204,3 +815,166 @@ impl std::fmt::Display for WsError {
 }
 
 impl std::error::Error for WsError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SNAPSHOT: &str = r#"[{"event_type":"book","asset_id":"t1","market":"0xabc",
+        "bids":[{"price":"0.48","size":"30"},{"price":"0.49","size":"20"}],
+        "asks":[{"price":"0.52","size":"25"},{"price":"0.51","size":"10"}],
+        "timestamp":"1000","hash":"h"}]"#;
+
+    fn price_change(timestamp: u64, price: &str, size: &str, side: &str, best: &str) -> String {
+        let best_field = if side == "BUY" {
+            "best_bid"
+        } else {
+            "best_ask"
+        };
+        format!(
+            r#"{{"event_type":"price_change","market":"0xabc","timestamp":"{}",
+            "price_changes":[{{"asset_id":"t1","price":"{}","size":"{}","side":"{}","{}":"{}"}}]}}"#,
+            timestamp, price, size, side, best_field, best
+        )
+    }
+
+    async fn apply_all(store: &OrderBookStore, text: &str) -> Vec<TokenId> {
+        let mut resync = Vec::new();
+        for msg in parse_messages(text) {
+            resync.extend(store.apply(&msg).await);
+        }
+        resync
+    }
+
+    #[test]
+    fn test_reconnect_backoff_is_capped() {
+        let mut backoff = MIN_BACKOFF;
+        for _ in 0..10 {
+            backoff = next_backoff(backoff);
+        }
+        assert_eq!(next_backoff(MIN_BACKOFF), Duration::from_secs(2));
+        assert_eq!(backoff, MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_last_trade_price_to_trade() {
+        let msgs = parse_messages(
+            r#"{"event_type":"last_trade_price","asset_id":"t1","market":"0xabc",
+            "price":"0.456","size":"219.2","side":"SELL","fee_rate_bps":"0","timestamp":"1750428146322"}"#,
+        );
+        let (token_id, trade) = to_trade(&msgs[0]).unwrap();
+        assert_eq!(token_id, "t1");
+        assert_eq!(trade.price, 0.456);
+        assert_eq!(trade.side, Side::Sell);
+        assert_eq!(trade.timestamp, 1750428146322);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_builds_sorted_book() {
+        let store = OrderBookStore::default();
+        apply_all(&store, SNAPSHOT).await;
+
+        let book = store.book(&"t1".into()).await.unwrap();
+        assert_eq!(book.best_bid(), Some(0.49));
+        assert_eq!(book.best_ask(), Some(0.51));
+        assert_eq!(book.asks[1].price, 0.52);
+        assert_eq!(book.timestamp, 1000);
+    }
+
+    #[tokio::test]
+    async fn test_deltas_update_and_remove_levels() {
+        let store = OrderBookStore::default();
+        apply_all(&store, SNAPSHOT).await;
+
+        // New best bid, then the old best ask is taken out
+        assert!(
+            apply_all(&store, &price_change(1001, "0.50", "5", "BUY", "0.50"))
+                .await
+                .is_empty()
+        );
+        assert!(
+            apply_all(&store, &price_change(1002, "0.51", "0", "SELL", "0.52"))
+                .await
+                .is_empty()
+        );
+
+        let book = store.book(&"t1".into()).await.unwrap();
+        assert_eq!(book.best_bid(), Some(0.50));
+        assert_eq!(book.bids.len(), 3);
+        assert_eq!(book.best_ask(), Some(0.52));
+        assert_eq!(book.asks.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_out_of_order_or_divergent_delta_forces_resync() {
+        let store = OrderBookStore::default();
+
+        // Delta before any snapshot
+        let resync = apply_all(&store, &price_change(999, "0.50", "5", "BUY", "0.50")).await;
+        assert_eq!(resync, vec!["t1"]);
+
+        apply_all(&store, SNAPSHOT).await;
+        let resync = apply_all(&store, &price_change(999, "0.50", "5", "BUY", "0.50")).await;
+        assert_eq!(resync, vec!["t1"]);
+        assert!(store.book(&"t1".into()).await.is_none());
+
+        // A fresh snapshot clears the stale flag
+        apply_all(&store, SNAPSHOT).await;
+        assert!(store.book(&"t1".into()).await.is_some());
+
+        // Exchange top of book disagrees with ours
+        let resync = apply_all(&store, &price_change(1001, "0.47", "5", "BUY", "0.50")).await;
+        assert_eq!(resync, vec!["t1"]);
+        assert!(store.book(&"t1".into()).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sequence_gap_forces_resync_and_rest_restores() {
+        let store = OrderBookStore::default();
+        apply_all(&store, SNAPSHOT).await;
+
+        let sequenced = |seq: u64, timestamp: u64, price: &str, hash: &str| {
+            format!(
+                r#"{{"event_type":"price_change","market":"0xabc","timestamp":"{}","seq":{},
+                "price_changes":[{{"asset_id":"t1","price":"{}","size":"5","side":"BUY","hash":"{}"}}]}}"#,
+                timestamp, seq, price, hash
+            )
+        };
+        assert!(apply_all(&store, &sequenced(7, 1001, "0.45", "a"))
+            .await
+            .is_empty());
+        assert!(apply_all(&store, &sequenced(8, 1002, "0.44", "b"))
+            .await
+            .is_empty());
+        // A replay of the last delta is skipped
+        assert!(apply_all(&store, &sequenced(8, 1002, "0.44", "b"))
+            .await
+            .is_empty());
+        assert_eq!(store.book(&"t1".into()).await.unwrap().bids.len(), 4);
+
+        // Event 9 never arrived
+        let resync = apply_all(&store, &sequenced(10, 1003, "0.43", "c")).await;
+        assert_eq!(resync, vec!["t1"]);
+        assert!(store.book(&"t1".into()).await.is_none());
+
+        let snapshot = OrderBook {
+            token_id: "t1".into(),
+            bids: vec![PriceLevel {
+                price: 0.40,
+                size: 10.0,
+            }],
+            asks: Vec::new(),
+            timestamp: 0,
+        };
+        assert!(store.restore(snapshot.clone()).await);
+        assert_eq!(
+            store.book(&"t1".into()).await.unwrap().best_bid(),
+            Some(0.40)
+        );
+        // Only stale or missing books are replaced
+        assert!(!store.restore(snapshot).await);
+        assert!(apply_all(&store, &sequenced(11, 1004, "0.41", "d"))
+            .await
+            .is_empty());
+    }
+}
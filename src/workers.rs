@@ -0,0 +1,889 @@
+//! Per-market worker tasks
+//!
+//! Each active market gets its own async worker that owns the market's order
+//! books and runs exits and signal detection on its own cadence, handing
+//! signals to the shared execution `Pipeline`. Workers share positions,
+//! allowance, and the spend ledger through `WorkerContext`; the `WorkerPool`
+//! spawns and retires them as markets appear and resolve.
+
+use crate::accounts::AccountRouter;
+use crate::allocator::CapitalAllocator;
+use crate::anomaly::{AnomalyBreaker, Observation};
+use crate::api::MarketCache;
+use crate::auth::ClobAuth;
+use crate::breakdown::PnlBreakdown;
+use crate::cadence::{AdaptiveCadence, MarketActivity};
+use crate::candles::CandleStore;
+use crate::config::{Config, ConfigError, StrategyConfig, StrategyParams, StrategyPatch};
+use crate::correlation::CorrelationTracker;
+use crate::ctf::CtfClient;
+use crate::data_source::{DataSource, DataSourceState};
+use crate::engine::EngineStatus;
+use crate::evm::OnChainState;
+use crate::execution::ExecutionEngine;
+use crate::fees::VolumeHistory;
+use crate::ledger::{SpendLedger, LEDGER_DOCUMENT};
+use crate::market::{HydrationMode, MarketDataProvider};
+use crate::metamask::{MetaMaskClient, StrategyMode};
+use crate::metrics::LatencyTracker;
+use crate::money::{self, Decimal};
+use crate::order_spec::{OrderSpec, OrderType};
+use crate::orders::{Order, OrderManager, OrderRequest, Recovery, Rejection, Requote};
+use crate::panics::spawn_supervised;
+use crate::pipeline::{post_exits, Candidate, LiveOrders, Pipeline};
+use crate::positions::{ExitResult, PositionManager};
+use crate::quarantine::TokenQuarantine;
+use crate::script::TradeFilter;
+use crate::shadow::ShadowLedger;
+use crate::skips::{SkipCounter, SkipReason};
+use crate::spread_history::SpreadHistory;
+use crate::storage::Storage;
+use crate::strategy::StrategyRegistry;
+use crate::streak::LosingStreakGuard;
+use crate::tape::TradeTape;
+use crate::throttle::TradeThrottle;
+use crate::tuning::{EdgeTuner, TUNING_LOG};
+use crate::types::{Market, MarketId, OrderBook, Quote, TokenId};
+use crate::utilization::UtilizationTracker;
+use crate::wallet::Wallet;
+use crate::webhook::{WebhookEvent, WebhookPublisher};
+use crate::websocket::{OrderBookStore, WebSocketClient};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Instant;
+use tokio::sync::{watch, Mutex, RwLock};
+use tokio::task::JoinHandle;
+
+/// Get the minimum edge required based on remaining allowance percentage
+pub fn get_min_edge_for_allowance(
+    remaining: Decimal,
+    daily_limit: Decimal,
+    strategy: &StrategyConfig,
+) -> f64 {
+    if daily_limit <= Decimal::ZERO {
+        return strategy.conservative_min_edge;
+    }
+
+    let remaining_pct = money::to_f64(remaining / daily_limit);
+
+    if remaining_pct < strategy.conservative_threshold {
+        strategy.conservative_min_edge // < 30% remaining: require 5% edge
+    } else if remaining_pct > strategy.aggressive_threshold {
+        strategy.aggressive_min_edge // > 70% remaining: accept 1% edge
+    } else {
+        strategy.normal_min_edge // 30-70%: require 2% edge
+    }
+}
+
+/// Get strategy mode based on remaining allowance
+pub fn get_strategy_mode(
+    remaining: Decimal,
+    daily_limit: Decimal,
+    strategy: &StrategyConfig,
+) -> StrategyMode {
+    if daily_limit <= Decimal::ZERO {
+        return StrategyMode::Conservative;
+    }
+
+    let remaining_pct = money::to_f64(remaining / daily_limit);
+
+    if remaining_pct < strategy.conservative_threshold {
+        StrategyMode::Conservative
+    } else if remaining_pct > strategy.aggressive_threshold {
+        StrategyMode::Aggressive
+    } else {
+        StrategyMode::Normal
+    }
+}
+
+/// Get strategy mode name for display
+pub fn get_strategy_mode_name(
+    remaining: Decimal,
+    daily_limit: Decimal,
+    strategy: &StrategyConfig,
+) -> &'static str {
+    get_strategy_mode(remaining, daily_limit, strategy).as_str()
+}
+
+/// The running agent's context, shared with the API once it is built
+pub type ContextState = Arc<RwLock<Option<Arc<WorkerContext>>>>;
+
+/// State shared by all market workers
+pub struct WorkerContext {
+    pub config: Config,
+    /// Thresholds, min edges, and trade size, adjustable at runtime; these
+    /// take precedence over the same fields in `config`
+    pub params: RwLock<StrategyParams>,
+    pub metamask: Arc<MetaMaskClient>,
+    /// Smart accounts trades are routed across (the first is `metamask`)
+    pub accounts: Arc<AccountRouter>,
+    pub position_manager: Arc<RwLock<PositionManager>>,
+    pub market_cache: Arc<RwLock<MarketCache>>,
+    pub market_provider: MarketDataProvider,
+    pub execution_engine: ExecutionEngine,
+    pub strategies: Mutex<StrategyRegistry>,
+    pub allocator: Mutex<CapitalAllocator>,
+    /// Min edges tuned from realized outcomes
+    pub tuner: Mutex<EdgeTuner>,
+    pub ledger: Mutex<SpendLedger>,
+    pub wallet: Mutex<Wallet>,
+    pub shadow: Mutex<ShadowLedger>,
+    pub latency: Arc<LatencyTracker>,
+    /// Streamed books, used instead of REST fetches while live
+    pub order_books: Option<Arc<OrderBookStore>>,
+    pub tape: Arc<TradeTape>,
+    pub spread_history: Arc<SpreadHistory>,
+    /// OHLC candles per token
+    pub candles: Arc<CandleStore>,
+    /// Latest on-chain balance and allowance, when an RPC is configured
+    pub on_chain: OnChainState,
+    /// Whether streamed books are currently trusted
+    pub data_source: DataSourceState,
+    /// Submits CTF merges/redemptions, when enabled
+    pub ctf: Option<CtfClient>,
+    pub storage: Storage,
+    /// Mirrors signals and executions to the configured endpoint
+    pub webhook: Option<WebhookPublisher>,
+    /// User trade filter run after the built-in filters
+    pub trade_filter: Option<TradeFilter>,
+    /// Account orders fed by the user channel
+    pub orders: Arc<OrderManager>,
+    /// Posts live orders, when `orders.submit` is on and L2 credentials loaded
+    pub clob: Option<Arc<ClobAuth>>,
+    /// Executions posted to the CLOB whose orders are still working
+    pub live_orders: LiveOrders,
+    /// Tokens backing off after failed book requests
+    pub quarantine: Arc<TokenQuarantine>,
+    /// Markets held out of trading after implausible feed data
+    pub anomalies: Arc<AnomalyBreaker>,
+    /// Return correlations between markets, refreshed each discovery
+    pub correlations: Arc<CorrelationTracker>,
+    /// Signals dropped before trading, by reason
+    pub skips: Arc<SkipCounter>,
+    /// Per-cycle and per-minute trade caps
+    pub throttle: TradeThrottle,
+    /// Pauses entries after consecutive losing exits
+    pub losing_streak: Arc<LosingStreakGuard>,
+    /// Realized PnL by trade origin and entry mode
+    pub pnl_breakdown: Arc<PnlBreakdown>,
+    /// Capital in positions vs idle allowance over the session
+    pub utilization: Arc<UtilizationTracker>,
+    /// Intents produced since the engine last checked
+    pub intent_count: AtomicUsize,
+    /// Set by the trading engine each tick; workers only execute while `Running`
+    pub status: RwLock<EngineStatus>,
+    /// Traded volume behind the fee tier, persisted by the engine
+    pub volume: Arc<StdMutex<VolumeHistory>>,
+}
+
+impl WorkerContext {
+    /// Strategy thresholds and min edges as currently adjusted
+    pub async fn strategy_config(&self) -> StrategyConfig {
+        self.params.read().await.strategy(&self.config.strategy)
+    }
+
+    /// Validate and apply a runtime parameter change
+    ///
+    /// The strategies, tuner, and shared parameters are updated under the
+    /// same locks, so no scan sees half of a change. Tuning restarts from the
+    /// new min edges.
+    pub async fn update_params(
+        &self,
+        patch: &StrategyPatch,
+    ) -> Result<StrategyParams, ConfigError> {
+        let mut strategies = self.strategies.lock().await;
+        let mut tuner = self.tuner.lock().await;
+        let mut params = self.params.write().await;
+        let updated = params.patched(patch)?;
+        strategies.set_params(&updated);
+        tuner.reset(&updated.strategy(&self.config.strategy));
+        *params = updated.clone();
+        println!("🎛️ [Params] Strategy parameters updated: {:?}", updated);
+        Ok(updated)
+    }
+
+    /// Attribute realized PnL to the strategy that opened each position
+    pub async fn record_exits(&self, market_id: &MarketId, exits: &[ExitResult]) {
+        for exit in exits {
+            let strategy = {
+                let ledger = self.ledger.lock().await;
+                exit.position
+                    .entry_executions
+                    .first()
+                    .and_then(|id| ledger.strategy_for(id))
+                    .map(|s| s.to_string())
+            };
+            if let Some(strategy) = strategy {
+                self.allocator
+                    .lock()
+                    .await
+                    .record_result(&strategy, exit.pnl);
+            }
+            if let Some(pause) = self.losing_streak.record_exit(exit.pnl, exit.exit_time) {
+                let secs = pause.resumes_at.saturating_sub(exit.exit_time);
+                println!(
+                    "🧊 [Streak] {} losing exits in a row, pausing entries for {}s",
+                    pause.losses, secs
+                );
+                if let Some(webhook) = &self.webhook {
+                    webhook.publish(WebhookEvent::EntriesPaused {
+                        timestamp: exit.exit_time,
+                        consecutive_losses: pause.losses,
+                        resumes_at: pause.resumes_at,
+                        resumes_in_secs: secs,
+                    });
+                }
+            }
+            let entries = &exit.position.entry_executions;
+            self.pnl_breakdown.record_exit(entries, exit.pnl);
+            // Scaled-out positions stay open and keep their tags
+            let still_open = self
+                .position_manager
+                .read()
+                .await
+                .get_position(&exit.position.token_id)
+                .is_some_and(|p| p.entry_executions.first() == entries.first());
+            if !still_open {
+                self.pnl_breakdown.untag(entries);
+                self.utilization
+                    .record_hold(exit.position.entry_time, exit.exit_time);
+            }
+            let adjustments = self
+                .tuner
+                .lock()
+                .await
+                .record_exit(&exit.position.entry_executions, money::to_f64(exit.pnl));
+            for adjustment in adjustments {
+                println!(
+                    "🎛️ [Tuning] {} min edge {:.2}% -> {:.2}% ({} marginal trades, {:.0}% won, avg PnL ${:.4})",
+                    adjustment.mode,
+                    adjustment.from * 100.0,
+                    adjustment.to * 100.0,
+                    adjustment.trades,
+                    adjustment.win_rate * 100.0,
+                    adjustment.avg_pnl
+                );
+                if let Err(e) = self.storage.append(TUNING_LOG, &adjustment) {
+                    println!("⚠️ Failed to log tuning adjustment: {}", e);
+                }
+            }
+            println!(
+                "📤 [Worker {}] Closed {} | {:?} | PnL: ${:.4} (fees ${:.4}) | Entry: {}",
+                market_id,
+                exit.position.token_id,
+                exit.reason,
+                exit.pnl,
+                exit.fees,
+                exit.position.entry_executions.join(",")
+            );
+        }
+        if !exits.is_empty() {
+            self.sample_utilization().await;
+        }
+    }
+
+    /// Sample the cost of open positions against the allowance left
+    pub async fn sample_utilization(&self) {
+        let deployed = self
+            .position_manager
+            .read()
+            .await
+            .get_positions()
+            .iter()
+            .map(|p| (p.size * p.entry_price).abs())
+            .sum();
+        let (remaining, _) = self.allowance().await;
+        self.utilization.sample(
+            Wallet::current_timestamp(),
+            deployed,
+            money::to_f64(remaining),
+        );
+    }
+
+    /// Number of intents produced since the last call
+    pub fn take_intent_count(&self) -> usize {
+        self.intent_count.swap(0, Ordering::Relaxed)
+    }
+
+    /// Whether workers may execute right now
+    ///
+    /// Needs a permission grant (except in shadow mode) and a running engine.
+    /// Latency is rechecked here because it can spike between engine ticks.
+    pub async fn can_trade(&self) -> bool {
+        if !self.config.trading.shadow_mode && !self.accounts.any_active().await {
+            return false;
+        }
+        if *self.status.read().await != EngineStatus::Running {
+            return false;
+        }
+        if let Some((endpoint, p99_ms)) = self.latency.degraded() {
+            println!(
+                "   ⚠️ Trading suspended: {} p99 latency {:.0}ms exceeds {}ms",
+                endpoint.as_str(),
+                p99_ms,
+                self.config.safety.max_latency_p99_ms
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Fetch a book over REST, backing off tokens that keep failing
+    pub async fn fetch_book(&self, market_id: &MarketId, token_id: &TokenId) -> Option<OrderBook> {
+        match self.market_provider.fetch_order_book(token_id).await {
+            Ok(book) => {
+                self.quarantine.record_success(token_id);
+                Some(book)
+            }
+            Err(e) => {
+                let retry =
+                    self.quarantine
+                        .record_failure(token_id, &e.to_string(), Instant::now());
+                println!(
+                    "⚠️ [Worker {}] Book fetch for {} failed: {} (retry in {}s)",
+                    market_id,
+                    token_id,
+                    e,
+                    retry.as_secs()
+                );
+                None
+            }
+        }
+    }
+
+    /// The freshest book available: streamed while the stream is trusted,
+    /// otherwise fetched (unless the token is backing off)
+    pub async fn fresh_book(&self, market_id: &MarketId, token_id: &TokenId) -> Option<OrderBook> {
+        if self.quarantine.is_closed(token_id) {
+            return None;
+        }
+        if let (Some(books), DataSource::Stream) =
+            (&self.order_books, *self.data_source.read().await)
+        {
+            if let Some(book) = books.book(token_id).await {
+                return Some(book);
+            }
+        }
+        if !self.quarantine.allows(token_id, Instant::now()) {
+            return None;
+        }
+        self.fetch_book(market_id, token_id).await
+    }
+
+    /// Current (remaining allowance, daily limit) across every active account
+    ///
+    /// Shadow mode evaluates signals as if the full configured limit were available.
+    pub async fn allowance(&self) -> (Decimal, Decimal) {
+        let configured = money::usdc(self.config.permission.daily_limit_usdc);
+        if self.config.trading.shadow_mode {
+            return (configured, configured);
+        }
+        match self.accounts.allowance().await {
+            (remaining, daily_limit) if daily_limit > Decimal::ZERO => (remaining, daily_limit),
+            _ => (Decimal::ZERO, configured),
+        }
+    }
+
+    /// Apply a change to the spend ledger and persist it right away
+    pub async fn update_ledger<T>(&self, change: impl FnOnce(&mut SpendLedger) -> T) -> T {
+        let mut ledger = self.ledger.lock().await;
+        let result = change(&mut ledger);
+        self.persist_ledger(&ledger);
+        result
+    }
+
+    /// Persist the spend ledger, for changes made while holding its lock
+    pub fn persist_ledger(&self, ledger: &SpendLedger) {
+        if let Err(e) = self.storage.save(LEDGER_DOCUMENT, ledger) {
+            println!("⚠️ Failed to persist spend ledger: {}", e);
+        }
+    }
+
+    /// Cancel expired good-til-date orders
+    ///
+    /// Their reservations are released as the pipeline books the closes.
+    pub async fn sweep_expired_orders(&self, auth: Option<&ClobAuth>) {
+        self.orders
+            .sweep_expired(auth, Wallet::current_timestamp())
+            .await;
+    }
+
+    /// Cancel-replace resting quotes the market has moved away from
+    pub async fn requote_stale_orders(&self, auth: &ClobAuth) {
+        let threshold = self.config.orders.requote_threshold;
+        if threshold <= 0.0 {
+            return;
+        }
+        for order in self.orders.open_orders() {
+            let Some(book) = self.fresh_book(&order.market, &order.token_id).await else {
+                continue;
+            };
+            let Some(requote) = order.requote(&book, threshold) else {
+                continue;
+            };
+            match self.orders.cancel_replace(auth, &requote).await {
+                Ok(execution_id) => {
+                    let request = OrderRequest {
+                        market: order.market.clone(),
+                        token_id: order.token_id.clone(),
+                        side: order.side,
+                        price: requote.to,
+                        size: requote.size,
+                        order_type: OrderType::PostOnly,
+                        execution_id: execution_id.clone(),
+                    };
+                    let replacement = self.place_order(auth, request).await;
+                    match (replacement, order.expires_at) {
+                        // The replacement takes over the quote's execution and expiry
+                        (Some(replacement), Some(expires_at)) => {
+                            self.orders.set_expiry(
+                                &replacement.id,
+                                execution_id.as_deref(),
+                                expires_at,
+                            );
+                        }
+                        (Some(_), None) => {}
+                        // Nothing works for the execution any more
+                        (None, _) => self.orders.give_up(&order.id),
+                    }
+                }
+                Err(e) => println!(
+                    "⚠️ [Orders] Failed to cancel stale quote {}: {}",
+                    requote.order_id, e
+                ),
+            }
+        }
+    }
+
+    /// Sign and post an order, recovering once if the CLOB refuses it
+    ///
+    /// A resize or reprice is posted in the refused order's place. None if
+    /// neither made it onto the book, in which case the caller releases the
+    /// order's reservation.
+    pub async fn place_order(&self, auth: &ClobAuth, request: OrderRequest) -> Option<Order> {
+        let rejection = match self
+            .orders
+            .submit(auth, &request, Wallet::current_timestamp())
+            .await
+        {
+            Ok(order) => return Some(order),
+            Err(rejection) => rejection,
+        };
+        let retry = self.handle_rejection(rejection).await?;
+        match self
+            .orders
+            .submit(auth, &request.requoted(&retry), Wallet::current_timestamp())
+            .await
+        {
+            Ok(order) => Some(order),
+            Err(rejection) => {
+                // Already retried once: only quarantine
+                self.handle_rejection(rejection).await;
+                None
+            }
+        }
+    }
+
+    /// Recover from an order the CLOB refused
+    ///
+    /// Quarantines the token of a closed market until discovery drops it;
+    /// for a resize or reprice, returns the replacement to place, sized to
+    /// the on-chain balance and priced at a fresh touch. The reservation is
+    /// kept for the replacement.
+    pub async fn handle_rejection(&self, rejection: Rejection) -> Option<Requote> {
+        let market_id = &rejection.order.market;
+        match rejection.recovery {
+            Recovery::Quarantine => {
+                self.quarantine.close(
+                    &rejection.order.token_id,
+                    rejection.reason.as_str(),
+                    Instant::now(),
+                );
+                None
+            }
+            Recovery::Drop => None,
+            Recovery::Resize | Recovery::Reprice => {
+                let spec = {
+                    let cache = self.market_cache.read().await;
+                    let market = cache.markets.get(market_id)?;
+                    OrderSpec::for_market(market, &self.config.trading)
+                };
+                let book = self
+                    .fresh_book(market_id, &rejection.order.token_id)
+                    .await?;
+                let balance = self
+                    .on_chain
+                    .read()
+                    .await
+                    .map(|account| account.usdc_balance.min(account.exchange_allowance));
+                let retry = rejection.retry(&spec, &book, balance);
+                if let Some(retry) = &retry {
+                    println!(
+                        "🔁 [Orders] Retrying {} as {:.2} @ {:.3}",
+                        retry.order_id, retry.size, retry.to
+                    );
+                }
+                retry
+            }
+        }
+    }
+
+    /// Split the daily allowance across strategies
+    pub async fn allocate_budgets(&self) {
+        let (_, daily_limit) = self.allowance().await;
+        let names = self.strategies.lock().await.names();
+        let budgets = self
+            .allocator
+            .lock()
+            .await
+            .allocate(money::to_f64(daily_limit), &names)
+            .into_iter()
+            .map(|(name, budget)| (name, money::usdc(budget)))
+            .collect();
+        self.ledger.lock().await.set_budgets(budgets);
+    }
+}
+
+/// Worker owning one market's books and signal detection
+struct MarketWorker {
+    ctx: Arc<WorkerContext>,
+    /// Latest market metadata from discovery; closed when the market is retired
+    updates: watch::Receiver<Market>,
+    /// Latest order book per token
+    books: HashMap<TokenId, OrderBook>,
+    /// Signals and price moves that drive the poll interval
+    activity: MarketActivity,
+    cadence: AdaptiveCadence,
+    /// Hands detected signals to risk filtering and execution
+    pipeline: Pipeline,
+}
+
+impl MarketWorker {
+    async fn run(mut self) {
+        loop {
+            self.tick().await;
+
+            let interval = self.cadence.interval(&self.activity, Instant::now());
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                changed = self.updates.changed() => {
+                    // Sender dropped: market retired
+                    if changed.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Refresh books, then run exits and strategies for this market
+    async fn tick(&mut self) {
+        let ctx = self.ctx.clone();
+        let mut market = self.updates.borrow().clone();
+
+        // Refresh books and mark outcome prices at their midpoints. With
+        // midpoint hydration, tokens without a streamed book only get a
+        // midpoint; their full books are fetched once the market signals.
+        let source = *ctx.data_source.read().await;
+        let light = ctx.config.api.hydration == HydrationMode::Midpoints;
+        let mut unbooked = Vec::new();
+        for (idx, token_id) in market.clob_token_ids.iter().enumerate() {
+            let streamed = match (&ctx.order_books, source) {
+                // Closed markets are dropped like quarantined tokens below
+                _ if ctx.quarantine.is_closed(token_id) => None,
+                (Some(books), DataSource::Stream) => books.book(token_id).await,
+                _ => None,
+            };
+            let fetched = match streamed {
+                Some(book) => Some(book),
+                // Backing off after repeated failures (or closed); its old book is stale
+                None if !ctx.quarantine.allows(token_id, Instant::now()) => {
+                    self.books.remove(token_id);
+                    continue;
+                }
+                None if light => {
+                    unbooked.push(token_id.clone());
+                    continue;
+                }
+                None => ctx.fetch_book(&market.id, token_id).await,
+            };
+            if let Some(book) = fetched {
+                if let (Some(mid), Some(price)) =
+                    (book.midpoint(), market.outcome_prices.get_mut(idx))
+                {
+                    *price = mid;
+                }
+                self.books.insert(token_id.clone(), book);
+            }
+        }
+        if !unbooked.is_empty() {
+            match ctx.market_provider.fetch_midpoints(&unbooked).await {
+                Ok(midpoints) => {
+                    for (idx, token_id) in market.clob_token_ids.iter().enumerate() {
+                        if let (Some(mid), Some(price)) =
+                            (midpoints.get(token_id), market.outcome_prices.get_mut(idx))
+                        {
+                            *price = *mid;
+                        }
+                    }
+                }
+                Err(e) => println!("⚠️ [Worker {}] Midpoint fetch failed: {}", market.id, e),
+            }
+            // Books from an earlier signal are stale by now
+            for token_id in &unbooked {
+                self.books.remove(token_id);
+            }
+        }
+
+        // Distrust the market for a while if the fresh data looks corrupted;
+        // nothing is published, exited, or traded on it meanwhile
+        let observations: Vec<Observation> = market
+            .clob_token_ids
+            .iter()
+            .zip(&market.outcome_prices)
+            .map(|(token_id, price)| Observation {
+                token_id: token_id.clone(),
+                price: *price,
+                depth: self
+                    .books
+                    .get(token_id)
+                    .map(|book| book.total_bid_liquidity() + book.total_ask_liquidity()),
+            })
+            .collect();
+        ctx.anomalies
+            .observe(&market.id, &observations, Instant::now());
+        if !ctx.anomalies.allows(&market.id, Instant::now()) {
+            return;
+        }
+
+        // Executable prices for signal detection, where books are known
+        market.outcome_quotes = market
+            .clob_token_ids
+            .iter()
+            .map(|token_id| {
+                self.books
+                    .get(token_id)
+                    .map(Quote::from_book)
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        self.activity
+            .observe(&market.outcome_prices, Instant::now());
+
+        // Publish fresh prices to the API cache
+        {
+            let mut cache = ctx.market_cache.write().await;
+            cache.markets.set_prices(&market.id, &market.outcome_prices);
+            cache.last_update = Some(Instant::now());
+        }
+
+        let now = Wallet::current_timestamp();
+        ctx.spread_history
+            .record(&market.id, now, market.get_spread());
+        for (token_id, price) in market.clob_token_ids.iter().zip(&market.outcome_prices) {
+            ctx.candles.record(token_id, now, *price);
+        }
+        self.check_exits(&market, now).await;
+
+        let mut intents = {
+            let mut strategies = ctx.strategies.lock().await;
+            strategies.on_tick(std::slice::from_ref(&market), now);
+            for token_id in &market.clob_token_ids {
+                if let Some(metrics) = ctx.tape.metrics(token_id) {
+                    strategies.on_trades(token_id, &metrics);
+                }
+            }
+            strategies.scan(std::slice::from_ref(&market))
+        };
+        if intents.is_empty() {
+            return;
+        }
+        let detected_at = Instant::now();
+        self.activity.signal(detected_at);
+        ctx.intent_count.fetch_add(intents.len(), Ordering::Relaxed);
+        ctx.market_cache.write().await.record_signals(&intents, now);
+        if let Some(webhook) = &ctx.webhook {
+            for intent in &intents {
+                webhook.publish(WebhookEvent::Signal {
+                    timestamp: now,
+                    intent: intent.clone(),
+                });
+            }
+        }
+
+        if !ctx.can_trade().await {
+            for _ in &intents {
+                ctx.skips.record(SkipReason::Suspended);
+            }
+            return;
+        }
+        if ctx.losing_streak.paused_for(now).is_some() {
+            for _ in &intents {
+                ctx.skips.record(SkipReason::LosingStreak);
+            }
+            return;
+        }
+
+        // Best edge first, so a burst spends the trade caps on its best intents
+        intents.sort_by(|a, b| b.edge.total_cmp(&a.edge));
+
+        // Full books only for markets that signal
+        for token_id in &unbooked {
+            if let Some(book) = ctx.fetch_book(&market.id, token_id).await {
+                self.books.insert(token_id.clone(), book);
+            }
+        }
+
+        // Risk checks and execution run downstream; ingestion carries on
+        for intent in intents {
+            let books = intent
+                .token_ids
+                .iter()
+                .filter_map(|token_id| Some((token_id.clone(), self.books.get(token_id)?.clone())))
+                .collect();
+            let candidate = Candidate {
+                market: market.clone(),
+                intent,
+                books,
+                now,
+                detected_at,
+            };
+            if !self.pipeline.submit(candidate) {
+                break;
+            }
+        }
+    }
+
+    /// Close positions in this market that hit an exit condition
+    ///
+    /// With a live CLOB the exits are posted and booked as they fill;
+    /// paper positions close at the market price at once.
+    async fn check_exits(&self, market: &Market, now: u64) {
+        let ctx = &self.ctx;
+        let fee_model = &ctx.execution_engine.fee_model;
+        let fee_rate = fee_model.market_taker_rate(&market.id, fee_model.taker_rate());
+        let markets = std::slice::from_ref(market);
+        match &ctx.clob {
+            Some(auth) if !ctx.config.trading.shadow_mode => {
+                let due = ctx
+                    .position_manager
+                    .write()
+                    .await
+                    .due_exits(markets, now, fee_rate);
+                post_exits(ctx, auth, due, fee_rate).await;
+            }
+            _ => {
+                let exits = ctx
+                    .position_manager
+                    .write()
+                    .await
+                    .check_exits(markets, now, fee_rate);
+                ctx.record_exits(&market.id, &exits).await;
+            }
+        }
+    }
+}
+
+/// Running workers, keyed by market id
+pub struct WorkerPool {
+    ctx: Arc<WorkerContext>,
+    workers: HashMap<MarketId, (watch::Sender<Market>, JoinHandle<()>)>,
+    /// Shared by all workers
+    pipeline: Pipeline,
+    /// Book stream, subscribed to every running worker's tokens
+    stream: Option<Arc<WebSocketClient>>,
+}
+
+impl WorkerPool {
+    pub fn new(ctx: Arc<WorkerContext>) -> Self {
+        Self {
+            pipeline: Pipeline::spawn(ctx.clone()),
+            ctx,
+            workers: HashMap::new(),
+            stream: None,
+        }
+    }
+
+    /// Keep the book stream subscribed to running markets
+    pub fn with_stream(mut self, stream: Arc<WebSocketClient>) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    /// Number of running workers
+    pub fn len(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// No workers are running
+    pub fn is_empty(&self) -> bool {
+        self.workers.is_empty()
+    }
+
+    /// Spawn workers for new tradeable markets and retire the rest
+    pub async fn sync(&mut self, markets: &[Market]) {
+        let tradeable: HashMap<&MarketId, &Market> = markets
+            .iter()
+            .filter(|m| m.active && m.accepting_orders)
+            .map(|m| (&m.id, m))
+            .collect();
+
+        // Retire workers whose market resolved or dropped out of discovery
+        // (dropping the sender lets the worker finish its tick and exit)
+        let before = self.workers.len();
+        self.workers
+            .retain(|id, (_, handle)| tradeable.contains_key(id) && !handle.is_finished());
+        let retired = before - self.workers.len();
+
+        let mut spawned = 0;
+        let mut new_tokens = Vec::new();
+        for (id, market) in &tradeable {
+            match self.workers.get(*id) {
+                Some((updates, _)) => {
+                    // Refresh metadata; the worker keeps its own prices
+                    updates.send_replace((**market).clone());
+                }
+                None => {
+                    let (updates, receiver) = watch::channel((**market).clone());
+                    // A worker that panics restarts with fresh books and cadence
+                    let ctx = self.ctx.clone();
+                    let pipeline = self.pipeline.clone();
+                    let handle = spawn_supervised("Worker", move || {
+                        MarketWorker {
+                            ctx: ctx.clone(),
+                            updates: receiver.clone(),
+                            books: HashMap::new(),
+                            activity: MarketActivity::default(),
+                            cadence: AdaptiveCadence::from_config(&ctx.config.cadence),
+                            pipeline: pipeline.clone(),
+                        }
+                        .run()
+                    });
+                    self.workers.insert((*id).clone(), (updates, handle));
+                    new_tokens.extend(market.clob_token_ids.iter().cloned());
+                    spawned += 1;
+                }
+            }
+        }
+
+        // The stream connects (and reconnects) on its own once it has tokens
+        if let Some(stream) = &self.stream {
+            if !new_tokens.is_empty() {
+                if let Err(e) = stream.subscribe_assets(new_tokens).await {
+                    println!("⚠️ [WebSocket] Subscribe failed: {}", e);
+                }
+            }
+        }
+
+        if spawned > 0 || retired > 0 {
+            println!(
+                "🧵 [Workers] {} running (+{} spawned, -{} retired)",
+                self.workers.len(),
+                spawned,
+                retired
+            );
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Wiring an agent from configuration, without starting its tasks
+
+use polyshark::agent::AgentBuilder;
+use polyshark::config::{AccountConfig, Config};
+use polyshark::money;
+use polyshark::storage::Storage;
+use std::sync::Arc;
+
+fn temp_storage(name: &str) -> Storage {
+    let dir = std::env::temp_dir().join(format!("polyshark_it_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    Storage::new(dir)
+}
+
+fn headless_config() -> Config {
+    let mut config = Config::default_config();
+    config.api.headless = true;
+    config.trading.shadow_mode = true;
+    config
+}
+
+#[tokio::test]
+async fn test_api_and_workers_share_state() {
+    let agent = AgentBuilder::new(headless_config())
+        .with_storage(temp_storage("shared"))
+        .build()
+        .await
+        .unwrap();
+
+    assert!(Arc::ptr_eq(
+        &agent.ctx.market_cache,
+        &agent.api_state.market_cache
+    ));
+    assert!(Arc::ptr_eq(
+        &agent.ctx.position_manager,
+        &agent.api_state.position_manager
+    ));
+    assert!(Arc::ptr_eq(&agent.ctx.orders, &agent.api_state.orders));
+    // Dry-run quotes read the trading context through the API state
+    assert!(agent.api_state.trading.read().await.is_some());
+    // No EVM key: fills are simulated, nothing is posted
+    assert!(agent.ctx.clob.is_none());
+}
+
+#[tokio::test]
+async fn test_spend_cap_covers_every_account() {
+    let mut config = headless_config();
+    config.accounts.push(AccountConfig {
+        name: "second".to_string(),
+        daily_limit_usdc: 15.0,
+        grant_file: String::new(),
+        grant_env: String::new(),
+        max_exposure: 0.0,
+    });
+    let agent = AgentBuilder::new(config)
+        .with_storage(temp_storage("accounts"))
+        .build()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        agent.ctx.wallet.lock().await.daily_limit(),
+        money::usdc(25.0)
+    );
+}